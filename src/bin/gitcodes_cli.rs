@@ -4,7 +4,10 @@ use lumin::view::FileContents;
 use std::path::PathBuf;
 use tracing_subscriber::{self, EnvFilter};
 
-use gitcodes_mcp::gitcodes::local_repository::prevent_directory_traversal;
+use gitcodes_mcp::gitcodes::local_repository::{
+    prevent_directory_traversal, ContextMode, HotspotMetric, OutlineItem, PickaxeMode,
+    SourceLanguage, SymbolKind,
+};
 use gitcodes_mcp::gitcodes::repository_manager;
 use gitcodes_mcp::gitcodes::LocalRepository;
 use gitcodes_mcp::tools::{IssueSortOption, OrderOption, SortOption};
@@ -152,7 +155,7 @@ enum Commands {
     Grep {
         /// Repository URL or local file path
         #[arg(
-            help = "Repository URL or local file path - supports GitHub formats: 'https://github.com/user/repo', 'git@github.com:user/repo.git', 'github:user/repo', or local paths (both absolute and relative paths are supported, but '..' is not allowed for security reasons)"
+            help = "Repository URL or local file path - supports GitHub formats: 'https://github.com/user/repo', 'git@github.com:user/repo.git', 'github:user/repo', local paths (both absolute and relative paths are supported, but '..' is not allowed for security reasons), or git bundle files as 'bundle:<path>'"
         )]
         repository_location: String,
 
@@ -170,6 +173,18 @@ enum Commands {
         #[arg(long, default_value = "false")]
         case_sensitive: Option<bool>,
 
+        /// Treat the pattern as a literal string instead of a regular expression
+        #[arg(short = 'F', long = "fixed-strings")]
+        fixed_string: bool,
+
+        /// Only match whole words
+        #[arg(short = 'w', long = "word-regexp")]
+        word_regexp: bool,
+
+        /// Allow matches to span multiple lines
+        #[arg(short = 'U', long)]
+        multiline: bool,
+
         /// File extensions to search (deprecated, use include_globs instead)
         #[arg(short = 'e', long = "ext", value_delimiter = ',')]
         file_extensions: Option<Vec<String>>,
@@ -186,6 +201,141 @@ enum Commands {
         #[arg(long = "exclude", value_delimiter = ',')]
         exclude_dirs: Option<Vec<String>>,
 
+        /// Only search files with uncommitted changes (local repositories only)
+        #[arg(long)]
+        changed_only: bool,
+
+        /// Narrow the search with the cached trigram index of the repository
+        #[arg(long)]
+        use_index: bool,
+
+        /// Number of lines to include before each match
+        #[arg(short = 'B', long = "before-context")]
+        before_context: Option<usize>,
+
+        /// Number of lines to include after each match
+        #[arg(short = 'A', long = "after-context")]
+        after_context: Option<usize>,
+
+        /// Show the enclosing function, method, impl or class of each match (like git grep -W)
+        #[arg(short = 'W', long = "function-context")]
+        function_context: bool,
+
+        /// Maximum number of lines to show per enclosing item (default: 50)
+        #[arg(long)]
+        max_context_lines: Option<usize>,
+
+        /// Continue a search after the page that printed this cursor
+        #[arg(long)]
+        cursor: Option<String>,
+    },
+    /// Find files matching several patterns combined with AND/OR/NOT
+    GrepBoolean {
+        /// Repository URL or local file path
+        #[arg(
+            help = "Repository URL or local file path - supports GitHub formats: 'https://github.com/user/repo', 'git@github.com:user/repo.git', 'github:user/repo', local paths (both absolute and relative paths are supported, but '..' is not allowed for security reasons), or git bundle files as 'bundle:<path>'"
+        )]
+        repository_location: String,
+
+        /// Branch, Commit or tag (default is 'main' or 'master')
+        #[arg(short, long)]
+        ref_name: Option<String>,
+
+        /// Pattern that must match in a file (repeatable, all must match)
+        #[arg(long = "all")]
+        all_of: Vec<String>,
+
+        /// Pattern of which at least one must match in a file (repeatable)
+        #[arg(long = "any")]
+        any_of: Vec<String>,
+
+        /// Pattern that must not match in a file (repeatable)
+        #[arg(long = "none")]
+        none_of: Vec<String>,
+
+        /// Whether to be case-sensitive
+        #[arg(long)]
+        case_sensitive: bool,
+
+        /// Treat the patterns as literal strings instead of regular expressions
+        #[arg(short = 'F', long = "fixed-strings")]
+        fixed_string: bool,
+
+        /// Only match whole words
+        #[arg(short = 'w', long = "word-regexp")]
+        word_regexp: bool,
+
+        /// Allow matches to span multiple lines
+        #[arg(short = 'U', long)]
+        multiline: bool,
+
+        /// Glob patterns to include in search (e.g., **/*.rs,src/**/*.md)
+        #[arg(long = "include", value_delimiter = ',')]
+        include_globs: Option<Vec<String>>,
+
+        /// Directories to exclude from search
+        #[arg(long = "exclude", value_delimiter = ',')]
+        exclude_dirs: Option<Vec<String>>,
+
+        /// Number of lines to include before each match
+        #[arg(short = 'B', long = "before-context")]
+        before_context: Option<usize>,
+
+        /// Number of lines to include after each match
+        #[arg(short = 'A', long = "after-context")]
+        after_context: Option<usize>,
+    },
+    /// Search code in several repositories with the same pattern
+    GrepRepositories {
+        /// Search pattern - the text pattern to search for in the code
+        #[arg(
+            help = "Search pattern - the text pattern to search for in the code. Supports regular expressions by default"
+        )]
+        pattern: String,
+
+        /// Repository URLs or local file paths
+        #[arg(
+            required_unless_present = "collection",
+            help = "Repository URLs or local file paths to search - each supports the same formats as the repository location of grep"
+        )]
+        repository_locations: Vec<String>,
+
+        /// Also search the repositories of this saved collection
+        #[arg(long)]
+        collection: Option<String>,
+
+        /// Branch, Commit or tag to search in every repository (default is 'main' or 'master')
+        #[arg(short, long)]
+        ref_name: Option<String>,
+
+        /// Whether to be case-sensitive
+        #[arg(long)]
+        case_sensitive: bool,
+
+        /// Treat the pattern as a literal string instead of a regular expression
+        #[arg(short = 'F', long = "fixed-strings")]
+        fixed_string: bool,
+
+        /// Only match whole words
+        #[arg(short = 'w', long = "word-regexp")]
+        word_regexp: bool,
+
+        /// Allow matches to span multiple lines
+        #[arg(short = 'U', long)]
+        multiline: bool,
+
+        /// Glob patterns to include in search (e.g., **/*.rs,src/**/*.md)
+        #[arg(long = "include", value_delimiter = ',')]
+        include_globs: Option<Vec<String>>,
+
+        /// Directories to exclude from search
+        #[arg(long = "exclude", value_delimiter = ',')]
+        exclude_dirs: Option<Vec<String>>,
+
+        /// Narrow the search with the cached trigram index of each repository
+        #[arg(long)]
+        use_index: bool,
+
         /// Number of lines to include before each match
         #[arg(short = 'B', long = "before-context")]
         before_context: Option<usize>,
@@ -193,12 +343,36 @@ enum Commands {
         /// Number of lines to include after each match
         #[arg(short = 'A', long = "after-context")]
         after_context: Option<usize>,
+
+        /// Show the enclosing function, method, impl or class of each match (like git grep -W)
+        #[arg(short = 'W', long = "function-context")]
+        function_context: bool,
+
+        /// Maximum number of lines to show per enclosing item (default: 50)
+        #[arg(long)]
+        max_context_lines: Option<usize>,
+
+        /// Maximum number of repositories cloned and searched at the same time (default 4)
+        #[arg(short = 'j', long)]
+        max_concurrency: Option<usize>,
+    },
+    /// Save a named list of repositories to search with grep-repositories --collection
+    SaveCollection {
+        /// Name of the collection
+        name: String,
+
+        /// Repository URLs or local file paths
+        #[arg(
+            required = true,
+            help = "Repository URLs or local file paths in the collection - each supports the same formats as the repository location of grep"
+        )]
+        repository_locations: Vec<String>,
     },
     /// Show the contents of a file in a GitHub repository
     ShowFile {
         /// Repository URL or local file path
         #[arg(
-            help = "Repository URL or local file path - supports GitHub formats: 'https://github.com/user/repo', 'git@github.com:user/repo.git', 'github:user/repo', or local paths (both absolute and relative paths are supported, but '..' is not allowed for security reasons)"
+            help = "Repository URL or local file path - supports GitHub formats: 'https://github.com/user/repo', 'git@github.com:user/repo.git', 'github:user/repo', local paths (both absolute and relative paths are supported, but '..' is not allowed for security reasons), or git bundle files as 'bundle:<path>'"
         )]
         repository_location: String,
 
@@ -232,15 +406,19 @@ enum Commands {
     ListRefs {
         /// Repository URL or local file path
         #[arg(
-            help = "Repository URL or local file path - supports GitHub formats: 'https://github.com/user/repo', 'git@github.com:user/repo.git', 'github:user/repo', or local paths (both absolute and relative paths are supported, but '..' is not allowed for security reasons)"
+            help = "Repository URL or local file path - supports GitHub formats: 'https://github.com/user/repo', 'git@github.com:user/repo.git', 'github:user/repo', local paths (both absolute and relative paths are supported, but '..' is not allowed for security reasons), or git bundle files as 'bundle:<path>'"
         )]
         repository_location: String,
+
+        /// Omit prerelease tags (e.g. 'v2.0.0-rc.1')
+        #[arg(long, default_value_t = false)]
+        stable_only: bool,
     },
     /// Get the directory tree structure of a repository
     Tree {
         /// Repository URL or local file path
         #[arg(
-            help = "Repository URL or local file path - supports GitHub formats: 'https://github.com/user/repo', 'git@github.com:user/repo.git', 'github:user/repo', or local paths (both absolute and relative paths are supported, but '..' is not allowed for security reasons)"
+            help = "Repository URL or local file path - supports GitHub formats: 'https://github.com/user/repo', 'git@github.com:user/repo.git', 'github:user/repo', local paths (both absolute and relative paths are supported, but '..' is not allowed for security reasons), or git bundle files as 'bundle:<path>'"
         )]
         repository_location: String,
 
@@ -268,503 +446,2590 @@ enum Commands {
         #[arg(long)]
         search_relative_path: Option<String>,
     },
-}
+    /// Find commits that added or removed a string (like `git log -S`/`-G`)
+    History {
+        /// Repository URL or local file path
+        #[arg(
+            help = "Repository URL or local file path - supports GitHub formats: 'https://github.com/user/repo', 'git@github.com:user/repo.git', 'github:user/repo', local paths (both absolute and relative paths are supported, but '..' is not allowed for security reasons), or git bundle files as 'bundle:<path>'"
+        )]
+        repository_location: String,
 
-/// Sorting options for repository search
-#[derive(clap::ValueEnum, Clone, Debug)]
-enum SortOptionArg {
-    Relevance,
-    Stars,
-    Forks,
-    Updated,
-}
+        /// String or regex to look for
+        pattern: String,
 
-/// Sorting options for issue search
-#[derive(clap::ValueEnum, Clone, Debug)]
-enum IssueSortOptionArg {
-    Created,
-    Updated,
-    Comments,
-    #[value(name = "best-match")]
-    BestMatch,
-}
+        /// Pickaxe mode: 'occurrences' (like `git log -S`) or 'diff-match' (like `git log -G`)
+        #[arg(short, long, value_enum, default_value = "occurrences")]
+        mode: PickaxeModeArg,
 
-/// Order options for repository search
-#[derive(clap::ValueEnum, Clone, Debug)]
-enum OrderOptionArg {
-    Ascending,
-    Descending,
-}
+        /// Treat the pattern as a regex in occurrences mode
+        #[arg(long, default_value_t = false)]
+        regex: bool,
 
-impl From<SortOptionArg> for SortOption {
-    fn from(value: SortOptionArg) -> Self {
-        match value {
-            SortOptionArg::Relevance => SortOption::Relevance,
-            SortOptionArg::Stars => SortOption::Stars,
-            SortOptionArg::Forks => SortOption::Forks,
-            SortOptionArg::Updated => SortOption::Updated,
-        }
-    }
-}
+        /// Match case-insensitively
+        #[arg(short = 'i', long, default_value_t = false)]
+        ignore_case: bool,
 
-impl From<IssueSortOptionArg> for IssueSortOption {
-    fn from(value: IssueSortOptionArg) -> Self {
-        match value {
-            IssueSortOptionArg::Created => IssueSortOption::Created,
-            IssueSortOptionArg::Updated => IssueSortOption::Updated,
-            IssueSortOptionArg::Comments => IssueSortOption::Comments,
-            IssueSortOptionArg::BestMatch => IssueSortOption::BestMatch,
-        }
-    }
-}
+        /// Limit the search to these paths (directory prefixes or globs, comma-separated)
+        #[arg(short, long, value_delimiter = ',')]
+        paths: Option<Vec<String>>,
 
-impl From<OrderOptionArg> for OrderOption {
-    fn from(value: OrderOptionArg) -> Self {
-        match value {
-            OrderOptionArg::Ascending => OrderOption::Ascending,
-            OrderOptionArg::Descending => OrderOption::Descending,
-        }
-    }
-}
+        /// Exclusive start of the commit range (branch, tag, or commit)
+        #[arg(long)]
+        from: Option<String>,
 
-/// Helper function to clean up a repository
-///
-/// This function handles the cleanup of a local repository, including logging.
-/// It silences warnings for explicitly provided local paths.
-/// Will not clean up if preserve_repos is true.
-///
-/// # Arguments
-///
-/// * `repo` - The LocalRepository to clean up
-/// * `preserve_repos` - If true, repositories will not be cleaned up
-fn cleanup_repository(repo: LocalRepository, preserve_repos: bool) {
-    if preserve_repos {
-        tracing::info!("Preserving repository at user request (--preserve-repos flag was used)");
-        return;
-    }
+        /// Inclusive end of the commit range (default: HEAD)
+        #[arg(long)]
+        to: Option<String>,
 
-    if let Err(err) = repo.cleanup() {
-        // Only log warnings for temporary repositories (not user-provided local paths)
-        if err.contains("doesn't match temporary repository pattern") {
-            // This is an explicitly provided local path, suppress the warning
-            tracing::debug!(
-                "Skipping cleanup for explicitly provided local path: {}",
-                err
-            );
-        } else {
-            tracing::warn!("Failed to clean up repository: {}", err);
-        }
-    } else {
-        tracing::debug!("Successfully cleaned up repository");
-    }
-}
+        /// Maximum number of matching commits to show (default: 20)
+        #[arg(short = 'n', long)]
+        max_commits: Option<usize>,
 
-/// Helper function to clean up an optional repository
-///
-/// This function handles the cleanup of an optional local repository, including logging.
-/// Will not clean up if preserve_repos is true.
-///
-/// # Arguments
-///
-/// * `repo_opt` - The `Option<LocalRepository>` to clean up if Some
-/// * `preserve_repos` - If true, repositories will not be cleaned up
-fn cleanup_repository_opt(repo_opt: Option<LocalRepository>, preserve_repos: bool) {
-    if let Some(repo) = repo_opt {
-        cleanup_repository(repo, preserve_repos);
-    }
-}
+        /// Number of context lines around each change (default: 3)
+        #[arg(short = 'C', long)]
+        context_lines: Option<u32>,
+    },
+    /// Generate a changelog of the commits between two refs
+    Changelog {
+        /// Repository URL or local file path
+        #[arg(
+            help = "Repository URL or local file path - supports GitHub formats: 'https://github.com/user/repo', 'git@github.com:user/repo.git', 'github:user/repo', local paths (both absolute and relative paths are supported, but '..' is not allowed for security reasons), or git bundle files as 'bundle:<path>'"
+        )]
+        repository_location: String,
 
-/// Safely converts a repository location string to an absolute path if it's a relative path
-///
-/// This function handles relative paths securely by:
-/// 1. Using prevent_directory_traversal to reject directory traversal attempts
-/// 2. Converting relative paths to absolute using the current working directory
-/// 3. Leaving absolute paths unchanged
-///
-/// # Arguments
-///
-/// * `repository_location` - The repository location string (could be a URL or path)
-///
-/// # Returns
-///
-/// * `Result<String, String>` - The processed repository location or an error message
-fn process_repository_location(repository_location: &str) -> Result<String, String> {
-    // Skip processing if it doesn't look like a file path (e.g., URLs or GitHub shortcuts)
-    if repository_location.starts_with("http://")
-        || repository_location.starts_with("https://")
-        || repository_location.starts_with("git@")
-        || repository_location.starts_with("github:")
-    {
-        return Ok(repository_location.to_string());
-    }
+        /// Exclusive start of the range (e.g. the previous release tag)
+        from_ref: String,
 
-    // Convert the location to a path for validation
-    let path = std::path::Path::new(repository_location);
-    let path_buf = PathBuf::from(repository_location);
+        /// Inclusive end of the range (default: HEAD)
+        #[arg(default_value = "HEAD")]
+        to_ref: String,
 
-    // Security check: Use the standalone prevent_directory_traversal function
-    if let Err(e) = prevent_directory_traversal(&path_buf) {
-        return Err(format!("Security error: {}", e));
-    }
+        /// List merge commits too
+        #[arg(long, default_value_t = false)]
+        include_merges: bool,
 
-    // If the path is already absolute, just return it
-    if path.is_absolute() {
-        return Ok(repository_location.to_string());
-    }
+        /// Look up titles of referenced issues and pull requests on GitHub
+        #[arg(short = 'e', long, default_value_t = false)]
+        enrich_references: bool,
+    },
+    /// Show commit statistics per author (like `git shortlog -sne`)
+    Contributors {
+        /// Repository URL or local file path
+        #[arg(
+            help = "Repository URL or local file path - supports GitHub formats: 'https://github.com/user/repo', 'git@github.com:user/repo.git', 'github:user/repo', local paths (both absolute and relative paths are supported, but '..' is not allowed for security reasons), or git bundle files as 'bundle:<path>'"
+        )]
+        repository_location: String,
 
-    // Convert relative path to absolute using current working directory
-    match std::env::current_dir() {
-        Ok(current_dir) => {
-            match current_dir.join(path).canonicalize() {
-                Ok(absolute_path) => {
-                    // Convert path to string, handling non-UTF8 paths
-                    match absolute_path.to_str() {
-                        Some(path_str) => Ok(path_str.to_string()),
-                        None => Err(format!(
-                            "Path '{}' contains invalid Unicode characters",
-                            repository_location
-                        )),
-                    }
+        /// Exclusive start of the commit range
+        #[arg(long)]
+        from_ref: Option<String>,
+
+        /// Inclusive end of the commit range (default: HEAD)
+        #[arg(long)]
+        to_ref: Option<String>,
+
+        /// Only count commits authored at or after this date (e.g. "2024-01-01" or "6 months ago")
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only count commits authored before this date
+        #[arg(long)]
+        until: Option<String>,
+
+        /// Don't map identities through the repository's .mailmap
+        #[arg(long, default_value_t = false)]
+        no_mailmap: bool,
+
+        /// Don't count added and removed lines (faster on large histories)
+        #[arg(long, default_value_t = false)]
+        no_line_stats: bool,
+
+        /// Count merge commits too
+        #[arg(long, default_value_t = false)]
+        include_merges: bool,
+
+        /// Maximum number of contributors to show (default: 50)
+        #[arg(short = 'n', long)]
+        max_contributors: Option<usize>,
+    },
+    /// Rank files and directories by change frequency or churn
+    Hotspots {
+        /// Repository URL or local file path
+        #[arg(
+            help = "Repository URL or local file path - supports GitHub formats: 'https://github.com/user/repo', 'git@github.com:user/repo.git', 'github:user/repo', local paths (both absolute and relative paths are supported, but '..' is not allowed for security reasons), or git bundle files as 'bundle:<path>'"
+        )]
+        repository_location: String,
+
+        /// Exclusive start of the commit range
+        #[arg(long)]
+        from_ref: Option<String>,
+
+        /// Inclusive end of the commit range (default: HEAD)
+        #[arg(long)]
+        to_ref: Option<String>,
+
+        /// Only count commits authored at or after this date (e.g. "2024-01-01" or "6 months ago")
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only count commits authored before this date
+        #[arg(long)]
+        until: Option<String>,
+
+        /// Glob patterns of files to include (e.g., **/*.rs,src/**)
+        #[arg(long = "include", value_delimiter = ',')]
+        include_globs: Option<Vec<String>>,
+
+        /// Directories or glob patterns to exclude
+        #[arg(long = "exclude", value_delimiter = ',')]
+        exclude_dirs: Option<Vec<String>>,
+
+        /// What to rank by
+        #[arg(long, value_enum, default_value = "commits")]
+        metric: HotspotMetricArg,
+
+        /// Multiply the metric by the current line count of each file
+        #[arg(short = 'w', long, default_value_t = false)]
+        weight_by_size: bool,
+
+        /// Number of leading path components directories are grouped by (default: 1)
+        #[arg(long)]
+        directory_depth: Option<usize>,
+
+        /// Maximum number of files and directories to show (default: 20)
+        #[arg(short = 'n', long)]
+        max_results: Option<usize>,
+    },
+    /// Compare two refs: merge-base and ahead/behind commits
+    CompareRefs {
+        /// Repository URL or local file path
+        #[arg(
+            help = "Repository URL or local file path - supports GitHub formats: 'https://github.com/user/repo', 'git@github.com:user/repo.git', 'github:user/repo', local paths (both absolute and relative paths are supported, but '..' is not allowed for security reasons), or git bundle files as 'bundle:<path>'"
+        )]
+        repository_location: String,
+
+        /// The ref compared against (e.g. main or upstream/main)
+        base_ref: String,
+
+        /// The ref being compared (e.g. feature or origin/feature)
+        head_ref: String,
+
+        /// Maximum number of commits listed per side (default: 50)
+        #[arg(short = 'n', long)]
+        max_commits: Option<usize>,
+    },
+    /// Find where functions, methods, types and traits are defined
+    FindSymbol {
+        /// Repository URL or local file path
+        #[arg(
+            help = "Repository URL or local file path - supports GitHub formats: 'https://github.com/user/repo', 'git@github.com:user/repo.git', 'github:user/repo', local paths (both absolute and relative paths are supported, but '..' is not allowed for security reasons), or git bundle files as 'bundle:<path>'"
+        )]
+        repository_location: String,
+
+        /// Symbol name to find (a regular expression with --regex)
+        name: String,
+
+        /// Treat the name as a regular expression
+        #[arg(short = 'E', long)]
+        regex: bool,
+
+        /// Whether to be case-sensitive
+        #[arg(long)]
+        case_sensitive: bool,
+
+        /// Only show symbols of these kinds (e.g., function,method)
+        #[arg(long = "kind", value_enum, value_delimiter = ',')]
+        kinds: Option<Vec<SymbolKindArg>>,
+
+        /// Branch, Commit or tag (default is 'main' or 'master')
+        #[arg(short, long)]
+        ref_name: Option<String>,
+
+        /// Glob patterns of files to search (e.g., **/*.rs,src/**)
+        #[arg(long = "include", value_delimiter = ',')]
+        include_globs: Option<Vec<String>>,
+
+        /// Directories or glob patterns to exclude
+        #[arg(long = "exclude", value_delimiter = ',')]
+        exclude_dirs: Option<Vec<String>>,
+
+        /// Maximum number of symbols to show (default: 50)
+        #[arg(short = 'n', long)]
+        max_results: Option<usize>,
+    },
+    /// Show the full source of a definition by its qualified name (e.g., Mutex::lock)
+    ShowSymbol {
+        /// Repository URL or local file path
+        #[arg(
+            help = "Repository URL or local file path - supports GitHub formats: 'https://github.com/user/repo', 'git@github.com:user/repo.git', 'github:user/repo', local paths (both absolute and relative paths are supported, but '..' is not allowed for security reasons), or git bundle files as 'bundle:<path>'"
+        )]
+        repository_location: String,
+
+        /// Symbol name, optionally qualified with '::' or '.' (e.g., tokio::sync::Mutex::lock)
+        qualified_name: String,
+
+        /// Branch, Commit or tag (default is 'main' or 'master')
+        #[arg(short, long)]
+        ref_name: Option<String>,
+
+        /// Glob patterns of files to search (e.g., **/*.rs,src/**)
+        #[arg(long = "include", value_delimiter = ',')]
+        include_globs: Option<Vec<String>>,
+
+        /// Directories or glob patterns to exclude
+        #[arg(long = "exclude", value_delimiter = ',')]
+        exclude_dirs: Option<Vec<String>>,
+
+        /// Maximum number of definitions to show (default: 10)
+        #[arg(short = 'n', long)]
+        max_results: Option<usize>,
+    },
+    /// Find where an identifier is used, ignoring comments and strings
+    FindReferences {
+        /// Repository URL or local file path
+        #[arg(
+            help = "Repository URL or local file path - supports GitHub formats: 'https://github.com/user/repo', 'git@github.com:user/repo.git', 'github:user/repo', local paths (both absolute and relative paths are supported, but '..' is not allowed for security reasons), or git bundle files as 'bundle:<path>'"
+        )]
+        repository_location: String,
+
+        /// The identifier to find (exact, case-sensitive)
+        name: String,
+
+        /// Branch, Commit or tag (default is 'main' or 'master')
+        #[arg(short, long)]
+        ref_name: Option<String>,
+
+        /// List the names in the definitions as references too
+        #[arg(long)]
+        include_definitions: bool,
+
+        /// Glob patterns of files to show references from (e.g., **/*.rs,src/**)
+        #[arg(long = "include", value_delimiter = ',')]
+        include_globs: Option<Vec<String>>,
+
+        /// Directories or glob patterns to exclude
+        #[arg(long = "exclude", value_delimiter = ',')]
+        exclude_dirs: Option<Vec<String>>,
+
+        /// Number of references to skip
+        #[arg(long)]
+        skip: Option<usize>,
+
+        /// Maximum number of references to show (default: 100)
+        #[arg(short = 'n', long)]
+        take: Option<usize>,
+    },
+    /// Find code by syntax with an AST pattern like 'File::open($PATH).unwrap()'
+    StructuralSearch {
+        /// Repository URL or local file path
+        #[arg(
+            help = "Repository URL or local file path - supports GitHub formats: 'https://github.com/user/repo', 'git@github.com:user/repo.git', 'github:user/repo', local paths (both absolute and relative paths are supported, but '..' is not allowed for security reasons), or git bundle files as 'bundle:<path>'"
+        )]
+        repository_location: String,
+
+        /// The pattern: code with $NAME (one node) and $$$NAME (any number of nodes)
+        pattern: String,
+
+        /// Language of the pattern and of the files to search
+        #[arg(short, long, value_enum)]
+        language: SourceLanguageArg,
+
+        /// Branch, Commit or tag (default is 'main' or 'master')
+        #[arg(short, long)]
+        ref_name: Option<String>,
+
+        /// Glob patterns of files to search (e.g., **/*.rs,src/**)
+        #[arg(long = "include", value_delimiter = ',')]
+        include_globs: Option<Vec<String>>,
+
+        /// Directories or glob patterns to exclude
+        #[arg(long = "exclude", value_delimiter = ',')]
+        exclude_dirs: Option<Vec<String>>,
+
+        /// Number of matches to skip
+        #[arg(long)]
+        skip: Option<usize>,
+
+        /// Maximum number of matches to show (default: 50)
+        #[arg(short = 'n', long)]
+        take: Option<usize>,
+    },
+    /// Rank code chunks by relevance to a free-text query (BM25)
+    SearchRanked {
+        /// Repository URL or local file path
+        #[arg(
+            help = "Repository URL or local file path - supports GitHub formats: 'https://github.com/user/repo', 'git@github.com:user/repo.git', 'github:user/repo', local paths (both absolute and relative paths are supported, but '..' is not allowed for security reasons), or git bundle files as 'bundle:<path>'"
+        )]
+        repository_location: String,
+
+        /// What to look for, e.g. "where is the config file parsed"
+        query: String,
+
+        /// Branch, Commit or tag (default is the commit at HEAD)
+        #[arg(short, long)]
+        ref_name: Option<String>,
+
+        /// Glob patterns of files to show chunks from (e.g., **/*.rs,src/**)
+        #[arg(long = "include", value_delimiter = ',')]
+        include_globs: Option<Vec<String>>,
+
+        /// Directories or glob patterns to exclude
+        #[arg(long = "exclude", value_delimiter = ',')]
+        exclude_dirs: Option<Vec<String>>,
+
+        /// Number of chunks to skip
+        #[arg(long)]
+        skip: Option<usize>,
+
+        /// Maximum number of chunks to show (default: 10)
+        #[arg(short = 'n', long)]
+        take: Option<usize>,
+    },
+    /// Find files by glob (**/Cargo.toml) or fuzzy name (octoclient)
+    FindFiles {
+        /// Repository URL or local file path
+        #[arg(
+            help = "Repository URL or local file path - supports GitHub formats: 'https://github.com/user/repo', 'git@github.com:user/repo.git', 'github:user/repo', local paths (both absolute and relative paths are supported, but '..' is not allowed for security reasons), or git bundle files as 'bundle:<path>'"
+        )]
+        repository_location: String,
+
+        /// A glob like '**/Cargo.toml', or text to match fuzzily like 'octoclient'
+        query: String,
+
+        /// Branch, Commit or tag (default is 'main' or 'master')
+        #[arg(short, long)]
+        ref_name: Option<String>,
+
+        /// Also find files ignored by .gitignore and hidden files
+        #[arg(long)]
+        no_ignore: bool,
+
+        /// Number of files to skip
+        #[arg(long)]
+        skip: Option<usize>,
+
+        /// Maximum number of files to show (default: 50)
+        #[arg(short = 'n', long)]
+        take: Option<usize>,
+    },
+    /// Count matching lines per file, top-level directory and extension
+    GrepFacets {
+        /// Repository URL or local file path
+        #[arg(
+            help = "Repository URL or local file path - supports GitHub formats: 'https://github.com/user/repo', 'git@github.com:user/repo.git', 'github:user/repo', local paths (both absolute and relative paths are supported, but '..' is not allowed for security reasons), or git bundle files as 'bundle:<path>'"
+        )]
+        repository_location: String,
+
+        /// Search pattern, a regular expression unless --fixed-strings is given
+        pattern: String,
+
+        /// Branch, Commit or tag (default is 'main' or 'master')
+        #[arg(short, long)]
+        ref_name: Option<String>,
+
+        /// Whether to be case-sensitive
+        #[arg(long, default_value = "false")]
+        case_sensitive: Option<bool>,
+
+        /// Treat the pattern as a literal string instead of a regular expression
+        #[arg(short = 'F', long = "fixed-strings")]
+        fixed_string: bool,
+
+        /// Only match whole words
+        #[arg(short = 'w', long = "word-regexp")]
+        word_regexp: bool,
+
+        /// Allow matches to span multiple lines
+        #[arg(short = 'U', long)]
+        multiline: bool,
+
+        /// Glob patterns to include in search (e.g., **/*.rs,src/**/*.md)
+        #[arg(long = "include", value_delimiter = ',')]
+        include_globs: Option<Vec<String>>,
+
+        /// Directories to exclude from search
+        #[arg(long = "exclude", value_delimiter = ',')]
+        exclude_dirs: Option<Vec<String>>,
+
+        /// Only search files with uncommitted changes (local repositories only)
+        #[arg(long)]
+        changed_only: bool,
+
+        /// Narrow the search with the cached trigram index of the repository
+        #[arg(long)]
+        use_index: bool,
+
+        /// Maximum number of values to show per facet (default: 10)
+        #[arg(short = 'n', long)]
+        top_n: Option<usize>,
+    },
+    /// Show the structural outline of a source file with line ranges
+    Outline {
+        /// Repository URL or local file path
+        #[arg(
+            help = "Repository URL or local file path - supports GitHub formats: 'https://github.com/user/repo', 'git@github.com:user/repo.git', 'github:user/repo', local paths (both absolute and relative paths are supported, but '..' is not allowed for security reasons), or git bundle files as 'bundle:<path>'"
+        )]
+        repository_location: String,
+
+        /// Path to the file within the repository
+        file_path: String,
+
+        /// Branch, Commit or tag (default is 'main' or 'master')
+        #[arg(short, long)]
+        ref_name: Option<String>,
+    },
+    /// Show the uncommitted changes of a local repository with diffs against HEAD
+    Status {
+        /// Local repository path
+        #[arg(
+            help = "Local repository path (both absolute and relative paths are supported, but '..' is not allowed for security reasons)"
+        )]
+        repository_location: String,
+
+        /// Don't list untracked files
+        #[arg(long)]
+        no_untracked: bool,
+
+        /// Only list the files, without diff hunks
+        #[arg(long)]
+        no_diff: bool,
+
+        /// Number of context lines around each hunk (default: 3)
+        #[arg(short = 'U', long = "unified")]
+        context_lines: Option<u32>,
+    },
+}
+
+/// Sorting options for repository search
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum SortOptionArg {
+    Relevance,
+    Stars,
+    Forks,
+    Updated,
+}
+
+/// Sorting options for issue search
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum IssueSortOptionArg {
+    Created,
+    Updated,
+    Comments,
+    #[value(name = "best-match")]
+    BestMatch,
+}
+
+/// Order options for repository search
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum OrderOptionArg {
+    Ascending,
+    Descending,
+}
+
+/// Pickaxe modes for history search
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum PickaxeModeArg {
+    Occurrences,
+    DiffMatch,
+}
+
+/// Ranking metrics for hotspot analysis
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum HotspotMetricArg {
+    Commits,
+    Churn,
+}
+
+/// Symbol kinds for symbol search
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum SymbolKindArg {
+    Function,
+    Method,
+    Struct,
+    Enum,
+    Union,
+    Trait,
+    Interface,
+    Class,
+    TypeAlias,
+    Module,
+    Macro,
+}
+
+/// Languages for structural search
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum SourceLanguageArg {
+    Rust,
+    Go,
+    Python,
+    #[value(name = "typescript")]
+    TypeScript,
+    Tsx,
+}
+
+impl From<SortOptionArg> for SortOption {
+    fn from(value: SortOptionArg) -> Self {
+        match value {
+            SortOptionArg::Relevance => SortOption::Relevance,
+            SortOptionArg::Stars => SortOption::Stars,
+            SortOptionArg::Forks => SortOption::Forks,
+            SortOptionArg::Updated => SortOption::Updated,
+        }
+    }
+}
+
+impl From<IssueSortOptionArg> for IssueSortOption {
+    fn from(value: IssueSortOptionArg) -> Self {
+        match value {
+            IssueSortOptionArg::Created => IssueSortOption::Created,
+            IssueSortOptionArg::Updated => IssueSortOption::Updated,
+            IssueSortOptionArg::Comments => IssueSortOption::Comments,
+            IssueSortOptionArg::BestMatch => IssueSortOption::BestMatch,
+        }
+    }
+}
+
+impl From<OrderOptionArg> for OrderOption {
+    fn from(value: OrderOptionArg) -> Self {
+        match value {
+            OrderOptionArg::Ascending => OrderOption::Ascending,
+            OrderOptionArg::Descending => OrderOption::Descending,
+        }
+    }
+}
+
+impl From<PickaxeModeArg> for PickaxeMode {
+    fn from(value: PickaxeModeArg) -> Self {
+        match value {
+            PickaxeModeArg::Occurrences => PickaxeMode::Occurrences,
+            PickaxeModeArg::DiffMatch => PickaxeMode::DiffMatch,
+        }
+    }
+}
+
+impl From<HotspotMetricArg> for HotspotMetric {
+    fn from(value: HotspotMetricArg) -> Self {
+        match value {
+            HotspotMetricArg::Commits => HotspotMetric::Commits,
+            HotspotMetricArg::Churn => HotspotMetric::Churn,
+        }
+    }
+}
+
+impl From<SymbolKindArg> for SymbolKind {
+    fn from(value: SymbolKindArg) -> Self {
+        match value {
+            SymbolKindArg::Function => SymbolKind::Function,
+            SymbolKindArg::Method => SymbolKind::Method,
+            SymbolKindArg::Struct => SymbolKind::Struct,
+            SymbolKindArg::Enum => SymbolKind::Enum,
+            SymbolKindArg::Union => SymbolKind::Union,
+            SymbolKindArg::Trait => SymbolKind::Trait,
+            SymbolKindArg::Interface => SymbolKind::Interface,
+            SymbolKindArg::Class => SymbolKind::Class,
+            SymbolKindArg::TypeAlias => SymbolKind::TypeAlias,
+            SymbolKindArg::Module => SymbolKind::Module,
+            SymbolKindArg::Macro => SymbolKind::Macro,
+        }
+    }
+}
+
+impl From<SourceLanguageArg> for SourceLanguage {
+    fn from(value: SourceLanguageArg) -> Self {
+        match value {
+            SourceLanguageArg::Rust => SourceLanguage::Rust,
+            SourceLanguageArg::Go => SourceLanguage::Go,
+            SourceLanguageArg::Python => SourceLanguage::Python,
+            SourceLanguageArg::TypeScript => SourceLanguage::TypeScript,
+            SourceLanguageArg::Tsx => SourceLanguage::Tsx,
+        }
+    }
+}
+
+/// Helper function to clean up a repository
+///
+/// This function handles the cleanup of a local repository, including logging.
+/// It silences warnings for explicitly provided local paths.
+/// Will not clean up if preserve_repos is true.
+///
+/// # Arguments
+///
+/// * `repo` - The LocalRepository to clean up
+/// * `preserve_repos` - If true, repositories will not be cleaned up
+fn cleanup_repository(repo: LocalRepository, preserve_repos: bool) {
+    if preserve_repos {
+        tracing::info!("Preserving repository at user request (--preserve-repos flag was used)");
+        return;
+    }
+
+    if let Err(err) = repo.cleanup() {
+        // Only log warnings for temporary repositories (not user-provided local paths)
+        if err.contains("doesn't match temporary repository pattern") {
+            // This is an explicitly provided local path, suppress the warning
+            tracing::debug!(
+                "Skipping cleanup for explicitly provided local path: {}",
+                err
+            );
+        } else {
+            tracing::warn!("Failed to clean up repository: {}", err);
+        }
+    } else {
+        tracing::debug!("Successfully cleaned up repository");
+    }
+}
+
+/// Helper function to clean up an optional repository
+///
+/// This function handles the cleanup of an optional local repository, including logging.
+/// Will not clean up if preserve_repos is true.
+///
+/// # Arguments
+///
+/// * `repo_opt` - The `Option<LocalRepository>` to clean up if Some
+/// * `preserve_repos` - If true, repositories will not be cleaned up
+fn cleanup_repository_opt(repo_opt: Option<LocalRepository>, preserve_repos: bool) {
+    if let Some(repo) = repo_opt {
+        cleanup_repository(repo, preserve_repos);
+    }
+}
+
+/// Prints outline items as an indented tree, one item per line with its line range
+///
+/// # Arguments
+///
+/// * `items` - The items to print, with their children below them
+/// * `depth` - The indentation level of the items
+fn print_outline_items(items: &[OutlineItem], depth: usize) {
+    for item in items {
+        let doc = item
+            .doc
+            .as_deref()
+            .map(|doc| format!("  // {}", doc))
+            .unwrap_or_default();
+        println!(
+            "{}{}-{}: {:?} {}{}",
+            "  ".repeat(depth),
+            item.start_line,
+            item.end_line,
+            item.kind,
+            item.signature,
+            doc
+        );
+        print_outline_items(&item.children, depth + 1);
+    }
+}
+
+/// Safely converts a repository location string to an absolute path if it's a relative path
+///
+/// This function handles relative paths securely by:
+/// 1. Using prevent_directory_traversal to reject directory traversal attempts
+/// 2. Converting relative paths to absolute using the current working directory
+/// 3. Leaving absolute paths unchanged
+///
+/// # Arguments
+///
+/// * `repository_location` - The repository location string (could be a URL or path)
+///
+/// # Returns
+///
+/// * `Result<String, String>` - The processed repository location or an error message
+fn process_repository_location(repository_location: &str) -> Result<String, String> {
+    // Bundle locations carry a file path after the prefix, which is resolved the same way
+    if let Some(bundle_path) = repository_location.strip_prefix("bundle:") {
+        return process_repository_location(bundle_path).map(|path| format!("bundle:{}", path));
+    }
+
+    // Skip processing if it doesn't look like a file path (e.g., URLs or GitHub shortcuts)
+    if repository_location.starts_with("http://")
+        || repository_location.starts_with("https://")
+        || repository_location.starts_with("git@")
+        || repository_location.starts_with("github:")
+    {
+        return Ok(repository_location.to_string());
+    }
+
+    // Convert the location to a path for validation
+    let path = std::path::Path::new(repository_location);
+    let path_buf = PathBuf::from(repository_location);
+
+    // Security check: Use the standalone prevent_directory_traversal function
+    if let Err(e) = prevent_directory_traversal(&path_buf) {
+        return Err(format!("Security error: {}", e));
+    }
+
+    // If the path is already absolute, just return it
+    if path.is_absolute() {
+        return Ok(repository_location.to_string());
+    }
+
+    // Convert relative path to absolute using current working directory
+    match std::env::current_dir() {
+        Ok(current_dir) => {
+            match current_dir.join(path).canonicalize() {
+                Ok(absolute_path) => {
+                    // Convert path to string, handling non-UTF8 paths
+                    match absolute_path.to_str() {
+                        Some(path_str) => Ok(path_str.to_string()),
+                        None => Err(format!(
+                            "Path '{}' contains invalid Unicode characters",
+                            repository_location
+                        )),
+                    }
+                }
+                Err(e) => Err(format!(
+                    "Failed to resolve path '{}': {}",
+                    repository_location, e
+                )),
+            }
+        }
+        Err(e) => Err(format!("Failed to get current working directory: {}", e)),
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    // Initialize logging
+    let level = if cli.debug {
+        tracing::Level::DEBUG
+    } else if cli.verbose {
+        tracing::Level::INFO
+    } else {
+        tracing::Level::ERROR // Only show errors by default (no INFO or WARN)
+    };
+
+    // If verbose is not enabled, completely silence the INFO level logs from our repository_manager
+    let env_filter = if cli.verbose || cli.debug {
+        // Show logs based on the level determined above
+        EnvFilter::from_default_env().add_directive(level.into())
+    } else {
+        // Silent mode - hide INFO logs from repository_manager
+        EnvFilter::from_default_env()
+            .add_directive(level.into())
+            .add_directive(
+                "gitcodes_mcp::gitcodes::repository_manager=error"
+                    .parse()
+                    .unwrap(),
+            )
+    };
+
+    // Configure a minimal custom logging format - just level and message
+    tracing_subscriber::fmt()
+        .with_env_filter(env_filter) // Use our custom filter
+        .with_writer(std::io::stderr) // Use stderr for logging
+        .with_thread_ids(false) // Hide thread IDs
+        .with_target(false) // Hide module targets
+        .with_file(false) // Hide file names
+        .with_line_number(false) // Hide line numbers
+        .with_timer(tracing_subscriber::fmt::time::uptime()) // Show uptime instead of timestamp
+        .init();
+
+    // Initialize the global repository manager at startup
+    // This ensures a single process_id is used throughout the application lifetime
+    let manager = repository_manager::instance::init_repository_manager(
+        cli.github_token.clone(),
+        cli.repository_cache_dir.clone(),
+    );
+
+    tracing::debug!("GitCodes CLI initialized");
+    if cli.github_token.is_some() {
+        tracing::debug!("Using GitHub token from command line arguments");
+    }
+
+    if let Some(dir) = &cli.repository_cache_dir {
+        tracing::debug!("Using custom repository cache directory: {}", dir.display());
+    }
+
+    if cli.preserve_repos {
+        tracing::debug!(
+            "Repository preservation mode enabled - repositories will not be cleaned up"
+        );
+    }
+
+    // Process the command
+    match cli.command {
+        Commands::RepositorySearch {
+            query,
+            sort_by,
+            order,
+            per_page,
+            page,
+        } => {
+            use gitcodes_mcp::gitcodes::repository_manager::providers::GitProvider;
+
+            // Default to GitHub as provider
+            let git_provider = GitProvider::Github;
+
+            // Convert from clap enum types to the types used by repository_manager
+            let sort_option = sort_by.map(|s| s.into());
+            let order_option = order.map(|o| o.into());
+
+            // Execute the search using the repository manager
+            match manager
+                .search_repositories(
+                    git_provider,
+                    query,
+                    sort_option,
+                    order_option,
+                    per_page,
+                    page,
+                )
+                .await
+            {
+                Ok(result) => {
+                    // Pretty print each repository item with additional fields
+                    for (i, repo) in result.items.iter().enumerate() {
+                        let description = repo.description.as_deref().unwrap_or("<no description>");
+
+                        // Basic format as shown in the documentation
+                        println!(
+                            "{}. {}/{} - {} stars",
+                            i + 1,
+                            repo.owner.id.as_ref().unwrap_or(&"N/A".to_string()),
+                            repo.name,
+                            repo.stargazers_count.unwrap_or(0)
+                        );
+                        println!("   Description: {}", description);
+                        println!(
+                            "   URL: {}",
+                            repo.html_url.as_ref().map(|u| u.as_str()).unwrap_or("N/A")
+                        );
+
+                        // Additional fields requested
+                        println!("   ID: {}", repo.id);
+
+                        if let Some(lang) = &repo.language {
+                            println!("   Language: {}", lang);
+                        } else {
+                            println!("   Language: <none>");
+                        }
+
+                        println!("   Archived: {}", repo.archived.unwrap_or(false));
+                        println!(
+                            "   Created: {}",
+                            repo.created_at.as_ref().unwrap_or(&"N/A".to_string())
+                        );
+                        if let Some(score) = repo.score {
+                            println!("   Score: {:.2}", score);
+                        }
+                        println!(
+                            "   Last Push: {}",
+                            repo.pushed_at.as_ref().unwrap_or(&"N/A".to_string())
+                        );
+
+                        // Add empty line after each repository
+                        println!();
+                    }
+
+                    // If no results found
+                    if result.items.is_empty() {
+                        println!("No repositories matched your search criteria.");
+                    }
+
+                    Ok(())
+                }
+                Err(err) => {
+                    tracing::error!("Search failed: {}", err);
+
+                    // Provide more user-friendly error message with suggestions
+                    let error_msg = format!("Search failed: {}", err);
+                    let suggestion = if error_msg.contains("API rate limit") {
+                        "\nSuggestion: You may have exceeded GitHub's API rate limits. Try the following:\n  - Use a GitHub token with '-t' option\n  - Wait a few minutes and try again\n  - Reduce the number of requests"
+                    } else if error_msg.contains("authentication") || error_msg.contains("401") {
+                        "\nSuggestion: Authentication failed. Try the following:\n  - Check that your GitHub token is valid and has not expired\n  - Ensure the token has appropriate permissions\n  - Regenerate your GitHub token if necessary"
+                    } else {
+                        "\nSuggestion: Check your network connection and GitHub credentials."
+                    };
+
+                    anyhow::bail!("{}{}", error_msg, suggestion)
+                }
+            }
+        }
+        Commands::IssueSearch {
+            query,
+            sort_by,
+            order,
+            per_page,
+            page,
+            repository,
+            labels,
+            state,
+            creator,
+            mentioned,
+            assignee,
+            milestone,
+            issue_type,
+        } => {
+            use gitcodes_mcp::gitcodes::repository_manager::providers::GitProvider;
+
+            // Default to GitHub as provider
+            let git_provider = GitProvider::Github;
+
+            // Convert from clap enum types to the types used by repository_manager
+            let sort_option = sort_by.map(|s| s.into());
+            let order_option = order.map(|o| o.into());
+
+            // Create issue search parameters
+            let search_params = gitcodes_mcp::gitcodes::repository_manager::IssueSearchParams {
+                query,
+                sort_by: sort_option,
+                order: order_option,
+                per_page,
+                page,
+                repository,
+                labels,
+                state,
+                creator,
+                mentioned,
+                assignee,
+                milestone,
+                issue_type,
+            };
+
+            // Execute the search using the repository manager
+            match manager.search_issues(git_provider, search_params).await {
+                Ok(result) => {
+                    // Pretty print each issue item
+                    for (i, issue) in result.items.iter().enumerate() {
+                        let body_preview = issue
+                            .body
+                            .as_deref()
+                            .unwrap_or("<no description>")
+                            .chars()
+                            .take(100)
+                            .collect::<String>();
+                        let body_display = if issue.body.as_deref().unwrap_or("").len() > 100 {
+                            format!("{}...", body_preview)
+                        } else {
+                            body_preview
+                        };
+
+                        // Basic format
+                        println!(
+                            "{}. #{} - {} [{}]",
+                            i + 1,
+                            issue.number,
+                            issue.title,
+                            issue.state
+                        );
+                        println!("   Repository: {}", issue.repository.name);
+                        println!("   Author: {}", issue.user.login);
+                        println!("   Description: {}", body_display);
+                        println!("   URL: {}", issue.html_url);
+
+                        // Additional fields
+                        println!("   Comments: {}", issue.comments);
+                        println!("   Created: {}", issue.created_at);
+                        println!("   Updated: {}", issue.updated_at);
+
+                        if let Some(closed_at) = &issue.closed_at {
+                            println!("   Closed: {}", closed_at);
+                        }
+
+                        // Show labels if any
+                        if !issue.labels.is_empty() {
+                            let label_names: Vec<String> = issue
+                                .labels
+                                .iter()
+                                .map(|label| label.name.clone())
+                                .collect();
+                            println!("   Labels: {}", label_names.join(", "));
+                        }
+
+                        // Show assignees if any
+                        if !issue.assignees.is_empty() {
+                            let assignee_names: Vec<String> = issue
+                                .assignees
+                                .iter()
+                                .map(|assignee| assignee.login.clone())
+                                .collect();
+                            println!("   Assignees: {}", assignee_names.join(", "));
+                        }
+
+                        // Show milestone if any
+                        // if let Some(milestone) = &issue.milestone {
+                        //     println!("   Milestone: {} [{}]", milestone.title, milestone.state);
+                        // }
+
+                        if let Some(score) = issue.score {
+                            println!("   Score: {:.2}", score);
+                        }
+
+                        // Add empty line after each issue
+                        println!();
+                    }
+
+                    // Summary information
+                    println!(
+                        "Found {} issues (total: {})",
+                        result.items.len(),
+                        result.total_count
+                    );
+                    if result.incomplete_results {
+                        println!(
+                            "Note: Results may be incomplete due to timeout or other factors."
+                        );
+                    }
+
+                    // If no results found
+                    if result.items.is_empty() {
+                        println!("No issues matched your search criteria.");
+                    }
+
+                    Ok(())
+                }
+                Err(err) => {
+                    tracing::error!("Issue search failed: {}", err);
+
+                    // Provide more user-friendly error message with suggestions
+                    let error_msg = format!("Issue search failed: {}", err);
+                    let suggestion = if error_msg.contains("API rate limit") {
+                        "\nSuggestion: You may have exceeded GitHub's API rate limits. Try the following:\n  - Use a GitHub token with '-t' option\n  - Wait a few minutes and try again\n  - Reduce the number of requests"
+                    } else if error_msg.contains("authentication") || error_msg.contains("401") {
+                        "\nSuggestion: Authentication failed. Try the following:\n  - Check that your GitHub token is valid and has not expired\n  - Ensure the token has appropriate permissions\n  - Regenerate your GitHub token if necessary"
+                    } else {
+                        "\nSuggestion: Check your network connection, GitHub credentials, and search query syntax."
+                    };
+
+                    anyhow::bail!("{}{}", error_msg, suggestion)
+                }
+            }
+        }
+        Commands::Grep {
+            repository_location,
+            ref_name,
+            pattern,
+            case_sensitive,
+            file_extensions,
+            include_globs,
+            exclude_dirs,
+            changed_only,
+            use_index,
+            fixed_string,
+            word_regexp,
+            multiline,
+            before_context,
+            after_context,
+            function_context,
+            max_context_lines,
+            cursor,
+        } => {
+            tracing::debug!(
+                "Searching for code pattern in repository: {}",
+                repository_location
+            );
+            tracing::debug!("Pattern: {}", pattern);
+            if let Some(r) = &ref_name {
+                tracing::debug!("Ref: {}", r);
+            }
+
+            // Process the repository location (convert relative paths to absolute)
+            let processed_location = match process_repository_location(&repository_location) {
+                Ok(location) => location,
+                Err(e) => {
+                    tracing::error!("Failed to process repository location: {}", e);
+                    return Err(anyhow::anyhow!(
+                        "Failed to process repository location: {}",
+                        e
+                    ));
+                }
+            };
+
+            // Use the services module to perform the grep operation
+            use gitcodes_mcp::services;
+
+            let grep_params = services::GrepParams {
+                repository_location_str: processed_location.clone(),
+                pattern,
+                ref_name: ref_name.clone(),
+                case_sensitive: case_sensitive.unwrap_or(false),
+                file_extensions: file_extensions.clone(),
+                include_globs: include_globs.clone(),
+                exclude_dirs: exclude_dirs.clone(),
+                changed_files_only: changed_only,
+                use_index,
+                fixed_string,
+                word_regexp,
+                multiline,
+                before_context,
+                after_context,
+                context_mode: function_context.then_some(ContextMode::EnclosingItem),
+                max_context_lines,
+                skip: None,                        // No skip (pagination)
+                take: None,                        // No take (pagination)
+                cursor,
+                match_content_omit_num: Some(150), // Default to 150 characters
+            };
+
+            match services::perform_grep_in_repository(manager, grep_params).await {
+                Ok((result, local_repo)) => {
+                    // Just print each match in a simple format: file:line:content
+                    if !result.matches.is_empty() {
+                        for m in &result.matches {
+                            println!(
+                                "{}:{}:{}",
+                                m.file_path.display(),
+                                m.line_number,
+                                m.line_content
+                            );
+                        }
+                    } else {
+                        // Let user know if no matches were found
+                        tracing::warn!("No matches found.");
+                    }
+                    if let Some(next_cursor) = &result.next_cursor {
+                        eprintln!("More matches: continue with --cursor {}", next_cursor);
+                    }
+
+                    // Clean up the repository when finished (unless preserve flag is set)
+                    cleanup_repository(local_repo, cli.preserve_repos);
+
+                    Ok(())
+                }
+                Err(e) => {
+                    // Log the error but don't exit immediately
+                    tracing::error!("Failed to search code: {}", e);
+
+                    // Check if this is a terminal error or if our fallback mechanism might have worked
+                    if e.contains("All URL formats failed")
+                        || !e.contains("Failed to clone repository")
+                    {
+                        // This is a terminal error after all fallbacks, provide user-friendly message
+                        let error_msg = format!("Failed to search code: {}", e);
+                        let suggestion = if error_msg.contains("clone")
+                            || error_msg.contains("Failed to parse repository location")
+                        {
+                            if error_msg.contains("invalid remote git url")
+                                || error_msg.contains("must be absolute")
+                            {
+                                "\nSuggestion: The repository location format appears to be invalid. Try the following:\n  - For GitHub: Use 'git@github.com:user/repo.git' (SSH format works most reliably)\n  - Alternative formats: 'https://github.com/user/repo' (without .git suffix) or 'github:user/repo'\n  - For local repositories: Use an absolute path to an existing local git repository\n  - Relative paths are not supported for security reasons"
+                            } else if error_msg.contains("HTTPS")
+                                || error_msg.contains("I/O error")
+                                || error_msg.contains("io error")
+                                || error_msg.contains("An IO error occurred")
+                            {
+                                "\nSuggestion: There was an issue cloning via HTTPS. Try the following:\n  - For GitHub URLs, try the format: 'https://github.com/user/repo' (without .git suffix)\n  - As an alternative, use SSH URL format: 'git@github.com:user/repo.git'\n  - Check your network connection or firewall settings\n  - Check if you have a proxy configured and it's properly set in your environment variables\n  - Run 'git config --global --get http.proxy' to verify your Git proxy settings\n  - Verify the repository exists and is accessible\n  - For private repositories, provide a GitHub token with '-t'"
+                            } else if error_msg.contains("authentication")
+                                || error_msg.contains("credential")
+                            {
+                                "\nSuggestion: Authentication failed when cloning the repository. Try the following:\n  - For private repositories, provide a GitHub token with '-t'\n  - Check if your token has the correct permissions\n  - Try using SSH URL format with properly configured SSH keys: 'git@github.com:user/repo.git'\n  - Verify your SSH keys are set up correctly (run 'ssh -T git@github.com')"
+                            } else if error_msg.contains("checkout")
+                                || error_msg.contains("reference")
+                                || error_msg.contains("ref")
+                            {
+                                "\nSuggestion: Failed to checkout the specified branch or reference. Try the following:\n  - Verify the branch/tag name exists in the repository\n  - Try without specifying a branch (it will use the default branch)\n  - Check the repository's available branches on GitHub"
+                            } else {
+                                "\nSuggestion: Failed to clone repository. Try the following:\n  - Use SSH URL format: 'git@github.com:user/repo.git' (most reliable)\n  - Check your network connection\n  - Check for any proxy requirements in your network\n  - Try running: ./git_diagnostic <repository_url> to diagnose issues\n  - Verify the repository exists and is accessible\n  - Ensure you have proper permissions (provide a GitHub token with '-t' if it's a private repository)\n  - Check if the ref/branch/tag exists in the repository"
+                            }
+                        } else if error_msg.contains("pattern") {
+                            "\nSuggestion: There was an issue with your search pattern. Try the following:\n  - Simplify your search pattern\n  - Escape special regex characters if you're not using regex\n  - Use the --case-sensitive option if needed"
+                        } else {
+                            "\nSuggestion: Check your repository location and search parameters."
+                        };
+
+                        anyhow::bail!("{}{}", error_msg, suggestion)
+                    } else {
+                        // The repository manager might be handling this with fallbacks
+                        tracing::info!("Repository manager is handling the error with fallbacks");
+                        // Continue without error to let repository manager fallbacks work
+                        Ok(())
+                    }
+                }
+            }
+        }
+        Commands::GrepBoolean {
+            repository_location,
+            ref_name,
+            all_of,
+            any_of,
+            none_of,
+            case_sensitive,
+            fixed_string,
+            word_regexp,
+            multiline,
+            include_globs,
+            exclude_dirs,
+            before_context,
+            after_context,
+        } => {
+            tracing::debug!(
+                "Boolean search in repository: {} (all: {:?}, any: {:?}, none: {:?})",
+                repository_location,
+                all_of,
+                any_of,
+                none_of
+            );
+
+            // Process the repository location (convert relative paths to absolute)
+            let processed_location = match process_repository_location(&repository_location) {
+                Ok(location) => location,
+                Err(e) => {
+                    tracing::error!("Failed to process repository location: {}", e);
+                    return Err(anyhow::anyhow!(
+                        "Failed to process repository location: {}",
+                        e
+                    ));
+                }
+            };
+
+            let grep_params = gitcodes_mcp::services::BooleanGrepParams {
+                repository_location_str: processed_location,
+                ref_name,
+                all_of,
+                any_of,
+                none_of,
+                case_sensitive,
+                fixed_string,
+                word_regexp,
+                multiline,
+                include_globs,
+                exclude_dirs,
+                before_context,
+                after_context,
+                skip: None,                        // No skip (pagination)
+                take: None,                        // No take (pagination)
+                match_content_omit_num: Some(150), // Default to 150 characters
+            };
+
+            match gitcodes_mcp::services::perform_boolean_grep_in_repository(manager, grep_params)
+                .await
+            {
+                Ok((result, local_repo)) => {
+                    // Same format as grep: file:line:content
+                    if result.matches.is_empty() {
+                        tracing::warn!("No files match {}", result.pattern);
+                    }
+                    for m in &result.matches {
+                        println!(
+                            "{}:{}:{}",
+                            m.file_path.display(),
+                            m.line_number,
+                            m.line_content
+                        );
+                    }
+
+                    // Clean up the repository when finished (unless preserve flag is set)
+                    cleanup_repository(local_repo, cli.preserve_repos);
+
+                    Ok(())
+                }
+                Err(e) => {
+                    tracing::error!("Failed to search code: {}", e);
+                    anyhow::bail!("Failed to search code: {}", e)
+                }
+            }
+        }
+        Commands::GrepRepositories {
+            pattern,
+            repository_locations,
+            collection,
+            ref_name,
+            case_sensitive,
+            fixed_string,
+            word_regexp,
+            multiline,
+            include_globs,
+            exclude_dirs,
+            use_index,
+            before_context,
+            after_context,
+            function_context,
+            max_context_lines,
+            max_concurrency,
+        } => {
+            tracing::debug!(
+                "Searching for code pattern in repositories: {:?}",
+                repository_locations
+            );
+            tracing::debug!("Pattern: {}", pattern);
+
+            // Process the repository locations (convert relative paths to absolute)
+            let mut processed_locations = Vec::new();
+            for repository_location in &repository_locations {
+                match process_repository_location(repository_location) {
+                    Ok(location) => processed_locations.push(location),
+                    Err(e) => {
+                        tracing::error!("Failed to process repository location: {}", e);
+                        return Err(anyhow::anyhow!(
+                            "Failed to process repository location: {}",
+                            e
+                        ));
+                    }
+                }
+            }
+
+            let grep_params = gitcodes_mcp::services::MultiRepositoryGrepParams {
+                repository_location_strs: processed_locations,
+                collection,
+                max_concurrency,
+                pattern,
+                ref_name,
+                case_sensitive,
+                file_extensions: None,
+                include_globs,
+                exclude_dirs,
+                changed_files_only: false,
+                use_index,
+                fixed_string,
+                word_regexp,
+                multiline,
+                before_context,
+                after_context,
+                context_mode: function_context.then_some(ContextMode::EnclosingItem),
+                max_context_lines,
+                skip: None,                        // No skip (pagination)
+                take: None,                        // No take (pagination)
+                match_content_omit_num: Some(150), // Default to 150 characters
+            };
+
+            match gitcodes_mcp::services::perform_grep_in_repositories(manager, grep_params).await {
+                Ok(outcomes) => {
+                    let mut failed = 0;
+                    let searched = outcomes.len();
+                    for outcome in outcomes {
+                        match outcome.result {
+                            Ok((result, local_repo)) => {
+                                // Like grep, prefixed with the repository: repository:file:line:content
+                                for m in &result.matches {
+                                    println!(
+                                        "{}:{}:{}:{}",
+                                        outcome.repository_location_str,
+                                        m.file_path.display(),
+                                        m.line_number,
+                                        m.line_content
+                                    );
+                                }
+
+                                // Clean up the repository when finished (unless preserve flag is set)
+                                cleanup_repository(local_repo, cli.preserve_repos);
+                            }
+                            Err(e) => {
+                                failed += 1;
+                                tracing::error!(
+                                    "Failed to search {}: {}",
+                                    outcome.repository_location_str,
+                                    e
+                                );
+                            }
+                        }
+                    }
+
+                    if failed == searched {
+                        anyhow::bail!("Failed to search code in any of the repositories")
+                    }
+                    Ok(())
+                }
+                Err(e) => {
+                    tracing::error!("Failed to search code: {}", e);
+                    anyhow::bail!("Failed to search code: {}", e)
+                }
+            }
+        }
+        Commands::SaveCollection {
+            name,
+            repository_locations,
+        } => {
+            // Process the repository locations (convert relative paths to absolute)
+            let mut processed_locations = Vec::new();
+            for repository_location in &repository_locations {
+                match process_repository_location(repository_location) {
+                    Ok(location) => processed_locations.push(location),
+                    Err(e) => {
+                        tracing::error!("Failed to process repository location: {}", e);
+                        return Err(anyhow::anyhow!(
+                            "Failed to process repository location: {}",
+                            e
+                        ));
+                    }
+                }
+            }
+
+            let count = processed_locations.len();
+            match manager.save_repository_collection(&name, processed_locations) {
+                Ok(()) => {
+                    println!("Saved collection '{}' with {} repositories", name, count);
+                    Ok(())
+                }
+                Err(e) => {
+                    tracing::error!("Failed to save collection: {}", e);
+                    anyhow::bail!("Failed to save collection: {}", e)
+                }
+            }
+        }
+        Commands::ShowFile {
+            repository_location,
+            ref_name,
+            file_path,
+            max_size,
+            line_from,
+            line_to,
+            without_line_numbers,
+        } => {
+            tracing::debug!(
+                "Showing file contents from repository: {}, file: {}",
+                repository_location,
+                file_path
+            );
+            if let Some(r) = &ref_name {
+                tracing::debug!("Ref: {}", r);
+            }
+
+            // Process the repository location (convert relative paths to absolute)
+            let processed_location = match process_repository_location(&repository_location) {
+                Ok(location) => location,
+                Err(e) => {
+                    tracing::error!("Failed to process repository location: {}", e);
+                    return Err(anyhow::anyhow!(
+                        "Failed to process repository location: {}",
+                        e
+                    ));
+                }
+            };
+
+            // Use the services module to access the file contents
+            use gitcodes_mcp::services;
+
+            // Clone file_path to retain ownership of the original for later use
+            let file_path_clone = file_path.clone();
+
+            let show_params = services::ShowFileParams {
+                repository_location_str: processed_location.clone(),
+                file_path: file_path_clone,
+                ref_name: ref_name.clone(),
+                max_size,
+                line_from,
+                line_to,
+                without_line_numbers: Some(without_line_numbers),
+            };
+
+            match services::show_file_contents(manager, show_params).await {
+                Ok((file_contents, local_repo, without_line_numbers)) => {
+                    // Format and display the file contents based on type
+                    match file_contents {
+                        FileContents::Text { content, metadata } => {
+                            // Display metadata about the file
+                            let line_range_info = match (line_from, line_to) {
+                                (Some(from), Some(to)) => format!(" (lines {}-{})", from, to),
+                                (Some(from), None) => format!(" (from line {})", from),
+                                (None, Some(to)) => format!(" (up to line {})", to),
+                                (None, None) => String::new(),
+                            };
+
+                            println!("File: {}{}", file_path, line_range_info);
+                            println!("Type: Text file");
+                            println!(
+                                "Size: {} characters, {} lines",
+                                metadata.char_count, metadata.line_count
+                            );
+
+                            // Print the actual content based on format preference
+                            if without_line_numbers {
+                                // Plain text format
+                                println!("{}", content.to_string());
+                            } else {
+                                // Line number format (file:line:content)
+                                let content_str = content.to_string();
+                                let mut line_number = line_from.unwrap_or(1);
+
+                                for line in content_str.lines() {
+                                    println!("{file_path}:{line_number}:{line}");
+                                    line_number += 1;
+                                }
+                            }
+                        }
+                        FileContents::Binary { message, metadata } => {
+                            println!("File: {}", file_path);
+                            println!("Type: Binary file");
+                            println!("Size: {} bytes", metadata.size_bytes);
+                            if let Some(mime) = &metadata.mime_type {
+                                println!("MIME type: {}", mime);
+                            }
+                            println!("---");
+                            println!("{}", message);
+                        }
+                        FileContents::Image { message, metadata } => {
+                            println!("File: {}", file_path);
+                            println!("Type: Image file");
+                            // media_type is directly a String, not an Option
+                            println!("Media type: {}", metadata.media_type);
+                            println!("Size: {} bytes", metadata.size_bytes);
+                            println!("---");
+                            println!("{}", message);
+                            println!("Note: Use a graphical viewer to see this image.");
+                        }
+                    }
+
+                    // Clean up the repository when finished (unless preserve flag is set)
+                    cleanup_repository(local_repo, cli.preserve_repos);
+
+                    Ok(())
+                }
+                Err(e) => {
+                    tracing::error!("Failed to show file contents: {}", e);
+
+                    // Provide more user-friendly error message with suggestions
+                    let error_msg = format!("Failed to show file contents: {}", e);
+                    let suggestion = if error_msg.contains("clone")
+                        || error_msg.contains("Failed to parse repository location")
+                    {
+                        if error_msg.contains("invalid remote git url")
+                            || error_msg.contains("must be absolute")
+                        {
+                            "\nSuggestion: The repository location format appears to be invalid. Try the following:\n  - For GitHub: Use 'git@github.com:user/repo.git' (SSH format works most reliably)\n  - Alternative formats: 'https://github.com/user/repo' or 'github:user/repo'\n  - For local repositories: Use an absolute path to an existing local git repository\n  - Relative paths are not supported for security reasons"
+                        } else {
+                            "\nSuggestion: Could not access repository. Try the following:\n  - Check your network connection\n  - Verify the repository exists and is accessible\n  - Ensure you have proper permissions (provide a GitHub token with '-t' if it's a private repository)"
+                        }
+                    } else if error_msg.contains("not found")
+                        || error_msg.contains("File not found")
+                    {
+                        "\nSuggestion: File not found in repository. Try the following:\n  - Check the file path spelling\n  - Check if the file exists in the specified branch or tag\n  - Use the correct path relative to the repository root"
+                    } else if error_msg.contains("too large") {
+                        "\nSuggestion: File is too large to display. Try the following:\n  - Use the --max-size option to increase the maximum file size\n  - Use the --from-line and --to-line options to view a subset of the file"
+                    } else if error_msg.contains("..") || error_msg.contains("Invalid path") {
+                        "\nSuggestion: Invalid file path. Ensure that:\n  - The path does not contain '..' (parent directory references)\n  - The path is relative to the repository root\n  - The path does not attempt to access outside the repository"
+                    } else {
+                        "\nSuggestion: Check your repository location and file path."
+                    };
+
+                    anyhow::bail!("{}{}", error_msg, suggestion)
+                }
+            }
+        }
+        Commands::ListRefs {
+            repository_location,
+            stable_only,
+        } => {
+            tracing::debug!("Listing references for repository: {}", repository_location);
+
+            // Process the repository location (convert relative paths to absolute)
+            let processed_location = match process_repository_location(&repository_location) {
+                Ok(location) => location,
+                Err(e) => {
+                    tracing::error!("Failed to process repository location: {}", e);
+                    return Err(anyhow::anyhow!(
+                        "Failed to process repository location: {}",
+                        e
+                    ));
+                }
+            };
+
+            // Get the refs directly from the repository manager
+
+            match manager.list_repository_refs(&processed_location).await {
+                Err(e) => {
+                    tracing::error!("Failed to list repository references: {}", e);
+
+                    // Provide more user-friendly error message with suggestions
+                    let error_msg = format!("Failed to list repository references: {}", e);
+                    let suggestion = if error_msg.contains("clone")
+                        || error_msg.contains("Failed to parse repository location")
+                    {
+                        if error_msg.contains("invalid remote git url")
+                            || error_msg.contains("must be absolute")
+                        {
+                            "\nSuggestion: The repository location format appears to be invalid. Try the following:\n  - For GitHub: Use 'git@github.com:user/repo.git' (SSH format works most reliably)\n  - Alternative formats: 'https://github.com/user/repo' or 'github:user/repo'\n  - For local repositories: Use an absolute path to an existing local git repository\n  - Relative paths are not supported for security reasons"
+                        } else {
+                            "\nSuggestion: Failed to access repository. Try the following:\n  - Check your network connection\n  - Verify the repository exists and is accessible\n  - Ensure you have proper permissions (provide a GitHub token with '-t' if it's a private repository)"
+                        }
+                    } else if error_msg.contains("API") || error_msg.contains("rate limit") {
+                        "\nSuggestion: You may have exceeded GitHub's API rate limits. Try the following:\n  - Use a GitHub token with '-t' option\n  - Wait a few minutes and try again"
+                    } else if error_msg.contains("not found") || error_msg.contains("404") {
+                        "\nSuggestion: Repository not found. Try the following:\n  - Check the spelling of the repository name\n  - Ensure the repository exists and is public (or you have access to it)\n  - Use the correct repository format (user/repo)"
+                    } else {
+                        "\nSuggestion: Check the repository location and your access permissions."
+                    };
+
+                    return Err(anyhow::anyhow!("{}{}", error_msg, suggestion));
+                }
+                Ok((mut refs, local_repo_opt)) => {
+                    // Tags are already sorted by version, newest first
+                    if stable_only {
+                        repository_manager::version_tags::retain_stable_tags(&mut refs.tags);
+                    }
+
+                    if let Some(default_branch) = &refs.default_branch {
+                        println!("Default branch: {}\n", default_branch);
+                    }
+
+                    // Print the header for the table
+                    println!(
+                        "{:<40} {:<10} {:<40} {:<25}",
+                        "Reference", "Type", "Commit ID", "Commit Date"
+                    );
+                    println!("{:-<40} {:-<10} {:-<40} {:-<25}", "", "", "", "");
+
+                    // Display branches
+                    if !refs.branches.is_empty() {
+                        for branch in &refs.branches {
+                            println!(
+                                "{:<40} {:<10} {:<40} {:<25}",
+                                format!("branch: {}", branch.name),
+                                "branch",
+                                branch.commit_id,
+                                branch.commit_date.as_deref().unwrap_or("")
+                            );
+                        }
+                    }
+
+                    // Display tags, with the message of annotated tags below them
+                    if !refs.tags.is_empty() {
+                        for tag in &refs.tags {
+                            println!(
+                                "{:<40} {:<10} {:<40} {:<25}",
+                                format!("tag: {}", tag.name),
+                                if tag.is_annotated_tag() {
+                                    "annotated"
+                                } else {
+                                    "tag"
+                                },
+                                tag.commit_id,
+                                tag.commit_date.as_deref().unwrap_or("")
+                            );
+                            if let Some(details) = &tag.tag {
+                                let tagger = details.tagger_name.as_deref().unwrap_or("unknown");
+                                let summary = details.message.lines().next().unwrap_or("");
+                                println!("    tagged by {}: {}", tagger, summary);
+                            }
+                        }
+                    }
+
+                    // Display count of references found
+                    let total_refs = refs.branches.len() + refs.tags.len();
+                    println!(
+                        "
+Total: {} references found ({} branches, {} tags)",
+                        total_refs,
+                        refs.branches.len(),
+                        refs.tags.len()
+                    );
+
+                    // Clean up the repository if it exists (unless preserve flag is set)
+                    cleanup_repository_opt(local_repo_opt, cli.preserve_repos);
+                }
+            }
+
+            Ok(())
+        }
+        Commands::Tree {
+            repository_location,
+            ref_name,
+            case_sensitive,
+            respect_gitignore,
+            depth,
+            strip_path_prefix,
+            search_relative_path,
+        } => {
+            tracing::debug!("Getting tree for repository: {}", repository_location);
+
+            // Process the repository location (convert relative paths to absolute)
+            let processed_location = match process_repository_location(&repository_location) {
+                Ok(location) => location,
+                Err(e) => {
+                    tracing::error!("Failed to process repository location: {}", e);
+                    return Err(anyhow::anyhow!(
+                        "Failed to process repository location: {}",
+                        e
+                    ));
+                }
+            };
+
+            // Call the tree service function
+            let tree_params = gitcodes_mcp::services::TreeServiceParams {
+                repository_location_str: processed_location.clone(),
+                ref_name: ref_name.clone(),
+                case_sensitive: Some(case_sensitive),
+                respect_gitignore: Some(respect_gitignore),
+                depth,
+                strip_path_prefix: Some(strip_path_prefix),
+                search_relative_path: search_relative_path.map(std::path::PathBuf::from),
+            };
+
+            match gitcodes_mcp::services::get_repository_tree(manager, tree_params).await {
+                Ok((tree, local_repo)) => {
+                    // Display the tree structure
+                    if tree.is_empty() {
+                        println!("No directories found in repository");
+                    } else {
+                        println!("Directory tree for repository: {}", repository_location);
+                        if let Some(ref_name) = &ref_name {
+                            println!("Reference: {}", ref_name);
+                        }
+                        println!("---");
+
+                        // Print each directory tree
+                        for dir_tree in &tree {
+                            if dir_tree.dir.is_empty() {
+                                println!("Repository root:");
+                            } else {
+                                println!("{}:", dir_tree.dir);
+                            }
+
+                            // Sort entries by type (directories first) and then by name
+                            let mut sorted_entries = dir_tree.entries.clone();
+                            sorted_entries.sort_by(|a, b| {
+                                use gitcodes_mcp::gitcodes::local_repository::TreeEntry;
+                                match (a, b) {
+                                    (
+                                        TreeEntry::Directory(a_name),
+                                        TreeEntry::Directory(b_name),
+                                    ) => a_name.cmp(b_name),
+                                    (TreeEntry::File(a_name), TreeEntry::File(b_name)) => {
+                                        a_name.cmp(b_name)
+                                    }
+                                    (TreeEntry::Directory(_), TreeEntry::File(_)) => {
+                                        std::cmp::Ordering::Less
+                                    }
+                                    (TreeEntry::File(_), TreeEntry::Directory(_)) => {
+                                        std::cmp::Ordering::Greater
+                                    }
+                                }
+                            });
+
+                            // Display entries with tree-like formatting
+                            for (i, entry) in sorted_entries.iter().enumerate() {
+                                let is_last = i == sorted_entries.len() - 1;
+                                let prefix = if is_last { "└── " } else { "├── " };
+
+                                match entry {
+                                    gitcodes_mcp::gitcodes::local_repository::TreeEntry::Directory(name) => {
+                                        println!("{}📁 {}/", prefix, name);
+                                    }
+                                    gitcodes_mcp::gitcodes::local_repository::TreeEntry::File(name) => {
+                                        println!("{}📄 {}", prefix, name);
+                                    }
+                                }
+                            }
+                            println!(); // Empty line between directories
+                        }
+
+                        // Print summary
+                        let total_files: usize = tree
+                            .iter()
+                            .map(|dir| {
+                                dir.entries
+                                    .iter()
+                                    .filter(|entry| {
+                                        matches!(
+                                            entry,
+                                            gitcodes_mcp::gitcodes::local_repository::TreeEntry::File(_)
+                                        )
+                                    })
+                                    .count()
+                            })
+                            .sum();
+
+                        let total_dirs: usize = tree
+                            .iter()
+                            .map(|dir| {
+                                dir.entries
+                                    .iter()
+                                    .filter(|entry| {
+                                        matches!(
+                                            entry,
+                                            gitcodes_mcp::gitcodes::local_repository::TreeEntry::Directory(_)
+                                        )
+                                    })
+                                    .count()
+                            })
+                            .sum();
+
+                        println!("Total: {} files, {} directories", total_files, total_dirs);
+                    }
+
+                    // Clean up the repository when finished (unless preserve flag is set)
+                    cleanup_repository(local_repo, cli.preserve_repos);
+
+                    Ok(())
+                }
+                Err(e) => {
+                    tracing::error!("Failed to get repository tree: {}", e);
+
+                    // Provide more user-friendly error message with suggestions
+                    let error_msg = format!("Failed to get repository tree: {}", e);
+                    let suggestion = if error_msg.contains("clone")
+                        || error_msg.contains("Failed to parse repository location")
+                    {
+                        if error_msg.contains("invalid remote git url")
+                            || error_msg.contains("must be absolute")
+                        {
+                            "\nSuggestion: The repository location format appears to be invalid. Try the following:\n  - For GitHub: Use 'git@github.com:user/repo.git' (SSH format works most reliably)\n  - Alternative formats: 'https://github.com/user/repo' or 'github:user/repo'\n  - For local repositories: Use an absolute path to an existing local git repository\n  - Relative paths are not supported for security reasons"
+                        } else {
+                            "\nSuggestion: Could not access repository. Try the following:\n  - Check your network connection\n  - Verify the repository exists and is accessible\n  - Ensure you have proper permissions (provide a GitHub token with '-t' if it's a private repository)"
+                        }
+                    } else if error_msg.contains("not found") {
+                        "\nSuggestion: Repository not found. Try the following:\n  - Check the repository location spelling\n  - Check if the repository exists and is accessible\n  - Use the correct repository format"
+                    } else {
+                        "\nSuggestion: Check your repository location and access permissions."
+                    };
+
+                    anyhow::bail!("{}{}", error_msg, suggestion)
+                }
+            }
+        }
+        Commands::History {
+            repository_location,
+            pattern,
+            mode,
+            regex,
+            ignore_case,
+            paths,
+            from,
+            to,
+            max_commits,
+            context_lines,
+        } => {
+            tracing::debug!("Searching history of repository: {}", repository_location);
+
+            // Process the repository location (convert relative paths to absolute)
+            let processed_location = match process_repository_location(&repository_location) {
+                Ok(location) => location,
+                Err(e) => {
+                    tracing::error!("Failed to process repository location: {}", e);
+                    return Err(anyhow::anyhow!(
+                        "Failed to process repository location: {}",
+                        e
+                    ));
+                }
+            };
+
+            let history_params = gitcodes_mcp::services::HistorySearchServiceParams {
+                repository_location_str: processed_location,
+                pattern,
+                mode: Some(mode.into()),
+                is_regex: Some(regex),
+                case_sensitive: Some(!ignore_case),
+                paths,
+                from_ref: from,
+                to_ref: to,
+                max_commits,
+                context_lines,
+            };
+
+            match gitcodes_mcp::services::search_history(manager, history_params).await {
+                Ok((result, local_repo)) => {
+                    println!(
+                        "Commits changing '{}' in {} ({} commits scanned)",
+                        result.pattern, result.range, result.scanned_commits
+                    );
+                    println!("---");
+
+                    if result.commits.is_empty() {
+                        println!("No matching commits found");
+                    }
+
+                    for commit in &result.commits {
+                        println!("commit {}", commit.commit_id);
+                        println!("Author: {} <{}>", commit.author_name, commit.author_email);
+                        println!("Date:   {}", commit.date);
+                        println!();
+                        println!("    {}", commit.summary);
+                        println!();
+
+                        for file in &commit.files {
+                            println!("{} ({})", file.file_path, file.change_type);
+                            for hunk in &file.hunks {
+                                println!(
+                                    "@@ -{},{} +{},{} @@",
+                                    hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines
+                                );
+                                println!("{}", hunk.content);
+                            }
+                            println!();
+                        }
+                    }
+
+                    if result.truncated {
+                        println!("(more commits may match; raise --max-commits to see them)");
+                    }
+
+                    // Clean up the repository when finished (unless preserve flag is set)
+                    cleanup_repository(local_repo, cli.preserve_repos);
+
+                    Ok(())
+                }
+                Err(e) => {
+                    tracing::error!("Failed to search history: {}", e);
+                    anyhow::bail!("Failed to search history: {}", e)
                 }
-                Err(e) => Err(format!(
-                    "Failed to resolve path '{}': {}",
-                    repository_location, e
-                )),
             }
         }
-        Err(e) => Err(format!("Failed to get current working directory: {}", e)),
-    }
-}
+        Commands::Changelog {
+            repository_location,
+            from_ref,
+            to_ref,
+            include_merges,
+            enrich_references,
+        } => {
+            tracing::debug!("Generating changelog for repository: {}", repository_location);
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let cli = Cli::parse();
+            // Process the repository location (convert relative paths to absolute)
+            let processed_location = match process_repository_location(&repository_location) {
+                Ok(location) => location,
+                Err(e) => {
+                    tracing::error!("Failed to process repository location: {}", e);
+                    return Err(anyhow::anyhow!(
+                        "Failed to process repository location: {}",
+                        e
+                    ));
+                }
+            };
 
-    // Initialize logging
-    let level = if cli.debug {
-        tracing::Level::DEBUG
-    } else if cli.verbose {
-        tracing::Level::INFO
-    } else {
-        tracing::Level::ERROR // Only show errors by default (no INFO or WARN)
-    };
+            let changelog_params = gitcodes_mcp::services::ChangelogServiceParams {
+                repository_location_str: processed_location,
+                from_ref,
+                to_ref,
+                include_merges: Some(include_merges),
+                enrich_references: Some(enrich_references),
+            };
 
-    // If verbose is not enabled, completely silence the INFO level logs from our repository_manager
-    let env_filter = if cli.verbose || cli.debug {
-        // Show logs based on the level determined above
-        EnvFilter::from_default_env().add_directive(level.into())
-    } else {
-        // Silent mode - hide INFO logs from repository_manager
-        EnvFilter::from_default_env()
-            .add_directive(level.into())
-            .add_directive(
-                "gitcodes_mcp::gitcodes::repository_manager=error"
-                    .parse()
-                    .unwrap(),
-            )
-    };
+            match gitcodes_mcp::services::changelog_between(manager, changelog_params).await {
+                Ok((changelog, local_repo)) => {
+                    println!(
+                        "Changes from {} to {} ({} commits, {} breaking)",
+                        changelog.from_ref,
+                        changelog.to_ref,
+                        changelog.total_commits,
+                        changelog.breaking_changes
+                    );
 
-    // Configure a minimal custom logging format - just level and message
-    tracing_subscriber::fmt()
-        .with_env_filter(env_filter) // Use our custom filter
-        .with_writer(std::io::stderr) // Use stderr for logging
-        .with_thread_ids(false) // Hide thread IDs
-        .with_target(false) // Hide module targets
-        .with_file(false) // Hide file names
-        .with_line_number(false) // Hide line numbers
-        .with_timer(tracing_subscriber::fmt::time::uptime()) // Show uptime instead of timestamp
-        .init();
+                    for group in &changelog.groups {
+                        println!();
+                        println!("## {}", group.title);
+                        for entry in &group.commits {
+                            let scope = entry
+                                .scope
+                                .as_ref()
+                                .map(|scope| format!("**{}**: ", scope))
+                                .unwrap_or_default();
+                            let breaking = if entry.breaking { "[BREAKING] " } else { "" };
+                            println!(
+                                "- {}{}{} ({})",
+                                breaking,
+                                scope,
+                                entry.description,
+                                &entry.commit_id[..7]
+                            );
+                        }
+                    }
 
-    // Initialize the global repository manager at startup
-    // This ensures a single process_id is used throughout the application lifetime
-    let manager = repository_manager::instance::init_repository_manager(
-        cli.github_token.clone(),
-        cli.repository_cache_dir.clone(),
-    );
+                    if !changelog.references.is_empty() {
+                        println!();
+                        println!("## References");
+                        for reference in &changelog.references {
+                            match &reference.title {
+                                Some(title) => println!("- #{} {}", reference.number, title),
+                                None => println!("- #{}", reference.number),
+                            }
+                        }
+                    }
 
-    tracing::debug!("GitCodes CLI initialized");
-    if cli.github_token.is_some() {
-        tracing::debug!("Using GitHub token from command line arguments");
-    }
+                    if let Some(error) = &changelog.enrichment_error {
+                        eprintln!("Warning: {}", error);
+                    }
 
-    if let Some(dir) = &cli.repository_cache_dir {
-        tracing::debug!("Using custom repository cache directory: {}", dir.display());
-    }
+                    // Clean up the repository when finished (unless preserve flag is set)
+                    cleanup_repository(local_repo, cli.preserve_repos);
 
-    if cli.preserve_repos {
-        tracing::debug!(
-            "Repository preservation mode enabled - repositories will not be cleaned up"
-        );
-    }
+                    Ok(())
+                }
+                Err(e) => {
+                    tracing::error!("Failed to generate changelog: {}", e);
+                    anyhow::bail!("Failed to generate changelog: {}", e)
+                }
+            }
+        }
+        Commands::Contributors {
+            repository_location,
+            from_ref,
+            to_ref,
+            since,
+            until,
+            no_mailmap,
+            no_line_stats,
+            include_merges,
+            max_contributors,
+        } => {
+            tracing::debug!(
+                "Collecting contributors of repository: {}",
+                repository_location
+            );
 
-    // Process the command
-    match cli.command {
-        Commands::RepositorySearch {
-            query,
-            sort_by,
-            order,
-            per_page,
-            page,
+            // Process the repository location (convert relative paths to absolute)
+            let processed_location = match process_repository_location(&repository_location) {
+                Ok(location) => location,
+                Err(e) => {
+                    tracing::error!("Failed to process repository location: {}", e);
+                    return Err(anyhow::anyhow!(
+                        "Failed to process repository location: {}",
+                        e
+                    ));
+                }
+            };
+
+            let contributors_params = gitcodes_mcp::services::ContributorsServiceParams {
+                repository_location_str: processed_location,
+                from_ref,
+                to_ref,
+                since,
+                until,
+                use_mailmap: Some(!no_mailmap),
+                include_line_stats: Some(!no_line_stats),
+                include_merges: Some(include_merges),
+                max_contributors,
+            };
+
+            match gitcodes_mcp::services::contributors(manager, contributors_params).await {
+                Ok((report, local_repo)) => {
+                    println!(
+                        "{} commits by {} contributors in {}",
+                        report.total_commits, report.total_contributors, report.range
+                    );
+                    if let (Some(first), Some(last)) =
+                        (&report.first_commit_date, &report.last_commit_date)
+                    {
+                        println!("Active from {} to {}", first, last);
+                    }
+                    println!();
+
+                    for contributor in &report.contributors {
+                        let lines = match (contributor.lines_added, contributor.lines_removed) {
+                            (Some(added), Some(removed)) => format!("  +{} -{}", added, removed),
+                            _ => String::new(),
+                        };
+                        println!(
+                            "{:>6}  {} <{}>{}",
+                            contributor.commits, contributor.name, contributor.email, lines
+                        );
+                        println!(
+                            "        {} .. {}",
+                            contributor.first_commit_date, contributor.last_commit_date
+                        );
+                    }
+
+                    if report.truncated {
+                        println!(
+                            "\n... {} more contributors not shown",
+                            report.total_contributors - report.contributors.len()
+                        );
+                    }
+
+                    // Clean up the repository when finished (unless preserve flag is set)
+                    cleanup_repository(local_repo, cli.preserve_repos);
+
+                    Ok(())
+                }
+                Err(e) => {
+                    tracing::error!("Failed to collect contributors: {}", e);
+                    anyhow::bail!("Failed to collect contributors: {}", e)
+                }
+            }
+        }
+        Commands::Hotspots {
+            repository_location,
+            from_ref,
+            to_ref,
+            since,
+            until,
+            include_globs,
+            exclude_dirs,
+            metric,
+            weight_by_size,
+            directory_depth,
+            max_results,
         } => {
-            use gitcodes_mcp::gitcodes::repository_manager::providers::GitProvider;
+            tracing::debug!("Analyzing hotspots of repository: {}", repository_location);
 
-            // Default to GitHub as provider
-            let git_provider = GitProvider::Github;
+            // Process the repository location (convert relative paths to absolute)
+            let processed_location = match process_repository_location(&repository_location) {
+                Ok(location) => location,
+                Err(e) => {
+                    tracing::error!("Failed to process repository location: {}", e);
+                    return Err(anyhow::anyhow!(
+                        "Failed to process repository location: {}",
+                        e
+                    ));
+                }
+            };
+
+            let hotspots_params = gitcodes_mcp::services::HotspotsServiceParams {
+                repository_location_str: processed_location,
+                from_ref,
+                to_ref,
+                since,
+                until,
+                include_globs,
+                exclude_dirs,
+                metric: Some(metric.into()),
+                weight_by_size: Some(weight_by_size),
+                directory_depth,
+                max_results,
+            };
+
+            match gitcodes_mcp::services::hotspots(manager, hotspots_params).await {
+                Ok((report, local_repo)) => {
+                    println!(
+                        "{} changed files in {} commits ({})",
+                        report.total_files, report.scanned_commits, report.range
+                    );
+
+                    println!();
+                    println!(
+                        "{:>10} {:>7} {:>7} {:>8} {:>8}  File",
+                        "Score", "Commits", "Authors", "Churn", "Lines"
+                    );
+                    for file in &report.files {
+                        println!(
+                            "{:>10.0} {:>7} {:>7} {:>8} {:>8}  {}",
+                            file.score,
+                            file.commits,
+                            file.authors,
+                            file.lines_added + file.lines_removed,
+                            file.lines,
+                            file.path
+                        );
+                    }
+
+                    if !report.directories.is_empty() {
+                        println!();
+                        println!(
+                            "{:>10} {:>7} {:>7} {:>8} {:>8}  Directory",
+                            "Score", "Commits", "Files", "Churn", "Lines"
+                        );
+                        for directory in &report.directories {
+                            println!(
+                                "{:>10.0} {:>7} {:>7} {:>8} {:>8}  {}/",
+                                directory.score,
+                                directory.commits,
+                                directory.files,
+                                directory.lines_added + directory.lines_removed,
+                                directory.lines,
+                                directory.path
+                            );
+                        }
+                    }
+
+                    // Clean up the repository when finished (unless preserve flag is set)
+                    cleanup_repository(local_repo, cli.preserve_repos);
+
+                    Ok(())
+                }
+                Err(e) => {
+                    tracing::error!("Failed to analyze hotspots: {}", e);
+                    anyhow::bail!("Failed to analyze hotspots: {}", e)
+                }
+            }
+        }
+        Commands::CompareRefs {
+            repository_location,
+            base_ref,
+            head_ref,
+            max_commits,
+        } => {
+            tracing::debug!(
+                "Comparing {}...{} in repository: {}",
+                base_ref,
+                head_ref,
+                repository_location
+            );
+
+            // Process the repository location (convert relative paths to absolute)
+            let processed_location = match process_repository_location(&repository_location) {
+                Ok(location) => location,
+                Err(e) => {
+                    tracing::error!("Failed to process repository location: {}", e);
+                    return Err(anyhow::anyhow!(
+                        "Failed to process repository location: {}",
+                        e
+                    ));
+                }
+            };
+
+            let compare_params = gitcodes_mcp::services::CompareRefsServiceParams {
+                repository_location_str: processed_location,
+                base_ref,
+                head_ref,
+                max_commits,
+            };
+
+            match gitcodes_mcp::services::compare_refs(manager, compare_params).await {
+                Ok((comparison, local_repo)) => {
+                    println!(
+                        "{} ({}) ... {} ({})",
+                        comparison.base_ref,
+                        &comparison.base_commit[..7],
+                        comparison.head_ref,
+                        &comparison.head_commit[..7]
+                    );
+                    match &comparison.merge_base {
+                        Some(merge_base) => println!("Merge-base: {}", merge_base),
+                        None => println!("Merge-base: none (unrelated histories)"),
+                    }
+                    println!(
+                        "Status: {:?}, {} ahead, {} behind",
+                        comparison.status, comparison.ahead_by, comparison.behind_by
+                    );
+
+                    for (title, commits) in [
+                        (
+                            format!("Only on {}", comparison.head_ref),
+                            &comparison.ahead_commits,
+                        ),
+                        (
+                            format!("Only on {}", comparison.base_ref),
+                            &comparison.behind_commits,
+                        ),
+                    ] {
+                        if commits.is_empty() {
+                            continue;
+                        }
+                        println!();
+                        println!("{}:", title);
+                        for commit in commits {
+                            println!(
+                                "  {} {} ({}, {})",
+                                &commit.commit_id[..7],
+                                commit.summary,
+                                commit.author_name,
+                                commit.date
+                            );
+                        }
+                    }
+
+                    if comparison.truncated {
+                        println!("\n(commit lists truncated; use -n to show more)");
+                    }
+
+                    // Clean up the repository when finished (unless preserve flag is set)
+                    cleanup_repository(local_repo, cli.preserve_repos);
+
+                    Ok(())
+                }
+                Err(e) => {
+                    tracing::error!("Failed to compare refs: {}", e);
+                    anyhow::bail!("Failed to compare refs: {}", e)
+                }
+            }
+        }
+        Commands::FindSymbol {
+            repository_location,
+            name,
+            regex,
+            case_sensitive,
+            kinds,
+            ref_name,
+            include_globs,
+            exclude_dirs,
+            max_results,
+        } => {
+            tracing::debug!(
+                "Finding symbol {} in repository: {}",
+                name,
+                repository_location
+            );
 
-            // Convert from clap enum types to the types used by repository_manager
-            let sort_option = sort_by.map(|s| s.into());
-            let order_option = order.map(|o| o.into());
+            // Process the repository location (convert relative paths to absolute)
+            let processed_location = match process_repository_location(&repository_location) {
+                Ok(location) => location,
+                Err(e) => {
+                    tracing::error!("Failed to process repository location: {}", e);
+                    return Err(anyhow::anyhow!(
+                        "Failed to process repository location: {}",
+                        e
+                    ));
+                }
+            };
 
-            // Execute the search using the repository manager
-            match manager
-                .search_repositories(
-                    git_provider,
-                    query,
-                    sort_option,
-                    order_option,
-                    per_page,
-                    page,
-                )
-                .await
-            {
-                Ok(result) => {
-                    // Pretty print each repository item with additional fields
-                    for (i, repo) in result.items.iter().enumerate() {
-                        let description = repo.description.as_deref().unwrap_or("<no description>");
+            let symbol_params = gitcodes_mcp::services::FindSymbolServiceParams {
+                repository_location_str: processed_location,
+                name,
+                regex: Some(regex),
+                case_sensitive: Some(case_sensitive),
+                kinds: kinds.map(|kinds| kinds.into_iter().map(Into::into).collect()),
+                ref_name,
+                include_globs,
+                exclude_dirs,
+                skip: None,
+                take: max_results,
+            };
 
-                        // Basic format as shown in the documentation
+            match gitcodes_mcp::services::find_symbol(manager, symbol_params).await {
+                Ok((result, local_repo)) => {
+                    if result.symbols.is_empty() {
+                        tracing::warn!("No definitions of {} found.", result.name);
+                    }
+                    // One definition per line: file:start-end: kind container signature
+                    for symbol in &result.symbols {
+                        let container = symbol
+                            .container
+                            .as_deref()
+                            .map(|container| format!(" [{}]", container))
+                            .unwrap_or_default();
                         println!(
-                            "{}. {}/{} - {} stars",
-                            i + 1,
-                            repo.owner.id.as_ref().unwrap_or(&"N/A".to_string()),
-                            repo.name,
-                            repo.stargazers_count.unwrap_or(0)
+                            "{}:{}-{}: {:?}{} {}",
+                            symbol.file_path,
+                            symbol.start_line,
+                            symbol.end_line,
+                            symbol.kind,
+                            container,
+                            symbol.signature
                         );
-                        println!("   Description: {}", description);
+                    }
+                    if result.total_symbols > result.symbols.len() {
                         println!(
-                            "   URL: {}",
-                            repo.html_url.as_ref().map(|u| u.as_str()).unwrap_or("N/A")
+                            "({} of {} definitions shown; use -n to show more)",
+                            result.symbols.len(),
+                            result.total_symbols
                         );
+                    }
 
-                        // Additional fields requested
-                        println!("   ID: {}", repo.id);
+                    // Clean up the repository when finished (unless preserve flag is set)
+                    cleanup_repository(local_repo, cli.preserve_repos);
 
-                        if let Some(lang) = &repo.language {
-                            println!("   Language: {}", lang);
-                        } else {
-                            println!("   Language: <none>");
-                        }
+                    Ok(())
+                }
+                Err(e) => {
+                    tracing::error!("Failed to find symbol: {}", e);
+                    anyhow::bail!("Failed to find symbol: {}", e)
+                }
+            }
+        }
+        Commands::ShowSymbol {
+            repository_location,
+            qualified_name,
+            ref_name,
+            include_globs,
+            exclude_dirs,
+            max_results,
+        } => {
+            tracing::debug!(
+                "Showing symbol {} in repository: {}",
+                qualified_name,
+                repository_location
+            );
 
-                        println!("   Archived: {}", repo.archived.unwrap_or(false));
+            // Process the repository location (convert relative paths to absolute)
+            let processed_location = match process_repository_location(&repository_location) {
+                Ok(location) => location,
+                Err(e) => {
+                    tracing::error!("Failed to process repository location: {}", e);
+                    return Err(anyhow::anyhow!(
+                        "Failed to process repository location: {}",
+                        e
+                    ));
+                }
+            };
+
+            let show_params = gitcodes_mcp::services::ShowSymbolServiceParams {
+                repository_location_str: processed_location,
+                qualified_name,
+                ref_name,
+                include_globs,
+                exclude_dirs,
+                max_results,
+            };
+
+            match gitcodes_mcp::services::show_symbol(manager, show_params).await {
+                Ok((result, local_repo)) => {
+                    if result.definitions.is_empty() {
+                        tracing::warn!("No definitions of {} found.", result.qualified_name);
+                    }
+                    for definition in result.definitions {
                         println!(
-                            "   Created: {}",
-                            repo.created_at.as_ref().unwrap_or(&"N/A".to_string())
+                            "// {} ({:?}) {}:{}-{}",
+                            definition.qualified_name,
+                            definition.symbol.kind,
+                            definition.symbol.file_path,
+                            definition.source_start_line,
+                            definition.symbol.end_line
                         );
-                        if let Some(score) = repo.score {
-                            println!("   Score: {:.2}", score);
+                        if let FileContents::Text { content, .. } = definition.contents {
+                            for line in content.line_contents {
+                                println!("{:>5}: {}", line.line_number, line.line);
+                            }
                         }
+                        println!();
+                    }
+                    if result.total_matches > 1 {
                         println!(
-                            "   Last Push: {}",
-                            repo.pushed_at.as_ref().unwrap_or(&"N/A".to_string())
+                            "({} definitions match {}; qualify the name to narrow them down)",
+                            result.total_matches, result.qualified_name
                         );
-
-                        // Add empty line after each repository
-                        println!();
                     }
 
-                    // If no results found
-                    if result.items.is_empty() {
-                        println!("No repositories matched your search criteria.");
-                    }
+                    // Clean up the repository when finished (unless preserve flag is set)
+                    cleanup_repository(local_repo, cli.preserve_repos);
 
                     Ok(())
                 }
-                Err(err) => {
-                    tracing::error!("Search failed: {}", err);
-
-                    // Provide more user-friendly error message with suggestions
-                    let error_msg = format!("Search failed: {}", err);
-                    let suggestion = if error_msg.contains("API rate limit") {
-                        "\nSuggestion: You may have exceeded GitHub's API rate limits. Try the following:\n  - Use a GitHub token with '-t' option\n  - Wait a few minutes and try again\n  - Reduce the number of requests"
-                    } else if error_msg.contains("authentication") || error_msg.contains("401") {
-                        "\nSuggestion: Authentication failed. Try the following:\n  - Check that your GitHub token is valid and has not expired\n  - Ensure the token has appropriate permissions\n  - Regenerate your GitHub token if necessary"
-                    } else {
-                        "\nSuggestion: Check your network connection and GitHub credentials."
-                    };
-
-                    anyhow::bail!("{}{}", error_msg, suggestion)
+                Err(e) => {
+                    tracing::error!("Failed to show symbol: {}", e);
+                    anyhow::bail!("Failed to show symbol: {}", e)
                 }
             }
         }
-        Commands::IssueSearch {
-            query,
-            sort_by,
-            order,
-            per_page,
-            page,
-            repository,
-            labels,
-            state,
-            creator,
-            mentioned,
-            assignee,
-            milestone,
-            issue_type,
+        Commands::FindReferences {
+            repository_location,
+            name,
+            ref_name,
+            include_definitions,
+            include_globs,
+            exclude_dirs,
+            skip,
+            take,
         } => {
-            use gitcodes_mcp::gitcodes::repository_manager::providers::GitProvider;
-
-            // Default to GitHub as provider
-            let git_provider = GitProvider::Github;
-
-            // Convert from clap enum types to the types used by repository_manager
-            let sort_option = sort_by.map(|s| s.into());
-            let order_option = order.map(|o| o.into());
+            tracing::debug!(
+                "Finding references to {} in repository: {}",
+                name,
+                repository_location
+            );
 
-            // Create issue search parameters
-            let search_params = gitcodes_mcp::gitcodes::repository_manager::IssueSearchParams {
-                query,
-                sort_by: sort_option,
-                order: order_option,
-                per_page,
-                page,
-                repository,
-                labels,
-                state,
-                creator,
-                mentioned,
-                assignee,
-                milestone,
-                issue_type,
+            // Process the repository location (convert relative paths to absolute)
+            let processed_location = match process_repository_location(&repository_location) {
+                Ok(location) => location,
+                Err(e) => {
+                    tracing::error!("Failed to process repository location: {}", e);
+                    return Err(anyhow::anyhow!(
+                        "Failed to process repository location: {}",
+                        e
+                    ));
+                }
             };
 
-            // Execute the search using the repository manager
-            match manager.search_issues(git_provider, search_params).await {
-                Ok(result) => {
-                    // Pretty print each issue item
-                    for (i, issue) in result.items.iter().enumerate() {
-                        let body_preview = issue
-                            .body
-                            .as_deref()
-                            .unwrap_or("<no description>")
-                            .chars()
-                            .take(100)
-                            .collect::<String>();
-                        let body_display = if issue.body.as_deref().unwrap_or("").len() > 100 {
-                            format!("{}...", body_preview)
-                        } else {
-                            body_preview
-                        };
+            let reference_params = gitcodes_mcp::services::FindReferencesServiceParams {
+                repository_location_str: processed_location,
+                name,
+                ref_name,
+                include_definitions: Some(include_definitions),
+                include_globs,
+                exclude_dirs,
+                skip,
+                take,
+            };
 
-                        // Basic format
+            match gitcodes_mcp::services::find_references(manager, reference_params).await {
+                Ok((result, local_repo)) => {
+                    for definition in &result.definitions {
                         println!(
-                            "{}. #{} - {} [{}]",
-                            i + 1,
-                            issue.number,
-                            issue.title,
-                            issue.state
+                            "// {:?} {} defined at {}:{}",
+                            definition.kind,
+                            definition.name,
+                            definition.file_path,
+                            definition.start_line
                         );
-                        println!("   Repository: {}", issue.repository.name);
-                        println!("   Author: {}", issue.user.login);
-                        println!("   Description: {}", body_display);
-                        println!("   URL: {}", issue.html_url);
-
-                        // Additional fields
-                        println!("   Comments: {}", issue.comments);
-                        println!("   Created: {}", issue.created_at);
-                        println!("   Updated: {}", issue.updated_at);
-
-                        if let Some(closed_at) = &issue.closed_at {
-                            println!("   Closed: {}", closed_at);
-                        }
-
-                        // Show labels if any
-                        if !issue.labels.is_empty() {
-                            let label_names: Vec<String> = issue
-                                .labels
-                                .iter()
-                                .map(|label| label.name.clone())
-                                .collect();
-                            println!("   Labels: {}", label_names.join(", "));
-                        }
+                    }
+                    for reference in &result.references {
+                        println!(
+                            "{}:{}:{}: {}",
+                            reference.file_path,
+                            reference.line,
+                            reference.column,
+                            reference.line_content
+                        );
+                    }
+                    println!(
+                        "({} of {} references to {} in {} indexed files)",
+                        result.references.len(),
+                        result.total_references,
+                        result.name,
+                        result.indexed_files
+                    );
 
-                        // Show assignees if any
-                        if !issue.assignees.is_empty() {
-                            let assignee_names: Vec<String> = issue
-                                .assignees
-                                .iter()
-                                .map(|assignee| assignee.login.clone())
-                                .collect();
-                            println!("   Assignees: {}", assignee_names.join(", "));
-                        }
+                    // Clean up the repository when finished (unless preserve flag is set)
+                    cleanup_repository(local_repo, cli.preserve_repos);
 
-                        // Show milestone if any
-                        // if let Some(milestone) = &issue.milestone {
-                        //     println!("   Milestone: {} [{}]", milestone.title, milestone.state);
-                        // }
+                    Ok(())
+                }
+                Err(e) => {
+                    tracing::error!("Failed to find references: {}", e);
+                    anyhow::bail!("Failed to find references: {}", e)
+                }
+            }
+        }
+        Commands::StructuralSearch {
+            repository_location,
+            pattern,
+            language,
+            ref_name,
+            include_globs,
+            exclude_dirs,
+            skip,
+            take,
+        } => {
+            tracing::debug!(
+                "Searching for pattern {} in repository: {}",
+                pattern,
+                repository_location
+            );
 
-                        if let Some(score) = issue.score {
-                            println!("   Score: {:.2}", score);
-                        }
+            // Process the repository location (convert relative paths to absolute)
+            let processed_location = match process_repository_location(&repository_location) {
+                Ok(location) => location,
+                Err(e) => {
+                    tracing::error!("Failed to process repository location: {}", e);
+                    return Err(anyhow::anyhow!(
+                        "Failed to process repository location: {}",
+                        e
+                    ));
+                }
+            };
 
-                        // Add empty line after each issue
-                        println!();
-                    }
+            let search_params = gitcodes_mcp::services::StructuralSearchServiceParams {
+                repository_location_str: processed_location,
+                pattern,
+                language: language.into(),
+                ref_name,
+                include_globs,
+                exclude_dirs,
+                skip,
+                take,
+            };
 
-                    // Summary information
-                    println!(
-                        "Found {} issues (total: {})",
-                        result.items.len(),
-                        result.total_count
-                    );
-                    if result.incomplete_results {
+            match gitcodes_mcp::services::structural_search(manager, search_params).await {
+                Ok((result, local_repo)) => {
+                    for found in &result.matches {
                         println!(
-                            "Note: Results may be incomplete due to timeout or other factors."
+                            "{}:{}:{}: {}",
+                            found.file_path,
+                            found.start_line,
+                            found.start_column,
+                            found.lines.first().map_or("", |line| line.trim())
                         );
+                        for (name, value) in &found.metavariables {
+                            println!("    ${} = {}", name, value);
+                        }
                     }
+                    println!(
+                        "({} of {} matches)",
+                        result.matches.len(),
+                        result.total_matches
+                    );
 
-                    // If no results found
-                    if result.items.is_empty() {
-                        println!("No issues matched your search criteria.");
-                    }
+                    // Clean up the repository when finished (unless preserve flag is set)
+                    cleanup_repository(local_repo, cli.preserve_repos);
 
                     Ok(())
                 }
-                Err(err) => {
-                    tracing::error!("Issue search failed: {}", err);
-
-                    // Provide more user-friendly error message with suggestions
-                    let error_msg = format!("Issue search failed: {}", err);
-                    let suggestion = if error_msg.contains("API rate limit") {
-                        "\nSuggestion: You may have exceeded GitHub's API rate limits. Try the following:\n  - Use a GitHub token with '-t' option\n  - Wait a few minutes and try again\n  - Reduce the number of requests"
-                    } else if error_msg.contains("authentication") || error_msg.contains("401") {
-                        "\nSuggestion: Authentication failed. Try the following:\n  - Check that your GitHub token is valid and has not expired\n  - Ensure the token has appropriate permissions\n  - Regenerate your GitHub token if necessary"
-                    } else {
-                        "\nSuggestion: Check your network connection, GitHub credentials, and search query syntax."
-                    };
-
-                    anyhow::bail!("{}{}", error_msg, suggestion)
+                Err(e) => {
+                    tracing::error!("Failed structural search: {}", e);
+                    anyhow::bail!("Failed structural search: {}", e)
                 }
             }
         }
-        Commands::Grep {
+        Commands::SearchRanked {
             repository_location,
+            query,
             ref_name,
-            pattern,
-            case_sensitive,
-            file_extensions,
             include_globs,
             exclude_dirs,
-            before_context,
-            after_context,
+            skip,
+            take,
         } => {
             tracing::debug!(
-                "Searching for code pattern in repository: {}",
+                "Ranking code for query {} in repository: {}",
+                query,
                 repository_location
             );
-            tracing::debug!("Pattern: {}", pattern);
-            if let Some(r) = &ref_name {
-                tracing::debug!("Ref: {}", r);
-            }
 
             // Process the repository location (convert relative paths to absolute)
             let processed_location = match process_repository_location(&repository_location) {
@@ -778,40 +3043,39 @@ async fn main() -> Result<()> {
                 }
             };
 
-            // Use the services module to perform the grep operation
-            use gitcodes_mcp::services;
-
-            let grep_params = services::GrepParams {
-                repository_location_str: processed_location.clone(),
-                pattern,
-                ref_name: ref_name.clone(),
-                case_sensitive: case_sensitive.unwrap_or(false),
-                file_extensions: file_extensions.clone(),
-                include_globs: include_globs.clone(),
-                exclude_dirs: exclude_dirs.clone(),
-                before_context,
-                after_context,
-                skip: None,                        // No skip (pagination)
-                take: None,                        // No take (pagination)
-                match_content_omit_num: Some(150), // Default to 150 characters
+            let search_params = gitcodes_mcp::services::RankedSearchServiceParams {
+                repository_location_str: processed_location,
+                query,
+                ref_name,
+                include_globs,
+                exclude_dirs,
+                skip,
+                take,
             };
 
-            match services::perform_grep_in_repository(manager, grep_params).await {
+            match gitcodes_mcp::services::search_code_ranked(manager, search_params).await {
                 Ok((result, local_repo)) => {
-                    // Just print each match in a simple format: file:line:content
-                    if !result.matches.is_empty() {
-                        for m in &result.matches {
-                            println!(
-                                "{}:{}:{}",
-                                m.file_path.display(),
-                                m.line_number,
-                                m.line_content
-                            );
+                    for chunk in &result.chunks {
+                        println!(
+                            "{}:{}-{} [{:.3}] {} ({})",
+                            chunk.file_path,
+                            chunk.start_line,
+                            chunk.end_line,
+                            chunk.score,
+                            chunk.symbol.as_deref().unwrap_or(""),
+                            chunk.matched_terms.join(", ")
+                        );
+                        for (offset, line) in chunk.lines.iter().enumerate() {
+                            println!("{:>6}  {}", chunk.start_line + offset, line);
                         }
-                    } else {
-                        // Let user know if no matches were found
-                        tracing::warn!("No matches found.");
+                        println!();
                     }
+                    println!(
+                        "({} of {} matching chunks, {} indexed)",
+                        result.chunks.len(),
+                        result.total_matches,
+                        result.indexed_chunks
+                    );
 
                     // Clean up the repository when finished (unless preserve flag is set)
                     cleanup_repository(local_repo, cli.preserve_repos);
@@ -819,73 +3083,24 @@ async fn main() -> Result<()> {
                     Ok(())
                 }
                 Err(e) => {
-                    // Log the error but don't exit immediately
-                    tracing::error!("Failed to search code: {}", e);
-
-                    // Check if this is a terminal error or if our fallback mechanism might have worked
-                    if e.contains("All URL formats failed")
-                        || !e.contains("Failed to clone repository")
-                    {
-                        // This is a terminal error after all fallbacks, provide user-friendly message
-                        let error_msg = format!("Failed to search code: {}", e);
-                        let suggestion = if error_msg.contains("clone")
-                            || error_msg.contains("Failed to parse repository location")
-                        {
-                            if error_msg.contains("invalid remote git url")
-                                || error_msg.contains("must be absolute")
-                            {
-                                "\nSuggestion: The repository location format appears to be invalid. Try the following:\n  - For GitHub: Use 'git@github.com:user/repo.git' (SSH format works most reliably)\n  - Alternative formats: 'https://github.com/user/repo' (without .git suffix) or 'github:user/repo'\n  - For local repositories: Use an absolute path to an existing local git repository\n  - Relative paths are not supported for security reasons"
-                            } else if error_msg.contains("HTTPS")
-                                || error_msg.contains("I/O error")
-                                || error_msg.contains("io error")
-                                || error_msg.contains("An IO error occurred")
-                            {
-                                "\nSuggestion: There was an issue cloning via HTTPS. Try the following:\n  - For GitHub URLs, try the format: 'https://github.com/user/repo' (without .git suffix)\n  - As an alternative, use SSH URL format: 'git@github.com:user/repo.git'\n  - Check your network connection or firewall settings\n  - Check if you have a proxy configured and it's properly set in your environment variables\n  - Run 'git config --global --get http.proxy' to verify your Git proxy settings\n  - Verify the repository exists and is accessible\n  - For private repositories, provide a GitHub token with '-t'"
-                            } else if error_msg.contains("authentication")
-                                || error_msg.contains("credential")
-                            {
-                                "\nSuggestion: Authentication failed when cloning the repository. Try the following:\n  - For private repositories, provide a GitHub token with '-t'\n  - Check if your token has the correct permissions\n  - Try using SSH URL format with properly configured SSH keys: 'git@github.com:user/repo.git'\n  - Verify your SSH keys are set up correctly (run 'ssh -T git@github.com')"
-                            } else if error_msg.contains("checkout")
-                                || error_msg.contains("reference")
-                                || error_msg.contains("ref")
-                            {
-                                "\nSuggestion: Failed to checkout the specified branch or reference. Try the following:\n  - Verify the branch/tag name exists in the repository\n  - Try without specifying a branch (it will use the default branch)\n  - Check the repository's available branches on GitHub"
-                            } else {
-                                "\nSuggestion: Failed to clone repository. Try the following:\n  - Use SSH URL format: 'git@github.com:user/repo.git' (most reliable)\n  - Check your network connection\n  - Check for any proxy requirements in your network\n  - Try running: ./git_diagnostic <repository_url> to diagnose issues\n  - Verify the repository exists and is accessible\n  - Ensure you have proper permissions (provide a GitHub token with '-t' if it's a private repository)\n  - Check if the ref/branch/tag exists in the repository"
-                            }
-                        } else if error_msg.contains("pattern") {
-                            "\nSuggestion: There was an issue with your search pattern. Try the following:\n  - Simplify your search pattern\n  - Escape special regex characters if you're not using regex\n  - Use the --case-sensitive option if needed"
-                        } else {
-                            "\nSuggestion: Check your repository location and search parameters."
-                        };
-
-                        anyhow::bail!("{}{}", error_msg, suggestion)
-                    } else {
-                        // The repository manager might be handling this with fallbacks
-                        tracing::info!("Repository manager is handling the error with fallbacks");
-                        // Continue without error to let repository manager fallbacks work
-                        Ok(())
-                    }
+                    tracing::error!("Failed ranked search: {}", e);
+                    anyhow::bail!("Failed ranked search: {}", e)
                 }
             }
         }
-        Commands::ShowFile {
+        Commands::FindFiles {
             repository_location,
+            query,
             ref_name,
-            file_path,
-            max_size,
-            line_from,
-            line_to,
-            without_line_numbers,
+            no_ignore,
+            skip,
+            take,
         } => {
             tracing::debug!(
-                "Showing file contents from repository: {}, file: {}",
-                repository_location,
-                file_path
+                "Finding files matching {} in repository: {}",
+                query,
+                repository_location
             );
-            if let Some(r) = &ref_name {
-                tracing::debug!("Ref: {}", r);
-            }
 
             // Process the repository location (convert relative paths to absolute)
             let processed_location = match process_repository_location(&repository_location) {
@@ -899,76 +3114,107 @@ async fn main() -> Result<()> {
                 }
             };
 
-            // Use the services module to access the file contents
-            use gitcodes_mcp::services;
-
-            // Clone file_path to retain ownership of the original for later use
-            let file_path_clone = file_path.clone();
-
-            let show_params = services::ShowFileParams {
-                repository_location_str: processed_location.clone(),
-                file_path: file_path_clone,
-                ref_name: ref_name.clone(),
-                max_size,
-                line_from,
-                line_to,
-                without_line_numbers: Some(without_line_numbers),
+            let find_params = gitcodes_mcp::services::FindFilesServiceParams {
+                repository_location_str: processed_location,
+                query,
+                ref_name,
+                respect_gitignore: Some(!no_ignore),
+                skip,
+                take,
             };
 
-            match services::show_file_contents(manager, show_params).await {
-                Ok((file_contents, local_repo, without_line_numbers)) => {
-                    // Format and display the file contents based on type
-                    match file_contents {
-                        FileContents::Text { content, metadata } => {
-                            // Display metadata about the file
-                            let line_range_info = match (line_from, line_to) {
-                                (Some(from), Some(to)) => format!(" (lines {}-{})", from, to),
-                                (Some(from), None) => format!(" (from line {})", from),
-                                (None, Some(to)) => format!(" (up to line {})", to),
-                                (None, None) => String::new(),
-                            };
+            match gitcodes_mcp::services::find_files(manager, find_params).await {
+                Ok((result, local_repo)) => {
+                    for file in &result.files {
+                        println!(
+                            "{}\t{}\t{}",
+                            file.path,
+                            file.size,
+                            file.language.as_deref().unwrap_or("-")
+                        );
+                    }
+                    println!(
+                        "({} of {} matching files)",
+                        result.files.len(),
+                        result.total_matches
+                    );
 
-                            println!("File: {}{}", file_path, line_range_info);
-                            println!("Type: Text file");
-                            println!(
-                                "Size: {} characters, {} lines",
-                                metadata.char_count, metadata.line_count
-                            );
+                    // Clean up the repository when finished (unless preserve flag is set)
+                    cleanup_repository(local_repo, cli.preserve_repos);
 
-                            // Print the actual content based on format preference
-                            if without_line_numbers {
-                                // Plain text format
-                                println!("{}", content.to_string());
-                            } else {
-                                // Line number format (file:line:content)
-                                let content_str = content.to_string();
-                                let mut line_number = line_from.unwrap_or(1);
+                    Ok(())
+                }
+                Err(e) => {
+                    tracing::error!("Failed to find files: {}", e);
+                    anyhow::bail!("Failed to find files: {}", e)
+                }
+            }
+        }
+        Commands::GrepFacets {
+            repository_location,
+            pattern,
+            ref_name,
+            case_sensitive,
+            fixed_string,
+            word_regexp,
+            multiline,
+            include_globs,
+            exclude_dirs,
+            changed_only,
+            use_index,
+            top_n,
+        } => {
+            tracing::debug!(
+                "Counting matches of {} per facet in repository: {}",
+                pattern,
+                repository_location
+            );
 
-                                for line in content_str.lines() {
-                                    println!("{file_path}:{line_number}:{line}");
-                                    line_number += 1;
-                                }
-                            }
-                        }
-                        FileContents::Binary { message, metadata } => {
-                            println!("File: {}", file_path);
-                            println!("Type: Binary file");
-                            println!("Size: {} bytes", metadata.size_bytes);
-                            if let Some(mime) = &metadata.mime_type {
-                                println!("MIME type: {}", mime);
-                            }
-                            println!("---");
-                            println!("{}", message);
+            // Process the repository location (convert relative paths to absolute)
+            let processed_location = match process_repository_location(&repository_location) {
+                Ok(location) => location,
+                Err(e) => {
+                    tracing::error!("Failed to process repository location: {}", e);
+                    return Err(anyhow::anyhow!(
+                        "Failed to process repository location: {}",
+                        e
+                    ));
+                }
+            };
+
+            let facets_params = gitcodes_mcp::services::GrepFacetsParams {
+                repository_location_str: processed_location,
+                pattern,
+                ref_name,
+                case_sensitive: case_sensitive.unwrap_or(false),
+                file_extensions: None,
+                include_globs,
+                exclude_dirs,
+                changed_files_only: changed_only,
+                use_index,
+                fixed_string,
+                word_regexp,
+                multiline,
+                top_n,
+            };
+
+            match gitcodes_mcp::services::grep_facets(manager, facets_params).await {
+                Ok((facets, local_repo)) => {
+                    println!(
+                        "{} matching lines in {} files",
+                        facets.total_match_line_number, facets.total_files
+                    );
+                    for (title, counts) in [
+                        ("Files", &facets.files),
+                        ("Directories", &facets.directories),
+                        ("Extensions", &facets.extensions),
+                    ] {
+                        if counts.is_empty() {
+                            continue;
                         }
-                        FileContents::Image { message, metadata } => {
-                            println!("File: {}", file_path);
-                            println!("Type: Image file");
-                            // media_type is directly a String, not an Option
-                            println!("Media type: {}", metadata.media_type);
-                            println!("Size: {} bytes", metadata.size_bytes);
-                            println!("---");
-                            println!("{}", message);
-                            println!("Note: Use a graphical viewer to see this image.");
+                        println!("\n{}:", title);
+                        for facet in counts {
+                            println!("{:>8}  {}", facet.count, facet.value);
                         }
                     }
 
@@ -978,40 +3224,21 @@ async fn main() -> Result<()> {
                     Ok(())
                 }
                 Err(e) => {
-                    tracing::error!("Failed to show file contents: {}", e);
-
-                    // Provide more user-friendly error message with suggestions
-                    let error_msg = format!("Failed to show file contents: {}", e);
-                    let suggestion = if error_msg.contains("clone")
-                        || error_msg.contains("Failed to parse repository location")
-                    {
-                        if error_msg.contains("invalid remote git url")
-                            || error_msg.contains("must be absolute")
-                        {
-                            "\nSuggestion: The repository location format appears to be invalid. Try the following:\n  - For GitHub: Use 'git@github.com:user/repo.git' (SSH format works most reliably)\n  - Alternative formats: 'https://github.com/user/repo' or 'github:user/repo'\n  - For local repositories: Use an absolute path to an existing local git repository\n  - Relative paths are not supported for security reasons"
-                        } else {
-                            "\nSuggestion: Could not access repository. Try the following:\n  - Check your network connection\n  - Verify the repository exists and is accessible\n  - Ensure you have proper permissions (provide a GitHub token with '-t' if it's a private repository)"
-                        }
-                    } else if error_msg.contains("not found")
-                        || error_msg.contains("File not found")
-                    {
-                        "\nSuggestion: File not found in repository. Try the following:\n  - Check the file path spelling\n  - Check if the file exists in the specified branch or tag\n  - Use the correct path relative to the repository root"
-                    } else if error_msg.contains("too large") {
-                        "\nSuggestion: File is too large to display. Try the following:\n  - Use the --max-size option to increase the maximum file size\n  - Use the --from-line and --to-line options to view a subset of the file"
-                    } else if error_msg.contains("..") || error_msg.contains("Invalid path") {
-                        "\nSuggestion: Invalid file path. Ensure that:\n  - The path does not contain '..' (parent directory references)\n  - The path is relative to the repository root\n  - The path does not attempt to access outside the repository"
-                    } else {
-                        "\nSuggestion: Check your repository location and file path."
-                    };
-
-                    anyhow::bail!("{}{}", error_msg, suggestion)
+                    tracing::error!("Failed to count matches: {}", e);
+                    anyhow::bail!("Failed to count matches: {}", e)
                 }
             }
         }
-        Commands::ListRefs {
+        Commands::Outline {
             repository_location,
+            file_path,
+            ref_name,
         } => {
-            tracing::debug!("Listing references for repository: {}", repository_location);
+            tracing::debug!(
+                "Outlining file {} in repository: {}",
+                file_path,
+                repository_location
+            );
 
             // Process the repository location (convert relative paths to absolute)
             let processed_location = match process_repository_location(&repository_location) {
@@ -1025,90 +3252,38 @@ async fn main() -> Result<()> {
                 }
             };
 
-            // Get the refs directly from the repository manager
-
-            match manager.list_repository_refs(&processed_location).await {
-                Err(e) => {
-                    tracing::error!("Failed to list repository references: {}", e);
-
-                    // Provide more user-friendly error message with suggestions
-                    let error_msg = format!("Failed to list repository references: {}", e);
-                    let suggestion = if error_msg.contains("clone")
-                        || error_msg.contains("Failed to parse repository location")
-                    {
-                        if error_msg.contains("invalid remote git url")
-                            || error_msg.contains("must be absolute")
-                        {
-                            "\nSuggestion: The repository location format appears to be invalid. Try the following:\n  - For GitHub: Use 'git@github.com:user/repo.git' (SSH format works most reliably)\n  - Alternative formats: 'https://github.com/user/repo' or 'github:user/repo'\n  - For local repositories: Use an absolute path to an existing local git repository\n  - Relative paths are not supported for security reasons"
-                        } else {
-                            "\nSuggestion: Failed to access repository. Try the following:\n  - Check your network connection\n  - Verify the repository exists and is accessible\n  - Ensure you have proper permissions (provide a GitHub token with '-t' if it's a private repository)"
-                        }
-                    } else if error_msg.contains("API") || error_msg.contains("rate limit") {
-                        "\nSuggestion: You may have exceeded GitHub's API rate limits. Try the following:\n  - Use a GitHub token with '-t' option\n  - Wait a few minutes and try again"
-                    } else if error_msg.contains("not found") || error_msg.contains("404") {
-                        "\nSuggestion: Repository not found. Try the following:\n  - Check the spelling of the repository name\n  - Ensure the repository exists and is public (or you have access to it)\n  - Use the correct repository format (user/repo)"
-                    } else {
-                        "\nSuggestion: Check the repository location and your access permissions."
-                    };
-
-                    return Err(anyhow::anyhow!("{}{}", error_msg, suggestion));
-                }
-                Ok((refs, local_repo_opt)) => {
-                    // Print the header for the table
-                    println!("{:<40} {:<10} {:<40}", "Reference", "Type", "Commit ID");
-                    println!("{:-<40} {:-<10} {:-<40}", "", "", "");
-
-                    // Display branches
-                    if !refs.branches.is_empty() {
-                        for branch in &refs.branches {
-                            println!(
-                                "{:<40} {:<10} {:<40}",
-                                format!("branch: {}", branch.name),
-                                "branch",
-                                branch.commit_id
-                            );
-                        }
-                    }
-
-                    // Display tags
-                    if !refs.tags.is_empty() {
-                        for tag in &refs.tags {
-                            println!(
-                                "{:<40} {:<10} {:<40}",
-                                format!("tag: {}", tag.name),
-                                "tag",
-                                tag.commit_id
-                            );
-                        }
-                    }
+            let outline_params = gitcodes_mcp::services::FileOutlineServiceParams {
+                repository_location_str: processed_location,
+                file_path,
+                ref_name,
+            };
 
-                    // Display count of references found
-                    let total_refs = refs.branches.len() + refs.tags.len();
+            match gitcodes_mcp::services::file_outline(manager, outline_params).await {
+                Ok((outline, local_repo)) => {
                     println!(
-                        "
-Total: {} references found ({} branches, {} tags)",
-                        total_refs,
-                        refs.branches.len(),
-                        refs.tags.len()
+                        "{} ({:?}, {} lines)",
+                        outline.file_path, outline.language, outline.line_count
                     );
+                    print_outline_items(&outline.items, 1);
 
-                    // Clean up the repository if it exists (unless preserve flag is set)
-                    cleanup_repository_opt(local_repo_opt, cli.preserve_repos);
+                    // Clean up the repository when finished (unless preserve flag is set)
+                    cleanup_repository(local_repo, cli.preserve_repos);
+
+                    Ok(())
+                }
+                Err(e) => {
+                    tracing::error!("Failed to outline file: {}", e);
+                    anyhow::bail!("Failed to outline file: {}", e)
                 }
             }
-
-            Ok(())
         }
-        Commands::Tree {
+        Commands::Status {
             repository_location,
-            ref_name,
-            case_sensitive,
-            respect_gitignore,
-            depth,
-            strip_path_prefix,
-            search_relative_path,
+            no_untracked,
+            no_diff,
+            context_lines,
         } => {
-            tracing::debug!("Getting tree for repository: {}", repository_location);
+            tracing::debug!("Reading working-tree status of: {}", repository_location);
 
             // Process the repository location (convert relative paths to absolute)
             let processed_location = match process_repository_location(&repository_location) {
@@ -1122,107 +3297,65 @@ Total: {} references found ({} branches, {} tags)",
                 }
             };
 
-            // Call the tree service function
-            let tree_params = gitcodes_mcp::services::TreeServiceParams {
-                repository_location_str: processed_location.clone(),
-                ref_name: ref_name.clone(),
-                case_sensitive: Some(case_sensitive),
-                respect_gitignore: Some(respect_gitignore),
-                depth,
-                strip_path_prefix: Some(strip_path_prefix),
-                search_relative_path: search_relative_path.map(std::path::PathBuf::from),
+            let status_params = gitcodes_mcp::services::GitStatusServiceParams {
+                repository_location_str: processed_location,
+                include_untracked: Some(!no_untracked),
+                include_diffs: Some(!no_diff),
+                context_lines,
             };
 
-            match gitcodes_mcp::services::get_repository_tree(manager, tree_params).await {
-                Ok((tree, local_repo)) => {
-                    // Display the tree structure
-                    if tree.is_empty() {
-                        println!("No directories found in repository");
-                    } else {
-                        println!("Directory tree for repository: {}", repository_location);
-                        if let Some(ref_name) = &ref_name {
-                            println!("Reference: {}", ref_name);
-                        }
-                        println!("---");
-
-                        // Print each directory tree
-                        for dir_tree in &tree {
-                            if dir_tree.dir.is_empty() {
-                                println!("Repository root:");
-                            } else {
-                                println!("{}:", dir_tree.dir);
-                            }
-
-                            // Sort entries by type (directories first) and then by name
-                            let mut sorted_entries = dir_tree.entries.clone();
-                            sorted_entries.sort_by(|a, b| {
-                                use gitcodes_mcp::gitcodes::local_repository::TreeEntry;
-                                match (a, b) {
-                                    (
-                                        TreeEntry::Directory(a_name),
-                                        TreeEntry::Directory(b_name),
-                                    ) => a_name.cmp(b_name),
-                                    (TreeEntry::File(a_name), TreeEntry::File(b_name)) => {
-                                        a_name.cmp(b_name)
-                                    }
-                                    (TreeEntry::Directory(_), TreeEntry::File(_)) => {
-                                        std::cmp::Ordering::Less
-                                    }
-                                    (TreeEntry::File(_), TreeEntry::Directory(_)) => {
-                                        std::cmp::Ordering::Greater
-                                    }
-                                }
-                            });
+            match gitcodes_mcp::services::git_status(manager, status_params).await {
+                Ok((status, local_repo)) => {
+                    let head = status
+                        .head_commit
+                        .as_deref()
+                        .map(|commit| commit[..7].to_string())
+                        .unwrap_or_else(|| "no commits yet".to_string());
+                    match &status.branch {
+                        Some(branch) => println!("On branch {} ({})", branch, head),
+                        None => println!("HEAD detached at {}", head),
+                    }
 
-                            // Display entries with tree-like formatting
-                            for (i, entry) in sorted_entries.iter().enumerate() {
-                                let is_last = i == sorted_entries.len() - 1;
-                                let prefix = if is_last { "└── " } else { "├── " };
+                    if status.clean {
+                        println!("Nothing to commit, working tree clean");
+                    } else {
+                        println!(
+                            "{} staged, {} unstaged, {} untracked",
+                            status.staged_count, status.unstaged_count, status.untracked_count
+                        );
+                        println!();
+                    }
 
-                                match entry {
-                                    gitcodes_mcp::gitcodes::local_repository::TreeEntry::Directory(name) => {
-                                        println!("{}📁 {}/", prefix, name);
-                                    }
-                                    gitcodes_mcp::gitcodes::local_repository::TreeEntry::File(name) => {
-                                        println!("{}📄 {}", prefix, name);
-                                    }
-                                }
-                            }
-                            println!(); // Empty line between directories
+                    // Short format like `git status -s`: index column, worktree column, path
+                    let code = |change: Option<&str>| match change {
+                        Some("added") => 'A',
+                        Some("deleted") => 'D',
+                        Some("modified") => 'M',
+                        Some("type_changed") => 'T',
+                        Some("conflicted") => 'U',
+                        _ => ' ',
+                    };
+                    for file in &status.files {
+                        let (index, worktree) = if file.untracked {
+                            ('?', '?')
+                        } else {
+                            (code(file.staged.as_deref()), code(file.unstaged.as_deref()))
+                        };
+                        if file.binary {
+                            println!("{}{} {} (binary)", index, worktree, file.path);
+                        } else {
+                            println!(
+                                "{}{} {} (+{} -{})",
+                                index, worktree, file.path, file.lines_added, file.lines_removed
+                            );
+                        }
+                        for hunk in &file.hunks {
+                            println!(
+                                "@@ -{},{} +{},{} @@",
+                                hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines
+                            );
+                            println!("{}", hunk.content);
                         }
-
-                        // Print summary
-                        let total_files: usize = tree
-                            .iter()
-                            .map(|dir| {
-                                dir.entries
-                                    .iter()
-                                    .filter(|entry| {
-                                        matches!(
-                                            entry,
-                                            gitcodes_mcp::gitcodes::local_repository::TreeEntry::File(_)
-                                        )
-                                    })
-                                    .count()
-                            })
-                            .sum();
-
-                        let total_dirs: usize = tree
-                            .iter()
-                            .map(|dir| {
-                                dir.entries
-                                    .iter()
-                                    .filter(|entry| {
-                                        matches!(
-                                            entry,
-                                            gitcodes_mcp::gitcodes::local_repository::TreeEntry::Directory(_)
-                                        )
-                                    })
-                                    .count()
-                            })
-                            .sum();
-
-                        println!("Total: {} files, {} directories", total_files, total_dirs);
                     }
 
                     // Clean up the repository when finished (unless preserve flag is set)
@@ -1231,27 +3364,8 @@ Total: {} references found ({} branches, {} tags)",
                     Ok(())
                 }
                 Err(e) => {
-                    tracing::error!("Failed to get repository tree: {}", e);
-
-                    // Provide more user-friendly error message with suggestions
-                    let error_msg = format!("Failed to get repository tree: {}", e);
-                    let suggestion = if error_msg.contains("clone")
-                        || error_msg.contains("Failed to parse repository location")
-                    {
-                        if error_msg.contains("invalid remote git url")
-                            || error_msg.contains("must be absolute")
-                        {
-                            "\nSuggestion: The repository location format appears to be invalid. Try the following:\n  - For GitHub: Use 'git@github.com:user/repo.git' (SSH format works most reliably)\n  - Alternative formats: 'https://github.com/user/repo' or 'github:user/repo'\n  - For local repositories: Use an absolute path to an existing local git repository\n  - Relative paths are not supported for security reasons"
-                        } else {
-                            "\nSuggestion: Could not access repository. Try the following:\n  - Check your network connection\n  - Verify the repository exists and is accessible\n  - Ensure you have proper permissions (provide a GitHub token with '-t' if it's a private repository)"
-                        }
-                    } else if error_msg.contains("not found") {
-                        "\nSuggestion: Repository not found. Try the following:\n  - Check the repository location spelling\n  - Check if the repository exists and is accessible\n  - Use the correct repository format"
-                    } else {
-                        "\nSuggestion: Check your repository location and access permissions."
-                    };
-
-                    anyhow::bail!("{}{}", error_msg, suggestion)
+                    tracing::error!("Failed to read working-tree status: {}", e);
+                    anyhow::bail!("Failed to read working-tree status: {}", e)
                 }
             }
         }
@@ -0,0 +1,291 @@
+//! Code search combining several patterns with AND/OR/NOT at file scope
+//!
+//! Every pattern is searched with [`LocalRepository::perform_code_search`], or with
+//! [`LocalRepository::perform_code_search_at_ref`] when a ref is given. A file is part of
+//! the result when it matches all of the `all_of` patterns, at least one of the `any_of`
+//! patterns and none of the `none_of` patterns. As soon as the first positive pattern has
+//! produced candidate files, the remaining patterns only read those files.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use lumin::search::SearchResultLine as LuminSearchResultLine;
+
+use super::{
+    CodeSearchOptions, CodeSearchResult, LocalRepository, prevent_directory_traversal,
+    search_result,
+};
+
+/// Options for a boolean multi-pattern code search
+///
+/// The pattern options (`case_sensitive`, `fixed_string`, `word_regexp` and `multiline`)
+/// and the file filters apply to every pattern alike.
+#[derive(Debug, Clone, Default)]
+pub struct BooleanSearchOptions {
+    /// Branch, tag, or commit whose tree is searched instead of the worktree
+    pub ref_name: Option<String>,
+
+    /// Patterns that must all match somewhere in a file
+    pub all_of: Vec<String>,
+
+    /// Patterns of which at least one must match somewhere in a file (ignored when empty)
+    pub any_of: Vec<String>,
+
+    /// Patterns that must not match anywhere in a file
+    pub none_of: Vec<String>,
+
+    /// Whether the patterns are case-sensitive
+    pub case_sensitive: bool,
+
+    /// Whether the patterns are literal strings rather than regexes
+    pub fixed_string: bool,
+
+    /// Whether the patterns only match whole words
+    pub word_regexp: bool,
+
+    /// Whether matches of the patterns may span multiple lines
+    pub multiline: bool,
+
+    /// Glob patterns of the files to search (e.g. ["**/*.rs"])
+    pub include_globs: Option<Vec<String>>,
+
+    /// Directories or glob patterns to exclude from the search
+    pub exclude_dirs: Option<Vec<String>>,
+
+    /// Number of lines to include before each match of a positive pattern
+    pub before_context: Option<usize>,
+
+    /// Number of lines to include after each match of a positive pattern
+    pub after_context: Option<usize>,
+
+    /// Number of result lines to skip (for pagination)
+    pub skip: Option<usize>,
+
+    /// Maximum number of result lines to return (for pagination)
+    pub take: Option<usize>,
+
+    /// Maximum number of characters to show around matches in long lines
+    pub match_content_omit_num: Option<usize>,
+}
+
+impl LocalRepository {
+    /// Searches for files matching a boolean combination of patterns
+    ///
+    /// For example, `all_of: ["impl Drop", "unsafe"]` with `none_of: ["#[cfg(test)]"]`
+    /// finds the files implementing `Drop` with unsafe code that have no test module.
+    ///
+    /// The result holds the matches of the positive (`all_of` and `any_of`) patterns in
+    /// the selected files, with their context lines. A line matched by several patterns
+    /// appears once. Lines are sorted by path and line number before `skip`/`take` are
+    /// applied, and `total_match_line_number` counts them before pagination, context lines
+    /// included, as for a single pattern search. The `pattern` of the result describes the
+    /// whole expression, e.g. `"impl Drop" AND "unsafe" AND NOT "#[cfg(test)]"`.
+    ///
+    /// # Parameters
+    ///
+    /// * `options` - The patterns and search options
+    ///
+    /// # Returns
+    ///
+    /// * `Result<CodeSearchResult, String>` - Matches of the positive patterns in the selected files
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if neither `all_of` nor `any_of` holds a pattern, a pattern is empty
+    /// or invalid, an exclude directory contains a traversal, or a search fails.
+    pub async fn search_code_boolean(
+        &self,
+        options: BooleanSearchOptions,
+    ) -> Result<CodeSearchResult, String> {
+        if let Err(e) = self.validate() {
+            return Err(format!("Repository validation failed: {}", e));
+        }
+        if options.all_of.is_empty() && options.any_of.is_empty() {
+            return Err(
+                "A boolean search needs at least one pattern in all_of or any_of".to_string(),
+            );
+        }
+        let patterns = || {
+            options
+                .all_of
+                .iter()
+                .chain(&options.any_of)
+                .chain(&options.none_of)
+        };
+        if patterns().any(|pattern| pattern.is_empty()) {
+            return Err("Boolean search patterns must not be empty".to_string());
+        }
+        for dir in options.exclude_dirs.iter().flatten() {
+            if let Err(e) = prevent_directory_traversal(&PathBuf::from(dir)) {
+                return Err(format!("Invalid exclude_dir path: {}", e));
+            }
+        }
+
+        // None until a positive pattern has been searched: every file is a candidate
+        let mut candidates: Option<BTreeSet<PathBuf>> = None;
+        let mut positive_lines = Vec::new();
+
+        for pattern in &options.all_of {
+            if has_no_candidates(&candidates) {
+                break;
+            }
+            let result = self
+                .search_boolean_term(&options, pattern, candidates.as_ref(), true)
+                .await?;
+            let files = matched_files(&result.matches);
+            candidates = Some(match candidates {
+                Some(previous) => previous.intersection(&files).cloned().collect(),
+                None => files,
+            });
+            positive_lines.extend(result.matches);
+        }
+
+        if !options.any_of.is_empty() && !has_no_candidates(&candidates) {
+            let mut any_files = BTreeSet::new();
+            for pattern in &options.any_of {
+                let result = self
+                    .search_boolean_term(&options, pattern, candidates.as_ref(), true)
+                    .await?;
+                any_files.extend(matched_files(&result.matches));
+                positive_lines.extend(result.matches);
+            }
+            candidates = Some(match candidates {
+                Some(previous) => previous.intersection(&any_files).cloned().collect(),
+                None => any_files,
+            });
+        }
+
+        let mut selected = candidates.unwrap_or_default();
+        for pattern in &options.none_of {
+            if selected.is_empty() {
+                break;
+            }
+            let result = self
+                .search_boolean_term(&options, pattern, Some(&selected), false)
+                .await?;
+            for file in matched_files(&result.matches) {
+                selected.remove(&file);
+            }
+        }
+
+        // One entry per line of the selected files; a match wins over a context line
+        let mut lines: BTreeMap<(PathBuf, u64), LuminSearchResultLine> = BTreeMap::new();
+        for line in positive_lines {
+            if !selected.contains(&line.file_path) {
+                continue;
+            }
+            let key = (line.file_path.clone(), line.line_number);
+            match lines.get(&key) {
+                Some(existing) if !existing.is_context || line.is_context => {}
+                _ => {
+                    lines.insert(key, line);
+                }
+            }
+        }
+
+        let total_match_line_number = lines.len();
+        let search_result_lines = lines
+            .into_values()
+            .skip(options.skip.unwrap_or(0))
+            .take(options.take.unwrap_or(usize::MAX))
+            .collect();
+
+        Ok(CodeSearchResult::new(search_result::CodeSearchParams {
+            total_match_line_number,
+            search_result_lines,
+            pattern: describe_expression(&options),
+            repository: self.repository_location.clone(),
+            case_sensitive: options.case_sensitive,
+            file_extensions: None,
+            include_globs: options.include_globs,
+            exclude_globs: options.exclude_dirs,
+            before_context: options.before_context,
+            after_context: options.after_context,
+        }))
+    }
+
+    /// Searches one pattern of a boolean search, restricted to `candidates` if given
+    ///
+    /// Context lines and content omission are only needed for the positive patterns,
+    /// whose matches end up in the result.
+    async fn search_boolean_term(
+        &self,
+        options: &BooleanSearchOptions,
+        pattern: &str,
+        candidates: Option<&BTreeSet<PathBuf>>,
+        positive: bool,
+    ) -> Result<CodeSearchResult, String> {
+        let search_options = CodeSearchOptions {
+            pattern: pattern.to_string(),
+            case_sensitive: options.case_sensitive,
+            fixed_string: options.fixed_string,
+            word_regexp: options.word_regexp,
+            multiline: options.multiline,
+            file_extensions: None,
+            include_globs: options.include_globs.clone(),
+            exclude_globs: options.exclude_dirs.clone(),
+            before_context: options.before_context.filter(|_| positive),
+            after_context: options.after_context.filter(|_| positive),
+            skip: None,
+            take: None,
+            match_content_omit_num: options.match_content_omit_num.filter(|_| positive),
+        };
+
+        let ref_name = options.ref_name.as_deref();
+        match candidates {
+            Some(files) => {
+                self.perform_code_search_in_files(files.iter().cloned(), ref_name, search_options)
+                    .await
+            }
+            None => match ref_name {
+                Some(ref_name) => {
+                    self.perform_code_search_at_ref(ref_name, search_options)
+                        .await
+                }
+                None => self.perform_code_search(search_options).await,
+            },
+        }
+    }
+}
+
+/// Returns the files with at least one matched (non-context) line
+fn matched_files(lines: &[LuminSearchResultLine]) -> BTreeSet<PathBuf> {
+    lines
+        .iter()
+        .filter(|line| !line.is_context)
+        .map(|line| line.file_path.clone())
+        .collect()
+}
+
+/// Whether a positive pattern has been searched and left no candidate files
+fn has_no_candidates(candidates: &Option<BTreeSet<PathBuf>>) -> bool {
+    candidates.as_ref().is_some_and(BTreeSet::is_empty)
+}
+
+/// Describes the boolean expression of a search, e.g. `"a" AND ("b" OR "c") AND NOT "d"`
+fn describe_expression(options: &BooleanSearchOptions) -> String {
+    let mut terms: Vec<String> = options
+        .all_of
+        .iter()
+        .map(|pattern| format!("{:?}", pattern))
+        .collect();
+
+    let alternatives: Vec<String> = options
+        .any_of
+        .iter()
+        .map(|pattern| format!("{:?}", pattern))
+        .collect();
+    match alternatives.len() {
+        0 => {}
+        1 => terms.extend(alternatives),
+        _ => terms.push(format!("({})", alternatives.join(" OR "))),
+    }
+
+    terms.extend(
+        options
+            .none_of
+            .iter()
+            .map(|pattern| format!("NOT {:?}", pattern)),
+    );
+    terms.join(" AND ")
+}
@@ -0,0 +1,295 @@
+//! Changelog generation for a range of commits
+//!
+//! The changelog lists the commits in `from..to` and groups them by their
+//! [Conventional Commits](https://www.conventionalcommits.org/) type (`feat`, `fix`, ...).
+//! Commits whose summary doesn't follow the convention end up in an "other" group.
+//! Issue and pull request numbers referenced as `#123` are extracted from the messages
+//! so that callers can look them up on the hosting provider.
+
+use std::collections::{BTreeSet, HashMap};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::LocalRepository;
+use super::history::{commit_author, commits_in_range};
+
+/// Group key used for commits that don't follow the Conventional Commits format
+pub const OTHER_CHANGES_TYPE: &str = "other";
+
+/// Conventional commit types in the order their groups are listed, with group titles
+///
+/// Summaries with any other type, e.g. `WIP: ...` or `Update: ...`, are listed as other changes.
+const KNOWN_COMMIT_TYPES: &[(&str, &str)] = &[
+    ("feat", "Features"),
+    ("fix", "Bug Fixes"),
+    ("perf", "Performance Improvements"),
+    ("refactor", "Code Refactoring"),
+    ("revert", "Reverts"),
+    ("docs", "Documentation"),
+    ("style", "Styles"),
+    ("test", "Tests"),
+    ("build", "Build System"),
+    ("ci", "Continuous Integration"),
+    ("chore", "Chores"),
+];
+
+/// `type(scope)!: description`
+static CONVENTIONAL_SUMMARY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^(?P<type>[A-Za-z]+)(?:\((?P<scope>[^()]*)\))?(?P<breaking>!)?:\s+(?P<description>.+)$",
+    )
+    .expect("valid conventional commit regex")
+});
+
+/// `#123`, but not `owner/repo#123` or HTML entities like `&#123;`
+static REFERENCE_NUMBER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?:^|[^\w/&])#(?P<number>\d+)\b").expect("valid reference regex"));
+
+/// `BREAKING CHANGE:` or `BREAKING-CHANGE:` footer
+static BREAKING_CHANGE_FOOTER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?m)^BREAKING[ -]CHANGE:").expect("valid breaking change regex"));
+
+/// A commit in a changelog
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangelogEntry {
+    /// Full commit SHA
+    pub commit_id: String,
+    /// Author name
+    pub author_name: String,
+    /// Authoring date in ISO 8601 format
+    pub date: String,
+    /// First line of the commit message
+    pub summary: String,
+    /// Conventional commit scope, e.g. `parser` in `fix(parser): ...`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// Description without the conventional commit prefix (the summary otherwise)
+    pub description: String,
+    /// Whether the commit is marked as a breaking change (`!` or a `BREAKING CHANGE:` footer)
+    pub breaking: bool,
+    /// Issue and pull request numbers referenced in the message, in order of appearance
+    pub references: Vec<u64>,
+}
+
+/// Commits sharing a conventional commit type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangelogGroup {
+    /// The lowercased commit type, or "other" for non-conventional commits
+    pub commit_type: String,
+    /// Human readable group title, e.g. "Bug Fixes"
+    pub title: String,
+    /// Commits of this type, newest first
+    pub commits: Vec<ChangelogEntry>,
+}
+
+/// An issue or pull request referenced by the commits of a changelog
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangelogReference {
+    /// Issue or pull request number
+    pub number: u64,
+    /// Title on the hosting provider, when the reference could be looked up
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// "issue" or "pull_request", when the reference could be looked up
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// State on the hosting provider ("open" or "closed"), when the reference could be looked up
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    /// Browser URL, when the reference could be looked up
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub html_url: Option<String>,
+}
+
+/// Changelog of the commits between two refs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Changelog {
+    /// Exclusive start of the range
+    pub from_ref: String,
+    /// Inclusive end of the range
+    pub to_ref: String,
+    /// Number of commits listed in the changelog
+    pub total_commits: usize,
+    /// Number of commits marked as breaking changes
+    pub breaking_changes: usize,
+    /// Commits grouped by type; known types first, "other" last
+    pub groups: Vec<ChangelogGroup>,
+    /// All referenced issue and pull request numbers, in ascending order
+    pub references: Vec<ChangelogReference>,
+    /// Why the references could not be looked up, when enrichment was requested but failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enrichment_error: Option<String>,
+}
+
+impl LocalRepository {
+    /// Builds a changelog of the commits reachable from `to_ref` but not from `from_ref`
+    ///
+    /// Commits are grouped by conventional commit type and referenced issue and pull
+    /// request numbers are collected. The references are returned without titles;
+    /// looking them up requires the hosting provider and is done by the service layer.
+    ///
+    /// # Parameters
+    ///
+    /// * `from_ref` - Exclusive start of the range, typically the previous release tag
+    /// * `to_ref` - Inclusive end of the range, typically the new release tag
+    /// * `include_merges` - Whether merge commits are listed (their messages often carry PR numbers)
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Changelog, String>` - The grouped commits or an error message
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if either ref cannot be resolved or the history
+    /// cannot be read.
+    pub async fn changelog_between(
+        &self,
+        from_ref: &str,
+        to_ref: &str,
+        include_merges: bool,
+    ) -> Result<Changelog, String> {
+        let repo = self.open_git_repository()?;
+        let commit_ids = commits_in_range(&repo, Some(from_ref), to_ref)?;
+
+        let mut groups: HashMap<String, Vec<ChangelogEntry>> = HashMap::new();
+        let mut references = BTreeSet::new();
+        let mut total_commits = 0;
+        let mut breaking_changes = 0;
+
+        for commit_id in commit_ids {
+            let commit_id = commit_id?;
+            let commit = repo
+                .find_commit(commit_id)
+                .map_err(|e| format!("Failed to read commit {}: {}", commit_id, e))?;
+            if !include_merges && commit.parent_ids().count() > 1 {
+                continue;
+            }
+
+            let message = commit
+                .message_raw()
+                .map_err(|e| format!("Failed to read message of commit {}: {}", commit_id, e))?
+                .to_string();
+            let author = commit_author(&commit)?;
+            let (commit_type, entry) = parse_commit_message(
+                commit_id.to_hex().to_string(),
+                author.name,
+                author.date,
+                &message,
+            );
+
+            total_commits += 1;
+            if entry.breaking {
+                breaking_changes += 1;
+            }
+            references.extend(entry.references.iter().copied());
+            groups.entry(commit_type).or_default().push(entry);
+        }
+
+        let mut groups: Vec<ChangelogGroup> = groups
+            .into_iter()
+            .map(|(commit_type, commits)| ChangelogGroup {
+                title: group_title(&commit_type),
+                commit_type,
+                commits,
+            })
+            .collect();
+        groups.sort_by_key(|group| group_rank(&group.commit_type));
+
+        Ok(Changelog {
+            from_ref: from_ref.to_string(),
+            to_ref: to_ref.to_string(),
+            total_commits,
+            breaking_changes,
+            groups,
+            references: references
+                .into_iter()
+                .map(|number| ChangelogReference {
+                    number,
+                    title: None,
+                    kind: None,
+                    state: None,
+                    html_url: None,
+                })
+                .collect(),
+            enrichment_error: None,
+        })
+    }
+}
+
+/// Parses a commit message into its changelog group key and entry
+fn parse_commit_message(
+    commit_id: String,
+    author_name: String,
+    date: String,
+    message: &str,
+) -> (String, ChangelogEntry) {
+    let summary = message
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+
+    let conventional = CONVENTIONAL_SUMMARY
+        .captures(&summary)
+        .filter(|captures| is_known_type(&captures["type"].to_lowercase()));
+    let (commit_type, scope, description, marked_breaking) = match conventional {
+        Some(captures) => (
+            captures["type"].to_lowercase(),
+            captures
+                .name("scope")
+                .map(|scope| scope.as_str().trim().to_string())
+                .filter(|scope| !scope.is_empty()),
+            captures["description"].trim().to_string(),
+            captures.name("breaking").is_some(),
+        ),
+        None => (OTHER_CHANGES_TYPE.to_string(), None, summary.clone(), false),
+    };
+
+    let mut references = Vec::new();
+    for captures in REFERENCE_NUMBER.captures_iter(message) {
+        if let Ok(number) = captures["number"].parse::<u64>() {
+            if !references.contains(&number) {
+                references.push(number);
+            }
+        }
+    }
+
+    let entry = ChangelogEntry {
+        commit_id,
+        author_name,
+        date,
+        summary,
+        scope,
+        description,
+        breaking: marked_breaking || BREAKING_CHANGE_FOOTER.is_match(message),
+        references,
+    };
+    (commit_type, entry)
+}
+
+/// Whether a lowercased type is one of the conventional commit types
+fn is_known_type(commit_type: &str) -> bool {
+    KNOWN_COMMIT_TYPES
+        .iter()
+        .any(|(known_type, _)| *known_type == commit_type)
+}
+
+/// Sort key placing known types in their listed order, then "other"
+fn group_rank(commit_type: &str) -> usize {
+    KNOWN_COMMIT_TYPES
+        .iter()
+        .position(|(known_type, _)| *known_type == commit_type)
+        .unwrap_or(usize::MAX)
+}
+
+/// Human readable title of a changelog group
+fn group_title(commit_type: &str) -> String {
+    KNOWN_COMMIT_TYPES
+        .iter()
+        .find(|(known_type, _)| *known_type == commit_type)
+        .map(|(_, title)| title.to_string())
+        .unwrap_or_else(|| "Other Changes".to_string())
+}
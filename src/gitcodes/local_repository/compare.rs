@@ -0,0 +1,169 @@
+//! Comparison of two refs: merge-base and ahead/behind counts
+//!
+//! This answers the same question as GitHub's compare view or `git rev-list --left-right
+//! --count base...head`: how far two branches have diverged, and which commits exist on
+//! only one side. Both refs are resolved like everywhere else, so plain branch names of a
+//! clone fall back to their `origin/` remote-tracking branch and other remotes can be
+//! addressed as `<remote>/<branch>` or `refs/remotes/<remote>/<branch>`.
+
+use serde::{Deserialize, Serialize};
+
+use super::LocalRepository;
+use super::history::{commit_author, commits_in_range};
+use super::object_database::peel_ref_to_commit;
+
+/// Default maximum number of commits listed per side of a comparison
+const DEFAULT_MAX_COMMITS: usize = 50;
+
+/// How the head ref relates to the base ref
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompareStatus {
+    /// Both refs point to the same commit
+    Identical,
+    /// The head contains all commits of the base plus some more (fast-forward possible)
+    Ahead,
+    /// The base contains all commits of the head plus some more
+    Behind,
+    /// Both sides have commits the other doesn't have
+    Diverged,
+}
+
+/// A commit listed in a comparison
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitSummary {
+    /// Full commit SHA
+    pub commit_id: String,
+    /// Author name
+    pub author_name: String,
+    /// Authoring date in ISO 8601 format
+    pub date: String,
+    /// First line of the commit message
+    pub summary: String,
+}
+
+/// Result of comparing two refs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefComparison {
+    /// The base ref as given
+    pub base_ref: String,
+    /// The head ref as given
+    pub head_ref: String,
+    /// Commit the base ref resolves to
+    pub base_commit: String,
+    /// Commit the head ref resolves to
+    pub head_commit: String,
+    /// Best common ancestor, or `None` if the histories are unrelated
+    pub merge_base: Option<String>,
+    /// How the head relates to the base
+    pub status: CompareStatus,
+    /// Number of commits on the head that are not on the base
+    pub ahead_by: usize,
+    /// Number of commits on the base that are not on the head
+    pub behind_by: usize,
+    /// Commits only on the head, newest first (limited to `max_commits`)
+    pub ahead_commits: Vec<CommitSummary>,
+    /// Commits only on the base, newest first (limited to `max_commits`)
+    pub behind_commits: Vec<CommitSummary>,
+    /// Whether either commit list was cut off by the limit
+    pub truncated: bool,
+}
+
+impl LocalRepository {
+    /// Compares two refs: merge-base, ahead/behind counts and the commits unique to each side
+    ///
+    /// `ahead_by` counts the commits reachable from `head_ref` but not from `base_ref`
+    /// (what a pull request from head into base would bring in), and `behind_by` the
+    /// reverse. Merge commits are counted like any other commit.
+    ///
+    /// Shallow clones only contain part of the history; call
+    /// [`LocalRepository::ensure_full_history`] first to get exact counts.
+    ///
+    /// # Parameters
+    ///
+    /// * `base_ref` - The ref compared against, e.g. the upstream `main`
+    /// * `head_ref` - The ref being compared, e.g. a fork's branch
+    /// * `max_commits` - Maximum number of commits listed per side (default 50); counts are always exact
+    ///
+    /// # Returns
+    ///
+    /// * `Result<RefComparison, String>` - The comparison or an error message
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if either ref cannot be resolved to a commit or the
+    /// history cannot be read.
+    pub async fn compare_refs(
+        &self,
+        base_ref: &str,
+        head_ref: &str,
+        max_commits: Option<usize>,
+    ) -> Result<RefComparison, String> {
+        let max_commits = max_commits.unwrap_or(DEFAULT_MAX_COMMITS);
+        let repo = self.open_git_repository()?;
+
+        let base_id = peel_ref_to_commit(&repo, base_ref)?.id;
+        let head_id = peel_ref_to_commit(&repo, head_ref)?.id;
+
+        let merge_base = match repo.merge_base(base_id, head_id) {
+            Ok(id) => Some(id.to_hex().to_string()),
+            Err(gix::repository::merge_base::Error::NotFound { .. }) => None,
+            Err(e) => {
+                return Err(format!(
+                    "Failed to find merge-base of '{}' and '{}': {}",
+                    base_ref, head_ref, e
+                ));
+            }
+        };
+
+        let base_hex = base_id.to_hex().to_string();
+        let head_hex = head_id.to_hex().to_string();
+        let ahead: Vec<gix::ObjectId> =
+            commits_in_range(&repo, Some(&base_hex), &head_hex)?.collect::<Result<_, _>>()?;
+        let behind: Vec<gix::ObjectId> =
+            commits_in_range(&repo, Some(&head_hex), &base_hex)?.collect::<Result<_, _>>()?;
+
+        let status = match (ahead.is_empty(), behind.is_empty()) {
+            (true, true) => CompareStatus::Identical,
+            (false, true) => CompareStatus::Ahead,
+            (true, false) => CompareStatus::Behind,
+            (false, false) => CompareStatus::Diverged,
+        };
+
+        let summarize = |ids: &[gix::ObjectId]| -> Result<Vec<CommitSummary>, String> {
+            ids.iter()
+                .take(max_commits)
+                .map(|id| {
+                    let commit = repo
+                        .find_commit(*id)
+                        .map_err(|e| format!("Failed to read commit {}: {}", id, e))?;
+                    let author = commit_author(&commit)?;
+                    let summary = commit
+                        .message()
+                        .map(|message| message.summary().to_string())
+                        .unwrap_or_default();
+                    Ok(CommitSummary {
+                        commit_id: id.to_hex().to_string(),
+                        author_name: author.name,
+                        date: author.date,
+                        summary,
+                    })
+                })
+                .collect()
+        };
+
+        Ok(RefComparison {
+            base_ref: base_ref.to_string(),
+            head_ref: head_ref.to_string(),
+            base_commit: base_hex,
+            head_commit: head_hex,
+            merge_base,
+            status,
+            ahead_by: ahead.len(),
+            behind_by: behind.len(),
+            ahead_commits: summarize(&ahead)?,
+            behind_commits: summarize(&behind)?,
+            truncated: ahead.len() > max_commits || behind.len() > max_commits,
+        })
+    }
+}
@@ -0,0 +1,271 @@
+//! Contributor statistics for a range of commits
+//!
+//! Aggregates the commits of a ref range or time window per author, like `git shortlog -sne`
+//! combined with `git log --numstat`. The result shows who works on a repository and how
+//! recently, which helps to judge how actively a project is maintained.
+
+use std::collections::HashMap;
+
+use gix::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use super::LocalRepository;
+use super::history::{
+    TimeWindow, commits_in_range, count_line_changes, diff_against_parent, read_text_blob,
+};
+
+/// Maximum number of contributors returned when no limit is given
+const DEFAULT_MAX_CONTRIBUTORS: usize = 50;
+
+/// Options for aggregating contributor statistics
+#[derive(Debug, Clone, Default)]
+pub struct ContributorsOptions {
+    /// Exclusive start of the commit range (all history when `None`)
+    pub from_ref: Option<String>,
+    /// Inclusive end of the commit range (default `HEAD`)
+    pub to_ref: Option<String>,
+    /// Only count commits authored at or after this date (ISO 8601 date, or relative like "6 months ago")
+    pub since: Option<String>,
+    /// Only count commits authored before this date
+    pub until: Option<String>,
+    /// Whether to map names and emails through the repository's `.mailmap`
+    pub use_mailmap: bool,
+    /// Whether to count the lines added and removed by each author (diffs every commit)
+    pub include_line_stats: bool,
+    /// Whether merge commits are counted
+    pub include_merges: bool,
+    /// Maximum number of contributors to return (default 50)
+    pub max_contributors: Option<usize>,
+}
+
+/// Commit statistics of one contributor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContributorStats {
+    /// Author name (the most recent spelling used with this email)
+    pub name: String,
+    /// Author email
+    pub email: String,
+    /// Number of commits
+    pub commits: usize,
+    /// Authoring date of the oldest commit in ISO 8601 format
+    pub first_commit_date: String,
+    /// Authoring date of the newest commit in ISO 8601 format
+    pub last_commit_date: String,
+    /// Lines added in text files, when line statistics were requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines_added: Option<u64>,
+    /// Lines removed in text files, when line statistics were requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines_removed: Option<u64>,
+}
+
+/// Contributor statistics of a commit range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContributorsReport {
+    /// The walked range, e.g. "v1.0.0..HEAD"
+    pub range: String,
+    /// Start of the time window in ISO 8601 format, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    /// End of the time window in ISO 8601 format, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+    /// Number of commits counted
+    pub total_commits: usize,
+    /// Number of distinct contributors, including those cut off by the limit
+    pub total_contributors: usize,
+    /// Authoring date of the oldest counted commit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_commit_date: Option<String>,
+    /// Authoring date of the newest counted commit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_commit_date: Option<String>,
+    /// Whether contributors were left out because of the limit
+    pub truncated: bool,
+    /// Contributors ordered by number of commits, most active first
+    pub contributors: Vec<ContributorStats>,
+}
+
+/// Running totals of one contributor while walking the history
+struct ContributorTotals {
+    name: String,
+    email: String,
+    commits: usize,
+    first_seconds: i64,
+    first_date: String,
+    last_seconds: i64,
+    last_date: String,
+    lines_added: u64,
+    lines_removed: u64,
+}
+
+impl LocalRepository {
+    /// Aggregates the commits in `from_ref..to_ref` per author
+    ///
+    /// Authors are identified by their email address (case-insensitive). With
+    /// `use_mailmap`, names and emails are first mapped through the repository's
+    /// `.mailmap`, which merges the identities of people who committed under several
+    /// addresses. Dates refer to the author date, which is also what the time window
+    /// filters on.
+    ///
+    /// Shallow clones only contain part of the history; call
+    /// [`LocalRepository::ensure_full_history`] first to count all of it.
+    ///
+    /// # Parameters
+    ///
+    /// * `options` - The range, time window and statistics to compute
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ContributorsReport, String>` - The per-author statistics or an error message
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if a ref or date cannot be parsed or the history
+    /// cannot be read.
+    pub async fn contributors(
+        &self,
+        options: ContributorsOptions,
+    ) -> Result<ContributorsReport, String> {
+        let window = TimeWindow::parse(options.since.as_deref(), options.until.as_deref())?;
+        let to_ref = options.to_ref.as_deref().unwrap_or("HEAD");
+
+        let repo = self.open_git_repository()?;
+        let mailmap = options.use_mailmap.then(|| repo.open_mailmap());
+        let commit_ids = commits_in_range(&repo, options.from_ref.as_deref(), to_ref)?;
+
+        let mut totals: HashMap<String, ContributorTotals> = HashMap::new();
+        let mut total_commits = 0;
+
+        for commit_id in commit_ids {
+            let commit_id = commit_id?;
+            let commit = repo
+                .find_commit(commit_id)
+                .map_err(|e| format!("Failed to read commit {}: {}", commit_id, e))?;
+            let parent_ids: Vec<ObjectId> = commit.parent_ids().map(|id| id.detach()).collect();
+            if !options.include_merges && parent_ids.len() > 1 {
+                continue;
+            }
+
+            let signature = commit
+                .author()
+                .map_err(|e| format!("Failed to read author of commit {}: {}", commit_id, e))?;
+            let time = signature.time().unwrap_or_default();
+            if !window.contains(time) {
+                continue;
+            }
+            let (name, email) = match &mailmap {
+                Some(mailmap) => {
+                    let resolved = mailmap.resolve(signature);
+                    (resolved.name.to_string(), resolved.email.to_string())
+                }
+                None => (signature.name.to_string(), signature.email.to_string()),
+            };
+
+            // Merge commits have no single diff to attribute, as with `git log --numstat`
+            let (added, removed) = if options.include_line_stats && parent_ids.len() <= 1 {
+                commit_line_changes(&repo, &commit, parent_ids.first().copied())?
+            } else {
+                (0, 0)
+            };
+
+            total_commits += 1;
+            let date = time.format(gix::date::time::format::ISO8601_STRICT);
+            let entry = totals
+                .entry(email.to_lowercase())
+                .or_insert_with(|| ContributorTotals {
+                    name: name.clone(),
+                    email: email.clone(),
+                    commits: 0,
+                    first_seconds: time.seconds,
+                    first_date: date.clone(),
+                    last_seconds: time.seconds,
+                    last_date: date.clone(),
+                    lines_added: 0,
+                    lines_removed: 0,
+                });
+            entry.commits += 1;
+            entry.lines_added += added;
+            entry.lines_removed += removed;
+            if time.seconds < entry.first_seconds {
+                entry.first_seconds = time.seconds;
+                entry.first_date = date.clone();
+            }
+            if time.seconds > entry.last_seconds {
+                entry.last_seconds = time.seconds;
+                entry.last_date = date;
+                entry.name = name;
+                entry.email = email;
+            }
+        }
+
+        let first_commit_date = totals
+            .values()
+            .min_by_key(|totals| totals.first_seconds)
+            .map(|totals| totals.first_date.clone());
+        let last_commit_date = totals
+            .values()
+            .max_by_key(|totals| totals.last_seconds)
+            .map(|totals| totals.last_date.clone());
+
+        let mut contributors: Vec<ContributorTotals> = totals.into_values().collect();
+        contributors.sort_by(|a, b| {
+            b.commits
+                .cmp(&a.commits)
+                .then_with(|| b.last_seconds.cmp(&a.last_seconds))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        let total_contributors = contributors.len();
+        let max_contributors = options.max_contributors.unwrap_or(DEFAULT_MAX_CONTRIBUTORS);
+        contributors.truncate(max_contributors);
+
+        let range = match &options.from_ref {
+            Some(from) => format!("{}..{}", from, to_ref),
+            None => to_ref.to_string(),
+        };
+
+        Ok(ContributorsReport {
+            range,
+            since: window.since(),
+            until: window.until(),
+            total_commits,
+            total_contributors,
+            first_commit_date,
+            last_commit_date,
+            truncated: total_contributors > max_contributors,
+            contributors: contributors
+                .into_iter()
+                .map(|totals| ContributorStats {
+                    name: totals.name,
+                    email: totals.email,
+                    commits: totals.commits,
+                    first_commit_date: totals.first_date,
+                    last_commit_date: totals.last_date,
+                    lines_added: options.include_line_stats.then_some(totals.lines_added),
+                    lines_removed: options.include_line_stats.then_some(totals.lines_removed),
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Sums the lines added and removed in the text files changed by a commit
+fn commit_line_changes(
+    repo: &gix::Repository,
+    commit: &gix::Commit<'_>,
+    parent: Option<ObjectId>,
+) -> Result<(u64, u64), String> {
+    let mut added = 0;
+    let mut removed = 0;
+    for change in diff_against_parent(repo, commit, parent)? {
+        let old = read_text_blob(repo, change.old_id)?;
+        let new = read_text_blob(repo, change.new_id)?;
+        // Binary files have no lines to count
+        if let (Some(old), Some(new)) = (old, new) {
+            let (insertions, removals) = count_line_changes(&old, &new);
+            added += u64::from(insertions);
+            removed += u64::from(removals);
+        }
+    }
+    Ok((added, removed))
+}
@@ -0,0 +1,222 @@
+//! Resolving qualified symbol names to the source of their definitions
+//!
+//! `show_symbol` takes a name as it appears in code, like `tokio::sync::Mutex::lock`,
+//! `Mutex::lock` or `models.User.save`, and returns the full source of the matching
+//! definitions, including the doc comments, attributes and decorators above them.
+//!
+//! A definition's qualified path is made of its module path, derived from the file path
+//! (e.g. `tokio::sync::mutex` for `tokio/src/sync/mutex.rs`), its containers (the impl
+//! type, trait, class or module it's defined in) and its name. A qualified name matches
+//! when its last segment is the definition's name and the segments before it appear in
+//! that order in the definition's path, so re-exported paths like `tokio::sync::Mutex`
+//! still resolve to `tokio::sync::mutex::Mutex`. Definitions whose path ends exactly with
+//! the given segments are returned first.
+
+use std::path::Path;
+
+use lumin::view::FileContents;
+use serde::{Deserialize, Serialize};
+
+use super::object_database::file_contents_from_bytes;
+use super::symbols::{CollectedSymbol, SymbolDefinition, collect_symbols};
+use super::syntax::SourceLanguage;
+use super::{LocalRepository, ViewFileParams};
+
+/// Options for resolving a qualified symbol name
+#[derive(Debug, Clone, Default)]
+pub struct ShowSymbolOptions {
+    /// The name to resolve, with its segments separated by `::` or `.`, e.g. "Mutex::lock"
+    pub qualified_name: String,
+    /// Branch, tag, or commit whose tree is searched instead of the worktree
+    pub ref_name: Option<String>,
+    /// Glob patterns of the files to search, as in code search
+    pub include_globs: Option<Vec<String>>,
+    /// Directories or glob patterns to exclude, as in code search
+    pub exclude_globs: Option<Vec<String>>,
+    /// Maximum number of definitions to return (all when `None`)
+    pub max_results: Option<usize>,
+}
+
+/// The full source of a symbol definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolSource {
+    /// The qualified path of the definition, e.g. "tokio::sync::mutex::Mutex::lock"
+    pub qualified_name: String,
+    /// The definition itself
+    pub symbol: SymbolDefinition,
+    /// First line of the returned source, including the doc comments and attributes above
+    /// the definition (1-based)
+    pub source_start_line: usize,
+    /// The lines from `source_start_line` to the end of the definition
+    pub contents: FileContents,
+}
+
+/// Result of resolving a qualified symbol name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolSourceResult {
+    /// The qualified name that was resolved
+    pub qualified_name: String,
+    /// Number of matching definitions before `max_results` was applied
+    pub total_matches: usize,
+    /// The matching definitions, best match first
+    pub definitions: Vec<SymbolSource>,
+}
+
+impl LocalRepository {
+    /// Resolves a qualified name to the full source of the matching definitions
+    ///
+    /// # Parameters
+    ///
+    /// * `options` - The qualified name, the ref and files to search, and the result limit
+    ///
+    /// # Returns
+    ///
+    /// * `Result<SymbolSourceResult, String>` - The matching definitions or an error message
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is empty, a glob or the ref can't be resolved, or the
+    /// files can't be read.
+    pub async fn show_symbol(
+        &self,
+        options: ShowSymbolOptions,
+    ) -> Result<SymbolSourceResult, String> {
+        if let Err(e) = self.validate() {
+            return Err(format!("Repository validation failed: {}", e));
+        }
+        let mut segments: Vec<&str> = options
+            .qualified_name
+            .split("::")
+            .flat_map(|segment| segment.split('.'))
+            .map(str::trim)
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        // Rust paths relative to the current crate or module say nothing about the file
+        while segments.len() > 1 && matches!(segments[0], "crate" | "self" | "super") {
+            segments.remove(0);
+        }
+        let Some((name, qualifiers)) = segments.split_last() else {
+            return Err("The qualified name must not be empty".to_string());
+        };
+
+        // Exact suffix matches sort before the others
+        let mut matches: Vec<(bool, SymbolSource)> = Vec::new();
+        self.visit_source_files(
+            options.ref_name.as_deref(),
+            options.include_globs.as_ref(),
+            options.exclude_globs.as_ref(),
+            |file| {
+                if !file.content.contains(name) {
+                    return Ok(());
+                }
+                let file_path = file.path.to_string_lossy().replace('\\', "/");
+                let module = module_path(&file_path, file.language);
+                for collected in collect_symbols(file.language, &file.content, &file_path, false)? {
+                    let CollectedSymbol {
+                        leading_start_line,
+                        symbol,
+                        ..
+                    } = collected;
+                    if symbol.name != *name {
+                        continue;
+                    }
+                    let mut path: Vec<&str> = module.iter().map(String::as_str).collect();
+                    if let Some(container) = &symbol.container {
+                        path.extend(container.split("::"));
+                    }
+                    if !is_subsequence(qualifiers, &path) {
+                        continue;
+                    }
+                    let exact = path.ends_with(qualifiers);
+
+                    let separator = match file.language {
+                        SourceLanguage::Rust => "::",
+                        _ => ".",
+                    };
+                    path.push(&symbol.name);
+                    let qualified_name = path.join(separator);
+
+                    let contents = file_contents_from_bytes(
+                        &file.path,
+                        file.content.as_bytes(),
+                        &ViewFileParams {
+                            file_path: file.path.clone(),
+                            max_size: None,
+                            line_from: Some(leading_start_line),
+                            line_to: Some(symbol.end_line),
+                        },
+                    )?;
+                    matches.push((
+                        exact,
+                        SymbolSource {
+                            qualified_name,
+                            symbol,
+                            source_start_line: leading_start_line,
+                            contents,
+                        },
+                    ));
+                }
+                Ok(())
+            },
+        )?;
+
+        matches.sort_by(|(a_exact, a), (b_exact, b)| {
+            b_exact
+                .cmp(a_exact)
+                .then(a.symbol.file_path.cmp(&b.symbol.file_path))
+                .then(a.symbol.start_line.cmp(&b.symbol.start_line))
+        });
+        let total_matches = matches.len();
+        let definitions = matches
+            .into_iter()
+            .map(|(_, definition)| definition)
+            .take(options.max_results.unwrap_or(usize::MAX))
+            .collect();
+
+        Ok(SymbolSourceResult {
+            qualified_name: options.qualified_name,
+            total_matches,
+            definitions,
+        })
+    }
+}
+
+/// The module path of a file as it's written in code
+///
+/// Source directories (`src`) and the file names that stand for their directory (`lib.rs`,
+/// `main.rs`, `mod.rs`, `__init__.py`, `index.ts`) are left out, and Go files are named
+/// after their package directory. Dashes become underscores, as in Rust crate names.
+fn module_path(file_path: &str, language: SourceLanguage) -> Vec<String> {
+    let path = Path::new(file_path);
+    let mut segments: Vec<String> = path
+        .parent()
+        .into_iter()
+        .flat_map(|parent| parent.iter())
+        .filter_map(|component| component.to_str())
+        .filter(|component| *component != "src")
+        .map(|component| component.replace('-', "_"))
+        .collect();
+
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("");
+    let stands_for_directory = match language {
+        SourceLanguage::Rust => matches!(stem, "lib" | "main" | "mod"),
+        SourceLanguage::Python => stem == "__init__",
+        SourceLanguage::TypeScript | SourceLanguage::Tsx => stem == "index",
+        SourceLanguage::Go => true,
+    };
+    if !stands_for_directory {
+        segments.push(stem.to_string());
+    }
+    segments
+}
+
+/// Whether all `needles` appear in `haystack` in the same order
+fn is_subsequence(needles: &[&str], haystack: &[&str]) -> bool {
+    let mut haystack = haystack.iter();
+    needles
+        .iter()
+        .all(|needle| haystack.any(|segment| segment == needle))
+}
@@ -0,0 +1,173 @@
+//! Expanding code search matches to their enclosing items
+//!
+//! `before_context`/`after_context` add a fixed number of lines around each match, which
+//! often cuts off the signature of the function the match is in. In the `enclosing_item`
+//! context mode, each matched line in a Rust, Go, Python, TypeScript or JavaScript file
+//! is shown with the innermost function, method, impl, class or other item containing
+//! it. Matches in the same item share its lines, so overlapping hits are merged.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use lumin::search::SearchResultLine;
+use rmcp::schemars;
+use serde::{Deserialize, Serialize};
+
+use super::symbols::{CollectedSymbol, collect_symbols};
+use super::{CodeSearchResult, LocalRepository};
+
+/// Default maximum number of lines shown for one enclosing item
+const DEFAULT_MAX_CONTEXT_LINES: usize = 50;
+
+/// How the context around code search matches is chosen
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextMode {
+    /// A fixed number of lines before and after each match (`before_context`/`after_context`)
+    #[default]
+    Lines,
+    /// The innermost item (function, method, impl, class, ...) containing each match
+    ///
+    /// Items longer than the line limit are shown as their first line plus the lines
+    /// around the match. Matches outside any item, and in files whose language isn't
+    /// supported, keep the fixed line context.
+    EnclosingItem,
+}
+
+impl LocalRepository {
+    /// Adds the lines of the enclosing item of each match to a code search result
+    ///
+    /// The files are read from the same place they were searched: the tree of `ref_name`
+    /// when given, otherwise the worktree. Files that can't be read or parsed keep their
+    /// lines as they are.
+    ///
+    /// # Parameters
+    ///
+    /// * `result` - The search result to expand
+    /// * `ref_name` - The ref that was searched, if any
+    /// * `max_lines` - Maximum number of lines to show for one item (default 50)
+    ///
+    /// # Returns
+    ///
+    /// * `CodeSearchResult` - The result with the matches and context lines of each file
+    ///   merged and in line order, files in their original order
+    pub(crate) fn expand_to_enclosing_items(
+        &self,
+        mut result: CodeSearchResult,
+        ref_name: Option<&str>,
+        max_lines: Option<usize>,
+    ) -> CodeSearchResult {
+        let max_lines = max_lines.unwrap_or(DEFAULT_MAX_CONTEXT_LINES).max(1);
+
+        // Group the lines by file, keeping the order in which the files were found
+        let mut file_order: Vec<PathBuf> = Vec::new();
+        let mut file_lines: HashMap<PathBuf, Vec<SearchResultLine>> = HashMap::new();
+        for line in std::mem::take(&mut result.matches) {
+            if !file_lines.contains_key(&line.file_path) {
+                file_order.push(line.file_path.clone());
+            }
+            file_lines
+                .entry(line.file_path.clone())
+                .or_default()
+                .push(line);
+        }
+
+        for file_path in file_order {
+            let lines = file_lines.remove(&file_path).unwrap_or_default();
+            match self.expand_file_lines(&file_path, &lines, ref_name, max_lines) {
+                Ok(expanded) => result.matches.extend(expanded),
+                Err(e) => {
+                    tracing::debug!("Keeping line context for {}: {}", file_path.display(), e);
+                    result.matches.extend(lines);
+                }
+            }
+        }
+        result
+    }
+
+    /// Merges the lines of one file with the enclosing items of its matches
+    fn expand_file_lines(
+        &self,
+        file_path: &Path,
+        lines: &[SearchResultLine],
+        ref_name: Option<&str>,
+        max_lines: usize,
+    ) -> Result<Vec<SearchResultLine>, String> {
+        let file = self.read_source_file(file_path, ref_name)?;
+        let path = file.path.to_string_lossy().replace('\\', "/");
+        let symbols = collect_symbols(file.language, &file.content, &path, true)?;
+        let source_lines: Vec<&str> = file.content.lines().collect();
+
+        // Line number to the line as found by the search; matches win over context lines
+        let mut merged: BTreeMap<u64, SearchResultLine> = BTreeMap::new();
+        for line in lines {
+            let keep_existing = merged
+                .get(&line.line_number)
+                .is_some_and(|existing| !existing.is_context);
+            if !keep_existing {
+                merged.insert(line.line_number, line.clone());
+            }
+        }
+
+        let match_lines: Vec<usize> = lines
+            .iter()
+            .filter(|line| !line.is_context)
+            .map(|line| line.line_number as usize)
+            .collect();
+        for match_line in match_lines {
+            let Some(item) = innermost_item(&symbols, match_line) else {
+                continue;
+            };
+            for line_number in item_lines(item, match_line, max_lines) {
+                let Some(content) = source_lines.get(line_number - 1) else {
+                    continue;
+                };
+                merged
+                    .entry(line_number as u64)
+                    .or_insert_with(|| SearchResultLine {
+                        file_path: file_path.to_path_buf(),
+                        line_number: line_number as u64,
+                        line_content: content.to_string(),
+                        content_omitted: false,
+                        is_context: true,
+                    });
+            }
+        }
+
+        Ok(merged.into_values().collect())
+    }
+}
+
+/// The deepest item whose lines contain `line`
+fn innermost_item(symbols: &[CollectedSymbol], line: usize) -> Option<&CollectedSymbol> {
+    symbols
+        .iter()
+        .filter(|item| item.symbol.start_line <= line && line <= item.symbol.end_line)
+        .max_by(|a, b| {
+            a.depth.cmp(&b.depth).then(
+                (b.symbol.end_line - b.symbol.start_line)
+                    .cmp(&(a.symbol.end_line - a.symbol.start_line)),
+            )
+        })
+}
+
+/// The lines of an item to show for a match in it
+///
+/// The whole item when it fits in `max_lines`, otherwise its first line and the lines
+/// centered on the match.
+fn item_lines(item: &CollectedSymbol, match_line: usize, max_lines: usize) -> Vec<usize> {
+    let (start, end) = (item.symbol.start_line, item.symbol.end_line);
+    if end - start < max_lines {
+        return (start..=end).collect();
+    }
+
+    let window = max_lines - 1;
+    let window_start = match_line
+        .saturating_sub(window / 2)
+        .clamp(start + 1, end + 1 - window.max(1));
+    std::iter::once(start)
+        .chain(window_start..window_start + window)
+        .collect()
+}
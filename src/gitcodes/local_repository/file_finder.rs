@@ -0,0 +1,401 @@
+//! Finding files by name
+//!
+//! Listing the whole tree to find one file is wasteful for large repositories. The file
+//! finder matches the paths of the files against a query instead, in one of two ways:
+//!
+//! - A query containing glob characters (`*`, `?`, `[` or `{`) is a glob, like
+//!   `**/Cargo.toml` or `src/**/*_test.go`. A glob without `/` matches the file name at
+//!   any depth, so `*.proto` finds every proto file.
+//! - Anything else is matched fuzzily, like the file pickers of editors: the characters
+//!   of the query must appear in the path in order, but not necessarily next to each
+//!   other, so `octoclient` finds `octocrab_client.rs`. Matches are scored higher when
+//!   the characters are consecutive, start words (after `/`, `_`, `-`, `.` or at a case
+//!   change) and lie in the file name rather than in the directories.
+//!
+//! Both modes are smart-case: the query is matched case-insensitively unless it contains
+//! an uppercase letter.
+
+use std::path::Path;
+
+use lumin::traverse::common::{collect_files_with_excludes, path_matches_any_glob};
+use rmcp::schemars;
+use serde::{Deserialize, Serialize};
+
+use super::LocalRepository;
+use super::object_database::peel_ref_to_commit;
+
+/// Default maximum number of files returned
+const DEFAULT_TAKE: usize = 50;
+
+/// Score of each matched character of a fuzzy query
+const SCORE_MATCH: i64 = 16;
+
+/// Bonus for a character matched at the start of a word
+const BONUS_BOUNDARY: i64 = 8;
+
+/// Bonus for a character matched right after the previously matched one
+const BONUS_CONSECUTIVE: i64 = 6;
+
+/// Penalty for skipping characters between two matched ones
+const PENALTY_GAP_START: i64 = 3;
+
+/// Additional penalty for each further skipped character
+const PENALTY_GAP_EXTENSION: i64 = 1;
+
+/// Bonus for a fuzzy query matching within the file name alone
+const BONUS_FILE_NAME: i64 = 24;
+
+/// How a query is matched against the paths
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FileMatchMode {
+    /// The query is a glob pattern
+    Glob,
+    /// The characters of the query appear in the path in order
+    Fuzzy,
+}
+
+/// Options for finding files by name
+#[derive(Debug, Clone, Default)]
+pub struct FindFilesOptions {
+    /// A glob like `**/Cargo.toml`, or any other text to match fuzzily
+    pub query: String,
+    /// Branch, tag, or commit whose tree is searched instead of the worktree
+    pub ref_name: Option<String>,
+    /// Whether files ignored by git and hidden files are left out (default: true)
+    pub respect_gitignore: Option<bool>,
+    /// Number of files to skip (for pagination)
+    pub skip: Option<usize>,
+    /// Maximum number of files to return (default 50)
+    pub take: Option<usize>,
+}
+
+/// A file whose path matches the query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FoundFile {
+    /// Path of the file relative to the repository root
+    pub path: String,
+    /// Size of the file in bytes
+    pub size: u64,
+    /// The language or file format, detected from the file name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// How well a fuzzy query matches; higher is better (not set for globs)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<i64>,
+}
+
+/// Result of finding files by name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindFilesResult {
+    /// The query as given
+    pub query: String,
+    /// How the query was matched
+    pub mode: FileMatchMode,
+    /// Number of matching files before pagination
+    pub total_matches: usize,
+    /// The matching files, best fuzzy matches first; glob matches by depth and path
+    pub files: Vec<FoundFile>,
+}
+
+impl LocalRepository {
+    /// Finds the files whose paths match a glob or fuzzy query
+    ///
+    /// # Parameters
+    ///
+    /// * `options` - The query, the tree to search and pagination
+    ///
+    /// # Returns
+    ///
+    /// * `Result<FindFilesResult, String>` - The matching files or an error message
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query is empty or an invalid glob, or if the ref can't be
+    /// resolved or the files can't be listed.
+    pub async fn find_files(&self, options: FindFilesOptions) -> Result<FindFilesResult, String> {
+        if let Err(e) = self.validate() {
+            return Err(format!("Repository validation failed: {}", e));
+        }
+        let query = options.query.trim();
+        if query.is_empty() {
+            return Err("The query must not be empty".to_string());
+        }
+        let case_sensitive = query.chars().any(char::is_uppercase);
+        let respect_gitignore = options.respect_gitignore.unwrap_or(true);
+        let files = match &options.ref_name {
+            Some(ref_name) => self.list_files_at_ref(ref_name, respect_gitignore)?,
+            None => self.list_worktree_files(respect_gitignore)?,
+        };
+
+        let mode = if query.contains(['*', '?', '[', '{']) {
+            FileMatchMode::Glob
+        } else {
+            FileMatchMode::Fuzzy
+        };
+        let mut matches: Vec<(Option<i64>, String, u64)> = Vec::new();
+        match mode {
+            FileMatchMode::Glob => {
+                let glob = if query.contains('/') {
+                    query.to_string()
+                } else {
+                    format!("**/{}", query)
+                };
+                let globs = [glob];
+                for (path, size) in files {
+                    if path_matches_any_glob(Path::new(&path), &globs, case_sensitive)
+                        .map_err(|e| format!("Invalid glob: {}", e))?
+                    {
+                        matches.push((None, path, size));
+                    }
+                }
+                matches.sort_by(|a, b| {
+                    a.1.matches('/')
+                        .count()
+                        .cmp(&b.1.matches('/').count())
+                        .then(a.1.cmp(&b.1))
+                });
+            }
+            FileMatchMode::Fuzzy => {
+                let query: Vec<char> = query
+                    .chars()
+                    .filter(|c| !c.is_whitespace())
+                    .map(|c| fold_case(c, case_sensitive))
+                    .collect();
+                for (path, size) in files {
+                    if let Some(score) = fuzzy_path_score(&query, &path, case_sensitive) {
+                        matches.push((Some(score), path, size));
+                    }
+                }
+                matches.sort_by(|a, b| {
+                    b.0.cmp(&a.0)
+                        .then(a.1.len().cmp(&b.1.len()))
+                        .then(a.1.cmp(&b.1))
+                });
+            }
+        }
+
+        let total_matches = matches.len();
+        let files = matches
+            .into_iter()
+            .skip(options.skip.unwrap_or(0))
+            .take(options.take.unwrap_or(DEFAULT_TAKE))
+            .map(|(score, path, size)| FoundFile {
+                language: language_name(&path).map(str::to_string),
+                path,
+                size,
+                score,
+            })
+            .collect();
+
+        Ok(FindFilesResult {
+            query: options.query,
+            mode,
+            total_matches,
+            files,
+        })
+    }
+
+    /// The paths (relative to the repository root) and sizes of the files in the worktree
+    fn list_worktree_files(&self, respect_gitignore: bool) -> Result<Vec<(String, u64)>, String> {
+        let repo_path = self.repository_location.as_path();
+        let files = collect_files_with_excludes(repo_path, respect_gitignore, true, None, None)
+            .map_err(|e| format!("Failed to list files: {}", e))?;
+        Ok(files
+            .into_iter()
+            .filter_map(|file_path| {
+                let relative = file_path.strip_prefix(repo_path).ok()?;
+                // The git directory itself is never part of the result
+                if relative.starts_with(".git") {
+                    return None;
+                }
+                let size = std::fs::metadata(&file_path).ok()?.len();
+                Some((relative.to_string_lossy().replace('\\', "/"), size))
+            })
+            .collect())
+    }
+
+    /// The paths and sizes of the files in the tree of a ref
+    ///
+    /// As in `get_tree_at_ref`, hidden paths are left out unless `respect_gitignore` is
+    /// false. Submodules have no content in this repository and are skipped.
+    fn list_files_at_ref(
+        &self,
+        ref_name: &str,
+        respect_gitignore: bool,
+    ) -> Result<Vec<(String, u64)>, String> {
+        let repo = self.open_git_repository()?;
+        let tree = peel_ref_to_commit(&repo, ref_name)?
+            .tree()
+            .map_err(|e| format!("Failed to read tree of '{}': {}", ref_name, e))?;
+        let records = tree
+            .traverse()
+            .breadthfirst
+            .files()
+            .map_err(|e| format!("Failed to traverse tree at '{}': {}", ref_name, e))?;
+
+        let mut files = Vec::new();
+        for record in records {
+            if !record.mode.is_blob() && !record.mode.is_link() {
+                continue;
+            }
+            let path = record.filepath.to_string();
+            if respect_gitignore && path.split('/').any(|c| c.starts_with('.')) {
+                continue;
+            }
+            let size = repo
+                .find_header(record.oid)
+                .map_err(|e| format!("Failed to read blob for '{}': {}", path, e))?
+                .size();
+            files.push((path, size));
+        }
+        Ok(files)
+    }
+}
+
+/// Lowercases a character unless the match is case-sensitive
+fn fold_case(c: char, case_sensitive: bool) -> char {
+    if case_sensitive {
+        c
+    } else {
+        c.to_lowercase().next().unwrap_or(c)
+    }
+}
+
+/// Scores a path against a fuzzy query, preferring matches within the file name
+fn fuzzy_path_score(query: &[char], path: &str, case_sensitive: bool) -> Option<i64> {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    let name_score = if query.contains(&'/') {
+        None
+    } else {
+        fuzzy_score(query, file_name, case_sensitive).map(|score| score + BONUS_FILE_NAME)
+    };
+    match name_score {
+        Some(score) => Some(score),
+        None => fuzzy_score(query, path, case_sensitive),
+    }
+}
+
+/// Scores the best way of finding the characters of `query` in `text` in order
+///
+/// Each matched character scores [`SCORE_MATCH`] plus a bonus when it starts a word or
+/// directly follows the previous match, and gaps between matches are penalized. Returns
+/// `None` if the query isn't a subsequence of the text.
+fn fuzzy_score(query: &[char], text: &str, case_sensitive: bool) -> Option<i64> {
+    let text: Vec<char> = text.chars().collect();
+    let folded: Vec<char> = text.iter().map(|&c| fold_case(c, case_sensitive)).collect();
+
+    // Most paths don't match at all; rule them out before scoring
+    let mut remaining = folded.iter();
+    if !query.iter().all(|q| remaining.any(|c| c == q)) {
+        return None;
+    }
+
+    let bonus: Vec<i64> = (0..text.len())
+        .map(|j| {
+            let starts_word = match j.checked_sub(1).map(|i| text[i]) {
+                None => true,
+                Some(previous) => {
+                    matches!(previous, '/' | '_' | '-' | '.' | ' ')
+                        || (previous.is_lowercase() && text[j].is_uppercase())
+                        || (!previous.is_numeric() && text[j].is_numeric())
+                }
+            };
+            if starts_word { BONUS_BOUNDARY } else { 0 }
+        })
+        .collect();
+
+    // scores[j]: the best score with the current query character matched at text[j]
+    const NONE: i64 = i64::MIN / 2;
+    let mut previous = vec![NONE; text.len()];
+    for (i, &q) in query.iter().enumerate() {
+        let mut scores = vec![NONE; text.len()];
+        let mut gap_best = NONE;
+        for j in 0..text.len() {
+            if i > 0 && j >= 2 {
+                gap_best =
+                    (gap_best - PENALTY_GAP_EXTENSION).max(previous[j - 2] - PENALTY_GAP_START);
+            }
+            if folded[j] != q {
+                continue;
+            }
+            let before = if i == 0 {
+                0
+            } else {
+                let consecutive = match j.checked_sub(1) {
+                    Some(k) => previous[k] + BONUS_CONSECUTIVE,
+                    None => NONE,
+                };
+                consecutive.max(gap_best)
+            };
+            if before > NONE / 2 {
+                scores[j] = before + SCORE_MATCH + bonus[j];
+            }
+        }
+        previous = scores;
+    }
+    previous.into_iter().max().filter(|&score| score > NONE / 2)
+}
+
+/// The language or file format of a file, detected from its name
+fn language_name(path: &str) -> Option<&'static str> {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    let language = match file_name {
+        "Dockerfile" | "Containerfile" => "Dockerfile",
+        "Makefile" | "GNUmakefile" | "makefile" => "Makefile",
+        "CMakeLists.txt" => "CMake",
+        "Cargo.lock" | "Pipfile" => "TOML",
+        "Gemfile" | "Rakefile" => "Ruby",
+        _ => {
+            let extension = file_name.rsplit_once('.')?.1.to_ascii_lowercase();
+            match extension.as_str() {
+                "rs" => "Rust",
+                "go" => "Go",
+                "py" | "pyi" => "Python",
+                "ts" | "mts" | "cts" => "TypeScript",
+                "tsx" => "TSX",
+                "js" | "mjs" | "cjs" => "JavaScript",
+                "jsx" => "JSX",
+                "java" => "Java",
+                "kt" | "kts" => "Kotlin",
+                "scala" => "Scala",
+                "swift" => "Swift",
+                "c" | "h" => "C",
+                "cc" | "cpp" | "cxx" | "hh" | "hpp" | "hxx" => "C++",
+                "cs" => "C#",
+                "m" | "mm" => "Objective-C",
+                "rb" => "Ruby",
+                "php" => "PHP",
+                "ex" | "exs" => "Elixir",
+                "erl" | "hrl" => "Erlang",
+                "hs" => "Haskell",
+                "ml" | "mli" => "OCaml",
+                "clj" | "cljs" => "Clojure",
+                "lua" => "Lua",
+                "dart" => "Dart",
+                "zig" => "Zig",
+                "sh" | "bash" | "zsh" => "Shell",
+                "ps1" => "PowerShell",
+                "sql" => "SQL",
+                "html" | "htm" => "HTML",
+                "css" => "CSS",
+                "scss" | "sass" => "Sass",
+                "vue" => "Vue",
+                "svelte" => "Svelte",
+                "md" | "markdown" => "Markdown",
+                "rst" => "reStructuredText",
+                "txt" => "Text",
+                "json" => "JSON",
+                "yaml" | "yml" => "YAML",
+                "toml" => "TOML",
+                "xml" => "XML",
+                "proto" => "Protocol Buffers",
+                "graphql" | "gql" => "GraphQL",
+                "tf" => "Terraform",
+                "nix" => "Nix",
+                _ => return None,
+            }
+        }
+    };
+    Some(language)
+}
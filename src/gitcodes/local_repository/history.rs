@@ -0,0 +1,763 @@
+//! Commit history walking and pickaxe search
+//!
+//! This module provides the history primitives shared by the history-oriented tools:
+//! turning a `from..to` range into a list of commits, and making sure a shallow clone
+//! has the history those walks need. On top of that it implements the pickaxe search
+//! behind the `search_history` tool, which mirrors `git log -S` / `git log -G`.
+
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use gix::ObjectId;
+use gix::diff::blob::unified_diff::{ConsumeHunk, ContextSize, NewlineSeparator};
+use gix::diff::blob::{Algorithm, UnifiedDiff, intern::InternedInput, sink::Counter, sources};
+use regex::{Regex, RegexBuilder};
+use rmcp::schemars;
+use serde::{Deserialize, Serialize};
+
+use super::LocalRepository;
+use super::object_database::peel_ref_to_commit;
+
+/// Default number of matching commits returned by a history search
+const DEFAULT_MAX_COMMITS: usize = 20;
+
+/// Default number of context lines around each change in a diff hunk
+const DEFAULT_HUNK_CONTEXT_LINES: u32 = 3;
+
+/// How a history search decides whether a commit matches
+///
+/// These correspond to git's two pickaxe options.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum PickaxeMode {
+    /// Match commits where the number of occurrences of the pattern in a file changed (`git log -S`)
+    ///
+    /// This finds the commits that introduced or removed a string, while ignoring commits
+    /// that merely moved it around within a file.
+    #[default]
+    Occurrences,
+    /// Match commits whose diff has an added or removed line matching the pattern (`git log -G`)
+    DiffMatch,
+}
+
+/// Options for searching the commit history with a pickaxe
+#[derive(Debug, Clone)]
+pub struct HistorySearchOptions {
+    /// The string or regex to look for
+    pub pattern: String,
+    /// Whether to count occurrences (`-S`) or match diff lines (`-G`)
+    pub mode: PickaxeMode,
+    /// Whether `pattern` is a regex. Only applies to [`PickaxeMode::Occurrences`];
+    /// [`PickaxeMode::DiffMatch`] always treats the pattern as a regex, as git does.
+    pub is_regex: bool,
+    /// Whether the pattern is matched case-sensitively
+    pub case_sensitive: bool,
+    /// Limit the search to these paths (directory prefixes or glob patterns, relative to the root)
+    pub paths: Option<Vec<String>>,
+    /// Exclusive start of the range; commits reachable from this ref are not searched
+    pub from_ref: Option<String>,
+    /// Inclusive end of the range (default `HEAD`)
+    pub to_ref: Option<String>,
+    /// Maximum number of matching commits to return (default 20)
+    pub max_commits: Option<usize>,
+    /// Number of context lines around each change in the returned hunks (default 3)
+    pub context_lines: Option<u32>,
+}
+
+/// Result of a pickaxe search over the commit history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistorySearchResult {
+    /// The pattern that was searched for
+    pub pattern: String,
+    /// The pickaxe mode that was used
+    pub mode: PickaxeMode,
+    /// The searched range in `from..to` notation (`to` alone when there is no start)
+    pub range: String,
+    /// Number of commits that were inspected
+    pub scanned_commits: usize,
+    /// Whether the search stopped early because `max_commits` matches were found
+    pub truncated: bool,
+    /// Matching commits, newest first
+    pub commits: Vec<HistoryCommit>,
+}
+
+/// A commit matched by a history search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryCommit {
+    /// Full commit hash
+    pub commit_id: String,
+    /// Author name
+    pub author_name: String,
+    /// Author email
+    pub author_email: String,
+    /// Author date in ISO 8601 format
+    pub date: String,
+    /// First line of the commit message
+    pub summary: String,
+    /// Files of this commit in which the pattern matched
+    pub files: Vec<HistoryFileChange>,
+}
+
+/// A file changed by a matching commit, with the hunks that matched
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryFileChange {
+    /// Path of the file relative to the repository root
+    pub file_path: String,
+    /// Kind of change: "added", "deleted" or "modified"
+    pub change_type: String,
+    /// Occurrences of the pattern before the commit (only for [`PickaxeMode::Occurrences`])
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_occurrences: Option<usize>,
+    /// Occurrences of the pattern after the commit (only for [`PickaxeMode::Occurrences`])
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_occurrences: Option<usize>,
+    /// The diff hunks containing the match
+    pub hunks: Vec<DiffHunk>,
+}
+
+/// A single hunk of a unified diff
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffHunk {
+    /// First line of the hunk in the old file (1-based)
+    pub old_start: u32,
+    /// Number of lines of the hunk in the old file
+    pub old_lines: u32,
+    /// First line of the hunk in the new file (1-based)
+    pub new_start: u32,
+    /// Number of lines of the hunk in the new file
+    pub new_lines: u32,
+    /// Hunk lines prefixed with '+', '-' or ' ', joined with newlines
+    pub content: String,
+}
+
+impl DiffHunk {
+    /// Iterates over the added and removed lines of the hunk, without their prefix
+    pub fn changed_lines(&self) -> impl Iterator<Item = &str> {
+        self.content
+            .lines()
+            .filter(|line| line.starts_with('+') || line.starts_with('-'))
+            .map(|line| &line[1..])
+    }
+}
+
+impl LocalRepository {
+    /// Fetches the missing history if the repository is a shallow clone
+    ///
+    /// Remote repositories are cloned with depth 1, which is enough to read and grep the
+    /// checked-out ref but leaves nothing to walk. History-based operations call this first
+    /// so they see the full history; it is a no-op for complete repositories.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), String>` - Success or an error message if the history couldn't be fetched
+    pub async fn ensure_full_history(&self) -> Result<(), String> {
+        let repo = self.open_git_repository()?;
+        if !repo.is_shallow() {
+            return Ok(());
+        }
+
+        let remote = repo
+            .find_fetch_remote(None)
+            .map_err(|e| format!("Cannot fetch history: no usable remote: {}", e))?;
+        let connection = remote
+            .connect(gix::remote::Direction::Fetch)
+            .map_err(|e| format!("Failed to connect to remote for fetching history: {}", e))?;
+
+        let mut progress = gix::progress::Discard;
+        connection
+            .prepare_fetch(&mut progress, Default::default())
+            .map_err(|e| format!("Failed to prepare history fetch: {}", e))?
+            .with_shallow(gix::remote::fetch::Shallow::undo())
+            .receive(&mut progress, &gix::interrupt::IS_INTERRUPTED)
+            .map_err(|e| format!("Failed to fetch repository history: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Searches the commit history for changes involving a string or regex (pickaxe search)
+    ///
+    /// Walks the commits in `from_ref..to_ref` (newest first) and compares each commit to
+    /// its parent. Merge commits are skipped, as `git log -S` does by default.
+    ///
+    /// - With [`PickaxeMode::Occurrences`] a file matches when the number of occurrences of
+    ///   the pattern differs between the parent and the commit.
+    /// - With [`PickaxeMode::DiffMatch`] a file matches when an added or removed line
+    ///   matches the pattern.
+    ///
+    /// For every matching file, the diff hunks whose changed lines contain the pattern are
+    /// returned.
+    ///
+    /// Shallow clones only contain part of the history; call
+    /// [`LocalRepository::ensure_full_history`] first to search all of it.
+    ///
+    /// # Parameters
+    ///
+    /// * `options` - The pattern, mode, path and range limits of the search
+    ///
+    /// # Returns
+    ///
+    /// * `Result<HistorySearchResult, String>` - The matching commits or an error message
+    pub async fn search_history(
+        &self,
+        options: HistorySearchOptions,
+    ) -> Result<HistorySearchResult, String> {
+        let regex_source = match options.mode {
+            PickaxeMode::Occurrences if !options.is_regex => regex::escape(&options.pattern),
+            _ => options.pattern.clone(),
+        };
+        let regex = RegexBuilder::new(&regex_source)
+            .case_insensitive(!options.case_sensitive)
+            .build()
+            .map_err(|e| format!("Invalid pattern '{}': {}", options.pattern, e))?;
+
+        let paths = options
+            .paths
+            .as_ref()
+            .map(|paths| normalize_path_filters(paths));
+        let max_commits = options.max_commits.unwrap_or(DEFAULT_MAX_COMMITS);
+        let context_lines = options.context_lines.unwrap_or(DEFAULT_HUNK_CONTEXT_LINES);
+        let to_ref = options.to_ref.as_deref().unwrap_or("HEAD");
+
+        let repo = self.open_git_repository()?;
+        let commit_ids = commits_in_range(&repo, options.from_ref.as_deref(), to_ref)?;
+
+        let mut scanned_commits = 0;
+        let mut truncated = false;
+        let mut commits = Vec::new();
+
+        for commit_id in commit_ids {
+            if commits.len() >= max_commits {
+                truncated = true;
+                break;
+            }
+            let commit_id = commit_id?;
+            scanned_commits += 1;
+
+            let commit = repo
+                .find_commit(commit_id)
+                .map_err(|e| format!("Failed to read commit {}: {}", commit_id, e))?;
+
+            let parent_ids: Vec<ObjectId> = commit.parent_ids().map(|id| id.detach()).collect();
+            if parent_ids.len() > 1 {
+                continue;
+            }
+
+            let changes = diff_against_parent(&repo, &commit, parent_ids.first().copied())?;
+            let mut files = Vec::new();
+            for change in changes {
+                if let Some(paths) = &paths {
+                    if !path_matches_filters(&change.file_path, paths) {
+                        continue;
+                    }
+                }
+
+                if let Some(file) =
+                    match_file_change(&repo, &change, &regex, options.mode, context_lines)?
+                {
+                    files.push(file);
+                }
+            }
+
+            if !files.is_empty() {
+                commits.push(history_commit(&commit, files)?);
+            }
+        }
+
+        let range = match &options.from_ref {
+            Some(from) => format!("{}..{}", from, to_ref),
+            None => to_ref.to_string(),
+        };
+
+        Ok(HistorySearchResult {
+            pattern: options.pattern,
+            mode: options.mode,
+            range,
+            scanned_commits,
+            truncated,
+            commits,
+        })
+    }
+}
+
+/// Lists the commits in `from..to`, newest first by commit time
+///
+/// Like `git rev-list from..to`: commits reachable from `to` but not from `from`.
+/// When `from` is `None`, all commits reachable from `to` are returned. The commits are
+/// walked as they are consumed, so callers that stop early don't read the whole range.
+pub(crate) fn commits_in_range<'repo>(
+    repo: &'repo gix::Repository,
+    from: Option<&str>,
+    to: &str,
+) -> Result<CommitRange<'repo>, String> {
+    let mut range = CommitRange {
+        repo,
+        queue: BinaryHeap::new(),
+        queued: HashMap::new(),
+        seen: HashSet::new(),
+        hidden: HashSet::new(),
+        visible_queued: 0,
+    };
+    let to_id = peel_ref_to_commit(repo, to)?.id;
+    range.enqueue(to_id)?;
+    if let Some(from) = from {
+        let from_id = peel_ref_to_commit(repo, from)?.id;
+        range.hide(from_id);
+        range.enqueue(from_id)?;
+    }
+    Ok(range)
+}
+
+/// The commits of a `from..to` range, walked lazily by [`commits_in_range`]
+///
+/// Both ends are walked together, newest commit first, as `git rev-list` does: the
+/// ancestors of a hidden commit are hidden too, and the walk ends as soon as only hidden
+/// commits are left to visit, so the history below the merge-base is never read. Among
+/// commits of the same time the hidden ones are visited first, so a commit is only listed
+/// once every hidden commit that could reach it has been visited. Like git, this relies
+/// on commit times; a commit older than its parent could be listed before the walk
+/// learns that it is hidden.
+pub(crate) struct CommitRange<'repo> {
+    repo: &'repo gix::Repository,
+    /// Commits to visit by commit time and whether they're hidden, newest and hidden on top
+    ///
+    /// A commit hidden while queued is pushed again; the entry popped last is skipped.
+    queue: BinaryHeap<(i64, bool, ObjectId)>,
+    /// The times of the commits waiting in `queue`
+    queued: HashMap<ObjectId, i64>,
+    /// Commits queued at some point, so each is visited once
+    seen: HashSet<ObjectId>,
+    /// Commits known to be reachable from `from`
+    hidden: HashSet<ObjectId>,
+    /// Number of queued commits that aren't hidden
+    visible_queued: usize,
+}
+
+impl CommitRange<'_> {
+    /// Queues a commit that wasn't seen yet
+    ///
+    /// The parents missing from a shallow clone are left out, as git does.
+    fn enqueue(&mut self, id: ObjectId) -> Result<(), String> {
+        if !self.seen.insert(id) {
+            return Ok(());
+        }
+        let commit = match self.repo.find_commit(id) {
+            Ok(commit) => commit,
+            Err(_) if self.repo.is_shallow() => return Ok(()),
+            Err(e) => return Err(format!("Failed to read commit {}: {}", id, e)),
+        };
+        let time = commit
+            .time()
+            .map_err(|e| format!("Failed to read time of commit {}: {}", id, e))?;
+        let hidden = self.hidden.contains(&id);
+        self.queue.push((time.seconds, hidden, id));
+        self.queued.insert(id, time.seconds);
+        if !hidden {
+            self.visible_queued += 1;
+        }
+        Ok(())
+    }
+
+    /// Marks a commit as reachable from `from`
+    fn hide(&mut self, id: ObjectId) {
+        if !self.hidden.insert(id) {
+            return;
+        }
+        if let Some(&time) = self.queued.get(&id) {
+            self.queue.push((time, true, id));
+            self.visible_queued -= 1;
+        }
+    }
+
+    /// Visits the newest queued commit, returning its id if it isn't hidden or already visited
+    fn visit_next(&mut self) -> Result<Option<ObjectId>, String> {
+        let Some((_, _, id)) = self.queue.pop() else {
+            return Ok(None);
+        };
+        if self.queued.remove(&id).is_none() {
+            return Ok(None);
+        }
+        let hidden = self.hidden.contains(&id);
+        if !hidden {
+            self.visible_queued -= 1;
+        }
+
+        let commit = self
+            .repo
+            .find_commit(id)
+            .map_err(|e| format!("Failed to read commit {}: {}", id, e))?;
+        for parent_id in commit.parent_ids() {
+            let parent_id = parent_id.detach();
+            if hidden {
+                self.hide(parent_id);
+            }
+            self.enqueue(parent_id)?;
+        }
+        Ok((!hidden).then_some(id))
+    }
+}
+
+impl Iterator for CommitRange<'_> {
+    type Item = Result<ObjectId, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.visible_queued > 0 {
+            match self.visit_next() {
+                Ok(Some(id)) => return Some(Ok(id)),
+                Ok(None) => {}
+                Err(e) => {
+                    // Nothing sensible follows a commit that can't be read
+                    self.visible_queued = 0;
+                    return Some(Err(e));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// A changed blob between a commit and its parent
+pub(crate) struct BlobChange {
+    /// Path relative to the repository root
+    pub file_path: String,
+    /// "added", "deleted" or "modified"
+    pub change_type: &'static str,
+    /// Blob id before the change, if the file existed
+    pub old_id: Option<ObjectId>,
+    /// Blob id after the change, if the file still exists
+    pub new_id: Option<ObjectId>,
+}
+
+/// Computes the blob changes introduced by `commit` relative to `parent`
+///
+/// Root commits are compared to the empty tree. Non-blob entries (submodules, directories)
+/// are ignored and renames are reported as a deletion plus an addition.
+pub(crate) fn diff_against_parent(
+    repo: &gix::Repository,
+    commit: &gix::Commit<'_>,
+    parent: Option<ObjectId>,
+) -> Result<Vec<BlobChange>, String> {
+    use gix::object::tree::diff::ChangeDetached;
+
+    let tree = commit
+        .tree()
+        .map_err(|e| format!("Failed to read tree of commit {}: {}", commit.id, e))?;
+    let parent_tree = match parent {
+        Some(parent) => repo
+            .find_commit(parent)
+            .map_err(|e| format!("Failed to read commit {}: {}", parent, e))?
+            .tree()
+            .map_err(|e| format!("Failed to read tree of commit {}: {}", parent, e))?,
+        None => repo.empty_tree(),
+    };
+
+    let changes = repo
+        .diff_tree_to_tree(
+            Some(&parent_tree),
+            Some(&tree),
+            gix::diff::Options::default(),
+        )
+        .map_err(|e| format!("Failed to diff commit {}: {}", commit.id, e))?;
+
+    let blob_changes = changes
+        .into_iter()
+        .filter_map(|change| match change {
+            ChangeDetached::Addition {
+                location,
+                entry_mode,
+                id,
+                ..
+            } if entry_mode.is_blob() => Some(BlobChange {
+                file_path: location.to_string(),
+                change_type: "added",
+                old_id: None,
+                new_id: Some(id),
+            }),
+            ChangeDetached::Deletion {
+                location,
+                entry_mode,
+                id,
+                ..
+            } if entry_mode.is_blob() => Some(BlobChange {
+                file_path: location.to_string(),
+                change_type: "deleted",
+                old_id: Some(id),
+                new_id: None,
+            }),
+            ChangeDetached::Modification {
+                location,
+                previous_entry_mode,
+                previous_id,
+                entry_mode,
+                id,
+            } if previous_entry_mode.is_blob() && entry_mode.is_blob() => Some(BlobChange {
+                file_path: location.to_string(),
+                change_type: "modified",
+                old_id: Some(previous_id),
+                new_id: Some(id),
+            }),
+            _ => None,
+        })
+        .collect();
+
+    Ok(blob_changes)
+}
+
+/// Reads a blob as text, returning `None` for binary content
+///
+/// A missing id stands for a file that doesn't exist on that side and reads as empty.
+pub(crate) fn read_text_blob(
+    repo: &gix::Repository,
+    id: Option<ObjectId>,
+) -> Result<Option<String>, String> {
+    let Some(id) = id else {
+        return Ok(Some(String::new()));
+    };
+    let blob = repo
+        .find_blob(id)
+        .map_err(|e| format!("Failed to read blob {}: {}", id, e))?;
+    if blob.data.contains(&0) {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(&blob.data).into_owned()))
+}
+
+/// Computes the unified diff hunks between two texts
+pub(crate) fn diff_hunks(old: &str, new: &str, context_lines: u32) -> Vec<DiffHunk> {
+    let input = InternedInput::new(sources::lines(old), sources::lines(new));
+    gix::diff::blob::diff(
+        Algorithm::Histogram,
+        &input,
+        UnifiedDiff::new(
+            &input,
+            HunkCollector::default(),
+            NewlineSeparator::AfterHeaderAndLine("\n"),
+            ContextSize::symmetrical(context_lines),
+        ),
+    )
+    // The collector never fails, so neither does the diff
+    .unwrap_or_default()
+}
+
+/// Counts the lines added and removed between two texts, like `git diff --numstat`
+pub(crate) fn count_line_changes(old: &str, new: &str) -> (u32, u32) {
+    let input = InternedInput::new(sources::lines(old), sources::lines(new));
+    let counter = gix::diff::blob::diff(Algorithm::Histogram, &input, Counter::default());
+    (counter.insertions, counter.removals)
+}
+
+/// Checks a changed file against the pickaxe and returns its matching hunks
+fn match_file_change(
+    repo: &gix::Repository,
+    change: &BlobChange,
+    regex: &Regex,
+    mode: PickaxeMode,
+    context_lines: u32,
+) -> Result<Option<HistoryFileChange>, String> {
+    let (Some(old), Some(new)) = (
+        read_text_blob(repo, change.old_id)?,
+        read_text_blob(repo, change.new_id)?,
+    ) else {
+        // Binary files are never matched
+        return Ok(None);
+    };
+
+    let (old_occurrences, new_occurrences) = match mode {
+        PickaxeMode::Occurrences => {
+            let old_count = regex.find_iter(&old).count();
+            let new_count = regex.find_iter(&new).count();
+            if old_count == new_count {
+                return Ok(None);
+            }
+            (Some(old_count), Some(new_count))
+        }
+        PickaxeMode::DiffMatch => {
+            // Cheap pre-check: a changed line can only match if one side matches at all
+            if !regex.is_match(&old) && !regex.is_match(&new) {
+                return Ok(None);
+            }
+            (None, None)
+        }
+    };
+
+    let all_hunks = diff_hunks(&old, &new, context_lines);
+    let matching_hunks: Vec<DiffHunk> = all_hunks
+        .iter()
+        .filter(|hunk| hunk.changed_lines().any(|line| regex.is_match(line)))
+        .cloned()
+        .collect();
+
+    let hunks = if !matching_hunks.is_empty() {
+        matching_hunks
+    } else if mode == PickaxeMode::Occurrences {
+        // The occurrence count changed, but no single line holds the match
+        // (e.g. a multi-line pattern), so show the whole diff
+        all_hunks
+    } else {
+        return Ok(None);
+    };
+
+    Ok(Some(HistoryFileChange {
+        file_path: change.file_path.clone(),
+        change_type: change.change_type.to_string(),
+        old_occurrences,
+        new_occurrences,
+        hunks,
+    }))
+}
+
+/// A time window that commits are filtered by, based on their author date
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct TimeWindow {
+    since: Option<gix::date::Time>,
+    until: Option<gix::date::Time>,
+}
+
+impl TimeWindow {
+    /// Parses the bounds of the window; either may be left open
+    ///
+    /// Dates may be given as ISO 8601, a plain `YYYY-MM-DD` date or relative to now
+    /// ("2 weeks ago"), like git's `--since` and `--until`.
+    pub fn parse(since: Option<&str>, until: Option<&str>) -> Result<Self, String> {
+        let now = std::time::SystemTime::now();
+        let parse = |input: &str| {
+            gix::date::parse(input.trim(), Some(now))
+                .map_err(|e| format!("Invalid date '{}': {}", input, e))
+        };
+        Ok(TimeWindow {
+            since: since.map(parse).transpose()?,
+            until: until.map(parse).transpose()?,
+        })
+    }
+
+    /// Whether `time` is at or after the start and before the end of the window
+    pub fn contains(&self, time: gix::date::Time) -> bool {
+        self.since.is_none_or(|since| time.seconds >= since.seconds)
+            && self.until.is_none_or(|until| time.seconds < until.seconds)
+    }
+
+    /// The start of the window in ISO 8601 format
+    pub fn since(&self) -> Option<String> {
+        self.since
+            .map(|time| time.format(gix::date::time::format::ISO8601_STRICT))
+    }
+
+    /// The end of the window in ISO 8601 format
+    pub fn until(&self) -> Option<String> {
+        self.until
+            .map(|time| time.format(gix::date::time::format::ISO8601_STRICT))
+    }
+}
+
+/// Author of a commit with the authoring date in ISO 8601 format
+pub(crate) struct CommitAuthor {
+    pub name: String,
+    pub email: String,
+    pub date: String,
+}
+
+/// Reads the author signature of a commit
+pub(crate) fn commit_author(commit: &gix::Commit<'_>) -> Result<CommitAuthor, String> {
+    let author = commit
+        .author()
+        .map_err(|e| format!("Failed to read author of commit {}: {}", commit.id, e))?;
+    let date = author
+        .time()
+        .map(|time| time.format(gix::date::time::format::ISO8601_STRICT))
+        .unwrap_or_default();
+
+    Ok(CommitAuthor {
+        name: author.name.to_string(),
+        email: author.email.to_string(),
+        date,
+    })
+}
+
+/// Builds the serializable description of a matching commit
+fn history_commit(
+    commit: &gix::Commit<'_>,
+    files: Vec<HistoryFileChange>,
+) -> Result<HistoryCommit, String> {
+    let author = commit_author(commit)?;
+    let summary = commit
+        .message()
+        .map(|message| message.summary().to_string())
+        .unwrap_or_default();
+
+    Ok(HistoryCommit {
+        commit_id: commit.id.to_hex().to_string(),
+        author_name: author.name,
+        author_email: author.email,
+        date: author.date,
+        summary,
+        files,
+    })
+}
+
+/// Normalizes user-supplied path filters to repository-relative form
+fn normalize_path_filters(paths: &[String]) -> Vec<String> {
+    paths
+        .iter()
+        .map(|path| {
+            path.trim_start_matches("./")
+                .trim_start_matches('/')
+                .trim_end_matches('/')
+                .to_string()
+        })
+        .filter(|path| !path.is_empty())
+        .collect()
+}
+
+/// Checks whether a path is selected by any of the path filters
+///
+/// A filter selects a path when it is equal to it, is one of its parent directories,
+/// or is a glob pattern matching it.
+pub(crate) fn path_matches_filters(path: &str, filters: &[String]) -> bool {
+    filters.iter().any(|filter| {
+        path == filter
+            || path
+                .strip_prefix(filter.as_str())
+                .is_some_and(|rest| rest.starts_with('/'))
+            || lumin::traverse::common::path_matches_any_glob(
+                std::path::Path::new(path),
+                std::slice::from_ref(filter),
+                true,
+            )
+            .unwrap_or(false)
+    })
+}
+
+/// Collects the hunks produced by [`UnifiedDiff`] into [`DiffHunk`]s
+#[derive(Default)]
+struct HunkCollector {
+    hunks: Vec<DiffHunk>,
+}
+
+impl ConsumeHunk for HunkCollector {
+    type Out = Vec<DiffHunk>;
+
+    fn consume_hunk(
+        &mut self,
+        before_hunk_start: u32,
+        before_hunk_len: u32,
+        after_hunk_start: u32,
+        after_hunk_len: u32,
+        _header: &str,
+        hunk: &[u8],
+    ) -> std::io::Result<()> {
+        self.hunks.push(DiffHunk {
+            old_start: before_hunk_start,
+            old_lines: before_hunk_len,
+            new_start: after_hunk_start,
+            new_lines: after_hunk_len,
+            content: String::from_utf8_lossy(hunk)
+                .trim_end_matches('\n')
+                .to_string(),
+        });
+        Ok(())
+    }
+
+    fn finish(self) -> Self::Out {
+        self.hunks
+    }
+}
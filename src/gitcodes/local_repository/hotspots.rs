@@ -0,0 +1,382 @@
+//! Change hotspot analysis
+//!
+//! Files that change often (and, among those, large ones) are where most of the work in a
+//! codebase happens and usually the best place to start reading. This module walks a
+//! commit range or time window, counts how often each file changed and how many lines
+//! were touched, and ranks the files and their directories accordingly.
+//!
+//! Only files that still exist at the end of the range are ranked, so deleted and renamed
+//! files don't crowd out the current code. Paths are filtered with the same include and
+//! exclude globs as `perform_code_search`.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use gix::ObjectId;
+use rmcp::schemars;
+use serde::{Deserialize, Serialize};
+
+use super::LocalRepository;
+use super::history::{
+    TimeWindow, commits_in_range, count_line_changes, diff_against_parent, read_text_blob,
+};
+use super::object_database::peel_ref_to_commit;
+use super::object_search::should_search_path;
+
+/// Default number of files and directories returned by a hotspot analysis
+const DEFAULT_MAX_RESULTS: usize = 20;
+
+/// Default number of leading path components directories are grouped by
+const DEFAULT_DIRECTORY_DEPTH: usize = 1;
+
+/// What hotspots are ranked by
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema,
+)]
+pub enum HotspotMetric {
+    /// Number of commits that changed the file
+    #[default]
+    Commits,
+    /// Number of lines added plus removed
+    Churn,
+}
+
+/// Options for a hotspot analysis
+#[derive(Debug, Clone, Default)]
+pub struct HotspotOptions {
+    /// Exclusive start of the commit range (all history when `None`)
+    pub from_ref: Option<String>,
+    /// Inclusive end of the commit range (default `HEAD`); file sizes are read from this ref
+    pub to_ref: Option<String>,
+    /// Only count commits authored at or after this date (ISO 8601 date, or relative like "6 months ago")
+    pub since: Option<String>,
+    /// Only count commits authored before this date
+    pub until: Option<String>,
+    /// Glob patterns of files to include, as in code search
+    pub include_globs: Option<Vec<String>>,
+    /// Directories or glob patterns to exclude, as in code search
+    pub exclude_globs: Option<Vec<String>>,
+    /// What to rank by
+    pub metric: HotspotMetric,
+    /// Whether to multiply the metric by the current number of lines of the file
+    pub weight_by_size: bool,
+    /// Number of leading path components directories are grouped by (default 1)
+    pub directory_depth: Option<usize>,
+    /// Maximum number of files and of directories to return (default 20)
+    pub max_results: Option<usize>,
+}
+
+/// Change statistics of a single file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileHotspot {
+    /// Path relative to the repository root
+    pub path: String,
+    /// Number of commits that changed the file
+    pub commits: usize,
+    /// Number of distinct authors of those commits
+    pub authors: usize,
+    /// Lines added (0 for binary files)
+    pub lines_added: u64,
+    /// Lines removed (0 for binary files)
+    pub lines_removed: u64,
+    /// Authoring date of the most recent change in ISO 8601 format
+    pub last_changed: String,
+    /// Current size in bytes
+    pub size_bytes: u64,
+    /// Current number of lines (0 for binary files)
+    pub lines: u64,
+    /// The ranking score: the metric, multiplied by `lines` when weighting by size
+    pub score: f64,
+}
+
+/// Change statistics of a directory, summed over its changed files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectoryHotspot {
+    /// Directory path relative to the repository root
+    pub path: String,
+    /// Number of changed files in the directory
+    pub files: usize,
+    /// Number of distinct commits that changed the directory
+    pub commits: usize,
+    /// Lines added in the directory
+    pub lines_added: u64,
+    /// Lines removed in the directory
+    pub lines_removed: u64,
+    /// Current number of lines of the changed files
+    pub lines: u64,
+    /// Sum of the scores of the changed files
+    pub score: f64,
+}
+
+/// Result of a hotspot analysis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotspotReport {
+    /// The walked range, e.g. "v1.0.0..HEAD"
+    pub range: String,
+    /// Start of the time window in ISO 8601 format, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    /// End of the time window in ISO 8601 format, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+    /// What the hotspots are ranked by
+    pub metric: HotspotMetric,
+    /// Whether scores are weighted by the current file size
+    pub weight_by_size: bool,
+    /// Number of non-merge commits in the range and time window
+    pub scanned_commits: usize,
+    /// Number of existing files changed in the range, before the limit is applied
+    pub total_files: usize,
+    /// Files with the highest scores first
+    pub files: Vec<FileHotspot>,
+    /// Directories with the highest scores first
+    pub directories: Vec<DirectoryHotspot>,
+}
+
+/// Running totals of a file while walking the history
+#[derive(Default)]
+struct FileTotals {
+    commits: usize,
+    authors: HashSet<String>,
+    lines_added: u64,
+    lines_removed: u64,
+    last_changed_seconds: Option<i64>,
+    last_changed: String,
+}
+
+impl LocalRepository {
+    /// Ranks the files and directories changed in `from_ref..to_ref` by change frequency or churn
+    ///
+    /// Merge commits are skipped. Each file's score is its number of commits or its churn
+    /// (lines added plus removed), optionally multiplied by its current number of lines,
+    /// which favors large files that keep changing. A directory's score is the sum of the
+    /// scores of its changed files.
+    ///
+    /// Shallow clones only contain part of the history; call
+    /// [`LocalRepository::ensure_full_history`] first to analyze all of it.
+    ///
+    /// # Parameters
+    ///
+    /// * `options` - The range, time window, path filters and ranking to use
+    ///
+    /// # Returns
+    ///
+    /// * `Result<HotspotReport, String>` - The ranked files and directories or an error message
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if a ref, date or glob cannot be parsed or the history
+    /// cannot be read.
+    pub async fn hotspots(&self, options: HotspotOptions) -> Result<HotspotReport, String> {
+        let window = TimeWindow::parse(options.since.as_deref(), options.until.as_deref())?;
+        let (include_globs, exclude_globs) = self.normalize_search_globs(
+            options.include_globs.as_ref(),
+            options.exclude_globs.as_ref(),
+        );
+        let to_ref = options.to_ref.as_deref().unwrap_or("HEAD");
+        let max_results = options.max_results.unwrap_or(DEFAULT_MAX_RESULTS);
+        let directory_depth = options
+            .directory_depth
+            .unwrap_or(DEFAULT_DIRECTORY_DEPTH)
+            .max(1);
+
+        let repo = self.open_git_repository()?;
+
+        // The files to rank: everything at the end of the range that passes the filters
+        let tree = peel_ref_to_commit(&repo, to_ref)?
+            .tree()
+            .map_err(|e| format!("Failed to read tree of '{}': {}", to_ref, e))?;
+        let mut current_files: HashMap<String, ObjectId> = HashMap::new();
+        for record in tree
+            .traverse()
+            .breadthfirst
+            .files()
+            .map_err(|e| format!("Failed to traverse tree at '{}': {}", to_ref, e))?
+        {
+            let path = record.filepath.to_string();
+            if record.mode.is_blob()
+                && should_search_path(
+                    Path::new(&path),
+                    include_globs.as_deref(),
+                    exclude_globs.as_deref(),
+                    true,
+                )?
+            {
+                current_files.insert(path, record.oid);
+            }
+        }
+
+        let mut totals: HashMap<String, FileTotals> = HashMap::new();
+        let mut directory_commits: HashMap<String, HashSet<ObjectId>> = HashMap::new();
+        let mut scanned_commits = 0;
+
+        for commit_id in commits_in_range(&repo, options.from_ref.as_deref(), to_ref)? {
+            let commit_id = commit_id?;
+            let commit = repo
+                .find_commit(commit_id)
+                .map_err(|e| format!("Failed to read commit {}: {}", commit_id, e))?;
+            let parent_ids: Vec<ObjectId> = commit.parent_ids().map(|id| id.detach()).collect();
+            if parent_ids.len() > 1 {
+                continue;
+            }
+
+            let author = commit
+                .author()
+                .map_err(|e| format!("Failed to read author of commit {}: {}", commit_id, e))?;
+            let time = author.time().unwrap_or_default();
+            if !window.contains(time) {
+                continue;
+            }
+            scanned_commits += 1;
+
+            for change in diff_against_parent(&repo, &commit, parent_ids.first().copied())? {
+                if !current_files.contains_key(&change.file_path) {
+                    continue;
+                }
+
+                let old = read_text_blob(&repo, change.old_id)?;
+                let new = read_text_blob(&repo, change.new_id)?;
+                let (added, removed) = match (old, new) {
+                    (Some(old), Some(new)) => count_line_changes(&old, &new),
+                    // Binary files have no lines to count
+                    _ => (0, 0),
+                };
+
+                if let Some(directory) = directory_key(&change.file_path, directory_depth) {
+                    directory_commits
+                        .entry(directory)
+                        .or_default()
+                        .insert(commit_id);
+                }
+
+                let file = totals.entry(change.file_path).or_default();
+                file.commits += 1;
+                file.authors.insert(author.email.to_string().to_lowercase());
+                file.lines_added += u64::from(added);
+                file.lines_removed += u64::from(removed);
+                if file
+                    .last_changed_seconds
+                    .is_none_or(|seconds| time.seconds > seconds)
+                {
+                    file.last_changed_seconds = Some(time.seconds);
+                    file.last_changed = time.format(gix::date::time::format::ISO8601_STRICT);
+                }
+            }
+        }
+
+        let mut files = Vec::with_capacity(totals.len());
+        for (path, file) in totals {
+            let blob = repo
+                .find_blob(current_files[&path])
+                .map_err(|e| format!("Failed to read blob for '{}': {}", path, e))?;
+            let lines = count_lines(&blob.data);
+            let metric = match options.metric {
+                HotspotMetric::Commits => file.commits as f64,
+                HotspotMetric::Churn => (file.lines_added + file.lines_removed) as f64,
+            };
+            let score = if options.weight_by_size {
+                metric * lines as f64
+            } else {
+                metric
+            };
+
+            files.push(FileHotspot {
+                path,
+                commits: file.commits,
+                authors: file.authors.len(),
+                lines_added: file.lines_added,
+                lines_removed: file.lines_removed,
+                last_changed: file.last_changed,
+                size_bytes: blob.data.len() as u64,
+                lines,
+                score,
+            });
+        }
+
+        let mut directories: HashMap<String, DirectoryHotspot> = HashMap::new();
+        for file in &files {
+            let Some(key) = directory_key(&file.path, directory_depth) else {
+                continue;
+            };
+            let directory = directories
+                .entry(key.clone())
+                .or_insert_with(|| DirectoryHotspot {
+                    commits: directory_commits.get(&key).map_or(0, HashSet::len),
+                    path: key,
+                    files: 0,
+                    lines_added: 0,
+                    lines_removed: 0,
+                    lines: 0,
+                    score: 0.0,
+                });
+            directory.files += 1;
+            directory.lines_added += file.lines_added;
+            directory.lines_removed += file.lines_removed;
+            directory.lines += file.lines;
+            directory.score += file.score;
+        }
+
+        let total_files = files.len();
+        files.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| {
+                    (b.lines_added + b.lines_removed).cmp(&(a.lines_added + a.lines_removed))
+                })
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        files.truncate(max_results);
+
+        let mut directories: Vec<DirectoryHotspot> = directories.into_values().collect();
+        directories.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| b.commits.cmp(&a.commits))
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        directories.truncate(max_results);
+
+        let range = match &options.from_ref {
+            Some(from) => format!("{}..{}", from, to_ref),
+            None => to_ref.to_string(),
+        };
+
+        Ok(HotspotReport {
+            range,
+            since: window.since(),
+            until: window.until(),
+            metric: options.metric,
+            weight_by_size: options.weight_by_size,
+            scanned_commits,
+            total_files,
+            files,
+            directories,
+        })
+    }
+}
+
+/// The directory a file is grouped under: its first `depth` directory components
+///
+/// Returns `None` for files at the repository root.
+fn directory_key(path: &str, depth: usize) -> Option<String> {
+    let (directory, _) = path.rsplit_once('/')?;
+    Some(
+        directory
+            .split('/')
+            .take(depth)
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
+
+/// Counts the lines of a blob, returning 0 for binary content
+fn count_lines(data: &[u8]) -> u64 {
+    if data.contains(&0) {
+        return 0;
+    }
+    let newlines = data.iter().filter(|&&byte| byte == b'\n').count() as u64;
+    match data.last() {
+        Some(b'\n') | None => newlines,
+        Some(_) => newlines + 1,
+    }
+}
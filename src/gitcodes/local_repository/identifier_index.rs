@@ -0,0 +1,340 @@
+//! Identifier index and reference search
+//!
+//! A full code search reads and matches every file on each query. To look up where a
+//! symbol is used, the source files of a commit are parsed once into an index holding
+//! the symbol definitions (like a ctags file) and the location of every identifier. As
+//! the parser tells identifiers from comments and string literals, mentions of a name in
+//! those aren't counted as references.
+//!
+//! The index is built lazily on the first reference search for a commit and cached as
+//! JSON inside the repository's git directory (`.git/gitcodes/`), so it lives next to
+//! the clone and is removed with it. Only committed content is indexed; without a ref
+//! the commit at `HEAD` is used.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tree_sitter::Tree;
+
+use super::LocalRepository;
+use super::index_cache::{INDEX_DIR_NAME, write_index};
+use super::object_search::should_search_path;
+use super::symbols::{CollectedSymbol, SymbolDefinition, collect_symbols_in_tree};
+use super::syntax::{parse_source, shorten_text};
+
+/// Bumped whenever the layout of [`IdentifierIndex`] changes, so stale caches are rebuilt
+const INDEX_FORMAT_VERSION: u32 = 1;
+
+/// Prefix of the file names of cached identifier indexes
+pub(super) const IDENTIFIER_INDEX_FILE_PREFIX: &str = "identifiers-v";
+
+/// Lines of references are shortened to this many characters
+const MAX_LINE_CONTENT_CHARS: usize = 200;
+
+/// The identifiers of all source files of one commit
+#[derive(Debug, Serialize, Deserialize)]
+struct IdentifierIndex {
+    /// The commit whose tree was indexed
+    commit_id: String,
+    /// Paths of the indexed files; occurrences refer to them by position
+    files: Vec<String>,
+    /// The symbol definitions of all files
+    definitions: Vec<IndexedDefinition>,
+    /// Identifier name to its occurrences as (file, line, column)
+    occurrences: HashMap<String, Vec<(u32, u32, u32)>>,
+}
+
+/// A symbol definition with the position of its name
+#[derive(Debug, Serialize, Deserialize)]
+struct IndexedDefinition {
+    symbol: SymbolDefinition,
+    /// Line and column of the name, to tell the definition apart from references
+    name_position: (usize, usize),
+}
+
+/// Options for a reference search
+#[derive(Debug, Clone, Default)]
+pub struct ReferenceSearchOptions {
+    /// The identifier to find, matched exactly and case-sensitively
+    pub name: String,
+    /// Branch, tag, or commit to search (the commit at `HEAD` when `None`)
+    pub ref_name: Option<String>,
+    /// Whether the names of the definitions are returned as references too
+    pub include_definitions: bool,
+    /// Glob patterns of the files to return references from, as in code search
+    pub include_globs: Option<Vec<String>>,
+    /// Directories or glob patterns to exclude, as in code search
+    pub exclude_globs: Option<Vec<String>>,
+    /// Number of references to skip (for pagination)
+    pub skip: Option<usize>,
+    /// Maximum number of references to return (for pagination)
+    pub take: Option<usize>,
+}
+
+/// A place where an identifier is used
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolReference {
+    /// Path of the file relative to the repository root
+    pub file_path: String,
+    /// Line of the identifier (1-based)
+    pub line: usize,
+    /// Column of the identifier (1-based, in bytes)
+    pub column: usize,
+    /// The trimmed source line, shortened if very long
+    pub line_content: String,
+}
+
+/// Result of a reference search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReferenceSearchResult {
+    /// The identifier that was searched for
+    pub name: String,
+    /// The commit whose files were searched
+    pub commit_id: String,
+    /// Number of source files in the index
+    pub indexed_files: usize,
+    /// The definitions of symbols with this name
+    pub definitions: Vec<SymbolDefinition>,
+    /// Number of references before pagination
+    pub total_references: usize,
+    /// References sorted by file path, line and column
+    pub references: Vec<SymbolReference>,
+}
+
+impl LocalRepository {
+    /// Finds the references to an identifier using the cached identifier index
+    ///
+    /// The index of the commit is built on first use, which parses every Rust, Go,
+    /// Python, TypeScript and JavaScript file of the commit; later searches only read
+    /// the index and the files holding references.
+    ///
+    /// # Parameters
+    ///
+    /// * `options` - The identifier to find, filters and pagination
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ReferenceSearchResult, String>` - The references or an error message
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is empty, the ref or a glob can't be resolved, or the
+    /// files can't be read.
+    pub async fn find_references(
+        &self,
+        options: ReferenceSearchOptions,
+    ) -> Result<ReferenceSearchResult, String> {
+        if let Err(e) = self.validate() {
+            return Err(format!("Repository validation failed: {}", e));
+        }
+        if options.name.is_empty() {
+            return Err("The identifier name must not be empty".to_string());
+        }
+        let commit_id = self.resolve_commit_id(options.ref_name.as_deref().unwrap_or("HEAD"))?;
+        let index = self.load_or_build_identifier_index(&commit_id)?;
+
+        let (include_globs, exclude_globs) = self.normalize_search_globs(
+            options.include_globs.as_ref(),
+            options.exclude_globs.as_ref(),
+        );
+        let mut file_matches = HashMap::new();
+        let mut matches_filters = |file: u32| -> Result<bool, String> {
+            if let Some(&matches) = file_matches.get(&file) {
+                return Ok(matches);
+            }
+            let matches = should_search_path(
+                Path::new(&index.files[file as usize]),
+                include_globs.as_deref(),
+                exclude_globs.as_deref(),
+                true,
+            )?;
+            file_matches.insert(file, matches);
+            Ok(matches)
+        };
+
+        let mut definitions = Vec::new();
+        let mut definition_positions = HashSet::new();
+        for definition in index
+            .definitions
+            .iter()
+            .filter(|definition| definition.symbol.name == options.name)
+        {
+            let (line, column) = definition.name_position;
+            definition_positions.insert((definition.symbol.file_path.as_str(), line, column));
+            definitions.push(definition.symbol.clone());
+        }
+
+        let mut occurrences = Vec::new();
+        for &(file, line, column) in index.occurrences.get(&options.name).into_iter().flatten() {
+            let file_path = index.files[file as usize].as_str();
+            let position = (file_path, line as usize, column as usize);
+            if !options.include_definitions && definition_positions.contains(&position) {
+                continue;
+            }
+            if matches_filters(file)? {
+                occurrences.push(position);
+            }
+        }
+        occurrences.sort();
+        let total_references = occurrences.len();
+
+        // Only the files on the requested page are read to fill in the lines
+        let mut file_lines: HashMap<&str, Vec<String>> = HashMap::new();
+        let mut references = Vec::new();
+        for (file_path, line, column) in occurrences
+            .into_iter()
+            .skip(options.skip.unwrap_or(0))
+            .take(options.take.unwrap_or(usize::MAX))
+        {
+            if !file_lines.contains_key(file_path) {
+                let file = self.read_source_file(Path::new(file_path), Some(&commit_id))?;
+                let lines = file.content.lines().map(str::to_string).collect();
+                file_lines.insert(file_path, lines);
+            }
+            let line_content = file_lines[file_path]
+                .get(line - 1)
+                .map(|content| shorten_text(content.trim(), MAX_LINE_CONTENT_CHARS))
+                .unwrap_or_default();
+            references.push(SymbolReference {
+                file_path: file_path.to_string(),
+                line,
+                column,
+                line_content,
+            });
+        }
+
+        definitions.sort_by(|a, b| {
+            a.file_path
+                .cmp(&b.file_path)
+                .then(a.start_line.cmp(&b.start_line))
+        });
+        Ok(ReferenceSearchResult {
+            name: options.name,
+            commit_id,
+            indexed_files: index.files.len(),
+            definitions,
+            total_references,
+            references,
+        })
+    }
+
+    /// The path of the cached identifier index of a commit
+    fn identifier_index_path(&self, commit_id: &str) -> Result<PathBuf, String> {
+        let repo = self.open_git_repository()?;
+        Ok(repo
+            .git_dir()
+            .join(INDEX_DIR_NAME)
+            .join(identifier_index_file_name(commit_id)))
+    }
+
+    /// Reads the cached identifier index of a commit, building and caching it if needed
+    ///
+    /// A cache that can't be read is rebuilt. Failing to write the cache isn't an error,
+    /// the index is just built again next time.
+    fn load_or_build_identifier_index(&self, commit_id: &str) -> Result<IdentifierIndex, String> {
+        let index_path = self.identifier_index_path(commit_id)?;
+        if let Ok(data) = std::fs::read(&index_path) {
+            match serde_json::from_slice::<IdentifierIndex>(&data) {
+                Ok(index) if index.commit_id == commit_id => return Ok(index),
+                Ok(_) => tracing::warn!("Ignoring mismatched index {}", index_path.display()),
+                Err(e) => {
+                    tracing::warn!("Ignoring unreadable index {}: {}", index_path.display(), e)
+                }
+            }
+        }
+
+        tracing::info!("Building identifier index of commit {}", commit_id);
+        let index = self.build_identifier_index(commit_id)?;
+        if let Err(e) = write_index(&index_path, &index) {
+            tracing::warn!(
+                "Failed to cache identifier index at {}: {}",
+                index_path.display(),
+                e
+            );
+        }
+        Ok(index)
+    }
+
+    /// Parses the source files of a commit into an identifier index
+    fn build_identifier_index(&self, commit_id: &str) -> Result<IdentifierIndex, String> {
+        let mut index = IdentifierIndex {
+            commit_id: commit_id.to_string(),
+            files: Vec::new(),
+            definitions: Vec::new(),
+            occurrences: HashMap::new(),
+        };
+
+        self.visit_source_files(Some(commit_id), None, None, |file| {
+            let file_path = file.path.to_string_lossy().replace('\\', "/");
+            let tree = parse_source(file.language, &file.content)?;
+            let file_number = index.files.len() as u32;
+
+            for CollectedSymbol {
+                name_position,
+                symbol,
+                ..
+            } in collect_symbols_in_tree(&tree, file.language, &file.content, &file_path, false)
+            {
+                index.definitions.push(IndexedDefinition {
+                    symbol,
+                    name_position,
+                });
+            }
+            visit_identifiers(&tree, &file.content, |name, line, column| {
+                index
+                    .occurrences
+                    .entry(name.to_string())
+                    .or_default()
+                    .push((file_number, line, column));
+            });
+
+            index.files.push(file_path);
+            Ok(())
+        })?;
+
+        Ok(index)
+    }
+}
+
+/// Calls `visit` with the text, line and column (1-based) of every identifier in a tree
+///
+/// Identifiers are the leaf nodes whose kind ends in `identifier` (`identifier`,
+/// `type_identifier`, `field_identifier`, `property_identifier`, ...) in all supported
+/// grammars. Comments and string literals have kinds of their own, so text in them is
+/// never visited.
+fn visit_identifiers(tree: &Tree, source: &str, mut visit: impl FnMut(&str, u32, u32)) {
+    let mut cursor = tree.walk();
+    loop {
+        let node = cursor.node();
+        if node.child_count() == 0
+            && node.is_named()
+            && (node.kind().ends_with("identifier")
+                || node.kind() == "shorthand_property_identifier_pattern")
+        {
+            let start = node.start_position();
+            visit(
+                &source[node.byte_range()],
+                start.row as u32 + 1,
+                start.column as u32 + 1,
+            );
+        }
+
+        if cursor.goto_first_child() {
+            continue;
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                return;
+            }
+        }
+    }
+}
+
+/// The file name of the cached identifier index of a commit
+pub(super) fn identifier_index_file_name(commit_id: &str) -> String {
+    format!(
+        "{}{}-{}.json",
+        IDENTIFIER_INDEX_FILE_PREFIX, INDEX_FORMAT_VERSION, commit_id
+    )
+}
@@ -0,0 +1,102 @@
+//! Cached indexes inside the git directory of a repository
+//!
+//! The identifier, chunk and trigram indexes are cached as JSON files in `.git/gitcodes/`,
+//! named after the commit or tree they were built from. They are written atomically, and
+//! once no ref points to their commit or tree anymore they are removed again.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde::Serialize;
+
+use super::LocalRepository;
+use super::identifier_index::{IDENTIFIER_INDEX_FILE_PREFIX, identifier_index_file_name};
+use super::object_database::peel_ref_to_commit;
+use super::ranked_search::{CHUNK_INDEX_FILE_PREFIX, chunk_index_file_name};
+use super::trigram_index::{TRIGRAM_INDEX_FILE_PREFIX, trigram_index_file_name};
+
+/// Name of the directory inside the git directory holding the cached indexes
+pub(super) const INDEX_DIR_NAME: &str = "gitcodes";
+
+impl LocalRepository {
+    /// Removes the cached indexes that neither `HEAD` nor any ref uses anymore
+    ///
+    /// Identifier and chunk indexes are kept for the commits the refs point to, trigram
+    /// indexes for the trees of those commits.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<usize, String>` - The number of removed indexes or an error message
+    pub fn prune_indexes(&self) -> Result<usize, String> {
+        let repo = self.open_git_repository()?;
+        let index_dir = repo.git_dir().join(INDEX_DIR_NAME);
+        let Ok(entries) = std::fs::read_dir(&index_dir) else {
+            return Ok(0);
+        };
+
+        // The file names of the indexes of the commits `HEAD` and the refs point to
+        let references = repo
+            .references()
+            .map_err(|e| format!("Failed to read references: {}", e))?;
+        let mut ref_names = vec!["HEAD".to_string()];
+        ref_names.extend(
+            references
+                .all()
+                .map_err(|e| format!("Failed to read references: {}", e))?
+                .flatten()
+                .map(|reference| reference.name().as_bstr().to_string()),
+        );
+        let mut live_files = HashSet::new();
+        for ref_name in &ref_names {
+            let Ok(commit) = peel_ref_to_commit(&repo, ref_name) else {
+                continue;
+            };
+            let commit_id = commit.id.to_hex().to_string();
+            live_files.insert(identifier_index_file_name(&commit_id));
+            live_files.insert(chunk_index_file_name(&commit_id));
+            if let Ok(tree_id) = commit.tree_id() {
+                live_files.insert(trigram_index_file_name(&tree_id.to_hex().to_string()));
+            }
+        }
+
+        let mut removed = 0;
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let is_index = [
+                IDENTIFIER_INDEX_FILE_PREFIX,
+                CHUNK_INDEX_FILE_PREFIX,
+                TRIGRAM_INDEX_FILE_PREFIX,
+            ]
+            .iter()
+            .any(|prefix| file_name.starts_with(prefix));
+            if is_index
+                && !live_files.contains(&file_name)
+                && std::fs::remove_file(entry.path()).is_ok()
+            {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// Writes an index to a temporary file first and then moves it into place, so readers
+/// never see a partially written index
+pub(super) fn write_index(index_path: &Path, index: &impl Serialize) -> Result<(), String> {
+    let index_dir = index_path
+        .parent()
+        .ok_or_else(|| format!("Invalid index path: {}", index_path.display()))?;
+    std::fs::create_dir_all(index_dir)
+        .map_err(|e| format!("Failed to create {}: {}", index_dir.display(), e))?;
+
+    let data =
+        serde_json::to_vec(index).map_err(|e| format!("Failed to serialize index: {}", e))?;
+    let partial_path =
+        index_path.with_extension(format!("partial-{}", uuid::Uuid::new_v4().simple()));
+    std::fs::write(&partial_path, data)
+        .map_err(|e| format!("Failed to write {}: {}", partial_path.display(), e))?;
+    std::fs::rename(&partial_path, index_path).map_err(|e| {
+        let _ = std::fs::remove_file(&partial_path);
+        format!("Failed to move index into place: {}", e)
+    })
+}
@@ -1,25 +1,90 @@
 use std::path::{Path, PathBuf};
 
 use gix;
-use lumin::search::{self, SearchResultLine as LuminSearchResultLine};
+use lumin::search::SearchResultLine as LuminSearchResultLine;
 
 use crate::gitcodes::repository_manager::providers::GitRemoteRepositoryInfo;
 use crate::gitcodes::repository_manager::RepositoryLocation;
 
 mod search_result;
-pub use search_result::CodeSearchResult;
+pub use search_result::{CodeSearchResult, FacetCount, SearchFacets};
 
 mod reference;
-pub use reference::GitRefObject;
+use reference::describe_ref_target;
+pub use reference::{GitRefObject, ObjectType, RefObject, TagDetails};
 
 mod tree;
 pub use tree::{RepositoryTree, TreeEntry, TreeParams};
 
+mod object_database;
+mod object_search;
+
+mod boolean_search;
+pub use boolean_search::BooleanSearchOptions;
+
+mod history;
+pub use history::{
+    DiffHunk, HistoryCommit, HistoryFileChange, HistorySearchOptions, HistorySearchResult,
+    PickaxeMode,
+};
+
+mod changelog;
+pub use changelog::{
+    Changelog, ChangelogEntry, ChangelogGroup, ChangelogReference, OTHER_CHANGES_TYPE,
+};
+
+mod contributors;
+pub use contributors::{ContributorStats, ContributorsOptions, ContributorsReport};
+
+mod hotspots;
+pub use hotspots::{DirectoryHotspot, FileHotspot, HotspotMetric, HotspotOptions, HotspotReport};
+
+mod compare;
+pub use compare::{CommitSummary, CompareStatus, RefComparison};
+
+mod status;
+pub use status::{FileStatus, StatusOptions, WorkingTreeStatus};
+
+mod syntax;
+pub use syntax::SourceLanguage;
+
+mod symbols;
+pub use symbols::{SymbolDefinition, SymbolKind, SymbolSearchOptions, SymbolSearchResult};
+
+mod outline;
+pub use outline::{FileOutline, OutlineItem};
+
+mod definitions;
+pub use definitions::{ShowSymbolOptions, SymbolSource, SymbolSourceResult};
+
+mod index_cache;
+
+mod identifier_index;
+pub use identifier_index::{ReferenceSearchOptions, ReferenceSearchResult, SymbolReference};
+
+mod enclosing_context;
+pub use enclosing_context::ContextMode;
+
+mod structural_search;
+pub use structural_search::{StructuralMatch, StructuralSearchOptions, StructuralSearchResult};
+
+mod trigram_index;
+
+mod ranked_search;
+pub use ranked_search::{RankedChunk, RankedSearchOptions, RankedSearchResult};
+
+mod file_finder;
+pub use file_finder::{FileMatchMode, FindFilesOptions, FindFilesResult, FoundFile};
+
+mod search_cursor;
+pub use search_cursor::SearchCursor;
+
 use super::providers::{self, ReferenceInfo, RepositoryRefs};
 
 /// Prefix constants for various git reference types
 const ORIGIN_PREFIX: &str = "refs/remotes/origin/";
 const HEAD_PREFIX: &str = "refs/heads/";
+const REMOTE_HEAD_REF: &str = "refs/remotes/origin/HEAD";
 const TAG_PREFIX: &str = "refs/tags/";
 
 /// Prevents directory traversal attacks in paths
@@ -101,16 +166,45 @@ pub struct ViewFileParams {
     pub line_to: Option<usize>,
 }
 
+/// Keeps only the search result lines whose file has one of the given extensions
+///
+/// Returns the lines unchanged when no extensions are given. Files without an
+/// extension are filtered out.
+fn filter_by_file_extensions(
+    lines: Vec<LuminSearchResultLine>,
+    file_extensions: Option<&Vec<String>>,
+) -> Vec<LuminSearchResultLine> {
+    let Some(extensions) = file_extensions else {
+        return lines;
+    };
+
+    lines
+        .into_iter()
+        .filter(|result| {
+            // Only keep files with matching extensions
+            if let Some(ext) = result.file_path.extension() {
+                if let Some(ext_str) = ext.to_str() {
+                    return extensions.iter().any(|e| e == ext_str);
+                }
+            }
+            false // Filter out files with no extension
+        })
+        .collect()
+}
+
 #[derive(Debug, Clone, serde::Deserialize)]
 pub struct LocalRepository {
     repository_location: PathBuf,
 }
 
 /// Options for performing a code search operation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct CodeSearchOptions {
     pub pattern: String,
     pub case_sensitive: bool,
+    pub fixed_string: bool,
+    pub word_regexp: bool,
+    pub multiline: bool,
     pub file_extensions: Option<Vec<String>>,
     pub include_globs: Option<Vec<String>>,
     pub exclude_globs: Option<Vec<String>>,
@@ -121,6 +215,27 @@ pub struct CodeSearchOptions {
     pub match_content_omit_num: Option<usize>,
 }
 
+impl CodeSearchOptions {
+    /// Returns the regex the matcher is built from, with `fixed_string` and `word_regexp` applied
+    ///
+    /// This stays a plain pattern string so the trigram index can read its literals. Whole
+    /// words are matched with half word boundaries like `grep -w` does, so that patterns
+    /// starting or ending with punctuation (e.g. `new(`) still match.
+    pub(crate) fn matcher_pattern(&self) -> String {
+        let pattern = if self.fixed_string {
+            regex::escape(&self.pattern)
+        } else {
+            self.pattern.clone()
+        };
+
+        if self.word_regexp {
+            format!(r"\b{{start-half}}(?:{})\b{{end-half}}", pattern)
+        } else {
+            pattern
+        }
+    }
+}
+
 /// Code search parameters for searching in a repository
 ///
 /// This struct encapsulates all the parameters needed for a code search.
@@ -136,8 +251,8 @@ pub struct CodeSearchOptions {
 ///
 /// # Pattern Syntax
 ///
-/// The pattern parameter is passed directly to the underlying search engine (lumin),
-/// which uses the regex syntax from the `grep` crate for pattern matching.
+/// The pattern parameter is passed directly to the underlying search engine, which uses
+/// the regex syntax from the `grep` crate for pattern matching.
 ///
 /// ## Regex Pattern Examples
 ///
@@ -154,8 +269,8 @@ pub struct CodeSearchOptions {
 ///
 /// ## For Literal Text Search
 ///
-/// If you want to search for a text pattern that contains regex special characters
-/// but want them interpreted literally, you need to escape those characters:
+/// Set `fixed_string` to match the pattern literally, e.g. `Vec<T>::new(`. Without it,
+/// regex special characters have to be escaped by hand:
 ///
 /// ```rust
 /// // Helper function to escape regex special characters for literal searches
@@ -187,19 +302,42 @@ pub struct CodeSearchParams {
     /// Note: Relative paths are not supported and will be rejected.
     pub repository_location: RepositoryLocation,
 
-    /// Optional specific branch or tag name
+    /// Optional specific branch, tag, or commit
+    ///
+    /// When set, the blobs of that commit's tree are searched directly from the
+    /// object database instead of the checked-out worktree.
     pub ref_name: Option<String>,
 
     /// Search pattern (text to find)
     ///
     /// The pattern is passed directly to the underlying search engine and is
-    /// interpreted as a regex pattern. If you want to search for text containing
-    /// regex special characters literally, you must escape them yourself.
+    /// interpreted as a regex pattern, unless `fixed_string` is set.
     pub pattern: String,
 
     /// Whether the search is case-sensitive (default: false)
     pub case_sensitive: bool,
 
+    /// Whether the pattern is a literal string rather than a regex (default: false)
+    ///
+    /// Like `grep -F`, every character of the pattern matches itself, so text such as
+    /// `Vec<T>::new(` or `a.b[0]` can be searched without escaping.
+    pub fixed_string: bool,
+
+    /// Whether the pattern must match whole words only (default: false)
+    ///
+    /// Like `grep -w`, a match must not be preceded or followed by a word character.
+    /// Unlike wrapping the pattern in `\b`, this also works for patterns that start or
+    /// end with punctuation.
+    pub word_regexp: bool,
+
+    /// Whether matches may span multiple lines (default: false)
+    ///
+    /// Like `rg --multiline`, the pattern can match line breaks through `\n` or `\s`,
+    /// e.g. `fn parse\(\s*input: &str,\s*strict`. `^` and `$` still match at line
+    /// boundaries and `.` doesn't match a line break unless the pattern enables it with
+    /// `(?s)`. Every line of a multiline match is reported as a matched line.
+    pub multiline: bool,
+
     /// File extensions to include in search (e.g. ["rs", "md"])
     ///
     /// @deprecated Use include_glob instead which provides more flexibility.
@@ -216,6 +354,22 @@ pub struct CodeSearchParams {
     /// These are converted to glob patterns like "target/**" internally.
     pub exclude_dirs: Option<Vec<String>>,
 
+    /// Whether to search only the files with uncommitted changes (default: false)
+    ///
+    /// Restricts the search to the staged, unstaged and untracked files of the working
+    /// tree, as listed by [`LocalRepository::changed_files`]. Files deleted in the working
+    /// tree are skipped. This cannot be combined with `ref_name`.
+    pub changed_files_only: bool,
+
+    /// Whether to narrow the search with the trigram index (default: false)
+    ///
+    /// The files of the searched tree are indexed by the trigrams they contain on the
+    /// first indexed search and the index is cached in the git directory, so repeated
+    /// searches only read the files that can contain a match. The result is the same as
+    /// without the index. Worktree searches use the index of `HEAD` and also search the
+    /// files with uncommitted changes.
+    pub use_index: bool,
+
     /// Number of lines to include before each match (default: 0)
     ///
     /// When this value is greater than zero, the search results will include
@@ -230,6 +384,19 @@ pub struct CodeSearchParams {
     /// additional context for understanding the matches.
     pub after_context: Option<usize>,
 
+    /// How the context around each match is chosen (default: [`ContextMode::Lines`])
+    ///
+    /// With [`ContextMode::EnclosingItem`], the innermost function, method, impl or class
+    /// containing each match is added to the lines of `before_context`/`after_context`,
+    /// so a match comes with the signature of the code it's in.
+    pub context_mode: Option<ContextMode>,
+
+    /// Maximum number of lines shown for one enclosing item (default: 50)
+    ///
+    /// Longer items are shown as their first line plus the lines around the match. Only
+    /// used with [`ContextMode::EnclosingItem`].
+    pub max_context_lines: Option<usize>,
+
     /// Number of search results to skip (for pagination)
     ///
     /// This parameter is useful for paginating search results by skipping a certain number
@@ -453,6 +620,66 @@ impl LocalRepository {
         Self::new(repo_dir)
     }
 
+    /// Generate a unique directory name for the repository unpacked from a bundle file
+    ///
+    /// The name is derived from the bundle's path, size and modification time, so a
+    /// bundle that is replaced by a newer one is unpacked again. It is a SHA-1 hash, so
+    /// the same bundle maps to the same directory across builds and restarts.
+    ///
+    /// # Parameters
+    ///
+    /// * `bundle_path` - Absolute path of the bundle file
+    /// * `cache_dir` - The repository manager's cache directory the bundle is unpacked into
+    /// * `process_id` - Optional unique process ID from the repository manager
+    ///                  Used as part of the hash calculation to ensure uniqueness
+    pub fn new_local_repository_for_bundle(
+        bundle_path: &Path,
+        cache_dir: &Path,
+        process_id: Option<&str>,
+    ) -> Self {
+        let mut hash_input = bundle_path.to_string_lossy().to_string();
+        if let Ok(metadata) = std::fs::metadata(bundle_path) {
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|modified| modified.as_nanos());
+            hash_input.push_str(&format!("\0{}\0{:?}", metadata.len(), modified));
+        }
+        if let Some(process_id) = process_id {
+            hash_input.push_str(&format!("\0{}", process_id));
+        }
+
+        let mut hasher = gix::hash::hasher(gix::hash::Kind::Sha1);
+        hasher.update(hash_input.as_bytes());
+        // A detected collision attack still reports the digest, which is all a name needs
+        let digest = hasher
+            .try_finalize()
+            .unwrap_or_else(|gix::hash::hasher::Error::CollisionAttack { digest }| digest);
+
+        let name: String = bundle_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy())
+            .unwrap_or_default()
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let dir_name = format!(
+            "{}_bundle_{}_{}",
+            Self::REPOSITORY_DIR_PREFIX,
+            name,
+            &digest.to_hex().to_string()[..12]
+        );
+
+        Self::new(cache_dir.join(dir_name))
+    }
+
     /// Get the repository directory path
     pub fn get_repository_dir(&self) -> &PathBuf {
         &self.repository_location
@@ -518,32 +745,43 @@ impl LocalRepository {
 
         // Process the references into structured objects
         let mut reference_infos = Vec::new();
+        let mut remote_head_target = None;
 
         for r in all_refs.flatten() {
             // Get reference name - fully qualified name (e.g., refs/heads/main)
             let ref_name = r.name().as_bstr().to_string();
 
-            let target = r.target();
-            if let gix::refs::TargetRef::Object(oid) = target {
-                let sha = oid.to_hex().to_string();
-
-                // Extract branch name by stripping known prefixes
-                // Try each prefix in sequence and use the first successful strip
-                let name = ref_name
-                    .strip_prefix(ORIGIN_PREFIX)
-                    .or_else(|| ref_name.strip_prefix(HEAD_PREFIX))
-                    .or_else(|| ref_name.strip_prefix(TAG_PREFIX))
-                    .unwrap_or(&ref_name)
-                    .to_string();
-
-                reference_infos.push(ReferenceInfo {
-                    // Extract short name from full ref path
-                    name,
-                    full_ref: ref_name.clone(),
-                    commit_id: sha,
-                });
+            match r.target() {
+                gix::refs::TargetRef::Object(oid) => {
+                    // Extract branch name by stripping known prefixes
+                    // Try each prefix in sequence and use the first successful strip
+                    let name = ref_name
+                        .strip_prefix(ORIGIN_PREFIX)
+                        .or_else(|| ref_name.strip_prefix(HEAD_PREFIX))
+                        .or_else(|| ref_name.strip_prefix(TAG_PREFIX))
+                        .unwrap_or(&ref_name)
+                        .to_string();
+
+                    let mut reference = ReferenceInfo::new(
+                        // Extract short name from full ref path
+                        name,
+                        ref_name.clone(),
+                        oid.to_hex().to_string(),
+                    );
+                    // Peel annotated tags and add dates; keep the bare reference if the
+                    // objects can't be read (e.g. missing in a shallow clone)
+                    if let Err(e) = describe_ref_target(&repo, oid.to_owned(), &mut reference) {
+                        tracing::debug!("Failed to read target of {}: {}", ref_name, e);
+                    }
+                    reference_infos.push(reference);
+                }
+                gix::refs::TargetRef::Symbolic(target) => {
+                    // The remote HEAD names the default branch; other symbolic refs are aliases
+                    if ref_name == REMOTE_HEAD_REF {
+                        remote_head_target = Some(target.as_bstr().to_string());
+                    }
+                }
             }
-            // Skip references that couldn't be read
         }
 
         // Transform into our domain model structure
@@ -564,8 +802,28 @@ impl LocalRepository {
         branches.dedup_by(|l, r| l.name == r.name);
         tags.dedup_by(|l, r| l.name == r.name);
 
-        // Convert to JSON string
-        Ok(RepositoryRefs { branches, tags })
+        // Without a remote HEAD (e.g. a repository that was never cloned), fall back to
+        // the branch checked out locally
+        let default_branch = remote_head_target
+            .or_else(|| {
+                repo.head_name()
+                    .ok()
+                    .flatten()
+                    .map(|name| name.as_bstr().to_string())
+            })
+            .map(|target| {
+                target
+                    .strip_prefix(ORIGIN_PREFIX)
+                    .or_else(|| target.strip_prefix(HEAD_PREFIX))
+                    .unwrap_or(&target)
+                    .to_string()
+            });
+
+        Ok(RepositoryRefs {
+            branches,
+            tags,
+            default_branch,
+        })
     }
 
     /// Update a local repository by pulling from remote
@@ -737,6 +995,10 @@ impl LocalRepository {
 
         // If we successfully fetched at least one remote, consider it a success
         if successful_fetches > 0 {
+            // Indexes of commits the fetch moved the refs away from are not used anymore
+            if let Err(e) = self.prune_indexes() {
+                tracing::warn!("Failed to prune cached indexes: {}", e);
+            }
             Ok(())
         } else if let Some(error) = last_error {
             // If all fetches failed, return the last error message
@@ -869,6 +1131,10 @@ impl LocalRepository {
     /// This function handles searching code within a repository. It takes
     /// a CodeSearchParams struct with all the necessary search parameters.
     ///
+    /// When `params.ref_name` is set, the tree of that commit is searched from the
+    /// object database (see [`LocalRepository::perform_code_search_at_ref`]), so any
+    /// number of refs can be searched from one clone without checking them out.
+    ///
     /// # Parameters
     ///
     /// * `params` - Parameters for the code search including repository and pattern
@@ -894,8 +1160,15 @@ impl LocalRepository {
     ///         file_extensions: Some(vec!["rs".to_string()]),
     ///         include_globs: None,
     ///         exclude_dirs: Some(vec!["target".to_string()]),
+    ///         changed_files_only: false,
+    ///         use_index: false,
+    ///         fixed_string: false,
+    ///         word_regexp: false,
+    ///         multiline: false,
     ///         before_context: None,
     ///         after_context: None,
+    ///         context_mode: None,
+    ///         max_context_lines: None,
     ///         skip: None,
     ///         take: None,
     ///         match_content_omit_num: Some(150),
@@ -904,18 +1177,24 @@ impl LocalRepository {
     ///     // Create a repository instance (mock for example)
     ///     let repo = gitcodes_mcp::gitcodes::local_repository::LocalRepository::new(std::path::PathBuf::from("/tmp/example"));
     ///
-    ///     // Using literal text search (with escaped regex)
-    ///     let literal_pattern = "file.txt".replace(".", "\\."); // Escape the period
+    ///     // Using literal text search (no escaping needed)
     ///     let params2 = CodeSearchParams {
     ///         repository_location: RepositoryLocation::from_str("https://github.com/user/repo").unwrap(),
     ///         ref_name: None,
-    ///         pattern: literal_pattern,
+    ///         pattern: "file.txt".to_string(),
     ///         case_sensitive: true,
     ///         file_extensions: None,
     ///         include_globs: None,
     ///         exclude_dirs: None,
+    ///         changed_files_only: false,
+    ///         use_index: false,
+    ///         fixed_string: true,
+    ///         word_regexp: false,
+    ///         multiline: false,
     ///         before_context: None,
     ///         after_context: None,
+    ///         context_mode: None,
+    ///         max_context_lines: None,
     ///         skip: None,
     ///         take: None,
     ///         match_content_omit_num: Some(150),
@@ -932,15 +1211,8 @@ impl LocalRepository {
             return Err(format!("Repository validation failed: {}", e));
         }
 
-        // If a specific ref is requested, try to update the repository
-        if let Some(ref_name) = &params.ref_name {
-            // Temporarily disabled due to ongoing refactoring
-            // Just log the request rather than attempting update
-            eprintln!("Note: Reference '{}' was requested, but repository updates are temporarily disabled", ref_name);
-        }
-
-        // Get the pattern - the caller is responsible for properly escaping regex special characters
-        // if they want to perform a literal text search
+        // Get the pattern - unless fixed_string is set, the caller is responsible for properly
+        // escaping regex special characters if they want to perform a literal text search
         let pattern = &params.pattern;
 
         // If exclude_dirs contains paths, verify they don't have directory traversal patterns
@@ -975,6 +1247,9 @@ impl LocalRepository {
         let search_options = CodeSearchOptions {
             pattern: pattern.to_string(),
             case_sensitive: params.case_sensitive,
+            fixed_string: params.fixed_string,
+            word_regexp: params.word_regexp,
+            multiline: params.multiline,
             file_extensions: params.file_extensions.clone(), // Keep file_extensions for backward compatibility
             include_globs,                                   // Pass file_extensions as include_glob
             exclude_globs: params.exclude_dirs,
@@ -985,7 +1260,38 @@ impl LocalRepository {
             match_content_omit_num: params.match_content_omit_num,
         };
 
-        self.perform_code_search(search_options).await
+        // A specific ref is searched straight from the object database, so the worktree
+        // doesn't need to be updated or checked out
+        let result = match &params.ref_name {
+            Some(_) if params.changed_files_only => Err(
+                "changed_files_only searches the working tree and cannot be combined with ref_name"
+                    .to_string(),
+            ),
+            Some(ref_name) if params.use_index => {
+                self.perform_indexed_code_search(Some(ref_name), search_options)
+                    .await
+            }
+            Some(ref_name) => {
+                self.perform_code_search_at_ref(ref_name, search_options)
+                    .await
+            }
+            None if params.changed_files_only => {
+                self.perform_changed_files_search(search_options).await
+            }
+            None if params.use_index => {
+                self.perform_indexed_code_search(None, search_options).await
+            }
+            None => self.perform_code_search(search_options).await,
+        }?;
+
+        match params.context_mode.unwrap_or_default() {
+            ContextMode::Lines => Ok(result),
+            ContextMode::EnclosingItem => Ok(self.expand_to_enclosing_items(
+                result,
+                params.ref_name.as_deref(),
+                params.max_context_lines,
+            )),
+        }
     }
 
     /// Get the directory tree structure of the repository
@@ -1194,13 +1500,17 @@ impl LocalRepository {
 
     /// Performs a code search on a prepared repository
     ///
-    /// This function executes the search using the lumin search library
-    /// and processes the results.
+    /// The files lumin would walk are matched with the `grep` crate, the same search the
+    /// trigram index narrows down and the search of a ref runs over the object database,
+    /// so all of them give the same lines for the same files.
     ///
     /// # Parameters
     ///
-    /// * `pattern` - The search pattern to look for (regex pattern passed directly to lumin)
+    /// * `pattern` - The search pattern to look for (regex pattern passed to lumin, see `fixed_string`)
     /// * `case_sensitive` - Whether the search should be case-sensitive (default: false)
+    /// * `fixed_string` - Whether the pattern is matched literally instead of as a regex
+    /// * `word_regexp` - Whether the pattern only matches whole words
+    /// * `multiline` - Whether matches may span multiple lines
     /// * `file_extensions` - DEPRECATED: Use `include_globs` instead. Optional array of file extensions to include (e.g. ["js", "ts"])
     /// * `include_globs` - Optional glob patterns to include in search (e.g. ["**/*.js", "**/*.ts"])
     /// * `exclude_globs` - Optional directories to exclude from search (converted to glob patterns internally)
@@ -1216,7 +1526,7 @@ impl LocalRepository {
     /// * `Result<CodeSearchResult, String>` - Structured search results or an error message
     ///
     /// This method searches for a pattern in the repository's code, with various filtering options.
-    /// It uses lumin's search functionality internally, with additional filtering capabilities.
+    /// It walks the worktree with lumin, with additional filtering capabilities.
     ///
     /// # Arguments
     ///
@@ -1255,8 +1565,27 @@ impl LocalRepository {
         &self,
         options: CodeSearchOptions,
     ) -> Result<CodeSearchResult, String> {
+        // The same walk and matcher as the searches narrowed by the trigram index, so
+        // `use_index` never changes the result
+        self.search_worktree_files(None, options)
+    }
+
+    /// Normalizes the include and exclude globs of a search into patterns relative to the repository root
+    ///
+    /// lumin 0.1.16 matches both include_glob and exclude_glob against paths relative to the
+    /// search directory. Simple directory names in the exclude list are converted to
+    /// `**/dirname/**` patterns, and leading slashes or the repository path are stripped.
+    ///
+    /// # Returns
+    ///
+    /// * `(Option<Vec<String>>, Option<Vec<String>>)` - The normalized include and exclude globs
+    fn normalize_search_globs(
+        &self,
+        include_globs: Option<&Vec<String>>,
+        exclude_globs: Option<&Vec<String>>,
+    ) -> (Option<Vec<String>>, Option<Vec<String>>) {
         // For include_globs, lumin 0.1.16 expects relative paths (relative to the search directory)
-        let normalized_include_globs = options.include_globs.as_ref().map(|globs| {
+        let normalized_include_globs = include_globs.map(|globs| {
             globs
                 .iter()
                 .map(|glob| {
@@ -1268,7 +1597,7 @@ impl LocalRepository {
 
         // For exclude_globs, lumin expects relative paths (relative to the search directory)
         // Convert directory names to glob patterns and ensure paths are relative
-        let normalized_exclude_globs = options.exclude_globs.as_ref().map(|dirs| {
+        let normalized_exclude_globs = exclude_globs.map(|dirs| {
             dirs.iter()
                 .map(|dir| {
                     // Check if this looks like a simple directory name or a glob pattern
@@ -1295,73 +1624,7 @@ impl LocalRepository {
                 .collect::<Vec<String>>()
         });
 
-        // Configure search options for lumin 0.1.16
-        // Note: in lumin 0.1.16, both include_glob and exclude_glob expect relative paths
-        let search_options = search::SearchOptions {
-            case_sensitive: options.case_sensitive,
-            respect_gitignore: true,
-            // Use normalized exclude patterns that handle both directory names and glob patterns
-            exclude_glob: normalized_exclude_globs,
-            match_content_omit_num: options.match_content_omit_num,
-            before_context: options.before_context.unwrap_or(0), // Default to 0 if None
-            after_context: options.after_context.unwrap_or(0),   // Default to 0 if None
-            depth: None,                                         // Use default depth (no limit)
-            include_glob: normalized_include_globs, // Pattern for filtering files by glob
-            skip: options.skip,                     // For pagination
-            take: options.take,                     // For pagination
-            omit_path_prefix: Some(self.repository_location.clone()), // Omit repository path prefix from results
-        };
-
-        // Get repository path
-        let repo_path = self.repository_location.as_path();
-
-        // Execute the search directly with the provided pattern
-        // The caller is responsible for properly formatting the regex pattern
-        let search_result = search::search_files(&options.pattern, repo_path, &search_options)
-            .map_err(|e| format!("Code search failed: {}", e))?;
-
-        // Process the search results
-        // Even though lumin 0.1.13 has good glob support, we still apply extension filtering
-        // for maximum reliability and backward compatibility
-        let all_results = if let Some(extensions) = &options.file_extensions {
-            // Extract matches from all the matched files, filtering by extension
-            let filtered_results: Vec<LuminSearchResultLine> = search_result
-                .lines
-                .into_iter()
-                .filter(|result| {
-                    // Only keep files with matching extensions
-                    if let Some(ext) = result.file_path.extension() {
-                        if let Some(ext_str) = ext.to_str() {
-                            return extensions.iter().any(|e| e == ext_str);
-                        }
-                    }
-                    false // Filter out files with no extension
-                })
-                .collect();
-
-            filtered_results
-        } else {
-            // No extension filtering needed
-            search_result.lines
-        };
-
-        // Directory exclusion is now handled via SearchOptions exclude_glob
-
-        // Create a CodeSearchResult - make sure we preserve the original pattern
-        // from the request, not a potentially modified version from lumin
-        let params = crate::gitcodes::local_repository::search_result::CodeSearchParams {
-            total_match_line_number: search_result.total_number,
-            search_result_lines: all_results,
-            pattern: options.pattern.clone(), // Use the original pattern string
-            repository: repo_path.to_path_buf(),
-            case_sensitive: options.case_sensitive,
-            file_extensions: options.file_extensions,
-            include_globs: options.include_globs,
-            exclude_globs: options.exclude_globs,
-            before_context: options.before_context,
-            after_context: options.after_context,
-        };
-        Ok(CodeSearchResult::new(params))
+        (normalized_include_globs, normalized_exclude_globs)
     }
 
     /// Generate a 12-character hash value from repository information
@@ -1409,6 +1672,9 @@ mod tests {
         let options = CodeSearchOptions {
             pattern: "test".to_string(),
             case_sensitive: false,
+            fixed_string: false,
+            word_regexp: false,
+            multiline: false,
             file_extensions: None,
             include_globs: Some(vec![
                 "src/**/*.rs".to_string(),
@@ -1455,6 +1721,9 @@ mod tests {
         let options = CodeSearchOptions {
             pattern: "test".to_string(),
             case_sensitive: false,
+            fixed_string: false,
+            word_regexp: false,
+            multiline: false,
             file_extensions: None,
             include_globs: None,
             exclude_globs: Some(vec![
@@ -0,0 +1,620 @@
+//! Readers that serve file contents and directory trees straight from the git object database
+//!
+//! `view_file_contents` and `get_tree_with_params` read the checked-out worktree through lumin,
+//! so looking at another ref means another checkout. The readers in this module instead resolve
+//! a `ref:path` pair via gix and read the blob or tree objects directly. This lets many refs be
+//! inspected from a single clone (including bare clones) without touching the worktree.
+//!
+//! The results use the same shapes as the worktree readers (`lumin::view::FileContents` and
+//! [`RepositoryTree`]) so callers can convert them with the existing compact response types.
+
+use std::collections::BTreeMap;
+use std::num::NonZeroU32;
+use std::path::Path;
+
+use gix::bstr::ByteSlice;
+use lumin::view::{
+    BinaryMetadata, FileContents, ImageMetadata, LineContent, TextContent, TextMetadata,
+};
+
+use crate::gitcodes::repository_manager::version_tags::LatestAlias;
+
+use super::{
+    LocalRepository, RepositoryTree, TreeEntry, TreeParams, ViewFileParams,
+    prevent_directory_traversal,
+};
+
+/// Default maximum blob size read by [`LocalRepository::view_file_contents_at_ref`]
+///
+/// Mirrors the 10MB default of `lumin::view::ViewOptions`.
+const DEFAULT_MAX_BLOB_SIZE: usize = 10 * 1024 * 1024;
+
+/// Remote name tried as a fallback when a short ref name doesn't resolve on its own
+///
+/// Clones only create a local branch for the checked-out ref, so other branches are
+/// available as `refs/remotes/origin/<name>`.
+const DEFAULT_REMOTE_NAME: &str = "origin";
+
+impl LocalRepository {
+    /// Opens the underlying git repository with gix
+    ///
+    /// # Returns
+    ///
+    /// * `Result<gix::Repository, String>` - The opened repository or an error message
+    pub fn open_git_repository(&self) -> Result<gix::Repository, String> {
+        gix::open(&self.repository_location).map_err(|e| {
+            format!(
+                "Failed to open repository at {}: {}",
+                self.repository_location.display(),
+                e
+            )
+        })
+    }
+
+    /// Resolves a branch, tag or commit name to the full SHA of the commit it points to
+    ///
+    /// Short names are resolved with git's usual rules (`refs/heads/`, `refs/tags/`, ...).
+    /// If that fails, `origin/<ref_name>` is tried so that remote-tracking branches of a
+    /// clone can be addressed by their plain branch name.
+    ///
+    /// # Parameters
+    ///
+    /// * `ref_name` - Branch name, tag name, or (abbreviated) commit hash
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String, String>` - The hex commit id or an error message
+    pub fn resolve_commit_id(&self, ref_name: &str) -> Result<String, String> {
+        let repo = self.open_git_repository()?;
+        let commit = peel_ref_to_commit(&repo, ref_name)?;
+        Ok(commit.id.to_hex().to_string())
+    }
+
+    /// Fetches a branch, tag or commit from `origin` if it doesn't resolve locally
+    ///
+    /// Remote repositories are cloned with depth 1 and the clone is reused for later
+    /// requests, so a ref other than the one first cloned is usually missing. Only the
+    /// requested ref is fetched, keeping a shallow clone shallow. Refs that already
+    /// resolve are left alone without contacting the remote.
+    ///
+    /// The `latest` / `latest-stable` aliases are looked up among all tags of the remote,
+    /// as tags released since the clone are missing locally; the tag they pick is fetched
+    /// if needed, so that resolving the alias locally afterwards finds it. A branch or tag
+    /// literally named like the alias is fetched instead. This is the only place aliases
+    /// of remote repositories are resolved, so every tool picks the same release.
+    ///
+    /// # Parameters
+    ///
+    /// * `ref_name` - Branch name, tag name, full ref name, full commit hash, or alias
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), String>` - Success or an error message if the ref still doesn't resolve
+    pub async fn ensure_ref_available(&self, ref_name: &str) -> Result<(), String> {
+        let mut repo = self.open_git_repository()?;
+        let alias = LatestAlias::from_ref_name(ref_name);
+        if alias.is_none() && peel_ref_to_commit(&repo, ref_name).is_ok() {
+            return Ok(());
+        }
+
+        // Updated refs are logged with a committer, like gix does when cloning
+        if repo.committer().is_none() {
+            let mut config = repo.config_snapshot_mut();
+            config
+                .append_config(
+                    [
+                        "gitoxide.committer.nameFallback=gitcodes",
+                        "gitoxide.committer.emailFallback=noEmailAvailable@example.com",
+                    ],
+                    gix::config::Source::Api,
+                )
+                .map_err(|e| format!("Failed to configure the committer for fetching: {}", e))?;
+            config
+                .commit()
+                .map_err(|e| format!("Failed to configure the committer for fetching: {}", e))?;
+        }
+
+        let url = repo
+            .find_remote(DEFAULT_REMOTE_NAME)
+            .ok()
+            .and_then(|remote| remote.url(gix::remote::Direction::Fetch).cloned())
+            .ok_or_else(|| {
+                format!(
+                    "Failed to resolve reference '{}': the repository has no remote to fetch it from",
+                    ref_name
+                )
+            })?;
+        let fetched_ref = match alias {
+            Some(alias) => {
+                let ref_names = remote_ref_names(&repo, &url)?;
+                let is_literal_ref = ref_names.iter().any(|name| {
+                    name.strip_prefix("refs/heads/")
+                        .or_else(|| name.strip_prefix("refs/tags/"))
+                        == Some(ref_name)
+                });
+                let tag_names = ref_names
+                    .iter()
+                    .filter_map(|name| name.strip_prefix("refs/tags/"));
+                if is_literal_ref {
+                    ref_name.to_string()
+                } else if let Some(tag_name) = alias.select(tag_names) {
+                    let tag_ref = format!("refs/tags/{}", tag_name);
+                    if repo.find_reference(tag_ref.as_str()).is_ok() {
+                        return Ok(());
+                    }
+                    tag_ref
+                } else {
+                    return peel_ref_to_commit(&repo, ref_name).map(|_| ()).map_err(|_| {
+                        format!(
+                            "Cannot resolve '{}': the repository has no tags with a {}semantic version",
+                            ref_name,
+                            if alias == LatestAlias::LatestStable {
+                                "stable "
+                            } else {
+                                ""
+                            }
+                        )
+                    });
+                }
+            }
+            None => ref_name.to_string(),
+        };
+        let refspecs = if fetched_ref.starts_with("refs/") {
+            vec![format!("+{0}:{0}", fetched_ref)]
+        } else if fetched_ref.len() == 40 && fetched_ref.chars().all(|c| c.is_ascii_hexdigit()) {
+            vec![fetched_ref]
+        } else {
+            vec![
+                format!(
+                    "+refs/heads/{0}:refs/remotes/{1}/{0}",
+                    fetched_ref, DEFAULT_REMOTE_NAME
+                ),
+                format!("+refs/tags/{0}:refs/tags/{0}", fetched_ref),
+            ]
+        };
+        let remote = repo
+            .remote_at(url)
+            .map_err(|e| format!("Failed to set up fetching '{}': {}", ref_name, e))?
+            .with_refspecs(
+                refspecs.iter().map(String::as_str),
+                gix::remote::Direction::Fetch,
+            )
+            .map_err(|e| format!("Invalid reference name '{}': {}", ref_name, e))?
+            .with_fetch_tags(gix::remote::fetch::Tags::None);
+        let connection = remote.connect(gix::remote::Direction::Fetch).map_err(|e| {
+            format!(
+                "Failed to connect to remote for fetching '{}': {}",
+                ref_name, e
+            )
+        })?;
+
+        let mut progress = gix::progress::Discard;
+        let mut fetch = connection
+            .prepare_fetch(&mut progress, Default::default())
+            .map_err(|e| {
+                format!(
+                    "Failed to resolve reference '{}' on the remote: {}",
+                    ref_name, e
+                )
+            })?;
+        if repo.is_shallow() {
+            let depth = NonZeroU32::new(1).unwrap();
+            fetch = fetch.with_shallow(gix::remote::fetch::Shallow::DepthAtRemote(depth));
+        }
+        fetch
+            .receive(&mut progress, &gix::interrupt::IS_INTERRUPTED)
+            .map_err(|e| format!("Failed to fetch reference '{}': {}", ref_name, e))?;
+
+        let repo = self.open_git_repository()?;
+        peel_ref_to_commit(&repo, ref_name).map(|_| ())
+    }
+
+    /// Returns the fetch URL of the `origin` remote, if the repository has one
+    ///
+    /// This lets callers find out which hosted repository a local clone belongs to,
+    /// e.g. to look up issue and pull request titles.
+    pub fn origin_url(&self) -> Option<String> {
+        let repo = self.open_git_repository().ok()?;
+        let remote = repo.find_remote(DEFAULT_REMOTE_NAME).ok()?;
+        remote
+            .url(gix::remote::Direction::Fetch)
+            .map(|url| url.to_bstring().to_string())
+    }
+
+    /// View a file's contents at a specific ref without checking it out
+    ///
+    /// The `ref_name` and `params.file_path` together form a `ref:path` spec that is
+    /// resolved against the object database. The blob is then converted into the same
+    /// `lumin::view::FileContents` structure that [`LocalRepository::view_file_contents`]
+    /// returns, honoring `max_size`, `line_from` and `line_to`.
+    ///
+    /// # Parameters
+    ///
+    /// * `ref_name` - Branch, tag, or commit to read the file from
+    /// * `params` - The parameters for the view operation, including file path and line range
+    ///
+    /// # Returns
+    ///
+    /// * `Result<lumin::view::FileContents, String>` - The file contents or an error message
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The path contains directory traversal patterns ("..")
+    /// - The ref cannot be resolved to a commit
+    /// - The path doesn't exist at that ref or isn't a file
+    /// - The file is too large (exceeds the max_size limit in params)
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use gitcodes_mcp::gitcodes::local_repository::{LocalRepository, ViewFileParams};
+    /// use std::path::PathBuf;
+    ///
+    /// async fn example() -> Result<(), String> {
+    ///     let repo = LocalRepository::new(PathBuf::from("/path/to/repo"));
+    ///
+    ///     let params = ViewFileParams {
+    ///         file_path: PathBuf::from("Cargo.toml"),
+    ///         max_size: None,
+    ///         line_from: None,
+    ///         line_to: None,
+    ///     };
+    ///
+    ///     // Read Cargo.toml as it was at the v1.0.0 tag
+    ///     let contents = repo.view_file_contents_at_ref("v1.0.0", params).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn view_file_contents_at_ref(
+        &self,
+        ref_name: &str,
+        params: ViewFileParams,
+    ) -> Result<FileContents, String> {
+        prevent_directory_traversal(&params.file_path)?;
+        let file_path = self.normalize_repository_path(&params.file_path)?;
+
+        let repo = self.open_git_repository()?;
+        let tree = peel_ref_to_commit(&repo, ref_name)?
+            .tree()
+            .map_err(|e| format!("Failed to read tree of '{}': {}", ref_name, e))?;
+
+        let entry = tree
+            .lookup_entry_by_path(&file_path)
+            .map_err(|e| {
+                format!(
+                    "Failed to look up '{}' at '{}': {}",
+                    file_path.display(),
+                    ref_name,
+                    e
+                )
+            })?
+            .ok_or_else(|| {
+                format!(
+                    "File not found in repository at ref '{}': {}",
+                    ref_name,
+                    params.file_path.display()
+                )
+            })?;
+
+        if !entry.mode().is_blob_or_symlink() {
+            return Err(format!(
+                "Not a file at ref '{}': {}",
+                ref_name,
+                params.file_path.display()
+            ));
+        }
+
+        let blob = entry
+            .object()
+            .map_err(|e| format!("Failed to read blob for '{}': {}", file_path.display(), e))?;
+
+        file_contents_from_bytes(&file_path, &blob.data, &params)
+    }
+
+    /// Get the directory tree structure of the repository at a specific ref
+    ///
+    /// This is the object database counterpart of [`LocalRepository::get_tree_with_params`].
+    /// The tree is read from the commit `ref_name` points to, so no checkout is needed.
+    ///
+    /// Only committed content exists in the object database, so `.gitignore` rules don't
+    /// change the result. For parity with the worktree reader, hidden entries (names
+    /// starting with '.') are skipped unless `respect_gitignore` is `Some(false)`.
+    /// Paths are always relative to the repository root.
+    ///
+    /// # Parameters
+    ///
+    /// * `ref_name` - Branch, tag, or commit to read the tree from
+    /// * `params` - Optional configuration parameters for the tree generation
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<RepositoryTree>, String>` - The directory tree structure or an error message
+    pub async fn get_tree_at_ref(
+        &self,
+        ref_name: &str,
+        params: Option<TreeParams>,
+    ) -> Result<Vec<RepositoryTree>, String> {
+        let params = params.unwrap_or(TreeParams {
+            case_sensitive: None,
+            search_relative_path: None,
+            respect_gitignore: None,
+            depth: None,
+            strip_path_prefix: None,
+        });
+
+        let repo = self.open_git_repository()?;
+        let root_tree = peel_ref_to_commit(&repo, ref_name)?
+            .tree()
+            .map_err(|e| format!("Failed to read tree of '{}': {}", ref_name, e))?;
+
+        // Descend into the requested subdirectory, if any
+        let (root_dir, tree) = match &params.search_relative_path {
+            Some(relative_path) => {
+                let relative_path = self.normalize_repository_path(relative_path)?;
+                let entry = root_tree
+                    .lookup_entry_by_path(&relative_path)
+                    .map_err(|e| format!("Failed to look up '{}': {}", relative_path.display(), e))?
+                    .filter(|entry| entry.mode().is_tree())
+                    .ok_or_else(|| {
+                        format!(
+                            "Directory not found in repository at ref '{}': {}",
+                            ref_name,
+                            relative_path.display()
+                        )
+                    })?;
+                let tree = entry
+                    .object()
+                    .map(|object| object.into_tree())
+                    .map_err(|e| {
+                        format!("Failed to read tree '{}': {}", relative_path.display(), e)
+                    })?;
+                (relative_path.to_string_lossy().to_string(), tree)
+            }
+            None => (String::new(), root_tree),
+        };
+
+        let records = tree
+            .traverse()
+            .breadthfirst
+            .files()
+            .map_err(|e| format!("Failed to traverse tree at '{}': {}", ref_name, e))?;
+
+        let skip_hidden = params.respect_gitignore.unwrap_or(true);
+        let mut dirs: BTreeMap<String, Vec<TreeEntry>> = BTreeMap::new();
+
+        for record in records {
+            // Submodules are commits from another repository and have no content here
+            if record.mode.is_commit() {
+                continue;
+            }
+
+            let relative = record.filepath.to_string();
+            let components: Vec<&str> = relative.split('/').collect();
+
+            if let Some(depth) = params.depth {
+                if components.len() > depth {
+                    continue;
+                }
+            }
+            if skip_hidden && components.iter().any(|c| c.starts_with('.')) {
+                continue;
+            }
+
+            let (parent, name) = match relative.rsplit_once('/') {
+                Some((parent, name)) => (join_tree_path(&root_dir, parent), name.to_string()),
+                None => (root_dir.clone(), relative.clone()),
+            };
+
+            let entry = if record.mode.is_tree() {
+                TreeEntry::Directory(name)
+            } else {
+                TreeEntry::File(name)
+            };
+            dirs.entry(parent).or_default().push(entry);
+        }
+
+        if dirs.is_empty() {
+            return Ok(vec![RepositoryTree {
+                dir: root_dir,
+                entries: vec![TreeEntry::Directory(".".to_string())],
+            }]);
+        }
+
+        Ok(dirs
+            .into_iter()
+            .map(|(dir, entries)| RepositoryTree { dir, entries })
+            .collect())
+    }
+}
+
+/// Resolves a ref name to the commit it points to, peeling annotated tags
+///
+/// Falls back to `origin/<ref_name>` when the name doesn't resolve as given, and then
+/// to the `latest` / `latest-stable` aliases, which pick the tag with the highest version.
+/// Aliases only see the local tags; [`LocalRepository::ensure_ref_available`] fetches
+/// the tag they pick on the remote first.
+pub(crate) fn peel_ref_to_commit<'repo>(
+    repo: &'repo gix::Repository,
+    ref_name: &str,
+) -> Result<gix::Commit<'repo>, String> {
+    let mut candidates = vec![
+        ref_name.to_string(),
+        format!("{}/{}", DEFAULT_REMOTE_NAME, ref_name),
+    ];
+    if let Some(alias) = LatestAlias::from_ref_name(ref_name) {
+        let tag_names = local_tag_names(repo)?;
+        if let Some(tag_name) = alias.select(tag_names.iter().map(String::as_str)) {
+            candidates.push(format!("refs/tags/{}", tag_name));
+        }
+    }
+
+    let mut last_error = None;
+    for candidate in candidates.iter() {
+        match repo.rev_parse_single(candidate.as_str()) {
+            Ok(id) => {
+                return id
+                    .object()
+                    .map(|object| object.peel_to_commit())
+                    .map_err(|e| format!("Failed to read object for '{}': {}", ref_name, e))?
+                    .map_err(|e| {
+                        format!("Reference '{}' does not point to a commit: {}", ref_name, e)
+                    });
+            }
+            Err(e) => last_error = Some(e.to_string()),
+        }
+    }
+
+    Err(format!(
+        "Failed to resolve reference '{}': {}",
+        ref_name,
+        last_error.unwrap_or_default()
+    ))
+}
+
+/// Lists the full names of the branches and tags of a remote without fetching them
+///
+/// The git protocol returns every ref in one listing, unlike the paginated provider APIs.
+fn remote_ref_names(repo: &gix::Repository, url: &gix::Url) -> Result<Vec<String>, String> {
+    let remote = repo
+        .remote_at(url.clone())
+        .map_err(|e| format!("Failed to set up listing remote refs: {}", e))?
+        .with_refspecs(
+            ["refs/heads/*:refs/heads/*", "refs/tags/*:refs/tags/*"],
+            gix::remote::Direction::Fetch,
+        )
+        .map_err(|e| format!("Failed to set up listing remote refs: {}", e))?
+        .with_fetch_tags(gix::remote::fetch::Tags::None);
+    let connection = remote
+        .connect(gix::remote::Direction::Fetch)
+        .map_err(|e| format!("Failed to connect to remote for listing refs: {}", e))?;
+
+    let mut progress = gix::progress::Discard;
+    let fetch = connection
+        .prepare_fetch(&mut progress, Default::default())
+        .map_err(|e| format!("Failed to list remote refs: {}", e))?;
+    Ok(fetch
+        .ref_map()
+        .remote_refs
+        .iter()
+        .filter_map(|reference| {
+            let (name, _, _) = reference.unpack();
+            Some(name.to_str().ok()?.to_string())
+        })
+        .collect())
+}
+
+/// Lists the short names of all tags in the repository
+fn local_tag_names(repo: &gix::Repository) -> Result<Vec<String>, String> {
+    let references = repo
+        .references()
+        .map_err(|e| format!("Failed to read references: {}", e))?;
+    let tags = references
+        .tags()
+        .map_err(|e| format!("Failed to read tags: {}", e))?;
+
+    Ok(tags
+        .filter_map(Result::ok)
+        .map(|tag| tag.name().shorten().to_string())
+        .collect())
+}
+
+/// Joins a repository-relative directory with a child path, treating "" as the root
+fn join_tree_path(dir: &str, child: &str) -> String {
+    if dir.is_empty() {
+        child.to_string()
+    } else {
+        format!("{}/{}", dir, child)
+    }
+}
+
+/// Converts raw blob bytes into `lumin::view::FileContents`
+///
+/// Applies the same rules as `lumin::view::view_file`: the size limit applies to the
+/// whole file unless a line range is requested, in which case it applies to the
+/// selected lines. Images are detected by their magic bytes, and content that isn't
+/// valid UTF-8 (or contains NUL bytes) is reported as binary.
+pub(crate) fn file_contents_from_bytes(
+    file_path: &Path,
+    data: &[u8],
+    params: &ViewFileParams,
+) -> Result<FileContents, String> {
+    let max_size = params.max_size.unwrap_or(DEFAULT_MAX_BLOB_SIZE);
+    let using_line_filters = params.line_from.is_some() || params.line_to.is_some();
+    let size_bytes = data.len() as u64;
+
+    if !using_line_filters && data.len() > max_size {
+        return Err(format!(
+            "File is too large: {} (size: {}, limit: {})",
+            file_path.display(),
+            data.len(),
+            max_size
+        ));
+    }
+
+    if let Some(kind) = infer::get(data) {
+        if kind.matcher_type() == infer::MatcherType::Image {
+            return Ok(FileContents::Image {
+                message: format!("Image file detected: {}", kind.mime_type()),
+                metadata: ImageMetadata {
+                    binary: true,
+                    size_bytes,
+                    media_type: "image".to_string(),
+                },
+            });
+        }
+    }
+
+    let text = match std::str::from_utf8(data) {
+        Ok(text) if !data.contains(&0) => text,
+        _ => {
+            let mime_type = infer::get(data).map(|kind| kind.mime_type().to_string());
+            return Ok(FileContents::Binary {
+                message: format!("Binary file detected, size: {} bytes", size_bytes),
+                metadata: BinaryMetadata {
+                    binary: true,
+                    size_bytes,
+                    mime_type,
+                },
+            });
+        }
+    };
+
+    let all_lines: Vec<&str> = text.lines().collect();
+    let line_count = all_lines.len();
+    let from_line = params.line_from.unwrap_or(1).max(1);
+    let to_line = params.line_to.unwrap_or(line_count).min(line_count);
+
+    let line_contents: Vec<LineContent> = if from_line > to_line {
+        Vec::new()
+    } else {
+        all_lines[from_line - 1..to_line]
+            .iter()
+            .enumerate()
+            .map(|(offset, line)| LineContent {
+                line_number: from_line + offset,
+                line: line.to_string(),
+            })
+            .collect()
+    };
+
+    if using_line_filters {
+        let filtered_size: usize = line_contents.iter().map(|line| line.line.len() + 1).sum();
+        if filtered_size > max_size {
+            return Err(format!(
+                "Filtered content is too large: {} (filtered size: {}, limit: {})",
+                file_path.display(),
+                filtered_size,
+                max_size
+            ));
+        }
+    }
+
+    Ok(FileContents::Text {
+        content: TextContent { line_contents },
+        metadata: TextMetadata {
+            line_count,
+            char_count: text.chars().count(),
+        },
+    })
+}
@@ -0,0 +1,462 @@
+//! Code search over the worktree and over the blobs of a commit
+//!
+//! Searching a historical ref in the worktree requires checking it out first, which means
+//! one worktree per ref. The search of a ref instead walks the tree of the resolved commit
+//! and runs the `grep` matcher over each blob in memory. The worktree is searched with the
+//! same matcher and result handling, over the files lumin would walk, so both give the
+//! results of `lumin::search::search_files`: paths are relative to the repository root,
+//! hidden paths are skipped, include/exclude globs are matched against relative paths,
+//! lines are sorted by path and line number, and `skip`/`take` are applied after counting
+//! the total.
+//!
+//! Searches narrowed to a set of candidate files (by the trigram index, the working-tree
+//! status or a boolean search) take the same walks and only read the candidates, so they
+//! find exactly what the unrestricted search finds in those files.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use grep::matcher::Matcher;
+use grep::regex::{RegexMatcher, RegexMatcherBuilder};
+use grep::searcher::{BinaryDetection, Searcher, SearcherBuilder, Sink, SinkContext, SinkMatch};
+use lumin::search::SearchResultLine as LuminSearchResultLine;
+use lumin::traverse::common::{collect_files_with_excludes, path_matches_any_glob};
+
+use super::object_database::peel_ref_to_commit;
+use super::{CodeSearchOptions, CodeSearchResult, LocalRepository, filter_by_file_extensions};
+
+/// Marker inserted where line content was omitted around a match (same as lumin)
+const OMIT_MARKER: &str = "<omit>";
+
+impl LocalRepository {
+    /// Performs a code search over the tree of a specific ref without checking it out
+    ///
+    /// This is the object database counterpart of [`LocalRepository::perform_code_search`].
+    /// It honors the same options: include/exclude globs, file extensions, context lines,
+    /// `skip`/`take` pagination and `match_content_omit_num`.
+    ///
+    /// Only regular files are searched. Symlinks and submodules are skipped, as are blobs
+    /// containing NUL bytes (treated as binary, like lumin does).
+    ///
+    /// # Parameters
+    ///
+    /// * `ref_name` - Branch, tag, or commit whose tree is searched
+    /// * `options` - The search options
+    ///
+    /// # Returns
+    ///
+    /// * `Result<CodeSearchResult, String>` - Structured search results or an error message
+    pub async fn perform_code_search_at_ref(
+        &self,
+        ref_name: &str,
+        options: CodeSearchOptions,
+    ) -> Result<CodeSearchResult, String> {
+        self.search_tree_at_ref(ref_name, None, options)
+    }
+
+    /// Performs a code search restricted to a set of candidate files
+    ///
+    /// The worktree or the tree of the ref is walked as by an unrestricted search, with the
+    /// same include and exclude globs, but only the files in `paths` are read and searched.
+    /// So the result is that of an unrestricted search limited to those files, and the
+    /// cost grows with the number of candidates rather than with the size of the tree.
+    ///
+    /// # Parameters
+    ///
+    /// * `paths` - Paths of the candidate files relative to the repository root
+    /// * `ref_name` - Branch, tag, or commit whose tree is searched instead of the worktree
+    /// * `options` - The search options
+    ///
+    /// # Returns
+    ///
+    /// * `Result<CodeSearchResult, String>` - The matches in the candidate files or an error message
+    pub(super) async fn perform_code_search_in_files(
+        &self,
+        paths: impl IntoIterator<Item = impl Into<PathBuf>>,
+        ref_name: Option<&str>,
+        options: CodeSearchOptions,
+    ) -> Result<CodeSearchResult, String> {
+        let candidates: HashSet<PathBuf> = paths.into_iter().map(Into::into).collect();
+        match ref_name {
+            Some(ref_name) => self.search_tree_at_ref(ref_name, Some(&candidates), options),
+            None => self.search_worktree_files(Some(&candidates), options),
+        }
+    }
+
+    /// Searches the blobs in the tree of a ref, only the `candidates` if given
+    fn search_tree_at_ref(
+        &self,
+        ref_name: &str,
+        candidates: Option<&HashSet<PathBuf>>,
+        options: CodeSearchOptions,
+    ) -> Result<CodeSearchResult, String> {
+        let (include_globs, exclude_globs) = self.normalize_search_globs(
+            options.include_globs.as_ref(),
+            options.exclude_globs.as_ref(),
+        );
+
+        let matcher = build_matcher(&options)?;
+
+        let repo = self.open_git_repository()?;
+        let tree = peel_ref_to_commit(&repo, ref_name)?
+            .tree()
+            .map_err(|e| format!("Failed to read tree of '{}': {}", ref_name, e))?;
+        let records = tree
+            .traverse()
+            .breadthfirst
+            .files()
+            .map_err(|e| format!("Failed to traverse tree at '{}': {}", ref_name, e))?;
+
+        let mut searcher = build_searcher(&options);
+
+        let mut result_lines = Vec::new();
+        for record in records {
+            if !record.mode.is_blob() {
+                continue;
+            }
+
+            let relative_path = PathBuf::from(record.filepath.to_string());
+            if !is_candidate(candidates, &relative_path) {
+                continue;
+            }
+            if !should_search_path(
+                &relative_path,
+                include_globs.as_deref(),
+                exclude_globs.as_deref(),
+                options.case_sensitive,
+            )? {
+                continue;
+            }
+
+            let blob = repo.find_blob(record.oid).map_err(|e| {
+                format!(
+                    "Failed to read blob for '{}': {}",
+                    relative_path.display(),
+                    e
+                )
+            })?;
+
+            search_content(
+                &mut searcher,
+                &matcher,
+                &relative_path,
+                &blob.data,
+                options.match_content_omit_num,
+                &mut result_lines,
+            )?;
+        }
+
+        Ok(self.finish_code_search(result_lines, options))
+    }
+
+    /// Searches the files of the worktree lumin would search, only the `candidates` if given
+    ///
+    /// Files ignored by git and hidden paths are skipped. Every file is read and searched
+    /// with the `grep` matcher directly, so matches may span multiple lines when `multiline`
+    /// is set; every line of such a match is reported as a matched line.
+    pub(super) fn search_worktree_files(
+        &self,
+        candidates: Option<&HashSet<PathBuf>>,
+        options: CodeSearchOptions,
+    ) -> Result<CodeSearchResult, String> {
+        let (include_globs, exclude_globs) = self.normalize_search_globs(
+            options.include_globs.as_ref(),
+            options.exclude_globs.as_ref(),
+        );
+        let matcher = build_matcher(&options)?;
+        let mut searcher = build_searcher(&options);
+
+        let repo_path = self.repository_location.as_path();
+        let files = collect_files_with_excludes(
+            repo_path,
+            true,
+            options.case_sensitive,
+            None,
+            exclude_globs.as_ref(),
+        )
+        .map_err(|e| format!("Code search failed: {}", e))?;
+
+        let mut result_lines = Vec::new();
+        for file_path in files {
+            let relative_path = file_path
+                .strip_prefix(repo_path)
+                .unwrap_or(&file_path)
+                .to_path_buf();
+            if !is_candidate(candidates, &relative_path) {
+                continue;
+            }
+            if let Some(includes) = &include_globs {
+                if !path_matches_any_glob(&relative_path, includes, options.case_sensitive)
+                    .map_err(|e| format!("Invalid include glob: {}", e))?
+                {
+                    continue;
+                }
+            }
+
+            // Files that can't be read are skipped, as lumin does
+            let Ok(data) = std::fs::read(&file_path) else {
+                continue;
+            };
+
+            search_content(
+                &mut searcher,
+                &matcher,
+                &relative_path,
+                &data,
+                options.match_content_omit_num,
+                &mut result_lines,
+            )?;
+        }
+
+        Ok(self.finish_code_search(result_lines, options))
+    }
+
+    /// Sorts, counts and paginates collected result lines into a [`CodeSearchResult`]
+    fn finish_code_search(
+        &self,
+        result_lines: Vec<LuminSearchResultLine>,
+        options: CodeSearchOptions,
+    ) -> CodeSearchResult {
+        // Filter and count before paginating, so the total and the pages only cover the
+        // lines that are returned, then sort for consistent ordering (as lumin does)
+        let mut result_lines =
+            filter_by_file_extensions(result_lines, options.file_extensions.as_ref());
+        let total_match_line_number = result_lines.len();
+        result_lines.sort_by(|a, b| {
+            a.file_path
+                .cmp(&b.file_path)
+                .then(a.line_number.cmp(&b.line_number))
+        });
+
+        let search_result_lines: Vec<LuminSearchResultLine> = result_lines
+            .into_iter()
+            .skip(options.skip.unwrap_or(0))
+            .take(options.take.unwrap_or(usize::MAX))
+            .collect();
+
+        let params = crate::gitcodes::local_repository::search_result::CodeSearchParams {
+            total_match_line_number,
+            search_result_lines,
+            pattern: options.pattern,
+            repository: self.repository_location.clone(),
+            case_sensitive: options.case_sensitive,
+            file_extensions: options.file_extensions,
+            include_globs: options.include_globs,
+            exclude_globs: options.exclude_globs,
+            before_context: options.before_context,
+            after_context: options.after_context,
+        };
+        CodeSearchResult::new(params)
+    }
+}
+
+/// Creates the matcher for a search, like lumin does but honoring all pattern options
+fn build_matcher(options: &CodeSearchOptions) -> Result<RegexMatcher, String> {
+    RegexMatcherBuilder::new()
+        .case_insensitive(!options.case_sensitive)
+        .multi_line(options.multiline)
+        .build(&options.matcher_pattern())
+        .map_err(|e| format!("Code search failed: invalid pattern: {}", e))
+}
+
+/// Creates the searcher for a search, with the context lines of the options
+fn build_searcher(options: &CodeSearchOptions) -> Searcher {
+    SearcherBuilder::new()
+        .binary_detection(BinaryDetection::quit(b'\x00'))
+        .multi_line(options.multiline)
+        .before_context(options.before_context.unwrap_or(0))
+        .after_context(options.after_context.unwrap_or(0))
+        .build()
+}
+
+/// Searches the content of one file and appends its matched and context lines
+fn search_content(
+    searcher: &mut Searcher,
+    matcher: &RegexMatcher,
+    relative_path: &Path,
+    data: &[u8],
+    match_content_omit_num: Option<usize>,
+    result_lines: &mut Vec<LuminSearchResultLine>,
+) -> Result<(), String> {
+    let mut matches = Vec::new();
+    searcher
+        .search_slice(
+            matcher,
+            data,
+            MatchCollector {
+                matches: &mut matches,
+            },
+        )
+        .map_err(|e| format!("Error searching file {}: {}", relative_path.display(), e))?;
+
+    for (line_number, content, is_context) in matches {
+        let (line_content, content_omitted) = match match_content_omit_num {
+            Some(omit_num) if !is_context => omit_line_content(matcher, content, omit_num),
+            _ => (content, false),
+        };
+
+        result_lines.push(LuminSearchResultLine {
+            file_path: relative_path.to_path_buf(),
+            line_number,
+            line_content,
+            content_omitted,
+            is_context,
+        });
+    }
+    Ok(())
+}
+
+/// Whether a file is one of the candidates, every file being one when there are none
+fn is_candidate(candidates: Option<&HashSet<PathBuf>>, path: &Path) -> bool {
+    candidates.is_none_or(|candidates| candidates.contains(path))
+}
+
+/// Decides whether a blob at `path` (relative to the repository root) should be searched
+///
+/// Hidden paths are skipped, matching lumin's behavior when respecting gitignore.
+/// A path must match one of the include globs (if any) and none of the exclude globs.
+pub(super) fn should_search_path(
+    path: &Path,
+    include_globs: Option<&[String]>,
+    exclude_globs: Option<&[String]>,
+    case_sensitive: bool,
+) -> Result<bool, String> {
+    let is_hidden = path
+        .components()
+        .any(|component| component.as_os_str().to_string_lossy().starts_with('.'));
+    if is_hidden {
+        return Ok(false);
+    }
+
+    if let Some(excludes) = exclude_globs {
+        if path_matches_any_glob(path, excludes, case_sensitive)
+            .map_err(|e| format!("Invalid exclude glob: {}", e))?
+        {
+            return Ok(false);
+        }
+    }
+
+    match include_globs {
+        Some(includes) => path_matches_any_glob(path, includes, case_sensitive)
+            .map_err(|e| format!("Invalid include glob: {}", e)),
+        None => Ok(true),
+    }
+}
+
+/// Shortens a matched line to `omit_num` characters around each match
+///
+/// Follows `lumin::search::search_files`: the matches themselves are always kept in full,
+/// overlapping ranges are merged, and omitted gaps are marked with `<omit>`.
+///
+/// # Returns
+///
+/// * `(String, bool)` - The (possibly shortened) line and whether anything was omitted
+fn omit_line_content(matcher: &RegexMatcher, content: String, omit_num: usize) -> (String, bool) {
+    let mut keep_ranges = Vec::new();
+    let _ = matcher.find_iter(content.as_bytes(), |m| {
+        keep_ranges.push(context_range(&content, m.start(), m.end(), omit_num));
+        true
+    });
+
+    if keep_ranges.is_empty() {
+        return (content, false);
+    }
+
+    // Sort and merge overlapping ranges
+    keep_ranges.sort_by_key(|&(start, _)| start);
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in keep_ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+
+    let omitted = merged.len() > 1 || merged[0].0 > 0 || merged[merged.len() - 1].1 < content.len();
+    if !omitted {
+        return (content, false);
+    }
+
+    let mut result = String::new();
+    let mut last_end = 0;
+    for (start, end) in merged {
+        // No marker at the very beginning of the line
+        if start > last_end && last_end > 0 {
+            result.push_str(OMIT_MARKER);
+        }
+        result.push_str(&content[start..end]);
+        last_end = end;
+    }
+    if last_end < content.len() {
+        result.push_str(OMIT_MARKER);
+    }
+
+    (result, true)
+}
+
+/// Returns the byte range covering a match plus up to `omit_num` characters on each side
+fn context_range(content: &str, start: usize, end: usize, omit_num: usize) -> (usize, usize) {
+    // Snap the match to character boundaries
+    let start = (0..=start)
+        .rev()
+        .find(|&i| content.is_char_boundary(i))
+        .unwrap_or(0);
+    let end = (end..=content.len())
+        .find(|&i| content.is_char_boundary(i))
+        .unwrap_or(content.len());
+
+    let chars_before = content[..start].chars().count();
+    let context_start = content[..start]
+        .char_indices()
+        .nth(chars_before.saturating_sub(omit_num))
+        .map(|(i, _)| i)
+        .unwrap_or(start);
+
+    let context_end = content[end..]
+        .char_indices()
+        .nth(omit_num)
+        .map(|(i, _)| end + i)
+        .unwrap_or(content.len());
+
+    (context_start, context_end)
+}
+
+/// Sink collecting matched and context lines as `(line_number, content, is_context)`
+///
+/// A multiline match is split into one matched line per line it spans.
+struct MatchCollector<'a> {
+    matches: &'a mut Vec<(u64, String, bool)>,
+}
+
+impl Sink for MatchCollector<'_> {
+    type Error = std::io::Error;
+
+    fn matched(
+        &mut self,
+        _searcher: &grep::searcher::Searcher,
+        mat: &SinkMatch<'_>,
+    ) -> Result<bool, Self::Error> {
+        let first_line_number = mat.line_number().unwrap_or(0);
+        for (offset, line) in String::from_utf8_lossy(mat.bytes())
+            .split_terminator('\n')
+            .enumerate()
+        {
+            self.matches
+                .push((first_line_number + offset as u64, line.to_string(), false));
+        }
+        Ok(true)
+    }
+
+    fn context(
+        &mut self,
+        _searcher: &grep::searcher::Searcher,
+        ctx: &SinkContext<'_>,
+    ) -> Result<bool, Self::Error> {
+        let line = String::from_utf8_lossy(ctx.bytes())
+            .trim_end_matches('\n')
+            .to_string();
+        self.matches
+            .push((ctx.line_number().unwrap_or(0), line, true));
+        Ok(true)
+    }
+}
@@ -0,0 +1,121 @@
+//! Structural outline of a source file
+//!
+//! Viewing a long file line by line is expensive. The outline lists the definitions of a
+//! file as a tree: modules, impl blocks, traits, classes and interfaces with the items
+//! defined in them, each with its signature, line range and the first line of its
+//! documentation. The line ranges can be passed to `view_file_contents` as
+//! `line_from`/`line_to` to read exactly one item.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::LocalRepository;
+use super::symbols::{CollectedSymbol, SymbolKind, collect_symbols};
+use super::syntax::SourceLanguage;
+
+/// An item in the outline of a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutlineItem {
+    /// The name of the item; for an impl block, the implementing type
+    pub name: String,
+    /// What kind of item it is
+    pub kind: SymbolKind,
+    /// The item up to its body on a single line, e.g. "impl<T> Display for Mutex<T>"
+    pub signature: String,
+    /// The first line of the doc comment or docstring
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    /// First line of the item (1-based)
+    pub start_line: usize,
+    /// Last line of the item (1-based, inclusive)
+    pub end_line: usize,
+    /// The items defined in the body of this one
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<OutlineItem>,
+}
+
+/// The outline of a source file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileOutline {
+    /// Path of the file relative to the repository root
+    pub file_path: String,
+    /// The language of the file
+    pub language: SourceLanguage,
+    /// Number of lines in the file
+    pub line_count: usize,
+    /// The top-level items in document order
+    pub items: Vec<OutlineItem>,
+}
+
+impl LocalRepository {
+    /// Builds the structural outline of a source file
+    ///
+    /// Function bodies aren't descended into, so the outline holds the items a reader
+    /// navigates by: top-level definitions and the members of modules, impls, traits,
+    /// classes and interfaces.
+    ///
+    /// # Parameters
+    ///
+    /// * `file_path` - Path of the file relative to the repository root
+    /// * `ref_name` - Branch, tag, or commit to read the file at instead of the worktree
+    ///
+    /// # Returns
+    ///
+    /// * `Result<FileOutline, String>` - The outline or an error message
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read or its language isn't supported.
+    pub async fn file_outline(
+        &self,
+        file_path: &Path,
+        ref_name: Option<&str>,
+    ) -> Result<FileOutline, String> {
+        if let Err(e) = self.validate() {
+            return Err(format!("Repository validation failed: {}", e));
+        }
+        let file = self.read_source_file(file_path, ref_name)?;
+        let file_path = file.path.to_string_lossy().replace('\\', "/");
+        let symbols = collect_symbols(file.language, &file.content, &file_path, true)?;
+
+        // The symbols come in document order with their depth, so each one belongs to the
+        // closest preceding symbol one level up
+        let mut stack: Vec<(usize, OutlineItem)> = Vec::new();
+        let mut items = Vec::new();
+        for CollectedSymbol { depth, symbol, .. } in symbols {
+            close_items(&mut stack, &mut items, depth);
+            stack.push((
+                depth,
+                OutlineItem {
+                    name: symbol.name,
+                    kind: symbol.kind,
+                    signature: symbol.signature,
+                    doc: symbol.doc,
+                    start_line: symbol.start_line,
+                    end_line: symbol.end_line,
+                    children: Vec::new(),
+                },
+            ));
+        }
+        close_items(&mut stack, &mut items, 0);
+
+        Ok(FileOutline {
+            file_path,
+            language: file.language,
+            line_count: file.content.lines().count(),
+            items,
+        })
+    }
+}
+
+/// Pops the items at `depth` or deeper off the stack, attaching each to its parent
+fn close_items(stack: &mut Vec<(usize, OutlineItem)>, items: &mut Vec<OutlineItem>, depth: usize) {
+    while stack.last().is_some_and(|(top, _)| *top >= depth) {
+        let (_, item) = stack.pop().expect("the stack isn't empty");
+        match stack.last_mut() {
+            Some((_, parent)) => parent.children.push(item),
+            None => items.push(item),
+        }
+    }
+}
@@ -0,0 +1,566 @@
+//! Ranked full-text search over code chunks
+//!
+//! A grep reports every matching line in file order, which is of little help for a
+//! question like "where is the retry backoff computed". The ranked search splits the
+//! files of a commit into chunks, one per function, method or other definition of the
+//! supported languages and windows of lines for everything else, and scores each chunk
+//! against the words of the query with BM25, the ranking function of classic search
+//! engines: chunks containing more of the query's rarer words, more often, rank higher.
+//!
+//! Words are taken apart the way identifiers are written, so `parseConfig` and
+//! `parse_config` both contain the words `parse` and `config` as well as the whole
+//! identifier. Words are lowercased and stripped of common English suffixes, so
+//! "parsing" finds `parse`. Everything runs locally, no embedding model is involved.
+//!
+//! Like the identifier index, the chunk index of a commit is built on the first ranked
+//! search and cached as JSON inside the repository's git directory. Only committed
+//! content is indexed; without a ref the commit at `HEAD` is used.
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::LocalRepository;
+use super::index_cache::{INDEX_DIR_NAME, write_index};
+use super::object_database::peel_ref_to_commit;
+use super::object_search::should_search_path;
+use super::symbols::collect_symbols;
+use super::syntax::SourceLanguage;
+
+/// Bumped whenever the layout of [`ChunkIndex`] or the tokenization changes, so stale
+/// caches are rebuilt
+const INDEX_FORMAT_VERSION: u32 = 1;
+
+/// Prefix of the file names of cached chunk indexes
+pub(super) const CHUNK_INDEX_FILE_PREFIX: &str = "chunks-v";
+
+/// Lines per chunk for text outside of definitions
+const WINDOW_LINES: usize = 40;
+
+/// Definitions longer than this many lines are split into windows
+const MAX_CHUNK_LINES: usize = 150;
+
+/// Files larger than this many bytes (usually generated or minified) aren't indexed
+const MAX_FILE_BYTES: usize = 512 * 1024;
+
+/// Default number of chunks returned
+const DEFAULT_TAKE: usize = 10;
+
+/// Default maximum number of source lines returned for one chunk
+const DEFAULT_MAX_LINES_PER_CHUNK: usize = 30;
+
+/// BM25 term frequency saturation
+const BM25_K1: f64 = 1.2;
+
+/// BM25 chunk length normalization
+const BM25_B: f64 = 0.75;
+
+/// Words too common in questions to tell chunks apart
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "do", "does", "for", "from", "how", "in",
+    "is", "it", "of", "on", "or", "that", "the", "this", "to", "was", "what", "when", "where",
+    "which", "who", "why", "with",
+];
+
+/// The chunks of all text files of one commit with their words
+#[derive(Debug, Serialize, Deserialize)]
+struct ChunkIndex {
+    /// The commit whose tree was indexed
+    commit_id: String,
+    /// Paths of the indexed files; chunks refer to them by position
+    files: Vec<String>,
+    /// The chunks of all files, in file and line order
+    chunks: Vec<IndexedChunk>,
+    /// Word to the chunks containing it and how often, as (chunk, count)
+    postings: HashMap<String, Vec<(u32, u32)>>,
+}
+
+/// A chunk of a file in the index
+#[derive(Debug, Serialize, Deserialize)]
+struct IndexedChunk {
+    file: u32,
+    start_line: u32,
+    end_line: u32,
+    /// The qualified name of the definition the chunk holds, if any
+    symbol: Option<String>,
+    /// Number of words in the chunk
+    length: u32,
+}
+
+/// Options for a ranked search
+#[derive(Debug, Clone, Default)]
+pub struct RankedSearchOptions {
+    /// Free text, e.g. "where is the config file parsed"; identifiers may be used as written
+    pub query: String,
+    /// Branch, tag, or commit to search (the commit at `HEAD` when `None`)
+    pub ref_name: Option<String>,
+    /// Glob patterns of the files to return chunks from, as in code search
+    pub include_globs: Option<Vec<String>>,
+    /// Directories or glob patterns to exclude, as in code search
+    pub exclude_globs: Option<Vec<String>>,
+    /// Number of chunks to skip (for pagination)
+    pub skip: Option<usize>,
+    /// Maximum number of chunks to return (default 10)
+    pub take: Option<usize>,
+    /// Maximum number of source lines returned for one chunk (default 30)
+    pub max_lines_per_chunk: Option<usize>,
+}
+
+/// A chunk of a file matching a ranked search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankedChunk {
+    /// Path of the file relative to the repository root
+    pub file_path: String,
+    /// First line of the chunk (1-based)
+    pub start_line: usize,
+    /// Last line of the chunk (1-based, inclusive)
+    pub end_line: usize,
+    /// The qualified name of the function, method or type the chunk holds, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    /// The BM25 score; higher is more relevant, comparable only within one search
+    pub score: f64,
+    /// The words of the query found in the chunk, after normalization
+    pub matched_terms: Vec<String>,
+    /// The source lines from `start_line`, at most `max_lines_per_chunk` of them
+    pub lines: Vec<String>,
+}
+
+/// Result of a ranked search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankedSearchResult {
+    /// The query as given
+    pub query: String,
+    /// The words searched for, after normalization
+    pub terms: Vec<String>,
+    /// The commit whose files were searched
+    pub commit_id: String,
+    /// Number of chunks in the index
+    pub indexed_chunks: usize,
+    /// Number of chunks containing any of the words, before pagination
+    pub total_matches: usize,
+    /// The chunks, most relevant first
+    pub chunks: Vec<RankedChunk>,
+}
+
+impl LocalRepository {
+    /// Ranks the chunks of the files of a commit by their relevance to a query
+    ///
+    /// The chunk index of the commit is built on first use, which reads every text file
+    /// of the commit and parses the Rust, Go, Python, TypeScript and JavaScript files;
+    /// later searches only read the index and the files of the returned chunks.
+    ///
+    /// # Parameters
+    ///
+    /// * `options` - The query, filters and pagination
+    ///
+    /// # Returns
+    ///
+    /// * `Result<RankedSearchResult, String>` - The chunks, most relevant first, or an
+    ///   error message
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query holds no searchable word, the ref or a glob can't be
+    /// resolved, or the files can't be read.
+    pub async fn search_code_ranked(
+        &self,
+        options: RankedSearchOptions,
+    ) -> Result<RankedSearchResult, String> {
+        if let Err(e) = self.validate() {
+            return Err(format!("Repository validation failed: {}", e));
+        }
+        let mut terms = Vec::new();
+        for term in tokenize(&options.query) {
+            if !terms.contains(&term) {
+                terms.push(term);
+            }
+        }
+        if terms.is_empty() {
+            return Err(format!(
+                "The query '{}' holds no searchable words",
+                options.query
+            ));
+        }
+        let max_lines = options
+            .max_lines_per_chunk
+            .unwrap_or(DEFAULT_MAX_LINES_PER_CHUNK)
+            .max(1);
+
+        let commit_id = self.resolve_commit_id(options.ref_name.as_deref().unwrap_or("HEAD"))?;
+        let index = self.load_or_build_chunk_index(&commit_id)?;
+
+        // Okapi BM25 with the non-negative IDF of Lucene
+        let chunk_count = index.chunks.len() as f64;
+        let total_length: f64 = index.chunks.iter().map(|chunk| chunk.length as f64).sum();
+        let average_length = (total_length / chunk_count.max(1.0)).max(1.0);
+        let mut scores: HashMap<u32, (f64, Vec<usize>)> = HashMap::new();
+        for (term_number, term) in terms.iter().enumerate() {
+            let Some(posting) = index.postings.get(term) else {
+                continue;
+            };
+            let containing = posting.len() as f64;
+            let idf = (1.0 + (chunk_count - containing + 0.5) / (containing + 0.5)).ln();
+            for &(chunk, count) in posting {
+                let count = count as f64;
+                let length = index.chunks[chunk as usize].length as f64;
+                let saturation =
+                    count + BM25_K1 * (1.0 - BM25_B + BM25_B * length / average_length);
+                let entry = scores.entry(chunk).or_default();
+                entry.0 += idf * count * (BM25_K1 + 1.0) / saturation;
+                entry.1.push(term_number);
+            }
+        }
+
+        let (include_globs, exclude_globs) = self.normalize_search_globs(
+            options.include_globs.as_ref(),
+            options.exclude_globs.as_ref(),
+        );
+        let mut file_matches = HashMap::new();
+        let mut ranked = Vec::new();
+        for (chunk, (score, term_numbers)) in scores {
+            let file = index.chunks[chunk as usize].file;
+            let matches = match file_matches.get(&file) {
+                Some(&matches) => matches,
+                None => {
+                    let matches = should_search_path(
+                        Path::new(&index.files[file as usize]),
+                        include_globs.as_deref(),
+                        exclude_globs.as_deref(),
+                        true,
+                    )?;
+                    file_matches.insert(file, matches);
+                    matches
+                }
+            };
+            if matches {
+                ranked.push((score, chunk, term_numbers));
+            }
+        }
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+        let total_matches = ranked.len();
+
+        // Only the files on the requested page are read to fill in the lines
+        let repo = self.open_git_repository()?;
+        let tree = peel_ref_to_commit(&repo, &commit_id)?
+            .tree()
+            .map_err(|e| format!("Failed to read tree of '{}': {}", commit_id, e))?;
+        let mut file_lines: HashMap<u32, Vec<String>> = HashMap::new();
+        let mut chunks = Vec::new();
+        for (score, chunk, mut term_numbers) in ranked
+            .into_iter()
+            .skip(options.skip.unwrap_or(0))
+            .take(options.take.unwrap_or(DEFAULT_TAKE))
+        {
+            let chunk = &index.chunks[chunk as usize];
+            let file_path = &index.files[chunk.file as usize];
+            if let Entry::Vacant(vacant) = file_lines.entry(chunk.file) {
+                let entry = tree
+                    .lookup_entry_by_path(file_path)
+                    .map_err(|e| format!("Failed to look up '{}': {}", file_path, e))?
+                    .ok_or_else(|| format!("File not found in commit: {}", file_path))?;
+                let blob = entry
+                    .object()
+                    .map_err(|e| format!("Failed to read blob for '{}': {}", file_path, e))?;
+                let lines = String::from_utf8_lossy(&blob.data)
+                    .lines()
+                    .map(str::to_string)
+                    .collect();
+                vacant.insert(lines);
+            }
+
+            let (start_line, end_line) = (chunk.start_line as usize, chunk.end_line as usize);
+            let lines = file_lines[&chunk.file]
+                .iter()
+                .skip(start_line - 1)
+                .take((end_line + 1 - start_line).min(max_lines))
+                .cloned()
+                .collect();
+            term_numbers.sort();
+            chunks.push(RankedChunk {
+                file_path: file_path.clone(),
+                start_line,
+                end_line,
+                symbol: chunk.symbol.clone(),
+                score: (score * 1000.0).round() / 1000.0,
+                matched_terms: term_numbers
+                    .into_iter()
+                    .map(|term_number| terms[term_number].clone())
+                    .collect(),
+                lines,
+            });
+        }
+
+        Ok(RankedSearchResult {
+            query: options.query,
+            terms,
+            commit_id,
+            indexed_chunks: index.chunks.len(),
+            total_matches,
+            chunks,
+        })
+    }
+
+    /// Reads the cached chunk index of a commit, building and caching it if needed
+    ///
+    /// A cache that can't be read is rebuilt. Failing to write the cache isn't an error,
+    /// the index is just built again next time.
+    fn load_or_build_chunk_index(&self, commit_id: &str) -> Result<ChunkIndex, String> {
+        let repo = self.open_git_repository()?;
+        let index_path = repo
+            .git_dir()
+            .join(INDEX_DIR_NAME)
+            .join(chunk_index_file_name(commit_id));
+        if let Ok(data) = std::fs::read(&index_path) {
+            match serde_json::from_slice::<ChunkIndex>(&data) {
+                Ok(index) if index.commit_id == commit_id => return Ok(index),
+                Ok(_) => tracing::warn!("Ignoring mismatched index {}", index_path.display()),
+                Err(e) => {
+                    tracing::warn!("Ignoring unreadable index {}: {}", index_path.display(), e)
+                }
+            }
+        }
+
+        tracing::info!("Building chunk index of commit {}", commit_id);
+        let index = build_chunk_index(&repo, commit_id)?;
+        if let Err(e) = write_index(&index_path, &index) {
+            tracing::warn!(
+                "Failed to cache chunk index at {}: {}",
+                index_path.display(),
+                e
+            );
+        }
+        Ok(index)
+    }
+}
+
+/// The file name of the cached chunk index of a commit
+pub(super) fn chunk_index_file_name(commit_id: &str) -> String {
+    format!(
+        "{}{}-{}.json",
+        CHUNK_INDEX_FILE_PREFIX, INDEX_FORMAT_VERSION, commit_id
+    )
+}
+
+/// Splits the text files of a commit into chunks and records their words
+///
+/// Hidden paths, symlinks, submodules, files with NUL bytes or invalid UTF-8 and very
+/// large files are skipped.
+fn build_chunk_index(repo: &gix::Repository, commit_id: &str) -> Result<ChunkIndex, String> {
+    let tree = peel_ref_to_commit(repo, commit_id)?
+        .tree()
+        .map_err(|e| format!("Failed to read tree of '{}': {}", commit_id, e))?;
+    let records = tree
+        .traverse()
+        .breadthfirst
+        .files()
+        .map_err(|e| format!("Failed to traverse tree at '{}': {}", commit_id, e))?;
+
+    let mut index = ChunkIndex {
+        commit_id: commit_id.to_string(),
+        files: Vec::new(),
+        chunks: Vec::new(),
+        postings: HashMap::new(),
+    };
+    let mut counts: HashMap<String, u32> = HashMap::new();
+    for record in records {
+        let file_path = record.filepath.to_string();
+        if !record.mode.is_blob() || !should_search_path(Path::new(&file_path), None, None, true)? {
+            continue;
+        }
+        let blob = repo
+            .find_blob(record.oid)
+            .map_err(|e| format!("Failed to read blob for '{}': {}", file_path, e))?;
+        if blob.data.len() > MAX_FILE_BYTES || blob.data.contains(&0) {
+            continue;
+        }
+        let Ok(content) = std::str::from_utf8(&blob.data) else {
+            continue;
+        };
+
+        let file_number = index.files.len() as u32;
+        let lines: Vec<&str> = content.lines().collect();
+        let path_terms = tokenize(&file_path);
+        for (start_line, end_line, symbol) in chunk_ranges(&file_path, content, lines.len()) {
+            counts.clear();
+            for term in lines[start_line - 1..end_line]
+                .iter()
+                .flat_map(|line| tokenize(line))
+            {
+                *counts.entry(term).or_default() += 1;
+            }
+            // Chunks of blank lines and punctuation only are left out
+            if counts.is_empty() {
+                continue;
+            }
+            for term in &path_terms {
+                *counts.entry(term.clone()).or_default() += 1;
+            }
+
+            let chunk_number = index.chunks.len() as u32;
+            let mut length = 0;
+            for (term, &count) in &counts {
+                length += count;
+                index
+                    .postings
+                    .entry(term.clone())
+                    .or_default()
+                    .push((chunk_number, count));
+            }
+            index.chunks.push(IndexedChunk {
+                file: file_number,
+                start_line: start_line as u32,
+                end_line: end_line as u32,
+                symbol,
+                length,
+            });
+        }
+        index.files.push(file_path);
+    }
+    Ok(index)
+}
+
+/// The chunks of a file as 1-based inclusive line ranges with the definition they hold
+///
+/// Source files of the supported languages get one chunk per innermost definition,
+/// starting at its doc comments; the lines between definitions and the files of other
+/// languages are cut into windows.
+fn chunk_ranges(
+    file_path: &str,
+    content: &str,
+    line_count: usize,
+) -> Vec<(usize, usize, Option<String>)> {
+    let symbols = SourceLanguage::from_path(Path::new(file_path))
+        .and_then(|language| collect_symbols(language, content, file_path, true).ok())
+        .unwrap_or_default();
+
+    let mut chunks = Vec::new();
+    let mut next_line = 1;
+    for (position, collected) in symbols.iter().enumerate() {
+        // Definitions with other definitions in their body are covered by those and by
+        // the windows around them
+        if symbols
+            .get(position + 1)
+            .is_some_and(|next| next.depth > collected.depth)
+        {
+            continue;
+        }
+        let start_line = collected.leading_start_line.max(next_line);
+        let end_line = collected.symbol.end_line.min(line_count);
+        if end_line < start_line {
+            continue;
+        }
+        push_windows(&mut chunks, next_line, start_line - 1, None);
+
+        let symbol = &collected.symbol;
+        let name = match &symbol.container {
+            Some(container) => format!("{}::{}", container, symbol.name),
+            None => symbol.name.clone(),
+        };
+        if end_line + 1 - start_line > MAX_CHUNK_LINES {
+            push_windows(&mut chunks, start_line, end_line, Some(name));
+        } else {
+            chunks.push((start_line, end_line, Some(name)));
+        }
+        next_line = end_line + 1;
+    }
+    push_windows(&mut chunks, next_line, line_count, None);
+    chunks
+}
+
+/// Cuts the lines `start_line..=end_line` into windows of [`WINDOW_LINES`] lines
+fn push_windows(
+    chunks: &mut Vec<(usize, usize, Option<String>)>,
+    start_line: usize,
+    end_line: usize,
+    symbol: Option<String>,
+) {
+    let mut window_start = start_line;
+    while window_start <= end_line {
+        let window_end = (window_start + WINDOW_LINES - 1).min(end_line);
+        chunks.push((window_start, window_end, symbol.clone()));
+        window_start = window_end + 1;
+    }
+}
+
+/// Splits text into normalized words
+///
+/// Each identifier yields its parts split at underscores and case changes and, if it has
+/// more than one part, the whole identifier without underscores: `parseConfigFile` yields
+/// `parse`, `config`, `file` and `parseconfigfile`. Words are lowercased and stemmed;
+/// single characters and stop words are dropped.
+fn tokenize(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for word in text.split(|c: char| !c.is_alphanumeric() && c != '_') {
+        let parts = identifier_parts(word);
+        if parts.len() > 1 {
+            push_term(&mut terms, &word.replace('_', "").to_lowercase());
+        }
+        for part in parts {
+            push_term(&mut terms, &part.to_lowercase());
+        }
+    }
+    terms
+}
+
+/// Adds the stem of a lowercased word to the terms unless it's too short or a stop word
+fn push_term(terms: &mut Vec<String>, word: &str) {
+    if word.chars().count() > 1 && !STOP_WORDS.contains(&word) {
+        terms.push(stem(word));
+    }
+}
+
+/// The parts of an identifier, split at underscores, lower-to-upper case changes and the
+/// end of acronyms: `HTTPServer_config` is `HTTP`, `Server` and `config`
+fn identifier_parts(word: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    for segment in word.split('_').filter(|segment| !segment.is_empty()) {
+        let chars: Vec<(usize, char)> = segment.char_indices().collect();
+        let mut part_start = 0;
+        for i in 1..chars.len() {
+            let (offset, c) = chars[i];
+            let previous = chars[i - 1].1;
+            let starts_word = c.is_uppercase()
+                && (previous.is_lowercase()
+                    || previous.is_numeric()
+                    || (previous.is_uppercase()
+                        && chars
+                            .get(i + 1)
+                            .is_some_and(|(_, next)| next.is_lowercase())));
+            if starts_word {
+                parts.push(&segment[part_start..offset]);
+                part_start = offset;
+            }
+        }
+        parts.push(&segment[part_start..]);
+    }
+    parts
+}
+
+/// Strips common English suffixes from a lowercased word, so "parses", "parsed" and
+/// "parsing" all become "pars" like "parse"
+///
+/// Deliberately crude: it only has to map a word and its inflections to the same term,
+/// not produce a real stem. Stems keep at least three characters.
+fn stem(word: &str) -> String {
+    if !word.is_ascii() {
+        return word.to_string();
+    }
+    let mut stem = word;
+    for suffix in ["ing", "ed", "es", "s"] {
+        if let Some(stripped) = stem.strip_suffix(suffix) {
+            if stripped.len() >= 3 && !(suffix == "s" && stripped.ends_with('s')) {
+                stem = stripped;
+                break;
+            }
+        }
+    }
+    if let Some(stripped) = stem.strip_suffix('e') {
+        if stripped.len() >= 3 {
+            stem = stripped;
+        }
+    }
+    stem.to_string()
+}
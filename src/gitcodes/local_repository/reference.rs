@@ -1,7 +1,9 @@
 //! This module defines structs for git references.
 
+use crate::gitcodes::repository_manager::providers::ReferenceInfo;
+
 /// Object type, typically "commit" for references.
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum ObjectType {
     #[serde(rename = "commit")]
     Commit,
@@ -13,8 +15,19 @@ pub enum ObjectType {
     Blob,
 }
 
+impl From<gix::object::Kind> for ObjectType {
+    fn from(kind: gix::object::Kind) -> Self {
+        match kind {
+            gix::object::Kind::Commit => ObjectType::Commit,
+            gix::object::Kind::Tag => ObjectType::Tag,
+            gix::object::Kind::Tree => ObjectType::Tree,
+            gix::object::Kind::Blob => ObjectType::Blob,
+        }
+    }
+}
+
 /// The target object of a git reference, including its SHA and type.
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct RefObject {
     /// The SHA1 hash of the target object
     pub sha: String,
@@ -47,3 +60,74 @@ impl GitRefObject {
         }
     }
 }
+
+/// The tag object behind an annotated tag.
+///
+/// Lightweight tags point directly at a commit and have no such details.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TagDetails {
+    /// Name of the person who created the tag
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tagger_name: Option<String>,
+    /// Email of the person who created the tag
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tagger_email: Option<String>,
+    /// When the tag was created (ISO 8601)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    /// The tag message, without a trailing newline
+    pub message: String,
+}
+
+/// Fills in what a reference points to: the object type, the peeled commit and its date,
+/// and the tagger and message of annotated tags
+///
+/// # Parameters
+///
+/// * `repo` - The repository containing the reference
+/// * `id` - The object the reference points to directly
+/// * `reference` - The reference to update; `commit_id` is replaced by the peeled commit
+///
+/// # Returns
+///
+/// * `Result<(), String>` - Success or an error message if the objects cannot be read
+pub(crate) fn describe_ref_target(
+    repo: &gix::Repository,
+    id: gix::ObjectId,
+    reference: &mut ReferenceInfo,
+) -> Result<(), String> {
+    let object = repo
+        .find_object(id)
+        .map_err(|e| format!("Failed to read object {}: {}", id, e))?;
+    reference.object = Some(RefObject {
+        sha: id.to_hex().to_string(),
+        object_type: object.kind.into(),
+    });
+
+    if object.kind == gix::object::Kind::Tag {
+        let tag = object.clone().into_tag();
+        let decoded = tag
+            .decode()
+            .map_err(|e| format!("Failed to decode tag {}: {}", id, e))?;
+        let tagger = decoded.tagger.as_ref();
+        reference.tag = Some(TagDetails {
+            tagger_name: tagger.map(|tagger| tagger.name.to_string()),
+            tagger_email: tagger.map(|tagger| tagger.email.to_string()),
+            date: tagger
+                .and_then(|tagger| tagger.time().ok())
+                .map(|time| time.format(gix::date::time::format::ISO8601_STRICT)),
+            message: decoded.message.to_string().trim_end().to_string(),
+        });
+    }
+
+    let commit = object
+        .peel_to_commit()
+        .map_err(|e| format!("Failed to peel {} to a commit: {}", id, e))?;
+    reference.commit_id = commit.id.to_hex().to_string();
+    reference.commit_date = commit
+        .time()
+        .ok()
+        .map(|time| time.format(gix::date::time::format::ISO8601_STRICT));
+
+    Ok(())
+}
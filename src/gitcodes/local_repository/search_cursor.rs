@@ -0,0 +1,88 @@
+//! Opaque cursors for paging through code search results of a fixed commit
+//!
+//! `skip`/`take` pagination runs the whole search again for every page, against whatever
+//! the repository contains at that moment, so results shift when a clone is updated
+//! between two pages. A cursor pins the pages of a search to the commit the first page
+//! was searched at. It records the commit id, a hash of the query it belongs to and the
+//! position after the page, so the next page searches the same tree from the object
+//! database and starts exactly where the previous page ended.
+//!
+//! The cursor is the hex encoding of `v1:<commit id>:<query hash>:<position>`. Callers
+//! should treat it as opaque.
+
+use std::fmt::Write;
+
+/// Version prefix of the cursor format
+const CURSOR_VERSION: &str = "v1";
+
+/// The state of a paginated code search, encoded as an opaque string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchCursor {
+    /// Hex id of the commit whose tree is searched
+    pub commit_id: String,
+
+    /// Hash of the search parameters, except the pagination ones
+    pub query_hash: u64,
+
+    /// Number of result lines before the next page
+    pub position: usize,
+}
+
+impl SearchCursor {
+    /// Encodes the cursor as an opaque string
+    pub fn encode(&self) -> String {
+        let text = format!(
+            "{}:{}:{:016x}:{}",
+            CURSOR_VERSION, self.commit_id, self.query_hash, self.position
+        );
+        let mut cursor = String::with_capacity(text.len() * 2);
+        for byte in text.bytes() {
+            let _ = write!(cursor, "{:02x}", byte);
+        }
+        cursor
+    }
+
+    /// Decodes a cursor returned by [`SearchCursor::encode`]
+    ///
+    /// # Parameters
+    ///
+    /// * `cursor` - The opaque cursor string
+    ///
+    /// # Returns
+    ///
+    /// * `Result<SearchCursor, String>` - The decoded cursor or an error message
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string isn't a cursor of this version.
+    pub fn decode(cursor: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid cursor '{}': not returned by a code search", cursor);
+
+        if cursor.len() % 2 != 0 || !cursor.is_ascii() {
+            return Err(invalid());
+        }
+        let bytes = (0..cursor.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(&cursor[index..index + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| invalid())?;
+        let text = String::from_utf8(bytes).map_err(|_| invalid())?;
+
+        let parts: Vec<&str> = text.split(':').collect();
+        let [version, commit_id, query_hash, position] = parts[..] else {
+            return Err(invalid());
+        };
+        if version != CURSOR_VERSION
+            || commit_id.is_empty()
+            || !commit_id.chars().all(|c| c.is_ascii_hexdigit())
+        {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            commit_id: commit_id.to_string(),
+            query_hash: u64::from_str_radix(query_hash, 16).map_err(|_| invalid())?,
+            position: position.parse().map_err(|_| invalid())?,
+        })
+    }
+}
@@ -4,6 +4,7 @@
 
 use lumin::search::SearchResultLine as LuminSearchResultLine;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 /// Parameters for creating a new CodeSearchResult
@@ -25,7 +26,7 @@ pub struct CodeSearchParams {
 ///
 /// Contains all matches found along with the search parameters that were used.
 /// This provides a complete picture of both the search configuration and results.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CodeSearchResult {
     /// Total number of lines that matched the search pattern
     ///
@@ -61,6 +62,13 @@ pub struct CodeSearchResult {
 
     /// Number of lines of context included after each match
     pub after_context: Option<usize>,
+
+    /// Cursor for the next page of a search pinned to a commit (if there are more results)
+    ///
+    /// Passing it back with the same search continues after the last line of this page,
+    /// against the same commit even if the repository was updated in between.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 impl CodeSearchResult {
@@ -81,6 +89,7 @@ impl CodeSearchResult {
             exclude_globs: params.exclude_globs,
             before_context: params.before_context,
             after_context: params.after_context,
+            next_cursor: None,
         }
     }
 
@@ -93,3 +102,101 @@ impl CodeSearchResult {
             .map_err(|e| format!("Failed to convert search results to JSON: {}", e))
     }
 }
+
+/// Value used for files without an extension in [`SearchFacets::extensions`]
+const NO_EXTENSION_FACET: &str = "(none)";
+
+/// Value used for files at the repository root in [`SearchFacets::directories`]
+const ROOT_DIRECTORY_FACET: &str = ".";
+
+/// Number of matched lines for one value of a facet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FacetCount {
+    /// The file path, top-level directory or extension
+    pub value: String,
+
+    /// Number of matched lines with this value
+    pub count: usize,
+}
+
+/// Match counts of a code search broken down by file, top-level directory and extension
+///
+/// Only matched lines are counted, context lines are not, and no line content is
+/// included. Each facet lists its values with the most matched lines first, up to the
+/// requested number of values.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchFacets {
+    /// The search pattern that was used
+    pub pattern: String,
+
+    /// Total number of lines that matched the search pattern
+    pub total_match_line_number: usize,
+
+    /// Total number of files with at least one matched line
+    pub total_files: usize,
+
+    /// Files with the most matched lines
+    pub files: Vec<FacetCount>,
+
+    /// Top-level directories with the most matched lines, `"."` for files at the root
+    pub directories: Vec<FacetCount>,
+
+    /// File extensions with the most matched lines, `"(none)"` for files without one
+    pub extensions: Vec<FacetCount>,
+}
+
+impl CodeSearchResult {
+    /// Counts the matched lines per file, top-level directory and extension
+    ///
+    /// # Parameters
+    ///
+    /// * `top_n` - Maximum number of values listed per facet
+    ///
+    /// # Returns
+    ///
+    /// * `SearchFacets` - The match counts, most matched lines first and ties by value
+    pub fn facets(&self, top_n: usize) -> SearchFacets {
+        let mut files: HashMap<String, usize> = HashMap::new();
+        let mut directories: HashMap<String, usize> = HashMap::new();
+        let mut extensions: HashMap<String, usize> = HashMap::new();
+        let mut total_match_line_number = 0;
+
+        for line in self.matches.iter().filter(|line| !line.is_context) {
+            total_match_line_number += 1;
+            let path = line.file_path.to_string_lossy().replace('\\', "/");
+            let directory = match path.split_once('/') {
+                Some((directory, _)) => directory.to_string(),
+                None => ROOT_DIRECTORY_FACET.to_string(),
+            };
+            let extension = line
+                .file_path
+                .extension()
+                .map(|extension| extension.to_string_lossy().to_string())
+                .unwrap_or_else(|| NO_EXTENSION_FACET.to_string());
+
+            *directories.entry(directory).or_default() += 1;
+            *extensions.entry(extension).or_default() += 1;
+            *files.entry(path).or_default() += 1;
+        }
+
+        SearchFacets {
+            pattern: self.pattern.clone(),
+            total_match_line_number,
+            total_files: files.len(),
+            files: top_facet_counts(files, top_n),
+            directories: top_facet_counts(directories, top_n),
+            extensions: top_facet_counts(extensions, top_n),
+        }
+    }
+}
+
+/// Sorts the counts of a facet, most matched lines first, and keeps the first `top_n`
+fn top_facet_counts(counts: HashMap<String, usize>, top_n: usize) -> Vec<FacetCount> {
+    let mut counts: Vec<FacetCount> = counts
+        .into_iter()
+        .map(|(value, count)| FacetCount { value, count })
+        .collect();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+    counts.truncate(top_n);
+    counts
+}
@@ -0,0 +1,282 @@
+//! Working-tree status of local repositories
+//!
+//! Reports the uncommitted work in a checkout like `git status`: changes staged in the
+//! index, changes in the working tree that are not staged yet, and untracked files.
+//! Each file can come with its diff against `HEAD` (`git diff HEAD`), so the work in
+//! progress can be reviewed without committing it first.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::history::{DiffHunk, count_line_changes, diff_hunks, read_text_blob};
+use super::{CodeSearchOptions, CodeSearchResult, LocalRepository};
+
+/// Default number of context lines around each diff hunk
+const DEFAULT_CONTEXT_LINES: u32 = 3;
+
+/// Options for reading the working-tree status
+#[derive(Debug, Clone, Default)]
+pub struct StatusOptions {
+    /// Whether untracked files are listed
+    pub include_untracked: bool,
+    /// Whether the diff hunks against `HEAD` are included for each text file
+    pub include_diffs: bool,
+    /// Number of context lines around each hunk (default 3)
+    pub context_lines: Option<u32>,
+}
+
+/// Status of a single changed file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileStatus {
+    /// Path of the file relative to the repository root
+    pub path: String,
+    /// Change staged in the index relative to `HEAD`: "added", "deleted" or "modified"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub staged: Option<String>,
+    /// Change in the working tree that is not staged: "added" (intent to add), "deleted",
+    /// "modified", "type_changed" or "conflicted"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unstaged: Option<String>,
+    /// Whether the file is not tracked at all
+    pub untracked: bool,
+    /// Whether the file is binary on either side, in which case no line counts or hunks are given
+    pub binary: bool,
+    /// Lines added in the working tree compared to `HEAD`
+    pub lines_added: u32,
+    /// Lines removed in the working tree compared to `HEAD`
+    pub lines_removed: u32,
+    /// Diff hunks of the working tree against `HEAD`, when diffs were requested
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub hunks: Vec<DiffHunk>,
+}
+
+/// Uncommitted changes of a local repository
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkingTreeStatus {
+    /// The checked-out branch, or `None` if `HEAD` is detached
+    pub branch: Option<String>,
+    /// The commit `HEAD` points to, or `None` if the branch has no commits yet
+    pub head_commit: Option<String>,
+    /// Whether there are no staged, unstaged or untracked changes
+    pub clean: bool,
+    /// Number of files with staged changes
+    pub staged_count: usize,
+    /// Number of files with unstaged changes
+    pub unstaged_count: usize,
+    /// Number of untracked files
+    pub untracked_count: usize,
+    /// The changed files, sorted by path
+    pub files: Vec<FileStatus>,
+}
+
+impl LocalRepository {
+    /// Reads the staged, unstaged and untracked changes of the working tree
+    ///
+    /// Renames are not tracked: a renamed file shows up as a deletion of the old path and an
+    /// addition of the new one. Files ignored through `.gitignore` are never listed.
+    ///
+    /// # Parameters
+    ///
+    /// * `options` - Which changes to list and whether to include the diffs against `HEAD`
+    ///
+    /// # Returns
+    ///
+    /// * `Result<WorkingTreeStatus, String>` - The status or an error message
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the repository cannot be opened, is bare, or its
+    /// index or working tree cannot be read.
+    pub fn working_tree_status(&self, options: StatusOptions) -> Result<WorkingTreeStatus, String> {
+        let repo = self.open_git_repository()?;
+        let workdir = self.working_tree_dir(&repo)?;
+
+        let mut files = collect_status(&repo, options.include_untracked)?;
+
+        let head_tree = repo
+            .head_tree_id_or_empty()
+            .map_err(|e| format!("Failed to read HEAD: {}", e))?
+            .object()
+            .map_err(|e| format!("Failed to read HEAD tree: {}", e))?
+            .into_tree();
+        let context_lines = options.context_lines.unwrap_or(DEFAULT_CONTEXT_LINES);
+        for file in files.values_mut() {
+            let old_id = head_tree
+                .lookup_entry_by_path(&file.path)
+                .map_err(|e| format!("Failed to look up '{}' in HEAD: {}", file.path, e))?
+                .filter(|entry| entry.mode().is_blob())
+                .map(|entry| entry.object_id());
+            let old = read_text_blob(&repo, old_id)?;
+            let new = read_worktree_text(&workdir.join(&file.path))?;
+            let (Some(old), Some(new)) = (old, new) else {
+                file.binary = true;
+                continue;
+            };
+            (file.lines_added, file.lines_removed) = count_line_changes(&old, &new);
+            if options.include_diffs {
+                file.hunks = diff_hunks(&old, &new, context_lines);
+            }
+        }
+
+        let files: Vec<FileStatus> = files.into_values().collect();
+        let staged_count = files.iter().filter(|file| file.staged.is_some()).count();
+        let unstaged_count = files.iter().filter(|file| file.unstaged.is_some()).count();
+        let untracked_count = files.iter().filter(|file| file.untracked).count();
+
+        let branch = repo
+            .head_name()
+            .map_err(|e| format!("Failed to read HEAD: {}", e))?
+            .map(|name| name.shorten().to_string());
+        let head_commit = repo
+            .head_id()
+            .ok()
+            .map(|id| id.detach().to_hex().to_string());
+
+        Ok(WorkingTreeStatus {
+            branch,
+            head_commit,
+            clean: files.is_empty(),
+            staged_count,
+            unstaged_count,
+            untracked_count,
+            files,
+        })
+    }
+
+    /// Lists the files with uncommitted changes that still exist in the working tree
+    ///
+    /// This covers staged, unstaged and untracked files; deleted files are left out since
+    /// there is nothing left to read.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<String>, String>` - The repository-relative paths, sorted
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error under the same conditions as
+    /// [`LocalRepository::working_tree_status`].
+    pub fn changed_files(&self) -> Result<Vec<String>, String> {
+        let repo = self.open_git_repository()?;
+        let workdir = self.working_tree_dir(&repo)?;
+
+        Ok(collect_status(&repo, true)?
+            .into_keys()
+            .filter(|path| workdir.join(path).is_file())
+            .collect())
+    }
+
+    /// Performs a code search restricted to the files with uncommitted changes
+    ///
+    /// The worktree is searched like [`LocalRepository::perform_code_search`] does, with
+    /// the include and exclude globs of `options`, but only the changed files are read,
+    /// so a large diff costs no more than reading its files.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<CodeSearchResult, String>` - The matches in changed files or an error message
+    pub async fn perform_changed_files_search(
+        &self,
+        options: CodeSearchOptions,
+    ) -> Result<CodeSearchResult, String> {
+        let changed_files = self.changed_files()?;
+        self.perform_code_search_in_files(changed_files, None, options)
+            .await
+    }
+
+    /// Returns the working tree of the repository, failing for bare repositories
+    fn working_tree_dir(&self, repo: &gix::Repository) -> Result<PathBuf, String> {
+        repo.workdir().map(Path::to_path_buf).ok_or_else(|| {
+            format!(
+                "Repository at {} has no working tree",
+                self.repository_location.display()
+            )
+        })
+    }
+}
+
+/// Runs the status of the repository and merges its events into one entry per path
+fn collect_status(
+    repo: &gix::Repository,
+    include_untracked: bool,
+) -> Result<BTreeMap<String, FileStatus>, String> {
+    use gix::diff::index::Change;
+    use gix::status::index_worktree::iter::Summary;
+
+    let untracked = if include_untracked {
+        gix::status::UntrackedFiles::Files
+    } else {
+        gix::status::UntrackedFiles::None
+    };
+    let items = repo
+        .status(gix::progress::Discard)
+        .map_err(|e| format!("Failed to read repository status: {}", e))?
+        .untracked_files(untracked)
+        .index_worktree_rewrites(None)
+        .tree_index_track_renames(gix::status::tree_index::TrackRenames::Disabled)
+        .into_iter(None)
+        .map_err(|e| format!("Failed to read repository status: {}", e))?;
+
+    let mut files = BTreeMap::new();
+    for item in items {
+        let item = item.map_err(|e| format!("Failed to read repository status: {}", e))?;
+        let path = item.location().to_string();
+        match item {
+            gix::status::Item::TreeIndex(change) => {
+                let kind = match change {
+                    Change::Addition { .. } => "added",
+                    Change::Deletion { .. } => "deleted",
+                    Change::Modification { .. } | Change::Rewrite { .. } => "modified",
+                };
+                file_entry(&mut files, path).staged = Some(kind.to_string());
+            }
+            gix::status::Item::IndexWorktree(change) => {
+                let kind = match change.summary() {
+                    None => continue,
+                    Some(Summary::Added) => {
+                        file_entry(&mut files, path).untracked = true;
+                        continue;
+                    }
+                    Some(Summary::IntentToAdd) => "added",
+                    Some(Summary::Removed) => "deleted",
+                    Some(Summary::Modified | Summary::Renamed | Summary::Copied) => "modified",
+                    Some(Summary::TypeChange) => "type_changed",
+                    Some(Summary::Conflict) => "conflicted",
+                };
+                file_entry(&mut files, path).unstaged = Some(kind.to_string());
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Returns the entry for `path`, creating an unchanged one if needed
+fn file_entry(files: &mut BTreeMap<String, FileStatus>, path: String) -> &mut FileStatus {
+    files.entry(path.clone()).or_insert_with(|| FileStatus {
+        path,
+        staged: None,
+        unstaged: None,
+        untracked: false,
+        binary: false,
+        lines_added: 0,
+        lines_removed: 0,
+        hunks: Vec::new(),
+    })
+}
+
+/// Reads a working-tree file as text, returning `None` for binary content
+///
+/// A file that doesn't exist (or isn't a regular file) reads as empty, like a missing blob.
+fn read_worktree_text(path: &Path) -> Result<Option<String>, String> {
+    if !path.is_file() {
+        return Ok(Some(String::new()));
+    }
+    let data =
+        std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    if data.contains(&0) {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(&data).into_owned()))
+}
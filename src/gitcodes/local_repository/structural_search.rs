@@ -0,0 +1,482 @@
+//! Structural code search with AST patterns
+//!
+//! Regex can't express "any call to `unwrap()` on the result of `File::open`". A
+//! structural search takes a pattern written as code of the searched language, like
+//! `File::open($PATH).unwrap()`, parses it with the same tree-sitter grammar as the
+//! files, and reports the syntax nodes of the files that have the same shape.
+//!
+//! Patterns follow the ast-grep conventions:
+//!
+//! - `$NAME` matches any single node (an expression, type, identifier, ...) and records
+//!   it under `NAME`. When the same name appears more than once, all occurrences must
+//!   match the same text.
+//! - `$$$NAME` matches zero or more nodes in a list, like the arguments of a call or the
+//!   statements of a block. `$_` and `$$$` match without recording anything.
+//! - Everything else must match exactly, except for whitespace and comments.
+//!
+//! Rust, Go and Python don't allow `$` in identifiers, so metavariables are rewritten to
+//! identifiers starting with `µ` before the pattern is parsed.
+
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use tree_sitter::{Node, Tree};
+
+use super::LocalRepository;
+use super::syntax::{SourceLanguage, node_text, parse_source, shorten_text};
+
+/// Default maximum number of source lines returned for one match
+const DEFAULT_MAX_LINES_PER_MATCH: usize = 20;
+
+/// Metavariable values are shortened to this many characters
+const MAX_METAVARIABLE_CHARS: usize = 200;
+
+/// Options for a structural code search
+#[derive(Debug, Clone)]
+pub struct StructuralSearchOptions {
+    /// The pattern, code of `language` with `$NAME`/`$$$NAME` metavariables
+    pub pattern: String,
+    /// The language of the pattern and the files to search; TypeScript patterns also
+    /// search TSX and JavaScript files and vice versa
+    pub language: SourceLanguage,
+    /// Branch, tag, or commit whose tree is searched instead of the worktree
+    pub ref_name: Option<String>,
+    /// Glob patterns of the files to search, as in code search
+    pub include_globs: Option<Vec<String>>,
+    /// Directories or glob patterns to exclude, as in code search
+    pub exclude_globs: Option<Vec<String>>,
+    /// Number of matches to skip (for pagination)
+    pub skip: Option<usize>,
+    /// Maximum number of matches to return (for pagination)
+    pub take: Option<usize>,
+    /// Maximum number of source lines returned for one match (default 20)
+    pub max_lines_per_match: Option<usize>,
+}
+
+/// A syntax node matching a structural pattern
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StructuralMatch {
+    /// Path of the file relative to the repository root
+    pub file_path: String,
+    /// First line of the node (1-based)
+    pub start_line: usize,
+    /// Column where the node starts (1-based, in bytes)
+    pub start_column: usize,
+    /// Last line of the node (1-based, inclusive)
+    pub end_line: usize,
+    /// Column after the end of the node (1-based, in bytes)
+    pub end_column: usize,
+    /// The source lines from `start_line`, at most `max_lines_per_match` of them
+    pub lines: Vec<String>,
+    /// The text each named metavariable matched
+    pub metavariables: BTreeMap<String, String>,
+}
+
+/// Result of a structural code search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StructuralSearchResult {
+    /// The pattern as given
+    pub pattern: String,
+    /// The language of the pattern
+    pub language: SourceLanguage,
+    /// Number of matches before pagination
+    pub total_matches: usize,
+    /// The matches sorted by file path and position
+    pub matches: Vec<StructuralMatch>,
+    /// Glob patterns used to include files in the search (if any)
+    pub include_globs: Option<Vec<String>>,
+    /// Directories or glob patterns excluded from the search (if any)
+    pub exclude_globs: Option<Vec<String>>,
+}
+
+impl LocalRepository {
+    /// Finds the syntax nodes matching a structural pattern
+    ///
+    /// # Parameters
+    ///
+    /// * `options` - The pattern and its language, the files to search and pagination
+    ///
+    /// # Returns
+    ///
+    /// * `Result<StructuralSearchResult, String>` - The matches or an error message
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern isn't valid code of its language or is a lone
+    /// metavariable, or if the ref or a glob can't be resolved.
+    pub async fn structural_search(
+        &self,
+        options: StructuralSearchOptions,
+    ) -> Result<StructuralSearchResult, String> {
+        if let Err(e) = self.validate() {
+            return Err(format!("Repository validation failed: {}", e));
+        }
+        let max_lines = options
+            .max_lines_per_match
+            .unwrap_or(DEFAULT_MAX_LINES_PER_MATCH)
+            .max(1);
+
+        // The pattern is parsed with the grammar of each searched file's language
+        let mut patterns = HashMap::new();
+        patterns.insert(
+            options.language,
+            Pattern::parse(&options.pattern, options.language)?,
+        );
+        let required_words = patterns[&options.language].required_words();
+
+        let mut matches = Vec::new();
+        self.visit_source_files(
+            options.ref_name.as_deref(),
+            options.include_globs.as_ref(),
+            options.exclude_globs.as_ref(),
+            |file| {
+                if !same_family(file.language, options.language)
+                    || !required_words
+                        .iter()
+                        .all(|word| file.content.contains(word.as_str()))
+                {
+                    return Ok(());
+                }
+                if let Entry::Vacant(entry) = patterns.entry(file.language) {
+                    entry.insert(Pattern::parse(&options.pattern, file.language)?);
+                }
+                let pattern = &patterns[&file.language];
+                let file_path = file.path.to_string_lossy().replace('\\', "/");
+                let tree = parse_source(file.language, &file.content)?;
+                let source_lines: Vec<&str> = file.content.lines().collect();
+
+                pattern.find_matches(&tree, &file.content, |node, metavariables| {
+                    let (start, end) = (node.start_position(), node.end_position());
+                    let last_line = end.row.min(start.row + max_lines - 1);
+                    matches.push(StructuralMatch {
+                        file_path: file_path.clone(),
+                        start_line: start.row + 1,
+                        start_column: start.column + 1,
+                        end_line: end.row + 1,
+                        end_column: end.column + 1,
+                        lines: source_lines
+                            .get(start.row..=last_line.min(source_lines.len().saturating_sub(1)))
+                            .unwrap_or_default()
+                            .iter()
+                            .map(|line| line.to_string())
+                            .collect(),
+                        metavariables: metavariables
+                            .into_iter()
+                            .map(|(name, value)| {
+                                (name, shorten_text(&value, MAX_METAVARIABLE_CHARS))
+                            })
+                            .collect(),
+                    });
+                });
+                Ok(())
+            },
+        )?;
+
+        matches.sort_by(|a, b| {
+            a.file_path
+                .cmp(&b.file_path)
+                .then(a.start_line.cmp(&b.start_line))
+                .then(a.start_column.cmp(&b.start_column))
+        });
+        let total_matches = matches.len();
+        let matches = matches
+            .into_iter()
+            .skip(options.skip.unwrap_or(0))
+            .take(options.take.unwrap_or(usize::MAX))
+            .collect();
+
+        Ok(StructuralSearchResult {
+            pattern: options.pattern,
+            language: options.language,
+            total_matches,
+            matches,
+            include_globs: options.include_globs,
+            exclude_globs: options.exclude_globs,
+        })
+    }
+}
+
+/// Whether a pattern of one language is matched against files of another
+///
+/// TypeScript and TSX share their node kinds, so their patterns are interchangeable.
+fn same_family(file_language: SourceLanguage, pattern_language: SourceLanguage) -> bool {
+    let is_typescript =
+        |language| matches!(language, SourceLanguage::TypeScript | SourceLanguage::Tsx);
+    file_language == pattern_language
+        || (is_typescript(file_language) && is_typescript(pattern_language))
+}
+
+/// A metavariable in a pattern
+#[derive(Debug, PartialEq, Eq)]
+enum Metavariable<'p> {
+    /// `$NAME` or `$_`: one node
+    Single(Option<&'p str>),
+    /// `$$$NAME` or `$$$`: any number of nodes
+    Multiple(Option<&'p str>),
+}
+
+/// A pattern parsed with the grammar of one language
+struct Pattern {
+    /// The pattern with its metavariables rewritten to identifiers of the language
+    source: String,
+    tree: Tree,
+    /// The character metavariables start with in `source`
+    marker: char,
+}
+
+impl Pattern {
+    /// Parses a pattern, trying it as written and as a statement ending in `;`
+    fn parse(pattern: &str, language: SourceLanguage) -> Result<Self, String> {
+        let marker = match language {
+            SourceLanguage::TypeScript | SourceLanguage::Tsx => '$',
+            SourceLanguage::Rust | SourceLanguage::Go | SourceLanguage::Python => 'µ',
+        };
+        let rewritten = rewrite_metavariables(pattern.trim(), marker);
+
+        for source in [rewritten.clone(), format!("{};", rewritten)] {
+            let tree = parse_source(language, &source)?;
+            if tree.root_node().has_error() {
+                continue;
+            }
+            let pattern = Self {
+                source,
+                tree,
+                marker,
+            };
+            let root = pattern.root();
+            if root.child_count() == 0 && pattern.metavariable(root).is_some() {
+                return Err(
+                    "The pattern must contain more than a metavariable, e.g. '$X.unwrap()'"
+                        .to_string(),
+                );
+            }
+            return Ok(pattern);
+        }
+        Err(format!(
+            "The pattern is not valid {:?} code: {}",
+            language, pattern
+        ))
+    }
+
+    /// The node the pattern stands for, below the source file and statement wrappers
+    fn root(&self) -> Node<'_> {
+        let mut node = self.tree.root_node();
+        loop {
+            let mut cursor = node.walk();
+            let mut children = node
+                .named_children(&mut cursor)
+                .filter(|child| !child.is_extra());
+            let (Some(child), None) = (children.next(), children.next()) else {
+                return node;
+            };
+            if statement_text(node, &self.source) != statement_text(child, &self.source) {
+                return node;
+            }
+            node = child;
+        }
+    }
+
+    /// The words a file must contain to possibly match: the identifiers and keywords of
+    /// the pattern that aren't metavariables
+    fn required_words(&self) -> Vec<String> {
+        let mut words = Vec::new();
+        let mut stack = vec![self.root()];
+        while let Some(node) = stack.pop() {
+            if self.metavariable(node).is_some() {
+                continue;
+            }
+            if node.child_count() == 0 {
+                let text = node_text(node, &self.source);
+                if !text.is_empty() && text.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                    words.push(text.to_string());
+                }
+            }
+            let mut cursor = node.walk();
+            stack.extend(node.children(&mut cursor));
+        }
+        words
+    }
+
+    /// The metavariable a pattern node stands for, if it is one
+    fn metavariable(&self, node: Node) -> Option<Metavariable<'_>> {
+        let text = node_text(node, &self.source);
+        let marker_len = self.marker.len_utf8();
+        let multiple = text.starts_with(&self.marker.to_string().repeat(3));
+        let name = if multiple {
+            &text[3 * marker_len..]
+        } else {
+            text.strip_prefix(self.marker)?
+        };
+        let is_name = name
+            .chars()
+            .enumerate()
+            .all(|(i, c)| c.is_ascii_uppercase() || c == '_' || (i > 0 && c.is_ascii_digit()));
+        if !is_name || (name.is_empty() && !multiple) {
+            return None;
+        }
+        let name = Some(name).filter(|name| !name.is_empty() && *name != "_");
+        Some(if multiple {
+            Metavariable::Multiple(name)
+        } else {
+            Metavariable::Single(name)
+        })
+    }
+
+    /// Calls `found` with every node of a tree that matches the pattern and the values of
+    /// its named metavariables
+    fn find_matches(
+        &self,
+        tree: &Tree,
+        source: &str,
+        mut found: impl FnMut(Node, BTreeMap<String, String>),
+    ) {
+        let root = self.root();
+        let mut cursor = tree.walk();
+        loop {
+            let node = cursor.node();
+            if node.is_named() && node.kind() == root.kind() {
+                let mut metavariables = BTreeMap::new();
+                if self.match_node(root, node, source, &mut metavariables) {
+                    found(node, metavariables);
+                }
+            }
+
+            if cursor.goto_first_child() {
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Whether a node matches a pattern node, recording the metavariables it binds
+    fn match_node(
+        &self,
+        pattern: Node,
+        node: Node,
+        source: &str,
+        metavariables: &mut BTreeMap<String, String>,
+    ) -> bool {
+        if let Some(Metavariable::Single(name)) = self.metavariable(pattern) {
+            return node.is_named() && bind(metavariables, name, node_text(node, source));
+        }
+        if pattern.kind() != node.kind() {
+            return false;
+        }
+        if pattern.child_count() == 0 {
+            return node_text(pattern, &self.source) == node_text(node, source);
+        }
+        self.match_list(
+            &significant_children(pattern),
+            &significant_children(node),
+            source,
+            metavariables,
+        )
+    }
+
+    /// Whether a list of nodes matches a list of pattern nodes
+    fn match_list(
+        &self,
+        patterns: &[Node],
+        nodes: &[Node],
+        source: &str,
+        metavariables: &mut BTreeMap<String, String>,
+    ) -> bool {
+        let Some((pattern, rest)) = patterns.split_first() else {
+            return nodes.is_empty();
+        };
+
+        if let Some(Metavariable::Multiple(name)) = self.metavariable(*pattern) {
+            for taken in 0..=nodes.len() {
+                let mut attempt = metavariables.clone();
+                let text = match (nodes.first(), nodes[..taken].last()) {
+                    (Some(first), Some(last)) => &source[first.start_byte()..last.end_byte()],
+                    _ => "",
+                };
+                if bind(&mut attempt, name, text)
+                    && self.match_list(rest, &nodes[taken..], source, &mut attempt)
+                {
+                    *metavariables = attempt;
+                    return true;
+                }
+            }
+            return false;
+        }
+
+        if let Some((node, nodes_rest)) = nodes.split_first() {
+            let mut attempt = metavariables.clone();
+            if self.match_node(*pattern, *node, source, &mut attempt)
+                && self.match_list(rest, nodes_rest, source, &mut attempt)
+            {
+                *metavariables = attempt;
+                return true;
+            }
+        }
+
+        // A trailing comma in the code, as in `f(\n    a,\n)` for `f($A)`
+        if let [comma, closing @ ..] = nodes {
+            let is_trailing =
+                comma.kind() == "," && closing.first().is_none_or(|closing| !closing.is_named());
+            if is_trailing
+                && pattern.kind() != ","
+                && self.match_list(patterns, closing, source, metavariables)
+            {
+                return true;
+            }
+        }
+
+        // A separator before `$$$` that matches nothing, as in `f($A, $$$REST)` for `f(a)`
+        let before_multiple = rest.first().is_some_and(|next| {
+            matches!(self.metavariable(*next), Some(Metavariable::Multiple(_)))
+        });
+        !pattern.is_named()
+            && before_multiple
+            && self.match_list(rest, nodes, source, metavariables)
+    }
+}
+
+/// Records the text a metavariable matched, failing if it already matched something else
+fn bind(metavariables: &mut BTreeMap<String, String>, name: Option<&str>, text: &str) -> bool {
+    let Some(name) = name else {
+        return true;
+    };
+    match metavariables.get(name) {
+        Some(bound) => bound == text,
+        None => {
+            metavariables.insert(name.to_string(), text.to_string());
+            true
+        }
+    }
+}
+
+/// The text of a node without surrounding whitespace and a trailing `;`
+fn statement_text<'s>(node: Node, source: &'s str) -> &'s str {
+    let text = node_text(node, source).trim();
+    text.strip_suffix(';').unwrap_or(text).trim_end()
+}
+
+/// The children of a node without comments
+fn significant_children(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .filter(|child| !child.is_extra())
+        .collect()
+}
+
+/// Rewrites `$NAME`, `$_` and `$$$NAME` to start with `marker` instead of `$`
+fn rewrite_metavariables(pattern: &str, marker: char) -> String {
+    if marker == '$' {
+        return pattern.to_string();
+    }
+    let metavariable = regex::Regex::new(r"\$\$\$([A-Z_][A-Z0-9_]*)?|\$([A-Z_][A-Z0-9_]*)")
+        .expect("the metavariable regex is valid");
+    metavariable
+        .replace_all(pattern, |captures: &regex::Captures| {
+            captures[0].replace('$', &marker.to_string())
+        })
+        .into_owned()
+}
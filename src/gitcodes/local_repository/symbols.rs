@@ -0,0 +1,746 @@
+//! Symbol definition search
+//!
+//! A regex search for `fn lock` also finds calls, comments and strings. This module parses
+//! the source files with tree-sitter (see the `syntax` module) and collects the
+//! definitions of functions, methods, types, traits, interfaces, classes, modules and
+//! macros, so a name can be looked up where it is defined.
+//!
+//! Definitions are collected from the top level of a file and from the bodies of modules,
+//! impls, traits, classes and interfaces. Function bodies aren't descended into, so local
+//! helper functions and closures don't show up.
+
+use regex::{Regex, RegexBuilder};
+use rmcp::schemars;
+use serde::{Deserialize, Serialize};
+use tree_sitter::{Node, Tree};
+
+use super::LocalRepository;
+use super::syntax::{SourceLanguage, line_range, node_text, parse_source};
+
+/// Signatures longer than this many characters are shortened
+const MAX_SIGNATURE_CHARS: usize = 200;
+
+/// The kind of a symbol definition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolKind {
+    /// A free function
+    Function,
+    /// A function defined in an impl, trait, class or interface
+    Method,
+    /// A Rust or Go struct
+    Struct,
+    /// An enum
+    Enum,
+    /// A Rust union
+    Union,
+    /// A Rust trait
+    Trait,
+    /// A Go or TypeScript interface
+    Interface,
+    /// A Python or TypeScript class
+    Class,
+    /// A type alias or other named type
+    TypeAlias,
+    /// A Rust module or TypeScript namespace
+    Module,
+    /// A Rust `macro_rules!` macro
+    Macro,
+    /// A Rust impl block, named after the implementing type (only listed in file outlines)
+    Impl,
+}
+
+/// A symbol definition found in a source file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolDefinition {
+    /// The name of the symbol
+    pub name: String,
+    /// What kind of symbol it is
+    pub kind: SymbolKind,
+    /// The enclosing modules, types or classes joined with `::`, e.g. "sync::Mutex"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
+    /// The definition up to its body on a single line, e.g. "pub fn lock(&self) -> Guard<'_, T>"
+    pub signature: String,
+    /// The first line of the doc comment or docstring
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    /// Path of the defining file relative to the repository root
+    pub file_path: String,
+    /// The language of the defining file
+    pub language: SourceLanguage,
+    /// First line of the definition (1-based)
+    pub start_line: usize,
+    /// Last line of the definition (1-based, inclusive)
+    pub end_line: usize,
+}
+
+/// Options for a symbol definition search
+#[derive(Debug, Clone, Default)]
+pub struct SymbolSearchOptions {
+    /// The symbol name, or a regex matched against symbol names if `regex` is set
+    pub name: String,
+    /// Whether `name` is a regex (unanchored) rather than an exact name
+    pub regex: bool,
+    /// Whether names are matched case-sensitively
+    pub case_sensitive: bool,
+    /// Only return symbols of these kinds (all kinds when `None`)
+    pub kinds: Option<Vec<SymbolKind>>,
+    /// Branch, tag, or commit whose tree is searched instead of the worktree
+    pub ref_name: Option<String>,
+    /// Glob patterns of the files to search, as in code search
+    pub include_globs: Option<Vec<String>>,
+    /// Directories or glob patterns to exclude, as in code search
+    pub exclude_globs: Option<Vec<String>>,
+    /// Number of symbols to skip (for pagination)
+    pub skip: Option<usize>,
+    /// Maximum number of symbols to return (for pagination)
+    pub take: Option<usize>,
+}
+
+/// Result of a symbol definition search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolSearchResult {
+    /// The name or regex that was searched for
+    pub name: String,
+    /// Number of parsed source files
+    pub scanned_files: usize,
+    /// Number of matching symbols before pagination
+    pub total_symbols: usize,
+    /// Matching symbols sorted by file path and line
+    pub symbols: Vec<SymbolDefinition>,
+}
+
+impl LocalRepository {
+    /// Finds the definitions of symbols matching a name or regex
+    ///
+    /// Rust, Go, Python, TypeScript and JavaScript files are parsed with tree-sitter, so
+    /// usages, comments and strings mentioning the name aren't returned. Files of other
+    /// languages are skipped.
+    ///
+    /// # Parameters
+    ///
+    /// * `options` - The name to look for, filters and pagination
+    ///
+    /// # Returns
+    ///
+    /// * `Result<SymbolSearchResult, String>` - The matching definitions or an error message
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is empty or an invalid regex, a glob or the ref can't be
+    /// resolved, or the files can't be read.
+    pub async fn find_symbols(
+        &self,
+        options: SymbolSearchOptions,
+    ) -> Result<SymbolSearchResult, String> {
+        if let Err(e) = self.validate() {
+            return Err(format!("Repository validation failed: {}", e));
+        }
+        let matcher = NameMatcher::new(&options.name, options.regex, options.case_sensitive)?;
+
+        let mut symbols = Vec::new();
+        let scanned_files = self.visit_source_files(
+            options.ref_name.as_deref(),
+            options.include_globs.as_ref(),
+            options.exclude_globs.as_ref(),
+            |file| {
+                if !matcher.may_occur_in(&file.content) {
+                    return Ok(());
+                }
+                let file_path = file.path.to_string_lossy().replace('\\', "/");
+                for symbol in extract_symbols(file.language, &file.content, &file_path)? {
+                    let kind_matches = options
+                        .kinds
+                        .as_ref()
+                        .is_none_or(|kinds| kinds.contains(&symbol.kind));
+                    if kind_matches && matcher.matches(&symbol.name) {
+                        symbols.push(symbol);
+                    }
+                }
+                Ok(())
+            },
+        )?;
+
+        symbols.sort_by(|a, b| {
+            a.file_path
+                .cmp(&b.file_path)
+                .then(a.start_line.cmp(&b.start_line))
+        });
+        let total_symbols = symbols.len();
+        let symbols = symbols
+            .into_iter()
+            .skip(options.skip.unwrap_or(0))
+            .take(options.take.unwrap_or(usize::MAX))
+            .collect();
+
+        Ok(SymbolSearchResult {
+            name: options.name,
+            scanned_files,
+            total_symbols,
+            symbols,
+        })
+    }
+}
+
+/// Matches symbol names against an exact name or a regex
+enum NameMatcher {
+    Exact { name: String, case_sensitive: bool },
+    Regex(Regex),
+}
+
+impl NameMatcher {
+    fn new(name: &str, regex: bool, case_sensitive: bool) -> Result<Self, String> {
+        if name.is_empty() {
+            return Err("The symbol name must not be empty".to_string());
+        }
+        if regex {
+            let regex = RegexBuilder::new(name)
+                .case_insensitive(!case_sensitive)
+                .build()
+                .map_err(|e| format!("Invalid symbol name regex '{}': {}", name, e))?;
+            return Ok(Self::Regex(regex));
+        }
+        Ok(Self::Exact {
+            name: name.to_string(),
+            case_sensitive,
+        })
+    }
+
+    fn matches(&self, candidate: &str) -> bool {
+        match self {
+            Self::Exact {
+                name,
+                case_sensitive: true,
+            } => candidate == name,
+            Self::Exact { name, .. } => candidate.eq_ignore_ascii_case(name),
+            Self::Regex(regex) => regex.is_match(candidate),
+        }
+    }
+
+    /// Whether a file could define the name, to skip parsing files that can't
+    fn may_occur_in(&self, content: &str) -> bool {
+        match self {
+            Self::Exact {
+                name,
+                case_sensitive: true,
+            } => content.contains(name.as_str()),
+            Self::Exact { name, .. } => content
+                .to_ascii_lowercase()
+                .contains(&name.to_ascii_lowercase()),
+            Self::Regex(_) => true,
+        }
+    }
+}
+
+/// Where in a file the walk is, which decides whether functions are methods
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    /// The top level of a file or the body of a module
+    Module,
+    /// The body of an impl, trait, class or interface
+    Type,
+}
+
+/// Extracts the symbol definitions of a source file in document order
+///
+/// # Parameters
+///
+/// * `language` - The language of the file
+/// * `source` - The file contents
+/// * `file_path` - Path of the file relative to the repository root, copied into each symbol
+///
+/// # Returns
+///
+/// * `Result<Vec<SymbolDefinition>, String>` - The definitions or an error message
+pub(crate) fn extract_symbols(
+    language: SourceLanguage,
+    source: &str,
+    file_path: &str,
+) -> Result<Vec<SymbolDefinition>, String> {
+    let symbols = collect_symbols(language, source, file_path, false)?;
+    Ok(symbols
+        .into_iter()
+        .map(|collected| collected.symbol)
+        .collect())
+}
+
+/// A symbol definition with where it sits in the file
+pub(crate) struct CollectedSymbol {
+    /// 0 for top-level items, 1 for the items in their bodies, and so on
+    pub depth: usize,
+    /// First line of the doc comments, attributes and decorators above the definition,
+    /// or its `start_line` if there are none
+    pub leading_start_line: usize,
+    /// Line and column (1-based, in bytes) of the name of the definition
+    pub name_position: (usize, usize),
+    pub symbol: SymbolDefinition,
+}
+
+/// Extracts the symbol definitions of a source file in document order
+///
+/// Rust impl blocks are only included if `include_impls` is set; the methods in them are
+/// nested one level deeper either way.
+pub(crate) fn collect_symbols(
+    language: SourceLanguage,
+    source: &str,
+    file_path: &str,
+    include_impls: bool,
+) -> Result<Vec<CollectedSymbol>, String> {
+    let tree = parse_source(language, source)?;
+    Ok(collect_symbols_in_tree(
+        &tree,
+        language,
+        source,
+        file_path,
+        include_impls,
+    ))
+}
+
+/// Extracts the symbol definitions from the syntax tree of a source file, like
+/// [`collect_symbols`] does for the unparsed source
+pub(crate) fn collect_symbols_in_tree(
+    tree: &Tree,
+    language: SourceLanguage,
+    source: &str,
+    file_path: &str,
+    include_impls: bool,
+) -> Vec<CollectedSymbol> {
+    let mut collector = SymbolCollector {
+        language,
+        source,
+        file_path,
+        include_impls,
+        containers: Vec::new(),
+        depth: 0,
+        symbols: Vec::new(),
+    };
+    collector.visit_children(tree.root_node(), Scope::Module);
+    collector.symbols
+}
+
+/// Walks a syntax tree, collecting definitions along with their enclosing containers
+struct SymbolCollector<'a> {
+    language: SourceLanguage,
+    source: &'a str,
+    file_path: &'a str,
+    include_impls: bool,
+    containers: Vec<String>,
+    /// How many definitions enclose the current node
+    depth: usize,
+    symbols: Vec<CollectedSymbol>,
+}
+
+impl<'a> SymbolCollector<'a> {
+    fn visit_children(&mut self, node: Node<'a>, scope: Scope) {
+        let mut cursor = node.walk();
+        let children: Vec<Node<'a>> = node.named_children(&mut cursor).collect();
+        for child in children {
+            match self.language {
+                SourceLanguage::Rust => self.visit_rust(child, scope),
+                SourceLanguage::Go => self.visit_go(child),
+                SourceLanguage::Python => self.visit_python(child, scope),
+                SourceLanguage::TypeScript | SourceLanguage::Tsx => {
+                    self.visit_typescript(child, scope)
+                }
+            }
+        }
+    }
+
+    fn visit_rust(&mut self, node: Node<'a>, scope: Scope) {
+        let kind = match node.kind() {
+            "function_item" | "function_signature_item" => match scope {
+                Scope::Type => SymbolKind::Method,
+                Scope::Module => SymbolKind::Function,
+            },
+            "struct_item" => SymbolKind::Struct,
+            "enum_item" => SymbolKind::Enum,
+            "union_item" => SymbolKind::Union,
+            "type_item" => SymbolKind::TypeAlias,
+            "macro_definition" => SymbolKind::Macro,
+            "trait_item" => {
+                self.add_named(node, SymbolKind::Trait, node.child_by_field_name("body"));
+                self.visit_container_body(node, Scope::Type);
+                return;
+            }
+            "mod_item" => {
+                self.add_named(node, SymbolKind::Module, node.child_by_field_name("body"));
+                self.visit_container_body(node, Scope::Module);
+                return;
+            }
+            "impl_item" => {
+                if let (Some(type_node), Some(body)) = (
+                    node.child_by_field_name("type"),
+                    node.child_by_field_name("body"),
+                ) {
+                    if self.include_impls {
+                        self.add_symbol(node, type_node, SymbolKind::Impl, Some(body));
+                        // The name of a symbol is plain, e.g. `Mutex` for `impl<T> Mutex<T>`
+                        if let Some(collected) = self.symbols.last_mut() {
+                            collected.symbol.name = type_name(type_node, self.source);
+                        }
+                    }
+                    self.containers.push(type_name(type_node, self.source));
+                    self.depth += 1;
+                    self.visit_children(body, Scope::Type);
+                    self.depth -= 1;
+                    self.containers.pop();
+                }
+                return;
+            }
+            "foreign_mod_item" => {
+                if let Some(body) = node.child_by_field_name("body") {
+                    self.visit_children(body, Scope::Module);
+                }
+                return;
+            }
+            _ => return,
+        };
+        self.add_named(node, kind, node.child_by_field_name("body"));
+    }
+
+    fn visit_go(&mut self, node: Node<'a>) {
+        match node.kind() {
+            "function_declaration" => {
+                self.add_named(node, SymbolKind::Function, node.child_by_field_name("body"))
+            }
+            "method_declaration" => {
+                // The receiver's type is the container, e.g. `Mutex` for `func (m *Mutex) Lock()`
+                let receiver = node
+                    .child_by_field_name("receiver")
+                    .and_then(|receiver| receiver.named_child(0))
+                    .and_then(|parameter| parameter.child_by_field_name("type"))
+                    .map(|type_node| type_name(type_node, self.source));
+                if let Some(receiver) = &receiver {
+                    self.containers.push(receiver.clone());
+                }
+                self.add_named(node, SymbolKind::Method, node.child_by_field_name("body"));
+                if receiver.is_some() {
+                    self.containers.pop();
+                }
+            }
+            "type_declaration" => {
+                let mut cursor = node.walk();
+                let specs: Vec<Node<'a>> = node.named_children(&mut cursor).collect();
+                for spec in specs {
+                    // A single declaration is reported from `type`, a group from the spec
+                    let definition = if node.named_child_count() == 1 {
+                        node
+                    } else {
+                        spec
+                    };
+                    let type_node = spec.child_by_field_name("type");
+                    let body = type_node.and_then(|type_node| type_node.named_child(0));
+                    match (spec.kind(), type_node.map(|type_node| type_node.kind())) {
+                        ("type_spec", Some("struct_type")) => {
+                            self.add_definition(definition, spec, SymbolKind::Struct, body)
+                        }
+                        ("type_spec", Some("interface_type")) => {
+                            self.add_definition(definition, spec, SymbolKind::Interface, body);
+                            self.visit_go_interface(spec, type_node);
+                        }
+                        ("type_spec", _) | ("type_alias", _) => {
+                            self.add_definition(definition, spec, SymbolKind::TypeAlias, None)
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Collects the methods of a Go interface type
+    fn visit_go_interface(&mut self, spec: Node<'a>, interface: Option<Node<'a>>) {
+        let (Some(name), Some(interface)) = (spec.child_by_field_name("name"), interface) else {
+            return;
+        };
+        self.containers
+            .push(node_text(name, self.source).to_string());
+        self.depth += 1;
+        let mut cursor = interface.walk();
+        let elements: Vec<Node<'a>> = interface.named_children(&mut cursor).collect();
+        for element in elements {
+            if element.kind() == "method_elem" {
+                self.add_named(element, SymbolKind::Method, None);
+            }
+        }
+        self.depth -= 1;
+        self.containers.pop();
+    }
+
+    fn visit_python(&mut self, node: Node<'a>, scope: Scope) {
+        match node.kind() {
+            "function_definition" => {
+                let kind = match scope {
+                    Scope::Type => SymbolKind::Method,
+                    Scope::Module => SymbolKind::Function,
+                };
+                self.add_named(node, kind, node.child_by_field_name("body"));
+            }
+            "class_definition" => {
+                self.add_named(node, SymbolKind::Class, node.child_by_field_name("body"));
+                self.visit_container_body(node, Scope::Type);
+            }
+            "decorated_definition" => {
+                if let Some(definition) = node.child_by_field_name("definition") {
+                    self.visit_python(definition, scope);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_typescript(&mut self, node: Node<'a>, scope: Scope) {
+        let body = node.child_by_field_name("body");
+        match node.kind() {
+            "function_declaration" | "generator_function_declaration" | "function_signature" => {
+                self.add_named(node, SymbolKind::Function, body)
+            }
+            "method_definition" | "method_signature" | "abstract_method_signature" => {
+                self.add_named(node, SymbolKind::Method, body)
+            }
+            "class_declaration" | "abstract_class_declaration" => {
+                self.add_named(node, SymbolKind::Class, body);
+                self.visit_container_body(node, Scope::Type);
+            }
+            "interface_declaration" => {
+                self.add_named(node, SymbolKind::Interface, body);
+                self.visit_container_body(node, Scope::Type);
+            }
+            "enum_declaration" => self.add_named(node, SymbolKind::Enum, body),
+            "type_alias_declaration" => self.add_named(node, SymbolKind::TypeAlias, None),
+            "internal_module" | "module" => {
+                self.add_named(node, SymbolKind::Module, body);
+                self.visit_container_body(node, Scope::Module);
+            }
+            // `export ...`, `declare ...` and `namespace X {}` (parsed as an expression) wrap declarations
+            "export_statement" | "ambient_declaration" | "expression_statement" => {
+                self.visit_children(node, scope)
+            }
+            // `const handler = () => {}` defines a function as well
+            "lexical_declaration" | "variable_declaration" if scope == Scope::Module => {
+                let mut cursor = node.walk();
+                let declarators: Vec<Node<'a>> = node.named_children(&mut cursor).collect();
+                for declarator in declarators {
+                    let value = declarator.child_by_field_name("value");
+                    let is_function = value.is_some_and(|value| {
+                        matches!(
+                            value.kind(),
+                            "arrow_function" | "function_expression" | "function"
+                        )
+                    });
+                    if let (true, Some(name)) =
+                        (is_function, declarator.child_by_field_name("name"))
+                    {
+                        let body = value.and_then(|value| value.child_by_field_name("body"));
+                        self.add_symbol(node, name, SymbolKind::Function, body);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Walks the body of a module, trait, class or interface with its name as container
+    fn visit_container_body(&mut self, node: Node<'a>, scope: Scope) {
+        let (Some(name), Some(body)) = (
+            node.child_by_field_name("name"),
+            node.child_by_field_name("body"),
+        ) else {
+            return;
+        };
+        self.containers
+            .push(node_text(name, self.source).to_string());
+        self.depth += 1;
+        self.visit_children(body, scope);
+        self.depth -= 1;
+        self.containers.pop();
+    }
+
+    /// Adds a definition whose name is in its `name` field
+    fn add_named(&mut self, node: Node<'a>, kind: SymbolKind, body: Option<Node<'a>>) {
+        self.add_definition(node, node, kind, body);
+    }
+
+    /// Adds a definition spanning `node` whose name is in the `name` field of `named`
+    fn add_definition(
+        &mut self,
+        node: Node<'a>,
+        named: Node<'a>,
+        kind: SymbolKind,
+        body: Option<Node<'a>>,
+    ) {
+        if let Some(name) = named.child_by_field_name("name") {
+            self.add_symbol(node, name, kind, body);
+        }
+    }
+
+    fn add_symbol(
+        &mut self,
+        node: Node<'a>,
+        name: Node<'a>,
+        kind: SymbolKind,
+        body: Option<Node<'a>>,
+    ) {
+        let (start_line, end_line) = line_range(node);
+        let (doc, leading_start_line) = self.leading_docs(node, body);
+        let symbol = SymbolDefinition {
+            name: node_text(name, self.source).to_string(),
+            kind,
+            container: (!self.containers.is_empty()).then(|| self.containers.join("::")),
+            signature: signature(node, body, self.source),
+            doc,
+            file_path: self.file_path.to_string(),
+            language: self.language,
+            start_line,
+            end_line,
+        };
+        let name_start = name.start_position();
+        self.symbols.push(CollectedSymbol {
+            depth: self.depth,
+            leading_start_line,
+            name_position: (name_start.row + 1, name_start.column + 1),
+            symbol,
+        });
+    }
+
+    /// The first line of the documentation of a definition, and the first line of the
+    /// doc comments, attributes and decorators above it
+    ///
+    /// Python documents definitions with a docstring at the start of the body. The other
+    /// languages use the comments right above the definition (or above the `export`
+    /// statement wrapping it): `///` and `/** */` in Rust, any comment in Go, and `/** */`
+    /// in TypeScript. Rust attributes and decorators between the comment and the item are
+    /// skipped.
+    fn leading_docs(&self, node: Node<'a>, body: Option<Node<'a>>) -> (Option<String>, usize) {
+        if self.language == SourceLanguage::Python {
+            let leading_row = node
+                .parent()
+                .filter(|parent| parent.kind() == "decorated_definition")
+                .unwrap_or(node)
+                .start_position()
+                .row;
+            let docstring = body
+                .and_then(|body| body.named_child(0))
+                .filter(|statement| statement.kind() == "expression_statement")
+                .and_then(|statement| statement.named_child(0))
+                .filter(|string| string.kind() == "string");
+            let doc = docstring.and_then(|docstring| {
+                let text = node_text(docstring, self.source)
+                    .trim_start_matches(|c: char| c.is_ascii_alphabetic())
+                    .trim_matches(|c| c == '"' || c == '\'');
+                first_doc_line(text)
+            });
+            return (doc, leading_row + 1);
+        }
+
+        let mut anchor = node;
+        while anchor.prev_named_sibling().is_none() {
+            match anchor.parent() {
+                Some(parent)
+                    if matches!(parent.kind(), "export_statement" | "ambient_declaration") =>
+                {
+                    anchor = parent
+                }
+                _ => break,
+            }
+        }
+
+        let is_doc = |comment: &str| match self.language {
+            SourceLanguage::Rust => {
+                (comment.starts_with("///") && !comment.starts_with("////"))
+                    || (comment.starts_with("/**") && !comment.starts_with("/***"))
+            }
+            SourceLanguage::Go => true,
+            _ => comment.starts_with("/**"),
+        };
+
+        // Walk up through the comments and attributes directly above, stopping at a blank line
+        let mut docs = Vec::new();
+        let mut leading_row = anchor.start_position().row;
+        let mut next_row = leading_row;
+        let mut sibling = anchor.prev_named_sibling();
+        while let Some(previous) = sibling {
+            let is_attribute = matches!(previous.kind(), "attribute_item" | "decorator");
+            if !is_attribute && !previous.kind().ends_with("comment") {
+                break;
+            }
+            // Line comments may end at the start of the next line
+            let end = previous.end_position();
+            let end_row = if end.column == 0 && end.row > previous.start_position().row {
+                end.row - 1
+            } else {
+                end.row
+            };
+            if end_row + 1 < next_row {
+                break;
+            }
+            let text = node_text(previous, self.source);
+            if is_attribute || is_doc(text) {
+                leading_row = previous.start_position().row;
+            }
+            if !is_attribute && is_doc(text) {
+                docs.push(text);
+            }
+            next_row = previous.start_position().row;
+            sibling = previous.prev_named_sibling();
+        }
+        docs.reverse();
+
+        let doc = docs.into_iter().find_map(first_doc_line);
+        (doc, leading_row + 1)
+    }
+}
+
+/// The first non-empty line of a comment or docstring, without comment markers
+fn first_doc_line(text: &str) -> Option<String> {
+    text.lines().find_map(|line| {
+        let line = line.trim();
+        let line = ["///", "//!", "//", "/**", "/*"]
+            .iter()
+            .find_map(|marker| line.strip_prefix(marker))
+            .unwrap_or(line);
+        let line = line.trim_end().trim_end_matches("*/").trim();
+        let line = line.strip_prefix('*').unwrap_or(line).trim();
+        (!line.is_empty()).then(|| line.to_string())
+    })
+}
+
+/// The name of a type without generics, references or paths, e.g. `Mutex` for `&sync::Mutex<T>`
+fn type_name(node: Node, source: &str) -> String {
+    match node.kind() {
+        "scoped_type_identifier" | "qualified_type" => node
+            .child_by_field_name("name")
+            .map(|name| type_name(name, source))
+            .unwrap_or_else(|| node_text(node, source).to_string()),
+        _ => match node.child_by_field_name("type").or_else(|| {
+            (node.kind() == "pointer_type")
+                .then(|| node.named_child(0))
+                .flatten()
+        }) {
+            Some(inner) => type_name(inner, source),
+            None => node_text(node, source).to_string(),
+        },
+    }
+}
+
+/// The text of a definition up to its body, on one line
+fn signature(node: Node, body: Option<Node>, source: &str) -> String {
+    let end = body
+        .filter(|body| body.start_byte() > node.start_byte())
+        .map_or(node.end_byte(), |body| body.start_byte());
+    let text = &source[node.start_byte()..end];
+    let mut signature = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    // Python puts a colon before the body; the members of a Go interface aren't wrapped
+    // in a body node, so its opening brace is left over
+    if (signature.ends_with(':') || signature.ends_with('{')) && body.is_some() {
+        signature.pop();
+        signature.truncate(signature.trim_end().len());
+    }
+    if signature.chars().count() > MAX_SIGNATURE_CHARS {
+        signature = signature.chars().take(MAX_SIGNATURE_CHARS).collect();
+        signature.push_str("...");
+    }
+    signature
+}
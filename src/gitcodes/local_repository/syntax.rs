@@ -0,0 +1,306 @@
+//! Parsing of source files into syntax trees with tree-sitter
+//!
+//! Regex search sees text only, so it can't tell a definition from a usage or a comment.
+//! The syntax-aware features parse the files of the supported languages with tree-sitter
+//! instead. This module detects the language of a file, parses it, and walks the source
+//! files of the worktree or, for a ref, of the commit's tree in the object database. The
+//! files are filtered like a code search: hidden paths are skipped, files ignored by git
+//! aren't part of the worktree walk, and include/exclude globs are matched against paths
+//! relative to the repository root.
+
+use std::path::{Path, PathBuf};
+
+use lumin::traverse::common::{collect_files_with_excludes, path_matches_any_glob};
+use rmcp::schemars;
+use serde::{Deserialize, Serialize};
+use tree_sitter::{Node, Parser, Tree};
+
+use super::object_database::peel_ref_to_commit;
+use super::object_search::should_search_path;
+use super::{LocalRepository, prevent_directory_traversal};
+
+/// A language the syntax-aware features can parse
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceLanguage {
+    /// Rust (`.rs`)
+    Rust,
+    /// Go (`.go`)
+    Go,
+    /// Python (`.py`, `.pyi`)
+    Python,
+    /// TypeScript (`.ts`, `.mts`, `.cts`)
+    #[serde(rename = "typescript")]
+    TypeScript,
+    /// TSX, also used for JavaScript (`.tsx`, `.js`, `.jsx`, `.mjs`, `.cjs`)
+    Tsx,
+}
+
+impl SourceLanguage {
+    /// Detects the language of a file from its extension
+    ///
+    /// JavaScript is parsed with the TSX grammar, which accepts plain JavaScript and JSX.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<SourceLanguage>` - The language, or `None` if the file type isn't supported
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "rs" => Some(Self::Rust),
+            "go" => Some(Self::Go),
+            "py" | "pyi" => Some(Self::Python),
+            "ts" | "mts" | "cts" => Some(Self::TypeScript),
+            "tsx" | "js" | "jsx" | "mjs" | "cjs" => Some(Self::Tsx),
+            _ => None,
+        }
+    }
+
+    /// The tree-sitter grammar of the language
+    fn grammar(self) -> tree_sitter::Language {
+        match self {
+            Self::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Self::Go => tree_sitter_go::LANGUAGE.into(),
+            Self::Python => tree_sitter_python::LANGUAGE.into(),
+            Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Self::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+        }
+    }
+}
+
+/// Parses source code into a syntax tree
+///
+/// tree-sitter recovers from syntax errors, so a tree is returned for any input; the
+/// parts it couldn't make sense of are marked as error nodes.
+///
+/// # Errors
+///
+/// Returns an error if the grammar can't be loaded or parsing is aborted.
+pub(crate) fn parse_source(language: SourceLanguage, source: &str) -> Result<Tree, String> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&language.grammar())
+        .map_err(|e| format!("Failed to load the {:?} grammar: {}", language, e))?;
+    parser
+        .parse(source, None)
+        .ok_or_else(|| format!("Failed to parse {:?} source", language))
+}
+
+/// The source text of a node
+pub(crate) fn node_text<'a>(node: Node, source: &'a str) -> &'a str {
+    &source[node.byte_range()]
+}
+
+/// Shortens text to `max_chars` characters, marking the cut with "..."
+pub(crate) fn shorten_text(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut shortened: String = text.chars().take(max_chars).collect();
+    shortened.push_str("...");
+    shortened
+}
+
+/// The 1-based line numbers of the first and last line of a node
+pub(crate) fn line_range(node: Node) -> (usize, usize) {
+    (node.start_position().row + 1, node.end_position().row + 1)
+}
+
+/// A source file of a supported language, read from the worktree or the object database
+pub(crate) struct SourceFile {
+    /// Path relative to the repository root
+    pub path: PathBuf,
+    /// The language detected from the file extension
+    pub language: SourceLanguage,
+    /// The file contents
+    pub content: String,
+}
+
+impl LocalRepository {
+    /// Reads one source file of a supported language from the worktree or, for a ref, from
+    /// the object database
+    ///
+    /// # Parameters
+    ///
+    /// * `file_path` - Path of the file relative to the repository root
+    /// * `ref_name` - Branch, tag, or commit to read the file at instead of the worktree
+    ///
+    /// # Returns
+    ///
+    /// * `Result<SourceFile, String>` - The file contents and language or an error message
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path contains directory traversal patterns, the file doesn't
+    /// exist or isn't valid UTF-8, or its language isn't supported.
+    pub(crate) fn read_source_file(
+        &self,
+        file_path: &Path,
+        ref_name: Option<&str>,
+    ) -> Result<SourceFile, String> {
+        prevent_directory_traversal(file_path)?;
+        let path = self.normalize_repository_path(file_path)?;
+        let language = SourceLanguage::from_path(&path).ok_or_else(|| {
+            format!(
+                "Unsupported file type: {} (supported are Rust, Go, Python, TypeScript and JavaScript files)",
+                file_path.display()
+            )
+        })?;
+
+        let data = match ref_name {
+            Some(ref_name) => {
+                let repo = self.open_git_repository()?;
+                let tree = peel_ref_to_commit(&repo, ref_name)?
+                    .tree()
+                    .map_err(|e| format!("Failed to read tree of '{}': {}", ref_name, e))?;
+                let entry = tree
+                    .lookup_entry_by_path(&path)
+                    .map_err(|e| {
+                        format!(
+                            "Failed to look up '{}' at '{}': {}",
+                            path.display(),
+                            ref_name,
+                            e
+                        )
+                    })?
+                    .filter(|entry| entry.mode().is_blob())
+                    .ok_or_else(|| {
+                        format!(
+                            "File not found in repository at ref '{}': {}",
+                            ref_name,
+                            file_path.display()
+                        )
+                    })?;
+                entry
+                    .object()
+                    .map_err(|e| format!("Failed to read blob for '{}': {}", path.display(), e))?
+                    .data
+                    .clone()
+            }
+            None => {
+                let full_path = self.repository_location.join(&path);
+                if !full_path.is_file() {
+                    return Err(format!(
+                        "File not found in repository: {}",
+                        file_path.display()
+                    ));
+                }
+                std::fs::read(&full_path)
+                    .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?
+            }
+        };
+        let content = String::from_utf8(data)
+            .map_err(|_| format!("Not a UTF-8 text file: {}", file_path.display()))?;
+
+        Ok(SourceFile {
+            path,
+            language,
+            content,
+        })
+    }
+
+    /// Calls `visit` for every source file of a supported language
+    ///
+    /// Without a ref the worktree is walked like `perform_code_search` walks it; with a ref
+    /// the blobs of that commit's tree are read from the object database, like
+    /// `perform_code_search_at_ref` does. Files that aren't valid UTF-8 or can't be read
+    /// are skipped.
+    ///
+    /// # Parameters
+    ///
+    /// * `ref_name` - Branch, tag, or commit whose tree is walked instead of the worktree
+    /// * `include_globs` - Glob patterns of the files to visit
+    /// * `exclude_globs` - Directories or glob patterns to skip
+    /// * `visit` - Called with each file; an error stops the walk
+    ///
+    /// # Returns
+    ///
+    /// * `Result<usize, String>` - The number of visited files or an error message
+    pub(crate) fn visit_source_files(
+        &self,
+        ref_name: Option<&str>,
+        include_globs: Option<&Vec<String>>,
+        exclude_globs: Option<&Vec<String>>,
+        mut visit: impl FnMut(SourceFile) -> Result<(), String>,
+    ) -> Result<usize, String> {
+        let (include_globs, exclude_globs) =
+            self.normalize_search_globs(include_globs, exclude_globs);
+        let mut visited = 0;
+
+        if let Some(ref_name) = ref_name {
+            let repo = self.open_git_repository()?;
+            let tree = peel_ref_to_commit(&repo, ref_name)?
+                .tree()
+                .map_err(|e| format!("Failed to read tree of '{}': {}", ref_name, e))?;
+            let records = tree
+                .traverse()
+                .breadthfirst
+                .files()
+                .map_err(|e| format!("Failed to traverse tree at '{}': {}", ref_name, e))?;
+
+            for record in records {
+                if !record.mode.is_blob() {
+                    continue;
+                }
+                let path = PathBuf::from(record.filepath.to_string());
+                let Some(language) = SourceLanguage::from_path(&path) else {
+                    continue;
+                };
+                if !should_search_path(
+                    &path,
+                    include_globs.as_deref(),
+                    exclude_globs.as_deref(),
+                    true,
+                )? {
+                    continue;
+                }
+
+                let blob = repo
+                    .find_blob(record.oid)
+                    .map_err(|e| format!("Failed to read blob for '{}': {}", path.display(), e))?;
+                let Ok(content) = String::from_utf8(blob.data.clone()) else {
+                    continue;
+                };
+                visited += 1;
+                visit(SourceFile {
+                    path,
+                    language,
+                    content,
+                })?;
+            }
+            return Ok(visited);
+        }
+
+        let repo_path = self.repository_location.as_path();
+        let files =
+            collect_files_with_excludes(repo_path, true, true, None, exclude_globs.as_ref())
+                .map_err(|e| format!("Failed to list source files: {}", e))?;
+        for file_path in files {
+            let path = file_path
+                .strip_prefix(repo_path)
+                .unwrap_or(&file_path)
+                .to_path_buf();
+            let Some(language) = SourceLanguage::from_path(&path) else {
+                continue;
+            };
+            if let Some(includes) = &include_globs {
+                if !path_matches_any_glob(&path, includes, true)
+                    .map_err(|e| format!("Invalid include glob: {}", e))?
+                {
+                    continue;
+                }
+            }
+
+            let Ok(content) = std::fs::read_to_string(&file_path) else {
+                continue;
+            };
+            visited += 1;
+            visit(SourceFile {
+                path,
+                language,
+                content,
+            })?;
+        }
+        Ok(visited)
+    }
+}
@@ -126,7 +126,7 @@ pub struct RepositoryTree {
 /// Entry in a repository directory tree
 ///
 /// This enum represents either a file or a directory in the repository tree structure.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "type", content = "name")]
 pub enum TreeEntry {
     /// A file entry with just a name
@@ -0,0 +1,429 @@
+//! Trigram index for narrowing code searches
+//!
+//! A code search reads every file of the repository and runs the regex over it. For a
+//! repository that is searched again and again, most of that work can be skipped: the
+//! index records which files contain each sequence of three bytes (trigram), and a
+//! regex can only match in a file containing the trigrams of the literal text it
+//! requires. `fn\s+parse_(config|args)` needs `parse_` and either `config` or `args`, so
+//! only the files holding `par`, `ars`, `rse`, `se_` and the trigrams of one of the two
+//! words are searched.
+//!
+//! The index is built from the tree of a commit on the first indexed search and cached
+//! as JSON inside the repository's git directory, like the identifier index. It is keyed
+//! by the tree id, so a fetch or checkout that changes the tree gets a new index while
+//! refs pointing to the same content share one; [`LocalRepository::fetch_remote`]
+//! removes the indexes of trees no ref points to anymore. Worktree searches use the
+//! index of `HEAD` and always search the files with uncommitted changes as well.
+//!
+//! Trigrams are recorded with ASCII letters lowercased, so one index serves both case
+//! sensitive and insensitive searches. Patterns from which no trigram can be derived,
+//! like `\w+` or `.*`, fall back to searching every file.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+use regex_syntax::hir::{Hir, HirKind};
+use serde::{Deserialize, Serialize};
+
+use super::index_cache::{INDEX_DIR_NAME, write_index};
+use super::object_database::peel_ref_to_commit;
+use super::{CodeSearchOptions, CodeSearchResult, LocalRepository};
+
+/// Bumped whenever the layout of [`TrigramIndex`] changes, so stale caches are rebuilt
+const INDEX_FORMAT_VERSION: u32 = 1;
+
+/// Prefix of the file names of cached trigram indexes
+pub(super) const TRIGRAM_INDEX_FILE_PREFIX: &str = "trigrams-v";
+
+/// Number of loaded indexes kept in memory, so repeated searches skip reading the cache
+const MAX_LOADED_INDEXES: usize = 4;
+
+/// Loaded indexes by the path of their cache file
+type LoadedIndexes = Vec<(PathBuf, Arc<TrigramIndex>)>;
+
+/// The most recently used indexes, most recent last
+static LOADED_INDEXES: Lazy<Mutex<LoadedIndexes>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// The trigrams of all text files of one tree
+#[derive(Debug, Serialize, Deserialize)]
+struct TrigramIndex {
+    /// The tree that was indexed
+    tree_id: String,
+    /// Paths of the indexed files; postings refer to them by position
+    files: Vec<String>,
+    /// Trigram, three bytes packed into the low bits, to the sorted files containing it
+    postings: HashMap<u32, Vec<u32>>,
+}
+
+/// What a file must contain to possibly match a pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TrigramQuery {
+    /// No restriction, every file may match
+    All,
+    /// All of these trigrams
+    Trigrams(BTreeSet<u32>),
+    /// All of the queries
+    And(Vec<TrigramQuery>),
+    /// At least one of the queries
+    Or(Vec<TrigramQuery>),
+}
+
+impl LocalRepository {
+    /// Performs a code search over the files the trigram index can't rule out
+    ///
+    /// The index of the searched tree is loaded or built first. Without a ref, the index
+    /// of `HEAD` is used and the files with uncommitted changes are searched in addition.
+    /// When the pattern doesn't require any trigram, or the index or the worktree status
+    /// can't be read, all files are searched as without the index.
+    ///
+    /// # Parameters
+    ///
+    /// * `ref_name` - Branch, tag, or commit whose tree is searched instead of the worktree
+    /// * `options` - The search options
+    ///
+    /// # Returns
+    ///
+    /// * `Result<CodeSearchResult, String>` - The same result as an unindexed search or an
+    ///   error message
+    pub async fn perform_indexed_code_search(
+        &self,
+        ref_name: Option<&str>,
+        options: CodeSearchOptions,
+    ) -> Result<CodeSearchResult, String> {
+        let query = TrigramQuery::from_pattern(&options.matcher_pattern(), options.case_sensitive);
+        if query == TrigramQuery::All {
+            return self.perform_unindexed_code_search(ref_name, options).await;
+        }
+        let changed_files = match ref_name {
+            Some(_) => Vec::new(),
+            None => match self.changed_files() {
+                Ok(changed_files) => changed_files,
+                Err(e) => {
+                    tracing::debug!("Searching without the trigram index: {}", e);
+                    return self.perform_unindexed_code_search(ref_name, options).await;
+                }
+            },
+        };
+
+        let index = match self
+            .load_or_build_trigram_index(ref_name.unwrap_or("HEAD"))
+            .await
+        {
+            Ok(index) => index,
+            Err(e) => {
+                tracing::debug!("Searching without the trigram index: {}", e);
+                return self.perform_unindexed_code_search(ref_name, options).await;
+            }
+        };
+        let mut candidates: BTreeSet<String> = match query.candidate_files(&index) {
+            Some(files) => files
+                .into_iter()
+                .map(|file| index.files[file as usize].clone())
+                .collect(),
+            None => index.files.iter().cloned().collect(),
+        };
+        candidates.extend(changed_files);
+        tracing::debug!(
+            "Trigram index narrowed the search to {} of {} files",
+            candidates.len(),
+            index.files.len()
+        );
+
+        self.perform_code_search_in_files(candidates, ref_name, options)
+            .await
+    }
+
+    /// Searches all files of the worktree or of the tree of a ref
+    async fn perform_unindexed_code_search(
+        &self,
+        ref_name: Option<&str>,
+        options: CodeSearchOptions,
+    ) -> Result<CodeSearchResult, String> {
+        match ref_name {
+            Some(ref_name) => self.perform_code_search_at_ref(ref_name, options).await,
+            None => self.perform_code_search(options).await,
+        }
+    }
+
+    /// Reads the cached trigram index of the tree of a ref, building and caching it if needed
+    ///
+    /// A cache that can't be read is rebuilt. Failing to write the cache isn't an error,
+    /// the index is just built again next time. Reading and building run on a blocking
+    /// thread without holding the lock of the loaded indexes, so searches of other trees
+    /// aren't held up by a large build.
+    async fn load_or_build_trigram_index(
+        &self,
+        ref_name: &str,
+    ) -> Result<Arc<TrigramIndex>, String> {
+        let repo = self.open_git_repository()?;
+        let tree_id = peel_ref_to_commit(&repo, ref_name)?
+            .tree_id()
+            .map_err(|e| format!("Failed to read tree of '{}': {}", ref_name, e))?
+            .to_hex()
+            .to_string();
+        let index_path = repo
+            .git_dir()
+            .join(INDEX_DIR_NAME)
+            .join(trigram_index_file_name(&tree_id));
+
+        if let Some(index) = find_loaded_index(&index_path)? {
+            return Ok(index);
+        }
+
+        let local_repo = self.clone();
+        let ref_name = ref_name.to_string();
+        let path = index_path.clone();
+        let index = tokio::task::spawn_blocking(move || {
+            local_repo.read_or_build_trigram_index(&ref_name, tree_id, &path)
+        })
+        .await
+        .map_err(|e| format!("Failed to build trigram index: {}", e))??;
+
+        let index = Arc::new(index);
+        let mut loaded = lock_loaded_indexes()?;
+        // A concurrent search of the same tree may have loaded it in the meantime
+        loaded.retain(|(path, _)| *path != index_path);
+        if loaded.len() >= MAX_LOADED_INDEXES {
+            loaded.remove(0);
+        }
+        loaded.push((index_path, index.clone()));
+        Ok(index)
+    }
+
+    /// Reads the cached trigram index at `index_path`, or builds and caches it if needed
+    fn read_or_build_trigram_index(
+        &self,
+        ref_name: &str,
+        tree_id: String,
+        index_path: &Path,
+    ) -> Result<TrigramIndex, String> {
+        let cached = std::fs::read(index_path).ok().and_then(|data| {
+            match serde_json::from_slice::<TrigramIndex>(&data) {
+                Ok(index) if index.tree_id == tree_id => Some(index),
+                Ok(_) => {
+                    tracing::warn!("Ignoring mismatched index {}", index_path.display());
+                    None
+                }
+                Err(e) => {
+                    tracing::warn!("Ignoring unreadable index {}: {}", index_path.display(), e);
+                    None
+                }
+            }
+        });
+        if let Some(index) = cached {
+            return Ok(index);
+        }
+
+        tracing::info!("Building trigram index of tree {}", tree_id);
+        let repo = self.open_git_repository()?;
+        let index = build_trigram_index(&repo, ref_name, tree_id)?;
+        if let Err(e) = write_index(index_path, &index) {
+            tracing::warn!(
+                "Failed to cache trigram index at {}: {}",
+                index_path.display(),
+                e
+            );
+        }
+        Ok(index)
+    }
+}
+
+/// Locks the loaded indexes
+fn lock_loaded_indexes() -> Result<std::sync::MutexGuard<'static, LoadedIndexes>, String> {
+    LOADED_INDEXES
+        .lock()
+        .map_err(|_| "The trigram index cache is poisoned".to_string())
+}
+
+/// Returns the loaded index cached at `index_path`, marking it as the most recently used
+fn find_loaded_index(index_path: &Path) -> Result<Option<Arc<TrigramIndex>>, String> {
+    let mut loaded = lock_loaded_indexes()?;
+    let Some(position) = loaded.iter().position(|(path, _)| path == index_path) else {
+        return Ok(None);
+    };
+    let entry = loaded.remove(position);
+    let index = entry.1.clone();
+    loaded.push(entry);
+    Ok(Some(index))
+}
+
+/// The file name of the cached trigram index of a tree
+pub(super) fn trigram_index_file_name(tree_id: &str) -> String {
+    format!(
+        "{}{}-{}.json",
+        TRIGRAM_INDEX_FILE_PREFIX, INDEX_FORMAT_VERSION, tree_id
+    )
+}
+
+/// Records the trigrams of every text file in the tree of a ref
+///
+/// Symlinks, submodules and blobs containing NUL bytes are skipped, as the search skips
+/// them too.
+fn build_trigram_index(
+    repo: &gix::Repository,
+    ref_name: &str,
+    tree_id: String,
+) -> Result<TrigramIndex, String> {
+    let tree = peel_ref_to_commit(repo, ref_name)?
+        .tree()
+        .map_err(|e| format!("Failed to read tree of '{}': {}", ref_name, e))?;
+    let records = tree
+        .traverse()
+        .breadthfirst
+        .files()
+        .map_err(|e| format!("Failed to traverse tree at '{}': {}", ref_name, e))?;
+
+    let mut index = TrigramIndex {
+        tree_id,
+        files: Vec::new(),
+        postings: HashMap::new(),
+    };
+    let mut file_trigrams = HashSet::new();
+    for record in records {
+        if !record.mode.is_blob() {
+            continue;
+        }
+        let blob = repo
+            .find_blob(record.oid)
+            .map_err(|e| format!("Failed to read blob for '{}': {}", record.filepath, e))?;
+        if blob.data.contains(&0) {
+            continue;
+        }
+
+        let file_number = index.files.len() as u32;
+        file_trigrams.clear();
+        file_trigrams.extend(trigrams(&blob.data));
+        for &trigram in &file_trigrams {
+            index.postings.entry(trigram).or_default().push(file_number);
+        }
+        index.files.push(record.filepath.to_string());
+    }
+    Ok(index)
+}
+
+/// The trigrams of some text, with ASCII letters lowercased
+fn trigrams(text: &[u8]) -> impl Iterator<Item = u32> + '_ {
+    text.windows(3).map(|window| {
+        window.iter().fold(0, |trigram, byte| {
+            (trigram << 8) | byte.to_ascii_lowercase() as u32
+        })
+    })
+}
+
+impl TrigramQuery {
+    /// Derives the trigrams a file must contain to match a regex
+    ///
+    /// Patterns the regex parser rejects get [`TrigramQuery::All`], leaving the error to
+    /// the search itself.
+    fn from_pattern(pattern: &str, case_sensitive: bool) -> Self {
+        match regex_syntax::ParserBuilder::new().build().parse(pattern) {
+            Ok(hir) => Self::from_hir(&hir, case_sensitive).simplify(),
+            Err(_) => TrigramQuery::All,
+        }
+    }
+
+    fn from_hir(hir: &Hir, case_sensitive: bool) -> Self {
+        match hir.kind() {
+            HirKind::Literal(literal) => Self::from_literal(&literal.0, case_sensitive),
+            HirKind::Capture(capture) => Self::from_hir(&capture.sub, case_sensitive),
+            HirKind::Repetition(repetition) if repetition.min > 0 => {
+                Self::from_hir(&repetition.sub, case_sensitive)
+            }
+            HirKind::Concat(parts) => TrigramQuery::And(
+                parts
+                    .iter()
+                    .map(|part| Self::from_hir(part, case_sensitive))
+                    .collect(),
+            ),
+            HirKind::Alternation(alternatives) => TrigramQuery::Or(
+                alternatives
+                    .iter()
+                    .map(|alternative| Self::from_hir(alternative, case_sensitive))
+                    .collect(),
+            ),
+            _ => TrigramQuery::All,
+        }
+    }
+
+    /// The trigrams of a literal
+    ///
+    /// A case insensitive search also matches other spellings of non-ASCII characters,
+    /// so only the trigrams of the ASCII runs of the literal are required then.
+    fn from_literal(literal: &[u8], case_sensitive: bool) -> Self {
+        let trigrams: BTreeSet<u32> = if case_sensitive {
+            trigrams(literal).collect()
+        } else {
+            literal
+                .split(|byte| !byte.is_ascii())
+                .flat_map(trigrams)
+                .collect()
+        };
+        if trigrams.is_empty() {
+            TrigramQuery::All
+        } else {
+            TrigramQuery::Trigrams(trigrams)
+        }
+    }
+
+    /// Drops the parts that don't restrict anything
+    fn simplify(self) -> Self {
+        match self {
+            TrigramQuery::And(parts) => {
+                let mut parts: Vec<_> = parts
+                    .into_iter()
+                    .map(Self::simplify)
+                    .filter(|part| *part != TrigramQuery::All)
+                    .collect();
+                match parts.len() {
+                    0 => TrigramQuery::All,
+                    1 => parts.remove(0),
+                    _ => TrigramQuery::And(parts),
+                }
+            }
+            TrigramQuery::Or(alternatives) => {
+                let mut alternatives: Vec<_> =
+                    alternatives.into_iter().map(Self::simplify).collect();
+                if alternatives.is_empty() || alternatives.contains(&TrigramQuery::All) {
+                    TrigramQuery::All
+                } else if alternatives.len() == 1 {
+                    alternatives.remove(0)
+                } else {
+                    TrigramQuery::Or(alternatives)
+                }
+            }
+            query => query,
+        }
+    }
+
+    /// The files of an index that may match, `None` for all of them
+    fn candidate_files(&self, index: &TrigramIndex) -> Option<BTreeSet<u32>> {
+        match self {
+            TrigramQuery::All => None,
+            TrigramQuery::Trigrams(trigrams) => {
+                let mut files: Option<BTreeSet<u32>> = None;
+                for trigram in trigrams {
+                    let posting = index.postings.get(trigram).map(Vec::as_slice);
+                    let posting = posting.unwrap_or_default().iter().copied();
+                    files = Some(match files {
+                        None => posting.collect(),
+                        Some(files) => posting.filter(|file| files.contains(file)).collect(),
+                    });
+                }
+                files
+            }
+            TrigramQuery::And(parts) => parts
+                .iter()
+                .filter_map(|part| part.candidate_files(index))
+                .reduce(|a, b| a.intersection(&b).copied().collect()),
+            TrigramQuery::Or(alternatives) => {
+                let mut files = BTreeSet::new();
+                for alternative in alternatives {
+                    files.extend(alternative.candidate_files(index)?);
+                }
+                Some(files)
+            }
+        }
+    }
+}
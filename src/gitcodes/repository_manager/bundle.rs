@@ -0,0 +1,265 @@
+//! Unpacking of git bundle files into repositories
+//!
+//! A bundle (`git bundle create repo.bundle --all`) is a header listing refs followed by a
+//! packfile, which makes it a convenient way to ship a repository into environments without
+//! network access. gitoxide has no transport for bundles, so the header is parsed here, the
+//! pack is indexed into a freshly initialized repository, the refs are recreated and the
+//! default branch is checked out.
+//!
+//! Only complete bundles are supported: incremental bundles that require commits from
+//! another repository (prerequisites) and filtered (partial) bundles are rejected.
+
+use std::io::BufRead;
+use std::path::Path;
+
+use gix::ObjectId;
+use gix::refs::transaction::{Change, LogChange, PreviousValue, RefEdit};
+
+/// Signature line of version 2 bundles
+const BUNDLE_V2_SIGNATURE: &str = "# v2 git bundle";
+/// Signature line of version 3 bundles, which may add capabilities
+const BUNDLE_V3_SIGNATURE: &str = "# v3 git bundle";
+
+/// The ref section of a bundle header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BundleHeader {
+    /// Refs in the order listed, as (object id, full ref name) pairs; may include `HEAD`
+    pub refs: Vec<(ObjectId, String)>,
+}
+
+/// Reads the header of a bundle up to the blank line that precedes the packfile
+///
+/// # Errors
+///
+/// Returns an error if the signature is unknown, a line is malformed, the bundle has
+/// prerequisites or uses a capability other than the SHA-1 object format.
+pub(crate) fn read_bundle_header(reader: &mut impl BufRead) -> Result<BundleHeader, String> {
+    let mut read_line = || -> Result<Option<String>, String> {
+        let mut line = String::new();
+        let read = reader
+            .read_line(&mut line)
+            .map_err(|e| format!("Failed to read bundle header: {}", e))?;
+        Ok((read > 0).then(|| line.trim_end_matches(['\n', '\r']).to_string()))
+    };
+
+    let signature = read_line()?.unwrap_or_default();
+    let is_v3 = match signature.as_str() {
+        BUNDLE_V2_SIGNATURE => false,
+        BUNDLE_V3_SIGNATURE => true,
+        _ => {
+            return Err(
+                "Not a git bundle: expected a '# v2 git bundle' or '# v3 git bundle' header"
+                    .to_string(),
+            );
+        }
+    };
+
+    let mut refs = Vec::new();
+    loop {
+        let Some(line) = read_line()? else {
+            return Err("Invalid git bundle: the header is not followed by a packfile".to_string());
+        };
+        if line.is_empty() {
+            break;
+        }
+
+        if let Some(capability) = line.strip_prefix('@') {
+            if !is_v3 {
+                return Err(format!(
+                    "Invalid git bundle: capability '{}' in a v2 bundle",
+                    capability
+                ));
+            }
+            if capability != "object-format=sha1" {
+                return Err(format!(
+                    "Unsupported git bundle capability '{}': only complete SHA-1 bundles can be imported",
+                    capability
+                ));
+            }
+            continue;
+        }
+
+        if let Some(prerequisite) = line.strip_prefix('-') {
+            let commit = prerequisite.split(' ').next().unwrap_or_default();
+            return Err(format!(
+                "Incremental git bundles are not supported: the bundle requires commit {} which it doesn't contain. Create a complete bundle with 'git bundle create <file> --all'",
+                commit
+            ));
+        }
+
+        let (id, name) = line
+            .split_once(' ')
+            .ok_or_else(|| format!("Invalid git bundle: malformed ref line '{}'", line))?;
+        let id = ObjectId::from_hex(id.as_bytes())
+            .map_err(|e| format!("Invalid git bundle: bad object id in '{}': {}", line, e))?;
+        refs.push((id, name.to_string()));
+    }
+
+    if refs.is_empty() {
+        return Err("Invalid git bundle: it doesn't contain any refs".to_string());
+    }
+    Ok(BundleHeader { refs })
+}
+
+/// Unpacks a bundle into a new repository with a working tree at `destination`
+///
+/// The refs of the bundle are recreated under their original names, so branches stay
+/// local branches and tags stay tags. `HEAD` points to the branch the bundle's `HEAD`
+/// refers to, or else to `main`, `master` or the first branch.
+///
+/// # Errors
+///
+/// Returns an error if the bundle cannot be read or is unsupported (see
+/// [`read_bundle_header`]), or if the repository cannot be written. A partially written
+/// `destination` is left for the caller to remove.
+pub(crate) fn unpack_bundle(bundle_path: &Path, destination: &Path) -> Result<(), String> {
+    let file = std::fs::File::open(bundle_path)
+        .map_err(|e| format!("Failed to open bundle {}: {}", bundle_path.display(), e))?;
+    let mut reader = std::io::BufReader::new(file);
+    let header = read_bundle_header(&mut reader)
+        .map_err(|e| format!("{} ({})", e, bundle_path.display()))?;
+
+    let repo = gix::init(destination).map_err(|e| {
+        format!(
+            "Failed to create repository at {}: {}",
+            destination.display(),
+            e
+        )
+    })?;
+
+    // The rest of the file is a packfile, which is indexed straight into the object database
+    let pack_dir = repo.objects.store_ref().path().join("pack");
+    let outcome = gix::odb::pack::Bundle::write_to_directory(
+        &mut reader,
+        Some(&pack_dir),
+        &mut gix::progress::Discard,
+        &gix::interrupt::IS_INTERRUPTED,
+        None::<gix::objs::find::Never>,
+        Default::default(),
+    )
+    .map_err(|e| format!("Failed to unpack bundle {}: {}", bundle_path.display(), e))?;
+    if let Some(keep_path) = outcome.keep_path {
+        let _ = std::fs::remove_file(keep_path);
+    }
+    // Pick up the new pack
+    let mut repo =
+        gix::open(destination).map_err(|e| format!("Failed to open unpacked bundle: {}", e))?;
+    // Reflogs need a committer identity, which may not be configured, and aren't used anyway
+    repo.refs.write_reflog = gix::refs::store::WriteReflog::Disable;
+
+    let mut head_target = None;
+    for (id, name) in &header.refs {
+        if name == "HEAD" {
+            head_target = Some(*id);
+            continue;
+        }
+        repo.reference(
+            name.as_str(),
+            *id,
+            PreviousValue::Any,
+            "gitcodes: import from bundle",
+        )
+        .map_err(|e| format!("Failed to create ref '{}' from bundle: {}", name, e))?;
+    }
+
+    set_head(&repo, &header, head_target)?;
+    checkout_head(&repo)
+}
+
+/// Points `HEAD` to the branch the bundle's `HEAD` refers to, or a default branch
+fn set_head(
+    repo: &gix::Repository,
+    header: &BundleHeader,
+    head_target: Option<ObjectId>,
+) -> Result<(), String> {
+    let branches: Vec<&(ObjectId, String)> = header
+        .refs
+        .iter()
+        .filter(|(_, name)| name.starts_with("refs/heads/"))
+        .collect();
+    let preferred = |candidates: &[&(ObjectId, String)]| {
+        ["refs/heads/main", "refs/heads/master"]
+            .iter()
+            .find_map(|default| candidates.iter().find(|(_, name)| name == default))
+            .or_else(|| candidates.first())
+            .map(|(_, name)| name.clone())
+    };
+
+    let branch = match head_target {
+        Some(head_id) => {
+            let matching: Vec<_> = branches
+                .iter()
+                .copied()
+                .filter(|(id, _)| *id == head_id)
+                .collect();
+            preferred(&matching)
+        }
+        None => preferred(&branches),
+    };
+
+    let new = match (branch, head_target) {
+        (Some(branch), _) => gix::refs::Target::Symbolic(
+            branch
+                .as_str()
+                .try_into()
+                .map_err(|e| format!("Invalid branch name '{}' in bundle: {}", branch, e))?,
+        ),
+        (None, Some(head_id)) => gix::refs::Target::Object(head_id),
+        (None, None) => gix::refs::Target::Object(header.refs[0].0),
+    };
+
+    repo.edit_reference(RefEdit {
+        change: Change::Update {
+            log: LogChange::default(),
+            expected: PreviousValue::Any,
+            new,
+        },
+        name: "HEAD"
+            .try_into()
+            .map_err(|e| format!("Invalid HEAD name: {}", e))?,
+        deref: false,
+    })
+    .map_err(|e| format!("Failed to set HEAD of unpacked bundle: {}", e))?;
+    Ok(())
+}
+
+/// Writes the tree of `HEAD` to the working tree and the index, like a clone does
+fn checkout_head(repo: &gix::Repository) -> Result<(), String> {
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| "Unpacked bundle has no working tree".to_string())?;
+    let tree_id = repo
+        .head_commit()
+        .map_err(|e| format!("Failed to read HEAD of unpacked bundle: {}", e))?
+        .tree_id()
+        .map_err(|e| format!("Failed to read HEAD tree of unpacked bundle: {}", e))?;
+
+    let mut index = repo
+        .index_from_tree(&tree_id)
+        .map_err(|e| format!("Failed to build index of unpacked bundle: {}", e))?;
+    let mut options = repo
+        .checkout_options(gix::worktree::stack::state::attributes::Source::IdMapping)
+        .map_err(|e| format!("Failed to read checkout options: {}", e))?;
+    options.destination_is_initially_empty = true;
+
+    let objects = repo
+        .objects
+        .clone()
+        .into_arc()
+        .map_err(|e| format!("Failed to prepare object database for checkout: {}", e))?;
+    gix::worktree::state::checkout(
+        &mut index,
+        workdir,
+        objects,
+        &gix::progress::Discard,
+        &gix::progress::Discard,
+        &gix::interrupt::IS_INTERRUPTED,
+        options,
+    )
+    .map_err(|e| format!("Failed to check out unpacked bundle: {}", e))?;
+
+    index
+        .write(Default::default())
+        .map_err(|e| format!("Failed to write index of unpacked bundle: {}", e))?;
+    Ok(())
+}
@@ -0,0 +1,125 @@
+//! Named collections of repository locations
+//!
+//! Searching the same set of repositories again and again ("the top 10 async runtimes")
+//! is easier with a name for the set. Collections are saved as a JSON object mapping each
+//! name to its locations, in a file inside the repository cache directory, so they live
+//! as long as the cached clones and are shared by every process using that directory.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use super::RepositoryManager;
+
+/// File name of the saved collections inside the repository cache directory
+const COLLECTIONS_FILE_NAME: &str = "gitcodes_repository_collections.json";
+
+/// Saved collections by name
+type Collections = BTreeMap<String, Vec<String>>;
+
+impl RepositoryManager {
+    /// Saves a named collection of repository locations, replacing one of the same name
+    ///
+    /// # Parameters
+    ///
+    /// * `name` - Name of the collection, e.g. `async-runtimes`
+    /// * `repository_locations` - The repository locations, in any format that
+    ///   [`super::RepositoryLocation`] accepts; they aren't checked until searched
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), String>` - Success or an error message
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name or the list of locations is empty, or the saved
+    /// collections can't be read or written.
+    pub fn save_repository_collection(
+        &self,
+        name: &str,
+        repository_locations: Vec<String>,
+    ) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("The collection name must not be empty".to_string());
+        }
+        if repository_locations.is_empty() {
+            return Err(format!(
+                "Collection '{}' needs at least one repository location",
+                name
+            ));
+        }
+
+        let mut collections = self.read_repository_collections()?;
+        collections.insert(name.to_string(), repository_locations);
+        let data = serde_json::to_vec_pretty(&collections)
+            .map_err(|e| format!("Failed to serialize repository collections: {}", e))?;
+        // Written to a temporary file first and then moved into place, so other processes
+        // never read a partially written file
+        let path = self.repository_collections_path();
+        let partial_path =
+            path.with_extension(format!("partial-{}", uuid::Uuid::new_v4().simple()));
+        std::fs::write(&partial_path, data)
+            .and_then(|_| std::fs::rename(&partial_path, &path))
+            .map_err(|e| {
+                let _ = std::fs::remove_file(&partial_path);
+                format!(
+                    "Failed to save repository collections to {}: {}",
+                    path.display(),
+                    e
+                )
+            })
+    }
+
+    /// Returns the repository locations of a saved collection
+    ///
+    /// # Parameters
+    ///
+    /// * `name` - Name the collection was saved under
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<String>, String>` - The locations in the order saved or an error message
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the saved collections if there is none called `name`.
+    pub fn repository_collection(&self, name: &str) -> Result<Vec<String>, String> {
+        let mut collections = self.read_repository_collections()?;
+        collections.remove(name.trim()).ok_or_else(|| {
+            let names: Vec<&str> = collections.keys().map(String::as_str).collect();
+            format!(
+                "No repository collection named '{}'; saved collections: [{}]",
+                name,
+                names.join(", ")
+            )
+        })
+    }
+
+    fn repository_collections_path(&self) -> PathBuf {
+        self.local_repository_cache_dir_base
+            .join(COLLECTIONS_FILE_NAME)
+    }
+
+    /// Reads the saved collections, none if nothing was saved yet
+    fn read_repository_collections(&self) -> Result<Collections, String> {
+        let path = self.repository_collections_path();
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Collections::new()),
+            Err(e) => {
+                return Err(format!(
+                    "Failed to read repository collections from {}: {}",
+                    path.display(),
+                    e
+                ));
+            }
+        };
+        serde_json::from_slice(&data).map_err(|e| {
+            format!(
+                "Invalid repository collections file {}: {}",
+                path.display(),
+                e
+            )
+        })
+    }
+}
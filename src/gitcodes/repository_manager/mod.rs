@@ -1,6 +1,9 @@
+mod bundle;
+mod collections;
 pub mod instance;
 pub mod providers;
 mod repository_location;
+pub mod version_tags;
 
 use std::{num::NonZeroU32, path::PathBuf, str::FromStr};
 
@@ -335,6 +338,26 @@ impl RepositoryManager {
                     ))
                 }
             }
+            // For bundles, check if the bundle has been unpacked already
+            RepositoryLocation::Bundle(bundle_path) => {
+                let local_repo = LocalRepository::new_local_repository_for_bundle(
+                    bundle_path,
+                    &self.local_repository_cache_dir_base,
+                    Some(&self.process_id),
+                );
+                let repo_dir = local_repo.get_repository_dir();
+                if repo_dir.is_dir() {
+                    local_repo
+                        .validate()
+                        .map_err(|e| format!("Repository exists but is invalid: {}", e))?;
+                    Ok(local_repo)
+                } else {
+                    Err(format!(
+                        "Bundle not unpacked locally at {}",
+                        repo_dir.display()
+                    ))
+                }
+            }
         }
     }
 
@@ -363,21 +386,38 @@ impl RepositoryManager {
                 Ok(local_path.clone())
             }
             RepositoryLocation::RemoteRepository(remote_repository) => {
-                let remote_repository_with_ref_name_if_any = match (remote_repository, ref_name) {
-                    // If we have a ref_name, create a new instance with that ref_name
-                    (GitRemoteRepository::Github(github_info), Some(ref_name_str)) => {
-                        // Create a new GitHub info with the updated ref_name
-                        let mut updated_github_info = github_info.clone();
-                        updated_github_info.repo_info.ref_name = Some(ref_name_str);
-                        GitRemoteRepository::Github(updated_github_info)
-                    }
-                    // Otherwise just clone the original repository
-                    _ => remote_repository.clone(),
-                };
-
-                self.clone_repository(&remote_repository_with_ref_name_if_any)
-                    .await
+                // `latest` / `latest-stable` are resolved from the complete tag listing of
+                // the remote once the default branch is cloned, rather than checked out
+                let clone_ref_name = ref_name.as_ref().filter(|ref_name| {
+                    version_tags::LatestAlias::from_ref_name(ref_name).is_none()
+                });
+
+                let remote_repository_with_ref_name_if_any =
+                    match (remote_repository, clone_ref_name) {
+                        // If we have a ref_name, create a new instance with that ref_name
+                        (GitRemoteRepository::Github(github_info), Some(ref_name_str)) => {
+                            // Create a new GitHub info with the updated ref_name
+                            let mut updated_github_info = github_info.clone();
+                            updated_github_info.repo_info.ref_name = Some(ref_name_str.clone());
+                            GitRemoteRepository::Github(updated_github_info)
+                        }
+                        // Otherwise just clone the original repository
+                        _ => remote_repository.clone(),
+                    };
+
+                let local_repo = self
+                    .clone_repository(&remote_repository_with_ref_name_if_any)
+                    .await?;
+                // A reused clone only holds the refs fetched so far. For an alias this
+                // fetches the tag it picks, so that resolving the alias locally picks it too
+                if let Some(ref_name) = &ref_name {
+                    local_repo.ensure_ref_available(ref_name).await?;
+                }
+                Ok(local_repo)
             }
+            // A bundle contains all of its refs, so they are resolved from the unpacked
+            // repository later rather than checked out here
+            RepositoryLocation::Bundle(bundle_path) => self.unpack_bundle(bundle_path).await,
         }
     }
 
@@ -838,6 +878,67 @@ impl RepositoryManager {
         }
     }
 
+    /// Unpacks a git bundle into the cache directory (reuses an earlier unpacked copy)
+    ///
+    /// The bundle is unpacked into a temporary directory next to its final location first
+    /// and then moved into place, so an interrupted import is never mistaken for a valid
+    /// repository.
+    ///
+    /// # Parameters
+    ///
+    /// * `bundle_path` - Absolute path of the bundle file
+    ///
+    /// # Returns
+    ///
+    /// * `Result<LocalRepository, String>` - The repository unpacked from the bundle or an error
+    async fn unpack_bundle(
+        &self,
+        bundle_path: &std::path::Path,
+    ) -> Result<LocalRepository, String> {
+        let local_repo = LocalRepository::new_local_repository_for_bundle(
+            bundle_path,
+            &self.local_repository_cache_dir_base,
+            Some(&self.process_id),
+        );
+        let repo_dir = local_repo.get_repository_dir();
+        if repo_dir.is_dir() && local_repo.validate().is_ok() {
+            tracing::info!(
+                "Bundle already unpacked at {}, reusing it",
+                repo_dir.display()
+            );
+            return Ok(local_repo);
+        }
+
+        let partial_dir = repo_dir.with_file_name(format!(
+            "{}_partial_{}",
+            repo_dir
+                .file_name()
+                .map(|name| name.to_string_lossy())
+                .unwrap_or_default(),
+            uuid::Uuid::new_v4().simple()
+        ));
+        tracing::info!(
+            "Unpacking bundle {} to {}",
+            bundle_path.display(),
+            repo_dir.display()
+        );
+
+        let unpacked = bundle::unpack_bundle(bundle_path, &partial_dir).and_then(|_| {
+            if repo_dir.exists() {
+                std::fs::remove_dir_all(repo_dir)
+                    .map_err(|e| format!("Failed to remove invalid repository directory: {}", e))?;
+            }
+            std::fs::rename(&partial_dir, repo_dir)
+                .map_err(|e| format!("Failed to move unpacked bundle into place: {}", e))
+        });
+        if let Err(e) = unpacked {
+            let _ = std::fs::remove_dir_all(&partial_dir);
+            return Err(e);
+        }
+
+        Ok(local_repo)
+    }
+
     /// Returns a GitHub API client instance
     ///
     /// Creates a new GitHub client with the manager's authentication token
@@ -891,15 +992,23 @@ impl RepositoryManager {
                     GitRemoteRepository::Github(github_repo_info) => {
                         // For GitHub repositories, use the GitHub API
                         let github_client = self.get_github_client()?;
-                        let refs = github_client
+                        let mut refs = github_client
                             .list_repository_refs(&github_repo_info.repo_info)
                             .await?;
+                        version_tags::sort_tags_by_version(&mut refs.tags);
 
                         // Return the structured refs result without a local repository reference
                         Ok((refs, None))
                     }
                 }
             }
+            bundle @ RepositoryLocation::Bundle(_) => {
+                // Bundles have no remote to fetch from, so list the unpacked refs as they are
+                let local_repo = self.prepare_repository(bundle, None).await?;
+                let mut refs = local_repo.list_repository_refs().await?;
+                version_tags::sort_tags_by_version(&mut refs.tags);
+                Ok((refs, Some(local_repo)))
+            }
             local_repository @ RepositoryLocation::LocalPath(_) => {
                 // For local repositories, prepare the repository and use git commands
                 let local_repo = self.prepare_repository(local_repository, None).await?;
@@ -912,7 +1021,8 @@ impl RepositoryManager {
                 }
 
                 // Use the local repository to list refs
-                let refs = local_repo.list_repository_refs().await?;
+                let mut refs = local_repo.list_repository_refs().await?;
+                version_tags::sort_tags_by_version(&mut refs.tags);
 
                 // Return both the structured refs and the local repository reference
                 Ok((refs, Some(local_repo)))
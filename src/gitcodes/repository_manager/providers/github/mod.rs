@@ -235,7 +235,6 @@ pub struct GithubSearchParams {
 ///    assignee: None,
 ///    milestone: None,
 ///    issue_type: None,
-
 /// };
 ///
 /// // Advanced search with boolean operations
@@ -253,7 +252,6 @@ pub struct GithubSearchParams {
 ///    assignee: None,
 ///    milestone: None,
 ///    issue_type: None,
-
 /// };
 /// ```
 #[derive(Debug, Serialize, Deserialize)]
@@ -396,6 +394,7 @@ impl GithubClient {
     /// - `author:username` - Filter by author
     /// - `created:2021-01-01..2021-12-31` - Filter by creation date range
     /// - `updated:>2021-01-01` - Filter by last update date
+    ///
     /// Search for issues and pull requests in a GitHub repository
     ///
     /// This method sends a request to GitHub's search API to find issues and pull requests
@@ -411,7 +410,6 @@ impl GithubClient {
     /// use gitcodes_mcp::gitcodes::repository_manager::providers::github::{GithubIssueSearchParams, GithubIssueSortOption, GithubOrderOption};
     ///
     /// // Search for open bugs in a specific repository
-
     /// ```
     pub async fn search_issues(
         &self,
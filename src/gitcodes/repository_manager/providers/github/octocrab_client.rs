@@ -7,16 +7,169 @@ use super::{GitRemoteRepositoryInfo, GithubIssueSearchParams, GithubSearchParams
 use crate::gitcodes::repository_manager::providers::*;
 use octocrab::models::{issues::Issue as OctocrabIssue, Repository as OctocrabRepository};
 use octocrab::{Octocrab, Page};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::gitcodes::local_repository::{GitRefObject, ObjectType, RefObject, TagDetails};
+
+/// GraphQL query listing the default branch and one page of the refs under a prefix
+///
+/// A page holds at most 100 refs; `pageInfo.endCursor` is passed back as `after` for the next one.
+const REFS_GRAPHQL_QUERY: &str = r#"
+query($owner: String!, $name: String!, $refPrefix: String!, $after: String) {
+  repository(owner: $owner, name: $name) {
+    defaultBranchRef { name }
+    refs(refPrefix: $refPrefix, first: 100, after: $after) {
+      pageInfo { hasNextPage endCursor }
+      nodes { name target { ...RefTarget } }
+    }
+  }
+}
+
+fragment RefTarget on GitObject {
+  __typename
+  oid
+  ... on Commit { committedDate }
+  ... on Tag {
+    message
+    tagger { name email date }
+    target { oid ... on Commit { committedDate } }
+  }
+}
+"#;
+
+/// Envelope of a GraphQL response
+#[derive(Debug, Deserialize)]
+struct GraphqlResponse<T> {
+    data: Option<T>,
+    errors: Option<Vec<GraphqlError>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphqlError {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphqlRefsData {
+    repository: Option<GraphqlRepositoryRefs>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphqlRepositoryRefs {
+    default_branch_ref: Option<GraphqlBranchName>,
+    refs: GraphqlRefConnection,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphqlBranchName {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphqlRefConnection {
+    page_info: GraphqlPageInfo,
+    nodes: Vec<GraphqlRef>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphqlPageInfo {
+    has_next_page: bool,
+    end_cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphqlRef {
+    name: String,
+    target: GraphqlRefTarget,
+}
+
+/// The object a ref points to; the tag fields are only set for annotated tags
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphqlRefTarget {
+    #[serde(rename = "__typename")]
+    typename: String,
+    oid: String,
+    committed_date: Option<String>,
+    message: Option<String>,
+    tagger: Option<GraphqlTagger>,
+    target: Option<GraphqlTagTarget>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphqlTagger {
+    name: Option<String>,
+    email: Option<String>,
+    date: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphqlTagTarget {
+    oid: String,
+    committed_date: Option<String>,
+}
+
+impl GraphqlRef {
+    /// Converts the ref into the common model, peeling annotated tags to their commit
+    fn into_reference_info(self, prefix: &str) -> ReferenceInfo {
+        let target = self.target;
+        let object_type = match target.typename.as_str() {
+            "Tag" => ObjectType::Tag,
+            "Tree" => ObjectType::Tree,
+            "Blob" => ObjectType::Blob,
+            _ => ObjectType::Commit,
+        };
+
+        let (commit_id, commit_date, tag) = if object_type == ObjectType::Tag {
+            let tagger = target.tagger;
+            let details = TagDetails {
+                tagger_name: tagger.as_ref().and_then(|tagger| tagger.name.clone()),
+                tagger_email: tagger.as_ref().and_then(|tagger| tagger.email.clone()),
+                date: tagger.and_then(|tagger| tagger.date),
+                message: target.message.unwrap_or_default().trim_end().to_string(),
+            };
+            let (commit_id, commit_date) = match target.target {
+                Some(peeled) => (peeled.oid, peeled.committed_date),
+                None => (target.oid.clone(), None),
+            };
+            (commit_id, commit_date, Some(details))
+        } else {
+            (target.oid.clone(), target.committed_date, None)
+        };
+
+        ReferenceInfo {
+            object: Some(RefObject {
+                sha: target.oid,
+                object_type,
+            }),
+            commit_date,
+            tag,
+            ..ReferenceInfo::new(
+                self.name.clone(),
+                format!("{}{}", prefix, self.name),
+                commit_id,
+            )
+        }
+    }
+}
 
 /// Octocrab-based GitHub client
 #[derive(Debug, Clone)]
 pub struct OctocrabGithubClient {
     client: Octocrab,
+    /// Whether a token was configured; the GraphQL API is only available with one
+    authenticated: bool,
 }
 
 impl OctocrabGithubClient {
     /// Create a new OctocrabGithubClient
     pub fn new(github_token: Option<String>) -> Result<Self, String> {
+        let authenticated = github_token.is_some();
         let client = if let Some(token) = github_token {
             Octocrab::builder()
                 .personal_token(token)
@@ -28,7 +181,10 @@ impl OctocrabGithubClient {
                 .map_err(|e| format!("Failed to create octocrab client: {}", e))?
         };
 
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            authenticated,
+        })
     }
 
     /// Search repositories using octocrab
@@ -102,53 +258,214 @@ impl OctocrabGithubClient {
     }
 
     /// List repository refs (branches and tags)
+    ///
+    /// With a token, the GraphQL API returns the default branch, commit dates and annotated
+    /// tag details. Without one, the REST API is used, which reports the default branch and
+    /// which tags are annotated, but no dates or tag messages: fetching each tag object would
+    /// quickly exhaust the unauthenticated rate limit. Both page through every ref.
     pub async fn list_repository_refs(
         &self,
         repo_info: &GitRemoteRepositoryInfo,
+    ) -> Result<RepositoryRefs, String> {
+        if self.authenticated {
+            self.list_repository_refs_graphql(repo_info).await
+        } else {
+            self.list_repository_refs_rest(repo_info).await
+        }
+    }
+
+    /// List repository refs with the GraphQL API (requires a token)
+    async fn list_repository_refs_graphql(
+        &self,
+        repo_info: &GitRemoteRepositoryInfo,
+    ) -> Result<RepositoryRefs, String> {
+        let (default_branch, branches) = self.list_refs_graphql(repo_info, "refs/heads/").await?;
+        let (_, tags) = self.list_refs_graphql(repo_info, "refs/tags/").await?;
+
+        Ok(RepositoryRefs {
+            branches,
+            tags,
+            default_branch,
+        })
+    }
+
+    /// List the refs under `ref_prefix` with the GraphQL API, following the page cursor
+    ///
+    /// Every page also reports the default branch, which is returned alongside the refs.
+    async fn list_refs_graphql(
+        &self,
+        repo_info: &GitRemoteRepositoryInfo,
+        ref_prefix: &str,
+    ) -> Result<(Option<String>, Vec<ReferenceInfo>), String> {
+        let mut refs = Vec::new();
+        let mut after: Option<String> = None;
+        let default_branch = loop {
+            let query = serde_json::json!({
+                "query": REFS_GRAPHQL_QUERY,
+                "variables": {
+                    "owner": repo_info.user,
+                    "name": repo_info.repo,
+                    "refPrefix": ref_prefix,
+                    "after": after,
+                },
+            });
+            let response: GraphqlResponse<GraphqlRefsData> = self
+                .client
+                .graphql(&query)
+                .await
+                .map_err(|e| format!("Failed to list refs: {}", e))?;
+
+            if let Some(errors) = response.errors.filter(|errors| !errors.is_empty()) {
+                let messages: Vec<String> = errors.into_iter().map(|error| error.message).collect();
+                return Err(format!("Failed to list refs: {}", messages.join("; ")));
+            }
+            let repository = response
+                .data
+                .and_then(|data| data.repository)
+                .ok_or_else(|| {
+                    format!(
+                        "Failed to list refs: repository {}/{} not found",
+                        repo_info.user, repo_info.repo
+                    )
+                })?;
+
+            let default_branch = repository.default_branch_ref.map(|branch| branch.name);
+            refs.extend(
+                repository
+                    .refs
+                    .nodes
+                    .into_iter()
+                    .map(|node| node.into_reference_info(ref_prefix)),
+            );
+            let page_info = repository.refs.page_info;
+            match page_info.end_cursor {
+                Some(end_cursor) if page_info.has_next_page => after = Some(end_cursor),
+                _ => break default_branch,
+            }
+        };
+
+        Ok((default_branch, refs))
+    }
+
+    /// List repository refs with the REST API
+    async fn list_repository_refs_rest(
+        &self,
+        repo_info: &GitRemoteRepositoryInfo,
     ) -> Result<RepositoryRefs, String> {
         let repo_handler = self.client.repos(&repo_info.user, &repo_info.repo);
 
-        // Get branches
-        let branches_result = repo_handler
+        // Get branches; a page holds at most 100, so all of them are requested
+        let branches_page = repo_handler
             .list_branches()
+            .per_page(100)
             .send()
             .await
             .map_err(|e| format!("Failed to list branches: {}", e))?;
+        let branches_result = self
+            .client
+            .all_pages(branches_page)
+            .await
+            .map_err(|e| format!("Failed to list branches: {}", e))?;
 
         let branches = branches_result
-            .items
             .into_iter()
             .map(|branch| {
-                let name = branch.name.clone();
-                ReferenceInfo {
-                    name,
-                    full_ref: format!("refs/heads/{}", branch.name),
-                    commit_id: branch.commit.sha,
-                }
+                let mut reference = ReferenceInfo::new(
+                    branch.name.clone(),
+                    format!("refs/heads/{}", branch.name),
+                    branch.commit.sha.clone(),
+                );
+                reference.object = Some(RefObject {
+                    sha: branch.commit.sha,
+                    object_type: ObjectType::Commit,
+                });
+                reference
             })
             .collect();
 
         // Get tags
-        let tags_result = repo_handler
+        let tags_page = repo_handler
             .list_tags()
+            .per_page(100)
             .send()
             .await
             .map_err(|e| format!("Failed to list tags: {}", e))?;
+        let tags_result = self
+            .client
+            .all_pages(tags_page)
+            .await
+            .map_err(|e| format!("Failed to list tags: {}", e))?;
+
+        // The tags listing is peeled to commits; the git refs tell which tags are annotated.
+        // This is best effort, so a failure only leaves the object types out.
+        let tag_objects = self.list_tag_objects(repo_info).await.unwrap_or_else(|e| {
+            tracing::debug!("Failed to list tag objects: {}", e);
+            HashMap::new()
+        });
 
         let tags = tags_result
-            .items
             .into_iter()
             .map(|tag| {
-                let name = tag.name.clone();
+                let full_ref = format!("refs/tags/{}", tag.name);
+                let object = tag_objects.get(&full_ref).cloned();
                 ReferenceInfo {
-                    name,
-                    full_ref: format!("refs/tags/{}", tag.name),
-                    commit_id: tag.commit.sha,
+                    object,
+                    ..ReferenceInfo::new(tag.name, full_ref, tag.commit.sha)
                 }
             })
             .collect();
 
-        Ok(RepositoryRefs { branches, tags })
+        // Best effort as well: the refs are still listed without the default branch
+        let default_branch = match repo_handler.get().await {
+            Ok(repository) => repository.default_branch,
+            Err(e) => {
+                tracing::debug!("Failed to get repository: {}", e);
+                None
+            }
+        };
+
+        Ok(RepositoryRefs {
+            branches,
+            tags,
+            default_branch,
+        })
+    }
+
+    /// List the git objects the tags of a repository point to, keyed by full ref name
+    ///
+    /// `/git/matching-refs/tags` returns at most 100 refs per page, so pages are requested
+    /// until one comes back short.
+    async fn list_tag_objects(
+        &self,
+        repo_info: &GitRemoteRepositoryInfo,
+    ) -> Result<HashMap<String, RefObject>, String> {
+        const PER_PAGE: usize = 100;
+
+        let route = format!(
+            "/repos/{}/{}/git/matching-refs/tags",
+            repo_info.user, repo_info.repo
+        );
+        let mut tag_objects = HashMap::new();
+        for page in 1.. {
+            let refs = self
+                .client
+                .get::<Vec<GitRefObject>, _, _>(
+                    &route,
+                    Some(&[("per_page", PER_PAGE), ("page", page)]),
+                )
+                .await
+                .map_err(|e| format!("Failed to list tag refs: {}", e))?;
+            let is_last_page = refs.len() < PER_PAGE;
+            tag_objects.extend(
+                refs.into_iter()
+                    .map(|git_ref| (git_ref.ref_name, git_ref.object)),
+            );
+            if is_last_page {
+                break;
+            }
+        }
+
+        Ok(tag_objects)
     }
 
     /// Build issue and pull request search query from parameters
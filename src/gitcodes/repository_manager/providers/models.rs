@@ -9,6 +9,8 @@ use serde::{Deserialize, Serialize};
 use strum::{AsRefStr, Display, EnumString};
 use url::Url;
 
+use crate::gitcodes::local_repository::{ObjectType, RefObject, TagDetails};
+
 /// Git Provider enum
 ///
 /// Enumerates the supported Git providers for repository search.
@@ -139,19 +141,23 @@ pub struct RepositoryLicense {
 ///
 /// This struct provides a vendor-agnostic representation of repository references.
 /// It separates branches and tags into separate collections for easier consumption.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RepositoryRefs {
     /// List of branch references
     pub branches: Vec<ReferenceInfo>,
 
     /// List of tag references
     pub tags: Vec<ReferenceInfo>,
+
+    /// Name of the default branch, as pointed to by the remote HEAD
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_branch: Option<String>,
 }
 
 /// Information about a git reference (branch or tag)
 ///
 /// Common representation of a git reference that works across Git providers.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ReferenceInfo {
     /// Reference name (branch or tag name without path prefix)
     pub name: String,
@@ -159,8 +165,42 @@ pub struct ReferenceInfo {
     /// Full reference path (e.g., "refs/heads/main")
     pub full_ref: String,
 
-    /// Commit SHA this reference points to
+    /// Commit SHA this reference points to (annotated tags are peeled to their commit)
     pub commit_id: String,
+
+    /// The object the reference points to directly
+    ///
+    /// This is a tag object for annotated tags and the commit for branches and
+    /// lightweight tags. `None` when the provider didn't report it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object: Option<RefObject>,
+
+    /// Commit date of the commit at the tip of the reference (ISO 8601)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_date: Option<String>,
+
+    /// Tagger, date and message of an annotated tag
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<TagDetails>,
+}
+
+impl ReferenceInfo {
+    /// Creates a reference that only knows its name, full path and commit
+    pub fn new(name: String, full_ref: String, commit_id: String) -> Self {
+        ReferenceInfo {
+            name,
+            full_ref,
+            commit_id,
+            ..Default::default()
+        }
+    }
+
+    /// Whether the reference is an annotated tag, i.e. points to a tag object
+    pub fn is_annotated_tag(&self) -> bool {
+        self.object
+            .as_ref()
+            .is_some_and(|object| object.object_type == ObjectType::Tag)
+    }
 }
 
 /// Common domain model for issue search results
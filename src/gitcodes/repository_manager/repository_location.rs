@@ -11,7 +11,7 @@ use super::providers::GitRemoteRepository;
 ///
 /// # Valid repository locations
 ///
-/// This enum supports two types of repository locations:
+/// This enum supports three types of repository locations:
 ///
 /// 1. **Remote Repository URLs**:
 ///    - GitHub formats: `github:user/repo`, `git@github.com:user/repo.git`, `https://github.com/user/repo`
@@ -22,9 +22,14 @@ use super::providers::GitRemoteRepository;
 ///    - Relative paths are not supported for security reasons
 ///    - File URLs (e.g., 'file:///path/to/repo' or 'file:/path/to/repo') are also supported and converted to local paths
 ///
-/// When creating from a string using `from_str`, the function will first check if the string is
-/// a file URL and convert it to a local path. Then it will check if the path exists and is absolute,
-/// in which case it will be treated as a local repository. Otherwise, it will attempt to parse it as a remote repository URL.
+/// 3. **Git Bundles**:
+///    - `bundle:` followed by the absolute path of a bundle file (e.g., 'bundle:/path/to/repo.bundle')
+///    - The bundle is unpacked into a cached repository on first use
+///
+/// When creating from a string using `from_str`, a `bundle:` prefix selects a bundle file. Otherwise
+/// the function will first check if the string is a file URL and convert it to a local path. Then it
+/// will check if the path exists and is absolute, in which case it will be treated as a local
+/// repository. Otherwise, it will attempt to parse it as a remote repository URL.
 #[derive(Debug, Clone, serde::Deserialize)]
 pub enum RepositoryLocation {
     RemoteRepository(GitRemoteRepository),
@@ -34,6 +39,11 @@ pub enum RepositoryLocation {
     /// The path must exist and be a valid Git repository directory.
     /// File URLs (e.g., 'file:///path/to/repo') are automatically converted to local paths.
     LocalPath(LocalRepository),
+    /// A git bundle file (must be an absolute path)
+    ///
+    /// Written as `bundle:/path/to/repo.bundle`. The repository manager unpacks the bundle
+    /// into the cache directory, so every tool can use it like a cloned repository.
+    Bundle(PathBuf),
 }
 
 impl FromStr for RepositoryLocation {
@@ -42,6 +52,24 @@ impl FromStr for RepositoryLocation {
     fn from_str(repo_location_path_or_url: &str) -> Result<Self, Self::Err> {
         let sanitized_location = repo_location_path_or_url.trim();
 
+        // Handle bundle: locations, which name a bundle file rather than a repository
+        if let Some(bundle_path) = sanitized_location.strip_prefix("bundle:") {
+            let path = Path::new(bundle_path);
+            if !path.is_absolute() {
+                return Err(format!(
+                    "Invalid repository location: '{}'. Bundle paths must be absolute paths",
+                    sanitized_location
+                ));
+            }
+            if !path.is_file() {
+                return Err(format!(
+                    "Invalid repository location: '{}'. Bundle file not found",
+                    sanitized_location
+                ));
+            }
+            return Ok(RepositoryLocation::Bundle(path.to_path_buf()));
+        }
+
         // Handle file:// URLs by converting them to local paths
         let path_str = if sanitized_location.starts_with("file:") {
             // Strip the file: or file:// prefix to get the actual path
@@ -71,7 +99,7 @@ impl FromStr for RepositoryLocation {
         } else {
             // Try to parse as remote repository URL
             let remote_repository = GitRemoteRepository::parse_url(repo_location_path_or_url)
-                .map_err(|e| format!("Invalid repository location: {}. Valid formats include absolute local paths or remote URLs (https://github.com/user/repo, git@github.com:user/repo.git, github:user/repo)", e))?;
+                .map_err(|e| format!("Invalid repository location: {}. Valid formats include absolute local paths, remote URLs (https://github.com/user/repo, git@github.com:user/repo.git, github:user/repo) or bundle files (bundle:/path/to/repo.bundle)", e))?;
             Ok(RepositoryLocation::RemoteRepository(remote_repository))
         }
     }
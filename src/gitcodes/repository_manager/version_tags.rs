@@ -0,0 +1,219 @@
+//! Semantic version parsing and ordering of tags
+//!
+//! Release tags usually carry a semantic version behind a prefix: `v1.2.3`, `1.2.3`,
+//! `crate-name-v1.2.3` (as produced by release tooling in workspaces) or `pkg@1.2.3`.
+//! This module extracts those versions so that tags can be listed newest first and
+//! the `latest` / `latest-stable` ref aliases can be resolved to a concrete tag.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use semver::Version;
+
+use super::providers::ReferenceInfo;
+
+/// Ref alias resolving to the tag with the highest version, including prereleases
+pub const LATEST_ALIAS: &str = "latest";
+
+/// Ref alias resolving to the tag with the highest version without a prerelease part
+pub const LATEST_STABLE_ALIAS: &str = "latest-stable";
+
+/// Characters after which a version may start inside a tag name
+const PREFIX_SEPARATORS: &[char] = &['-', '_', '/', '@'];
+
+/// A semantic version found in a tag name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagVersion {
+    /// Everything in front of the version, e.g. `v` or `crate-name-v` (may be empty)
+    pub prefix: String,
+    /// The parsed version
+    pub version: Version,
+}
+
+impl TagVersion {
+    /// Whether the version has a prerelease part (e.g. `1.0.0-rc.1`)
+    pub fn is_prerelease(&self) -> bool {
+        !self.version.pre.is_empty()
+    }
+}
+
+/// Which tags a `latest` alias considers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LatestAlias {
+    /// `latest`: the highest version, prereleases included
+    Latest,
+    /// `latest-stable`: the highest version without a prerelease part
+    LatestStable,
+}
+
+impl LatestAlias {
+    /// Recognizes the `latest` and `latest-stable` aliases (case-insensitive)
+    pub fn from_ref_name(ref_name: &str) -> Option<Self> {
+        if ref_name.eq_ignore_ascii_case(LATEST_ALIAS) {
+            Some(LatestAlias::Latest)
+        } else if ref_name.eq_ignore_ascii_case(LATEST_STABLE_ALIAS) {
+            Some(LatestAlias::LatestStable)
+        } else {
+            None
+        }
+    }
+
+    /// Picks the tag this alias refers to among `tag_names`
+    ///
+    /// Only the tags of one release line are compared, so that `latest` of a workspace
+    /// doesn't jump between packages (see `release_line`). Tags without a version are
+    /// ignored. Returns `None` if no tag of the release line qualifies.
+    pub fn select<'a, I>(self, tag_names: I) -> Option<&'a str>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let versioned: Vec<(&str, TagVersion)> = tag_names
+            .into_iter()
+            .filter_map(|name| parse_tag_version(name).map(|version| (name, version)))
+            .collect();
+        let line = release_line(versioned.iter().map(|(_, version)| version))?.to_string();
+
+        versioned
+            .into_iter()
+            .filter(|(_, version)| release_line_of(&version.prefix) == line)
+            .filter(|(_, version)| self == LatestAlias::Latest || !version.is_prerelease())
+            .max_by(|(a_name, a), (b_name, b)| {
+                // Prefer the plain spelling when the same version is tagged twice
+                a.version
+                    .cmp(&b.version)
+                    .then_with(|| b.prefix.cmp(&a.prefix))
+                    .then_with(|| b_name.cmp(a_name))
+            })
+            .map(|(name, _)| name)
+    }
+}
+
+/// The prefix of the release line the `latest` aliases follow
+///
+/// Tags with a plain version (`1.2.3` or `v1.2.3`) are the repository's own releases and
+/// form the release line whenever there is one; the empty string stands for them. A
+/// workspace tagging only `crate-a-v1.0.0`, `crate-b-v0.3.0`, ... uses the prefix most
+/// tags carry instead, the alphabetically first on a tie. Returns `None` without tags.
+fn release_line<'a, I>(versions: I) -> Option<&'a str>
+where
+    I: IntoIterator<Item = &'a TagVersion>,
+{
+    let mut tag_counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for version in versions {
+        *tag_counts
+            .entry(release_line_of(&version.prefix))
+            .or_default() += 1;
+    }
+    if tag_counts.contains_key("") {
+        return Some("");
+    }
+    tag_counts
+        .into_iter()
+        .max_by(|(a_prefix, a_count), (b_prefix, b_count)| {
+            a_count.cmp(b_count).then_with(|| b_prefix.cmp(a_prefix))
+        })
+        .map(|(prefix, _)| prefix)
+}
+
+/// The release line of a tag prefix, with `v` and `V` counting as no prefix
+fn release_line_of(prefix: &str) -> &str {
+    if prefix.eq_ignore_ascii_case("v") {
+        ""
+    } else {
+        prefix
+    }
+}
+
+/// Extracts the semantic version from a tag name
+///
+/// The version may follow a prefix ending in `v`, `-`, `_`, `/` or `@`. Versions with only
+/// a major and minor component (`v1.2`) are read as `1.2.0`.
+///
+/// # Examples
+///
+/// ```
+/// use gitcodes_mcp::gitcodes::repository_manager::version_tags::parse_tag_version;
+///
+/// let tag = parse_tag_version("my-crate-v1.2.3-rc.1").unwrap();
+/// assert_eq!(tag.prefix, "my-crate-v");
+/// assert_eq!(tag.version.to_string(), "1.2.3-rc.1");
+/// assert!(tag.is_prerelease());
+///
+/// assert!(parse_tag_version("nightly").is_none());
+/// ```
+pub fn parse_tag_version(tag_name: &str) -> Option<TagVersion> {
+    let candidate_starts = std::iter::once(0).chain(
+        tag_name
+            .char_indices()
+            .filter(|(_, c)| PREFIX_SEPARATORS.contains(c))
+            .map(|(i, c)| i + c.len_utf8()),
+    );
+
+    for start in candidate_starts {
+        let rest = &tag_name[start..];
+        let (v_len, core) = match rest.strip_prefix(['v', 'V']) {
+            Some(core) => (1, core),
+            None => (0, rest),
+        };
+        if let Some(version) = parse_lenient_version(core) {
+            return Some(TagVersion {
+                prefix: tag_name[..start + v_len].to_string(),
+                version,
+            });
+        }
+    }
+
+    None
+}
+
+/// Parses a semantic version, accepting `major.minor` as `major.minor.0`
+fn parse_lenient_version(text: &str) -> Option<Version> {
+    if !text.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    if let Ok(version) = Version::parse(text) {
+        return Some(version);
+    }
+
+    let numbers_end = text.find(['-', '+']).unwrap_or(text.len());
+    let (numbers, suffix) = text.split_at(numbers_end);
+    let components: Vec<&str> = numbers.split('.').collect();
+    if components.len() == 2 {
+        Version::parse(&format!("{}.0{}", numbers, suffix)).ok()
+    } else {
+        None
+    }
+}
+
+/// Sorts tags newest version first
+///
+/// Tags with a version come first, ordered by descending version (then by prefix, so
+/// tags of different packages with the same version stay together). Tags without a
+/// version follow in alphabetical order.
+pub fn sort_tags_by_version(tags: &mut [ReferenceInfo]) {
+    tags.sort_by_cached_key(|tag| match parse_tag_version(&tag.name) {
+        Some(tag_version) => (
+            false,
+            Reverse(tag_version.version),
+            tag_version.prefix,
+            tag.name.clone(),
+        ),
+        None => (
+            true,
+            Reverse(Version::new(0, 0, 0)),
+            String::new(),
+            tag.name.clone(),
+        ),
+    });
+}
+
+/// Removes tags whose version has a prerelease part
+///
+/// Tags without a version are kept, since nothing marks them as prereleases.
+pub fn retain_stable_tags(tags: &mut Vec<ReferenceInfo>) {
+    tags.retain(|tag| {
+        parse_tag_version(&tag.name)
+            .map(|version| !version.is_prerelease())
+            .unwrap_or(true)
+    });
+}
@@ -1,4 +1,12 @@
-use crate::gitcodes::local_repository::{CodeSearchParams, ViewFileParams};
+use crate::gitcodes::local_repository::{
+    BooleanSearchOptions, Changelog, CodeSearchParams, ContextMode, ContributorsOptions,
+    ContributorsReport, FileOutline, FindFilesOptions, FindFilesResult, HistorySearchOptions,
+    HistorySearchResult, HotspotMetric, HotspotOptions, HotspotReport, PickaxeMode,
+    RankedSearchOptions, RankedSearchResult, RefComparison, ReferenceSearchOptions,
+    ReferenceSearchResult, SearchCursor, SearchFacets, ShowSymbolOptions, SourceLanguage,
+    StatusOptions, StructuralSearchOptions, StructuralSearchResult, SymbolKind,
+    SymbolSearchOptions, SymbolSearchResult, SymbolSourceResult, ViewFileParams, WorkingTreeStatus,
+};
 use crate::gitcodes::repository_manager;
 use crate::gitcodes::CodeSearchResult;
 use repository_manager::RepositoryLocation;
@@ -6,7 +14,7 @@ use std::path::PathBuf;
 use std::str::FromStr;
 
 /// Parameters for performing a grep operation in a repository
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct GrepParams {
     pub repository_location_str: String,
     pub pattern: String,
@@ -15,10 +23,18 @@ pub struct GrepParams {
     pub file_extensions: Option<Vec<String>>,
     pub include_globs: Option<Vec<String>>,
     pub exclude_dirs: Option<Vec<String>>,
+    pub changed_files_only: bool,
+    pub use_index: bool,
+    pub fixed_string: bool,
+    pub word_regexp: bool,
+    pub multiline: bool,
     pub before_context: Option<usize>,
     pub after_context: Option<usize>,
+    pub context_mode: Option<ContextMode>,
+    pub max_context_lines: Option<usize>,
     pub skip: Option<usize>,
     pub take: Option<usize>,
+    pub cursor: Option<String>,
     pub match_content_omit_num: Option<usize>,
 }
 
@@ -61,16 +77,33 @@ pub struct TreeServiceParams {
 ///
 /// * `repository_manager` - The repository manager for cloning/preparing repositories
 /// * `repository_location_str` - The repository location string to parse (e.g., "github:user/repo" or "/path/to/local/repo")
-/// * `pattern` - The search pattern (already processed for regex escaping if needed)
-/// * `ref_name` - Optional reference name (branch/tag) to checkout
+/// * `pattern` - The search pattern, a regex unless `fixed_string` is set
+/// * `ref_name` - Optional reference name (branch/tag/commit); when given, that commit's tree is searched from the object database
 /// * `case_sensitive` - Whether to perform a case-sensitive search
 /// * `file_extensions` - Optional list of file extensions to filter by (e.g., ["rs", "md"]) (deprecated, use include_globs instead)
 /// * `include_globs` - Optional list of glob patterns to include files (e.g., ["**/*.rs", "**/*.md"]) (not exposed through this API yet)
 /// * `exclude_dirs` - Optional list of directories to exclude (e.g., ["target", "node_modules"])
+/// * `changed_files_only` - Whether to search only the files with uncommitted changes of a local working tree
+/// * `use_index` - Whether to narrow the search with the cached trigram index of the searched tree
+/// * `fixed_string` - Whether to match the pattern literally instead of as a regex
+/// * `word_regexp` - Whether the pattern only matches whole words
+/// * `multiline` - Whether matches may span multiple lines
 /// * `before_context` - Optional number of lines to include before each match
 /// * `after_context` - Optional number of lines to include after each match
+/// * `context_mode` - Optional way the context is chosen; `EnclosingItem` adds the function, method, impl or class containing each match
+/// * `max_context_lines` - Optional maximum number of lines per enclosing item (default 50)
 /// * `skip` - Optional number of results to skip (for pagination)
 /// * `take` - Optional maximum number of results to return (for pagination, defaults to 50 if not specified)
+/// * `cursor` - Optional `next_cursor` of the previous page of the same search, instead of `skip`
+///
+/// # Cursors
+///
+/// A search of a ref, or of a cloned repository, is pinned to the commit it resolves to,
+/// and when there are more results than the page holds, the result has a `next_cursor`.
+/// Passing it back with the same parameters searches that commit again, even if the clone
+/// was updated in between, and continues after the last line of the page. Searches of the
+/// working tree of a local repository may include uncommitted changes and get no cursor;
+/// give `ref_name` (e.g. `HEAD`) to page through them with cursors.
 ///
 /// # Returns
 ///
@@ -81,6 +114,8 @@ pub struct TreeServiceParams {
 /// This function returns an error if:
 /// - The repository location string cannot be parsed
 /// - The repository cannot be prepared (cloned or validated)
+/// - The cursor is invalid, combined with `skip`, or belongs to a different search
+/// - The commit of the cursor is no longer in the repository
 /// - The code search operation fails
 pub async fn perform_grep_in_repository(
     repository_manager: &repository_manager::RepositoryManager,
@@ -96,36 +131,415 @@ pub async fn perform_grep_in_repository(
     let repository_location = RepositoryLocation::from_str(&params.repository_location_str)
         .map_err(|e| format!("Failed to parse repository location: {}", e))?;
 
+    // A cached clone of a remote repository never has uncommitted changes
+    if params.changed_files_only && !matches!(repository_location, RepositoryLocation::LocalPath(_))
+    {
+        return Err(format!(
+            "changed_files_only is only available for local repositories, not '{}'",
+            params.repository_location_str
+        ));
+    }
+
+    let cursor = params
+        .cursor
+        .as_deref()
+        .map(SearchCursor::decode)
+        .transpose()?;
+    if cursor.is_some() && params.skip.is_some() {
+        return Err(
+            "cursor and skip cannot be combined, the cursor holds the position".to_string(),
+        );
+    }
+    let query_hash = grep_query_hash(&params);
+
     // Prepare the repository (clone if necessary)
     let local_repo = repository_manager
         .prepare_repository(&repository_location, params.ref_name.clone())
         .await?;
 
-    // Use the pattern as provided - the caller is responsible for any regex escaping
+    // The commit the pages of this search are pinned to, if it has one
+    let snapshot = match &cursor {
+        Some(cursor) => {
+            if cursor.query_hash != query_hash {
+                return Err("The cursor belongs to a different search; pass the same repository, pattern, ref and filters as the search that returned it".to_string());
+            }
+            local_repo
+                .resolve_commit_id(&cursor.commit_id)
+                .map_err(|_| {
+                    format!(
+                        "The commit {} this cursor was created for is no longer available, e.g. because the cached clone was removed; start the search again without a cursor",
+                        cursor.commit_id
+                    )
+                })?;
+            Some(cursor.commit_id.clone())
+        }
+        None if params.changed_files_only => None,
+        None => match (&params.ref_name, &repository_location) {
+            (Some(ref_name), _) => Some(local_repo.resolve_commit_id(ref_name)?),
+            // The working tree of a local repository may have uncommitted changes
+            (None, RepositoryLocation::LocalPath(_)) => None,
+            (None, _) => Some(local_repo.resolve_commit_id("HEAD")?),
+        },
+    };
+    let skip = cursor.map(|cursor| cursor.position).or(params.skip);
+    let take = params.take.unwrap_or(50); // Default to 50 if not specified
+
+    // Use the pattern as provided - escaping is done by the search when fixed_string is set
 
     // Create search parameters directly as CodeSearchParams
     let search_params = CodeSearchParams {
         repository_location: repository_location.clone(),
-        ref_name: params.ref_name,
-        pattern: params.pattern.clone(), // Already processed for regex if needed
+        ref_name: snapshot.clone().or(params.ref_name),
+        pattern: params.pattern.clone(),
         case_sensitive: params.case_sensitive,
+        fixed_string: params.fixed_string,
+        word_regexp: params.word_regexp,
+        multiline: params.multiline,
         file_extensions: params.file_extensions.clone(),
         include_globs: params.include_globs.clone(),
         exclude_dirs: params.exclude_dirs.clone(),
+        changed_files_only: params.changed_files_only,
+        use_index: params.use_index,
         before_context: params.before_context,
         after_context: params.after_context,
-        skip: params.skip,              // Allow pagination through service API
-        take: params.take.or(Some(50)), // Default to 50 if not specified
+        context_mode: params.context_mode,
+        max_context_lines: params.max_context_lines,
+        skip,             // Allow pagination through service API
+        take: Some(take), // From the cursor or the parameters
         match_content_omit_num: params.match_content_omit_num.or(Some(150)), // Default to 150 if not specified
     };
 
     // Execute the grep operation
-    let search_result = local_repo.search_code(search_params).await?;
+    let mut search_result = local_repo.search_code(search_params).await?;
+
+    if let Some(commit_id) = snapshot {
+        let position = skip.unwrap_or(0).saturating_add(take);
+        if position < search_result.total_match_line_number {
+            let next_cursor = SearchCursor {
+                commit_id,
+                query_hash,
+                position,
+            };
+            search_result.next_cursor = Some(next_cursor.encode());
+        }
+    }
 
     // Return both the search results and the local repository instance
     Ok((search_result, local_repo))
 }
 
+/// Hashes the parameters that make up a grep search, for the cursors of its pages
+///
+/// Everything but the pagination and `use_index`, which doesn't change the results, is
+/// hashed, so a cursor can't continue a different search. The hash is the start of the
+/// SHA-1 of the parameters serialized as JSON with sorted keys. Unlike the hashers of the
+/// standard library it doesn't change between Rust releases, so cursors issued before the
+/// server was rebuilt are still accepted.
+fn grep_query_hash(params: &GrepParams) -> u64 {
+    let query = serde_json::json!({
+        "repository_location": params.repository_location_str,
+        "pattern": params.pattern,
+        "ref_name": params.ref_name,
+        "case_sensitive": params.case_sensitive,
+        "file_extensions": params.file_extensions,
+        "include_globs": params.include_globs,
+        "exclude_dirs": params.exclude_dirs,
+        "changed_files_only": params.changed_files_only,
+        "fixed_string": params.fixed_string,
+        "word_regexp": params.word_regexp,
+        "multiline": params.multiline,
+        "before_context": params.before_context,
+        "after_context": params.after_context,
+        "context_mode": params.context_mode,
+        "max_context_lines": params.max_context_lines,
+        "match_content_omit_num": params.match_content_omit_num,
+    });
+
+    let mut hasher = gix::hash::hasher(gix::hash::Kind::Sha1);
+    hasher.update(query.to_string().as_bytes());
+    // A detected collision attack still reports the digest, which is all a cursor needs
+    let digest = hasher
+        .try_finalize()
+        .unwrap_or_else(|gix::hash::hasher::Error::CollisionAttack { digest }| digest);
+    let mut prefix = [0; 8];
+    prefix.copy_from_slice(&digest.as_bytes()[..8]);
+    u64::from_be_bytes(prefix)
+}
+
+/// Parameters for performing the same grep operation in several repositories
+#[derive(Debug, Clone, Default)]
+pub struct MultiRepositoryGrepParams {
+    pub repository_location_strs: Vec<String>,
+    /// Name of a saved collection whose locations are searched after the given ones
+    pub collection: Option<String>,
+    pub max_concurrency: Option<usize>,
+    pub pattern: String,
+    pub ref_name: Option<String>,
+    pub case_sensitive: bool,
+    pub file_extensions: Option<Vec<String>>,
+    pub include_globs: Option<Vec<String>>,
+    pub exclude_dirs: Option<Vec<String>>,
+    pub changed_files_only: bool,
+    pub use_index: bool,
+    pub fixed_string: bool,
+    pub word_regexp: bool,
+    pub multiline: bool,
+    pub before_context: Option<usize>,
+    pub after_context: Option<usize>,
+    pub context_mode: Option<ContextMode>,
+    pub max_context_lines: Option<usize>,
+    pub skip: Option<usize>,
+    pub take: Option<usize>,
+    pub match_content_omit_num: Option<usize>,
+}
+
+/// The outcome of a grep operation in one of several repositories
+#[derive(Debug)]
+pub struct RepositoryGrepOutcome {
+    /// The repository location string as given
+    pub repository_location_str: String,
+
+    /// The search result and local repository, or why this repository couldn't be searched
+    pub result: Result<
+        (
+            CodeSearchResult,
+            crate::gitcodes::local_repository::LocalRepository,
+        ),
+        String,
+    >,
+}
+
+/// Number of repositories prepared and searched at the same time by default
+const DEFAULT_MAX_CONCURRENCY: usize = 4;
+
+/// Performs the same grep operation in several repositories concurrently
+///
+/// The repositories are the given locations followed by those of the saved collection,
+/// if one is named. Locations that refer to the same repository, like `github:user/repo`
+/// and `https://github.com/user/repo`, are searched once under the first spelling.
+///
+/// Every repository is searched with [`perform_grep_in_repository`], so pagination and
+/// content omission apply per repository and default to the same values. At most
+/// `max_concurrency` repositories (default 4) are cloned and searched at the same time.
+/// A repository that can't be parsed, prepared or searched doesn't fail the others;
+/// its error is reported in its outcome instead.
+///
+/// # Parameters
+///
+/// * `repository_manager` - The repository manager for cloning/preparing repositories
+/// * `params` - The repository locations and the search to run in each of them
+///
+/// # Returns
+///
+/// * `Result<Vec<RepositoryGrepOutcome>, String>` - One outcome per distinct repository, in the order given
+///
+/// # Errors
+///
+/// This function returns an error if the collection isn't saved, no repository location
+/// is given or `max_concurrency` is 0.
+pub async fn perform_grep_in_repositories(
+    repository_manager: &repository_manager::RepositoryManager,
+    params: MultiRepositoryGrepParams,
+) -> Result<Vec<RepositoryGrepOutcome>, String> {
+    let mut location_strs = params.repository_location_strs.clone();
+    if let Some(collection) = &params.collection {
+        location_strs.extend(repository_manager.repository_collection(collection)?);
+    }
+
+    // The same repository twice would be cloned into the same directory concurrently
+    let mut locations: Vec<String> = Vec::new();
+    let mut repository_keys = std::collections::HashSet::new();
+    for location in location_strs {
+        if repository_keys.insert(repository_key(repository_manager, &location)) {
+            locations.push(location);
+        }
+    }
+    if locations.is_empty() {
+        return Err("At least one repository location is required".to_string());
+    }
+    let max_concurrency = params.max_concurrency.unwrap_or(DEFAULT_MAX_CONCURRENCY);
+    if max_concurrency == 0 {
+        return Err("max_concurrency must be at least 1".to_string());
+    }
+
+    let semaphore = std::sync::Arc::new(tokio::sync::Semaphore::new(max_concurrency));
+    let mut tasks = tokio::task::JoinSet::new();
+    let mut task_indices = std::collections::HashMap::new();
+    for (index, location) in locations.iter().enumerate() {
+        let grep_params = GrepParams {
+            repository_location_str: location.clone(),
+            pattern: params.pattern.clone(),
+            ref_name: params.ref_name.clone(),
+            case_sensitive: params.case_sensitive,
+            file_extensions: params.file_extensions.clone(),
+            include_globs: params.include_globs.clone(),
+            exclude_dirs: params.exclude_dirs.clone(),
+            changed_files_only: params.changed_files_only,
+            use_index: params.use_index,
+            fixed_string: params.fixed_string,
+            word_regexp: params.word_regexp,
+            multiline: params.multiline,
+            before_context: params.before_context,
+            after_context: params.after_context,
+            context_mode: params.context_mode,
+            max_context_lines: params.max_context_lines,
+            skip: params.skip,
+            take: params.take,
+            cursor: None,
+            match_content_omit_num: params.match_content_omit_num,
+        };
+        let manager = repository_manager.clone();
+        let semaphore = semaphore.clone();
+        let handle = tasks.spawn(async move {
+            let _permit = semaphore
+                .acquire_owned()
+                .await
+                .map_err(|e| format!("Failed to wait for a search slot: {}", e))?;
+            perform_grep_in_repository(&manager, grep_params).await
+        });
+        // Tasks finish in any order; their ids map the results back to the locations
+        task_indices.insert(handle.id(), index);
+    }
+
+    let mut results: Vec<Option<Result<_, String>>> = locations.iter().map(|_| None).collect();
+    while let Some(joined) = tasks.join_next_with_id().await {
+        match joined {
+            Ok((id, result)) => results[task_indices[&id]] = Some(result),
+            Err(e) => {
+                results[task_indices[&e.id()]] = Some(Err(format!("Search task failed: {}", e)))
+            }
+        }
+    }
+
+    Ok(locations
+        .into_iter()
+        .zip(results)
+        .map(|(repository_location_str, result)| RepositoryGrepOutcome {
+            repository_location_str,
+            result: result.unwrap_or_else(|| Err("Search task did not finish".to_string())),
+        })
+        .collect())
+}
+
+/// Identifies the repository a location string refers to
+///
+/// Remote repositories are identified by the directory they're cloned into, local ones
+/// and bundles by their canonical path. Strings that don't parse are kept as they are,
+/// so each reports its own error.
+fn repository_key(
+    repository_manager: &repository_manager::RepositoryManager,
+    location_str: &str,
+) -> PathBuf {
+    match RepositoryLocation::from_str(location_str) {
+        Ok(RepositoryLocation::RemoteRepository(
+            repository_manager::providers::GitRemoteRepository::Github(github_info),
+        )) => {
+            let repo_info = repository_manager::providers::GitRemoteRepositoryInfo {
+                ref_name: None,
+                ..github_info.repo_info
+            };
+            crate::gitcodes::local_repository::LocalRepository::new_local_repository_to_clone(
+                repo_info,
+                Some(&repository_manager.process_id),
+            )
+            .get_repository_dir()
+            .clone()
+        }
+        Ok(RepositoryLocation::LocalPath(local_repo)) => {
+            let path = local_repo.get_repository_dir();
+            path.canonicalize().unwrap_or_else(|_| path.clone())
+        }
+        Ok(RepositoryLocation::Bundle(path)) => path.canonicalize().unwrap_or(path),
+        Err(_) => PathBuf::from(location_str),
+    }
+}
+
+/// Parameters for a boolean multi-pattern search in a repository
+#[derive(Debug, Clone, Default)]
+pub struct BooleanGrepParams {
+    pub repository_location_str: String,
+    pub ref_name: Option<String>,
+    pub all_of: Vec<String>,
+    pub any_of: Vec<String>,
+    pub none_of: Vec<String>,
+    pub case_sensitive: bool,
+    pub fixed_string: bool,
+    pub word_regexp: bool,
+    pub multiline: bool,
+    pub include_globs: Option<Vec<String>>,
+    pub exclude_dirs: Option<Vec<String>>,
+    pub before_context: Option<usize>,
+    pub after_context: Option<usize>,
+    pub skip: Option<usize>,
+    pub take: Option<usize>,
+    pub match_content_omit_num: Option<usize>,
+}
+
+/// Searches a repository for files matching a boolean combination of patterns
+///
+/// This function handles the entire process:
+/// 1. Parses a repository location string into a RepositoryLocation
+/// 2. Prepares (clones if needed) the repository using the provided manager
+/// 3. Selects the files matching all `all_of` patterns, at least one `any_of` pattern
+///    and none of the `none_of` patterns, returning the matches of the positive patterns
+///
+/// Pagination and content omission default to the same values as
+/// [`perform_grep_in_repository`].
+///
+/// # Parameters
+///
+/// * `repository_manager` - The repository manager for cloning/preparing repositories
+/// * `params` - The repository location, patterns and search options
+///
+/// # Returns
+///
+/// * `Result<(CodeSearchResult, LocalRepository), String>` - The matches in the selected files and the local repository
+///
+/// # Errors
+///
+/// This function returns an error if the repository location cannot be parsed or
+/// prepared, or if the search fails (see [`LocalRepository::search_code_boolean`]).
+///
+/// [`LocalRepository::search_code_boolean`]: crate::gitcodes::LocalRepository::search_code_boolean
+pub async fn perform_boolean_grep_in_repository(
+    repository_manager: &repository_manager::RepositoryManager,
+    params: BooleanGrepParams,
+) -> Result<
+    (
+        CodeSearchResult,
+        crate::gitcodes::local_repository::LocalRepository,
+    ),
+    String,
+> {
+    let repository_location = RepositoryLocation::from_str(&params.repository_location_str)
+        .map_err(|e| format!("Failed to parse repository location: {}", e))?;
+
+    let local_repo = repository_manager
+        .prepare_repository(&repository_location, params.ref_name.clone())
+        .await?;
+
+    let options = BooleanSearchOptions {
+        ref_name: params.ref_name,
+        all_of: params.all_of,
+        any_of: params.any_of,
+        none_of: params.none_of,
+        case_sensitive: params.case_sensitive,
+        fixed_string: params.fixed_string,
+        word_regexp: params.word_regexp,
+        multiline: params.multiline,
+        include_globs: params.include_globs,
+        exclude_dirs: params.exclude_dirs,
+        before_context: params.before_context,
+        after_context: params.after_context,
+        skip: params.skip,
+        take: params.take.or(Some(50)),
+        match_content_omit_num: params.match_content_omit_num.or(Some(150)),
+    };
+
+    let search_result = local_repo.search_code_boolean(options).await?;
+    Ok((search_result, local_repo))
+}
+
 /// Shows the contents of a file within a repository, first preparing the repository if needed
 ///
 /// This pure function handles the entire file viewing process:
@@ -142,7 +556,7 @@ pub async fn perform_grep_in_repository(
 /// * `repository_manager` - The repository manager for cloning/preparing repositories
 /// * `repository_location_str` - The repository location string to parse (e.g., "github:user/repo" or "/path/to/local/repo")
 /// * `file_path` - The path of the file within the repository to view
-/// * `ref_name` - Optional reference name (branch/tag) to checkout
+/// * `ref_name` - Optional reference name (branch/tag/commit); when given, the file is read from the object database at that ref
 /// * `max_size` - Optional maximum file size to read (in bytes)
 /// * `line_from` - Optional start line number (1-indexed)
 /// * `line_to` - Optional end line number (1-indexed, inclusive)
@@ -176,7 +590,7 @@ pub async fn show_file_contents(
 
     // Prepare the repository (clone if necessary)
     let local_repo = repository_manager
-        .prepare_repository(&repository_location, params.ref_name)
+        .prepare_repository(&repository_location, params.ref_name.clone())
         .await?;
 
     // Set up view parameters
@@ -187,8 +601,15 @@ pub async fn show_file_contents(
         line_to: params.line_to,
     };
 
-    // View the file contents
-    let file_contents = local_repo.view_file_contents(view_params).await?;
+    // View the file contents, reading the blob from the object database when a ref is given
+    let file_contents = match &params.ref_name {
+        Some(ref_name) => {
+            local_repo
+                .view_file_contents_at_ref(ref_name, view_params)
+                .await?
+        }
+        None => local_repo.view_file_contents(view_params).await?,
+    };
 
     // Determine the effective value for without_line_numbers (default to false if not specified)
     let effective_without_line_numbers = params.without_line_numbers.unwrap_or(false);
@@ -212,7 +633,7 @@ pub async fn show_file_contents(
 ///
 /// * `repository_manager` - The repository manager for cloning/preparing repositories
 /// * `repository_location_str` - The repository location string to parse (e.g., "github:user/repo" or "/path/to/local/repo")
-/// * `ref_name` - Optional reference name (branch/tag) to checkout
+/// * `ref_name` - Optional reference name (branch/tag/commit); when given, the tree is read from the object database at that ref
 /// * `case_sensitive` - Optional whether file path matching should be case sensitive (default: false)
 /// * `respect_gitignore` - Optional whether to respect .gitignore files (default: true)
 /// * `depth` - Optional maximum depth of directory traversal (default: unlimited)
@@ -244,7 +665,7 @@ pub async fn get_repository_tree(
 
     // Prepare the repository (clone if necessary)
     let local_repo = repository_manager
-        .prepare_repository(&repository_location, params.ref_name)
+        .prepare_repository(&repository_location, params.ref_name.clone())
         .await?;
 
     // Create tree parameters
@@ -256,8 +677,1287 @@ pub async fn get_repository_tree(
         search_relative_path: params.search_relative_path,
     };
 
-    // Get the directory tree
-    let tree = local_repo.get_tree_with_params(Some(tree_params)).await?;
+    // Get the directory tree, reading tree objects from the object database when a ref is given
+    let tree = match &params.ref_name {
+        Some(ref_name) => {
+            local_repo
+                .get_tree_at_ref(ref_name, Some(tree_params))
+                .await?
+        }
+        None => local_repo.get_tree_with_params(Some(tree_params)).await?,
+    };
 
     Ok((tree, local_repo))
 }
+
+/// Parameters for searching the commit history of a repository
+#[derive(Debug, Clone)]
+pub struct HistorySearchServiceParams {
+    pub repository_location_str: String,
+    pub pattern: String,
+    pub mode: Option<PickaxeMode>,
+    pub is_regex: Option<bool>,
+    pub case_sensitive: Option<bool>,
+    pub paths: Option<Vec<String>>,
+    pub from_ref: Option<String>,
+    pub to_ref: Option<String>,
+    pub max_commits: Option<usize>,
+    pub context_lines: Option<u32>,
+}
+
+/// Searches the commit history of a repository for a string or regex (pickaxe search)
+///
+/// This pure function handles the entire history search process:
+/// 1. Parses a repository location string into a RepositoryLocation
+/// 2. Prepares (clones if needed) the repository using the provided manager
+/// 3. Fetches the full history if the clone is shallow, and the refs the clone is missing
+/// 4. Walks the requested commit range and collects the matching commits and hunks
+///
+/// # Parameters
+///
+/// * `repository_manager` - The repository manager for cloning/preparing repositories
+/// * `repository_location_str` - The repository location string to parse (e.g., "github:user/repo" or "/path/to/local/repo")
+/// * `pattern` - The string or regex to look for
+/// * `mode` - Optional pickaxe mode (default `occurrences`, like `git log -S`; `diff_match` is like `git log -G`)
+/// * `is_regex` - Optional whether the pattern is a regex in `occurrences` mode (default false)
+/// * `case_sensitive` - Optional whether to match case-sensitively (default true, like git)
+/// * `paths` - Optional directory prefixes or glob patterns limiting the searched files
+/// * `from_ref` - Optional exclusive start of the commit range
+/// * `to_ref` - Optional inclusive end of the commit range (default HEAD)
+/// * `max_commits` - Optional maximum number of matching commits (default 20)
+/// * `context_lines` - Optional number of context lines in diff hunks (default 3)
+///
+/// # Returns
+///
+/// * `Result<(HistorySearchResult, repository_manager::LocalRepository), String>` - A tuple containing the matching commits and the local repository instance
+///
+/// # Errors
+///
+/// This function returns an error if:
+/// - The repository location string cannot be parsed
+/// - The repository cannot be prepared (cloned or validated)
+/// - The missing history of a shallow clone, or a ref missing from it, cannot be fetched
+/// - The pattern is not a valid regex or a ref cannot be resolved
+pub async fn search_history(
+    repository_manager: &repository_manager::RepositoryManager,
+    params: HistorySearchServiceParams,
+) -> Result<
+    (
+        HistorySearchResult,
+        crate::gitcodes::local_repository::LocalRepository,
+    ),
+    String,
+> {
+    // Parse the repository location string
+    let repository_location = RepositoryLocation::from_str(&params.repository_location_str)
+        .map_err(|e| format!("Failed to parse repository location: {}", e))?;
+
+    let ref_names = [params.from_ref.as_deref(), params.to_ref.as_deref()];
+    let local_repo = prepare_repository_history(
+        repository_manager,
+        &repository_location,
+        ref_names.into_iter().flatten(),
+    )
+    .await?;
+
+    let options = HistorySearchOptions {
+        pattern: params.pattern,
+        mode: params.mode.unwrap_or_default(),
+        is_regex: params.is_regex.unwrap_or(false),
+        case_sensitive: params.case_sensitive.unwrap_or(true),
+        paths: params.paths,
+        from_ref: params.from_ref,
+        to_ref: params.to_ref,
+        max_commits: params.max_commits,
+        context_lines: params.context_lines,
+    };
+
+    let result = local_repo.search_history(options).await?;
+
+    Ok((result, local_repo))
+}
+
+/// Prepares a repository for walking its history between the given refs
+///
+/// Remote repositories are cloned shallowly and the clone is reused, so the missing
+/// history is fetched first, and then every ref the clone doesn't have yet, such as a tag
+/// or branch published since it was cloned, is fetched from the remote.
+///
+/// # Parameters
+///
+/// * `repository_manager` - The repository manager for cloning/preparing repositories
+/// * `repository_location` - The repository to prepare
+/// * `ref_names` - The refs the caller asked for; the default `HEAD` needs no fetching
+///
+/// # Returns
+///
+/// * `Result<LocalRepository, String>` - The prepared repository or an error message
+async fn prepare_repository_history<'a>(
+    repository_manager: &repository_manager::RepositoryManager,
+    repository_location: &RepositoryLocation,
+    ref_names: impl IntoIterator<Item = &'a str>,
+) -> Result<crate::gitcodes::local_repository::LocalRepository, String> {
+    let local_repo = repository_manager
+        .prepare_repository(repository_location, None)
+        .await?;
+    local_repo.ensure_full_history().await?;
+    for ref_name in ref_names {
+        local_repo.ensure_ref_available(ref_name).await?;
+    }
+    Ok(local_repo)
+}
+
+/// Parameters for generating a changelog between two refs
+#[derive(Debug, Clone)]
+pub struct ChangelogServiceParams {
+    pub repository_location_str: String,
+    pub from_ref: String,
+    pub to_ref: String,
+    pub include_merges: Option<bool>,
+    pub enrich_references: Option<bool>,
+}
+
+/// Maximum number of issue numbers looked up per GitHub search query
+///
+/// GitHub search queries may contain at most five `OR` operators.
+const REFERENCES_PER_SEARCH_QUERY: usize = 6;
+
+/// Generates a changelog of the commits between two refs
+///
+/// This pure function handles the entire changelog process:
+/// 1. Parses a repository location string into a RepositoryLocation
+/// 2. Prepares (clones if needed) the repository using the provided manager
+/// 3. Fetches the full history if the clone is shallow, and the refs the clone is missing
+/// 4. Groups the commits in `from_ref..to_ref` by conventional commit type
+/// 5. Optionally looks up the titles of referenced issues and pull requests on GitHub
+///
+/// Looking up references is best effort: when the repository is not hosted on GitHub or
+/// the search fails, the changelog is still returned and `enrichment_error` explains why
+/// the titles are missing.
+///
+/// # Parameters
+///
+/// * `repository_manager` - The repository manager for cloning/preparing repositories and searching issues
+/// * `repository_location_str` - The repository location string to parse (e.g., "github:user/repo" or "/path/to/local/repo")
+/// * `from_ref` - Exclusive start of the range, typically the previous release tag
+/// * `to_ref` - Inclusive end of the range, typically the new release tag
+/// * `include_merges` - Optional whether merge commits are listed (default false)
+/// * `enrich_references` - Optional whether to look up issue and pull request titles on GitHub (default false)
+///
+/// # Returns
+///
+/// * `Result<(Changelog, repository_manager::LocalRepository), String>` - A tuple containing the changelog and the local repository instance
+///
+/// # Errors
+///
+/// This function returns an error if:
+/// - The repository location string cannot be parsed
+/// - The repository cannot be prepared (cloned or validated)
+/// - The missing history of a shallow clone, or a ref missing from it, cannot be fetched
+/// - Either ref cannot be resolved
+pub async fn changelog_between(
+    repository_manager: &repository_manager::RepositoryManager,
+    params: ChangelogServiceParams,
+) -> Result<
+    (
+        Changelog,
+        crate::gitcodes::local_repository::LocalRepository,
+    ),
+    String,
+> {
+    // Parse the repository location string
+    let repository_location = RepositoryLocation::from_str(&params.repository_location_str)
+        .map_err(|e| format!("Failed to parse repository location: {}", e))?;
+
+    let local_repo = prepare_repository_history(
+        repository_manager,
+        &repository_location,
+        [params.from_ref.as_str(), params.to_ref.as_str()],
+    )
+    .await?;
+
+    let mut changelog = local_repo
+        .changelog_between(
+            &params.from_ref,
+            &params.to_ref,
+            params.include_merges.unwrap_or(false),
+        )
+        .await?;
+
+    if params.enrich_references.unwrap_or(false) && !changelog.references.is_empty() {
+        match github_repository_name(&repository_location, &local_repo) {
+            Some(repository) => {
+                if let Err(e) =
+                    enrich_changelog_references(repository_manager, &repository, &mut changelog)
+                        .await
+                {
+                    changelog.enrichment_error = Some(e);
+                }
+            }
+            None => {
+                changelog.enrichment_error = Some(
+                    "Repository is not hosted on GitHub, so references cannot be looked up"
+                        .to_string(),
+                );
+            }
+        }
+    }
+
+    Ok((changelog, local_repo))
+}
+
+/// Returns the "owner/repo" name of the GitHub repository behind a location
+///
+/// Local repositories are mapped through the URL of their `origin` remote.
+fn github_repository_name(
+    repository_location: &RepositoryLocation,
+    local_repo: &crate::gitcodes::local_repository::LocalRepository,
+) -> Option<String> {
+    let remote_info = match repository_location {
+        RepositoryLocation::RemoteRepository(
+            repository_manager::providers::GitRemoteRepository::Github(remote_info),
+        ) => remote_info.clone(),
+        RepositoryLocation::LocalPath(_) => {
+            repository_manager::providers::github::parse_github_url(&local_repo.origin_url()?)
+                .ok()?
+        }
+        RepositoryLocation::Bundle(_) => return None,
+    };
+
+    Some(format!(
+        "{}/{}",
+        remote_info.repo_info.user, remote_info.repo_info.repo
+    ))
+}
+
+/// Fills in the titles of the changelog's references using the GitHub issue search
+async fn enrich_changelog_references(
+    repository_manager: &repository_manager::RepositoryManager,
+    repository: &str,
+    changelog: &mut Changelog,
+) -> Result<(), String> {
+    let numbers: Vec<u64> = changelog
+        .references
+        .iter()
+        .map(|reference| reference.number)
+        .collect();
+
+    for chunk in numbers.chunks(REFERENCES_PER_SEARCH_QUERY) {
+        let query = chunk
+            .iter()
+            .map(|number| number.to_string())
+            .collect::<Vec<_>>()
+            .join(" OR ");
+        let search_params = repository_manager::IssueSearchParams {
+            query,
+            sort_by: None,
+            order: None,
+            per_page: Some(100),
+            page: None,
+            repository: Some(repository.to_string()),
+            labels: None,
+            state: None,
+            creator: None,
+            mentioned: None,
+            assignee: None,
+            milestone: None,
+            issue_type: None,
+        };
+
+        let results = repository_manager
+            .search_issues(
+                repository_manager::providers::models::GitProvider::Github,
+                search_params,
+            )
+            .await
+            .map_err(|e| format!("Failed to look up references on GitHub: {}", e))?;
+
+        // The search also matches the numbers in titles and bodies, so match items by number
+        for item in results.items {
+            if let Some(reference) = changelog
+                .references
+                .iter_mut()
+                .find(|reference| reference.number == item.number)
+            {
+                reference.kind = Some(
+                    if item.html_url.contains("/pull/") {
+                        "pull_request"
+                    } else {
+                        "issue"
+                    }
+                    .to_string(),
+                );
+                reference.title = Some(item.title);
+                reference.state = Some(item.state);
+                reference.html_url = Some(item.html_url);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Parameters for aggregating contributor statistics
+#[derive(Debug, Clone)]
+pub struct ContributorsServiceParams {
+    pub repository_location_str: String,
+    pub from_ref: Option<String>,
+    pub to_ref: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub use_mailmap: Option<bool>,
+    pub include_line_stats: Option<bool>,
+    pub include_merges: Option<bool>,
+    pub max_contributors: Option<usize>,
+}
+
+/// Aggregates the commits of a repository per author
+///
+/// This pure function handles the entire contributor statistics process:
+/// 1. Parses a repository location string into a RepositoryLocation
+/// 2. Prepares (clones if needed) the repository using the provided manager
+/// 3. Fetches the full history if the clone is shallow, and the refs the clone is missing
+/// 4. Counts commits, dates and changed lines per author in the requested range
+///
+/// # Parameters
+///
+/// * `repository_manager` - The repository manager for cloning/preparing repositories
+/// * `repository_location_str` - The repository location string to parse (e.g., "github:user/repo" or "/path/to/local/repo")
+/// * `from_ref` - Optional exclusive start of the commit range
+/// * `to_ref` - Optional inclusive end of the commit range (default HEAD)
+/// * `since` - Optional start of the time window (e.g. "2024-01-01" or "6 months ago")
+/// * `until` - Optional end of the time window
+/// * `use_mailmap` - Optional whether to apply the repository's `.mailmap` (default true, like `git shortlog`)
+/// * `include_line_stats` - Optional whether to count added and removed lines (default true)
+/// * `include_merges` - Optional whether merge commits are counted (default false)
+/// * `max_contributors` - Optional maximum number of contributors returned (default 50)
+///
+/// # Returns
+///
+/// * `Result<(ContributorsReport, repository_manager::LocalRepository), String>` - A tuple containing the statistics and the local repository instance
+///
+/// # Errors
+///
+/// This function returns an error if:
+/// - The repository location string cannot be parsed
+/// - The repository cannot be prepared (cloned or validated)
+/// - The missing history of a shallow clone, or a ref missing from it, cannot be fetched
+/// - A ref or date cannot be parsed
+pub async fn contributors(
+    repository_manager: &repository_manager::RepositoryManager,
+    params: ContributorsServiceParams,
+) -> Result<
+    (
+        ContributorsReport,
+        crate::gitcodes::local_repository::LocalRepository,
+    ),
+    String,
+> {
+    // Parse the repository location string
+    let repository_location = RepositoryLocation::from_str(&params.repository_location_str)
+        .map_err(|e| format!("Failed to parse repository location: {}", e))?;
+
+    let ref_names = [params.from_ref.as_deref(), params.to_ref.as_deref()];
+    let local_repo = prepare_repository_history(
+        repository_manager,
+        &repository_location,
+        ref_names.into_iter().flatten(),
+    )
+    .await?;
+
+    let options = ContributorsOptions {
+        from_ref: params.from_ref,
+        to_ref: params.to_ref,
+        since: params.since,
+        until: params.until,
+        use_mailmap: params.use_mailmap.unwrap_or(true),
+        include_line_stats: params.include_line_stats.unwrap_or(true),
+        include_merges: params.include_merges.unwrap_or(false),
+        max_contributors: params.max_contributors,
+    };
+
+    let report = local_repo.contributors(options).await?;
+
+    Ok((report, local_repo))
+}
+
+/// Parameters for ranking the change hotspots of a repository
+#[derive(Debug, Clone)]
+pub struct HotspotsServiceParams {
+    pub repository_location_str: String,
+    pub from_ref: Option<String>,
+    pub to_ref: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub include_globs: Option<Vec<String>>,
+    pub exclude_dirs: Option<Vec<String>>,
+    pub metric: Option<HotspotMetric>,
+    pub weight_by_size: Option<bool>,
+    pub directory_depth: Option<usize>,
+    pub max_results: Option<usize>,
+}
+
+/// Ranks the files and directories of a repository by how often and how much they changed
+///
+/// This pure function handles the entire hotspot analysis:
+/// 1. Parses a repository location string into a RepositoryLocation
+/// 2. Prepares (clones if needed) the repository using the provided manager
+/// 3. Fetches the full history if the clone is shallow, and the refs the clone is missing
+/// 4. Counts commits and churn per file in the requested range and ranks them
+///
+/// # Parameters
+///
+/// * `repository_manager` - The repository manager for cloning/preparing repositories
+/// * `repository_location_str` - The repository location string to parse (e.g., "github:user/repo" or "/path/to/local/repo")
+/// * `from_ref` - Optional exclusive start of the commit range
+/// * `to_ref` - Optional inclusive end of the commit range (default HEAD)
+/// * `since` - Optional start of the time window (e.g. "2024-01-01" or "6 months ago")
+/// * `until` - Optional end of the time window
+/// * `include_globs` - Optional glob patterns of files to include (as in code search)
+/// * `exclude_dirs` - Optional directories or glob patterns to exclude (as in code search)
+/// * `metric` - Optional ranking metric (default `Commits`)
+/// * `weight_by_size` - Optional whether to multiply the metric by the current line count (default false)
+/// * `directory_depth` - Optional number of path components directories are grouped by (default 1)
+/// * `max_results` - Optional maximum number of files and of directories returned (default 20)
+///
+/// # Returns
+///
+/// * `Result<(HotspotReport, repository_manager::LocalRepository), String>` - A tuple containing the ranking and the local repository instance
+///
+/// # Errors
+///
+/// This function returns an error if:
+/// - The repository location string cannot be parsed
+/// - The repository cannot be prepared (cloned or validated)
+/// - The missing history of a shallow clone, or a ref missing from it, cannot be fetched
+/// - A ref, date or glob cannot be parsed
+pub async fn hotspots(
+    repository_manager: &repository_manager::RepositoryManager,
+    params: HotspotsServiceParams,
+) -> Result<
+    (
+        HotspotReport,
+        crate::gitcodes::local_repository::LocalRepository,
+    ),
+    String,
+> {
+    // Parse the repository location string
+    let repository_location = RepositoryLocation::from_str(&params.repository_location_str)
+        .map_err(|e| format!("Failed to parse repository location: {}", e))?;
+
+    let ref_names = [params.from_ref.as_deref(), params.to_ref.as_deref()];
+    let local_repo = prepare_repository_history(
+        repository_manager,
+        &repository_location,
+        ref_names.into_iter().flatten(),
+    )
+    .await?;
+
+    let options = HotspotOptions {
+        from_ref: params.from_ref,
+        to_ref: params.to_ref,
+        since: params.since,
+        until: params.until,
+        include_globs: params.include_globs,
+        exclude_globs: params.exclude_dirs,
+        metric: params.metric.unwrap_or_default(),
+        weight_by_size: params.weight_by_size.unwrap_or(false),
+        directory_depth: params.directory_depth,
+        max_results: params.max_results,
+    };
+
+    let report = local_repo.hotspots(options).await?;
+
+    Ok((report, local_repo))
+}
+
+/// Parameters for comparing two refs of a repository
+#[derive(Debug, Clone)]
+pub struct CompareRefsServiceParams {
+    pub repository_location_str: String,
+    pub base_ref: String,
+    pub head_ref: String,
+    pub max_commits: Option<usize>,
+}
+
+/// Compares two refs of a repository: merge-base, ahead/behind counts and unique commits
+///
+/// This pure function handles the entire comparison process:
+/// 1. Parses a repository location string into a RepositoryLocation
+/// 2. Prepares (clones if needed) the repository using the provided manager
+/// 3. Fetches the full history if the clone is shallow, and the refs the clone is missing
+/// 4. Finds the merge-base and the commits unique to each ref
+///
+/// # Parameters
+///
+/// * `repository_manager` - The repository manager for cloning/preparing repositories
+/// * `repository_location_str` - The repository location string to parse (e.g., "github:user/repo" or "/path/to/local/repo")
+/// * `base_ref` - The ref compared against (e.g. "main" or "upstream/main")
+/// * `head_ref` - The ref being compared (e.g. "feature" or "origin/feature")
+/// * `max_commits` - Optional maximum number of commits listed per side (default 50)
+///
+/// # Returns
+///
+/// * `Result<(RefComparison, repository_manager::LocalRepository), String>` - A tuple containing the comparison and the local repository instance
+///
+/// # Errors
+///
+/// This function returns an error if:
+/// - The repository location string cannot be parsed
+/// - The repository cannot be prepared (cloned or validated)
+/// - The missing history of a shallow clone, or a ref missing from it, cannot be fetched
+/// - Either ref cannot be resolved to a commit
+pub async fn compare_refs(
+    repository_manager: &repository_manager::RepositoryManager,
+    params: CompareRefsServiceParams,
+) -> Result<
+    (
+        RefComparison,
+        crate::gitcodes::local_repository::LocalRepository,
+    ),
+    String,
+> {
+    // Parse the repository location string
+    let repository_location = RepositoryLocation::from_str(&params.repository_location_str)
+        .map_err(|e| format!("Failed to parse repository location: {}", e))?;
+
+    let local_repo = prepare_repository_history(
+        repository_manager,
+        &repository_location,
+        [params.base_ref.as_str(), params.head_ref.as_str()],
+    )
+    .await?;
+
+    let comparison = local_repo
+        .compare_refs(&params.base_ref, &params.head_ref, params.max_commits)
+        .await?;
+
+    Ok((comparison, local_repo))
+}
+
+/// Parameters for reading the working-tree status of a local repository
+#[derive(Debug, Clone)]
+pub struct GitStatusServiceParams {
+    pub repository_location_str: String,
+    pub include_untracked: Option<bool>,
+    pub include_diffs: Option<bool>,
+    pub context_lines: Option<u32>,
+}
+
+/// Lists the uncommitted changes of a local repository, like `git status` with `git diff HEAD`
+///
+/// This pure function handles the entire status process:
+/// 1. Parses a repository location string into a RepositoryLocation
+/// 2. Rejects remote repositories, whose cached clones never have local changes
+/// 3. Prepares (validates) the local repository using the provided manager
+/// 4. Reads the staged, unstaged and untracked files with their diffs against HEAD
+///
+/// # Parameters
+///
+/// * `repository_manager` - The repository manager for preparing repositories
+/// * `repository_location_str` - The absolute path of a local repository (e.g., "/path/to/local/repo")
+/// * `include_untracked` - Whether untracked files are listed (default true)
+/// * `include_diffs` - Whether the diff hunks against HEAD are included (default true)
+/// * `context_lines` - Optional number of context lines around each hunk (default 3)
+///
+/// # Returns
+///
+/// * `Result<(WorkingTreeStatus, repository_manager::LocalRepository), String>` - A tuple containing the status and the local repository instance
+///
+/// # Errors
+///
+/// This function returns an error if:
+/// - The repository location string cannot be parsed
+/// - The location is a remote repository
+/// - The repository cannot be validated or has no working tree
+/// - The index or working tree cannot be read
+pub async fn git_status(
+    repository_manager: &repository_manager::RepositoryManager,
+    params: GitStatusServiceParams,
+) -> Result<
+    (
+        WorkingTreeStatus,
+        crate::gitcodes::local_repository::LocalRepository,
+    ),
+    String,
+> {
+    // Parse the repository location string
+    let repository_location = RepositoryLocation::from_str(&params.repository_location_str)
+        .map_err(|e| format!("Failed to parse repository location: {}", e))?;
+
+    if !matches!(repository_location, RepositoryLocation::LocalPath(_)) {
+        return Err(format!(
+            "Working-tree status is only available for local repositories, not '{}'",
+            params.repository_location_str
+        ));
+    }
+
+    let local_repo = repository_manager
+        .prepare_repository(&repository_location, None)
+        .await?;
+
+    let status = local_repo.working_tree_status(StatusOptions {
+        include_untracked: params.include_untracked.unwrap_or(true),
+        include_diffs: params.include_diffs.unwrap_or(true),
+        context_lines: params.context_lines,
+    })?;
+
+    Ok((status, local_repo))
+}
+
+/// Parameters for finding symbol definitions in a repository
+#[derive(Debug, Clone)]
+pub struct FindSymbolServiceParams {
+    pub repository_location_str: String,
+    pub name: String,
+    pub regex: Option<bool>,
+    pub case_sensitive: Option<bool>,
+    pub kinds: Option<Vec<SymbolKind>>,
+    pub ref_name: Option<String>,
+    pub include_globs: Option<Vec<String>>,
+    pub exclude_dirs: Option<Vec<String>>,
+    pub skip: Option<usize>,
+    pub take: Option<usize>,
+}
+
+/// Finds the definitions of functions, methods, types and traits by name in a repository
+///
+/// This pure function handles the entire symbol search:
+/// 1. Parses a repository location string into a RepositoryLocation
+/// 2. Prepares (clones if needed) the repository using the provided manager
+/// 3. Parses the source files of the supported languages and collects the matching definitions
+///
+/// # Parameters
+///
+/// * `repository_manager` - The repository manager for cloning/preparing repositories
+/// * `repository_location_str` - The repository location string to parse (e.g., "github:user/repo" or "/path/to/local/repo")
+/// * `name` - The symbol name, or a regex if `regex` is set
+/// * `regex` - Optional whether `name` is a regex (default false)
+/// * `case_sensitive` - Optional whether names are matched case-sensitively (default false)
+/// * `kinds` - Optional symbol kinds to return (default all)
+/// * `ref_name` - Optional reference name (branch/tag/commit); when given, that commit's tree is searched from the object database
+/// * `include_globs` - Optional glob patterns of files to search (as in code search)
+/// * `exclude_dirs` - Optional directories or glob patterns to exclude (as in code search)
+/// * `skip` - Optional number of symbols to skip
+/// * `take` - Optional maximum number of symbols to return (default 50)
+///
+/// # Returns
+///
+/// * `Result<(SymbolSearchResult, repository_manager::LocalRepository), String>` - A tuple containing the definitions and the local repository instance
+///
+/// # Errors
+///
+/// This function returns an error if:
+/// - The repository location string cannot be parsed
+/// - The repository cannot be prepared (cloned or validated)
+/// - The name is empty or an invalid regex
+/// - The ref or a glob cannot be resolved
+pub async fn find_symbol(
+    repository_manager: &repository_manager::RepositoryManager,
+    params: FindSymbolServiceParams,
+) -> Result<
+    (
+        SymbolSearchResult,
+        crate::gitcodes::local_repository::LocalRepository,
+    ),
+    String,
+> {
+    // Parse the repository location string
+    let repository_location = RepositoryLocation::from_str(&params.repository_location_str)
+        .map_err(|e| format!("Failed to parse repository location: {}", e))?;
+
+    // Prepare the repository (clone if necessary)
+    let local_repo = repository_manager
+        .prepare_repository(&repository_location, params.ref_name.clone())
+        .await?;
+
+    let options = SymbolSearchOptions {
+        name: params.name,
+        regex: params.regex.unwrap_or(false),
+        case_sensitive: params.case_sensitive.unwrap_or(false),
+        kinds: params.kinds,
+        ref_name: params.ref_name,
+        include_globs: params.include_globs,
+        exclude_globs: params.exclude_dirs,
+        skip: params.skip,
+        take: params.take.or(Some(50)),
+    };
+
+    let result = local_repo.find_symbols(options).await?;
+
+    Ok((result, local_repo))
+}
+
+/// Parameters for outlining a source file of a repository
+#[derive(Debug, Clone)]
+pub struct FileOutlineServiceParams {
+    pub repository_location_str: String,
+    pub file_path: String,
+    pub ref_name: Option<String>,
+}
+
+/// Builds the structural outline of a source file, first preparing the repository if needed
+///
+/// This pure function handles the entire outline process:
+/// 1. Parses a repository location string into a RepositoryLocation
+/// 2. Prepares (clones if needed) the repository using the provided manager
+/// 3. Parses the file and lists its items with their line ranges and doc comment first lines
+///
+/// # Parameters
+///
+/// * `repository_manager` - The repository manager for cloning/preparing repositories
+/// * `repository_location_str` - The repository location string to parse (e.g., "github:user/repo" or "/path/to/local/repo")
+/// * `file_path` - Path of the file relative to the repository root
+/// * `ref_name` - Optional reference name (branch/tag/commit); when given, the file is read from the object database at that ref
+///
+/// # Returns
+///
+/// * `Result<(FileOutline, repository_manager::LocalRepository), String>` - A tuple containing the outline and the local repository instance
+///
+/// # Errors
+///
+/// This function returns an error if:
+/// - The repository location string cannot be parsed
+/// - The repository cannot be prepared (cloned or validated)
+/// - The file doesn't exist, isn't UTF-8 text, or its language isn't supported
+pub async fn file_outline(
+    repository_manager: &repository_manager::RepositoryManager,
+    params: FileOutlineServiceParams,
+) -> Result<
+    (
+        FileOutline,
+        crate::gitcodes::local_repository::LocalRepository,
+    ),
+    String,
+> {
+    // Parse the repository location string
+    let repository_location = RepositoryLocation::from_str(&params.repository_location_str)
+        .map_err(|e| format!("Failed to parse repository location: {}", e))?;
+
+    // Prepare the repository (clone if necessary)
+    let local_repo = repository_manager
+        .prepare_repository(&repository_location, params.ref_name.clone())
+        .await?;
+
+    let outline = local_repo
+        .file_outline(&PathBuf::from(params.file_path), params.ref_name.as_deref())
+        .await?;
+
+    Ok((outline, local_repo))
+}
+
+/// Parameters for resolving a qualified symbol name to its definitions
+#[derive(Debug, Clone)]
+pub struct ShowSymbolServiceParams {
+    pub repository_location_str: String,
+    pub qualified_name: String,
+    pub ref_name: Option<String>,
+    pub include_globs: Option<Vec<String>>,
+    pub exclude_dirs: Option<Vec<String>>,
+    pub max_results: Option<usize>,
+}
+
+/// Resolves a qualified symbol name like `Mutex::lock` to the full source of its definitions
+///
+/// This pure function handles the entire lookup:
+/// 1. Parses a repository location string into a RepositoryLocation
+/// 2. Prepares (clones if needed) the repository using the provided manager
+/// 3. Parses the source files that mention the name and returns the matching definitions
+///    with their doc comments and attributes
+///
+/// # Parameters
+///
+/// * `repository_manager` - The repository manager for cloning/preparing repositories
+/// * `repository_location_str` - The repository location string to parse (e.g., "github:user/repo" or "/path/to/local/repo")
+/// * `qualified_name` - The name to resolve, segments separated by `::` or `.`
+/// * `ref_name` - Optional reference name (branch/tag/commit); when given, that commit's tree is searched from the object database
+/// * `include_globs` - Optional glob patterns of files to search (as in code search)
+/// * `exclude_dirs` - Optional directories or glob patterns to exclude (as in code search)
+/// * `max_results` - Optional maximum number of definitions to return (default 10)
+///
+/// # Returns
+///
+/// * `Result<(SymbolSourceResult, repository_manager::LocalRepository), String>` - A tuple containing the definitions and the local repository instance
+///
+/// # Errors
+///
+/// This function returns an error if:
+/// - The repository location string cannot be parsed
+/// - The repository cannot be prepared (cloned or validated)
+/// - The qualified name is empty
+/// - The ref or a glob cannot be resolved
+pub async fn show_symbol(
+    repository_manager: &repository_manager::RepositoryManager,
+    params: ShowSymbolServiceParams,
+) -> Result<
+    (
+        SymbolSourceResult,
+        crate::gitcodes::local_repository::LocalRepository,
+    ),
+    String,
+> {
+    // Parse the repository location string
+    let repository_location = RepositoryLocation::from_str(&params.repository_location_str)
+        .map_err(|e| format!("Failed to parse repository location: {}", e))?;
+
+    // Prepare the repository (clone if necessary)
+    let local_repo = repository_manager
+        .prepare_repository(&repository_location, params.ref_name.clone())
+        .await?;
+
+    let options = ShowSymbolOptions {
+        qualified_name: params.qualified_name,
+        ref_name: params.ref_name,
+        include_globs: params.include_globs,
+        exclude_globs: params.exclude_dirs,
+        max_results: params.max_results.or(Some(10)),
+    };
+
+    let result = local_repo.show_symbol(options).await?;
+
+    Ok((result, local_repo))
+}
+
+/// Parameters for finding the references to an identifier
+#[derive(Debug, Clone)]
+pub struct FindReferencesServiceParams {
+    pub repository_location_str: String,
+    pub name: String,
+    pub ref_name: Option<String>,
+    pub include_definitions: Option<bool>,
+    pub include_globs: Option<Vec<String>>,
+    pub exclude_dirs: Option<Vec<String>>,
+    pub skip: Option<usize>,
+    pub take: Option<usize>,
+}
+
+/// Finds the places where an identifier is used, excluding comments and string literals
+///
+/// This pure function handles the entire lookup:
+/// 1. Parses a repository location string into a RepositoryLocation
+/// 2. Prepares (clones if needed) the repository using the provided manager
+/// 3. Loads the identifier index of the commit, building and caching it on first use,
+///    and returns the definitions and references of the name
+///
+/// # Parameters
+///
+/// * `repository_manager` - The repository manager for cloning/preparing repositories
+/// * `repository_location_str` - The repository location string to parse (e.g., "github:user/repo" or "/path/to/local/repo")
+/// * `name` - The identifier to find (exact, case-sensitive)
+/// * `ref_name` - Optional reference name (branch/tag/commit); the commit at HEAD is indexed when omitted
+/// * `include_definitions` - Whether the names of the definitions are returned as references too (default false)
+/// * `include_globs` - Optional glob patterns of files to return references from (as in code search)
+/// * `exclude_dirs` - Optional directories or glob patterns to exclude (as in code search)
+/// * `skip` - Optional number of references to skip (for pagination)
+/// * `take` - Optional maximum number of references to return (default 100)
+///
+/// # Returns
+///
+/// * `Result<(ReferenceSearchResult, repository_manager::LocalRepository), String>` - A tuple containing the references and the local repository instance
+///
+/// # Errors
+///
+/// This function returns an error if:
+/// - The repository location string cannot be parsed
+/// - The repository cannot be prepared (cloned or validated)
+/// - The name is empty
+/// - The ref or a glob cannot be resolved
+pub async fn find_references(
+    repository_manager: &repository_manager::RepositoryManager,
+    params: FindReferencesServiceParams,
+) -> Result<
+    (
+        ReferenceSearchResult,
+        crate::gitcodes::local_repository::LocalRepository,
+    ),
+    String,
+> {
+    // Parse the repository location string
+    let repository_location = RepositoryLocation::from_str(&params.repository_location_str)
+        .map_err(|e| format!("Failed to parse repository location: {}", e))?;
+
+    // Prepare the repository (clone if necessary)
+    let local_repo = repository_manager
+        .prepare_repository(&repository_location, params.ref_name.clone())
+        .await?;
+
+    let options = ReferenceSearchOptions {
+        name: params.name,
+        ref_name: params.ref_name,
+        include_definitions: params.include_definitions.unwrap_or(false),
+        include_globs: params.include_globs,
+        exclude_globs: params.exclude_dirs,
+        skip: params.skip,
+        take: params.take.or(Some(100)),
+    };
+
+    let result = local_repo.find_references(options).await?;
+
+    Ok((result, local_repo))
+}
+
+/// Parameters for a structural code search
+#[derive(Debug, Clone)]
+pub struct StructuralSearchServiceParams {
+    pub repository_location_str: String,
+    pub pattern: String,
+    pub language: SourceLanguage,
+    pub ref_name: Option<String>,
+    pub include_globs: Option<Vec<String>>,
+    pub exclude_dirs: Option<Vec<String>>,
+    pub skip: Option<usize>,
+    pub take: Option<usize>,
+}
+
+/// Finds the syntax nodes matching an AST pattern with metavariables
+///
+/// This pure function handles the entire search:
+/// 1. Parses a repository location string into a RepositoryLocation
+/// 2. Prepares (clones if needed) the repository using the provided manager
+/// 3. Parses the pattern as code of its language and matches it against the syntax trees
+///    of the files of that language
+///
+/// # Parameters
+///
+/// * `repository_manager` - The repository manager for cloning/preparing repositories
+/// * `repository_location_str` - The repository location string to parse (e.g., "github:user/repo" or "/path/to/local/repo")
+/// * `pattern` - The pattern, e.g. `File::open($PATH).unwrap()`; `$NAME` matches one node, `$$$NAME` any number of nodes
+/// * `language` - The language of the pattern and of the files to search
+/// * `ref_name` - Optional reference name (branch/tag/commit) whose tree is searched instead of the worktree
+/// * `include_globs` - Optional glob patterns of files to search (as in code search)
+/// * `exclude_dirs` - Optional directories or glob patterns to exclude (as in code search)
+/// * `skip` - Optional number of matches to skip (for pagination)
+/// * `take` - Optional maximum number of matches to return (default 50)
+///
+/// # Returns
+///
+/// * `Result<(StructuralSearchResult, repository_manager::LocalRepository), String>` - A tuple containing the matches and the local repository instance
+///
+/// # Errors
+///
+/// This function returns an error if:
+/// - The repository location string cannot be parsed
+/// - The repository cannot be prepared (cloned or validated)
+/// - The pattern isn't valid code of its language or is only a metavariable
+/// - The ref or a glob cannot be resolved
+pub async fn structural_search(
+    repository_manager: &repository_manager::RepositoryManager,
+    params: StructuralSearchServiceParams,
+) -> Result<
+    (
+        StructuralSearchResult,
+        crate::gitcodes::local_repository::LocalRepository,
+    ),
+    String,
+> {
+    // Parse the repository location string
+    let repository_location = RepositoryLocation::from_str(&params.repository_location_str)
+        .map_err(|e| format!("Failed to parse repository location: {}", e))?;
+
+    // Prepare the repository (clone if necessary)
+    let local_repo = repository_manager
+        .prepare_repository(&repository_location, params.ref_name.clone())
+        .await?;
+
+    let options = StructuralSearchOptions {
+        pattern: params.pattern,
+        language: params.language,
+        ref_name: params.ref_name,
+        include_globs: params.include_globs,
+        exclude_globs: params.exclude_dirs,
+        skip: params.skip,
+        take: params.take.or(Some(50)),
+        max_lines_per_match: None,
+    };
+
+    let result = local_repo.structural_search(options).await?;
+
+    Ok((result, local_repo))
+}
+
+/// Parameters for a ranked full-text search
+#[derive(Debug, Clone)]
+pub struct RankedSearchServiceParams {
+    pub repository_location_str: String,
+    pub query: String,
+    pub ref_name: Option<String>,
+    pub include_globs: Option<Vec<String>>,
+    pub exclude_dirs: Option<Vec<String>>,
+    pub skip: Option<usize>,
+    pub take: Option<usize>,
+}
+
+/// Ranks the code chunks of a repository by their relevance to a free-text query
+///
+/// This pure function handles the entire search:
+/// 1. Parses a repository location string into a RepositoryLocation
+/// 2. Prepares (clones if needed) the repository using the provided manager
+/// 3. Loads or builds the cached chunk index of the commit and scores its chunks with BM25
+///
+/// # Parameters
+///
+/// * `repository_manager` - The repository manager for cloning/preparing repositories
+/// * `repository_location_str` - The repository location string to parse (e.g., "github:user/repo" or "/path/to/local/repo")
+/// * `query` - Free text, e.g. "where is the config file parsed"
+/// * `ref_name` - Optional reference name (branch/tag/commit) to search; the commit at HEAD when omitted
+/// * `include_globs` - Optional glob patterns of files to return chunks from (as in code search)
+/// * `exclude_dirs` - Optional directories or glob patterns to exclude (as in code search)
+/// * `skip` - Optional number of chunks to skip (for pagination)
+/// * `take` - Optional maximum number of chunks to return (default 10)
+///
+/// # Returns
+///
+/// * `Result<(RankedSearchResult, repository_manager::LocalRepository), String>` - A tuple containing the ranked chunks and the local repository instance
+///
+/// # Errors
+///
+/// This function returns an error if:
+/// - The repository location string cannot be parsed
+/// - The repository cannot be prepared (cloned or validated)
+/// - The query holds no searchable words
+/// - The ref or a glob cannot be resolved
+pub async fn search_code_ranked(
+    repository_manager: &repository_manager::RepositoryManager,
+    params: RankedSearchServiceParams,
+) -> Result<
+    (
+        RankedSearchResult,
+        crate::gitcodes::local_repository::LocalRepository,
+    ),
+    String,
+> {
+    // Parse the repository location string
+    let repository_location = RepositoryLocation::from_str(&params.repository_location_str)
+        .map_err(|e| format!("Failed to parse repository location: {}", e))?;
+
+    // Prepare the repository (clone if necessary)
+    let local_repo = repository_manager
+        .prepare_repository(&repository_location, params.ref_name.clone())
+        .await?;
+
+    let options = RankedSearchOptions {
+        query: params.query,
+        ref_name: params.ref_name,
+        include_globs: params.include_globs,
+        exclude_globs: params.exclude_dirs,
+        skip: params.skip,
+        take: params.take,
+        max_lines_per_chunk: None,
+    };
+
+    let result = local_repo.search_code_ranked(options).await?;
+
+    Ok((result, local_repo))
+}
+
+/// Parameters for finding files by name
+#[derive(Debug, Clone)]
+pub struct FindFilesServiceParams {
+    pub repository_location_str: String,
+    pub query: String,
+    pub ref_name: Option<String>,
+    pub respect_gitignore: Option<bool>,
+    pub skip: Option<usize>,
+    pub take: Option<usize>,
+}
+
+/// Finds the files of a repository whose paths match a glob or fuzzy query
+///
+/// This pure function handles the entire search:
+/// 1. Parses a repository location string into a RepositoryLocation
+/// 2. Prepares (clones if needed) the repository using the provided manager
+/// 3. Lists the files of the worktree or of the tree of the ref and matches their paths
+///
+/// # Parameters
+///
+/// * `repository_manager` - The repository manager for cloning/preparing repositories
+/// * `repository_location_str` - The repository location string to parse (e.g., "github:user/repo" or "/path/to/local/repo")
+/// * `query` - A glob like `**/Cargo.toml`, or any other text matched fuzzily like `octoclient`
+/// * `ref_name` - Optional reference name (branch/tag/commit) whose tree is searched instead of the worktree
+/// * `respect_gitignore` - Optional flag to leave out files ignored by git and hidden files (default: true)
+/// * `skip` - Optional number of files to skip (for pagination)
+/// * `take` - Optional maximum number of files to return (default 50)
+///
+/// # Returns
+///
+/// * `Result<(FindFilesResult, repository_manager::LocalRepository), String>` - A tuple containing the matching files and the local repository instance
+///
+/// # Errors
+///
+/// This function returns an error if:
+/// - The repository location string cannot be parsed
+/// - The repository cannot be prepared (cloned or validated)
+/// - The query is empty or an invalid glob
+/// - The ref cannot be resolved
+pub async fn find_files(
+    repository_manager: &repository_manager::RepositoryManager,
+    params: FindFilesServiceParams,
+) -> Result<
+    (
+        FindFilesResult,
+        crate::gitcodes::local_repository::LocalRepository,
+    ),
+    String,
+> {
+    // Parse the repository location string
+    let repository_location = RepositoryLocation::from_str(&params.repository_location_str)
+        .map_err(|e| format!("Failed to parse repository location: {}", e))?;
+
+    // Prepare the repository (clone if necessary)
+    let local_repo = repository_manager
+        .prepare_repository(&repository_location, params.ref_name.clone())
+        .await?;
+
+    let options = FindFilesOptions {
+        query: params.query,
+        ref_name: params.ref_name,
+        respect_gitignore: params.respect_gitignore,
+        skip: params.skip,
+        take: params.take,
+    };
+
+    let result = local_repo.find_files(options).await?;
+
+    Ok((result, local_repo))
+}
+
+/// Parameters for counting the matches of a grep operation per file, directory and extension
+#[derive(Debug, Clone, Default)]
+pub struct GrepFacetsParams {
+    pub repository_location_str: String,
+    pub pattern: String,
+    pub ref_name: Option<String>,
+    pub case_sensitive: bool,
+    pub file_extensions: Option<Vec<String>>,
+    pub include_globs: Option<Vec<String>>,
+    pub exclude_dirs: Option<Vec<String>>,
+    pub changed_files_only: bool,
+    pub use_index: bool,
+    pub fixed_string: bool,
+    pub word_regexp: bool,
+    pub multiline: bool,
+    pub top_n: Option<usize>,
+}
+
+/// Number of values listed per facet by default
+const DEFAULT_FACET_TOP_N: usize = 10;
+
+/// Counts the matches of a grep operation per file, top-level directory and extension
+///
+/// This pure function handles the entire aggregation:
+/// 1. Parses a repository location string into a RepositoryLocation
+/// 2. Prepares (clones if needed) the repository using the provided manager
+/// 3. Searches all files without pagination or context, like [`perform_grep_in_repository`]
+/// 4. Counts the matched lines per facet, without returning any line content
+///
+/// # Parameters
+///
+/// * `repository_manager` - The repository manager for cloning/preparing repositories
+/// * `repository_location_str` - The repository location string to parse (e.g., "github:user/repo" or "/path/to/local/repo")
+/// * `pattern` and the filters - The search, as for [`perform_grep_in_repository`]
+/// * `top_n` - Optional maximum number of values listed per facet (default 10)
+///
+/// # Returns
+///
+/// * `Result<(SearchFacets, repository_manager::LocalRepository), String>` - A tuple containing the match counts and the local repository instance
+///
+/// # Errors
+///
+/// This function returns an error if:
+/// - `top_n` is 0
+/// - The repository location string cannot be parsed
+/// - The repository cannot be prepared (cloned or validated)
+/// - The code search operation fails
+pub async fn grep_facets(
+    repository_manager: &repository_manager::RepositoryManager,
+    params: GrepFacetsParams,
+) -> Result<
+    (
+        SearchFacets,
+        crate::gitcodes::local_repository::LocalRepository,
+    ),
+    String,
+> {
+    let top_n = params.top_n.unwrap_or(DEFAULT_FACET_TOP_N);
+    if top_n == 0 {
+        return Err("top_n must be at least 1".to_string());
+    }
+
+    // Parse the repository location string
+    let repository_location = RepositoryLocation::from_str(&params.repository_location_str)
+        .map_err(|e| format!("Failed to parse repository location: {}", e))?;
+
+    // A cached clone of a remote repository never has uncommitted changes
+    if params.changed_files_only && !matches!(repository_location, RepositoryLocation::LocalPath(_))
+    {
+        return Err(format!(
+            "changed_files_only is only available for local repositories, not '{}'",
+            params.repository_location_str
+        ));
+    }
+
+    // Prepare the repository (clone if necessary)
+    let local_repo = repository_manager
+        .prepare_repository(&repository_location, params.ref_name.clone())
+        .await?;
+
+    // Every match is counted, so the search is neither paginated nor given context
+    let search_params = CodeSearchParams {
+        repository_location,
+        ref_name: params.ref_name,
+        pattern: params.pattern,
+        case_sensitive: params.case_sensitive,
+        fixed_string: params.fixed_string,
+        word_regexp: params.word_regexp,
+        multiline: params.multiline,
+        file_extensions: params.file_extensions,
+        include_globs: params.include_globs,
+        exclude_dirs: params.exclude_dirs,
+        changed_files_only: params.changed_files_only,
+        use_index: params.use_index,
+        before_context: None,
+        after_context: None,
+        context_mode: None,
+        max_context_lines: None,
+        skip: None,
+        take: None,
+        match_content_omit_num: Some(0),
+    };
+
+    let search_result = local_repo.search_code(search_params).await?;
+
+    Ok((search_result.facets(top_n), local_repo))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grep_query_hash_is_stable() {
+        let params = GrepParams {
+            repository_location_str: "github:user/repo".to_string(),
+            pattern: "fn main".to_string(),
+            ref_name: Some("main".to_string()),
+            ..Default::default()
+        };
+
+        // Cursors carry this hash between calls, possibly across a rebuild of the server
+        assert_eq!(grep_query_hash(&params), 6817587455593275618);
+
+        // Pagination and the index don't change the results, so they don't change the hash
+        let paged = GrepParams {
+            skip: Some(10),
+            take: Some(5),
+            use_index: true,
+            ..params.clone()
+        };
+        assert_eq!(grep_query_hash(&paged), grep_query_hash(&params));
+
+        let other = GrepParams {
+            case_sensitive: true,
+            ..params.clone()
+        };
+        assert_ne!(grep_query_hash(&other), grep_query_hash(&params));
+    }
+}
@@ -4,6 +4,7 @@ use rmcp::{model::*, schemars, tool, Error as McpError, ServerHandler};
 use std::path::PathBuf;
 
 use crate::gitcodes::repository_manager::providers::models::GitProvider;
+use crate::gitcodes::repository_manager::version_tags::{self, LatestAlias};
 use std::str::FromStr;
 mod error;
 pub mod responses;
@@ -123,11 +124,28 @@ impl ServerHandler for GitHubCodeTools {
 ## Available Tools
 - `search_repositories`: Search for GitHub repositories
 - `search_issues_and_pull_requests`: Search for GitHub issues and pull requests
-- `grep_repository`: Search code within a GitHub repository (returns compact grouped format)
+- `grep_repository`: Search code within a GitHub repository (returns compact grouped format, with a `next_cursor` for stable paging)
 - `grep_repository_match_line_number`: Count matching lines only (returns number)
-- `list_repository_refs`: List branches and tags for a repository
+- `grep_repository_facets`: Count matching lines per file, top-level directory and extension (no line content)
+- `grep_repository_boolean`: Find files matching patterns combined with AND/OR/NOT (returns compact grouped format)
+- `grep_repositories`: Run the same code search in several repositories concurrently (results grouped by repository)
+- `save_repository_collection`: Save a named list of repositories to search with `grep_repositories`
+- `list_repository_refs`: List branches and tags for a repository (tags sorted by semantic version)
 - `show_file_contents`: View file contents in compact format with concatenated lines and enhanced metadata
 - `get_repository_tree`: Get the directory tree structure of a repository
+- `find_files`: Find files by glob (`**/Cargo.toml`) or fuzzy name (`octoclient`) with size and language
+- `search_history`: Find commits that added or removed a string (like `git log -S`/`-G`)
+- `changelog_between`: List the commits between two refs grouped by conventional commit type
+- `contributors`: Commit counts, activity dates and changed lines per author (like `git shortlog -sne`)
+- `hotspots`: Rank files and directories by change frequency or churn to find where to start reading
+- `compare_refs`: Merge-base, ahead/behind counts and unique commits of two refs
+- `git_status`: Staged, unstaged and untracked files of a local repository with diffs against HEAD
+- `find_symbol`: Find where functions, methods, types and traits are defined (syntax-aware, not regex)
+- `file_outline`: List the items of a source file (modules, impls, classes, functions) with line ranges and doc first lines
+- `show_symbol`: Show the full source of a definition by qualified name, e.g. `Mutex::lock`
+- `find_references`: Find where an identifier is used, ignoring comments and strings (cached identifier index)
+- `structural_search`: Find code by syntax with an AST pattern and `$NAME`/`$$$NAME` metavariables, e.g. `File::open($PATH).unwrap()`
+- `search_code_ranked`: Find the functions and code chunks most relevant to a free-text query, best first (BM25, offline)
 
 ### search_issues_and_pull_requests Examples
 Search for GitHub issues and pull requests with powerful query syntax support:
@@ -420,6 +438,7 @@ impl GitHubCodeTools {
     ///   - `file_path`: Path to the file containing matches
     ///   - `lines`: Concatenated line contents with format "{line_number}:{content}"
     /// - Search metadata: pattern, case_sensitive, file filters, context settings
+    /// - `next_cursor`: Cursor for the next page of a search pinned to a commit, if any
     ///
     /// # Authentication
     ///
@@ -433,26 +452,26 @@ impl GitHubCodeTools {
     /// 2. Code search is performed on the local files
     /// 3. Results are grouped by file and formatted as compact JSON
     #[tool(
-        description = "Search code in GitHub repositories or local directories using regex patterns (returns compact JSON format). Clones repos locally for searching. Supports private repos, branch selection, and context lines. Results are grouped by file with concatenated line contents. Example usage with basic search, filtering options, and pagination."
+        description = "Search code in GitHub repositories or local directories using regex patterns (returns compact JSON format). Clones repos locally for searching. Supports private repos, branch selection, context lines or the enclosing function/class of each match ('context_mode'), literal ('fixed_string'), whole-word ('word_regexp') and multiline matching. Results are grouped by file with concatenated line contents. Example usage with basic search, filtering options, and pagination. When more results remain, the response has a 'next_cursor'; pass it as 'cursor' with the same other arguments to get the next page from the same commit."
     )]
     #[allow(clippy::too_many_arguments)]
     async fn grep_repository(
         &self,
         #[tool(param)]
         #[schemars(
-            description = "Repository URL or local path (required). Supports GitHub formats: 'git@github.com:user/repo.git' (SSH, recommended), 'https://github.com/user/repo', 'github:user/repo', or absolute local paths. Private repos require GITCODES_MCP_GITHUB_TOKEN environment variable. This parameter is required and must be provided."
+            description = "Repository URL or local path (required). Supports GitHub formats: 'git@github.com:user/repo.git' (SSH, recommended), 'https://github.com/user/repo', 'github:user/repo', absolute local paths, or git bundle files as 'bundle:/path/to/repo.bundle'. Private repos require GITCODES_MCP_GITHUB_TOKEN environment variable. This parameter is required and must be provided."
         )]
         repository_location: String,
 
         #[tool(param)]
         #[schemars(
-            description = "Branch, commit, or tag (optional, default 'main'/'master'). Can be branch name (e.g. 'develop'), commit hash (full or short), or tag name (e.g. 'v1.0.0'). Falls back to repository's default branch if specified ref doesn't exist."
+            description = "Branch, commit, or tag (optional, default 'main'/'master'). Can be branch name (e.g. 'develop'), commit hash (full or short), or tag name (e.g. 'v1.0.0'). The aliases 'latest' and 'latest-stable' resolve to the tag with the highest semantic version ('latest-stable' skips prereleases). When given, the blobs of that commit's tree are searched straight from the git object database without a checkout, so many refs can be searched from one clone (also works for local repositories). A ref that exists neither locally nor on the remote is an error; there is no fallback to the default branch."
         )]
         ref_name: Option<String>,
 
         #[tool(param)]
         #[schemars(
-            description = "Regular expression pattern to search for (required). Escape special regex characters for literal searches: '.^$*+?()[]{}\\|' should be prefixed with backslash for literal matching. This parameter is required and must be provided."
+            description = "Regular expression pattern to search for (required). For literal text such as 'Vec<T>::new(' set 'fixed_string' instead of escaping the special regex characters '.^$*+?()[]{}\\|'. This parameter is required and must be provided."
         )]
         pattern: String,
 
@@ -462,6 +481,24 @@ impl GitHubCodeTools {
         )]
         case_sensitive: Option<bool>,
 
+        #[tool(param)]
+        #[schemars(
+            description = "Treat the pattern as a literal string (optional, default false), like 'grep -F'. Every character matches itself, so no regex escaping is needed."
+        )]
+        fixed_string: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Match whole words only (optional, default false), like 'grep -w'. A match must not be preceded or followed by a letter, digit or underscore. Works for patterns starting or ending with punctuation, unlike '\\b'."
+        )]
+        word_regexp: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Allow matches spanning multiple lines (optional, default false), like 'rg --multiline'. The pattern can match line breaks with '\\n' or '\\s', e.g. 'fn parse\\(\\s*input: &str,\\s*strict' for a signature split across lines. '.' only matches line breaks with the '(?s)' flag. Every line of a match is returned as a matched line."
+        )]
+        multiline: Option<bool>,
+
         #[tool(param)]
         #[schemars(
             description = "[DEPRECATED] File extensions to search. Use include_globs instead."
@@ -480,6 +517,18 @@ impl GitHubCodeTools {
         )]
         exclude_dirs: Option<Vec<String>>,
 
+        #[tool(param)]
+        #[schemars(
+            description = "Only search files with uncommitted changes (optional, default false). Restricts the search to staged, unstaged and untracked files of a local repository's working tree, e.g. to review work in progress. Only valid for local paths and cannot be combined with 'ref_name'."
+        )]
+        changed_files_only: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Narrow the search with a trigram index cached per tree (optional, default false). Only speeds up repeated searches of large repositories; the results don't change."
+        )]
+        use_index: Option<bool>,
+
         #[tool(param)]
         #[schemars(
             description = "Lines of context before each match (optional, default 0). Number of lines to show before matching line for context. Must be non-negative integer. Useful for understanding match context."
@@ -492,6 +541,18 @@ impl GitHubCodeTools {
         )]
         after_context: Option<usize>,
 
+        #[tool(param)]
+        #[schemars(
+            description = "How the context around matches is chosen (optional, default 'lines'). 'lines' shows before_context/after_context lines. 'enclosing_item' parses Rust, Go, Python, TypeScript and JavaScript files and adds the innermost function, method, impl or class containing each match, so a hit comes with its signature; hits in the same item are merged. Matches outside any item and in other files keep the line context."
+        )]
+        context_mode: Option<ContextMode>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum lines shown per enclosing item with context_mode 'enclosing_item' (optional, default 50). Longer items are shown as their first line plus the lines around the match."
+        )]
+        max_context_lines: Option<usize>,
+
         #[tool(param)]
         #[schemars(
             description = "Number of results to skip for pagination (optional). Must be non-negative integer. Use with 'take' parameter to implement pagination. Example: skip=20, take=10 gets results 21-30."
@@ -504,6 +565,12 @@ impl GitHubCodeTools {
         )]
         take: Option<usize>,
 
+        #[tool(param)]
+        #[schemars(
+            description = "The 'next_cursor' of the previous page (optional), to continue a search instead of using 'skip'. Pass the same other arguments as for the previous page. The next page is searched at the commit the first page was searched at, even if the repository was updated since, and starts right after the previous page. Fails if that commit is no longer available; start the search again without a cursor then. Cursors are returned for searches of a ref or of a cloned repository, not of a local working tree without 'ref_name'."
+        )]
+        cursor: Option<String>,
+
         #[tool(param)]
         #[schemars(
             description = "Maximum number of characters to show from matched content (optional, default 150). When matches contain very long lines, this parameter truncates the content to the specified number of characters to keep responses manageable. Set to None to show full content without truncation."
@@ -518,10 +585,18 @@ impl GitHubCodeTools {
             file_extensions: file_extensions.clone(),
             include_globs: include_globs.clone(),
             exclude_dirs: exclude_dirs.clone(),
+            changed_files_only: changed_files_only.unwrap_or(false),
+            use_index: use_index.unwrap_or(false),
+            fixed_string: fixed_string.unwrap_or(false),
+            word_regexp: word_regexp.unwrap_or(false),
+            multiline: multiline.unwrap_or(false),
             before_context,
             after_context,
+            context_mode,
+            max_context_lines,
             skip,
             take,
+            cursor,
             match_content_omit_num,
         };
 
@@ -584,19 +659,19 @@ impl GitHubCodeTools {
         &self,
         #[tool(param)]
         #[schemars(
-            description = "Repository URL or local path (required). Supports GitHub formats: 'git@github.com:user/repo.git' (SSH, recommended), 'https://github.com/user/repo', 'github:user/repo', or absolute local paths. Private repos require GITCODES_MCP_GITHUB_TOKEN environment variable. This parameter is required and must be provided."
+            description = "Repository URL or local path (required). Supports GitHub formats: 'git@github.com:user/repo.git' (SSH, recommended), 'https://github.com/user/repo', 'github:user/repo', absolute local paths, or git bundle files as 'bundle:/path/to/repo.bundle'. Private repos require GITCODES_MCP_GITHUB_TOKEN environment variable. This parameter is required and must be provided."
         )]
         repository_location: String,
 
         #[tool(param)]
         #[schemars(
-            description = "Branch, commit, or tag (optional, default 'main'/'master'). Can be branch name (e.g. 'develop'), commit hash (full or short), or tag name (e.g. 'v1.0.0'). Falls back to repository's default branch if specified ref doesn't exist."
+            description = "Branch, commit, or tag (optional, default 'main'/'master'). Can be branch name (e.g. 'develop'), commit hash (full or short), or tag name (e.g. 'v1.0.0'). The aliases 'latest' and 'latest-stable' resolve to the tag with the highest semantic version ('latest-stable' skips prereleases). When given, the blobs of that commit's tree are searched straight from the git object database without a checkout, so many refs can be searched from one clone (also works for local repositories). A ref that exists neither locally nor on the remote is an error; there is no fallback to the default branch."
         )]
         ref_name: Option<String>,
 
         #[tool(param)]
         #[schemars(
-            description = "Regular expression pattern to search for (required). Escape special regex characters for literal searches: '.^$*+?()[]{}\\|' should be prefixed with backslash for literal matching. This parameter is required and must be provided."
+            description = "Regular expression pattern to search for (required). For literal text such as 'Vec<T>::new(' set 'fixed_string' instead of escaping the special regex characters '.^$*+?()[]{}\\|'. This parameter is required and must be provided."
         )]
         pattern: String,
 
@@ -606,6 +681,24 @@ impl GitHubCodeTools {
         )]
         case_sensitive: Option<bool>,
 
+        #[tool(param)]
+        #[schemars(
+            description = "Treat the pattern as a literal string (optional, default false), like 'grep -F'. Every character matches itself, so no regex escaping is needed."
+        )]
+        fixed_string: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Match whole words only (optional, default false), like 'grep -w'. A match must not be preceded or followed by a letter, digit or underscore. Works for patterns starting or ending with punctuation, unlike '\\b'."
+        )]
+        word_regexp: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Allow matches spanning multiple lines (optional, default false), like 'rg --multiline'. The pattern can match line breaks with '\\n' or '\\s', e.g. 'fn parse\\(\\s*input: &str,\\s*strict' for a signature split across lines. '.' only matches line breaks with the '(?s)' flag. Every line of a match is returned as a matched line."
+        )]
+        multiline: Option<bool>,
+
         #[tool(param)]
         #[schemars(
             description = "[DEPRECATED] File extensions to search. Use include_globs instead."
@@ -624,6 +717,18 @@ impl GitHubCodeTools {
         )]
         exclude_dirs: Option<Vec<String>>,
 
+        #[tool(param)]
+        #[schemars(
+            description = "Only search files with uncommitted changes (optional, default false). Restricts the search to staged, unstaged and untracked files of a local repository's working tree, e.g. to review work in progress. Only valid for local paths and cannot be combined with 'ref_name'."
+        )]
+        changed_files_only: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Narrow the search with a trigram index cached per tree (optional, default false). Only speeds up repeated searches of large repositories; the results don't change."
+        )]
+        use_index: Option<bool>,
+
         #[tool(param)]
         #[schemars(
             description = "Lines of context before each match (optional, default 0). Number of lines to show before matching line for context. Must be non-negative integer. Useful for understanding match context."
@@ -662,10 +767,18 @@ impl GitHubCodeTools {
             file_extensions: file_extensions.clone(),
             include_globs: include_globs.clone(),
             exclude_dirs: exclude_dirs.clone(),
+            changed_files_only: changed_files_only.unwrap_or(false),
+            use_index: use_index.unwrap_or(false),
+            fixed_string: fixed_string.unwrap_or(false),
+            word_regexp: word_regexp.unwrap_or(false),
+            multiline: multiline.unwrap_or(false),
             before_context,
             after_context,
+            context_mode: None,
+            max_context_lines: None,
             skip,
             take,
+            cursor: None,
             match_content_omit_num,
         };
 
@@ -698,303 +811,1841 @@ impl GitHubCodeTools {
         }
     }
 
-    /// List branches and tags for a GitHub repository
-    ///
-    /// This tool retrieves a list of all branches and tags for the specified repository.
-    /// It supports both public and private repositories.
+    /// Search for files matching a boolean combination of patterns
     ///
-    /// # Authentication
-    ///
-    /// - For public repositories: No authentication needed
-    /// - For private repositories: Requires `GITCODES_MCP_GITHUB_TOKEN` with `repo` scope
-    ///
-    /// # Implementation Note
-    ///
-    /// This tool:
-    /// 1. Clones or updates the repository locally
-    /// 2. Fetches all branches and tags
-    /// 3. Formats the results into a readable format
+    /// Selects the files that match every `all_of` pattern, at least one `any_of` pattern
+    /// and none of the `none_of` patterns, and returns the matches of the positive patterns
+    /// in the same compact, file-grouped format as `grep_repository`.
     #[tool(
-        description = "List all branches and tags for a repository. Clones locally to retrieve references. Example: `{\"name\": \"list_repository_refs\", \"arguments\": {\"repository_location\": \"git@github.com:user/repo.git\"}}`"
+        description = "Find files matching several patterns combined with AND/OR/NOT at file scope, e.g. files containing 'impl Drop' AND 'unsafe' but NOT '#[cfg(test)]'. A file is selected when it matches all 'all_of' patterns, at least one 'any_of' pattern (if given) and none of the 'none_of' patterns. Returns the matching lines of the positive patterns in the selected files, grouped by file like grep_repository; 'pattern' in the response describes the expression. Example: `{\"name\": \"grep_repository_boolean\", \"arguments\": {\"repository_location\": \"github:user/repo\", \"all_of\": [\"impl Drop\", \"unsafe\"], \"none_of\": [\"#[cfg(test)]\"], \"fixed_string\": true}}`. Either-or: `{\"name\": \"grep_repository_boolean\", \"arguments\": {\"repository_location\": \"/path/to/repo\", \"any_of\": [\"TODO\", \"FIXME\"], \"include_globs\": [\"**/*.rs\"]}}`"
     )]
-    async fn list_repository_refs(
+    #[allow(clippy::too_many_arguments)]
+    async fn grep_repository_boolean(
         &self,
         #[tool(param)]
         #[schemars(
-            description = "Repository URL or local path (required). Supports GitHub formats: 'git@github.com:user/repo.git' (SSH, recommended), 'https://github.com/user/repo', 'github:user/repo', or absolute local paths. Private repos require GITCODES_MCP_GITHUB_TOKEN environment variable. This parameter is required and must be provided."
+            description = "Repository URL or local path (required). Supports GitHub formats: 'git@github.com:user/repo.git' (SSH, recommended), 'https://github.com/user/repo', 'github:user/repo', absolute local paths, or git bundle files as 'bundle:/path/to/repo.bundle'. Private repos require GITCODES_MCP_GITHUB_TOKEN environment variable. This parameter is required and must be provided."
         )]
         repository_location: String,
-    ) -> Result<CallToolResult, McpError> {
-        // Use the repository manager directly to handle repository refs listing
-        match self
-            .manager
-            .list_repository_refs(&repository_location)
-            .await
-        {
-            Ok((repo_refs, local_repo)) => {
-                // Note: We don't clean up the repository here to use it as a cache
-                // This improves performance for subsequent operations
-                if local_repo.is_some() {
-                    tracing::debug!("Repository kept for caching");
-                }
-
-                // Convert the structured repository refs to our response format
-                let response = responses::RepositoryRefsResponse {
-                    branches: repo_refs
-                        .branches
-                        .into_iter()
-                        .map(|ref_info| responses::ReferenceInfo {
-                            name: ref_info.name,
-                            full_ref: ref_info.full_ref,
-                            commit_id: ref_info.commit_id,
-                        })
-                        .collect(),
-                    tags: repo_refs
-                        .tags
-                        .into_iter()
-                        .map(|ref_info| responses::ReferenceInfo {
-                            name: ref_info.name,
-                            full_ref: ref_info.full_ref,
-                            commit_id: ref_info.commit_id,
-                        })
-                        .collect(),
-                };
-
-                // Serialize the response to JSON
-                match serde_json::to_string(&response) {
-                    Ok(json) => success_result(json),
-                    Err(e) => error_result(format!("Failed to serialize repository refs: {}", e)),
-                }
-            }
-            Err(err) => error_result(format!("Failed to list repository refs: {}", err)),
-        }
-    }
 
-    /// Show contents of a file in a GitHub repository in compact format
-    ///
-    /// This tool clones or updates the repository locally, then retrieves the contents of the specified file.
-    /// It supports both public and private repositories and returns content in a compact format.
-    ///
-    /// # Response Format
-    ///
-    /// Returns a compact JSON structure with concatenated line contents:
-    /// ```json
-    /// {
-    ///   "type": "text|binary|image",
-    ///   "line_contents": "1:line content\n2:another line",
-    ///   "metadata": {
-    ///     "file_path": "path/to/file.ext",
-    ///     "line_count": 100,
-    ///     "size": 1234
-    ///   }
-    /// }
-    /// ```
-    ///
-    /// # Authentication
-    ///
-    /// - For public repositories: No authentication needed
-    /// - For private repositories: Requires `GITCODES_MCP_GITHUB_TOKEN` with `repo` scope
-    ///
-    /// # Implementation Note
-    ///
-    /// This tool:
-    /// 1. Repository is cloned or updated locally
-    /// 2. File contents are retrieved and processed
-    /// 3. Results are converted to compact format with concatenated lines and enhanced metadata
-    /// 4. Response includes full file path and size information for better usability
-    #[tool(
-        description = "View file contents from repositories or local directories in compact format. Returns concatenated line contents with line numbers and enhanced metadata including file path. Supports line ranges and branch selection. Example: `{\"name\": \"show_file_contents\", \"arguments\": {\"repository_location\": \"git@github.com:user/repo.git\", \"file_path\": \"README.md\"}}`. With range: `{\"name\": \"show_file_contents\", \"arguments\": {\"repository_location\": \"github:user/repo\", \"file_path\": \"src/lib.rs\", \"line_from\": 10, \"line_to\": 12}}`. Returns format: `{\"type\": \"text\", \"line_contents\": \"10:content at line 10\\n11:content at line 11\\n12:content at line 12\", \"metadata\": {\"file_path\": \"src/lib.rs\", \"line_count\": 3, \"size\": 1234}}`"
-    )]
-    #[allow(clippy::too_many_arguments)]
-    async fn show_file_contents(
-        &self,
         #[tool(param)]
         #[schemars(
-            description = "Repository URL or local path (required). Supports GitHub formats: 'git@github.com:user/repo.git' (SSH, recommended), 'https://github.com/user/repo', 'github:user/repo', or absolute local paths. Private repos require GITCODES_MCP_GITHUB_TOKEN environment variable. This parameter is required and must be provided."
+            description = "Branch, commit, or tag (optional, default 'main'/'master'). When given, that commit's tree is searched straight from the git object database, like grep_repository does."
         )]
-        repository_location: String,
+        ref_name: Option<String>,
 
         #[tool(param)]
         #[schemars(
-            description = "Branch, commit, or tag (optional, default 'main'/'master'). Can be branch name (e.g. 'develop'), commit hash (full or short), or tag name (e.g. 'v1.0.0'). Falls back to repository's default branch if specified ref doesn't exist."
+            description = "Patterns that must all match somewhere in a file (AND). At least one pattern in 'all_of' or 'any_of' is required."
         )]
-        ref_name: Option<String>,
+        all_of: Option<Vec<String>>,
 
         #[tool(param)]
         #[schemars(
-            description = "File path relative to repository root (required). Paths with '..' are rejected for security. This parameter is required and must be provided to specify which file to view."
+            description = "Patterns of which at least one must match somewhere in a file (OR)."
         )]
-        file_path: String,
+        any_of: Option<Vec<String>>,
 
         #[tool(param)]
         #[schemars(
-            description = "Maximum file size in bytes (optional). Must be positive integer. Prevents loading extremely large files that could cause memory issues. Example: 1048576 for 1MB limit. When omitted, uses reasonable default limit."
+            description = "Patterns that must not match anywhere in a file (NOT). Their matches are not returned."
         )]
-        max_size: Option<usize>,
+        none_of: Option<Vec<String>>,
 
         #[tool(param)]
         #[schemars(
-            description = "Start line number (optional, 1-indexed). Must be positive integer. Use with line_to to view specific file sections. Example: line_from=10 starts from line 10. When omitted, starts from beginning of file."
+            description = "Case-sensitive matching for all patterns (optional, default false)."
         )]
-        line_from: Option<usize>,
+        case_sensitive: Option<bool>,
 
         #[tool(param)]
         #[schemars(
-            description = "End line number (optional, 1-indexed, inclusive). Must be positive integer and >= line_from. Use with line_from to view specific file sections. Example: line_to=20 ends at line 20. When omitted, reads to end of file."
+            description = "Treat all patterns as literal strings instead of regexes (optional, default false), like 'grep -F'."
         )]
-        line_to: Option<usize>,
+        fixed_string: Option<bool>,
 
         #[tool(param)]
         #[schemars(
-            description = "Show content without line numbers (optional, default false). When true, returns plain file content. When false or omitted, includes line numbers for easier reference. Useful for copying code snippets."
+            description = "Match whole words only for all patterns (optional, default false), like 'grep -w'."
         )]
-        without_line_numbers: Option<bool>,
-    ) -> Result<CallToolResult, McpError> {
-        // Process file viewing within the repository
-        // Handle repository cleanup in both success and error cases
-
-        let show_params = services::ShowFileParams {
-            repository_location_str: repository_location.clone(),
-            file_path: file_path.clone(),
-            ref_name: ref_name.clone(),
-            max_size,
-            line_from,
-            line_to,
-            without_line_numbers,
-        };
-
-        match services::show_file_contents(&self.manager, show_params).await {
-            Ok((file_contents, _local_repo, _without_line_numbers)) => {
-                // Note: We don't clean up the repository here to use it as a cache
-                // This improves performance for subsequent operations
-                tracing::debug!("Repository kept for caching");
-
-                // Convert to compact format with full file path
-                let compact_response = responses::CompactFileContentsResponse::from_file_contents(
-                    file_contents,
-                    file_path.clone(),
-                );
-
-                // Serialize the compact response to JSON and return it
-                match serde_json::to_string(&compact_response) {
-                    Ok(json) => success_result(json),
-                    Err(e) => error_result(format!("Failed to serialize file contents: {}", e)),
-                }
-            }
-            Err(err) => {
-                // File viewing failed, try to clean up repository if it was created
-                tracing::error!("File viewing failed: {}", err);
-
-                // Note: We don't clean up the repository here even on error
-                // to preserve it for potential future operations
-                tracing::debug!("Repository kept for caching even after error");
-
-                // Return the original error as an error result
-                error_result(format!("File viewing failed: {}", err))
-            }
-        }
-    }
+        word_regexp: Option<bool>,
 
-    /// Get the directory tree structure of a repository
-    ///
-    /// This method returns a hierarchical representation of all files and directories
-    /// in the repository, showing the repository's structure in tree format.
-    ///
-    /// # Authentication
-    ///
-    /// - Uses the `GITCODES_MCP_GITHUB_TOKEN` if available for authentication
-    /// - Without a token, limited to 60 requests/hour for GitHub repositories
-    /// - With a token, allows 5,000 requests/hour for GitHub repositories
-    /// - Local repositories don't require authentication
-    ///
-    /// # Rate Limiting
-    ///
-    /// GitHub API has rate limits that vary based on authentication:
-    /// - Unauthenticated: 60 requests/hour
-    /// - Authenticated: 5,000 requests/hour
-    #[tool(
-        description = "Get repository directory tree in hierarchical format. Supports depth limits, .gitignore filtering, and relative paths. Example: `{\"name\": \"get_repository_tree\", \"arguments\": {\"repository_location\": \"github:user/repo\"}}`. With options: `{\"name\": \"get_repository_tree\", \"arguments\": {\"repository_location\": \"git@github.com:user/repo.git\", \"depth\": 2, \"search_relative_path\": \"src\"}}`"
-    )]
-    #[allow(clippy::too_many_arguments)]
-    async fn get_repository_tree(
-        &self,
         #[tool(param)]
         #[schemars(
-            description = "Repository URL or local path (required). Supports GitHub formats: 'git@github.com:user/repo.git' (SSH, recommended), 'https://github.com/user/repo', 'github:user/repo', or absolute local paths. Private repos require GITCODES_MCP_GITHUB_TOKEN environment variable. This parameter is required and must be provided."
+            description = "Allow matches spanning multiple lines for all patterns (optional, default false), like 'rg --multiline'."
         )]
-        repository_location: String,
+        multiline: Option<bool>,
 
         #[tool(param)]
         #[schemars(
-            description = "Branch, commit, or tag (optional, default 'main'/'master'). Can be branch name (e.g. 'develop'), commit hash (full or short), or tag name (e.g. 'v1.0.0'). Falls back to repository's default branch if specified ref doesn't exist."
+            description = "Glob patterns of the files to search (optional), relative to the repository root. Example: [\"**/*.rs\"]."
         )]
-        ref_name: Option<String>,
+        include_globs: Option<Vec<String>>,
 
         #[tool(param)]
         #[schemars(
-            description = "Case-sensitive path matching (optional, default false). When true, file and directory name matching distinguishes between uppercase and lowercase. When false or omitted, uses case-insensitive path matching."
+            description = "Directories to exclude (optional). Directory names like [\"target\"] or glob patterns like [\"**/tests/**\"]."
         )]
-        case_sensitive: Option<bool>,
+        exclude_dirs: Option<Vec<String>>,
 
         #[tool(param)]
         #[schemars(
-            description = "Respect .gitignore files (optional, default true). When true or omitted, excludes files and directories listed in .gitignore. When false, includes all files regardless of .gitignore rules. Useful for seeing complete repository structure."
+            description = "Lines of context before each match of a positive pattern (optional, default 0)."
         )]
-        respect_gitignore: Option<bool>,
+        before_context: Option<usize>,
 
         #[tool(param)]
         #[schemars(
-            description = "Maximum traversal depth (optional, default unlimited). Must be positive integer. Depth 1 shows only top-level items, depth 2 includes one level of subdirectories, etc. When omitted, traverses entire directory tree. Useful for limiting large directory structures."
+            description = "Lines of context after each match of a positive pattern (optional, default 0)."
         )]
-        depth: Option<usize>,
+        after_context: Option<usize>,
+
+        #[tool(param)]
+        #[schemars(description = "Number of result lines to skip for pagination (optional).")]
+        skip: Option<usize>,
 
         #[tool(param)]
         #[schemars(
-            description = "Strip repository path prefix (optional, default true). When true or omitted, shows relative paths from repository root. When false, shows full absolute filesystem paths. Relative paths are usually more readable and portable."
+            description = "Maximum number of result lines to return (optional, default 50)."
         )]
-        strip_path_prefix: Option<bool>,
+        take: Option<usize>,
 
         #[tool(param)]
         #[schemars(
-            description = "Relative path to start tree generation from (optional). Path relative to repository root where tree traversal begins. Examples: 'src' (start from src directory), 'docs/api' (start from docs/api subdirectory). When omitted, starts from repository root. Useful for focusing on specific parts of large repositories."
+            description = "Maximum number of characters to show from matched content (optional, default 150)."
         )]
-        search_relative_path: Option<String>,
+        match_content_omit_num: Option<usize>,
     ) -> Result<CallToolResult, McpError> {
-        // Process tree retrieval within the repository
-        // Handle repository cleanup in both success and error cases
-
+        let grep_params = services::BooleanGrepParams {
+            repository_location_str: repository_location,
+            ref_name,
+            all_of: all_of.unwrap_or_default(),
+            any_of: any_of.unwrap_or_default(),
+            none_of: none_of.unwrap_or_default(),
+            case_sensitive: case_sensitive.unwrap_or(false),
+            fixed_string: fixed_string.unwrap_or(false),
+            word_regexp: word_regexp.unwrap_or(false),
+            multiline: multiline.unwrap_or(false),
+            include_globs,
+            exclude_dirs,
+            before_context,
+            after_context,
+            skip,
+            take,
+            match_content_omit_num,
+        };
+
+        match services::perform_boolean_grep_in_repository(&self.manager, grep_params).await {
+            Ok((result, _local_repo)) => {
+                let compact_result =
+                    responses::CompactCodeSearchResponse::from_search_result(result);
+                match serde_json::to_string(&compact_result) {
+                    Ok(json) => success_result(json),
+                    Err(e) => error_result(format!("Failed to serialize search results: {}", e)),
+                }
+            }
+            Err(err) => {
+                tracing::error!("Boolean code search failed: {}", err);
+                error_result(format!("Boolean code search failed: {}", err))
+            }
+        }
+    }
+
+    /// Search code in several repositories with one call
+    ///
+    /// Runs the same search as `grep_repository` in every given repository, preparing
+    /// and searching a bounded number of them concurrently. The results are grouped by
+    /// repository in the given order; a repository that can't be cloned or searched gets
+    /// an error entry instead of failing the whole call.
+    #[tool(
+        description = "Search code in several repositories at once with the same regex pattern and options as grep_repository, e.g. to compare how a set of libraries implement something. Repositories are cloned and searched concurrently (at most 'max_concurrency' at a time). Results are grouped by repository in the given order, each in the compact grep_repository format; a repository that fails gets an 'error' entry instead of a 'result', while the others are still returned. Pagination ('skip'/'take') applies per repository. A set searched often can be saved with save_repository_collection and passed as 'collection'. Example: `{\"name\": \"grep_repositories\", \"arguments\": {\"repository_locations\": [\"github:tokio-rs/tokio\", \"github:smol-rs/smol\", \"github:async-rs/async-std\"], \"pattern\": \"fn block_on\", \"include_globs\": [\"**/*.rs\"]}}`. With a saved collection: `{\"name\": \"grep_repositories\", \"arguments\": {\"collection\": \"async-runtimes\", \"pattern\": \"fn block_on\"}}`"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    async fn grep_repositories(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Repository URLs or local paths to search (required unless 'collection' is given). Each accepts the same formats as 'repository_location' of grep_repository: 'git@github.com:user/repo.git', 'https://github.com/user/repo', 'github:user/repo', absolute local paths, or 'bundle:/path/to/repo.bundle'. Locations of the same repository, like 'github:user/repo' and 'https://github.com/user/repo', are searched once."
+        )]
+        repository_locations: Option<Vec<String>>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Name of a collection saved with save_repository_collection (optional). Its repositories are searched after 'repository_locations'."
+        )]
+        collection: Option<String>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Branch, commit, or tag to search in every repository (optional, default is each repository's default branch). Repositories that don't have the ref report an error."
+        )]
+        ref_name: Option<String>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Regular expression pattern to search for (required). Set 'fixed_string' to search for literal text."
+        )]
+        pattern: String,
+
+        #[tool(param)]
+        #[schemars(description = "Case-sensitive matching (optional, default false).")]
+        case_sensitive: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Treat the pattern as a literal string (optional, default false), like 'grep -F'."
+        )]
+        fixed_string: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Match whole words only (optional, default false), like 'grep -w'."
+        )]
+        word_regexp: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Allow matches spanning multiple lines (optional, default false), like 'rg --multiline'."
+        )]
+        multiline: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Glob patterns of the files to search in every repository (optional), relative to the repository root. Example: [\"**/*.rs\"]."
+        )]
+        include_globs: Option<Vec<String>>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Directories to exclude in every repository (optional). Directory names like [\"target\"] or glob patterns like [\"**/tests/**\"]."
+        )]
+        exclude_dirs: Option<Vec<String>>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Narrow the search with the trigram index of each repository (optional, default false), as 'use_index' of grep_repository."
+        )]
+        use_index: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(description = "Lines of context before each match (optional, default 0).")]
+        before_context: Option<usize>,
+
+        #[tool(param)]
+        #[schemars(description = "Lines of context after each match (optional, default 0).")]
+        after_context: Option<usize>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "How the context around matches is chosen (optional, default 'lines'), as 'context_mode' of grep_repository. 'enclosing_item' adds the function, method, impl or class containing each match."
+        )]
+        context_mode: Option<ContextMode>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum lines shown per enclosing item with context_mode 'enclosing_item' (optional, default 50)."
+        )]
+        max_context_lines: Option<usize>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Number of result lines to skip in each repository for pagination (optional)."
+        )]
+        skip: Option<usize>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum number of result lines to return per repository (optional, default 50)."
+        )]
+        take: Option<usize>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum number of characters to show from matched content (optional, default 150)."
+        )]
+        match_content_omit_num: Option<usize>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum number of repositories cloned and searched at the same time (optional, default 4)."
+        )]
+        max_concurrency: Option<usize>,
+    ) -> Result<CallToolResult, McpError> {
+        let grep_params = services::MultiRepositoryGrepParams {
+            repository_location_strs: repository_locations.unwrap_or_default(),
+            collection,
+            max_concurrency,
+            pattern: pattern.clone(),
+            ref_name,
+            case_sensitive: case_sensitive.unwrap_or(false),
+            file_extensions: None,
+            include_globs,
+            exclude_dirs,
+            changed_files_only: false,
+            use_index: use_index.unwrap_or(false),
+            fixed_string: fixed_string.unwrap_or(false),
+            word_regexp: word_regexp.unwrap_or(false),
+            multiline: multiline.unwrap_or(false),
+            before_context,
+            after_context,
+            context_mode,
+            max_context_lines,
+            skip,
+            take,
+            match_content_omit_num,
+        };
+
+        match services::perform_grep_in_repositories(&self.manager, grep_params).await {
+            Ok(outcomes) => {
+                // Note: The repositories are kept as a cache, like for grep_repository
+                for outcome in &outcomes {
+                    if let Err(err) = &outcome.result {
+                        tracing::warn!(
+                            "Code search in {} failed: {}",
+                            outcome.repository_location_str,
+                            err
+                        );
+                    }
+                }
+                let response =
+                    responses::MultiRepositoryCodeSearchResponse::from_outcomes(pattern, outcomes);
+                match serde_json::to_string(&response) {
+                    Ok(json) => success_result(json),
+                    Err(e) => error_result(format!("Failed to serialize search results: {}", e)),
+                }
+            }
+            Err(err) => {
+                tracing::error!("Multi-repository code search failed: {}", err);
+                error_result(format!("Multi-repository code search failed: {}", err))
+            }
+        }
+    }
+
+    /// Save a named collection of repositories for grep_repositories
+    ///
+    /// The collection is stored in the repository cache directory, replacing a collection
+    /// of the same name.
+    #[tool(
+        description = "Save a named list of repositories, to search them all with grep_repositories by passing the name as 'collection'. Saving under an existing name replaces that collection. Collections are kept in the repository cache directory. Example: `{\"name\": \"save_repository_collection\", \"arguments\": {\"name\": \"async-runtimes\", \"repository_locations\": [\"github:tokio-rs/tokio\", \"github:smol-rs/smol\"]}}`"
+    )]
+    async fn save_repository_collection(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "Name of the collection (required), e.g. 'async-runtimes'.")]
+        name: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Repository URLs or local paths in the collection (required, at least one), in the formats grep_repositories accepts."
+        )]
+        repository_locations: Vec<String>,
+    ) -> Result<CallToolResult, McpError> {
+        match self
+            .manager
+            .save_repository_collection(&name, repository_locations.clone())
+        {
+            Ok(()) => {
+                let response = responses::RepositoryCollectionResponse {
+                    name: name.trim().to_string(),
+                    repository_locations,
+                };
+                match serde_json::to_string(&response) {
+                    Ok(json) => success_result(json),
+                    Err(e) => error_result(format!("Failed to serialize collection: {}", e)),
+                }
+            }
+            Err(err) => {
+                tracing::error!("Saving repository collection failed: {}", err);
+                error_result(format!("Saving repository collection failed: {}", err))
+            }
+        }
+    }
+
+    /// List branches and tags for a GitHub repository
+    ///
+    /// This tool retrieves a list of all branches and tags for the specified repository.
+    /// It supports both public and private repositories.
+    ///
+    /// # Authentication
+    ///
+    /// - For public repositories: No authentication needed
+    /// - For private repositories: Requires `GITCODES_MCP_GITHUB_TOKEN` with `repo` scope
+    ///
+    /// # Implementation Note
+    ///
+    /// This tool:
+    /// 1. Clones or updates the repository locally
+    /// 2. Fetches all branches and tags
+    /// 3. Sorts tags by semantic version, newest first, and optionally drops prereleases
+    /// 4. Formats the results into a readable format
+    #[tool(
+        description = "List all branches and tags for a repository. Clones locally to retrieve references. Tags are sorted by semantic version, newest first (prefixes like 'v' and 'crate-name-v' are understood; tags without a version come last), and the response names the tags the 'latest' and 'latest-stable' ref aliases resolve to. Also reports the default branch, the commit date of each ref, and for annotated tags (object type 'tag') the tagger, tag date and message. GitHub repositories are listed through the GitHub API; without a GitHub token it reports no commit dates and no tagger, tag date or message, only which tags are annotated. Example: `{\"name\": \"list_repository_refs\", \"arguments\": {\"repository_location\": \"git@github.com:user/repo.git\"}}`. Stable releases only: `{\"name\": \"list_repository_refs\", \"arguments\": {\"repository_location\": \"github:user/repo\", \"include_prereleases\": false}}`"
+    )]
+    async fn list_repository_refs(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Repository URL or local path (required). Supports GitHub formats: 'git@github.com:user/repo.git' (SSH, recommended), 'https://github.com/user/repo', 'github:user/repo', absolute local paths, or git bundle files as 'bundle:/path/to/repo.bundle'. Private repos require GITCODES_MCP_GITHUB_TOKEN environment variable. This parameter is required and must be provided."
+        )]
+        repository_location: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Include prerelease tags (optional, default true). When false, tags whose semantic version has a prerelease part (e.g. 'v2.0.0-rc.1', 'v1.0.0-beta') are omitted. Tags without a version are always listed."
+        )]
+        include_prereleases: Option<bool>,
+    ) -> Result<CallToolResult, McpError> {
+        // Use the repository manager directly to handle repository refs listing
+        match self
+            .manager
+            .list_repository_refs(&repository_location)
+            .await
+        {
+            Ok((mut repo_refs, local_repo)) => {
+                // Note: We don't clean up the repository here to use it as a cache
+                // This improves performance for subsequent operations
+                if local_repo.is_some() {
+                    tracing::debug!("Repository kept for caching");
+                }
+
+                let tag_names = || repo_refs.tags.iter().map(|tag| tag.name.as_str());
+                let latest_tag = LatestAlias::Latest.select(tag_names()).map(String::from);
+                let latest_stable_tag = LatestAlias::LatestStable
+                    .select(tag_names())
+                    .map(String::from);
+
+                if !include_prereleases.unwrap_or(true) {
+                    version_tags::retain_stable_tags(&mut repo_refs.tags);
+                }
+
+                // Convert the structured repository refs to our response format
+                let response = responses::RepositoryRefsResponse {
+                    branches: repo_refs
+                        .branches
+                        .into_iter()
+                        .map(|ref_info| responses::ReferenceInfo {
+                            name: ref_info.name,
+                            full_ref: ref_info.full_ref,
+                            commit_id: ref_info.commit_id,
+                            commit_date: ref_info.commit_date,
+                            object: ref_info.object,
+                            tag: ref_info.tag,
+                            version: None,
+                        })
+                        .collect(),
+                    default_branch: repo_refs.default_branch,
+                    tags: repo_refs
+                        .tags
+                        .into_iter()
+                        .map(|ref_info| responses::ReferenceInfo {
+                            version: version_tags::parse_tag_version(&ref_info.name)
+                                .map(|tag_version| tag_version.version.to_string()),
+                            name: ref_info.name,
+                            full_ref: ref_info.full_ref,
+                            commit_id: ref_info.commit_id,
+                            commit_date: ref_info.commit_date,
+                            object: ref_info.object,
+                            tag: ref_info.tag,
+                        })
+                        .collect(),
+                    latest_tag,
+                    latest_stable_tag,
+                };
+
+                // Serialize the response to JSON
+                match serde_json::to_string(&response) {
+                    Ok(json) => success_result(json),
+                    Err(e) => error_result(format!("Failed to serialize repository refs: {}", e)),
+                }
+            }
+            Err(err) => error_result(format!("Failed to list repository refs: {}", err)),
+        }
+    }
+
+    /// Show contents of a file in a GitHub repository in compact format
+    ///
+    /// This tool clones or updates the repository locally, then retrieves the contents of the specified file.
+    /// It supports both public and private repositories and returns content in a compact format.
+    ///
+    /// # Response Format
+    ///
+    /// Returns a compact JSON structure with concatenated line contents:
+    /// ```json
+    /// {
+    ///   "type": "text|binary|image",
+    ///   "line_contents": "1:line content\n2:another line",
+    ///   "metadata": {
+    ///     "file_path": "path/to/file.ext",
+    ///     "line_count": 100,
+    ///     "size": 1234
+    ///   }
+    /// }
+    /// ```
+    ///
+    /// # Authentication
+    ///
+    /// - For public repositories: No authentication needed
+    /// - For private repositories: Requires `GITCODES_MCP_GITHUB_TOKEN` with `repo` scope
+    ///
+    /// # Implementation Note
+    ///
+    /// This tool:
+    /// 1. Repository is cloned or updated locally
+    /// 2. File contents are retrieved and processed
+    /// 3. Results are converted to compact format with concatenated lines and enhanced metadata
+    /// 4. Response includes full file path and size information for better usability
+    #[tool(
+        description = "View file contents from repositories or local directories in compact format. Returns concatenated line contents with line numbers and enhanced metadata including file path. Supports line ranges and branch selection. Example: `{\"name\": \"show_file_contents\", \"arguments\": {\"repository_location\": \"git@github.com:user/repo.git\", \"file_path\": \"README.md\"}}`. With range: `{\"name\": \"show_file_contents\", \"arguments\": {\"repository_location\": \"github:user/repo\", \"file_path\": \"src/lib.rs\", \"line_from\": 10, \"line_to\": 12}}`. Returns format: `{\"type\": \"text\", \"line_contents\": \"10:content at line 10\\n11:content at line 11\\n12:content at line 12\", \"metadata\": {\"file_path\": \"src/lib.rs\", \"line_count\": 3, \"size\": 1234}}`"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    async fn show_file_contents(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Repository URL or local path (required). Supports GitHub formats: 'git@github.com:user/repo.git' (SSH, recommended), 'https://github.com/user/repo', 'github:user/repo', absolute local paths, or git bundle files as 'bundle:/path/to/repo.bundle'. Private repos require GITCODES_MCP_GITHUB_TOKEN environment variable. This parameter is required and must be provided."
+        )]
+        repository_location: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Branch, commit, or tag (optional, default 'main'/'master'). Can be branch name (e.g. 'develop'), commit hash (full or short), or tag name (e.g. 'v1.0.0'). The aliases 'latest' and 'latest-stable' resolve to the tag with the highest semantic version ('latest-stable' skips prereleases). When given, the file is read straight from the git object database at that ref without a checkout, which also works for local repositories. A ref that exists neither locally nor on the remote is an error; there is no fallback to the default branch."
+        )]
+        ref_name: Option<String>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "File path relative to repository root (required). Paths with '..' are rejected for security. This parameter is required and must be provided to specify which file to view."
+        )]
+        file_path: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum file size in bytes (optional). Must be positive integer. Prevents loading extremely large files that could cause memory issues. Example: 1048576 for 1MB limit. When omitted, uses reasonable default limit."
+        )]
+        max_size: Option<usize>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Start line number (optional, 1-indexed). Must be positive integer. Use with line_to to view specific file sections. Example: line_from=10 starts from line 10. When omitted, starts from beginning of file."
+        )]
+        line_from: Option<usize>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "End line number (optional, 1-indexed, inclusive). Must be positive integer and >= line_from. Use with line_from to view specific file sections. Example: line_to=20 ends at line 20. When omitted, reads to end of file."
+        )]
+        line_to: Option<usize>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Show content without line numbers (optional, default false). When true, returns plain file content. When false or omitted, includes line numbers for easier reference. Useful for copying code snippets."
+        )]
+        without_line_numbers: Option<bool>,
+    ) -> Result<CallToolResult, McpError> {
+        // Process file viewing within the repository
+        // Handle repository cleanup in both success and error cases
+
+        let show_params = services::ShowFileParams {
+            repository_location_str: repository_location.clone(),
+            file_path: file_path.clone(),
+            ref_name: ref_name.clone(),
+            max_size,
+            line_from,
+            line_to,
+            without_line_numbers,
+        };
+
+        match services::show_file_contents(&self.manager, show_params).await {
+            Ok((file_contents, _local_repo, _without_line_numbers)) => {
+                // Note: We don't clean up the repository here to use it as a cache
+                // This improves performance for subsequent operations
+                tracing::debug!("Repository kept for caching");
+
+                // Convert to compact format with full file path
+                let compact_response = responses::CompactFileContentsResponse::from_file_contents(
+                    file_contents,
+                    file_path.clone(),
+                );
+
+                // Serialize the compact response to JSON and return it
+                match serde_json::to_string(&compact_response) {
+                    Ok(json) => success_result(json),
+                    Err(e) => error_result(format!("Failed to serialize file contents: {}", e)),
+                }
+            }
+            Err(err) => {
+                // File viewing failed, try to clean up repository if it was created
+                tracing::error!("File viewing failed: {}", err);
+
+                // Note: We don't clean up the repository here even on error
+                // to preserve it for potential future operations
+                tracing::debug!("Repository kept for caching even after error");
+
+                // Return the original error as an error result
+                error_result(format!("File viewing failed: {}", err))
+            }
+        }
+    }
+
+    /// Get the directory tree structure of a repository
+    ///
+    /// This method returns a hierarchical representation of all files and directories
+    /// in the repository, showing the repository's structure in tree format.
+    ///
+    /// # Authentication
+    ///
+    /// - Uses the `GITCODES_MCP_GITHUB_TOKEN` if available for authentication
+    /// - Without a token, limited to 60 requests/hour for GitHub repositories
+    /// - With a token, allows 5,000 requests/hour for GitHub repositories
+    /// - Local repositories don't require authentication
+    ///
+    /// # Rate Limiting
+    ///
+    /// GitHub API has rate limits that vary based on authentication:
+    /// - Unauthenticated: 60 requests/hour
+    /// - Authenticated: 5,000 requests/hour
+    #[tool(
+        description = "Get repository directory tree in hierarchical format. Supports depth limits, .gitignore filtering, and relative paths. Example: `{\"name\": \"get_repository_tree\", \"arguments\": {\"repository_location\": \"github:user/repo\"}}`. With options: `{\"name\": \"get_repository_tree\", \"arguments\": {\"repository_location\": \"git@github.com:user/repo.git\", \"depth\": 2, \"search_relative_path\": \"src\"}}`"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    async fn get_repository_tree(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Repository URL or local path (required). Supports GitHub formats: 'git@github.com:user/repo.git' (SSH, recommended), 'https://github.com/user/repo', 'github:user/repo', absolute local paths, or git bundle files as 'bundle:/path/to/repo.bundle'. Private repos require GITCODES_MCP_GITHUB_TOKEN environment variable. This parameter is required and must be provided."
+        )]
+        repository_location: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Branch, commit, or tag (optional, default 'main'/'master'). Can be branch name (e.g. 'develop'), commit hash (full or short), or tag name (e.g. 'v1.0.0'). The aliases 'latest' and 'latest-stable' resolve to the tag with the highest semantic version ('latest-stable' skips prereleases). When given, the tree is read straight from the git object database at that ref without a checkout, which also works for local repositories. A ref that exists neither locally nor on the remote is an error; there is no fallback to the default branch."
+        )]
+        ref_name: Option<String>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Case-sensitive path matching (optional, default false). When true, file and directory name matching distinguishes between uppercase and lowercase. When false or omitted, uses case-insensitive path matching."
+        )]
+        case_sensitive: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Respect .gitignore files (optional, default true). When true or omitted, excludes files and directories listed in .gitignore. When false, includes all files regardless of .gitignore rules. Useful for seeing complete repository structure."
+        )]
+        respect_gitignore: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum traversal depth (optional, default unlimited). Must be positive integer. Depth 1 shows only top-level items, depth 2 includes one level of subdirectories, etc. When omitted, traverses entire directory tree. Useful for limiting large directory structures."
+        )]
+        depth: Option<usize>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Strip repository path prefix (optional, default true). When true or omitted, shows relative paths from repository root. When false, shows full absolute filesystem paths. Relative paths are usually more readable and portable."
+        )]
+        strip_path_prefix: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Relative path to start tree generation from (optional). Path relative to repository root where tree traversal begins. Examples: 'src' (start from src directory), 'docs/api' (start from docs/api subdirectory). When omitted, starts from repository root. Useful for focusing on specific parts of large repositories."
+        )]
+        search_relative_path: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        // Process tree retrieval within the repository
+        // Handle repository cleanup in both success and error cases
+
         let tree_params = services::TreeServiceParams {
             repository_location_str: repository_location.clone(),
             ref_name: ref_name.clone(),
             case_sensitive,
             respect_gitignore,
-            depth,
-            strip_path_prefix,
-            search_relative_path: search_relative_path.map(std::path::PathBuf::from),
+            depth,
+            strip_path_prefix,
+            search_relative_path: search_relative_path.map(std::path::PathBuf::from),
+        };
+
+        match services::get_repository_tree(&self.manager, tree_params).await {
+            Ok((tree, _local_repo)) => {
+                // Note: We don't clean up the repository here to use it as a cache
+                // This improves performance for subsequent operations
+                tracing::debug!("Repository kept for caching");
+
+                // Serialize the tree to JSON and return it
+                match serde_json::to_string(&tree) {
+                    Ok(json) => success_result(json),
+                    Err(e) => error_result(format!("Failed to serialize repository tree: {}", e)),
+                }
+            }
+            Err(err) => {
+                // Tree retrieval failed, try to clean up repository if it was created
+                tracing::error!("Tree retrieval failed: {}", err);
+
+                // Note: We don't clean up the repository here even on error
+                // to preserve it for potential future operations
+                tracing::debug!("Repository kept for caching even after error");
+
+                // Return the original error as an error result
+                error_result(format!("Tree retrieval failed: {}", err))
+            }
+        }
+    }
+
+    /// Find the commits that added or removed a string (pickaxe search)
+    ///
+    /// This method walks the commit history of a repository and returns the commits
+    /// whose changes match the pattern, together with the matching diff hunks.
+    /// It works like `git log -S` (occurrence count changed) or `git log -G`
+    /// (an added or removed line matches).
+    ///
+    /// Remote repositories are cloned shallowly, so the full history is fetched
+    /// the first time this tool is used on them.
+    #[tool(
+        description = "Find commits that added or removed a string, like `git log -S`/`git log -G`. Returns the matching commits (newest first) with author, date, summary and the matching diff hunks per file. Example: `{\"name\": \"search_history\", \"arguments\": {\"repository_location\": \"github:user/repo\", \"pattern\": \"fn parse_config\"}}`. With regex, paths and range: `{\"name\": \"search_history\", \"arguments\": {\"repository_location\": \"/path/to/repo\", \"pattern\": \"timeout_ms\\\\s*=\", \"mode\": \"diff_match\", \"paths\": [\"src\"], \"from_ref\": \"v1.0.0\", \"to_ref\": \"main\"}}`"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    async fn search_history(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Repository URL or local path (required). Supports GitHub formats: 'git@github.com:user/repo.git' (SSH, recommended), 'https://github.com/user/repo', 'github:user/repo', absolute local paths, or git bundle files as 'bundle:/path/to/repo.bundle'. Private repos require GITCODES_MCP_GITHUB_TOKEN environment variable. This parameter is required and must be provided."
+        )]
+        repository_location: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "String or regex to look for (required). In 'occurrences' mode it is a literal string unless is_regex is true. In 'diff_match' mode it is always a regex. Example: 'fn parse_config' finds the commits that added or removed that function."
+        )]
+        pattern: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Pickaxe mode (optional, default 'occurrences'). 'occurrences' matches commits where the number of occurrences of the pattern in a file changed, like `git log -S`; commits that only move the string around are not reported. 'diff_match' matches commits with an added or removed line matching the regex, like `git log -G`."
+        )]
+        mode: Option<PickaxeMode>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Treat the pattern as a regex in 'occurrences' mode (optional, default false), like `git log -S --pickaxe-regex`. Ignored in 'diff_match' mode, where the pattern is always a regex."
+        )]
+        is_regex: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Case-sensitive matching (optional, default true, like git). When false, 'Foo' also matches 'foo' and 'FOO'."
+        )]
+        case_sensitive: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Limit the search to these paths (optional). Each entry is a file path, a directory prefix, or a glob pattern relative to the repository root. Examples: ['src'], ['src/**/*.rs', 'Cargo.toml']. When omitted, all files are searched."
+        )]
+        paths: Option<Vec<String>>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Exclusive start of the commit range (optional). Branch, tag, or commit; commits reachable from it are not searched, like the 'from' in `git log from..to`. Example: 'v1.0.0' to search changes made since that release. When omitted, the whole history up to to_ref is searched."
+        )]
+        from_ref: Option<String>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Inclusive end of the commit range (optional, default 'HEAD'). Branch, tag, or commit to start walking the history from."
+        )]
+        to_ref: Option<String>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum number of matching commits to return (optional, default 20). Must be positive integer. The response has 'truncated': true when more commits may match."
+        )]
+        max_commits: Option<usize>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Number of unchanged context lines around each change in the returned diff hunks (optional, default 3, like `git diff`)."
+        )]
+        context_lines: Option<u32>,
+    ) -> Result<CallToolResult, McpError> {
+        let history_params = services::HistorySearchServiceParams {
+            repository_location_str: repository_location,
+            pattern,
+            mode,
+            is_regex,
+            case_sensitive,
+            paths,
+            from_ref,
+            to_ref,
+            max_commits,
+            context_lines,
+        };
+
+        match services::search_history(&self.manager, history_params).await {
+            Ok((result, _local_repo)) => {
+                // Note: We don't clean up the repository here to use it as a cache
+                tracing::debug!("Repository kept for caching");
+
+                match serde_json::to_string(&result) {
+                    Ok(json) => success_result(json),
+                    Err(e) => {
+                        error_result(format!("Failed to serialize history search results: {}", e))
+                    }
+                }
+            }
+            Err(err) => {
+                tracing::error!("History search failed: {}", err);
+                error_result(format!("History search failed: {}", err))
+            }
+        }
+    }
+
+    /// Generate a changelog of the commits between two refs
+    ///
+    /// This method lists the commits reachable from `to_ref` but not from `from_ref`,
+    /// grouped by conventional commit type, together with the issue and pull request
+    /// numbers referenced in their messages.
+    ///
+    /// # Authentication
+    ///
+    /// Looking up reference titles uses the GitHub search API and is subject to its
+    /// rate limits; `GITCODES_MCP_GITHUB_TOKEN` raises them. Listing commits doesn't
+    /// use the API.
+    #[tool(
+        description = "Generate a changelog of the commits between two refs (e.g. two release tags), like `git log from..to`. Commits are grouped by conventional commit type (feat, fix, ...; non-conventional commits go to 'other') and referenced issue/PR numbers (#123) are extracted. Optionally looks up their titles on GitHub. Example: `{\"name\": \"changelog_between\", \"arguments\": {\"repository_location\": \"github:user/repo\", \"from_ref\": \"v1.2.0\", \"to_ref\": \"v1.3.0\"}}`. With titles: `{\"name\": \"changelog_between\", \"arguments\": {\"repository_location\": \"github:user/repo\", \"from_ref\": \"v1.2.0\", \"to_ref\": \"v1.3.0\", \"enrich_references\": true}}`"
+    )]
+    async fn changelog_between(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Repository URL or local path (required). Supports GitHub formats: 'git@github.com:user/repo.git' (SSH, recommended), 'https://github.com/user/repo', 'github:user/repo', absolute local paths, or git bundle files as 'bundle:/path/to/repo.bundle'. Private repos require GITCODES_MCP_GITHUB_TOKEN environment variable. This parameter is required and must be provided."
+        )]
+        repository_location: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Exclusive start of the range (required). Branch, tag, or commit, typically the previous release tag (e.g. 'v1.2.0'). Commits reachable from it are not listed."
+        )]
+        from_ref: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Inclusive end of the range (required). Branch, tag, or commit, typically the new release tag (e.g. 'v1.3.0') or 'HEAD'."
+        )]
+        to_ref: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "List merge commits too (optional, default false). Useful for repositories merging pull requests with merge commits, whose messages ('Merge pull request #123 ...') carry the PR numbers."
+        )]
+        include_merges: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Look up titles, state and URLs of referenced issues and pull requests via the GitHub issue search (optional, default false). Only works for GitHub repositories (local clones are matched through their 'origin' remote). Lookup failures don't fail the request; they are reported in 'enrichment_error'."
+        )]
+        enrich_references: Option<bool>,
+    ) -> Result<CallToolResult, McpError> {
+        let changelog_params = services::ChangelogServiceParams {
+            repository_location_str: repository_location,
+            from_ref,
+            to_ref,
+            include_merges,
+            enrich_references,
+        };
+
+        match services::changelog_between(&self.manager, changelog_params).await {
+            Ok((changelog, _local_repo)) => {
+                // Note: We don't clean up the repository here to use it as a cache
+                tracing::debug!("Repository kept for caching");
+
+                match serde_json::to_string(&changelog) {
+                    Ok(json) => success_result(json),
+                    Err(e) => error_result(format!("Failed to serialize changelog: {}", e)),
+                }
+            }
+            Err(err) => {
+                tracing::error!("Changelog generation failed: {}", err);
+                error_result(format!("Changelog generation failed: {}", err))
+            }
+        }
+    }
+
+    /// Aggregate commit statistics per author
+    ///
+    /// This method walks a ref range and/or time window and reports, for every author,
+    /// the number of commits, the first and last commit dates and the lines added and
+    /// removed. It helps to judge how actively a repository is maintained.
+    #[tool(
+        description = "Aggregate commits per author over a ref range or time window, like `git shortlog -sne` with line counts. Returns commit counts, first/last commit dates and lines added/removed per contributor (most active first), plus the overall first/last commit dates - useful to gauge how actively a dependency is maintained. Identities are merged through the repository's .mailmap by default. Example: `{\"name\": \"contributors\", \"arguments\": {\"repository_location\": \"github:user/repo\", \"since\": \"1 year ago\"}}`. For a release: `{\"name\": \"contributors\", \"arguments\": {\"repository_location\": \"/path/to/repo\", \"from_ref\": \"v1.0.0\", \"to_ref\": \"v2.0.0\", \"include_line_stats\": false}}`"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    async fn contributors(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Repository URL or local path (required). Supports GitHub formats: 'git@github.com:user/repo.git' (SSH, recommended), 'https://github.com/user/repo', 'github:user/repo', absolute local paths, or git bundle files as 'bundle:/path/to/repo.bundle'. Private repos require GITCODES_MCP_GITHUB_TOKEN environment variable. This parameter is required and must be provided."
+        )]
+        repository_location: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Exclusive start of the commit range (optional). Branch, tag, or commit; commits reachable from it are not counted. Omit to count the whole history."
+        )]
+        from_ref: Option<String>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Inclusive end of the commit range (optional, default 'HEAD'). Branch, tag, or commit."
+        )]
+        to_ref: Option<String>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Only count commits authored at or after this date (optional). Accepts ISO 8601 ('2024-01-01', '2024-01-01T00:00:00Z') or relative dates ('6 months ago', '2 weeks ago')."
+        )]
+        since: Option<String>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Only count commits authored before this date (optional). Same formats as 'since'."
+        )]
+        until: Option<String>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Map names and emails through the repository's .mailmap (optional, default true), merging people who committed under several identities."
+        )]
+        use_mailmap: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Count lines added and removed per author (optional, default true). Requires diffing every commit; disable for faster results on large histories."
+        )]
+        include_line_stats: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Count merge commits too (optional, default false). Merge commits never contribute line counts."
+        )]
+        include_merges: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum number of contributors to return (optional, default 50). 'total_contributors' always reports the full count."
+        )]
+        max_contributors: Option<usize>,
+    ) -> Result<CallToolResult, McpError> {
+        let contributors_params = services::ContributorsServiceParams {
+            repository_location_str: repository_location,
+            from_ref,
+            to_ref,
+            since,
+            until,
+            use_mailmap,
+            include_line_stats,
+            include_merges,
+            max_contributors,
+        };
+
+        match services::contributors(&self.manager, contributors_params).await {
+            Ok((report, _local_repo)) => {
+                // Note: We don't clean up the repository here to use it as a cache
+                tracing::debug!("Repository kept for caching");
+
+                match serde_json::to_string(&report) {
+                    Ok(json) => success_result(json),
+                    Err(e) => {
+                        error_result(format!("Failed to serialize contributor statistics: {}", e))
+                    }
+                }
+            }
+            Err(err) => {
+                tracing::error!("Contributor statistics failed: {}", err);
+                error_result(format!("Contributor statistics failed: {}", err))
+            }
+        }
+    }
+
+    /// Rank files and directories by how often and how much they changed
+    ///
+    /// This method walks a ref range and/or time window and scores every file that still
+    /// exists by its number of commits or its churn, optionally weighted by its current
+    /// size. It points at the parts of an unfamiliar codebase where most work happens.
+    #[tool(
+        description = "Rank files and directories by change frequency (commits) or churn (lines added + removed) over a ref range or time window, optionally weighted by current file size (large files that keep changing score highest). Helps decide where to start reading an unfamiliar codebase. Only files existing at 'to_ref' are ranked; merge commits are skipped. Example: `{\"name\": \"hotspots\", \"arguments\": {\"repository_location\": \"github:user/repo\", \"since\": \"1 year ago\"}}`. Weighted churn in Rust files: `{\"name\": \"hotspots\", \"arguments\": {\"repository_location\": \"/path/to/repo\", \"metric\": \"Churn\", \"weight_by_size\": true, \"include_globs\": [\"**/*.rs\"], \"directory_depth\": 2}}`"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    async fn hotspots(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Repository URL or local path (required). Supports GitHub formats: 'git@github.com:user/repo.git' (SSH, recommended), 'https://github.com/user/repo', 'github:user/repo', absolute local paths, or git bundle files as 'bundle:/path/to/repo.bundle'. Private repos require GITCODES_MCP_GITHUB_TOKEN environment variable. This parameter is required and must be provided."
+        )]
+        repository_location: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Exclusive start of the commit range (optional). Branch, tag, or commit; commits reachable from it are not counted. Omit to analyze the whole history."
+        )]
+        from_ref: Option<String>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Inclusive end of the commit range (optional, default 'HEAD'). File sizes are taken from this ref."
+        )]
+        to_ref: Option<String>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Only count commits authored at or after this date (optional). Accepts ISO 8601 ('2024-01-01') or relative dates ('6 months ago')."
+        )]
+        since: Option<String>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Only count commits authored before this date (optional). Same formats as 'since'."
+        )]
+        until: Option<String>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Glob patterns of files to include (optional), as in grep. Examples: ['**/*.rs'], ['src/**']"
+        )]
+        include_globs: Option<Vec<String>>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Directories or glob patterns to exclude (optional), as in grep. Examples: ['vendor', 'tests'], ['**/*.lock']"
+        )]
+        exclude_dirs: Option<Vec<String>>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "What to rank by (optional, default 'Commits'): 'Commits' (number of commits changing the file) or 'Churn' (lines added plus removed)."
+        )]
+        metric: Option<HotspotMetric>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Multiply the metric by the file's current line count (optional, default false), favoring large files that change often."
+        )]
+        weight_by_size: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Number of leading path components directories are grouped by (optional, default 1). 1 ranks top-level directories like 'src', 2 ranks 'src/parser' etc."
+        )]
+        directory_depth: Option<usize>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum number of files and of directories to return (optional, default 20)."
+        )]
+        max_results: Option<usize>,
+    ) -> Result<CallToolResult, McpError> {
+        let hotspots_params = services::HotspotsServiceParams {
+            repository_location_str: repository_location,
+            from_ref,
+            to_ref,
+            since,
+            until,
+            include_globs,
+            exclude_dirs,
+            metric,
+            weight_by_size,
+            directory_depth,
+            max_results,
+        };
+
+        match services::hotspots(&self.manager, hotspots_params).await {
+            Ok((report, _local_repo)) => {
+                // Note: We don't clean up the repository here to use it as a cache
+                tracing::debug!("Repository kept for caching");
+
+                match serde_json::to_string(&report) {
+                    Ok(json) => success_result(json),
+                    Err(e) => error_result(format!("Failed to serialize hotspots: {}", e)),
+                }
+            }
+            Err(err) => {
+                tracing::error!("Hotspot analysis failed: {}", err);
+                error_result(format!("Hotspot analysis failed: {}", err))
+            }
+        }
+    }
+
+    /// Compare two refs of a repository
+    ///
+    /// This method finds the merge-base of two refs and counts and lists the commits
+    /// that exist on only one side, e.g. to check whether a fork has diverged from
+    /// upstream.
+    #[tool(
+        description = "Compare two refs, like GitHub's compare view or `git rev-list --left-right --count base...head`. Returns the merge-base, the status ('identical', 'ahead', 'behind' or 'diverged'), ahead/behind commit counts and the commits unique to each side (newest first). Branch names of clones resolve to their 'origin/' remote-tracking branches; other remotes can be given as 'remote/branch' or 'refs/remotes/remote/branch'. Example: `{\"name\": \"compare_refs\", \"arguments\": {\"repository_location\": \"github:user/repo\", \"base_ref\": \"main\", \"head_ref\": \"feature\"}}`. Fork against upstream in a local clone: `{\"name\": \"compare_refs\", \"arguments\": {\"repository_location\": \"/path/to/fork\", \"base_ref\": \"upstream/main\", \"head_ref\": \"main\"}}`"
+    )]
+    async fn compare_refs(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Repository URL or local path (required). Supports GitHub formats: 'git@github.com:user/repo.git' (SSH, recommended), 'https://github.com/user/repo', 'github:user/repo', absolute local paths, or git bundle files as 'bundle:/path/to/repo.bundle'. Private repos require GITCODES_MCP_GITHUB_TOKEN environment variable. This parameter is required and must be provided."
+        )]
+        repository_location: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "The ref compared against (required), e.g. 'main' or 'upstream/main'. Branch, tag, commit, or remote-tracking branch. 'behind_by' counts its commits missing from head_ref."
+        )]
+        base_ref: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "The ref being compared (required), e.g. 'feature' or 'origin/feature'. Branch, tag, commit, or remote-tracking branch. 'ahead_by' counts its commits missing from base_ref."
+        )]
+        head_ref: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum number of commits listed per side (optional, default 50). The ahead/behind counts are always exact."
+        )]
+        max_commits: Option<usize>,
+    ) -> Result<CallToolResult, McpError> {
+        let compare_params = services::CompareRefsServiceParams {
+            repository_location_str: repository_location,
+            base_ref,
+            head_ref,
+            max_commits,
+        };
+
+        match services::compare_refs(&self.manager, compare_params).await {
+            Ok((comparison, _local_repo)) => {
+                // Note: We don't clean up the repository here to use it as a cache
+                tracing::debug!("Repository kept for caching");
+
+                match serde_json::to_string(&comparison) {
+                    Ok(json) => success_result(json),
+                    Err(e) => error_result(format!("Failed to serialize comparison: {}", e)),
+                }
+            }
+            Err(err) => {
+                tracing::error!("Ref comparison failed: {}", err);
+                error_result(format!("Ref comparison failed: {}", err))
+            }
+        }
+    }
+
+    /// Show the working-tree status of a local repository
+    ///
+    /// This method lists the staged, unstaged and untracked files of a local checkout
+    /// with their diffs against HEAD, so the work in progress can be reviewed.
+    #[tool(
+        description = "List the uncommitted changes of a local repository, like `git status` combined with `git diff HEAD`. Returns the branch, HEAD commit and for each changed file its staged change ('added', 'deleted', 'modified'), unstaged change ('added', 'deleted', 'modified', 'type_changed', 'conflicted'), whether it is untracked, line counts and the diff hunks of the working tree against HEAD. Only local paths are supported. Combine with `grep_repository`'s 'changed_files_only' to search the work in progress. Example: `{\"name\": \"git_status\", \"arguments\": {\"repository_location\": \"/path/to/repo\"}}`. Without diffs: `{\"name\": \"git_status\", \"arguments\": {\"repository_location\": \"/path/to/repo\", \"include_diffs\": false}}`"
+    )]
+    async fn git_status(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Absolute path of a local repository with a working tree (required), e.g. '/path/to/repo'. Remote repositories are rejected since their cached clones never have local changes."
+        )]
+        repository_location: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Whether untracked files are listed (optional, default true). Files ignored through .gitignore are never listed."
+        )]
+        include_untracked: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Whether the diff hunks of each text file against HEAD are included (optional, default true). Set to false for a compact file list with line counts only."
+        )]
+        include_diffs: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Number of context lines around each diff hunk (optional, default 3)."
+        )]
+        context_lines: Option<u32>,
+    ) -> Result<CallToolResult, McpError> {
+        let status_params = services::GitStatusServiceParams {
+            repository_location_str: repository_location,
+            include_untracked,
+            include_diffs,
+            context_lines,
+        };
+
+        match services::git_status(&self.manager, status_params).await {
+            Ok((status, _local_repo)) => match serde_json::to_string(&status) {
+                Ok(json) => success_result(json),
+                Err(e) => error_result(format!("Failed to serialize status: {}", e)),
+            },
+            Err(err) => {
+                tracing::error!("Working-tree status failed: {}", err);
+                error_result(format!("Working-tree status failed: {}", err))
+            }
+        }
+    }
+
+    /// Find where functions, methods, types and traits are defined
+    ///
+    /// Parses the source files with tree-sitter and returns the definitions whose name
+    /// matches, with their kind, signature, file and line range. Unlike a regex search,
+    /// calls, comments and strings that mention the name are not returned.
+    #[tool(
+        description = "Find symbol definitions (functions, methods, structs, enums, traits, interfaces, classes, type aliases, modules, macros) by name, using syntax parsing so usages, comments and strings are not returned. Supports Rust, Go, Python, TypeScript and JavaScript. Each hit has the kind, signature, enclosing container (e.g. the impl type or class), file path and line range, which can be passed to show_file_contents as line_from/line_to. Example: `{\"name\": \"find_symbol\", \"arguments\": {\"repository_location\": \"github:tokio-rs/tokio\", \"name\": \"block_on\"}}`. All types ending in 'Error': `{\"name\": \"find_symbol\", \"arguments\": {\"repository_location\": \"/path/to/repo\", \"name\": \"Error$\", \"regex\": true, \"kinds\": [\"struct\", \"enum\"]}}`"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    async fn find_symbol(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Repository URL or local path (required). Supports GitHub formats: 'git@github.com:user/repo.git' (SSH, recommended), 'https://github.com/user/repo', 'github:user/repo', absolute local paths, or git bundle files as 'bundle:/path/to/repo.bundle'. Private repos require GITCODES_MCP_GITHUB_TOKEN environment variable. This parameter is required and must be provided."
+        )]
+        repository_location: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Symbol name to find (required), e.g. 'lock' or 'Mutex'. Matched against the plain name, without the enclosing type or module. With 'regex' set, a regular expression matched anywhere in the name, e.g. '^new_' or 'Error$'."
+        )]
+        name: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Treat 'name' as a regular expression (optional, default false)."
+        )]
+        regex: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(description = "Case-sensitive name matching (optional, default false).")]
+        case_sensitive: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Only return these kinds of symbols (optional, default all): 'function', 'method', 'struct', 'enum', 'union', 'trait', 'interface', 'class', 'type_alias', 'module', 'macro'."
+        )]
+        kinds: Option<Vec<SymbolKind>>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Branch, commit, or tag (optional, default 'main'/'master'). When given, that commit's tree is parsed straight from the git object database, like grep_repository does."
+        )]
+        ref_name: Option<String>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Glob patterns of the files to search (optional), relative to the repository root. Example: [\"src/**/*.rs\"]."
+        )]
+        include_globs: Option<Vec<String>>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Directories to exclude (optional). Directory names like [\"vendor\"] or glob patterns like [\"**/tests/**\"]."
+        )]
+        exclude_dirs: Option<Vec<String>>,
+
+        #[tool(param)]
+        #[schemars(description = "Number of symbols to skip for pagination (optional).")]
+        skip: Option<usize>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum number of symbols to return (optional, default 50). 'total_symbols' in the response counts all matches."
+        )]
+        take: Option<usize>,
+    ) -> Result<CallToolResult, McpError> {
+        let symbol_params = services::FindSymbolServiceParams {
+            repository_location_str: repository_location,
+            name,
+            regex,
+            case_sensitive,
+            kinds,
+            ref_name,
+            include_globs,
+            exclude_dirs,
+            skip,
+            take,
+        };
+
+        match services::find_symbol(&self.manager, symbol_params).await {
+            Ok((result, _local_repo)) => {
+                // Note: We don't clean up the repository here to use it as a cache
+                tracing::debug!("Repository kept for caching");
+
+                match serde_json::to_string(&result) {
+                    Ok(json) => success_result(json),
+                    Err(e) => error_result(format!("Failed to serialize symbols: {}", e)),
+                }
+            }
+            Err(err) => {
+                tracing::error!("Symbol search failed: {}", err);
+                error_result(format!("Symbol search failed: {}", err))
+            }
+        }
+    }
+
+    /// Outline a source file before reading it
+    ///
+    /// Parses the file with tree-sitter and returns its items as a tree: modules, impl
+    /// blocks, traits, classes and interfaces with their members, each with its signature,
+    /// line range and the first line of its doc comment.
+    #[tool(
+        description = "Get the structural outline of a source file: its modules, impl blocks, traits, classes, interfaces and functions as a nested tree, each with kind, signature, line range and the first line of its doc comment or docstring. Use it as a map before reading a long file, then call show_file_contents with the item's start_line/end_line as line_from/line_to. Supports Rust, Go, Python, TypeScript and JavaScript. Example: `{\"name\": \"file_outline\", \"arguments\": {\"repository_location\": \"github:tokio-rs/tokio\", \"file_path\": \"tokio/src/sync/mutex.rs\"}}`"
+    )]
+    async fn file_outline(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Repository URL or local path (required). Supports GitHub formats: 'git@github.com:user/repo.git' (SSH, recommended), 'https://github.com/user/repo', 'github:user/repo', absolute local paths, or git bundle files as 'bundle:/path/to/repo.bundle'. Private repos require GITCODES_MCP_GITHUB_TOKEN environment variable. This parameter is required and must be provided."
+        )]
+        repository_location: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Path to the file relative to the repository root (required). Example: 'src/lib.rs'."
+        )]
+        file_path: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Branch, commit, or tag (optional, default 'main'/'master'). When given, the file is read straight from the git object database at that ref."
+        )]
+        ref_name: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        let outline_params = services::FileOutlineServiceParams {
+            repository_location_str: repository_location,
+            file_path,
+            ref_name,
+        };
+
+        match services::file_outline(&self.manager, outline_params).await {
+            Ok((outline, _local_repo)) => {
+                // Note: We don't clean up the repository here to use it as a cache
+                tracing::debug!("Repository kept for caching");
+
+                match serde_json::to_string(&outline) {
+                    Ok(json) => success_result(json),
+                    Err(e) => error_result(format!("Failed to serialize outline: {}", e)),
+                }
+            }
+            Err(err) => {
+                tracing::error!("File outline failed: {}", err);
+                error_result(format!("File outline failed: {}", err))
+            }
+        }
+    }
+
+    /// Show the full definition of a symbol by its qualified name
+    ///
+    /// Resolves names like `tokio::sync::Mutex::lock` or `Mutex::lock` to the defining
+    /// files and returns the source of each matching definition with its doc comments and
+    /// attributes, replacing a grep followed by show_file_contents.
+    #[tool(
+        description = "Show the full source of a function, method, type, trait, class or module by its qualified name, including its doc comments and attributes. The name may be qualified with modules, types or classes separated by '::' or '.', e.g. 'tokio::sync::Mutex::lock', 'Mutex::lock', 'models.User.save' or just 'block_on'; qualifiers only need to appear in order in the definition's path (file modules plus enclosing impl/class), so re-exported paths work. When several definitions match, all are returned (best match first) with their full qualified names to disambiguate. Supports Rust, Go, Python, TypeScript and JavaScript. Example: `{\"name\": \"show_symbol\", \"arguments\": {\"repository_location\": \"github:tokio-rs/tokio\", \"qualified_name\": \"Mutex::lock\"}}`"
+    )]
+    async fn show_symbol(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Repository URL or local path (required). Supports GitHub formats: 'git@github.com:user/repo.git' (SSH, recommended), 'https://github.com/user/repo', 'github:user/repo', absolute local paths, or git bundle files as 'bundle:/path/to/repo.bundle'. Private repos require GITCODES_MCP_GITHUB_TOKEN environment variable. This parameter is required and must be provided."
+        )]
+        repository_location: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Name of the symbol (required), optionally qualified with modules, types or classes separated by '::' or '.'. Examples: 'Mutex::lock', 'tokio::sync::Mutex', 'User.save'. The last segment must match the name exactly (case-sensitive)."
+        )]
+        qualified_name: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Branch, commit, or tag (optional, default 'main'/'master'). When given, that commit's tree is parsed straight from the git object database."
+        )]
+        ref_name: Option<String>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Glob patterns of the files to search (optional), relative to the repository root. Example: [\"src/**/*.rs\"]."
+        )]
+        include_globs: Option<Vec<String>>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Directories to exclude (optional). Directory names like [\"vendor\"] or glob patterns like [\"**/tests/**\"]."
+        )]
+        exclude_dirs: Option<Vec<String>>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum number of definitions to return (optional, default 10). 'total_matches' in the response counts all matches."
+        )]
+        max_results: Option<usize>,
+    ) -> Result<CallToolResult, McpError> {
+        let show_params = services::ShowSymbolServiceParams {
+            repository_location_str: repository_location,
+            qualified_name,
+            ref_name,
+            include_globs,
+            exclude_dirs,
+            max_results,
+        };
+
+        match services::show_symbol(&self.manager, show_params).await {
+            Ok((result, _local_repo)) => {
+                // Note: We don't clean up the repository here to use it as a cache
+                tracing::debug!("Repository kept for caching");
+
+                let response = responses::ShowSymbolResponse::from_result(result);
+                match serde_json::to_string(&response) {
+                    Ok(json) => success_result(json),
+                    Err(e) => error_result(format!("Failed to serialize definitions: {}", e)),
+                }
+            }
+            Err(err) => {
+                tracing::error!("Show symbol failed: {}", err);
+                error_result(format!("Show symbol failed: {}", err))
+            }
+        }
+    }
+
+    /// Find the references to an identifier
+    ///
+    /// Looks the name up in an identifier index of the commit, built by parsing every
+    /// source file once and cached next to the clone, so repeated lookups don't scan the
+    /// repository and mentions in comments and strings aren't reported.
+    #[tool(
+        description = "Find where an identifier (function, method, type, variable, field, ...) is used. Unlike a text search, mentions in comments and string literals are ignored and only whole identifiers match (exact, case-sensitive). The first call for a commit parses all Rust, Go, Python, TypeScript and JavaScript files into an identifier index that's cached next to the clone; later calls are fast. Returns the definitions of the name and the references sorted by file, line and column, with the source line of each. Only committed content is indexed. Example: `{\"name\": \"find_references\", \"arguments\": {\"repository_location\": \"github:tokio-rs/tokio\", \"name\": \"block_on\"}}`"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    async fn find_references(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Repository URL or local path (required). Supports GitHub formats: 'git@github.com:user/repo.git' (SSH, recommended), 'https://github.com/user/repo', 'github:user/repo', absolute local paths, or git bundle files as 'bundle:/path/to/repo.bundle'. Private repos require GITCODES_MCP_GITHUB_TOKEN environment variable. This parameter is required and must be provided."
+        )]
+        repository_location: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "The identifier to find (required), matched exactly and case-sensitively. Example: 'block_on'. Unqualified: use show_symbol to tell definitions with the same name apart."
+        )]
+        name: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Branch, commit, or tag (optional, default 'main'/'master'). Each commit gets its own index."
+        )]
+        ref_name: Option<String>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Whether the names in the definitions themselves are listed as references too (optional, default false)."
+        )]
+        include_definitions: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Glob patterns of the files to return references from (optional), relative to the repository root. Example: [\"src/**/*.rs\"]."
+        )]
+        include_globs: Option<Vec<String>>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Directories to exclude (optional). Directory names like [\"vendor\"] or glob patterns like [\"**/tests/**\"]."
+        )]
+        exclude_dirs: Option<Vec<String>>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Number of references to skip for pagination (optional). Use with 'take' parameter to implement pagination."
+        )]
+        skip: Option<usize>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum number of references to return (optional, default 100). 'total_references' in the response counts all references."
+        )]
+        take: Option<usize>,
+    ) -> Result<CallToolResult, McpError> {
+        let reference_params = services::FindReferencesServiceParams {
+            repository_location_str: repository_location,
+            name,
+            ref_name,
+            include_definitions,
+            include_globs,
+            exclude_dirs,
+            skip,
+            take,
+        };
+
+        match services::find_references(&self.manager, reference_params).await {
+            Ok((result, _local_repo)) => {
+                // Note: We don't clean up the repository here to use it as a cache
+                tracing::debug!("Repository kept for caching");
+
+                match serde_json::to_string(&result) {
+                    Ok(json) => success_result(json),
+                    Err(e) => error_result(format!("Failed to serialize references: {}", e)),
+                }
+            }
+            Err(err) => {
+                tracing::error!("Find references failed: {}", err);
+                error_result(format!("Find references failed: {}", err))
+            }
+        }
+    }
+
+    /// Search code by syntax with an AST pattern
+    ///
+    /// Parses the pattern with the tree-sitter grammar of its language and returns the
+    /// nodes of the files with the same shape, ignoring formatting and comments.
+    #[tool(
+        description = "Search code by its syntax instead of its text (ast-grep style). The pattern is code of the given language where `$NAME` matches any single node (expression, type, identifier, ...) and `$$$NAME` matches any number of nodes in a list (arguments, statements, ...); `$_` and `$$$` match without recording. A name used twice must match the same code both times. Whitespace, line breaks, trailing commas and comments don't matter, and code in comments or strings isn't matched. Supported languages: rust, go, python, typescript and tsx (tsx also covers JavaScript; typescript and tsx patterns search both). Returns the compact grep format with the lines of each matched node, plus 'ranges' per file with the node's start/end line and column and the text of each metavariable. 'total_match_line_number' counts the matched nodes. Example: `{\"name\": \"structural_search\", \"arguments\": {\"repository_location\": \"github:tokio-rs/tokio\", \"pattern\": \"File::open($PATH).unwrap()\", \"language\": \"rust\"}}`"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    async fn structural_search(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Repository URL or local path (required). Supports GitHub formats: 'git@github.com:user/repo.git' (SSH, recommended), 'https://github.com/user/repo', 'github:user/repo', absolute local paths, or git bundle files as 'bundle:/path/to/repo.bundle'. Private repos require GITCODES_MCP_GITHUB_TOKEN environment variable. This parameter is required and must be provided."
+        )]
+        repository_location: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "The pattern (required), valid code of 'language' with metavariables. Examples: 'File::open($PATH).unwrap()', '$X == $X', 'requests.get($URL, $$$REST)', 'console.log($$$ARGS)'. A lone metavariable is rejected."
+        )]
+        pattern: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Language of the pattern and of the files to search (required): 'rust', 'go', 'python', 'typescript' or 'tsx'."
+        )]
+        language: SourceLanguage,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Branch, commit, or tag (optional, default 'main'/'master'). Local repositories are searched in the worktree when omitted."
+        )]
+        ref_name: Option<String>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Glob patterns of the files to search (optional), relative to the repository root. Example: [\"src/**/*.rs\"]."
+        )]
+        include_globs: Option<Vec<String>>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Directories to exclude (optional). Directory names like [\"vendor\"] or glob patterns like [\"**/tests/**\"]."
+        )]
+        exclude_dirs: Option<Vec<String>>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Number of matched nodes to skip for pagination (optional). Use with 'take' parameter to implement pagination."
+        )]
+        skip: Option<usize>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum number of matched nodes to return (optional, default 50). 'total_match_line_number' in the response counts all matches."
+        )]
+        take: Option<usize>,
+    ) -> Result<CallToolResult, McpError> {
+        let search_params = services::StructuralSearchServiceParams {
+            repository_location_str: repository_location,
+            pattern,
+            language,
+            ref_name,
+            include_globs,
+            exclude_dirs,
+            skip,
+            take,
+        };
+
+        match services::structural_search(&self.manager, search_params).await {
+            Ok((result, _local_repo)) => {
+                // Note: We don't clean up the repository here to use it as a cache
+                tracing::debug!("Repository kept for caching");
+
+                let response = responses::CompactCodeSearchResponse::from_structural_result(result);
+                match serde_json::to_string(&response) {
+                    Ok(json) => success_result(json),
+                    Err(e) => error_result(format!("Failed to serialize search results: {}", e)),
+                }
+            }
+            Err(err) => {
+                tracing::error!("Structural search failed: {}", err);
+                error_result(format!("Structural search failed: {}", err))
+            }
+        }
+    }
+
+    /// Rank code chunks by relevance to a free-text query
+    ///
+    /// Splits the files of a commit into function-sized chunks, indexes their words once
+    /// per commit and returns the chunks scoring highest with BM25.
+    #[tool(
+        description = "Find the code most relevant to a free-text query, best first. Files are split into chunks (one per function, method or type in Rust, Go, Python, TypeScript and JavaScript; 40-line windows otherwise) and ranked with BM25 on their words, like a search engine: chunks holding more of the query's rarer words rank higher. Identifiers are split into words (parseConfig and parse_config match 'parse config') and simple inflections are ignored ('parsing' matches 'parse'). Fully offline, no embeddings; the index is built on the first search of a commit and cached. Searches committed content (HEAD when ref_name is omitted). Returns per chunk its file, line range, enclosing symbol, score, the matched query words and up to 30 source lines; use show_file_contents with the line range for more. Use grep_repository for exact text. Example: `{\"name\": \"search_code_ranked\", \"arguments\": {\"repository_location\": \"github:tokio-rs/tokio\", \"query\": \"retry with exponential backoff\"}}`"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    async fn search_code_ranked(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Repository URL or local path (required). Supports GitHub formats: 'git@github.com:user/repo.git' (SSH, recommended), 'https://github.com/user/repo', 'github:user/repo', absolute local paths, or git bundle files as 'bundle:/path/to/repo.bundle'. Private repos require GITCODES_MCP_GITHUB_TOKEN environment variable. This parameter is required and must be provided."
+        )]
+        repository_location: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "What to look for in words (required), e.g. 'where is the config file parsed' or 'tls certificate verification'. Identifiers may be written as in code. Common words like 'the' or 'how' are ignored."
+        )]
+        query: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Branch, commit, or tag (optional). The commit at HEAD (default branch for remote repositories) is searched when omitted."
+        )]
+        ref_name: Option<String>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Glob patterns of the files to return chunks from (optional), relative to the repository root. Example: [\"src/**/*.rs\"]."
+        )]
+        include_globs: Option<Vec<String>>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Directories to exclude (optional). Directory names like [\"vendor\"] or glob patterns like [\"**/tests/**\"]."
+        )]
+        exclude_dirs: Option<Vec<String>>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Number of chunks to skip for pagination (optional). Use with 'take' parameter to implement pagination."
+        )]
+        skip: Option<usize>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum number of chunks to return (optional, default 10). 'total_matches' in the response counts all chunks containing any query word."
+        )]
+        take: Option<usize>,
+    ) -> Result<CallToolResult, McpError> {
+        let search_params = services::RankedSearchServiceParams {
+            repository_location_str: repository_location,
+            query,
+            ref_name,
+            include_globs,
+            exclude_dirs,
+            skip,
+            take,
+        };
+
+        match services::search_code_ranked(&self.manager, search_params).await {
+            Ok((result, _local_repo)) => {
+                // Note: We don't clean up the repository here to use it as a cache
+                tracing::debug!("Repository kept for caching");
+
+                match serde_json::to_string(&result) {
+                    Ok(json) => success_result(json),
+                    Err(e) => error_result(format!("Failed to serialize search results: {}", e)),
+                }
+            }
+            Err(err) => {
+                tracing::error!("Ranked search failed: {}", err);
+                error_result(format!("Ranked search failed: {}", err))
+            }
+        }
+    }
+
+    /// Find files by glob or fuzzy name
+    ///
+    /// Matches the paths of the files of the repository against a glob or, for any other
+    /// query, fuzzily like the file pickers of editors.
+    #[tool(
+        description = "Find files by name without listing the whole tree. A query containing *, ?, [ or { is a glob ('**/Cargo.toml', 'src/**/*_test.go'; without '/' it matches the file name at any depth, e.g. '*.proto'). Any other query is matched fuzzily: its characters must appear in the path in order, so 'octoclient' finds 'octocrab_client.rs'; matches in the file name, at word starts and of consecutive characters rank higher. Matching is case-insensitive unless the query has an uppercase letter. Returns each file's path, size in bytes, language and fuzzy score, best matches first (globs by depth and path). Example: `{\"name\": \"find_files\", \"arguments\": {\"repository_location\": \"github:XAMPPRocky/octocrab\", \"query\": \"issuehandler\"}}`"
+    )]
+    async fn find_files(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Repository URL or local path (required). Supports GitHub formats: 'git@github.com:user/repo.git' (SSH, recommended), 'https://github.com/user/repo', 'github:user/repo', absolute local paths, or git bundle files as 'bundle:/path/to/repo.bundle'. Private repos require GITCODES_MCP_GITHUB_TOKEN environment variable. This parameter is required and must be provided."
+        )]
+        repository_location: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "A glob like '**/Cargo.toml' or '*.proto', or part of a file name or path to match fuzzily like 'octoclient' or 'srv/handler' (required)."
+        )]
+        query: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Branch, commit, or tag (optional, default 'main'/'master'). Local repositories are searched in the worktree when omitted."
+        )]
+        ref_name: Option<String>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Leave out files ignored by .gitignore and hidden files (optional, default true). Set to false to also find build outputs and dotfiles."
+        )]
+        respect_gitignore: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Number of files to skip for pagination (optional). Use with 'take' parameter to implement pagination."
+        )]
+        skip: Option<usize>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum number of files to return (optional, default 50). 'total_matches' in the response counts all matching files."
+        )]
+        take: Option<usize>,
+    ) -> Result<CallToolResult, McpError> {
+        let find_params = services::FindFilesServiceParams {
+            repository_location_str: repository_location,
+            query,
+            ref_name,
+            respect_gitignore,
+            skip,
+            take,
         };
 
-        match services::get_repository_tree(&self.manager, tree_params).await {
-            Ok((tree, _local_repo)) => {
+        match services::find_files(&self.manager, find_params).await {
+            Ok((result, _local_repo)) => {
                 // Note: We don't clean up the repository here to use it as a cache
-                // This improves performance for subsequent operations
                 tracing::debug!("Repository kept for caching");
 
-                // Serialize the tree to JSON and return it
-                match serde_json::to_string(&tree) {
+                match serde_json::to_string(&result) {
                     Ok(json) => success_result(json),
-                    Err(e) => error_result(format!("Failed to serialize repository tree: {}", e)),
+                    Err(e) => error_result(format!("Failed to serialize files: {}", e)),
                 }
             }
             Err(err) => {
-                // Tree retrieval failed, try to clean up repository if it was created
-                tracing::error!("Tree retrieval failed: {}", err);
+                tracing::error!("Find files failed: {}", err);
+                error_result(format!("Find files failed: {}", err))
+            }
+        }
+    }
 
-                // Note: We don't clean up the repository here even on error
-                // to preserve it for potential future operations
-                tracing::debug!("Repository kept for caching even after error");
+    /// Count the matches of a code search per file, top-level directory and extension
+    ///
+    /// Runs the same search as `grep_repository` over all files and aggregates the matched
+    /// lines instead of returning them, so an agent can see where the matches concentrate
+    /// before paging through them with `grep_repository` and narrowed `include_globs`.
+    #[tool(
+        description = "Count matching lines per file, top-level directory and extension without returning any line content. Works like grep_repository but returns facets: `total_match_line_number`, `total_files` and the top N `files`, `directories` ('.' for files at the root) and `extensions` ('(none)' for files without one), each as [{\"value\", \"count\"}] with the most matches first. Use it to decide where to drill in with grep_repository and include_globs. Example: `{\"name\": \"grep_repository_facets\", \"arguments\": {\"repository_location\": \"github:user/repo\", \"pattern\": \"unwrap\\\\(\\\\)\", \"top_n\": 5}}`"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    async fn grep_repository_facets(
+        &self,
+        #[tool(param)]
+        #[schemars(
+            description = "Repository URL or local path (required). Supports GitHub formats: 'git@github.com:user/repo.git' (SSH, recommended), 'https://github.com/user/repo', 'github:user/repo', absolute local paths, or git bundle files as 'bundle:/path/to/repo.bundle'. Private repos require GITCODES_MCP_GITHUB_TOKEN environment variable. This parameter is required and must be provided."
+        )]
+        repository_location: String,
 
-                // Return the original error as an error result
-                error_result(format!("Tree retrieval failed: {}", err))
+        #[tool(param)]
+        #[schemars(
+            description = "Branch, commit, or tag (optional, default 'main'/'master'). Can be branch name (e.g. 'develop'), commit hash (full or short), or tag name (e.g. 'v1.0.0'). The aliases 'latest' and 'latest-stable' resolve to the tag with the highest semantic version ('latest-stable' skips prereleases). When given, the blobs of that commit's tree are searched straight from the git object database without a checkout, so many refs can be searched from one clone (also works for local repositories)."
+        )]
+        ref_name: Option<String>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Regular expression pattern to search for (required). For literal text such as 'Vec<T>::new(' set 'fixed_string' instead of escaping the special regex characters '.^$*+?()[]{}\\|'. This parameter is required and must be provided."
+        )]
+        pattern: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Case-sensitive matching (optional, default false). When true, pattern matching distinguishes between uppercase and lowercase characters. When false or omitted, performs case-insensitive search."
+        )]
+        case_sensitive: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Treat the pattern as a literal string (optional, default false), like 'grep -F'. Every character matches itself, so no regex escaping is needed."
+        )]
+        fixed_string: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Match whole words only (optional, default false), like 'grep -w'. A match must not be preceded or followed by a letter, digit or underscore. Works for patterns starting or ending with punctuation, unlike '\\b'."
+        )]
+        word_regexp: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Allow matches spanning multiple lines (optional, default false), like 'rg --multiline'. The pattern can match line breaks with '\\n' or '\\s', e.g. 'fn parse\\(\\s*input: &str,\\s*strict' for a signature split across lines. '.' only matches line breaks with the '(?s)' flag. Every line of a match is returned as a matched line."
+        )]
+        multiline: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "[DEPRECATED] File extensions to search. Use include_globs instead."
+        )]
+        file_extensions: Option<Vec<String>>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Glob patterns to include (optional). Filters files to search using glob syntax. Examples: [\"**/*.rs\"] (all Rust files), [\"src/**/*.md\"] (Markdown files in src), [\"*.json\", \"*.yaml\"] (config files). When omitted, searches all text files."
+        )]
+        include_globs: Option<Vec<String>>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Directories to exclude (optional). List of directory names to skip during search. Common examples: [\"target\", \"node_modules\"] (build artifacts), [\".git\", \".svn\"] (version control), [\"dist\", \"build\"] (output directories). When omitted, respects .gitignore patterns."
+        )]
+        exclude_dirs: Option<Vec<String>>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Only search files with uncommitted changes (optional, default false). Restricts the search to staged, unstaged and untracked files of a local repository's working tree, e.g. to review work in progress. Only valid for local paths and cannot be combined with 'ref_name'."
+        )]
+        changed_files_only: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Narrow the search with a trigram index cached per tree (optional, default false). Only speeds up repeated searches of large repositories; the results don't change."
+        )]
+        use_index: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum number of values listed per facet (optional, default 10). Must be at least 1. The totals always count all matches."
+        )]
+        top_n: Option<usize>,
+    ) -> Result<CallToolResult, McpError> {
+        let facets_params = services::GrepFacetsParams {
+            repository_location_str: repository_location,
+            pattern,
+            ref_name,
+            case_sensitive: case_sensitive.unwrap_or(false),
+            file_extensions,
+            include_globs,
+            exclude_dirs,
+            changed_files_only: changed_files_only.unwrap_or(false),
+            use_index: use_index.unwrap_or(false),
+            fixed_string: fixed_string.unwrap_or(false),
+            word_regexp: word_regexp.unwrap_or(false),
+            multiline: multiline.unwrap_or(false),
+            top_n,
+        };
+
+        match services::grep_facets(&self.manager, facets_params).await {
+            Ok((facets, _local_repo)) => {
+                // Note: We don't clean up the repository here to use it as a cache
+                tracing::debug!("Repository kept for caching");
+
+                match serde_json::to_string(&facets) {
+                    Ok(json) => success_result(json),
+                    Err(e) => error_result(format!("Failed to serialize facets: {}", e)),
+                }
+            }
+            Err(err) => {
+                tracing::error!("Grep facets failed: {}", err);
+                error_result(format!("Grep facets failed: {}", err))
             }
         }
     }
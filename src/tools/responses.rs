@@ -9,6 +9,8 @@
 //! ## Code Search Responses
 //! - [`CodeSearchResponse`]: Direct alias to `CodeSearchResult` (legacy format)
 //! - [`CompactCodeSearchResponse`]: New compact format that groups results by file
+//! - [`MatchedNodeRange`]: Position of a node found by a structural search
+//! - [`MultiRepositoryCodeSearchResponse`]: Compact results of one search in several repositories
 //!
 //! ## Issue Search Responses
 //! - [`CompactIssueSearchResponse`]: Compact format with simplified issue structure
@@ -17,6 +19,7 @@
 //! ## File Content Responses  
 //! - [`FileContentsResponse`]: Direct alias to `FileContents` (legacy format)
 //! - [`CompactFileContentsResponse`]: Compact format with concatenated line contents
+//! - [`ShowSymbolResponse`]: Full sources of the definitions a qualified name resolves to
 //!
 //! ## Repository Information Responses
 //! - [`RepositoryRefsResponse`]: Lists branches and tags for a repository
@@ -33,9 +36,14 @@
 //! efficiency and readability are important considerations.
 
 use crate::gitcodes::CodeSearchResult;
+use crate::gitcodes::local_repository::{
+    RefObject, StructuralSearchResult, SymbolKind, SymbolSourceResult, TagDetails,
+};
 use crate::gitcodes::repository_manager::providers::IssueSearchResults;
+use crate::services::RepositoryGrepOutcome;
 use lumin::view::FileContents;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Response for the grep_repository tool (legacy format)
 ///
@@ -104,6 +112,10 @@ pub struct CompactCodeSearchResponse {
 
     /// Number of lines of context included after each match
     pub after_context: Option<usize>,
+
+    /// Cursor to pass back for the next page, if there are more results
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 /// A file match containing grouped lines for the compact response
@@ -115,6 +127,91 @@ pub struct CompactFileMatch {
     /// Concatenated line contents with line numbers
     /// Format: "{line_number}:{content}\n{line_number}:{content}..."
     pub lines: String,
+
+    /// The syntax nodes that matched, for structural searches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ranges: Option<Vec<MatchedNodeRange>>,
+}
+
+/// The position of a syntax node matched by a structural search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchedNodeRange {
+    /// First line of the node (1-based)
+    pub start_line: usize,
+
+    /// Column where the node starts (1-based, in bytes)
+    pub start_column: usize,
+
+    /// Last line of the node (1-based, inclusive)
+    pub end_line: usize,
+
+    /// Column after the end of the node (1-based, in bytes)
+    pub end_column: usize,
+
+    /// The text each named metavariable of the pattern matched
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metavariables: BTreeMap<String, String>,
+}
+
+/// Response for the grep_repositories tool
+///
+/// Holds one entry per repository, in the order the repositories were given. Each entry
+/// has either the compact search result of that repository or the error that kept it
+/// from being searched, so one unavailable repository doesn't hide the others.
+///
+/// # Format
+///
+/// ```json
+/// {
+///   "pattern": "fn block_on",
+///   "total_match_line_number": 3,
+///   "repositories": [
+///     {
+///       "repository_location": "github:tokio-rs/tokio",
+///       "result": {"total_match_line_number": 3, "matches": [...], ...}
+///     },
+///     {
+///       "repository_location": "github:user/missing",
+///       "error": "Failed to clone repository: ..."
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiRepositoryCodeSearchResponse {
+    /// The search pattern that was used in every repository
+    pub pattern: String,
+
+    /// Sum of the matching lines of all searched repositories
+    pub total_match_line_number: usize,
+
+    /// Results or errors per repository
+    pub repositories: Vec<RepositoryCodeSearchEntry>,
+}
+
+/// The search result or error of one repository in a [`MultiRepositoryCodeSearchResponse`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepositoryCodeSearchEntry {
+    /// The repository location as given
+    pub repository_location: String,
+
+    /// Matches grouped by file, if the repository was searched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<CompactCodeSearchResponse>,
+
+    /// Why the repository couldn't be searched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Response of the save_repository_collection tool, echoing what was saved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepositoryCollectionResponse {
+    /// Name to pass as `collection` to grep_repositories
+    pub name: String,
+
+    /// The repository locations of the collection, in the order given
+    pub repository_locations: Vec<String>,
 }
 
 /// Compact response for the search_issues tool
@@ -238,8 +335,20 @@ pub struct RepositoryRefsResponse {
     /// List of branch references
     pub branches: Vec<ReferenceInfo>,
 
-    /// List of tag references
+    /// Name of the default branch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_branch: Option<String>,
+
+    /// List of tag references, newest semantic version first
     pub tags: Vec<ReferenceInfo>,
+
+    /// Tag the `latest` ref alias resolves to (highest version, prereleases included)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_tag: Option<String>,
+
+    /// Tag the `latest-stable` ref alias resolves to (highest version without prerelease)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_stable_tag: Option<String>,
 }
 
 /// Information about a git reference (branch or tag)
@@ -251,8 +360,24 @@ pub struct ReferenceInfo {
     /// Full reference path (e.g., "refs/heads/main")
     pub full_ref: String,
 
-    /// Commit SHA this reference points to
+    /// Commit SHA this reference points to (annotated tags are peeled to their commit)
     pub commit_id: String,
+
+    /// Commit date of the commit at the tip of the reference (ISO 8601)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit_date: Option<String>,
+
+    /// The object the reference points to directly; type "tag" marks an annotated tag
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object: Option<RefObject>,
+
+    /// Tagger, date and message of an annotated tag
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<TagDetails>,
+
+    /// Semantic version parsed from a tag name (e.g. "1.2.3" for "v1.2.3")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
 }
 
 /// Response for the show_file_contents tool (legacy format)
@@ -430,6 +555,143 @@ impl CompactFileContentsResponse {
     }
 }
 
+/// Response for the show_symbol tool
+///
+/// Each definition the qualified name resolves to comes with its source, from the doc
+/// comments and attributes above it to its last line, in the
+/// [`CompactFileContentsResponse`] form of `show_file_contents`.
+///
+/// # Format
+///
+/// ```json
+/// {
+///   "qualified_name": "Mutex::lock",
+///   "total_matches": 1,
+///   "definitions": [
+///     {
+///       "qualified_name": "tokio::sync::mutex::Mutex::lock",
+///       "kind": "method",
+///       "signature": "pub async fn lock(&self) -> MutexGuard<'_, T>",
+///       "start_line": 3,
+///       "end_line": 6,
+///       "contents": {
+///         "type": "text",
+///         "line_contents": "3:/// Locks this mutex\n4:pub async fn lock(&self) -> MutexGuard<'_, T> {\n...",
+///         "metadata": {"file_path": "tokio/src/sync/mutex.rs", "line_count": 120, "size": 211}
+///       }
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShowSymbolResponse {
+    /// The qualified name that was resolved
+    pub qualified_name: String,
+
+    /// Number of matching definitions, including those left out by the result limit
+    pub total_matches: usize,
+
+    /// The matching definitions, best match first
+    pub definitions: Vec<SymbolSourceEntry>,
+}
+
+/// One definition in a [`ShowSymbolResponse`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolSourceEntry {
+    /// The full qualified path of the definition, derived from its file and containers
+    pub qualified_name: String,
+
+    /// What kind of symbol it is
+    pub kind: SymbolKind,
+
+    /// The definition up to its body on a single line
+    pub signature: String,
+
+    /// First line of the source, including doc comments and attributes (1-based)
+    pub start_line: usize,
+
+    /// Last line of the definition (1-based, inclusive)
+    pub end_line: usize,
+
+    /// The source lines of the definition
+    pub contents: CompactFileContentsResponse,
+}
+
+impl ShowSymbolResponse {
+    /// Convert a SymbolSourceResult to the compact response
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - The resolved definitions
+    ///
+    /// # Returns
+    ///
+    /// A ShowSymbolResponse with the sources in compact file contents form
+    pub fn from_result(result: SymbolSourceResult) -> Self {
+        let definitions = result
+            .definitions
+            .into_iter()
+            .map(|definition| SymbolSourceEntry {
+                qualified_name: definition.qualified_name,
+                kind: definition.symbol.kind,
+                signature: definition.symbol.signature,
+                start_line: definition.source_start_line,
+                end_line: definition.symbol.end_line,
+                contents: CompactFileContentsResponse::from_file_contents(
+                    definition.contents,
+                    definition.symbol.file_path,
+                ),
+            })
+            .collect();
+
+        ShowSymbolResponse {
+            qualified_name: result.qualified_name,
+            total_matches: result.total_matches,
+            definitions,
+        }
+    }
+}
+
+impl MultiRepositoryCodeSearchResponse {
+    /// Convert the outcomes of a multi-repository search to the compact response
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The search pattern that was used
+    /// * `outcomes` - The outcome per repository, in the order to report them
+    ///
+    /// # Returns
+    ///
+    /// A MultiRepositoryCodeSearchResponse with one entry per outcome
+    pub fn from_outcomes(pattern: String, outcomes: Vec<RepositoryGrepOutcome>) -> Self {
+        let mut total_match_line_number = 0;
+        let repositories = outcomes
+            .into_iter()
+            .map(|outcome| match outcome.result {
+                Ok((search_result, _local_repo)) => {
+                    total_match_line_number += search_result.total_match_line_number;
+                    RepositoryCodeSearchEntry {
+                        repository_location: outcome.repository_location_str,
+                        result: Some(CompactCodeSearchResponse::from_search_result(search_result)),
+                        error: None,
+                    }
+                }
+                Err(error) => RepositoryCodeSearchEntry {
+                    repository_location: outcome.repository_location_str,
+                    result: None,
+                    error: Some(error),
+                },
+            })
+            .collect();
+
+        MultiRepositoryCodeSearchResponse {
+            pattern,
+            total_match_line_number,
+            repositories,
+        }
+    }
+}
+
 impl CompactIssueSearchResponse {
     /// Convert IssueSearchResults to CompactIssueSearchResponse
     ///
@@ -535,6 +797,7 @@ impl CompactCodeSearchResponse {
     ///     exclude_globs: None,
     ///     before_context: None,
     ///     after_context: None,
+    ///     next_cursor: None,
     /// };
     /// let compact = CompactCodeSearchResponse::from_search_result(search_result);
     /// ```
@@ -548,10 +811,7 @@ impl CompactCodeSearchResponse {
             let file_path = result_line.file_path.display().to_string();
             let line_content = format!("{}:{}", result_line.line_number, result_line.line_content);
 
-            file_groups
-                .entry(file_path)
-                .or_insert_with(Vec::new)
-                .push(line_content);
+            file_groups.entry(file_path).or_default().push(line_content);
         }
 
         // Convert grouped lines to CompactFileMatch structs
@@ -560,6 +820,7 @@ impl CompactCodeSearchResponse {
             .map(|(file_path, lines)| CompactFileMatch {
                 file_path,
                 lines: lines.join("\n"),
+                ranges: None,
             })
             .collect();
 
@@ -573,6 +834,72 @@ impl CompactCodeSearchResponse {
             exclude_globs: search_result.exclude_globs,
             before_context: search_result.before_context,
             after_context: search_result.after_context,
+            next_cursor: search_result.next_cursor,
+        }
+    }
+
+    /// Convert StructuralSearchResult to CompactCodeSearchResponse
+    ///
+    /// Groups the lines of the matched nodes by file like a grep result, in the order of
+    /// the matches, and adds the range and metavariables of each node to its file.
+    ///
+    /// # Arguments
+    ///
+    /// * `search_result` - The original StructuralSearchResult
+    ///
+    /// # Returns
+    ///
+    /// A CompactCodeSearchResponse whose `total_match_line_number` is the number of
+    /// matched nodes before pagination. Lines shared by several nodes appear once.
+    pub fn from_structural_result(search_result: StructuralSearchResult) -> Self {
+        let mut matches: Vec<CompactFileMatch> = Vec::new();
+        let mut file_lines: Vec<BTreeMap<usize, String>> = Vec::new();
+
+        for node in search_result.matches {
+            let same_file = matches
+                .last()
+                .is_some_and(|file| file.file_path == node.file_path);
+            if !same_file {
+                matches.push(CompactFileMatch {
+                    file_path: node.file_path.clone(),
+                    lines: String::new(),
+                    ranges: Some(Vec::new()),
+                });
+                file_lines.push(BTreeMap::new());
+            }
+            let lines = file_lines.last_mut().expect("a file was just added");
+            for (offset, line) in node.lines.into_iter().enumerate() {
+                lines.entry(node.start_line + offset).or_insert(line);
+            }
+            if let Some(ranges) = matches.last_mut().and_then(|file| file.ranges.as_mut()) {
+                ranges.push(MatchedNodeRange {
+                    start_line: node.start_line,
+                    start_column: node.start_column,
+                    end_line: node.end_line,
+                    end_column: node.end_column,
+                    metavariables: node.metavariables,
+                });
+            }
+        }
+        for (file, lines) in matches.iter_mut().zip(file_lines) {
+            file.lines = lines
+                .into_iter()
+                .map(|(line_number, content)| format!("{}:{}", line_number, content))
+                .collect::<Vec<_>>()
+                .join("\n");
+        }
+
+        CompactCodeSearchResponse {
+            total_match_line_number: search_result.total_matches,
+            matches,
+            pattern: search_result.pattern,
+            case_sensitive: true,
+            file_extensions: None,
+            include_globs: search_result.include_globs,
+            exclude_globs: search_result.exclude_globs,
+            before_context: None,
+            after_context: None,
+            next_cursor: None,
         }
     }
 }
@@ -0,0 +1,225 @@
+//! Tests for the boolean multi-pattern code search
+
+mod common;
+
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+use common::TestRepository;
+use gitcodes_mcp::gitcodes::repository_manager::RepositoryManager;
+use gitcodes_mcp::gitcodes::{BooleanSearchOptions, CodeSearchResult, LocalRepository};
+use gitcodes_mcp::services;
+
+/// Helper function to create a committed repository with:
+/// - `a.rs`: `impl Drop` and `unsafe`
+/// - `b.rs`: `impl Drop` and `unsafe`, but also `#[cfg(test)]`
+/// - `c.rs`: only `impl Drop`
+/// - `d.rs`: only `unsafe`
+/// - `e.md`: neither
+fn create_test_repository() -> (PathBuf, tempfile::TempDir) {
+    let repo = TestRepository::new();
+
+    let files = [
+        (
+            "a.rs",
+            "impl Drop for A {\n    fn drop(&mut self) {\n        unsafe { free(self.ptr) }\n    }\n}\n",
+        ),
+        (
+            "b.rs",
+            "impl Drop for B {}\nunsafe fn b() {}\n#[cfg(test)]\nmod tests {}\n",
+        ),
+        ("c.rs", "impl Drop for C {}\n"),
+        ("d.rs", "unsafe fn d() {}\n"),
+        ("e.md", "Nothing to see\n"),
+    ];
+    repo.write_all(&files).commit("Initial commit");
+
+    repo.into_parts()
+}
+
+fn to_strings(patterns: &[&str]) -> Vec<String> {
+    patterns.iter().map(|pattern| pattern.to_string()).collect()
+}
+
+/// Returns the lines of the result as `path:line_number`, context lines marked with `-`
+fn result_lines(result: &CodeSearchResult) -> Vec<String> {
+    result
+        .matches
+        .iter()
+        .map(|line| {
+            format!(
+                "{}{}{}",
+                line.file_path.display(),
+                if line.is_context { "-" } else { ":" },
+                line.line_number
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_all_of_with_none_of() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let repo = LocalRepository::new(repo_path);
+
+    let options = BooleanSearchOptions {
+        all_of: to_strings(&["impl Drop", "unsafe"]),
+        none_of: to_strings(&["#[cfg(test)]"]),
+        fixed_string: true,
+        ..Default::default()
+    };
+    let result = repo
+        .search_code_boolean(options.clone())
+        .await
+        .expect("Search failed");
+    assert_eq!(result_lines(&result), vec!["a.rs:1", "a.rs:3"]);
+    assert_eq!(result.total_match_line_number, 2);
+    assert_eq!(
+        result.pattern,
+        r##""impl Drop" AND "unsafe" AND NOT "#[cfg(test)]""##
+    );
+
+    // The same selection from the object database
+    let result = repo
+        .search_code_boolean(BooleanSearchOptions {
+            ref_name: Some("main".to_string()),
+            ..options.clone()
+        })
+        .await
+        .expect("Search at ref failed");
+    assert_eq!(result_lines(&result), vec!["a.rs:1", "a.rs:3"]);
+
+    // Context lines are merged with the matches they overlap
+    let result = repo
+        .search_code_boolean(BooleanSearchOptions {
+            after_context: Some(1),
+            ..options
+        })
+        .await
+        .expect("Search failed");
+    assert_eq!(
+        result_lines(&result),
+        vec!["a.rs:1", "a.rs-2", "a.rs:3", "a.rs-4"]
+    );
+    assert_eq!(result.total_match_line_number, 4);
+}
+
+#[tokio::test]
+async fn test_any_of_and_pagination() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let repo = LocalRepository::new(repo_path);
+
+    let options = BooleanSearchOptions {
+        any_of: to_strings(&["impl Drop", "unsafe"]),
+        none_of: to_strings(&[r"#\[cfg\(test\)\]"]),
+        ..Default::default()
+    };
+    let result = repo
+        .search_code_boolean(options.clone())
+        .await
+        .expect("Search failed");
+    assert_eq!(
+        result_lines(&result),
+        vec!["a.rs:1", "a.rs:3", "c.rs:1", "d.rs:1"]
+    );
+    assert_eq!(
+        result.pattern,
+        r##"("impl Drop" OR "unsafe") AND NOT "#\\[cfg\\(test\\)\\]""##
+    );
+
+    let result = repo
+        .search_code_boolean(BooleanSearchOptions {
+            skip: Some(1),
+            take: Some(2),
+            ..options
+        })
+        .await
+        .expect("Search failed");
+    assert_eq!(result_lines(&result), vec!["a.rs:3", "c.rs:1"]);
+    assert_eq!(result.total_match_line_number, 4);
+
+    // all_of and any_of combine: files with `impl Drop` and either `unsafe` or `#[cfg`
+    let result = repo
+        .search_code_boolean(BooleanSearchOptions {
+            all_of: to_strings(&["impl Drop"]),
+            any_of: to_strings(&["unsafe", "#[cfg"]),
+            fixed_string: true,
+            include_globs: Some(vec!["b.*".to_string()]),
+            ..Default::default()
+        })
+        .await
+        .expect("Search failed");
+    assert_eq!(result_lines(&result), vec!["b.rs:1", "b.rs:2", "b.rs:3"]);
+}
+
+#[tokio::test]
+async fn test_boolean_search_errors_and_empty_results() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let repo = LocalRepository::new(repo_path.clone());
+
+    let err = repo
+        .search_code_boolean(BooleanSearchOptions {
+            none_of: to_strings(&["unsafe"]),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert!(
+        err.contains("at least one pattern"),
+        "unexpected error: {}",
+        err
+    );
+
+    let err = repo
+        .search_code_boolean(BooleanSearchOptions {
+            all_of: to_strings(&["unsafe", ""]),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert!(
+        err.contains("must not be empty"),
+        "unexpected error: {}",
+        err
+    );
+
+    // No file matches both, so the negative pattern is never needed
+    let result = repo
+        .search_code_boolean(BooleanSearchOptions {
+            all_of: to_strings(&["fn d", "struct"]),
+            none_of: to_strings(&["unsafe"]),
+            ..Default::default()
+        })
+        .await
+        .expect("Search failed");
+    assert!(result.matches.is_empty());
+    assert_eq!(result.total_match_line_number, 0);
+
+    // Through the service, which defaults the pagination like grep does
+    let cache_dir = tempdir().expect("Failed to create temporary directory");
+    let manager = RepositoryManager::new(None, Some(cache_dir.path().to_path_buf()))
+        .expect("Failed to create RepositoryManager");
+    let (result, _local_repo) = services::perform_boolean_grep_in_repository(
+        &manager,
+        services::BooleanGrepParams {
+            repository_location_str: repo_path.display().to_string(),
+            ref_name: None,
+            all_of: to_strings(&["drop"]),
+            any_of: Vec::new(),
+            none_of: to_strings(&["cfg"]),
+            case_sensitive: false,
+            word_regexp: true,
+            include_globs: None,
+            exclude_dirs: None,
+            before_context: None,
+            after_context: None,
+            skip: None,
+            take: None,
+            match_content_omit_num: None,
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("Search failed");
+    assert_eq!(result_lines(&result), vec!["a.rs:1", "a.rs:2", "c.rs:1"]);
+}
@@ -0,0 +1,196 @@
+//! Tests for using git bundle files as repository locations
+
+mod common;
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tempfile::tempdir;
+
+use common::{TestRepository, git};
+use gitcodes_mcp::gitcodes::repository_manager::{RepositoryLocation, RepositoryManager};
+use gitcodes_mcp::services;
+
+/// Helper function to create a repository with a `main` and a `feature` branch and an
+/// annotated tag, and to bundle all of it into `repo.bundle`
+fn create_test_bundle() -> (PathBuf, PathBuf, tempfile::TempDir) {
+    let repo = TestRepository::init(Some("source"), "main");
+
+    repo.write("src/lib.rs", "pub fn shipped() {}\n")
+        .commit("Initial commit")
+        .git(&["tag", "-a", "v1.0.0", "-m", "Release 1.0.0"]);
+
+    repo.git(&["checkout", "-b", "feature"]);
+    repo.write("feature.txt", "fn feature_only() {}\n")
+        .commit("Add feature");
+    repo.git(&["checkout", "main"]);
+    repo.write("README.md", "# Snapshot\n").commit("Add readme");
+
+    let (repo_path, temp_dir) = repo.into_parts();
+    let bundle_path = temp_dir.path().join("repo.bundle");
+    git(
+        &repo_path,
+        &["bundle", "create", bundle_path.to_str().unwrap(), "--all"],
+    );
+
+    (repo_path, bundle_path, temp_dir)
+}
+
+fn create_test_manager(cache_dir: &Path) -> RepositoryManager {
+    RepositoryManager::new(None, Some(cache_dir.to_path_buf()))
+        .expect("Failed to create RepositoryManager")
+}
+
+#[test]
+fn test_bundle_location_parsing() {
+    let (_repo_path, bundle_path, temp_dir) = create_test_bundle();
+
+    let location = RepositoryLocation::from_str(&format!("bundle:{}", bundle_path.display()))
+        .expect("Failed to parse bundle location");
+    match location {
+        RepositoryLocation::Bundle(path) => assert_eq!(path, bundle_path),
+        other => panic!("Expected a bundle location, got {:?}", other),
+    }
+
+    let err = RepositoryLocation::from_str("bundle:repo.bundle").unwrap_err();
+    assert!(
+        err.contains("must be absolute"),
+        "unexpected error: {}",
+        err
+    );
+
+    let missing = temp_dir.path().join("missing.bundle");
+    let err = RepositoryLocation::from_str(&format!("bundle:{}", missing.display())).unwrap_err();
+    assert!(err.contains("not found"), "unexpected error: {}", err);
+}
+
+#[tokio::test]
+async fn test_bundle_is_unpacked_and_served_like_a_clone() {
+    let (repo_path, bundle_path, _temp_dir) = create_test_bundle();
+    let cache_dir = tempdir().expect("Failed to create temporary directory");
+    let manager = create_test_manager(cache_dir.path());
+    let location_str = format!("bundle:{}", bundle_path.display());
+    let location = RepositoryLocation::from_str(&location_str).unwrap();
+
+    let local_repo = manager
+        .prepare_repository(&location, None)
+        .await
+        .expect("Failed to unpack bundle");
+    let repo_dir = local_repo.get_repository_dir().clone();
+    // Unpacked into the manager's cache directory
+    assert_eq!(repo_dir.parent(), Some(cache_dir.path()));
+
+    // The default branch is checked out
+    assert_eq!(
+        std::fs::read_to_string(repo_dir.join("README.md")).unwrap(),
+        "# Snapshot\n"
+    );
+    assert!(repo_dir.join("src/lib.rs").is_file());
+    assert!(!repo_dir.join("feature.txt").exists());
+    assert_eq!(
+        git(&repo_dir, &["rev-parse", "--abbrev-ref", "HEAD"]),
+        "main"
+    );
+    assert_eq!(git(&repo_dir, &["status", "--porcelain"]), "");
+
+    // All refs of the bundle are available, including annotated tags
+    let (refs, _) = manager
+        .list_repository_refs(&location_str)
+        .await
+        .expect("Failed to list refs");
+    let branches: Vec<&str> = refs.branches.iter().map(|r| r.name.as_str()).collect();
+    assert!(branches.contains(&"main") && branches.contains(&"feature"));
+    let tag = refs.tags.iter().find(|r| r.name == "v1.0.0").unwrap();
+    assert_eq!(tag.commit_id, git(&repo_path, &["rev-parse", "v1.0.0^{}"]));
+
+    // Tools work on the bundle location, also for refs other than the checked out one
+    let grep = |ref_name: Option<&str>| services::GrepParams {
+        repository_location_str: location_str.clone(),
+        pattern: "fn feature_only".to_string(),
+        ref_name: ref_name.map(str::to_string),
+        case_sensitive: false,
+        file_extensions: None,
+        include_globs: None,
+        exclude_dirs: None,
+        before_context: None,
+        after_context: None,
+        skip: None,
+        take: None,
+        match_content_omit_num: None,
+        ..Default::default()
+    };
+    let (result, _) = services::perform_grep_in_repository(&manager, grep(None))
+        .await
+        .expect("Search failed");
+    assert_eq!(result.total_match_line_number, 0);
+    let (result, _) = services::perform_grep_in_repository(&manager, grep(Some("feature")))
+        .await
+        .expect("Search failed");
+    assert_eq!(result.total_match_line_number, 1);
+
+    // Preparing again reuses the unpacked repository
+    let again = manager
+        .prepare_repository(&location, None)
+        .await
+        .expect("Failed to reuse unpacked bundle");
+    assert_eq!(again.get_repository_dir(), &repo_dir);
+    assert_eq!(
+        manager
+            .get_local_path_for_repository(&location)
+            .await
+            .expect("Unpacked bundle not found")
+            .get_repository_dir(),
+        &repo_dir
+    );
+
+    local_repo.cleanup().expect("Failed to clean up");
+}
+
+#[tokio::test]
+async fn test_unsupported_bundles_are_rejected() {
+    let (repo_path, _bundle_path, temp_dir) = create_test_bundle();
+    let cache_dir = tempdir().expect("Failed to create temporary directory");
+    let manager = create_test_manager(cache_dir.path());
+
+    // An incremental bundle requires commits it doesn't contain
+    let incremental = temp_dir.path().join("incremental.bundle");
+    git(
+        &repo_path,
+        &[
+            "bundle",
+            "create",
+            incremental.to_str().unwrap(),
+            "v1.0.0..main",
+        ],
+    );
+    let location =
+        RepositoryLocation::from_str(&format!("bundle:{}", incremental.display())).unwrap();
+    let err = manager
+        .prepare_repository(&location, None)
+        .await
+        .unwrap_err();
+    assert!(
+        err.contains("Incremental git bundles are not supported"),
+        "unexpected error: {}",
+        err
+    );
+    let err = manager
+        .get_local_path_for_repository(&location)
+        .await
+        .unwrap_err();
+    assert!(err.contains("not unpacked"), "unexpected error: {}", err);
+
+    // Any other file is not a bundle at all
+    let not_a_bundle = temp_dir.path().join("notes.bundle");
+    std::fs::write(&not_a_bundle, "just some text\n").unwrap();
+    let location =
+        RepositoryLocation::from_str(&format!("bundle:{}", not_a_bundle.display())).unwrap();
+    let err = manager
+        .prepare_repository(&location, None)
+        .await
+        .unwrap_err();
+    assert!(
+        err.contains("Not a git bundle"),
+        "unexpected error: {}",
+        err
+    );
+}
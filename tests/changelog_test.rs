@@ -0,0 +1,315 @@
+//! Tests for changelog generation between two refs
+//!
+//! These tests build a repository with a release tag followed by a mix of conventional
+//! and free-form commits (plus a merged branch), then verify the grouping, breaking
+//! change detection and reference extraction.
+
+mod common;
+
+use std::path::PathBuf;
+
+use common::TestRepository;
+use gitcodes_mcp::gitcodes::{Changelog, LocalRepository, OTHER_CHANGES_TYPE};
+use gitcodes_mcp::services::{self, ChangelogServiceParams};
+
+/// Writes a file and commits it with the given message and date
+fn commit_file(repo: &TestRepository, file: &str, message: &str, date: &str) {
+    repo.write(file, message).commit_at(date, message);
+}
+
+/// Helper function to create a repository with a `v1.0.0` tag followed by:
+///
+/// * `feat(parser): support nested tables (#12)`
+/// * `fix: handle empty input` (body closes #15 and mentions `other/repo#99`)
+/// * `Update README`
+/// * `refactor!: rename Config to Settings`
+/// * `chore: bump dependencies` (body has a `BREAKING CHANGE:` footer)
+/// * a branch with `docs: add guide` merged as "Merge pull request #20 ..."
+fn create_test_repository() -> (PathBuf, tempfile::TempDir) {
+    let repo = TestRepository::new();
+
+    commit_file(&repo, "a.txt", "Initial commit", "2024-01-01T10:00:00Z");
+    repo.tag("v1.0.0");
+
+    commit_file(
+        &repo,
+        "b.txt",
+        "feat(parser): support nested tables (#12)",
+        "2024-01-02T10:00:00Z",
+    );
+    commit_file(
+        &repo,
+        "c.txt",
+        "fix: handle empty input\n\nCloses #15, see also other/repo#99.",
+        "2024-01-03T10:00:00Z",
+    );
+    commit_file(&repo, "README.md", "Update README", "2024-01-04T10:00:00Z");
+    commit_file(
+        &repo,
+        "d.txt",
+        "refactor!: rename Config to Settings",
+        "2024-01-05T10:00:00Z",
+    );
+    commit_file(
+        &repo,
+        "e.txt",
+        "chore: bump dependencies\n\nBREAKING CHANGE: the minimum Rust version is now 1.80",
+        "2024-01-06T10:00:00Z",
+    );
+
+    repo.git(&["checkout", "-b", "guide"]);
+    commit_file(&repo, "guide.md", "docs: add guide", "2024-01-07T10:00:00Z");
+    repo.git(&["checkout", "main"]);
+    let date = "2024-01-08T10:00:00Z";
+    common::git_with_env(
+        repo.path(),
+        &[("GIT_AUTHOR_DATE", date), ("GIT_COMMITTER_DATE", date)],
+        &[
+            "merge",
+            "--no-ff",
+            "guide",
+            "-m",
+            "Merge pull request #20 from user/guide",
+        ],
+    );
+    repo.tag("v1.1.0");
+
+    repo.into_parts()
+}
+
+fn group_types(changelog: &Changelog) -> Vec<&str> {
+    changelog
+        .groups
+        .iter()
+        .map(|group| group.commit_type.as_str())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_changelog_groups_by_type() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path);
+
+    let changelog = local_repo
+        .changelog_between("v1.0.0", "v1.1.0", false)
+        .await
+        .expect("Failed to generate changelog");
+
+    assert_eq!(changelog.from_ref, "v1.0.0");
+    assert_eq!(changelog.to_ref, "v1.1.0");
+    assert_eq!(changelog.total_commits, 6);
+    assert_eq!(
+        group_types(&changelog),
+        vec![
+            "feat",
+            "fix",
+            "refactor",
+            "docs",
+            "chore",
+            OTHER_CHANGES_TYPE
+        ]
+    );
+
+    let features = &changelog.groups[0];
+    assert_eq!(features.title, "Features");
+    assert_eq!(features.commits.len(), 1);
+    let feature = &features.commits[0];
+    assert_eq!(feature.scope.as_deref(), Some("parser"));
+    assert_eq!(feature.description, "support nested tables (#12)");
+    assert_eq!(feature.summary, "feat(parser): support nested tables (#12)");
+    assert_eq!(feature.author_name, "Test User");
+    assert!(feature.date.starts_with("2024-01-02"));
+    assert!(!feature.breaking);
+
+    let other = changelog.groups.last().unwrap();
+    assert_eq!(other.title, "Other Changes");
+    assert_eq!(other.commits[0].description, "Update README");
+    assert_eq!(other.commits[0].scope, None);
+}
+
+#[tokio::test]
+async fn test_changelog_breaking_changes_and_references() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path);
+
+    let changelog = local_repo
+        .changelog_between("v1.0.0", "v1.1.0", false)
+        .await
+        .expect("Failed to generate changelog");
+
+    // Both the `!` marker and the footer flag a breaking change
+    assert_eq!(changelog.breaking_changes, 2);
+    let breaking: Vec<&str> = changelog
+        .groups
+        .iter()
+        .flat_map(|group| group.commits.iter())
+        .filter(|entry| entry.breaking)
+        .map(|entry| entry.description.as_str())
+        .collect();
+    assert_eq!(
+        breaking,
+        vec!["rename Config to Settings", "bump dependencies"]
+    );
+
+    // Cross-repository references are not collected
+    let fix = &changelog.groups[1].commits[0];
+    assert_eq!(fix.references, vec![15]);
+    let numbers: Vec<u64> = changelog
+        .references
+        .iter()
+        .map(|reference| reference.number)
+        .collect();
+    assert_eq!(numbers, vec![12, 15]);
+    assert!(
+        changelog
+            .references
+            .iter()
+            .all(|reference| reference.title.is_none())
+    );
+}
+
+#[tokio::test]
+async fn test_changelog_merges_and_ranges() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path);
+
+    let with_merges = local_repo
+        .changelog_between("v1.0.0", "v1.1.0", true)
+        .await
+        .expect("Failed to generate changelog");
+    assert_eq!(with_merges.total_commits, 7);
+    let other = with_merges.groups.last().unwrap();
+    assert_eq!(
+        other.commits[0].summary,
+        "Merge pull request #20 from user/guide"
+    );
+    let numbers: Vec<u64> = with_merges
+        .references
+        .iter()
+        .map(|reference| reference.number)
+        .collect();
+    assert_eq!(numbers, vec![12, 15, 20]);
+
+    // An empty range yields an empty changelog
+    let empty = local_repo
+        .changelog_between("v1.1.0", "main", false)
+        .await
+        .expect("Failed to generate changelog");
+    assert_eq!(empty.total_commits, 0);
+    assert!(empty.groups.is_empty());
+
+    let missing = local_repo
+        .changelog_between("v0.9.0", "v1.1.0", false)
+        .await;
+    assert!(missing.is_err());
+}
+
+#[tokio::test]
+async fn test_changelog_unknown_types_are_other_changes() {
+    let repo = TestRepository::new();
+    repo.write("a.txt", "a\n")
+        .commit("Initial commit")
+        .tag("base");
+    for (file, message) in [
+        ("b.txt", "Fix(io): close handles"),
+        ("c.txt", "WIP: try a cache"),
+        ("d.txt", "Update: bump version"),
+        ("e.txt", "feature: dark mode"),
+        ("f.txt", "docs: usage"),
+    ] {
+        repo.write(file, message).commit(message);
+    }
+    let local_repo = LocalRepository::new(repo.path().to_path_buf());
+
+    let changelog = local_repo
+        .changelog_between("base", "main", false)
+        .await
+        .expect("Failed to generate changelog");
+    assert_eq!(
+        group_types(&changelog),
+        vec!["fix", "docs", OTHER_CHANGES_TYPE]
+    );
+
+    let fix = &changelog.groups[0].commits[0];
+    assert_eq!(fix.scope.as_deref(), Some("io"));
+    assert_eq!(fix.description, "close handles");
+
+    // Words that aren't conventional commit types keep the whole summary
+    let other = &changelog.groups[2];
+    assert_eq!(other.title, "Other Changes");
+    let descriptions: Vec<&str> = other
+        .commits
+        .iter()
+        .map(|entry| entry.description.as_str())
+        .collect();
+    assert_eq!(
+        descriptions,
+        vec![
+            "feature: dark mode",
+            "Update: bump version",
+            "WIP: try a cache"
+        ]
+    );
+    assert!(other.commits.iter().all(|entry| entry.scope.is_none()));
+}
+
+#[tokio::test]
+async fn test_changelog_service_without_github_remote() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let manager = gitcodes_mcp::gitcodes::repository_manager::RepositoryManager::new(None, None)
+        .expect("Failed to create repository manager");
+
+    let params = ChangelogServiceParams {
+        repository_location_str: repo_path.to_string_lossy().to_string(),
+        from_ref: "v1.0.0".to_string(),
+        to_ref: "HEAD".to_string(),
+        include_merges: None,
+        enrich_references: Some(true),
+    };
+
+    // The changelog is still returned when references can't be looked up
+    let (changelog, _local_repo) = services::changelog_between(&manager, params)
+        .await
+        .expect("Changelog service failed");
+    assert_eq!(changelog.total_commits, 6);
+    assert!(
+        changelog
+            .enrichment_error
+            .as_deref()
+            .unwrap()
+            .contains("not hosted on GitHub")
+    );
+}
+
+#[tokio::test]
+async fn test_changelog_service_fetches_refs_missing_from_a_clone() {
+    let (upstream_path, _upstream_dir) = create_test_repository();
+    let clone_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let clone_path = clone_dir.path().join("clone");
+    common::git(
+        clone_dir.path(),
+        &["clone", &upstream_path.to_string_lossy(), "clone"],
+    );
+
+    // Released upstream after the clone was made
+    common::write_file(&upstream_path, "f.txt", "feat: add export");
+    common::git(&upstream_path, &["add", "f.txt"]);
+    common::git(&upstream_path, &["commit", "-m", "feat: add export"]);
+    common::git(&upstream_path, &["tag", "v2.0.0"]);
+
+    let manager = gitcodes_mcp::gitcodes::repository_manager::RepositoryManager::new(None, None)
+        .expect("Failed to create repository manager");
+    let params = ChangelogServiceParams {
+        repository_location_str: clone_path.to_string_lossy().to_string(),
+        from_ref: "v1.1.0".to_string(),
+        to_ref: "v2.0.0".to_string(),
+        include_merges: None,
+        enrich_references: None,
+    };
+
+    let (changelog, _local_repo) = services::changelog_between(&manager, params)
+        .await
+        .expect("Changelog service failed");
+    assert_eq!(changelog.total_commits, 1);
+    assert_eq!(group_types(&changelog), vec!["feat"]);
+}
@@ -0,0 +1,183 @@
+//! Helpers shared by the integration tests that build git repositories
+//!
+//! Every test crate uses a different subset of these helpers.
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+use tempfile::{TempDir, tempdir};
+
+use gitcodes_mcp::gitcodes::repository_manager::RepositoryLocation;
+use gitcodes_mcp::gitcodes::{CodeSearchParams, LocalRepository};
+
+/// Runs a git command in the given directory, returning its trimmed output
+pub fn git(repo_path: &Path, args: &[&str]) -> String {
+    git_with_env(repo_path, &[], args)
+}
+
+/// Runs a git command with extra environment variables, such as fixed dates or authors
+pub fn git_with_env(repo_path: &Path, env: &[(&str, &str)], args: &[&str]) -> String {
+    let output = std::process::Command::new("git")
+        .current_dir(repo_path)
+        .args(args)
+        .envs(env.iter().copied())
+        .output()
+        .expect("Failed to run git");
+    assert!(
+        output.status.success(),
+        "git {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+/// Writes a file relative to the repository root, creating its parent directories
+pub fn write_file(repo_path: &Path, file: &str, content: &str) {
+    let path = repo_path.join(file);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, content).unwrap();
+}
+
+/// Parameters for searching the worktree of the repository at `repo_path`, with every option off
+///
+/// Meant for struct update syntax, e.g.
+/// `CodeSearchParams { take: Some(1), ..code_search_params(repo_path, "fn") }`.
+pub fn code_search_params(repo_path: &Path, pattern: &str) -> CodeSearchParams {
+    CodeSearchParams {
+        repository_location: RepositoryLocation::LocalPath(LocalRepository::new(
+            repo_path.to_path_buf(),
+        )),
+        ref_name: None,
+        pattern: pattern.to_string(),
+        case_sensitive: false,
+        fixed_string: false,
+        word_regexp: false,
+        multiline: false,
+        file_extensions: None,
+        include_globs: None,
+        exclude_dirs: None,
+        changed_files_only: false,
+        use_index: false,
+        before_context: None,
+        after_context: None,
+        context_mode: None,
+        max_context_lines: None,
+        skip: None,
+        take: None,
+        match_content_omit_num: None,
+    }
+}
+
+/// A git repository in a temporary directory, built up commit by commit
+///
+/// ```ignore
+/// let repo = TestRepository::new();
+/// repo.write("src/lib.rs", "pub fn f() {}\n")
+///     .commit("Initial commit")
+///     .tag("v1.0.0");
+/// let (repo_path, _temp_dir) = repo.into_parts();
+/// ```
+pub struct TestRepository {
+    path: PathBuf,
+    temp_dir: TempDir,
+}
+
+impl TestRepository {
+    /// Creates an empty repository on `main` at the root of a new temporary directory
+    pub fn new() -> Self {
+        Self::init(None, "main")
+    }
+
+    /// Creates an empty repository with the given initial branch
+    ///
+    /// # Parameters
+    ///
+    /// * `subdirectory` - Directory of the repository inside the temporary directory,
+    ///   leaving room for other files next to it; `None` uses the temporary directory itself
+    /// * `branch` - Name of the initial branch
+    pub fn init(subdirectory: Option<&str>, branch: &str) -> Self {
+        let temp_dir = tempdir().expect("Failed to create temporary directory");
+        let path = match subdirectory {
+            Some(subdirectory) => temp_dir.path().join(subdirectory),
+            None => temp_dir.path().to_path_buf(),
+        };
+        std::fs::create_dir_all(&path).unwrap();
+
+        git(&path, &["init", "-b", branch]);
+        git(&path, &["config", "user.name", "Test User"]);
+        git(&path, &["config", "user.email", "test@example.com"]);
+
+        Self { path, temp_dir }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Runs a git command in the repository, returning its trimmed output
+    pub fn git(&self, args: &[&str]) -> String {
+        git(&self.path, args)
+    }
+
+    /// Writes a file relative to the repository root, creating its parent directories
+    pub fn write(&self, file: &str, content: &str) -> &Self {
+        write_file(&self.path, file, content);
+        self
+    }
+
+    /// Writes all the given `(file, content)` pairs
+    pub fn write_all(&self, files: &[(&str, &str)]) -> &Self {
+        for (file, content) in files {
+            self.write(file, content);
+        }
+        self
+    }
+
+    /// Stages every change, including deletions, and commits it
+    pub fn commit(&self, message: &str) -> &Self {
+        self.commit_with_env(&[], message)
+    }
+
+    /// Stages every change and commits it with fixed author and committer dates
+    pub fn commit_at(&self, date: &str, message: &str) -> &Self {
+        self.commit_with_env(
+            &[("GIT_AUTHOR_DATE", date), ("GIT_COMMITTER_DATE", date)],
+            message,
+        )
+    }
+
+    /// Stages every change and commits it as the given `(name, email)` author at a fixed date
+    pub fn commit_as(&self, author: (&str, &str), date: &str, message: &str) -> &Self {
+        self.commit_with_env(
+            &[
+                ("GIT_AUTHOR_NAME", author.0),
+                ("GIT_AUTHOR_EMAIL", author.1),
+                ("GIT_AUTHOR_DATE", date),
+                ("GIT_COMMITTER_DATE", date),
+            ],
+            message,
+        )
+    }
+
+    fn commit_with_env(&self, env: &[(&str, &str)], message: &str) -> &Self {
+        self.git(&["add", "-A"]);
+        git_with_env(&self.path, env, &["commit", "-m", message]);
+        self
+    }
+
+    /// Creates a lightweight tag on `HEAD`
+    pub fn tag(&self, name: &str) -> &Self {
+        self.git(&["tag", name]);
+        self
+    }
+
+    /// Id of the commit `HEAD` points to
+    pub fn head(&self) -> String {
+        self.git(&["rev-parse", "HEAD"])
+    }
+
+    /// The repository path and the temporary directory that keeps it alive
+    pub fn into_parts(self) -> (PathBuf, TempDir) {
+        (self.path, self.temp_dir)
+    }
+}
@@ -49,6 +49,7 @@ fn test_compact_code_search_response_conversion() {
         exclude_globs: Some(vec!["**/target/**".to_string(), "**/.git/**".to_string()]),
         before_context: Some(0),
         after_context: Some(1),
+        ..Default::default()
     };
 
     // Convert to compact format
@@ -57,7 +58,7 @@ fn test_compact_code_search_response_conversion() {
     // Verify the conversion
     assert_eq!(compact.total_match_line_number, 4);
     assert_eq!(compact.pattern, "main");
-    assert_eq!(compact.case_sensitive, false);
+    assert!(!compact.case_sensitive);
     assert_eq!(compact.file_extensions, None);
     assert_eq!(
         compact.include_globs,
@@ -127,6 +128,7 @@ fn test_compact_code_search_response_serialization() {
         exclude_globs: None,
         before_context: None,
         after_context: None,
+        ..Default::default()
     };
 
     let compact = CompactCodeSearchResponse::from_search_result(search_result);
@@ -148,7 +150,7 @@ fn test_compact_code_search_response_serialization() {
 
     assert_eq!(deserialized.total_match_line_number, 1);
     assert_eq!(deserialized.pattern, "Example");
-    assert_eq!(deserialized.case_sensitive, true);
+    assert!(deserialized.case_sensitive);
     assert_eq!(deserialized.matches.len(), 1);
     assert_eq!(deserialized.matches[0].file_path, "example.rs");
     assert_eq!(deserialized.matches[0].lines, "1:// Example file");
@@ -192,6 +194,7 @@ fn test_compact_code_search_response_json_format() {
         exclude_globs: Some(vec!["**/target/**".to_string()]),
         before_context: Some(0),
         after_context: Some(1),
+        ..Default::default()
     };
 
     let compact = CompactCodeSearchResponse::from_search_result(search_result);
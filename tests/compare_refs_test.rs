@@ -0,0 +1,180 @@
+//! Tests for comparing two refs (merge-base and ahead/behind counts)
+
+mod common;
+
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+use common::{TestRepository, git};
+use gitcodes_mcp::gitcodes::{CompareStatus, LocalRepository, RefComparison};
+use gitcodes_mcp::services::{self, CompareRefsServiceParams};
+
+/// Writes a file and commits it with the given message
+fn commit(repo_path: &Path, file: &str, message: &str) {
+    common::write_file(repo_path, file, message);
+    git(repo_path, &["add", file]);
+    git(repo_path, &["commit", "-m", message]);
+}
+
+/// Helper function to create a repository where `feature` (2 commits) and `main`
+/// (1 commit) diverged after the commit tagged `base`, plus an unrelated `orphan` branch
+fn create_test_repository() -> (PathBuf, tempfile::TempDir) {
+    let repo = TestRepository::new();
+    let repo_path = repo.path();
+
+    commit(repo_path, "a.txt", "Initial commit");
+    commit(repo_path, "b.txt", "Second commit");
+    repo.tag("base");
+
+    repo.git(&["checkout", "-b", "feature"]);
+    commit(repo_path, "f1.txt", "Feature part 1");
+    commit(repo_path, "f2.txt", "Feature part 2");
+
+    repo.git(&["checkout", "main"]);
+    commit(repo_path, "m1.txt", "Main fix");
+
+    repo.git(&["checkout", "--orphan", "orphan"]);
+    commit(repo_path, "o.txt", "Unrelated root");
+    repo.git(&["checkout", "main"]);
+
+    repo.into_parts()
+}
+
+fn summaries(commits: &[gitcodes_mcp::gitcodes::CommitSummary]) -> Vec<&str> {
+    commits
+        .iter()
+        .map(|commit| commit.summary.as_str())
+        .collect()
+}
+
+async fn compare(local_repo: &LocalRepository, base: &str, head: &str) -> RefComparison {
+    local_repo
+        .compare_refs(base, head, None)
+        .await
+        .unwrap_or_else(|e| panic!("Failed to compare {}...{}: {}", base, head, e))
+}
+
+#[tokio::test]
+async fn test_compare_diverged_branches() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let base_commit = git(&repo_path, &["rev-parse", "base"]);
+    let local_repo = LocalRepository::new(repo_path);
+
+    let comparison = compare(&local_repo, "main", "feature").await;
+    assert_eq!(comparison.status, CompareStatus::Diverged);
+    assert_eq!(comparison.merge_base.as_deref(), Some(base_commit.as_str()));
+    assert_eq!(comparison.ahead_by, 2);
+    assert_eq!(comparison.behind_by, 1);
+    assert_eq!(
+        summaries(&comparison.ahead_commits),
+        vec!["Feature part 2", "Feature part 1"]
+    );
+    assert_eq!(summaries(&comparison.behind_commits), vec!["Main fix"]);
+    assert!(!comparison.truncated);
+
+    let limited = local_repo
+        .compare_refs("main", "feature", Some(1))
+        .await
+        .expect("Failed to compare refs");
+    assert_eq!(limited.ahead_by, 2);
+    assert_eq!(limited.ahead_commits.len(), 1);
+    assert!(limited.truncated);
+}
+
+#[tokio::test]
+async fn test_compare_statuses() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path);
+
+    let identical = compare(&local_repo, "main", "main").await;
+    assert_eq!(identical.status, CompareStatus::Identical);
+    assert_eq!((identical.ahead_by, identical.behind_by), (0, 0));
+
+    let ahead = compare(&local_repo, "base", "feature").await;
+    assert_eq!(ahead.status, CompareStatus::Ahead);
+    assert_eq!((ahead.ahead_by, ahead.behind_by), (2, 0));
+
+    let behind = compare(&local_repo, "feature", "base").await;
+    assert_eq!(behind.status, CompareStatus::Behind);
+    assert_eq!((behind.ahead_by, behind.behind_by), (0, 2));
+
+    let unrelated = compare(&local_repo, "main", "orphan").await;
+    assert_eq!(unrelated.status, CompareStatus::Diverged);
+    assert_eq!(unrelated.merge_base, None);
+    assert_eq!((unrelated.ahead_by, unrelated.behind_by), (1, 3));
+
+    assert!(
+        local_repo
+            .compare_refs("main", "missing", None)
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_compare_remote_tracking_branches() {
+    let (upstream_path, _upstream_dir) = create_test_repository();
+
+    // A fork: a clone whose origin is the upstream, with a local commit on main
+    let fork_dir = tempdir().expect("Failed to create temporary directory");
+    let fork_path = fork_dir.path().join("fork");
+    git(
+        fork_dir.path(),
+        &["clone", &upstream_path.to_string_lossy(), "fork"],
+    );
+    git(&fork_path, &["config", "user.name", "Test User"]);
+    git(&fork_path, &["config", "user.email", "test@example.com"]);
+    git(
+        &fork_path,
+        &[
+            "remote",
+            "add",
+            "upstream",
+            &upstream_path.to_string_lossy(),
+        ],
+    );
+    git(&fork_path, &["fetch", "upstream"]);
+    commit(&fork_path, "fork.txt", "Fork change");
+
+    let local_repo = LocalRepository::new(fork_path);
+
+    // `feature` only exists as a remote-tracking branch in the clone
+    let by_name = compare(&local_repo, "main", "feature").await;
+    let by_full_ref = compare(&local_repo, "main", "refs/remotes/origin/feature").await;
+    assert_eq!(by_name.head_commit, by_full_ref.head_commit);
+    assert_eq!((by_name.ahead_by, by_name.behind_by), (2, 2));
+
+    let fork = compare(&local_repo, "upstream/main", "main").await;
+    assert_eq!(fork.status, CompareStatus::Ahead);
+    assert_eq!(summaries(&fork.ahead_commits), vec!["Fork change"]);
+}
+
+#[tokio::test]
+async fn test_compare_service_fetches_refs_missing_from_a_clone() {
+    let (upstream_path, _upstream_dir) = create_test_repository();
+    let clone_dir = tempdir().expect("Failed to create temporary directory");
+    let clone_path = clone_dir.path().join("clone");
+    git(
+        clone_dir.path(),
+        &["clone", &upstream_path.to_string_lossy(), "clone"],
+    );
+
+    // Pushed upstream after the clone was made
+    git(&upstream_path, &["checkout", "-b", "hotfix", "main"]);
+    commit(&upstream_path, "h.txt", "Hotfix");
+
+    let manager = gitcodes_mcp::gitcodes::repository_manager::RepositoryManager::new(None, None)
+        .expect("Failed to create repository manager");
+    let params = CompareRefsServiceParams {
+        repository_location_str: clone_path.to_string_lossy().to_string(),
+        base_ref: "main".to_string(),
+        head_ref: "hotfix".to_string(),
+        max_commits: None,
+    };
+
+    let (comparison, _local_repo) = services::compare_refs(&manager, params)
+        .await
+        .expect("Compare service failed");
+    assert_eq!(comparison.status, CompareStatus::Ahead);
+    assert_eq!(summaries(&comparison.ahead_commits), vec!["Hotfix"]);
+}
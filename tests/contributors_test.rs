@@ -0,0 +1,236 @@
+//! Tests for contributor statistics
+//!
+//! These tests build a repository with three people committing at fixed dates, one of
+//! them under two email addresses merged by a `.mailmap`.
+
+mod common;
+
+use std::path::PathBuf;
+
+use common::TestRepository;
+use gitcodes_mcp::gitcodes::{ContributorsOptions, ContributorsReport, LocalRepository};
+use gitcodes_mcp::services::{self, ContributorsServiceParams};
+
+/// Writes a file and commits it as the given author at the given date
+fn commit_as(repo: &TestRepository, author: (&str, &str), date: &str, file: &str, content: &str) {
+    repo.write(file, content)
+        .commit_as(author, date, &format!("Update {}", file));
+}
+
+const ALICE: (&str, &str) = ("Alice", "alice@example.com");
+const ALICE_WORK: (&str, &str) = ("alice", "alice@work.example.com");
+const BOB: (&str, &str) = ("Bob", "bob@example.com");
+const CAROL: (&str, &str) = ("Carol", "carol@example.com");
+
+/// Helper function to create a repository with a `v1.0.0` tag after the first two commits:
+///
+/// * 2023-01-10 Alice adds 3 lines to `a.txt`
+/// * 2023-06-01 Bob adds 2 lines to `b.txt` (tagged `v1.0.0`)
+/// * 2024-02-01 Alice (work address) changes 1 line of `a.txt`
+/// * 2024-03-01 Carol adds a `.mailmap` (1 line)
+/// * 2024-04-01 Alice adds 1 line to `a.txt`
+fn create_test_repository() -> (PathBuf, tempfile::TempDir) {
+    let repo = TestRepository::new();
+
+    commit_as(
+        &repo,
+        ALICE,
+        "2023-01-10T10:00:00Z",
+        "a.txt",
+        "one\ntwo\nthree\n",
+    );
+    commit_as(
+        &repo,
+        BOB,
+        "2023-06-01T10:00:00Z",
+        "b.txt",
+        "first\nsecond\n",
+    );
+    repo.tag("v1.0.0");
+    commit_as(
+        &repo,
+        ALICE_WORK,
+        "2024-02-01T10:00:00Z",
+        "a.txt",
+        "one\n2\nthree\n",
+    );
+    commit_as(
+        &repo,
+        CAROL,
+        "2024-03-01T10:00:00Z",
+        ".mailmap",
+        "Alice <alice@example.com> <alice@work.example.com>\n",
+    );
+    commit_as(
+        &repo,
+        ALICE,
+        "2024-04-01T10:00:00Z",
+        "a.txt",
+        "one\n2\nthree\nfour\n",
+    );
+
+    repo.into_parts()
+}
+
+fn options() -> ContributorsOptions {
+    ContributorsOptions {
+        use_mailmap: true,
+        include_line_stats: true,
+        ..Default::default()
+    }
+}
+
+fn summary(report: &ContributorsReport) -> Vec<(&str, usize)> {
+    report
+        .contributors
+        .iter()
+        .map(|contributor| (contributor.email.as_str(), contributor.commits))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_contributors_with_mailmap() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path);
+
+    let report = local_repo
+        .contributors(options())
+        .await
+        .expect("Failed to collect contributors");
+
+    assert_eq!(report.range, "HEAD");
+    assert_eq!(report.total_commits, 5);
+    assert_eq!(report.total_contributors, 3);
+    assert!(!report.truncated);
+    assert_eq!(
+        summary(&report),
+        vec![
+            ("alice@example.com", 3),
+            ("carol@example.com", 1),
+            ("bob@example.com", 1)
+        ]
+    );
+    assert!(
+        report
+            .first_commit_date
+            .as_deref()
+            .unwrap()
+            .starts_with("2023-01-10")
+    );
+    assert!(
+        report
+            .last_commit_date
+            .as_deref()
+            .unwrap()
+            .starts_with("2024-04-01")
+    );
+
+    let alice = &report.contributors[0];
+    assert_eq!(alice.name, "Alice");
+    assert!(alice.first_commit_date.starts_with("2023-01-10"));
+    assert!(alice.last_commit_date.starts_with("2024-04-01"));
+    assert_eq!(alice.lines_added, Some(5));
+    assert_eq!(alice.lines_removed, Some(1));
+}
+
+#[tokio::test]
+async fn test_contributors_without_mailmap_or_line_stats() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path);
+
+    let report = local_repo
+        .contributors(ContributorsOptions {
+            max_contributors: Some(2),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to collect contributors");
+
+    assert_eq!(report.total_contributors, 4);
+    assert!(report.truncated);
+    assert_eq!(report.contributors.len(), 2);
+    assert_eq!(report.contributors[0].email, "alice@example.com");
+    assert_eq!(report.contributors[0].commits, 2);
+    assert_eq!(report.contributors[0].lines_added, None);
+}
+
+#[tokio::test]
+async fn test_contributors_range_and_time_window() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path);
+
+    let since_release = local_repo
+        .contributors(ContributorsOptions {
+            from_ref: Some("v1.0.0".to_string()),
+            ..options()
+        })
+        .await
+        .expect("Failed to collect contributors");
+    assert_eq!(since_release.range, "v1.0.0..HEAD");
+    assert_eq!(
+        summary(&since_release),
+        vec![("alice@example.com", 2), ("carol@example.com", 1)]
+    );
+
+    let window = local_repo
+        .contributors(ContributorsOptions {
+            since: Some("2023-06-01".to_string()),
+            until: Some("2024-03-01".to_string()),
+            ..options()
+        })
+        .await
+        .expect("Failed to collect contributors");
+    assert_eq!(
+        summary(&window),
+        vec![("alice@example.com", 1), ("bob@example.com", 1)]
+    );
+    assert!(window.since.as_deref().unwrap().starts_with("2023-06-01"));
+
+    let invalid = local_repo
+        .contributors(ContributorsOptions {
+            since: Some("not a date".to_string()),
+            ..options()
+        })
+        .await;
+    assert!(invalid.is_err());
+}
+
+#[tokio::test]
+async fn test_contributors_service_fetches_refs_missing_from_a_clone() {
+    let (upstream_path, _upstream_dir) = create_test_repository();
+    let clone_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let clone_path = clone_dir.path().join("clone");
+    common::git(
+        clone_dir.path(),
+        &["clone", &upstream_path.to_string_lossy(), "clone"],
+    );
+
+    // Pushed upstream after the clone was made
+    common::git(&upstream_path, &["checkout", "-b", "release"]);
+    common::write_file(&upstream_path, "b.txt", "first\nsecond\nthird\n");
+    common::git_with_env(
+        &upstream_path,
+        &[("GIT_AUTHOR_NAME", BOB.0), ("GIT_AUTHOR_EMAIL", BOB.1)],
+        &["commit", "-am", "Update b.txt"],
+    );
+
+    let manager = gitcodes_mcp::gitcodes::repository_manager::RepositoryManager::new(None, None)
+        .expect("Failed to create repository manager");
+    let params = ContributorsServiceParams {
+        repository_location_str: clone_path.to_string_lossy().to_string(),
+        from_ref: Some("main".to_string()),
+        to_ref: Some("release".to_string()),
+        since: None,
+        until: None,
+        use_mailmap: None,
+        include_line_stats: None,
+        include_merges: None,
+        max_contributors: None,
+    };
+
+    let (report, _local_repo) = services::contributors(&manager, params)
+        .await
+        .expect("Contributors service failed");
+    assert_eq!(report.range, "main..release");
+    assert_eq!(summary(&report), vec![("bob@example.com", 1)]);
+}
@@ -0,0 +1,251 @@
+//! Tests for expanding grep matches to their enclosing functions, impls and classes
+
+mod common;
+
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+use common::TestRepository;
+use gitcodes_mcp::gitcodes::repository_manager::RepositoryManager;
+use gitcodes_mcp::gitcodes::{CodeSearchParams, CodeSearchResult, ContextMode, LocalRepository};
+use gitcodes_mcp::services;
+use gitcodes_mcp::tools::responses::CompactCodeSearchResponse;
+
+const CONFIG_RS: &str = r#"use std::fs;
+
+pub struct Config {
+    pub name: String,
+}
+
+impl Config {
+    /// Reads the config file
+    pub fn load(path: &str) -> Config {
+        let text = fs::read_to_string(path).unwrap();
+        let name = text.trim().to_string();
+        Config { name }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+pub fn parse(text: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let name = line.to_string();
+        names.push(name);
+    }
+    names
+}
+"#;
+
+const SERVICE_PY: &str = r#"import os
+
+
+class Service:
+    def start(self):
+        path = os.getcwd()
+        return path
+"#;
+
+const NOTES_TXT: &str = "first\nsecond\nread_to_string here\nfourth\n";
+
+/// Helper function to create a committed repository with Rust, Python and text files
+fn create_test_repository() -> (PathBuf, tempfile::TempDir) {
+    let repo = TestRepository::new();
+
+    let files = [
+        ("src/config.rs", CONFIG_RS),
+        ("service.py", SERVICE_PY),
+        ("notes.txt", NOTES_TXT),
+    ];
+    repo.write_all(&files).commit("Initial commit");
+
+    repo.into_parts()
+}
+
+fn search_params(repo_path: &Path, pattern: &str) -> CodeSearchParams {
+    CodeSearchParams {
+        ref_name: None,
+        case_sensitive: true,
+        file_extensions: None,
+        include_globs: None,
+        exclude_dirs: None,
+        before_context: None,
+        after_context: None,
+        context_mode: Some(ContextMode::EnclosingItem),
+        skip: None,
+        take: None,
+        match_content_omit_num: None,
+        ..common::code_search_params(repo_path, pattern)
+    }
+}
+
+/// Returns the lines of a file as `line_number` for context lines and `line_number*`
+/// for matches
+fn lines_of(result: &CodeSearchResult, file_path: &str) -> Vec<String> {
+    result
+        .matches
+        .iter()
+        .filter(|line| line.file_path == Path::new(file_path))
+        .map(|line| {
+            format!(
+                "{}{}",
+                line.line_number,
+                if line.is_context { "" } else { "*" }
+            )
+        })
+        .collect()
+}
+
+fn numbers(lines: &[&str]) -> Vec<String> {
+    lines.iter().map(|line| line.to_string()).collect()
+}
+
+#[tokio::test]
+async fn test_matches_expand_to_enclosing_item() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let repo = LocalRepository::new(repo_path.clone());
+
+    // Both matches are in `load`, whose lines are shown once
+    let result = repo
+        .search_code(search_params(&repo_path, "text"))
+        .await
+        .expect("Search failed");
+    assert_eq!(
+        lines_of(&result, "src/config.rs")[..5],
+        numbers(&["9", "10*", "11*", "12", "13"])
+    );
+    // ... and `parse` around the other matches
+    assert_eq!(
+        lines_of(&result, "src/config.rs")[5..],
+        numbers(&[
+            "20*", "21", "22*", "23", "24", "25", "26", "27", "28", "29", "30", "31"
+        ])
+    );
+    assert_eq!(result.total_match_line_number, 4);
+
+    // Lines outside any item have no enclosing item
+    let result = repo
+        .search_code(search_params(&repo_path, "^use "))
+        .await
+        .expect("Search failed");
+    assert_eq!(lines_of(&result, "src/config.rs"), numbers(&["1*"]));
+
+    // The same lines from the object database
+    let at_ref = repo
+        .search_code(CodeSearchParams {
+            ref_name: Some("main".to_string()),
+            ..search_params(&repo_path, "text")
+        })
+        .await
+        .expect("Search at ref failed");
+    let worktree = repo
+        .search_code(search_params(&repo_path, "text"))
+        .await
+        .expect("Search failed");
+    assert_eq!(
+        lines_of(&at_ref, "src/config.rs"),
+        lines_of(&worktree, "src/config.rs")
+    );
+
+    // The compact response shows the merged lines in order
+    let compact = CompactCodeSearchResponse::from_search_result(worktree);
+    assert_eq!(compact.matches.len(), 1);
+    assert!(
+        compact.matches[0]
+            .lines
+            .starts_with("9:    pub fn load(path: &str) -> Config {\n10:        let text =")
+    );
+}
+
+#[tokio::test]
+async fn test_long_items_are_capped() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let repo = LocalRepository::new(repo_path.clone());
+
+    let result = repo
+        .search_code(CodeSearchParams {
+            max_context_lines: Some(4),
+            ..search_params(&repo_path, "names.push")
+        })
+        .await
+        .expect("Search failed");
+    // The signature and the lines around the match
+    assert_eq!(
+        lines_of(&result, "src/config.rs"),
+        numbers(&["20", "27", "28*", "29"])
+    );
+
+    // Items that fit are shown whole
+    let result = repo
+        .search_code(CodeSearchParams {
+            max_context_lines: Some(4),
+            ..search_params(&repo_path, "self.name")
+        })
+        .await
+        .expect("Search failed");
+    assert_eq!(
+        lines_of(&result, "src/config.rs"),
+        numbers(&["15", "16*", "17"])
+    );
+}
+
+#[tokio::test]
+async fn test_enclosing_context_through_service() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let cache_dir = tempdir().expect("Failed to create temporary directory");
+    let manager = RepositoryManager::new(None, Some(cache_dir.path().to_path_buf()))
+        .expect("Failed to create RepositoryManager");
+
+    let params = |pattern: &str, context_mode| services::GrepParams {
+        repository_location_str: repo_path.display().to_string(),
+        pattern: pattern.to_string(),
+        ref_name: None,
+        case_sensitive: true,
+        file_extensions: None,
+        include_globs: None,
+        exclude_dirs: None,
+        before_context: Some(1),
+        after_context: None,
+        context_mode,
+        skip: None,
+        take: None,
+        match_content_omit_num: None,
+        ..Default::default()
+    };
+
+    // The Python method, which includes the line before the match anyway
+    let (result, _local_repo) = services::perform_grep_in_repository(
+        &manager,
+        params("getcwd", Some(ContextMode::EnclosingItem)),
+    )
+    .await
+    .expect("Grep failed");
+    assert_eq!(lines_of(&result, "service.py"), numbers(&["5", "6*", "7"]));
+
+    // Files of other languages keep the line context
+    let (result, _local_repo) = services::perform_grep_in_repository(
+        &manager,
+        params("read_to_string", Some(ContextMode::EnclosingItem)),
+    )
+    .await
+    .expect("Grep failed");
+    assert_eq!(lines_of(&result, "notes.txt"), numbers(&["2", "3*"]));
+    assert_eq!(
+        lines_of(&result, "src/config.rs"),
+        numbers(&["9", "10*", "11", "12", "13"])
+    );
+
+    // The default mode only adds the fixed context
+    let (result, _local_repo) =
+        services::perform_grep_in_repository(&manager, params("read_to_string", None))
+            .await
+            .expect("Grep failed");
+    assert_eq!(lines_of(&result, "src/config.rs"), numbers(&["9", "10*"]));
+}
@@ -0,0 +1,200 @@
+//! Tests for finding files by glob and fuzzy name
+
+mod common;
+
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+use common::TestRepository;
+use gitcodes_mcp::gitcodes::repository_manager::RepositoryManager;
+use gitcodes_mcp::gitcodes::{FileMatchMode, FindFilesOptions, FindFilesResult, LocalRepository};
+use gitcodes_mcp::services::{self, FindFilesServiceParams};
+
+/// Helper function to create a committed repository with nested crates, a gitignored
+/// build output and a hidden file
+fn create_test_repository() -> (PathBuf, tempfile::TempDir) {
+    let repo = TestRepository::new();
+
+    let files = [
+        ("Cargo.toml", "[workspace]\n"),
+        ("crates/client/Cargo.toml", "[package]\nname = \"client\"\n"),
+        (
+            "crates/client/src/octocrab_client.rs",
+            "pub struct Client;\n",
+        ),
+        ("crates/client/src/octo/mod.rs", "pub mod client;\n"),
+        ("crates/client/src/octo/client.rs", "pub fn connect() {}\n"),
+        ("proto/api.proto", "syntax = \"proto3\";\n"),
+        ("docs/README.md", "# Docs\n"),
+        ("Dockerfile", "FROM rust\n"),
+        (".github/ci.yml", "on: push\n"),
+        (".gitignore", "target/\n"),
+    ];
+    repo.write_all(&files).commit("Initial commit");
+
+    repo.write("target/debug/client.d", "deps\n");
+
+    repo.into_parts()
+}
+
+fn query(query: &str) -> FindFilesOptions {
+    FindFilesOptions {
+        query: query.to_string(),
+        ..Default::default()
+    }
+}
+
+fn paths(result: &FindFilesResult) -> Vec<&str> {
+    result.files.iter().map(|file| file.path.as_str()).collect()
+}
+
+#[tokio::test]
+async fn test_fuzzy_matches_are_ranked() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let repo = LocalRepository::new(repo_path);
+
+    // The characters only have to appear in order; matches in the file name rank first
+    let result = repo
+        .find_files(query("octoclient"))
+        .await
+        .expect("Find files failed");
+    assert_eq!(result.mode, FileMatchMode::Fuzzy);
+    assert_eq!(
+        paths(&result),
+        vec![
+            "crates/client/src/octocrab_client.rs",
+            "crates/client/src/octo/client.rs",
+        ]
+    );
+    let scores: Vec<i64> = result.files.iter().map(|f| f.score.unwrap()).collect();
+    assert!(scores[0] > scores[1]);
+    assert_eq!(result.files[0].language.as_deref(), Some("Rust"));
+    assert_eq!(result.files[0].size, 19);
+
+    // A query with a separator matches against the whole path
+    let result = repo
+        .find_files(query("octo/mod"))
+        .await
+        .expect("Find files failed");
+    assert_eq!(paths(&result), vec!["crates/client/src/octo/mod.rs"]);
+
+    // Smart case: an uppercase letter makes the query case-sensitive
+    let result = repo
+        .find_files(query("docker"))
+        .await
+        .expect("Find files failed");
+    assert_eq!(paths(&result), vec!["Dockerfile"]);
+    assert_eq!(result.files[0].language.as_deref(), Some("Dockerfile"));
+    let result = repo
+        .find_files(query("DOCKER"))
+        .await
+        .expect("Find files failed");
+    assert_eq!(result.total_matches, 0);
+}
+
+#[tokio::test]
+async fn test_glob_matches() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let repo = LocalRepository::new(repo_path);
+
+    let result = repo
+        .find_files(query("**/Cargo.toml"))
+        .await
+        .expect("Find files failed");
+    assert_eq!(result.mode, FileMatchMode::Glob);
+    assert_eq!(
+        paths(&result),
+        vec!["Cargo.toml", "crates/client/Cargo.toml"]
+    );
+    assert!(result.files.iter().all(|file| file.score.is_none()));
+    assert_eq!(result.files[1].language.as_deref(), Some("TOML"));
+
+    // A glob without a separator matches file names at any depth
+    let result = repo
+        .find_files(query("*.proto"))
+        .await
+        .expect("Find files failed");
+    assert_eq!(paths(&result), vec!["proto/api.proto"]);
+    assert_eq!(
+        result.files[0].language.as_deref(),
+        Some("Protocol Buffers")
+    );
+
+    let result = repo
+        .find_files(FindFilesOptions {
+            skip: Some(1),
+            take: Some(2),
+            ..query("crates/**/*.rs")
+        })
+        .await
+        .expect("Find files failed");
+    assert_eq!(result.total_matches, 3);
+    assert_eq!(
+        paths(&result),
+        vec![
+            "crates/client/src/octo/client.rs",
+            "crates/client/src/octo/mod.rs",
+        ]
+    );
+
+    let err = repo.find_files(query("src/[")).await.unwrap_err();
+    assert!(err.contains("Invalid glob"), "unexpected error: {}", err);
+    let err = repo.find_files(query("  ")).await.unwrap_err();
+    assert!(
+        err.contains("must not be empty"),
+        "unexpected error: {}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn test_gitignore_and_refs() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let cache_dir = tempdir().expect("Failed to create temporary directory");
+    let manager = RepositoryManager::new(None, Some(cache_dir.path().to_path_buf()))
+        .expect("Failed to create RepositoryManager");
+
+    let params = |query: &str, ref_name: Option<&str>, respect_gitignore: Option<bool>| {
+        FindFilesServiceParams {
+            repository_location_str: repo_path.display().to_string(),
+            query: query.to_string(),
+            ref_name: ref_name.map(str::to_string),
+            respect_gitignore,
+            skip: None,
+            take: None,
+        }
+    };
+
+    // Ignored and hidden files are only found when asked for
+    let (result, _local_repo) = services::find_files(&manager, params("*.d", None, None))
+        .await
+        .expect("Find files failed");
+    assert_eq!(result.total_matches, 0);
+    let (result, _local_repo) = services::find_files(&manager, params("*.d", None, Some(false)))
+        .await
+        .expect("Find files failed");
+    assert_eq!(paths(&result), vec!["target/debug/client.d"]);
+    let (result, _local_repo) = services::find_files(&manager, params("*.yml", None, Some(false)))
+        .await
+        .expect("Find files failed");
+    assert_eq!(paths(&result), vec![".github/ci.yml"]);
+
+    // At a ref, the committed files are listed with the sizes of their blobs
+    std::fs::write(repo_path.join("proto/api.proto"), "changed\n").unwrap();
+    std::fs::write(repo_path.join("proto/new.proto"), "new\n").unwrap();
+    let (result, _local_repo) =
+        services::find_files(&manager, params("*.proto", Some("main"), None))
+            .await
+            .expect("Find files failed");
+    assert_eq!(paths(&result), vec!["proto/api.proto"]);
+    assert_eq!(result.files[0].size, 19);
+    let (result, _local_repo) = services::find_files(&manager, params("*.yml", Some("main"), None))
+        .await
+        .expect("Find files failed");
+    assert_eq!(result.total_matches, 0);
+    let (result, _local_repo) =
+        services::find_files(&manager, params("*.yml", Some("main"), Some(false)))
+            .await
+            .expect("Find files failed");
+    assert_eq!(paths(&result), vec![".github/ci.yml"]);
+}
@@ -0,0 +1,204 @@
+//! Tests for the structural outline of source files
+
+mod common;
+
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+use common::TestRepository;
+use gitcodes_mcp::gitcodes::repository_manager::RepositoryManager;
+use gitcodes_mcp::gitcodes::{LocalRepository, OutlineItem, SourceLanguage, SymbolKind};
+use gitcodes_mcp::services::{self, FileOutlineServiceParams};
+
+const LIB_RS: &str = r#"//! A small sync crate
+
+/// A mutual exclusion primitive.
+///
+/// Protects shared data.
+#[derive(Debug)]
+pub struct Mutex<T> {
+    inner: T,
+}
+
+/** Locking */
+impl<T> Mutex<T> {
+    /// Acquires the lock
+    pub fn lock(&self) -> Guard<'_, T> {
+        fn helper() {}
+        todo!()
+    }
+
+    // Not a doc comment
+    fn unlock(&self) {}
+}
+
+// Unrelated comment
+
+pub mod sync {
+    /// The state of a lock
+    pub enum State {
+        Locked,
+        Unlocked,
+    }
+}
+"#;
+
+const MUTEX_PY: &str = r#"class Mutex:
+    """A mutual exclusion primitive.
+
+    Protects shared data.
+    """
+
+    @property
+    def locked(self):
+        '''Whether the lock is held'''
+        return False
+
+
+def new_mutex():
+    return Mutex()
+"#;
+
+/// Helper function to create a committed repository with a Rust and a Python file
+fn create_test_repository() -> (PathBuf, tempfile::TempDir) {
+    let repo = TestRepository::new();
+
+    repo.write("src/lib.rs", LIB_RS)
+        .write("mutex.py", MUTEX_PY)
+        .write("README.md", "# Sync\n")
+        .commit("Initial commit");
+
+    repo.into_parts()
+}
+
+/// Returns the items as `start-end Kind name // doc`, indented by their depth
+fn outline_lines(items: &[OutlineItem], depth: usize, lines: &mut Vec<String>) {
+    for item in items {
+        lines.push(format!(
+            "{}{}-{} {:?} {}{}",
+            "  ".repeat(depth),
+            item.start_line,
+            item.end_line,
+            item.kind,
+            item.name,
+            item.doc
+                .as_deref()
+                .map(|doc| format!(" // {}", doc))
+                .unwrap_or_default()
+        ));
+        outline_lines(&item.children, depth + 1, lines);
+    }
+}
+
+fn outline_of(items: &[OutlineItem]) -> Vec<String> {
+    let mut lines = Vec::new();
+    outline_lines(items, 0, &mut lines);
+    lines
+}
+
+#[tokio::test]
+async fn test_rust_file_outline() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let repo = LocalRepository::new(repo_path);
+
+    let outline = repo
+        .file_outline(Path::new("src/lib.rs"), None)
+        .await
+        .expect("Outline failed");
+    assert_eq!(outline.file_path, "src/lib.rs");
+    assert_eq!(outline.language, SourceLanguage::Rust);
+    assert_eq!(outline.line_count, 31);
+
+    // Functions nested in function bodies aren't part of the outline
+    assert_eq!(
+        outline_of(&outline.items),
+        vec![
+            "7-9 Struct Mutex // A mutual exclusion primitive.",
+            "12-21 Impl Mutex // Locking",
+            "  14-17 Method lock // Acquires the lock",
+            "  20-20 Method unlock",
+            "25-31 Module sync",
+            "  27-30 Enum State // The state of a lock",
+        ]
+    );
+    assert_eq!(outline.items[1].kind, SymbolKind::Impl);
+    assert_eq!(outline.items[1].signature, "impl<T> Mutex<T>");
+    assert_eq!(
+        outline.items[1].children[0].signature,
+        "pub fn lock(&self) -> Guard<'_, T>"
+    );
+
+    // Leaf items have no children in the JSON
+    let json = serde_json::to_value(&outline).unwrap();
+    assert_eq!(json["items"][1]["kind"], "impl");
+    assert!(json["items"][0].get("children").is_none());
+    assert!(json["items"][1]["children"][1].get("doc").is_none());
+
+    // The same outline from the object database
+    let at_ref = repo
+        .file_outline(Path::new("src/lib.rs"), Some("main"))
+        .await
+        .expect("Outline at ref failed");
+    assert_eq!(outline_of(&at_ref.items), outline_of(&outline.items));
+}
+
+#[tokio::test]
+async fn test_python_file_outline_through_service() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let cache_dir = tempdir().expect("Failed to create temporary directory");
+    let manager = RepositoryManager::new(None, Some(cache_dir.path().to_path_buf()))
+        .expect("Failed to create RepositoryManager");
+
+    let (outline, _local_repo) = services::file_outline(
+        &manager,
+        FileOutlineServiceParams {
+            repository_location_str: repo_path.display().to_string(),
+            file_path: "mutex.py".to_string(),
+            ref_name: None,
+        },
+    )
+    .await
+    .expect("Outline failed");
+    assert_eq!(
+        outline_of(&outline.items),
+        vec![
+            "1-10 Class Mutex // A mutual exclusion primitive.",
+            "  8-10 Method locked // Whether the lock is held",
+            "13-14 Function new_mutex",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_file_outline_errors() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let repo = LocalRepository::new(repo_path);
+
+    let err = repo
+        .file_outline(Path::new("README.md"), None)
+        .await
+        .unwrap_err();
+    assert!(
+        err.contains("Unsupported file type"),
+        "unexpected error: {}",
+        err
+    );
+
+    let err = repo
+        .file_outline(Path::new("src/missing.rs"), None)
+        .await
+        .unwrap_err();
+    assert!(err.contains("File not found"), "unexpected error: {}", err);
+
+    let err = repo
+        .file_outline(Path::new("src/missing.rs"), Some("main"))
+        .await
+        .unwrap_err();
+    assert!(err.contains("File not found"), "unexpected error: {}", err);
+
+    let err = repo
+        .file_outline(Path::new("../src/lib.rs"), None)
+        .await
+        .unwrap_err();
+    assert!(err.contains(".."), "unexpected error: {}", err);
+}
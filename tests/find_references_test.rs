@@ -0,0 +1,253 @@
+//! Tests for finding identifier references through the cached identifier index
+
+mod common;
+
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+use common::{TestRepository, git};
+use gitcodes_mcp::gitcodes::repository_manager::RepositoryManager;
+use gitcodes_mcp::gitcodes::{
+    LocalRepository, ReferenceSearchOptions, ReferenceSearchResult, SymbolKind,
+};
+use gitcodes_mcp::services::{self, FindReferencesServiceParams};
+
+const LIB_RS: &str = r#"/// Runs a future to completion
+pub fn block_on(value: u32) -> u32 {
+    // block_on is not called here
+    value
+}
+
+pub fn run() -> u32 {
+    let name = "block_on";
+    block_on(name.len() as u32)
+}
+"#;
+
+const MAIN_RS: &str = r#"use crate::block_on;
+
+fn main() {
+    let block_on_result = block_on(1);
+    println!("{}", block_on(block_on_result));
+}
+"#;
+
+const CLIENT_TS: &str = r#"import { block_on } from "./runtime";
+
+// block_on from TypeScript
+export const result = block_on(3);
+"#;
+
+/// Helper function to create a committed repository with Rust and TypeScript files that
+/// use `block_on` in code, comments and strings
+fn create_test_repository() -> (PathBuf, tempfile::TempDir) {
+    let repo = TestRepository::new();
+
+    let files = [
+        ("src/lib.rs", LIB_RS),
+        ("src/main.rs", MAIN_RS),
+        ("web/client.ts", CLIENT_TS),
+        ("README.md", "Call `block_on` to run a future.\n"),
+    ];
+    repo.write_all(&files).commit("Initial commit");
+
+    repo.into_parts()
+}
+
+fn references_to(name: &str) -> ReferenceSearchOptions {
+    ReferenceSearchOptions {
+        name: name.to_string(),
+        ..Default::default()
+    }
+}
+
+/// The references as `file:line:column`
+fn locations(result: &ReferenceSearchResult) -> Vec<String> {
+    result
+        .references
+        .iter()
+        .map(|reference| {
+            format!(
+                "{}:{}:{}",
+                reference.file_path, reference.line, reference.column
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_references_skip_comments_and_strings() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let repo = LocalRepository::new(repo_path);
+
+    let result = repo
+        .find_references(references_to("block_on"))
+        .await
+        .expect("Find references failed");
+    assert_eq!(result.indexed_files, 3);
+    assert_eq!(
+        locations(&result),
+        vec![
+            "src/lib.rs:9:5",
+            "src/main.rs:1:12",
+            "src/main.rs:4:27",
+            "src/main.rs:5:20",
+            "web/client.ts:1:10",
+            "web/client.ts:4:23",
+        ]
+    );
+    assert_eq!(result.total_references, 6);
+    assert_eq!(
+        result.references[2].line_content,
+        "let block_on_result = block_on(1);"
+    );
+
+    // The definition is listed separately
+    assert_eq!(result.definitions.len(), 1);
+    assert_eq!(result.definitions[0].kind, SymbolKind::Function);
+    assert_eq!(result.definitions[0].file_path, "src/lib.rs");
+    assert_eq!(result.definitions[0].start_line, 2);
+
+    // ... and among the references on request
+    let result = repo
+        .find_references(ReferenceSearchOptions {
+            include_definitions: true,
+            take: Some(2),
+            ..references_to("block_on")
+        })
+        .await
+        .expect("Find references failed");
+    assert_eq!(locations(&result), vec!["src/lib.rs:2:8", "src/lib.rs:9:5"]);
+    assert_eq!(result.total_references, 7);
+
+    // Only whole identifiers match
+    let result = repo
+        .find_references(references_to("block_on_result"))
+        .await
+        .expect("Find references failed");
+    assert_eq!(
+        locations(&result),
+        vec!["src/main.rs:4:9", "src/main.rs:5:29"]
+    );
+    assert!(result.definitions.is_empty());
+}
+
+#[tokio::test]
+async fn test_index_is_cached_per_commit() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let repo = LocalRepository::new(repo_path.clone());
+    let first_commit = git(&repo_path, &["rev-parse", "HEAD"]);
+
+    let result = repo
+        .find_references(references_to("block_on"))
+        .await
+        .expect("Find references failed");
+    assert_eq!(result.commit_id, first_commit);
+    let index_path = repo_path
+        .join(".git/gitcodes")
+        .join(format!("identifiers-v1-{}.json", first_commit));
+    assert!(index_path.is_file(), "missing {}", index_path.display());
+
+    // Later searches read the cached index instead of the files
+    let cached: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(&index_path).unwrap()).unwrap();
+    assert_eq!(cached["files"].as_array().unwrap().len(), 3);
+    let mut tampered = cached.clone();
+    tampered["occurrences"]["block_on"] = serde_json::json!([[0, 9, 5]]);
+    std::fs::write(&index_path, serde_json::to_vec(&tampered).unwrap()).unwrap();
+    let result = repo
+        .find_references(references_to("block_on"))
+        .await
+        .expect("Find references failed");
+    assert_eq!(result.total_references, 1);
+
+    // A new commit gets its own index, the old one stays usable through its ref
+    std::fs::write(repo_path.join("src/extra.rs"), "fn f() { block_on(2); }\n").unwrap();
+    git(&repo_path, &["add", "."]);
+    git(&repo_path, &["commit", "-m", "Add extra"]);
+    let result = repo
+        .find_references(ReferenceSearchOptions {
+            include_globs: Some(vec!["src/extra.rs".to_string()]),
+            ..references_to("block_on")
+        })
+        .await
+        .expect("Find references failed");
+    assert_eq!(locations(&result), vec!["src/extra.rs:1:10"]);
+    assert_eq!(result.indexed_files, 4);
+
+    let result = repo
+        .find_references(ReferenceSearchOptions {
+            ref_name: Some(first_commit.clone()),
+            ..references_to("block_on")
+        })
+        .await
+        .expect("Find references at ref failed");
+    assert_eq!(result.commit_id, first_commit);
+    assert_eq!(result.total_references, 1);
+
+    // An unreadable index is rebuilt
+    std::fs::write(&index_path, "not json").unwrap();
+    let result = repo
+        .find_references(ReferenceSearchOptions {
+            ref_name: Some(first_commit),
+            ..references_to("block_on")
+        })
+        .await
+        .expect("Find references failed");
+    assert_eq!(result.total_references, 6);
+}
+
+#[tokio::test]
+async fn test_find_references_through_service() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let cache_dir = tempdir().expect("Failed to create temporary directory");
+    let manager = RepositoryManager::new(None, Some(cache_dir.path().to_path_buf()))
+        .expect("Failed to create RepositoryManager");
+
+    let params = |name: &str| FindReferencesServiceParams {
+        repository_location_str: repo_path.display().to_string(),
+        name: name.to_string(),
+        ref_name: None,
+        include_definitions: None,
+        include_globs: None,
+        exclude_dirs: Some(vec!["web".to_string()]),
+        skip: Some(1),
+        take: Some(2),
+    };
+
+    let (result, _local_repo) = services::find_references(&manager, params("block_on"))
+        .await
+        .expect("Find references failed");
+    assert_eq!(
+        locations(&result),
+        vec!["src/main.rs:1:12", "src/main.rs:4:27"]
+    );
+    assert_eq!(result.total_references, 4);
+
+    let json = serde_json::to_value(&result).unwrap();
+    assert_eq!(
+        json["references"][0]["line_content"],
+        "use crate::block_on;"
+    );
+    assert_eq!(json["definitions"][0]["kind"], "function");
+
+    let err = services::find_references(&manager, params(""))
+        .await
+        .unwrap_err();
+    assert!(
+        err.contains("must not be empty"),
+        "unexpected error: {}",
+        err
+    );
+
+    let err = services::find_references(
+        &manager,
+        FindReferencesServiceParams {
+            ref_name: Some("no-such-branch".to_string()),
+            ..params("block_on")
+        },
+    )
+    .await
+    .unwrap_err();
+    assert!(err.contains("no-such-branch"), "unexpected error: {}", err);
+}
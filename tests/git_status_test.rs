@@ -0,0 +1,246 @@
+//! Tests for the working-tree status and the search restricted to changed files
+
+mod common;
+
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+use common::{TestRepository, git};
+use gitcodes_mcp::gitcodes::local_repository::CodeSearchParams;
+use gitcodes_mcp::gitcodes::repository_manager::RepositoryManager;
+use gitcodes_mcp::gitcodes::{LocalRepository, StatusOptions};
+use gitcodes_mcp::services;
+
+/// Helper function to create a repository with one commit and uncommitted work:
+/// - `staged.txt` is modified and staged
+/// - `unstaged.txt` is modified but not staged
+/// - `removed.txt` is deleted from the working tree
+/// - `new.txt` is untracked
+/// - `clean.txt` is unchanged
+fn create_test_repository() -> (PathBuf, tempfile::TempDir) {
+    let repo = TestRepository::new();
+
+    for file in ["staged.txt", "unstaged.txt", "removed.txt", "clean.txt"] {
+        repo.write(file, "one\ntwo\nthree\n// TODO: old\n");
+    }
+    repo.commit("Initial commit");
+
+    repo.write("staged.txt", "one\ntwo\nthree\n// TODO: staged\n")
+        .git(&["add", "staged.txt"]);
+    repo.write("unstaged.txt", "one\n2\nthree\n// TODO: old\n");
+    std::fs::remove_file(repo.path().join("removed.txt")).unwrap();
+    repo.write("new.txt", "// TODO: new\n");
+
+    repo.into_parts()
+}
+
+fn grep_params(repo_path: &Path, include_globs: Option<Vec<String>>) -> CodeSearchParams {
+    CodeSearchParams {
+        ref_name: None,
+        case_sensitive: false,
+        file_extensions: None,
+        include_globs,
+        exclude_dirs: None,
+        changed_files_only: true,
+        before_context: None,
+        after_context: None,
+        skip: None,
+        take: None,
+        match_content_omit_num: None,
+        ..common::code_search_params(repo_path, "TODO")
+    }
+}
+
+fn matched_files(result: &gitcodes_mcp::gitcodes::CodeSearchResult) -> Vec<String> {
+    let mut files: Vec<String> = result
+        .matches
+        .iter()
+        .map(|line| line.file_path.display().to_string())
+        .collect();
+    files.sort();
+    files.dedup();
+    files
+}
+
+#[tokio::test]
+async fn test_working_tree_status_lists_staged_unstaged_and_untracked_files() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path.clone());
+
+    let status = local_repo
+        .working_tree_status(StatusOptions {
+            include_untracked: true,
+            include_diffs: true,
+            context_lines: Some(0),
+        })
+        .expect("Failed to read status");
+
+    assert_eq!(status.branch.as_deref(), Some("main"));
+    assert_eq!(
+        status.head_commit.as_deref(),
+        Some(git(&repo_path, &["rev-parse", "HEAD"]).as_str())
+    );
+    assert!(!status.clean);
+    assert_eq!(
+        (
+            status.staged_count,
+            status.unstaged_count,
+            status.untracked_count
+        ),
+        (1, 2, 1)
+    );
+
+    let paths: Vec<&str> = status.files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        vec!["new.txt", "removed.txt", "staged.txt", "unstaged.txt"]
+    );
+
+    let file = |path: &str| status.files.iter().find(|f| f.path == path).unwrap();
+
+    let staged = file("staged.txt");
+    assert_eq!(staged.staged.as_deref(), Some("modified"));
+    assert_eq!(staged.unstaged, None);
+    assert_eq!((staged.lines_added, staged.lines_removed), (1, 1));
+    assert_eq!(staged.hunks.len(), 1);
+    assert_eq!(staged.hunks[0].content, "-// TODO: old\n+// TODO: staged");
+
+    let unstaged = file("unstaged.txt");
+    assert_eq!(unstaged.staged, None);
+    assert_eq!(unstaged.unstaged.as_deref(), Some("modified"));
+    assert_eq!(unstaged.hunks[0].content, "-two\n+2");
+
+    let removed = file("removed.txt");
+    assert_eq!(removed.unstaged.as_deref(), Some("deleted"));
+    assert_eq!((removed.lines_added, removed.lines_removed), (0, 4));
+
+    let new = file("new.txt");
+    assert!(new.untracked);
+    assert_eq!((new.lines_added, new.lines_removed), (1, 0));
+
+    // Without untracked files and diffs only the tracked changes with line counts remain
+    let status = local_repo
+        .working_tree_status(StatusOptions::default())
+        .expect("Failed to read status");
+    assert_eq!(status.untracked_count, 0);
+    assert!(status.files.iter().all(|f| !f.untracked));
+    assert!(status.files.iter().all(|f| f.hunks.is_empty()));
+
+    // Committing everything leaves a clean working tree
+    git(&repo_path, &["add", "-A"]);
+    git(&repo_path, &["commit", "-m", "Work in progress"]);
+    let status = local_repo
+        .working_tree_status(StatusOptions::default())
+        .expect("Failed to read status");
+    assert!(status.clean);
+    assert!(status.files.is_empty());
+}
+
+#[tokio::test]
+async fn test_search_restricted_to_changed_files() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path.clone());
+
+    assert_eq!(
+        local_repo
+            .changed_files()
+            .expect("Failed to list changed files"),
+        vec!["new.txt", "staged.txt", "unstaged.txt"]
+    );
+
+    let result = local_repo
+        .search_code(grep_params(&repo_path, None))
+        .await
+        .expect("Search failed");
+    assert_eq!(
+        matched_files(&result),
+        vec!["new.txt", "staged.txt", "unstaged.txt"]
+    );
+    assert_eq!(result.total_match_line_number, 3);
+    assert_eq!(result.include_globs, None);
+
+    // The filters apply on top of the changed files and are reported as given
+    let include = Some(vec!["staged*".to_string()]);
+    let result = local_repo
+        .search_code(grep_params(&repo_path, include.clone()))
+        .await
+        .expect("Search failed");
+    assert_eq!(matched_files(&result), vec!["staged.txt"]);
+    assert_eq!(result.include_globs, include);
+
+    // Changed files are matched by path, so glob characters in their names are plain text
+    std::fs::write(repo_path.join("notes[1].txt"), "TODO: bracket\n").unwrap();
+    std::fs::write(repo_path.join("notes1.txt"), "TODO: committed\n").unwrap();
+    git(&repo_path, &["add", "notes1.txt"]);
+    git(
+        &repo_path,
+        &["commit", "-m", "Add notes", "--", "notes1.txt"],
+    );
+    let result = local_repo
+        .search_code(grep_params(&repo_path, Some(vec!["notes*".to_string()])))
+        .await
+        .expect("Search failed");
+    assert_eq!(matched_files(&result), vec!["notes[1].txt"]);
+    std::fs::remove_file(repo_path.join("notes[1].txt")).unwrap();
+
+    // A clean working tree has nothing to search
+    git(&repo_path, &["add", "-A"]);
+    git(&repo_path, &["commit", "-m", "Work in progress"]);
+    let result = local_repo
+        .search_code(grep_params(&repo_path, None))
+        .await
+        .expect("Search failed");
+    assert_eq!(result.total_match_line_number, 0);
+    assert!(result.matches.is_empty());
+}
+
+#[tokio::test]
+async fn test_status_and_changed_files_search_reject_unsupported_locations() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path.clone());
+
+    let mut params = grep_params(&repo_path, None);
+    params.ref_name = Some("main".to_string());
+    let err = local_repo.search_code(params).await.unwrap_err();
+    assert!(err.contains("ref_name"), "unexpected error: {}", err);
+
+    let cache_dir = tempdir().expect("Failed to create temporary directory");
+    let manager = RepositoryManager::new(None, Some(cache_dir.path().to_path_buf()))
+        .expect("Failed to create RepositoryManager");
+
+    let err = services::git_status(
+        &manager,
+        services::GitStatusServiceParams {
+            repository_location_str: "github:user/repo".to_string(),
+            include_untracked: None,
+            include_diffs: None,
+            context_lines: None,
+        },
+    )
+    .await
+    .unwrap_err();
+    assert!(err.contains("only available for local repositories"));
+
+    // A bare repository has no working tree
+    let bare_dir = tempdir().expect("Failed to create temporary directory");
+    git(bare_dir.path(), &["init", "--bare"]);
+    let err = LocalRepository::new(bare_dir.path().to_path_buf())
+        .working_tree_status(StatusOptions::default())
+        .unwrap_err();
+    assert!(err.contains("no working tree"), "unexpected error: {}", err);
+
+    // The service works on the local path
+    let (status, _local_repo) = services::git_status(
+        &manager,
+        services::GitStatusServiceParams {
+            repository_location_str: repo_path.display().to_string(),
+            include_untracked: None,
+            include_diffs: Some(false),
+            context_lines: None,
+        },
+    )
+    .await
+    .expect("Failed to read status");
+    assert_eq!(status.files.len(), 4);
+    assert!(status.files.iter().all(|f| f.hunks.is_empty()));
+}
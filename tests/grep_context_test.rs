@@ -3,10 +3,11 @@
 //! These tests ensure the before_context and after_context parameters
 //! correctly handle capturing lines of context around matches.
 
+mod common;
+
 use std::path::PathBuf;
 use tempfile::tempdir;
 
-use gitcodes_mcp::gitcodes::repository_manager::RepositoryLocation;
 use gitcodes_mcp::gitcodes::{CodeSearchParams, LocalRepository};
 
 /// Helper function to create a test file structure for context testing
@@ -76,7 +77,6 @@ Line 7: Final content line
 async fn test_grep_no_context() {
     let (local_repo_path, _temp_dir) = create_test_repository().await;
     let local_repo = LocalRepository::new(local_repo_path.clone());
-    let repo_location = RepositoryLocation::LocalPath(local_repo.clone());
 
     // Write the test file manually for complete control
     let test_file = local_repo_path.join("no_context.txt");
@@ -98,9 +98,7 @@ async fn test_grep_no_context() {
 
     // Test with no context lines
     let params = CodeSearchParams {
-        repository_location: repo_location,
         ref_name: None,
-        pattern: "pattern".to_string(),
         case_sensitive: false,
         file_extensions: None,
         include_globs: None,
@@ -110,6 +108,7 @@ async fn test_grep_no_context() {
         skip: None,
         take: None,
         match_content_omit_num: Some(150),
+        ..common::code_search_params(&local_repo_path, "pattern")
     };
 
     // Execute the search
@@ -165,7 +164,6 @@ async fn test_grep_no_context() {
 async fn test_grep_before_context() {
     let (local_repo_path, _temp_dir) = create_test_repository().await;
     let local_repo = LocalRepository::new(local_repo_path.clone());
-    let repo_location = RepositoryLocation::LocalPath(local_repo.clone());
 
     // Write the test file manually for complete control
     let test_file = local_repo_path.join("minimal.txt");
@@ -174,9 +172,7 @@ async fn test_grep_before_context() {
 
     // Test with 1 line of before context
     let params = CodeSearchParams {
-        repository_location: repo_location,
         ref_name: None,
-        pattern: "pattern".to_string(),
         case_sensitive: false,
         file_extensions: None,
         include_globs: None,
@@ -186,6 +182,7 @@ async fn test_grep_before_context() {
         skip: None,
         take: None,
         match_content_omit_num: Some(150),
+        ..common::code_search_params(&local_repo_path, "pattern")
     };
 
     // Add this file to git so the repo validation passes
@@ -206,7 +203,7 @@ async fn test_grep_before_context() {
 
     // We expect one match
     assert!(
-        result.matches.len() >= 1,
+        !result.matches.is_empty(),
         "Expected at least one match, got {}",
         result.matches.len()
     );
@@ -273,7 +270,6 @@ async fn test_grep_before_context() {
 async fn test_grep_after_context() {
     let (local_repo_path, _temp_dir) = create_test_repository().await;
     let local_repo = LocalRepository::new(local_repo_path.clone());
-    let repo_location = RepositoryLocation::LocalPath(local_repo.clone());
 
     // Write the test file manually for complete control
     let test_file = local_repo_path.join("after.txt");
@@ -295,9 +291,7 @@ async fn test_grep_after_context() {
 
     // Test with 1 line of after context
     let params = CodeSearchParams {
-        repository_location: repo_location,
         ref_name: None,
-        pattern: "pattern".to_string(),
         case_sensitive: false,
         file_extensions: None,
         include_globs: None,
@@ -307,6 +301,7 @@ async fn test_grep_after_context() {
         skip: None,
         take: None,
         match_content_omit_num: Some(150),
+        ..common::code_search_params(&local_repo_path, "pattern")
     };
 
     // Execute the search
@@ -366,7 +361,6 @@ async fn test_grep_after_context() {
 async fn test_grep_before_after_context() {
     let (local_repo_path, _temp_dir) = create_test_repository().await;
     let local_repo = LocalRepository::new(local_repo_path.clone());
-    let repo_location = RepositoryLocation::LocalPath(local_repo.clone());
 
     // Write the test file manually for complete control
     let test_file = local_repo_path.join("both.txt");
@@ -388,9 +382,7 @@ async fn test_grep_before_after_context() {
 
     // Test with both before and after context
     let params = CodeSearchParams {
-        repository_location: repo_location,
         ref_name: None,
-        pattern: "pattern".to_string(),
         case_sensitive: false,
         file_extensions: None,
         include_globs: None,
@@ -400,6 +392,7 @@ async fn test_grep_before_after_context() {
         skip: None,
         take: None,
         match_content_omit_num: Some(150),
+        ..common::code_search_params(&local_repo_path, "pattern")
     };
 
     // Execute the search
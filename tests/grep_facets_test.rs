@@ -0,0 +1,181 @@
+//! Tests for counting the matches of a code search per file, directory and extension
+
+mod common;
+
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+use common::TestRepository;
+use gitcodes_mcp::gitcodes::local_repository::CodeSearchParams;
+use gitcodes_mcp::gitcodes::repository_manager::RepositoryManager;
+use gitcodes_mcp::gitcodes::{FacetCount, LocalRepository};
+use gitcodes_mcp::services::{self, GrepFacetsParams};
+
+/// Helper function to create a committed repository with TODOs spread over several
+/// directories and file types
+fn create_test_repository() -> (PathBuf, tempfile::TempDir) {
+    let repo = TestRepository::new();
+
+    let files = [
+        (
+            "src/lib.rs",
+            "// TODO one\n// TODO two\n// TODO three\nfn f() {}\n",
+        ),
+        ("src/net/client.rs", "// TODO retry\n"),
+        ("web/app.ts", "// TODO types\n// TODO tests\n"),
+        ("docs/guide.md", "TODO: write the guide\n"),
+        ("Makefile", "# TODO: lint target\n"),
+        ("README.md", "No open tasks\n"),
+    ];
+    repo.write_all(&files).commit("Initial commit");
+
+    repo.into_parts()
+}
+
+fn search_params(repo_path: &Path, pattern: &str) -> CodeSearchParams {
+    CodeSearchParams {
+        ref_name: None,
+        case_sensitive: true,
+        file_extensions: None,
+        include_globs: None,
+        exclude_dirs: None,
+        before_context: None,
+        after_context: None,
+        skip: None,
+        take: None,
+        match_content_omit_num: None,
+        ..common::code_search_params(repo_path, pattern)
+    }
+}
+
+fn facet(value: &str, count: usize) -> FacetCount {
+    FacetCount {
+        value: value.to_string(),
+        count,
+    }
+}
+
+#[tokio::test]
+async fn test_matches_are_counted_per_facet() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let repo = LocalRepository::new(repo_path.clone());
+
+    let result = repo
+        .search_code(search_params(&repo_path, "TODO"))
+        .await
+        .expect("Search failed");
+    let facets = result.facets(10);
+
+    assert_eq!(facets.pattern, "TODO");
+    assert_eq!(facets.total_match_line_number, 8);
+    assert_eq!(facets.total_files, 5);
+    assert_eq!(
+        facets.files,
+        vec![
+            facet("src/lib.rs", 3),
+            facet("web/app.ts", 2),
+            facet("Makefile", 1),
+            facet("docs/guide.md", 1),
+            facet("src/net/client.rs", 1),
+        ]
+    );
+    // Nested directories count towards their top-level directory
+    assert_eq!(
+        facets.directories,
+        vec![
+            facet("src", 4),
+            facet("web", 2),
+            facet(".", 1),
+            facet("docs", 1)
+        ]
+    );
+    assert_eq!(
+        facets.extensions,
+        vec![
+            facet("rs", 4),
+            facet("ts", 2),
+            facet("(none)", 1),
+            facet("md", 1)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_top_n_and_context_lines() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let repo = LocalRepository::new(repo_path.clone());
+
+    // Context lines are part of the result but are not counted
+    let mut params = search_params(&repo_path, "TODO three");
+    params.before_context = Some(2);
+    params.after_context = Some(1);
+    let result = repo.search_code(params).await.expect("Search failed");
+    assert_eq!(result.matches.len(), 4);
+    let facets = result.facets(10);
+    assert_eq!(facets.total_match_line_number, 1);
+    assert_eq!(facets.files, vec![facet("src/lib.rs", 1)]);
+
+    // Only the values with the most matches are listed, the totals count all of them
+    let result = repo
+        .search_code(search_params(&repo_path, "TODO"))
+        .await
+        .expect("Search failed");
+    let facets = result.facets(1);
+    assert_eq!(facets.total_match_line_number, 8);
+    assert_eq!(facets.total_files, 5);
+    assert_eq!(facets.files, vec![facet("src/lib.rs", 3)]);
+    assert_eq!(facets.directories, vec![facet("src", 4)]);
+    assert_eq!(facets.extensions, vec![facet("rs", 4)]);
+
+    let facets = repo
+        .search_code(search_params(&repo_path, "FIXME"))
+        .await
+        .expect("Search failed")
+        .facets(10);
+    assert_eq!(facets.total_match_line_number, 0);
+    assert!(facets.files.is_empty() && facets.directories.is_empty());
+}
+
+#[tokio::test]
+async fn test_facets_through_service() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let cache_dir = tempdir().expect("Failed to create temporary directory");
+    let manager = RepositoryManager::new(None, Some(cache_dir.path().to_path_buf()))
+        .expect("Failed to create RepositoryManager");
+
+    let params = |ref_name: Option<&str>, top_n: Option<usize>| GrepFacetsParams {
+        repository_location_str: repo_path.display().to_string(),
+        pattern: "todo".to_string(),
+        ref_name: ref_name.map(str::to_string),
+        case_sensitive: false,
+        file_extensions: None,
+        include_globs: Some(vec!["src/**".to_string(), "web/**".to_string()]),
+        exclude_dirs: None,
+        top_n,
+        ..Default::default()
+    };
+
+    // Every match is counted, more than the default page of grep_repository
+    let many_todos = "// TODO\n".repeat(120);
+    std::fs::write(repo_path.join("src/lib.rs"), many_todos).unwrap();
+    let (facets, _local_repo) = services::grep_facets(&manager, params(None, None))
+        .await
+        .expect("Grep facets failed");
+    assert_eq!(facets.total_match_line_number, 123);
+    assert_eq!(facets.directories, vec![facet("src", 121), facet("web", 2)]);
+
+    // The committed tree of a ref is counted without the uncommitted changes
+    let (facets, _local_repo) = services::grep_facets(&manager, params(Some("main"), Some(2)))
+        .await
+        .expect("Grep facets failed");
+    assert_eq!(facets.total_match_line_number, 6);
+    assert_eq!(
+        facets.files,
+        vec![facet("src/lib.rs", 3), facet("web/app.ts", 2)]
+    );
+
+    let err = services::grep_facets(&manager, params(None, Some(0)))
+        .await
+        .unwrap_err();
+    assert!(err.contains("top_n"), "unexpected error: {}", err);
+}
@@ -0,0 +1,365 @@
+//! Tests for the pickaxe history search (`git log -S`/`-G` equivalents)
+//!
+//! These tests build a small repository whose history adds, moves and removes a
+//! function, then verify which commits each pickaxe mode reports.
+
+mod common;
+
+use std::path::PathBuf;
+
+use common::TestRepository;
+use gitcodes_mcp::gitcodes::{HistorySearchOptions, LocalRepository, PickaxeMode};
+use gitcodes_mcp::services::{self, HistorySearchServiceParams};
+
+/// Helper function to create a repository with the following history (oldest first)
+///
+/// 1. "Add config parser" adds `src/config.rs` with `parse_config` (tagged `v1.0.0`)
+/// 2. "Reorder config items" moves `parse_config` below a constant
+/// 3. "Document parse_config" adds `docs/usage.md` mentioning `parse_config`
+/// 4. "Remove config parser" deletes `parse_config` from `src/config.rs`
+fn create_test_repository() -> (PathBuf, tempfile::TempDir) {
+    let repo = TestRepository::new();
+
+    repo.write("README.md", "Config tools\n")
+        .write(
+            "src/config.rs",
+            "pub fn parse_config() {}\n\npub const TIMEOUT_MS: u32 = 10;\n",
+        )
+        .commit_at("2024-01-01T10:00:00Z", "Add config parser")
+        .tag("v1.0.0");
+
+    repo.write(
+        "src/config.rs",
+        "pub const TIMEOUT_MS: u32 = 10;\n\npub fn parse_config() {}\n",
+    )
+    .commit_at("2024-01-02T10:00:00Z", "Reorder config items");
+
+    repo.write("docs/usage.md", "Call parse_config first.\n")
+        .commit_at("2024-01-03T10:00:00Z", "Document parse_config");
+
+    repo.write("src/config.rs", "pub const TIMEOUT_MS: u32 = 20;\n")
+        .commit_at("2024-01-04T10:00:00Z", "Remove config parser");
+
+    repo.into_parts()
+}
+
+fn search_options(pattern: &str) -> HistorySearchOptions {
+    HistorySearchOptions {
+        pattern: pattern.to_string(),
+        mode: PickaxeMode::Occurrences,
+        is_regex: false,
+        case_sensitive: true,
+        paths: None,
+        from_ref: None,
+        to_ref: None,
+        max_commits: None,
+        context_lines: None,
+    }
+}
+
+#[tokio::test]
+async fn test_search_history_occurrences() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path);
+
+    let result = local_repo
+        .search_history(search_options("parse_config"))
+        .await
+        .expect("History search failed");
+
+    // Moving the function around does not change its occurrence count
+    let summaries: Vec<&str> = result
+        .commits
+        .iter()
+        .map(|commit| commit.summary.as_str())
+        .collect();
+    assert_eq!(
+        summaries,
+        vec![
+            "Remove config parser",
+            "Document parse_config",
+            "Add config parser"
+        ]
+    );
+    assert_eq!(result.scanned_commits, 4);
+    assert!(!result.truncated);
+
+    let removal = &result.commits[0];
+    assert_eq!(removal.author_name, "Test User");
+    assert_eq!(removal.author_email, "test@example.com");
+    assert!(removal.date.starts_with("2024-01-04"));
+    assert_eq!(removal.files.len(), 1);
+    let file = &removal.files[0];
+    assert_eq!(file.file_path, "src/config.rs");
+    assert_eq!(file.change_type, "modified");
+    assert_eq!(file.old_occurrences, Some(1));
+    assert_eq!(file.new_occurrences, Some(0));
+    assert_eq!(file.hunks.len(), 1);
+    assert!(file.hunks[0].content.contains("-pub fn parse_config() {}"));
+
+    let addition = &result.commits[2];
+    assert_eq!(addition.files[0].file_path, "src/config.rs");
+    assert_eq!(addition.files[0].change_type, "added");
+    assert_eq!(addition.files[0].old_occurrences, Some(0));
+    assert_eq!(addition.files[0].new_occurrences, Some(1));
+}
+
+#[tokio::test]
+async fn test_search_history_diff_match() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path);
+
+    let mut options = search_options(r"fn\s+parse_config");
+    options.mode = PickaxeMode::DiffMatch;
+    let result = local_repo
+        .search_history(options)
+        .await
+        .expect("History search failed");
+
+    // Unlike the occurrence mode, the reordering commit touches a matching line
+    let summaries: Vec<&str> = result
+        .commits
+        .iter()
+        .map(|commit| commit.summary.as_str())
+        .collect();
+    assert_eq!(
+        summaries,
+        vec![
+            "Remove config parser",
+            "Reorder config items",
+            "Add config parser"
+        ]
+    );
+    for commit in &result.commits {
+        assert_eq!(commit.files[0].old_occurrences, None);
+        assert!(commit.files[0].hunks.iter().any(|hunk| {
+            hunk.changed_lines()
+                .any(|line| line.contains("parse_config"))
+        }));
+    }
+}
+
+#[tokio::test]
+async fn test_search_history_regex_and_case() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path);
+
+    // A literal pattern is not interpreted as a regex
+    let literal = local_repo
+        .search_history(search_options("parse_.*"))
+        .await
+        .expect("History search failed");
+    assert!(literal.commits.is_empty());
+
+    let mut options = search_options("TIMEOUT_MS: u32 = \\d+");
+    options.is_regex = true;
+    let regex = local_repo
+        .search_history(options)
+        .await
+        .expect("History search failed");
+    let summaries: Vec<&str> = regex
+        .commits
+        .iter()
+        .map(|commit| commit.summary.as_str())
+        .collect();
+    assert_eq!(summaries, vec!["Add config parser"]);
+
+    let mut options = search_options("PARSE_CONFIG");
+    let case_sensitive = local_repo
+        .search_history(options.clone())
+        .await
+        .expect("History search failed");
+    assert!(case_sensitive.commits.is_empty());
+
+    options.case_sensitive = false;
+    let case_insensitive = local_repo
+        .search_history(options)
+        .await
+        .expect("History search failed");
+    assert_eq!(case_insensitive.commits.len(), 3);
+
+    let mut options = search_options("(unclosed");
+    options.is_regex = true;
+    let invalid = local_repo.search_history(options).await;
+    assert!(invalid.unwrap_err().contains("Invalid pattern"));
+}
+
+#[tokio::test]
+async fn test_search_history_paths_and_range() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path);
+
+    let mut options = search_options("parse_config");
+    options.paths = Some(vec!["docs".to_string()]);
+    let docs_only = local_repo
+        .search_history(options)
+        .await
+        .expect("History search failed");
+    assert_eq!(docs_only.commits.len(), 1);
+    assert_eq!(docs_only.commits[0].summary, "Document parse_config");
+    assert_eq!(docs_only.commits[0].files[0].file_path, "docs/usage.md");
+
+    let mut options = search_options("parse_config");
+    options.paths = Some(vec!["src/**/*.rs".to_string()]);
+    let sources_only = local_repo
+        .search_history(options)
+        .await
+        .expect("History search failed");
+    assert_eq!(sources_only.commits.len(), 2);
+
+    // Commits reachable from the tag are excluded
+    let mut options = search_options("parse_config");
+    options.from_ref = Some("v1.0.0".to_string());
+    let since_tag = local_repo
+        .search_history(options)
+        .await
+        .expect("History search failed");
+    assert_eq!(since_tag.range, "v1.0.0..HEAD");
+    assert_eq!(since_tag.scanned_commits, 3);
+    assert_eq!(since_tag.commits.len(), 2);
+
+    // The end of the range can be an older commit
+    let mut options = search_options("parse_config");
+    options.to_ref = Some("HEAD~2".to_string());
+    let until_reorder = local_repo
+        .search_history(options)
+        .await
+        .expect("History search failed");
+    assert_eq!(until_reorder.commits.len(), 1);
+    assert_eq!(until_reorder.commits[0].summary, "Add config parser");
+
+    let mut options = search_options("parse_config");
+    options.max_commits = Some(1);
+    let limited = local_repo
+        .search_history(options)
+        .await
+        .expect("History search failed");
+    assert_eq!(limited.commits.len(), 1);
+    assert!(limited.truncated);
+}
+
+#[tokio::test]
+async fn test_search_history_range_with_equal_commit_times() {
+    // Branches whose commits all share one time, so only the walk order tells them apart
+    let repo = TestRepository::new();
+    let date = "2024-01-01T10:00:00Z";
+    repo.write("base.txt", "marker 0\n").commit_at(date, "Base");
+    repo.git(&["branch", "feature"]);
+    for i in 1..=4 {
+        repo.write(&format!("main_{}.txt", i), "marker\n")
+            .commit_at(date, &format!("Main {}", i));
+    }
+    repo.git(&["checkout", "feature"]);
+    repo.write("feature.txt", "marker 5\n")
+        .commit_at(date, "Feature");
+    let (repo_path, _temp_dir) = repo.into_parts();
+    let local_repo = LocalRepository::new(repo_path);
+
+    let summaries = |from: &str, to: &str| {
+        let mut options = search_options("marker");
+        options.from_ref = Some(from.to_string());
+        options.to_ref = Some(to.to_string());
+        let local_repo = &local_repo;
+        async move {
+            let result = local_repo
+                .search_history(options)
+                .await
+                .expect("History search failed");
+            let mut summaries: Vec<String> = result
+                .commits
+                .into_iter()
+                .map(|commit| commit.summary)
+                .collect();
+            summaries.sort();
+            summaries
+        }
+    };
+
+    assert_eq!(summaries("main", "feature").await, vec!["Feature"]);
+    assert_eq!(
+        summaries("feature", "main").await,
+        vec!["Main 1", "Main 2", "Main 3", "Main 4"]
+    );
+    assert!(summaries("main", "main~2").await.is_empty());
+}
+
+#[tokio::test]
+async fn test_search_history_service() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let manager = gitcodes_mcp::gitcodes::repository_manager::RepositoryManager::new(None, None)
+        .expect("Failed to create repository manager");
+
+    let params = HistorySearchServiceParams {
+        repository_location_str: repo_path.to_string_lossy().to_string(),
+        pattern: "TIMEOUT_MS: u32 = 20".to_string(),
+        mode: None,
+        is_regex: None,
+        case_sensitive: None,
+        paths: None,
+        from_ref: None,
+        to_ref: None,
+        max_commits: None,
+        context_lines: Some(0),
+    };
+
+    let (result, _local_repo) = services::search_history(&manager, params)
+        .await
+        .expect("History search service failed");
+    assert_eq!(result.mode, PickaxeMode::Occurrences);
+    // Modes are named in snake_case, like the other enums of the tools
+    assert_eq!(
+        serde_json::to_value(result.mode).unwrap(),
+        serde_json::json!("occurrences")
+    );
+    assert_eq!(
+        serde_json::from_value::<PickaxeMode>(serde_json::json!("diff_match")).unwrap(),
+        PickaxeMode::DiffMatch
+    );
+    assert_eq!(result.commits.len(), 1);
+    let hunk = &result.commits[0].files[0].hunks[0];
+    assert_eq!(hunk.old_start, 1);
+    assert_eq!(hunk.old_lines, 3);
+    assert!(hunk.content.starts_with("-pub const TIMEOUT_MS: u32 = 10;"));
+    assert!(hunk.content.ends_with("+pub const TIMEOUT_MS: u32 = 20;"));
+}
+
+#[tokio::test]
+async fn test_search_history_service_fetches_refs_missing_from_a_clone() {
+    let (upstream_path, _upstream_dir) = create_test_repository();
+    let clone_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let clone_path = clone_dir.path().join("clone");
+    common::git(
+        clone_dir.path(),
+        &["clone", &upstream_path.to_string_lossy(), "clone"],
+    );
+
+    // Released upstream after the clone was made
+    common::write_file(
+        &upstream_path,
+        "src/config.rs",
+        "pub fn parse_config() {}\n",
+    );
+    common::git(&upstream_path, &["commit", "-am", "Restore config parser"]);
+    common::git(&upstream_path, &["tag", "v2.0.0"]);
+
+    let manager = gitcodes_mcp::gitcodes::repository_manager::RepositoryManager::new(None, None)
+        .expect("Failed to create repository manager");
+    let params = HistorySearchServiceParams {
+        repository_location_str: clone_path.to_string_lossy().to_string(),
+        pattern: "parse_config".to_string(),
+        mode: None,
+        is_regex: None,
+        case_sensitive: None,
+        paths: None,
+        from_ref: Some("v1.0.0".to_string()),
+        to_ref: Some("v2.0.0".to_string()),
+        max_commits: None,
+        context_lines: None,
+    };
+
+    let (result, _local_repo) = services::search_history(&manager, params)
+        .await
+        .expect("History search service failed");
+    assert_eq!(result.range, "v1.0.0..v2.0.0");
+    assert_eq!(result.commits[0].summary, "Restore config parser");
+}
@@ -0,0 +1,226 @@
+//! Tests for change hotspot analysis
+//!
+//! These tests build a small repository whose files change at different rates and check
+//! the ranking by commits and churn, size weighting, path filters and time windows.
+
+mod common;
+
+use std::path::PathBuf;
+
+use common::TestRepository;
+use gitcodes_mcp::gitcodes::{HotspotMetric, HotspotOptions, HotspotReport, LocalRepository};
+use gitcodes_mcp::services::{self, HotspotsServiceParams};
+
+fn numbered_lines(prefix: &str, count: usize) -> String {
+    (1..=count)
+        .map(|i| format!("{}{}", prefix, i))
+        .collect::<Vec<_>>()
+        .join("\n")
+        + "\n"
+}
+
+/// Helper function to create a repository with this history:
+///
+/// * 2023-01-01 adds `src/lib.rs` (10 lines), `src/util.rs` (2), `README.md` (1), `docs/guide.md` (5)
+/// * 2023-02-01 changes one line of `src/lib.rs` (tagged `v0.1.0`)
+/// * 2023-03-01 appends 2 lines to `src/lib.rs` and 1 to `src/util.rs`
+/// * 2023-04-01 changes one line of `src/util.rs` and adds `tmp.txt`
+/// * 2023-05-01 deletes `tmp.txt` and appends a line to `docs/guide.md`
+fn create_test_repository() -> (PathBuf, tempfile::TempDir) {
+    let repo = TestRepository::new();
+
+    repo.write("src/lib.rs", &numbered_lines("l", 10))
+        .write("src/util.rs", "u1\nu2\n")
+        .write("README.md", "readme\n")
+        .write("docs/guide.md", &numbered_lines("g", 5))
+        .commit_at("2023-01-01T10:00:00Z", "Initial commit");
+
+    repo.write(
+        "src/lib.rs",
+        &numbered_lines("l", 10).replace("l5\n", "L5\n"),
+    )
+    .commit_at("2023-02-01T10:00:00Z", "Fix lib")
+    .tag("v0.1.0");
+
+    repo.write(
+        "src/lib.rs",
+        &(numbered_lines("l", 12).replace("l5\n", "L5\n")),
+    )
+    .write("src/util.rs", "u1\nu2\nu3\n")
+    .commit_at("2023-03-01T10:00:00Z", "Extend lib and util");
+
+    repo.write("src/util.rs", "U1\nu2\nu3\n")
+        .write("tmp.txt", "tmp\n")
+        .commit_at("2023-04-01T10:00:00Z", "Tweak util");
+
+    std::fs::remove_file(repo.path().join("tmp.txt")).unwrap();
+    repo.write("docs/guide.md", &numbered_lines("g", 6))
+        .commit_at("2023-05-01T10:00:00Z", "Extend guide");
+
+    repo.into_parts()
+}
+
+fn file_paths(report: &HotspotReport) -> Vec<&str> {
+    report.files.iter().map(|file| file.path.as_str()).collect()
+}
+
+#[tokio::test]
+async fn test_hotspots_by_commits() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path);
+
+    let report = local_repo
+        .hotspots(HotspotOptions::default())
+        .await
+        .expect("Failed to analyze hotspots");
+
+    assert_eq!(report.range, "HEAD");
+    assert_eq!(report.scanned_commits, 5);
+    // The deleted tmp.txt is not ranked
+    assert_eq!(report.total_files, 4);
+    // lib.rs and util.rs both changed 3 times; the churn breaks the tie
+    assert_eq!(
+        file_paths(&report),
+        vec!["src/lib.rs", "src/util.rs", "docs/guide.md", "README.md"]
+    );
+
+    let lib = &report.files[0];
+    assert_eq!(lib.commits, 3);
+    assert_eq!(lib.authors, 1);
+    assert_eq!(lib.lines_added, 13);
+    assert_eq!(lib.lines_removed, 1);
+    assert_eq!(lib.lines, 12);
+    assert_eq!(lib.score, 3.0);
+    assert!(lib.last_changed.starts_with("2023-03-01"));
+
+    let directories: Vec<(&str, usize, usize, f64)> = report
+        .directories
+        .iter()
+        .map(|directory| {
+            (
+                directory.path.as_str(),
+                directory.files,
+                directory.commits,
+                directory.score,
+            )
+        })
+        .collect();
+    assert_eq!(directories, vec![("src", 2, 4, 6.0), ("docs", 1, 2, 2.0)]);
+}
+
+#[tokio::test]
+async fn test_hotspots_weighted_churn() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path);
+
+    let report = local_repo
+        .hotspots(HotspotOptions {
+            metric: HotspotMetric::Churn,
+            weight_by_size: true,
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to analyze hotspots");
+
+    let scores: Vec<(&str, f64)> = report
+        .files
+        .iter()
+        .map(|file| (file.path.as_str(), file.score))
+        .collect();
+    assert_eq!(
+        scores,
+        vec![
+            ("src/lib.rs", 168.0),
+            ("docs/guide.md", 36.0),
+            ("src/util.rs", 15.0),
+            ("README.md", 1.0)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_hotspots_filters_and_windows() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path);
+
+    let included = local_repo
+        .hotspots(HotspotOptions {
+            include_globs: Some(vec!["src/**".to_string()]),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to analyze hotspots");
+    assert_eq!(file_paths(&included), vec!["src/lib.rs", "src/util.rs"]);
+
+    let excluded = local_repo
+        .hotspots(HotspotOptions {
+            exclude_globs: Some(vec!["docs".to_string()]),
+            max_results: Some(1),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to analyze hotspots");
+    assert_eq!(excluded.total_files, 3);
+    assert_eq!(file_paths(&excluded), vec!["src/lib.rs"]);
+
+    let recent = local_repo
+        .hotspots(HotspotOptions {
+            since: Some("2023-03-01".to_string()),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to analyze hotspots");
+    assert_eq!(recent.scanned_commits, 3);
+    assert_eq!(recent.files[0].path, "src/util.rs");
+    assert_eq!(recent.files[0].commits, 2);
+
+    let since_tag = local_repo
+        .hotspots(HotspotOptions {
+            from_ref: Some("v0.1.0".to_string()),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to analyze hotspots");
+    assert_eq!(since_tag.range, "v0.1.0..HEAD");
+    assert_eq!(since_tag.scanned_commits, 3);
+    assert!(!file_paths(&since_tag).contains(&"README.md"));
+}
+
+#[tokio::test]
+async fn test_hotspots_service_fetches_refs_missing_from_a_clone() {
+    let (upstream_path, _upstream_dir) = create_test_repository();
+    let clone_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let clone_path = clone_dir.path().join("clone");
+    common::git(
+        clone_dir.path(),
+        &["clone", &upstream_path.to_string_lossy(), "clone"],
+    );
+
+    // Released upstream after the clone was made
+    common::write_file(&upstream_path, "README.md", "readme\nmore\n");
+    common::git(&upstream_path, &["commit", "-am", "Extend readme"]);
+    common::git(&upstream_path, &["tag", "v0.2.0"]);
+
+    let manager = gitcodes_mcp::gitcodes::repository_manager::RepositoryManager::new(None, None)
+        .expect("Failed to create repository manager");
+    let params = HotspotsServiceParams {
+        repository_location_str: clone_path.to_string_lossy().to_string(),
+        from_ref: Some("HEAD".to_string()),
+        to_ref: Some("v0.2.0".to_string()),
+        since: None,
+        until: None,
+        include_globs: None,
+        exclude_dirs: None,
+        metric: None,
+        weight_by_size: None,
+        directory_depth: None,
+        max_results: None,
+    };
+
+    let (report, _local_repo) = services::hotspots(&manager, params)
+        .await
+        .expect("Hotspots service failed");
+    assert_eq!(report.range, "HEAD..v0.2.0");
+    assert_eq!(report.scanned_commits, 1);
+    assert_eq!(file_paths(&report), vec!["README.md"]);
+}
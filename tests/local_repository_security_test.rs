@@ -4,11 +4,12 @@
 //! particularly focusing on preventing directory traversal attacks and other
 //! file system security vulnerabilities.
 
+mod common;
+
 use std::path::PathBuf;
 
 use gitcodes_mcp::gitcodes::local_repository::prevent_directory_traversal;
 use gitcodes_mcp::gitcodes::local_repository::*;
-use gitcodes_mcp::gitcodes::repository_manager::RepositoryLocation;
 
 /// Creates a test repository instance
 fn create_test_repo() -> LocalRepository {
@@ -208,9 +209,7 @@ async fn test_search_code_integration() {
 
     // Test that search_code properly checks exclude_dirs for directory traversal
     let params = CodeSearchParams {
-        repository_location: RepositoryLocation::LocalPath(repo.clone()),
         ref_name: None,
-        pattern: "test".to_string(),
         case_sensitive: false,
         file_extensions: None,
         include_globs: None,
@@ -223,6 +222,7 @@ async fn test_search_code_integration() {
         skip: None,
         take: None,
         match_content_omit_num: Some(150),
+        ..common::code_search_params(repo.get_repository_dir(), "test")
     };
 
     let result = repo.search_code(params).await;
@@ -10,6 +10,8 @@
 //! - Dynamic cloning ensures tests work in any environment with internet access
 //! - Follows the same patterns as other integration tests in the project
 
+mod common;
+
 use std::path::PathBuf;
 use std::str::FromStr;
 use tempfile::tempdir;
@@ -67,14 +69,9 @@ async fn test_local_repository_validation() {
 async fn test_grep_basic_pattern() {
     let local_repo = get_test_repository().await;
 
-    // Create repository location
-    let repo_location = RepositoryLocation::LocalPath(local_repo.clone());
-
     // Test search for a basic function pattern
     let params = CodeSearchParams {
-        repository_location: repo_location,
         ref_name: None,
-        pattern: "fn ".to_string(), // search for function declarations
         case_sensitive: false,
         file_extensions: Some(vec!["rs".to_string()]),
         include_globs: None,
@@ -84,6 +81,7 @@ async fn test_grep_basic_pattern() {
         skip: None,
         take: None,
         match_content_omit_num: Some(150),
+        ..common::code_search_params(local_repo.get_repository_dir(), "fn ") // search for function declarations
     };
 
     // Execute the search
@@ -116,13 +114,10 @@ async fn test_grep_basic_pattern() {
 #[tokio::test]
 async fn test_grep_case_sensitive() {
     let local_repo = get_test_repository().await;
-    let repo_location = RepositoryLocation::LocalPath(local_repo.clone());
 
     // Test with case-sensitive search
     let params_case_sensitive = CodeSearchParams {
-        repository_location: repo_location.clone(),
         ref_name: None,
-        pattern: "Error".to_string(), // Capital E
         case_sensitive: true,
         file_extensions: Some(vec!["rs".to_string()]),
         include_globs: None,
@@ -132,6 +127,7 @@ async fn test_grep_case_sensitive() {
         skip: None,
         take: None,
         match_content_omit_num: Some(150),
+        ..common::code_search_params(local_repo.get_repository_dir(), "Error") // Capital E
     };
 
     // Execute the case-sensitive search
@@ -143,9 +139,7 @@ async fn test_grep_case_sensitive() {
 
     // Test with case-insensitive search (should find more)
     let params_case_insensitive = CodeSearchParams {
-        repository_location: repo_location,
         ref_name: None,
-        pattern: "Error".to_string(), // Same term
         case_sensitive: false,
         file_extensions: Some(vec!["rs".to_string()]),
         include_globs: None,
@@ -155,6 +149,7 @@ async fn test_grep_case_sensitive() {
         skip: None,
         take: None,
         match_content_omit_num: Some(150),
+        ..common::code_search_params(local_repo.get_repository_dir(), "Error") // Same term
     };
 
     // Execute the case-insensitive search
@@ -185,16 +180,13 @@ async fn test_grep_case_sensitive() {
 #[tokio::test]
 async fn test_grep_file_extension_filter() {
     let local_repo = get_test_repository().await;
-    let repo_location = RepositoryLocation::LocalPath(local_repo.clone());
 
     // Common pattern that would exist in multiple file types
     let search_pattern = "test";
 
     // Search with .rs extension filter
     let params_rs = CodeSearchParams {
-        repository_location: repo_location.clone(),
         ref_name: None,
-        pattern: search_pattern.to_string(),
         case_sensitive: false,
         file_extensions: Some(vec!["rs".to_string()]),
         include_globs: None,
@@ -204,6 +196,7 @@ async fn test_grep_file_extension_filter() {
         skip: None,
         take: None,
         match_content_omit_num: Some(150),
+        ..common::code_search_params(local_repo.get_repository_dir(), search_pattern)
     };
 
     // Execute the search with .rs filter
@@ -225,9 +218,7 @@ async fn test_grep_file_extension_filter() {
 
     // Test search with a different extension (toml)
     let params_toml = CodeSearchParams {
-        repository_location: repo_location.clone(),
         ref_name: None,
-        pattern: search_pattern.to_string(),
         case_sensitive: false,
         file_extensions: Some(vec!["toml".to_string()]),
         include_globs: None,
@@ -237,6 +228,7 @@ async fn test_grep_file_extension_filter() {
         skip: None,
         take: None,
         match_content_omit_num: Some(150),
+        ..common::code_search_params(local_repo.get_repository_dir(), search_pattern)
     };
 
     // Execute the search with .toml filter
@@ -267,16 +259,13 @@ async fn test_grep_file_extension_filter() {
 #[tokio::test]
 async fn test_grep_exclude_dirs() {
     let local_repo = get_test_repository().await;
-    let repo_location = RepositoryLocation::LocalPath(local_repo.clone());
 
     // Search pattern that would exist in multiple directories
     let search_pattern = "fn ";
 
     // Search without exclusions
     let params_no_exclusion = CodeSearchParams {
-        repository_location: repo_location.clone(),
         ref_name: None,
-        pattern: search_pattern.to_string(),
         case_sensitive: false,
         file_extensions: Some(vec!["rs".to_string()]),
         include_globs: None,
@@ -286,6 +275,7 @@ async fn test_grep_exclude_dirs() {
         skip: None,
         take: None,
         match_content_omit_num: Some(150),
+        ..common::code_search_params(local_repo.get_repository_dir(), search_pattern)
     };
 
     // Execute the search without exclusions
@@ -350,9 +340,7 @@ async fn test_grep_exclude_dirs() {
 
     // Now search with the chosen directory excluded
     let params_with_exclusion = CodeSearchParams {
-        repository_location: repo_location,
         ref_name: None,
-        pattern: search_pattern.to_string(),
         case_sensitive: false,
         file_extensions: Some(vec!["rs".to_string()]),
         include_globs: None,
@@ -362,6 +350,7 @@ async fn test_grep_exclude_dirs() {
         skip: None,
         take: None,
         match_content_omit_num: Some(150),
+        ..common::code_search_params(local_repo.get_repository_dir(), search_pattern)
     };
 
     // Execute the search with exclusions
@@ -410,15 +399,12 @@ async fn test_grep_exclude_dirs() {
 #[tokio::test]
 async fn test_grep_regex_pattern() {
     let local_repo = get_test_repository().await;
-    let repo_location = RepositoryLocation::LocalPath(local_repo.clone());
 
     // Complex regex pattern to find trait implementations
     let regex_pattern = r"impl\s+\w+";
 
     let params = CodeSearchParams {
-        repository_location: repo_location,
         ref_name: None,
-        pattern: regex_pattern.to_string(),
         case_sensitive: false,
         file_extensions: Some(vec!["rs".to_string()]),
         include_globs: None,
@@ -428,6 +414,7 @@ async fn test_grep_regex_pattern() {
         skip: None,
         take: None,
         match_content_omit_num: Some(150),
+        ..common::code_search_params(local_repo.get_repository_dir(), regex_pattern)
     };
 
     // Execute the search with regex pattern
@@ -0,0 +1,238 @@
+//! Tests for running one code search in several repositories
+
+mod common;
+
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+use common::TestRepository;
+use gitcodes_mcp::gitcodes::ContextMode;
+use gitcodes_mcp::gitcodes::repository_manager::RepositoryManager;
+use gitcodes_mcp::services::{self, MultiRepositoryGrepParams};
+use gitcodes_mcp::tools::responses::MultiRepositoryCodeSearchResponse;
+
+/// Helper function to create a committed repository named `name` holding `src/lib.rs`
+fn create_test_repository(name: &str, lib_rs: &str) -> (PathBuf, tempfile::TempDir) {
+    let repo = TestRepository::init(Some(name), "main");
+    repo.write("src/lib.rs", lib_rs).commit("Initial commit");
+    repo.into_parts()
+}
+
+fn grep_params(repository_locations: Vec<String>, pattern: &str) -> MultiRepositoryGrepParams {
+    MultiRepositoryGrepParams {
+        repository_location_strs: repository_locations,
+        max_concurrency: None,
+        pattern: pattern.to_string(),
+        ref_name: None,
+        case_sensitive: false,
+        file_extensions: None,
+        include_globs: None,
+        exclude_dirs: None,
+        before_context: None,
+        after_context: None,
+        skip: None,
+        take: None,
+        match_content_omit_num: None,
+        ..Default::default()
+    }
+}
+
+fn create_test_manager(cache_dir: &Path) -> RepositoryManager {
+    RepositoryManager::new(None, Some(cache_dir.to_path_buf()))
+        .expect("Failed to create RepositoryManager")
+}
+
+#[tokio::test]
+async fn test_search_in_several_repositories() {
+    let (tokio_like, _tokio_dir) = create_test_repository(
+        "tokio_like",
+        "pub fn block_on() {}\npub fn spawn() {}\npub fn spawn_blocking() {}\n",
+    );
+    let (smol_like, _smol_dir) = create_test_repository("smol_like", "pub fn block_on() {}\n");
+    let (empty_like, empty_dir) = create_test_repository("empty_like", "pub struct Nothing;\n");
+    let missing = empty_dir.path().join("missing");
+
+    let locations: Vec<String> = [&tokio_like, &smol_like, &missing, &empty_like, &tokio_like]
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect();
+    let cache_dir = tempdir().expect("Failed to create temporary directory");
+    let manager = create_test_manager(cache_dir.path());
+
+    let outcomes = services::perform_grep_in_repositories(
+        &manager,
+        MultiRepositoryGrepParams {
+            max_concurrency: Some(2),
+            take: Some(2),
+            ..grep_params(locations.clone(), r"fn (block_on|spawn)")
+        },
+    )
+    .await
+    .expect("Search failed");
+
+    // One outcome per distinct location, in the given order
+    let order: Vec<&str> = outcomes
+        .iter()
+        .map(|outcome| outcome.repository_location_str.as_str())
+        .collect();
+    assert_eq!(
+        order,
+        locations[..4]
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+    );
+
+    // Pagination applies per repository
+    let (tokio_result, _) = outcomes[0].result.as_ref().expect("Search failed");
+    assert_eq!(tokio_result.total_match_line_number, 3);
+    assert_eq!(tokio_result.matches.len(), 2);
+    let (smol_result, _) = outcomes[1].result.as_ref().expect("Search failed");
+    assert_eq!(smol_result.total_match_line_number, 1);
+
+    // A location that can't be searched doesn't fail the others
+    let err = outcomes[2].result.as_ref().unwrap_err();
+    assert!(
+        err.contains("Failed to parse repository location"),
+        "unexpected error: {}",
+        err
+    );
+    let (empty_result, _) = outcomes[3].result.as_ref().expect("Search failed");
+    assert!(empty_result.matches.is_empty());
+
+    let response = MultiRepositoryCodeSearchResponse::from_outcomes(
+        "fn (block_on|spawn)".to_string(),
+        outcomes,
+    );
+    assert_eq!(response.total_match_line_number, 4);
+    assert_eq!(response.repositories.len(), 4);
+    assert!(response.repositories[2].result.is_none());
+    assert!(response.repositories[2].error.is_some());
+    let json = serde_json::to_value(&response).unwrap();
+    assert!(json["repositories"][2].get("result").is_none());
+    assert!(json["repositories"][0].get("error").is_none());
+    assert_eq!(
+        json["repositories"][1]["result"]["matches"][0]["lines"],
+        "1:pub fn block_on() {}"
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_multi_repository_search() {
+    let (repo_path, _temp_dir) = create_test_repository("repo", "pub fn block_on() {}\n");
+    let cache_dir = tempdir().expect("Failed to create temporary directory");
+    let manager = create_test_manager(cache_dir.path());
+
+    let err = services::perform_grep_in_repositories(&manager, grep_params(Vec::new(), "fn"))
+        .await
+        .unwrap_err();
+    assert!(
+        err.contains("At least one repository location"),
+        "unexpected error: {}",
+        err
+    );
+
+    let err = services::perform_grep_in_repositories(
+        &manager,
+        MultiRepositoryGrepParams {
+            max_concurrency: Some(0),
+            ..grep_params(vec![repo_path.display().to_string()], "fn")
+        },
+    )
+    .await
+    .unwrap_err();
+    assert!(err.contains("max_concurrency"), "unexpected error: {}", err);
+
+    // An invalid pattern is reported for each repository
+    let outcomes = services::perform_grep_in_repositories(
+        &manager,
+        grep_params(vec![repo_path.display().to_string()], "fn ("),
+    )
+    .await
+    .expect("Search failed");
+    assert_eq!(outcomes.len(), 1);
+    assert!(outcomes[0].result.is_err());
+}
+
+#[tokio::test]
+async fn test_search_saved_collection() {
+    let (tokio_like, _tokio_dir) =
+        create_test_repository("tokio_like", "pub fn block_on() {\n    run();\n}\n");
+    let (smol_like, _smol_dir) = create_test_repository("smol_like", "pub fn block_on() {}\n");
+    let cache_dir = tempdir().expect("Failed to create temporary directory");
+    let manager = create_test_manager(cache_dir.path());
+
+    // The file URL of a repository given directly is the same repository
+    manager
+        .save_repository_collection(
+            "runtimes",
+            vec![
+                format!("file://{}", tokio_like.display()),
+                smol_like.display().to_string(),
+            ],
+        )
+        .expect("Failed to save collection");
+    // Saved through a temporary file that is moved into place
+    let cached_files: Vec<String> = std::fs::read_dir(cache_dir.path())
+        .unwrap()
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    assert_eq!(cached_files, vec!["gitcodes_repository_collections.json"]);
+    let outcomes = services::perform_grep_in_repositories(
+        &manager,
+        MultiRepositoryGrepParams {
+            collection: Some("runtimes".to_string()),
+            ..grep_params(vec![tokio_like.display().to_string()], "block_on|run")
+        },
+    )
+    .await
+    .expect("Search failed");
+    let order: Vec<String> = outcomes
+        .iter()
+        .map(|outcome| outcome.repository_location_str.clone())
+        .collect();
+    assert_eq!(
+        order,
+        vec![
+            tokio_like.display().to_string(),
+            smol_like.display().to_string()
+        ]
+    );
+
+    // A collection alone is enough, and the search options reach every repository
+    let outcomes = services::perform_grep_in_repositories(
+        &manager,
+        MultiRepositoryGrepParams {
+            collection: Some("runtimes".to_string()),
+            use_index: true,
+            context_mode: Some(ContextMode::EnclosingItem),
+            ..grep_params(Vec::new(), r"run\(")
+        },
+    )
+    .await
+    .expect("Search failed");
+    assert_eq!(outcomes.len(), 2);
+    let (tokio_result, _) = outcomes[0].result.as_ref().expect("Search failed");
+    let lines: Vec<(u64, bool)> = tokio_result
+        .matches
+        .iter()
+        .map(|m| (m.line_number, m.is_context))
+        .collect();
+    assert_eq!(lines, vec![(1, true), (2, false), (3, true)]);
+
+    let err = services::perform_grep_in_repositories(
+        &manager,
+        MultiRepositoryGrepParams {
+            collection: Some("missing".to_string()),
+            ..grep_params(Vec::new(), "fn")
+        },
+    )
+    .await
+    .unwrap_err();
+    assert!(
+        err.contains("No repository collection named 'missing'") && err.contains("runtimes"),
+        "unexpected error: {}",
+        err
+    );
+}
@@ -0,0 +1,435 @@
+//! Tests for reading files, trees and grep results at a ref straight from the git object database
+//!
+//! These tests build a small repository with two commits and a tag, then verify that
+//! the object database readers see the content of the requested ref regardless of
+//! what is checked out in the worktree.
+
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use common::TestRepository;
+use gitcodes_mcp::gitcodes::repository_manager::RepositoryManager;
+use gitcodes_mcp::gitcodes::repository_manager::providers::GitRemoteRepositoryInfo;
+use gitcodes_mcp::gitcodes::{
+    CodeSearchParams, LocalRepository, TreeEntry, TreeParams, ViewFileParams,
+};
+use gitcodes_mcp::services::{self, GrepParams, ShowFileParams, TreeServiceParams};
+use lumin::view::FileContents;
+
+/// Helper function to create a repository whose tagged commit differs from HEAD
+///
+/// * `v1.0.0` contains `README.md` ("version one") and `src/lib.rs`
+/// * HEAD changes `README.md` to "version two" and adds `docs/guide.md`
+fn create_test_repository() -> (PathBuf, tempfile::TempDir) {
+    let repo = TestRepository::new();
+
+    repo.write("README.md", "version one\n")
+        .write(
+            "src/lib.rs",
+            "pub fn first() {}\npub fn second() {}\npub fn third() {}\n",
+        )
+        .commit("Initial commit")
+        .tag("v1.0.0");
+
+    repo.write("README.md", "version two\n")
+        .write("docs/guide.md", "# Guide\n")
+        .commit("Second commit");
+
+    repo.into_parts()
+}
+
+fn text_lines(contents: &FileContents) -> Vec<String> {
+    match contents {
+        FileContents::Text { content, .. } => content
+            .line_contents
+            .iter()
+            .map(|line| line.line.clone())
+            .collect(),
+        other => panic!("Expected text contents, got {:?}", other),
+    }
+}
+
+fn view_params(file_path: &str) -> ViewFileParams {
+    ViewFileParams {
+        file_path: PathBuf::from(file_path),
+        max_size: None,
+        line_from: None,
+        line_to: None,
+    }
+}
+
+#[tokio::test]
+async fn test_view_file_contents_at_ref() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path);
+
+    let old = local_repo
+        .view_file_contents_at_ref("v1.0.0", view_params("README.md"))
+        .await
+        .expect("Failed to read README.md at v1.0.0");
+    assert_eq!(text_lines(&old), vec!["version one"]);
+
+    let new = local_repo
+        .view_file_contents_at_ref("main", view_params("README.md"))
+        .await
+        .expect("Failed to read README.md at main");
+    assert_eq!(text_lines(&new), vec!["version two"]);
+
+    // Abbreviated commit ids resolve as well
+    let commit_id = local_repo
+        .resolve_commit_id("v1.0.0")
+        .expect("Failed to resolve v1.0.0");
+    let by_commit = local_repo
+        .view_file_contents_at_ref(&commit_id[..8], view_params("README.md"))
+        .await
+        .expect("Failed to read README.md by commit id");
+    assert_eq!(text_lines(&by_commit), vec!["version one"]);
+}
+
+#[tokio::test]
+async fn test_view_file_contents_at_ref_line_range() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path);
+
+    let params = ViewFileParams {
+        file_path: PathBuf::from("src/lib.rs"),
+        max_size: None,
+        line_from: Some(2),
+        line_to: Some(3),
+    };
+    let contents = local_repo
+        .view_file_contents_at_ref("v1.0.0", params)
+        .await
+        .expect("Failed to read src/lib.rs");
+
+    match contents {
+        FileContents::Text { content, metadata } => {
+            assert_eq!(metadata.line_count, 3);
+            let numbers: Vec<usize> = content
+                .line_contents
+                .iter()
+                .map(|line| line.line_number)
+                .collect();
+            assert_eq!(numbers, vec![2, 3]);
+            assert_eq!(content.line_contents[0].line, "pub fn second() {}");
+        }
+        other => panic!("Expected text contents, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_view_file_contents_at_ref_errors() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path);
+
+    // The guide only exists after the tag
+    let missing = local_repo
+        .view_file_contents_at_ref("v1.0.0", view_params("docs/guide.md"))
+        .await;
+    assert!(missing.unwrap_err().contains("File not found"));
+
+    let directory = local_repo
+        .view_file_contents_at_ref("v1.0.0", view_params("src"))
+        .await;
+    assert!(directory.unwrap_err().contains("Not a file"));
+
+    let bad_ref = local_repo
+        .view_file_contents_at_ref("no-such-ref", view_params("README.md"))
+        .await;
+    assert!(bad_ref.unwrap_err().contains("Failed to resolve reference"));
+
+    let traversal = local_repo
+        .view_file_contents_at_ref("v1.0.0", view_params("../outside.txt"))
+        .await;
+    assert!(traversal.is_err());
+}
+
+#[tokio::test]
+async fn test_get_tree_at_ref() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path);
+
+    let old_tree = local_repo
+        .get_tree_at_ref("v1.0.0", None)
+        .await
+        .expect("Failed to read tree at v1.0.0");
+    let dirs: Vec<&str> = old_tree.iter().map(|tree| tree.dir.as_str()).collect();
+    assert_eq!(dirs, vec!["", "src"]);
+    assert!(
+        old_tree[0]
+            .entries
+            .contains(&TreeEntry::File("README.md".to_string()))
+    );
+    assert!(
+        old_tree[0]
+            .entries
+            .contains(&TreeEntry::Directory("src".to_string()))
+    );
+    assert_eq!(
+        old_tree[1].entries,
+        vec![TreeEntry::File("lib.rs".to_string())]
+    );
+
+    let new_tree = local_repo
+        .get_tree_at_ref("main", None)
+        .await
+        .expect("Failed to read tree at main");
+    let dirs: Vec<&str> = new_tree.iter().map(|tree| tree.dir.as_str()).collect();
+    assert_eq!(dirs, vec!["", "docs", "src"]);
+
+    // Depth 1 only lists top-level entries
+    let params = TreeParams {
+        case_sensitive: None,
+        search_relative_path: None,
+        respect_gitignore: None,
+        depth: Some(1),
+        strip_path_prefix: None,
+    };
+    let shallow = local_repo
+        .get_tree_at_ref("main", Some(params))
+        .await
+        .expect("Failed to read shallow tree");
+    assert_eq!(shallow.len(), 1);
+    assert_eq!(shallow[0].entries.len(), 3);
+
+    // A relative path limits the listing to that directory
+    let params = TreeParams {
+        case_sensitive: None,
+        search_relative_path: Some(PathBuf::from("docs")),
+        respect_gitignore: None,
+        depth: None,
+        strip_path_prefix: None,
+    };
+    let docs = local_repo
+        .get_tree_at_ref("main", Some(params))
+        .await
+        .expect("Failed to read docs tree");
+    assert_eq!(docs.len(), 1);
+    assert_eq!(docs[0].dir, "docs");
+    assert_eq!(
+        docs[0].entries,
+        vec![TreeEntry::File("guide.md".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn test_services_read_from_ref() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let manager = RepositoryManager::new(None, None).expect("Failed to create RepositoryManager");
+    let location = repo_path.to_string_lossy().to_string();
+
+    let (contents, _repo, _) = services::show_file_contents(
+        &manager,
+        ShowFileParams {
+            repository_location_str: location.clone(),
+            file_path: "README.md".to_string(),
+            ref_name: Some("v1.0.0".to_string()),
+            max_size: None,
+            line_from: None,
+            line_to: None,
+            without_line_numbers: None,
+        },
+    )
+    .await
+    .expect("Failed to show file at ref");
+    assert_eq!(text_lines(&contents), vec!["version one"]);
+
+    // The worktree is untouched and still shows the latest content
+    let worktree = std::fs::read_to_string(repo_path.join("README.md")).unwrap();
+    assert_eq!(worktree, "version two\n");
+
+    let (tree, _repo) = services::get_repository_tree(
+        &manager,
+        TreeServiceParams {
+            repository_location_str: location,
+            ref_name: Some("v1.0.0".to_string()),
+            case_sensitive: None,
+            respect_gitignore: None,
+            depth: None,
+            strip_path_prefix: None,
+            search_relative_path: None,
+        },
+    )
+    .await
+    .expect("Failed to get tree at ref");
+    assert!(tree.iter().all(|entry| entry.dir != "docs"));
+}
+
+fn grep_params(repo_path: &Path, pattern: &str, ref_name: &str) -> CodeSearchParams {
+    CodeSearchParams {
+        ref_name: Some(ref_name.to_string()),
+        case_sensitive: false,
+        file_extensions: None,
+        include_globs: None,
+        exclude_dirs: None,
+        before_context: None,
+        after_context: None,
+        skip: None,
+        take: None,
+        match_content_omit_num: None,
+        ..common::code_search_params(repo_path, pattern)
+    }
+}
+
+#[tokio::test]
+async fn test_grep_at_ref() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path.clone());
+
+    let old = local_repo
+        .search_code(grep_params(&repo_path, "version", "v1.0.0"))
+        .await
+        .expect("Failed to grep v1.0.0");
+    assert_eq!(old.total_match_line_number, 1);
+    assert_eq!(old.matches[0].file_path, PathBuf::from("README.md"));
+    assert_eq!(old.matches[0].line_content, "version one");
+
+    let new = local_repo
+        .search_code(grep_params(&repo_path, "VERSION|guide", "main"))
+        .await
+        .expect("Failed to grep main");
+    let files: Vec<PathBuf> = new.matches.iter().map(|m| m.file_path.clone()).collect();
+    assert_eq!(
+        files,
+        vec![PathBuf::from("README.md"), PathBuf::from("docs/guide.md")]
+    );
+    assert_eq!(new.matches[0].line_content, "version two");
+
+    // The guide doesn't exist at the tag
+    let missing = local_repo
+        .search_code(grep_params(&repo_path, "guide", "v1.0.0"))
+        .await
+        .expect("Failed to grep v1.0.0");
+    assert!(missing.matches.is_empty());
+}
+
+#[tokio::test]
+async fn test_grep_at_ref_filters_and_pagination() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path.clone());
+
+    // Include globs
+    let mut params = grep_params(&repo_path, "pub fn|version", "v1.0.0");
+    params.include_globs = Some(vec!["**/*.rs".to_string()]);
+    let result = local_repo.search_code(params).await.unwrap();
+    assert_eq!(result.total_match_line_number, 3);
+    assert!(
+        result
+            .matches
+            .iter()
+            .all(|m| m.file_path == PathBuf::from("src/lib.rs"))
+    );
+
+    // Excluded directories
+    let mut params = grep_params(&repo_path, "pub fn|version", "v1.0.0");
+    params.exclude_dirs = Some(vec!["src".to_string()]);
+    let result = local_repo.search_code(params).await.unwrap();
+    assert_eq!(result.total_match_line_number, 1);
+    assert_eq!(result.matches[0].file_path, PathBuf::from("README.md"));
+
+    // Skip and take paginate after counting the total
+    let mut params = grep_params(&repo_path, "pub fn", "v1.0.0");
+    params.skip = Some(1);
+    params.take = Some(1);
+    let result = local_repo.search_code(params).await.unwrap();
+    assert_eq!(result.total_match_line_number, 3);
+    assert_eq!(result.matches.len(), 1);
+    assert_eq!(result.matches[0].line_content, "pub fn second() {}");
+
+    // File extensions are filtered before counting and paginating
+    let mut params = grep_params(&repo_path, "pub fn|version", "v1.0.0");
+    params.file_extensions = Some(vec!["rs".to_string()]);
+    params.take = Some(2);
+    let result = local_repo.search_code(params).await.unwrap();
+    assert_eq!(result.total_match_line_number, 3);
+    assert_eq!(result.matches.len(), 2);
+    assert!(
+        result
+            .matches
+            .iter()
+            .all(|m| m.file_path == PathBuf::from("src/lib.rs"))
+    );
+}
+
+#[tokio::test]
+async fn test_grep_at_ref_context_and_omission() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path.clone());
+
+    let mut params = grep_params(&repo_path, "second", "v1.0.0");
+    params.before_context = Some(1);
+    params.after_context = Some(1);
+    let result = local_repo.search_code(params).await.unwrap();
+    let lines: Vec<(u64, bool)> = result
+        .matches
+        .iter()
+        .map(|m| (m.line_number, m.is_context))
+        .collect();
+    assert_eq!(lines, vec![(1, true), (2, false), (3, true)]);
+
+    let mut params = grep_params(&repo_path, "second", "v1.0.0");
+    params.match_content_omit_num = Some(2);
+    let result = local_repo.search_code(params).await.unwrap();
+    assert_eq!(result.matches.len(), 1);
+    assert!(result.matches[0].content_omitted);
+    assert_eq!(result.matches[0].line_content, "n second()<omit>");
+}
+
+#[tokio::test]
+async fn test_grep_fetches_refs_missing_from_cached_clone() {
+    let (upstream_path, _temp_dir) = create_test_repository();
+    common::git(&upstream_path, &["tag", "v2.0.0"]);
+    let manager = RepositoryManager::new(None, None).expect("Failed to create RepositoryManager");
+
+    // Plant a shallow clone of `v1.0.0` where the manager caches `github:` repositories
+    let cached_repo = LocalRepository::new_local_repository_to_clone(
+        GitRemoteRepositoryInfo {
+            user: "gitcodes-test".to_string(),
+            repo: "cached-clone-refs".to_string(),
+            ref_name: None,
+        },
+        Some(&manager.process_id),
+    );
+    let clone_dir = cached_repo.get_repository_dir().clone();
+    let _ = std::fs::remove_dir_all(&clone_dir);
+    let upstream_url = format!("file://{}", upstream_path.display());
+    common::git(
+        &upstream_path,
+        &[
+            "clone",
+            "--quiet",
+            "--depth",
+            "1",
+            "--branch",
+            "v1.0.0",
+            &upstream_url,
+            clone_dir.to_str().unwrap(),
+        ],
+    );
+
+    let grep = |ref_name: &str| GrepParams {
+        repository_location_str: "github:gitcodes-test/cached-clone-refs".to_string(),
+        pattern: "version".to_string(),
+        ref_name: Some(ref_name.to_string()),
+        ..Default::default()
+    };
+    let results = [
+        services::perform_grep_in_repository(&manager, grep("v2.0.0")).await,
+        services::perform_grep_in_repository(&manager, grep("main")).await,
+        services::perform_grep_in_repository(&manager, grep("v1.0.0")).await,
+    ];
+    let is_shallow = clone_dir.join(".git").join("shallow").exists();
+    std::fs::remove_dir_all(&clone_dir).unwrap();
+
+    let lines: Vec<String> = results
+        .into_iter()
+        .map(|result| {
+            let (result, _local_repo) = result.expect("Grep failed");
+            result.matches[0].line_content.clone()
+        })
+        .collect();
+    assert_eq!(lines, vec!["version two", "version two", "version one"]);
+    // Only the requested refs were fetched, not the whole history
+    assert!(is_shallow);
+}
@@ -0,0 +1,271 @@
+//! Tests for the ranked full-text search over code chunks
+
+mod common;
+
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+use common::{TestRepository, git};
+use gitcodes_mcp::gitcodes::repository_manager::RepositoryManager;
+use gitcodes_mcp::gitcodes::{LocalRepository, RankedSearchOptions, RankedSearchResult};
+use gitcodes_mcp::services::{self, RankedSearchServiceParams};
+
+const CONFIG_RS: &str = r#"use std::fs;
+
+/// Reads the configuration file and parses it
+pub fn parse_config(path: &str) -> Config {
+    let text = fs::read_to_string(path).unwrap();
+    Config::from_toml(&text)
+}
+
+pub struct Config {
+    pub retries: u32,
+}
+
+impl Config {
+    fn from_toml(text: &str) -> Config {
+        let retries = text.len() as u32;
+        Config { retries }
+    }
+}
+"#;
+
+const RETRY_RS: &str = r#"use std::time::Duration;
+
+/// Waits longer after each failed attempt
+pub fn backoff_delay(attempt: u32) -> Duration {
+    Duration::from_millis(100 * 2u64.pow(attempt))
+}
+
+pub fn retry<T>(mut operation: impl FnMut() -> Option<T>) -> Option<T> {
+    for attempt in 0..5 {
+        if let Some(value) = operation() {
+            return Some(value);
+        }
+        std::thread::sleep(backoff_delay(attempt));
+    }
+    None
+}
+"#;
+
+const CLIENT_TS: &str = r#"export class HttpClient {
+  async fetchJson(url: string) {
+    const response = await fetch(url);
+    return response.json();
+  }
+
+  parseConfig(value: string) {
+    return value.split(";");
+  }
+}
+"#;
+
+/// Helper function to create a committed repository with Rust and TypeScript sources and
+/// a long document
+fn create_test_repository() -> (PathBuf, tempfile::TempDir) {
+    let repo = TestRepository::new();
+
+    let mut notes = String::new();
+    for line in 1..=100 {
+        if line == 90 {
+            notes.push_str("Deployment checklist: rotate the certificate\n");
+        } else {
+            notes.push_str("Nothing to see here\n");
+        }
+    }
+    let files = [
+        ("src/config.rs", CONFIG_RS),
+        ("src/retry.rs", RETRY_RS),
+        ("web/client.ts", CLIENT_TS),
+        ("docs/notes.txt", notes.as_str()),
+        (".hidden/secret.rs", "fn certificate() {}\n"),
+    ];
+    repo.write_all(&files).commit("Initial commit");
+
+    repo.into_parts()
+}
+
+fn query(query: &str) -> RankedSearchOptions {
+    RankedSearchOptions {
+        query: query.to_string(),
+        ..Default::default()
+    }
+}
+
+/// The chunks as `file:start-end`
+fn locations(result: &RankedSearchResult) -> Vec<String> {
+    result
+        .chunks
+        .iter()
+        .map(|chunk| {
+            format!(
+                "{}:{}-{}",
+                chunk.file_path, chunk.start_line, chunk.end_line
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_chunks_are_ranked_by_relevance() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let repo = LocalRepository::new(repo_path);
+
+    // Inflections and the parts of identifiers match the words of the query
+    let result = repo
+        .search_code_ranked(query("how is the configuration file parsed"))
+        .await
+        .expect("Ranked search failed");
+    assert_eq!(result.terms, vec!["configuration", "fil", "pars"]);
+    assert_eq!(result.chunks[0].file_path, "src/config.rs");
+    assert_eq!(
+        (result.chunks[0].start_line, result.chunks[0].end_line),
+        (3, 7)
+    );
+    assert_eq!(result.chunks[0].symbol.as_deref(), Some("parse_config"));
+    assert_eq!(
+        result.chunks[0].matched_terms,
+        vec!["configuration", "fil", "pars"]
+    );
+    assert_eq!(
+        result.chunks[0].lines[0],
+        "/// Reads the configuration file and parses it"
+    );
+    assert!(result.chunks.windows(2).all(|w| w[0].score >= w[1].score));
+
+    // A camelCase identifier in the query matches its snake_case spelling and vice versa
+    let result = repo
+        .search_code_ranked(query("parseConfig"))
+        .await
+        .expect("Ranked search failed");
+    let mut found = locations(&result)[..2].to_vec();
+    found.sort();
+    assert_eq!(found, ["src/config.rs:3-7", "web/client.ts:7-9"]);
+    assert!(
+        result.chunks[..2]
+            .iter()
+            .all(|chunk| chunk.matched_terms.contains(&"parseconfig".to_string()))
+    );
+
+    let result = repo
+        .search_code_ranked(query("retry backoff delay"))
+        .await
+        .expect("Ranked search failed");
+    let mut found = locations(&result)[..2].to_vec();
+    found.sort();
+    assert_eq!(found, ["src/retry.rs:3-6", "src/retry.rs:8-16"]);
+    assert_eq!(result.chunks[0].file_path, "src/retry.rs");
+}
+
+#[tokio::test]
+async fn test_windows_filters_and_pagination() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let repo = LocalRepository::new(repo_path);
+
+    // Text files are cut into windows of 40 lines; hidden files aren't indexed
+    let result = repo
+        .search_code_ranked(RankedSearchOptions {
+            max_lines_per_chunk: Some(2),
+            ..query("certificate")
+        })
+        .await
+        .expect("Ranked search failed");
+    assert_eq!(locations(&result), vec!["docs/notes.txt:81-100"]);
+    assert_eq!(result.chunks[0].symbol, None);
+    assert_eq!(result.chunks[0].lines.len(), 2);
+
+    // The methods of a class are chunks of their own
+    let result = repo
+        .search_code_ranked(RankedSearchOptions {
+            include_globs: Some(vec!["web/**".to_string()]),
+            ..query("fetch json response")
+        })
+        .await
+        .expect("Ranked search failed");
+    assert_eq!(locations(&result), vec!["web/client.ts:2-5"]);
+    assert_eq!(
+        result.chunks[0].symbol.as_deref(),
+        Some("HttpClient::fetchJson")
+    );
+
+    // Pages of chunks in ranking order
+    let all = repo
+        .search_code_ranked(query("config retries"))
+        .await
+        .expect("Ranked search failed");
+    assert!(all.total_matches >= 3);
+    let page = repo
+        .search_code_ranked(RankedSearchOptions {
+            skip: Some(1),
+            take: Some(1),
+            ..query("config retries")
+        })
+        .await
+        .expect("Ranked search failed");
+    assert_eq!(page.total_matches, all.total_matches);
+    assert_eq!(locations(&page), locations(&all)[1..2]);
+
+    // A query of stop words only can't rank anything
+    let err = repo
+        .search_code_ranked(query("how is it"))
+        .await
+        .unwrap_err();
+    assert!(
+        err.contains("no searchable words"),
+        "unexpected error: {}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn test_ranked_search_through_service() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let cache_dir = tempdir().expect("Failed to create temporary directory");
+    let manager = RepositoryManager::new(None, Some(cache_dir.path().to_path_buf()))
+        .expect("Failed to create RepositoryManager");
+
+    let params = |ref_name: Option<&str>| RankedSearchServiceParams {
+        repository_location_str: repo_path.display().to_string(),
+        query: "exponential backoff".to_string(),
+        ref_name: ref_name.map(str::to_string),
+        include_globs: None,
+        exclude_dirs: None,
+        skip: None,
+        take: None,
+    };
+
+    let first_commit = git(&repo_path, &["rev-parse", "HEAD"]);
+    let (result, _local_repo) = services::search_code_ranked(&manager, params(None))
+        .await
+        .expect("Ranked search failed");
+    assert_eq!(result.commit_id, first_commit);
+    assert_eq!(locations(&result)[0], "src/retry.rs:3-6");
+    assert!(
+        repo_path
+            .join(format!(".git/gitcodes/chunks-v1-{}.json", first_commit))
+            .is_file()
+    );
+
+    // A new commit gets its own index; the old one is still searched by ref
+    std::fs::write(
+        repo_path.join("src/retry.rs"),
+        "/// Exponential backoff with jitter\npub fn jittered_backoff() {}\n",
+    )
+    .unwrap();
+    git(&repo_path, &["commit", "-am", "Add jitter"]);
+    let (result, _local_repo) = services::search_code_ranked(&manager, params(None))
+        .await
+        .expect("Ranked search failed");
+    assert_eq!(locations(&result), vec!["src/retry.rs:1-2"]);
+    assert_eq!(
+        result.chunks[0].matched_terms,
+        vec!["exponential", "backoff"]
+    );
+
+    let (result, _local_repo) =
+        services::search_code_ranked(&manager, params(Some(first_commit.as_str())))
+            .await
+            .expect("Ranked search failed");
+    assert_eq!(result.commit_id, first_commit);
+    assert_eq!(locations(&result)[0], "src/retry.rs:3-6");
+}
@@ -0,0 +1,122 @@
+//! Tests for the details reported when listing refs: annotated tags, commit dates and
+//! the default branch
+
+mod common;
+
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+use common::{TestRepository, git};
+use gitcodes_mcp::gitcodes::LocalRepository;
+use gitcodes_mcp::gitcodes::local_repository::ObjectType;
+use gitcodes_mcp::gitcodes::repository_manager::providers::ReferenceInfo;
+
+/// Author, committer and tagger date of everything in the test repository
+const DATE: &str = "2024-03-01T12:00:00Z";
+
+/// Helper function to create a repository on `trunk` with an annotated tag `v1.0.0`,
+/// a lightweight tag `snapshot` and a `feature` branch
+fn create_test_repository() -> (PathBuf, tempfile::TempDir) {
+    let repo = TestRepository::init(None, "trunk");
+
+    repo.write("README.md", "# Test\n")
+        .commit_at(DATE, "Initial commit");
+    common::git_with_env(
+        repo.path(),
+        &[("GIT_COMMITTER_DATE", DATE)],
+        &[
+            "tag",
+            "-a",
+            "v1.0.0",
+            "-m",
+            "Release 1.0.0\n\nFirst stable release",
+        ],
+    );
+    repo.tag("snapshot");
+    repo.git(&["branch", "feature"]);
+
+    repo.into_parts()
+}
+
+fn find<'a>(refs: &'a [ReferenceInfo], name: &str) -> &'a ReferenceInfo {
+    refs.iter()
+        .find(|reference| reference.name == name)
+        .unwrap_or_else(|| panic!("Reference {} not found", name))
+}
+
+#[tokio::test]
+async fn test_annotated_and_lightweight_tags() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let head = git(&repo_path, &["rev-parse", "HEAD"]);
+    let tag_object = git(&repo_path, &["rev-parse", "v1.0.0"]);
+    let local_repo = LocalRepository::new(repo_path);
+
+    let refs = local_repo
+        .list_repository_refs()
+        .await
+        .expect("Failed to list refs");
+
+    // The annotated tag is peeled to its commit, and the tag object is reported separately
+    let annotated = find(&refs.tags, "v1.0.0");
+    assert!(annotated.is_annotated_tag());
+    assert_eq!(annotated.commit_id, head);
+    let object = annotated.object.as_ref().unwrap();
+    assert_eq!(object.object_type, ObjectType::Tag);
+    assert_eq!(object.sha, tag_object);
+    let details = annotated.tag.as_ref().unwrap();
+    assert_eq!(details.tagger_name.as_deref(), Some("Test User"));
+    assert_eq!(details.tagger_email.as_deref(), Some("test@example.com"));
+    assert!(details.date.as_deref().unwrap().starts_with("2024-03-01"));
+    assert_eq!(details.message, "Release 1.0.0\n\nFirst stable release");
+
+    let lightweight = find(&refs.tags, "snapshot");
+    assert!(!lightweight.is_annotated_tag());
+    assert_eq!(lightweight.commit_id, head);
+    assert_eq!(
+        lightweight.object.as_ref().unwrap().object_type,
+        ObjectType::Commit
+    );
+    assert!(lightweight.tag.is_none());
+
+    for reference in refs.tags.iter().chain(refs.branches.iter()) {
+        assert!(
+            reference
+                .commit_date
+                .as_deref()
+                .unwrap()
+                .starts_with("2024-03-01"),
+            "Unexpected commit date for {}",
+            reference.name
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_default_branch() {
+    let (repo_path, _temp_dir) = create_test_repository();
+
+    // Without a remote, the checked out branch is the default
+    let local_repo = LocalRepository::new(repo_path.clone());
+    let refs = local_repo
+        .list_repository_refs()
+        .await
+        .expect("Failed to list refs");
+    assert_eq!(refs.default_branch.as_deref(), Some("trunk"));
+
+    // In a clone, the remote HEAD names the default branch even after switching branches
+    let clone_dir = tempdir().expect("Failed to create temporary directory");
+    let clone_path = clone_dir.path().join("clone");
+    git(
+        clone_dir.path(),
+        &["clone", &repo_path.to_string_lossy(), "clone"],
+    );
+    git(&clone_path, &["checkout", "feature"]);
+
+    let cloned_repo = LocalRepository::new(clone_path);
+    let refs = cloned_repo
+        .list_repository_refs()
+        .await
+        .expect("Failed to list refs");
+    assert_eq!(refs.default_branch.as_deref(), Some("trunk"));
+    assert!(refs.branches.iter().all(|branch| branch.name != "HEAD"));
+}
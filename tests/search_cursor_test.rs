@@ -0,0 +1,249 @@
+//! Tests for paging through grep results with cursors pinned to a commit
+
+mod common;
+
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+use common::{TestRepository, git};
+use gitcodes_mcp::gitcodes::repository_manager::RepositoryManager;
+use gitcodes_mcp::gitcodes::{CodeSearchResult, SearchCursor};
+use gitcodes_mcp::services::{self, GrepParams};
+
+/// Helper function to create a committed repository with ten matching lines in three files
+fn create_test_repository() -> (PathBuf, tempfile::TempDir) {
+    let repo = TestRepository::init(Some("repo"), "main");
+
+    let files = [("a.rs", 4), ("src/b.rs", 3), ("src/c.rs", 3)];
+    for (name, count) in files {
+        let content = (1..=count)
+            .map(|line| format!("let item_{} = todo();", line))
+            .collect::<Vec<_>>()
+            .join("\n");
+        repo.write(name, &content);
+    }
+    repo.commit("Initial commit");
+
+    repo.into_parts()
+}
+
+fn grep(location: &str, pattern: &str, ref_name: Option<&str>) -> GrepParams {
+    GrepParams {
+        repository_location_str: location.to_string(),
+        pattern: pattern.to_string(),
+        ref_name: ref_name.map(str::to_string),
+        case_sensitive: false,
+        file_extensions: None,
+        include_globs: None,
+        exclude_dirs: None,
+        before_context: None,
+        after_context: None,
+        skip: None,
+        take: Some(4),
+        match_content_omit_num: None,
+        ..Default::default()
+    }
+}
+
+/// The matches as `file:line`
+fn matched_lines(result: &CodeSearchResult) -> Vec<String> {
+    result
+        .matches
+        .iter()
+        .map(|line| format!("{}:{}", line.file_path.display(), line.line_number))
+        .collect()
+}
+
+/// Follows the cursors of a search from its first page, returning the pages
+async fn all_pages(manager: &RepositoryManager, params: GrepParams) -> Vec<Vec<String>> {
+    let mut pages = Vec::new();
+    let mut cursor = None;
+    loop {
+        let (result, _local_repo) = services::perform_grep_in_repository(
+            manager,
+            GrepParams {
+                cursor: cursor.clone(),
+                ..params.clone()
+            },
+        )
+        .await
+        .expect("Grep failed");
+        pages.push(matched_lines(&result));
+        cursor = result.next_cursor;
+        if cursor.is_none() {
+            return pages;
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_cursors_page_through_all_results() {
+    let (repo_path, temp_dir) = create_test_repository();
+    let cache_dir = tempdir().expect("Failed to create temporary directory");
+    let manager = RepositoryManager::new(None, Some(cache_dir.path().to_path_buf()))
+        .expect("Failed to create RepositoryManager");
+    let location = repo_path.display().to_string();
+
+    let (all, _local_repo) = services::perform_grep_in_repository(
+        &manager,
+        GrepParams {
+            take: Some(100),
+            ..grep(&location, "todo", Some("main"))
+        },
+    )
+    .await
+    .expect("Grep failed");
+    assert_eq!(all.total_match_line_number, 10);
+    assert_eq!(all.next_cursor, None);
+
+    let pages = all_pages(&manager, grep(&location, "todo", Some("main"))).await;
+    assert_eq!(
+        pages.iter().map(Vec::len).collect::<Vec<_>>(),
+        vec![4, 4, 2]
+    );
+    assert_eq!(pages.concat(), matched_lines(&all));
+
+    // The working tree of a local repository has no fixed snapshot to page through
+    let (result, _local_repo) =
+        services::perform_grep_in_repository(&manager, grep(&location, "todo", None))
+            .await
+            .expect("Grep failed");
+    assert_eq!(result.matches.len(), 4);
+    assert_eq!(result.next_cursor, None);
+
+    // An unpacked bundle is searched like a clone, at the commit of its HEAD
+    let bundle_path = temp_dir.path().join("repo.bundle");
+    git(
+        &repo_path,
+        &["bundle", "create", bundle_path.to_str().unwrap(), "--all"],
+    );
+    let bundle_location = format!("bundle:{}", bundle_path.display());
+    let pages = all_pages(&manager, grep(&bundle_location, "todo", None)).await;
+    assert_eq!(pages.concat(), matched_lines(&all));
+}
+
+#[tokio::test]
+async fn test_cursor_stays_on_its_commit() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let cache_dir = tempdir().expect("Failed to create temporary directory");
+    let manager = RepositoryManager::new(None, Some(cache_dir.path().to_path_buf()))
+        .expect("Failed to create RepositoryManager");
+    let location = repo_path.display().to_string();
+    let first_commit = git(&repo_path, &["rev-parse", "HEAD"]);
+
+    let (first_page, _local_repo) =
+        services::perform_grep_in_repository(&manager, grep(&location, "todo", Some("main")))
+            .await
+            .expect("Grep failed");
+    let cursor = first_page.next_cursor.expect("Expected a next page");
+    let decoded = SearchCursor::decode(&cursor).expect("Failed to decode cursor");
+    assert_eq!(decoded.commit_id, first_commit);
+    assert_eq!(decoded.position, 4);
+
+    // A new commit on the branch shifts the results, but not those of the cursor
+    std::fs::write(repo_path.join("a.rs"), "let first = todo();\n").unwrap();
+    git(&repo_path, &["commit", "-am", "Remove items"]);
+    let (second_page, _local_repo) = services::perform_grep_in_repository(
+        &manager,
+        GrepParams {
+            cursor: Some(cursor.clone()),
+            ..grep(&location, "todo", Some("main"))
+        },
+    )
+    .await
+    .expect("Grep failed");
+    assert_eq!(
+        matched_lines(&second_page),
+        vec!["src/b.rs:1", "src/b.rs:2", "src/b.rs:3", "src/c.rs:1"]
+    );
+    assert_eq!(second_page.total_match_line_number, 10);
+
+    // The cursor only continues the search it came from
+    let err = services::perform_grep_in_repository(
+        &manager,
+        GrepParams {
+            cursor: Some(cursor.clone()),
+            ..grep(&location, "item", Some("main"))
+        },
+    )
+    .await
+    .unwrap_err();
+    assert!(
+        err.contains("different search"),
+        "unexpected error: {}",
+        err
+    );
+
+    let err = services::perform_grep_in_repository(
+        &manager,
+        GrepParams {
+            cursor: Some(cursor),
+            skip: Some(4),
+            ..grep(&location, "todo", Some("main"))
+        },
+    )
+    .await
+    .unwrap_err();
+    assert!(
+        err.contains("cannot be combined"),
+        "unexpected error: {}",
+        err
+    );
+
+    let err = services::perform_grep_in_repository(
+        &manager,
+        GrepParams {
+            cursor: Some("not-a-cursor".to_string()),
+            ..grep(&location, "todo", Some("main"))
+        },
+    )
+    .await
+    .unwrap_err();
+    assert!(err.contains("Invalid cursor"), "unexpected error: {}", err);
+}
+
+#[tokio::test]
+async fn test_cursor_of_evicted_commit_fails() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let cache_dir = tempdir().expect("Failed to create temporary directory");
+    let manager = RepositoryManager::new(None, Some(cache_dir.path().to_path_buf()))
+        .expect("Failed to create RepositoryManager");
+    let location = repo_path.display().to_string();
+
+    // A commit that only a temporary branch points to
+    git(&repo_path, &["checkout", "-b", "scratch"]);
+    std::fs::write(repo_path.join("d.rs"), "let extra = todo();\n").unwrap();
+    git(&repo_path, &["add", "."]);
+    git(&repo_path, &["commit", "-m", "Scratch work"]);
+    let scratch_commit = git(&repo_path, &["rev-parse", "HEAD"]);
+    git(&repo_path, &["checkout", "main"]);
+
+    let (first_page, _local_repo) = services::perform_grep_in_repository(
+        &manager,
+        grep(&location, "todo", Some(scratch_commit.as_str())),
+    )
+    .await
+    .expect("Grep failed");
+    assert_eq!(first_page.total_match_line_number, 11);
+    let cursor = first_page.next_cursor.expect("Expected a next page");
+
+    // Deleting the branch and pruning unreachable objects removes the commit
+    git(&repo_path, &["branch", "-D", "scratch"]);
+    git(&repo_path, &["reflog", "expire", "--expire=now", "--all"]);
+    git(&repo_path, &["gc", "--prune=now", "--quiet"]);
+
+    let err = services::perform_grep_in_repository(
+        &manager,
+        GrepParams {
+            cursor: Some(cursor),
+            ..grep(&location, "todo", Some(scratch_commit.as_str()))
+        },
+    )
+    .await
+    .unwrap_err();
+    assert!(
+        err.contains(&scratch_commit) && err.contains("no longer available"),
+        "unexpected error: {}",
+        err
+    );
+}
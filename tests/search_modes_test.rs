@@ -0,0 +1,230 @@
+//! Tests for the fixed-string, whole-word and multiline search modes
+
+mod common;
+
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+use common::TestRepository;
+use gitcodes_mcp::gitcodes::local_repository::CodeSearchParams;
+use gitcodes_mcp::gitcodes::repository_manager::RepositoryManager;
+use gitcodes_mcp::gitcodes::{CodeSearchResult, LocalRepository};
+use gitcodes_mcp::services;
+
+const LIB_RS: &str = "let v = Vec<T>::new();
+let w = Vec::new();
+fn parse(
+    input: &str,
+    strict: bool,
+) -> Result<(), String> {
+fn parse_all(input: &str) {}
+let x = renew();
+// parsed later
+";
+
+/// Helper function to create a committed repository with `src/lib.rs` and a
+/// `notes.md` that mentions `parse(` on a single line
+fn create_test_repository() -> (PathBuf, tempfile::TempDir) {
+    let repo = TestRepository::new();
+
+    repo.write("src/lib.rs", LIB_RS)
+        .write("notes.md", "Call parse( with input\n")
+        .commit("Initial commit");
+
+    repo.into_parts()
+}
+
+/// Returns the matched (non-context) lines as `path:line_number`
+fn matched_lines(result: &CodeSearchResult) -> Vec<String> {
+    result
+        .matches
+        .iter()
+        .filter(|line| !line.is_context)
+        .map(|line| format!("{}:{}", line.file_path.display(), line.line_number))
+        .collect()
+}
+
+/// Runs the search on the worktree and at `main`, asserting both agree
+async fn search_both(repo: &LocalRepository, params: CodeSearchParams) -> CodeSearchResult {
+    let worktree = repo
+        .search_code(params.clone())
+        .await
+        .expect("Worktree search failed");
+    let at_ref = repo
+        .search_code(CodeSearchParams {
+            ref_name: Some("main".to_string()),
+            ..params
+        })
+        .await
+        .expect("Search at ref failed");
+
+    assert_eq!(matched_lines(&worktree), matched_lines(&at_ref));
+    assert_eq!(
+        worktree.total_match_line_number,
+        at_ref.total_match_line_number
+    );
+    worktree
+}
+
+#[tokio::test]
+async fn test_fixed_string_search() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let repo = LocalRepository::new(repo_path.clone());
+
+    // As a regex the pattern is invalid
+    let err = repo
+        .search_code(common::code_search_params(&repo_path, "Vec<T>::new("))
+        .await
+        .unwrap_err();
+    assert!(
+        err.contains("Code search failed"),
+        "unexpected error: {}",
+        err
+    );
+
+    let result = search_both(
+        &repo,
+        CodeSearchParams {
+            fixed_string: true,
+            ..common::code_search_params(&repo_path, "Vec<T>::new(")
+        },
+    )
+    .await;
+    assert_eq!(matched_lines(&result), vec!["src/lib.rs:1"]);
+    // The result reports the pattern as given
+    assert_eq!(result.pattern, "Vec<T>::new(");
+
+    // Regex metacharacters match only themselves
+    let result = search_both(
+        &repo,
+        CodeSearchParams {
+            fixed_string: true,
+            ..common::code_search_params(&repo_path, "v.c")
+        },
+    )
+    .await;
+    assert!(result.matches.is_empty());
+}
+
+#[tokio::test]
+async fn test_word_regexp_search() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let repo = LocalRepository::new(repo_path.clone());
+
+    let result = search_both(
+        &repo,
+        CodeSearchParams {
+            word_regexp: true,
+            include_globs: Some(vec!["**/*.rs".to_string()]),
+            ..common::code_search_params(&repo_path, "parse")
+        },
+    )
+    .await;
+    assert_eq!(matched_lines(&result), vec!["src/lib.rs:3"]);
+
+    // Patterns ending in punctuation still need a word boundary at their start
+    let result = search_both(
+        &repo,
+        CodeSearchParams {
+            fixed_string: true,
+            word_regexp: true,
+            ..common::code_search_params(&repo_path, "new(")
+        },
+    )
+    .await;
+    assert_eq!(matched_lines(&result), vec!["src/lib.rs:1", "src/lib.rs:2"]);
+}
+
+#[tokio::test]
+async fn test_multiline_search() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let repo = LocalRepository::new(repo_path.clone());
+    let pattern = r"fn parse\(\s*input: &str,\s*strict";
+
+    // Line by line, the signature can't be found
+    let result = search_both(&repo, common::code_search_params(&repo_path, pattern)).await;
+    assert!(result.matches.is_empty());
+
+    let result = search_both(
+        &repo,
+        CodeSearchParams {
+            multiline: true,
+            after_context: Some(1),
+            ..common::code_search_params(&repo_path, pattern)
+        },
+    )
+    .await;
+    assert_eq!(
+        matched_lines(&result),
+        vec!["src/lib.rs:3", "src/lib.rs:4", "src/lib.rs:5"]
+    );
+    // Like lumin, the total counts the context line too
+    assert_eq!(result.total_match_line_number, 4);
+    let lines: Vec<(u64, &str, bool)> = result
+        .matches
+        .iter()
+        .map(|line| {
+            (
+                line.line_number,
+                line.line_content.as_str(),
+                line.is_context,
+            )
+        })
+        .collect();
+    assert_eq!(
+        lines,
+        vec![
+            (3, "fn parse(", false),
+            (4, "    input: &str,", false),
+            (5, "    strict: bool,", false),
+            (6, ") -> Result<(), String> {", true),
+        ]
+    );
+
+    // Single line matches, anchors and filters behave as without multiline
+    let result = search_both(
+        &repo,
+        CodeSearchParams {
+            multiline: true,
+            fixed_string: true,
+            exclude_dirs: Some(vec!["src".to_string()]),
+            ..common::code_search_params(&repo_path, "parse(")
+        },
+    )
+    .await;
+    assert_eq!(matched_lines(&result), vec!["notes.md:1"]);
+
+    let result = search_both(
+        &repo,
+        CodeSearchParams {
+            multiline: true,
+            ..common::code_search_params(&repo_path, r"^\) -> Result")
+        },
+    )
+    .await;
+    assert_eq!(matched_lines(&result), vec!["src/lib.rs:6"]);
+}
+
+#[tokio::test]
+async fn test_search_modes_through_service() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let cache_dir = tempdir().expect("Failed to create temporary directory");
+    let manager = RepositoryManager::new(None, Some(cache_dir.path().to_path_buf()))
+        .expect("Failed to create RepositoryManager");
+
+    let (result, _local_repo) = services::perform_grep_in_repository(
+        &manager,
+        services::GrepParams {
+            repository_location_str: repo_path.display().to_string(),
+            pattern: "input: &str,\n    strict".to_string(),
+            case_sensitive: true,
+            fixed_string: true,
+            word_regexp: true,
+            multiline: true,
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("Search failed");
+    assert_eq!(matched_lines(&result), vec!["src/lib.rs:4", "src/lib.rs:5"]);
+}
@@ -52,6 +52,7 @@ async fn test_perform_grep_with_cleanup() {
         skip: None,                 // No skip (pagination)
         take: None,                 // No take (pagination)
         match_content_omit_num: Some(150),
+        ..Default::default()
     };
     let result = services::perform_grep_in_repository(&manager, grep_params).await;
 
@@ -116,6 +117,7 @@ async fn test_grep_pagination() {
         skip: None,                                    // No skip (get all results for baseline)
         take: None,                                    // No take (get all results for baseline)
         match_content_omit_num: Some(150),
+        ..Default::default()
     };
     let full_result = services::perform_grep_in_repository(&manager, full_grep_params).await;
 
@@ -144,6 +146,7 @@ async fn test_grep_pagination() {
                 skip: Some(skip_count),                        // Skip first few results
                 take: Some(take_count), // Take only a few results for pagination
                 match_content_omit_num: Some(150),
+                ..Default::default()
             };
             let paginated_result =
                 services::perform_grep_in_repository(&manager, paginated_grep_params).await;
@@ -328,6 +331,7 @@ async fn test_grep_multiple_pages() {
         skip: None,                                    // No skip
         take: None,                                    // No take limit
         match_content_omit_num: Some(150),
+        ..Default::default()
     };
     let full_result = services::perform_grep_in_repository(&manager, full_grep_params).await;
 
@@ -362,6 +366,7 @@ async fn test_grep_multiple_pages() {
                     skip: Some(skip),      // Skip to the next page
                     take: Some(page_size), // Take one page worth of results
                     match_content_omit_num: Some(150),
+                    ..Default::default()
                 };
                 let page_result =
                     services::perform_grep_in_repository(&manager, page_grep_params).await;
@@ -427,7 +432,7 @@ async fn test_grep_multiple_pages() {
                         // Verify each file in the page results:
                         // 1. Has a .rs extension (our filtering criterion)
                         // 2. Appears somewhere in the full results set
-                        for (_i, result) in page_results.matches.iter().enumerate() {
+                        for result in page_results.matches.iter() {
                             let file_path = result.file_path.to_string_lossy();
 
                             // Verify file is .rs (our expectation for filtering)
@@ -554,6 +559,7 @@ async fn test_grep_url_formats() {
             skip: None,                    // No skip (pagination)
             take: None,                    // No take (pagination)
             match_content_omit_num: Some(150),
+            ..Default::default()
         };
         let result = services::perform_grep_in_repository(&manager, grep_params).await;
 
@@ -612,6 +618,7 @@ async fn test_grep_dir_exclusion() {
         skip: None,                 // No skip (pagination)
         take: None,                 // No take (pagination)
         match_content_omit_num: Some(150),
+        ..Default::default()
     };
     let grep_result = services::perform_grep_in_repository(&manager, grep_params).await;
 
@@ -649,6 +656,7 @@ async fn test_grep_dir_exclusion() {
                 skip: None,                 // No skip (pagination)
                 take: None,                 // No take (pagination)
                 match_content_omit_num: Some(150),
+                ..Default::default()
             };
             let exclude_result =
                 services::perform_grep_in_repository(&manager, exclude_grep_params).await;
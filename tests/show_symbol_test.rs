@@ -0,0 +1,250 @@
+//! Tests for resolving qualified symbol names to their full definitions
+
+mod common;
+
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+use common::TestRepository;
+use gitcodes_mcp::gitcodes::repository_manager::RepositoryManager;
+use gitcodes_mcp::gitcodes::{LocalRepository, ShowSymbolOptions, SymbolKind, SymbolSourceResult};
+use gitcodes_mcp::services::{self, ShowSymbolServiceParams};
+use gitcodes_mcp::tools::responses::ShowSymbolResponse;
+use lumin::view::FileContents;
+
+const MUTEX_RS: &str = r#"use std::cell::UnsafeCell;
+
+/// A mutual exclusion primitive
+pub struct Mutex<T> {
+    data: UnsafeCell<T>,
+}
+
+impl<T> Mutex<T> {
+    /// Acquires the lock.
+    ///
+    /// Blocks until it's available.
+    #[inline]
+    pub fn lock(&self) -> &T {
+        unsafe { &*self.data.get() }
+    }
+}
+
+/// Locks a mutex
+pub fn lock<T>(mutex: &Mutex<T>) -> &T {
+    mutex.lock()
+}
+"#;
+
+const RWLOCK_RS: &str = r#"pub struct RwLock;
+
+impl RwLock {
+    pub fn lock(&self) {}
+}
+"#;
+
+const MODELS_PY: &str = r#"class User:
+    def __init__(self, name):
+        self.name = name
+
+    @transaction
+    def save(self):
+        """Stores the user"""
+        db.insert(self)
+"#;
+
+/// Helper function to create a committed repository with the crate `my-sync`
+/// (`src/lib.rs`, `src/sync/mutex.rs`, `src/sync/rwlock.rs`) and a Python package
+fn create_test_repository() -> (PathBuf, tempfile::TempDir) {
+    let repo = TestRepository::new();
+
+    let files = [
+        ("my-sync/src/lib.rs", "pub mod sync;\n"),
+        ("my-sync/src/sync/mutex.rs", MUTEX_RS),
+        ("my-sync/src/sync/rwlock.rs", RWLOCK_RS),
+        ("app/models.py", MODELS_PY),
+    ];
+    repo.write_all(&files).commit("Initial commit");
+
+    repo.into_parts()
+}
+
+fn show(qualified_name: &str) -> ShowSymbolOptions {
+    ShowSymbolOptions {
+        qualified_name: qualified_name.to_string(),
+        ..Default::default()
+    }
+}
+
+fn qualified_names(result: &SymbolSourceResult) -> Vec<&str> {
+    result
+        .definitions
+        .iter()
+        .map(|definition| definition.qualified_name.as_str())
+        .collect()
+}
+
+/// The source lines of a definition without line numbers
+fn source_lines(contents: &FileContents) -> Vec<String> {
+    match contents {
+        FileContents::Text { content, .. } => content
+            .line_contents
+            .iter()
+            .map(|line| line.line.clone())
+            .collect(),
+        _ => panic!("Expected text contents"),
+    }
+}
+
+#[tokio::test]
+async fn test_show_method_with_docs_and_attributes() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let repo = LocalRepository::new(repo_path);
+
+    let result = repo
+        .show_symbol(show("Mutex::lock"))
+        .await
+        .expect("Show symbol failed");
+    assert_eq!(
+        qualified_names(&result),
+        vec!["my_sync::sync::mutex::Mutex::lock"]
+    );
+    assert_eq!(result.total_matches, 1);
+
+    let definition = &result.definitions[0];
+    assert_eq!(definition.symbol.kind, SymbolKind::Method);
+    assert_eq!(definition.symbol.file_path, "my-sync/src/sync/mutex.rs");
+    assert_eq!(definition.source_start_line, 9);
+    assert_eq!(
+        (definition.symbol.start_line, definition.symbol.end_line),
+        (13, 15)
+    );
+    assert_eq!(
+        source_lines(&definition.contents),
+        vec![
+            "    /// Acquires the lock.",
+            "    ///",
+            "    /// Blocks until it's available.",
+            "    #[inline]",
+            "    pub fn lock(&self) -> &T {",
+            "        unsafe { &*self.data.get() }",
+            "    }",
+        ]
+    );
+
+    // Re-exported and crate-relative paths resolve to the same definition
+    for qualified_name in ["my_sync::sync::Mutex::lock", "crate::mutex::Mutex::lock"] {
+        let result = repo
+            .show_symbol(show(qualified_name))
+            .await
+            .expect("Show symbol failed");
+        assert_eq!(
+            qualified_names(&result),
+            vec!["my_sync::sync::mutex::Mutex::lock"],
+            "for {}",
+            qualified_name
+        );
+    }
+
+    // The same definition from the object database
+    let at_ref = repo
+        .show_symbol(ShowSymbolOptions {
+            ref_name: Some("main".to_string()),
+            ..show("Mutex::lock")
+        })
+        .await
+        .expect("Show symbol at ref failed");
+    assert_eq!(
+        source_lines(&at_ref.definitions[0].contents),
+        source_lines(&definition.contents)
+    );
+}
+
+#[tokio::test]
+async fn test_ambiguous_names_and_ranking() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let repo = LocalRepository::new(repo_path);
+
+    let result = repo
+        .show_symbol(show("lock"))
+        .await
+        .expect("Show symbol failed");
+    assert_eq!(
+        qualified_names(&result),
+        vec![
+            "my_sync::sync::mutex::Mutex::lock",
+            "my_sync::sync::mutex::lock",
+            "my_sync::sync::rwlock::RwLock::lock",
+        ]
+    );
+
+    // The function directly in the `mutex` module is a better match than the method
+    let result = repo
+        .show_symbol(ShowSymbolOptions {
+            max_results: Some(1),
+            ..show("mutex::lock")
+        })
+        .await
+        .expect("Show symbol failed");
+    assert_eq!(qualified_names(&result), vec!["my_sync::sync::mutex::lock"]);
+    assert_eq!(result.total_matches, 2);
+    assert_eq!(result.definitions[0].source_start_line, 18);
+
+    let result = repo
+        .show_symbol(show("Mutex::unlock"))
+        .await
+        .expect("Show symbol failed");
+    assert!(result.definitions.is_empty());
+
+    let err = repo.show_symbol(show(" :: ")).await.unwrap_err();
+    assert!(
+        err.contains("must not be empty"),
+        "unexpected error: {}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn test_show_python_method_through_service() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let cache_dir = tempdir().expect("Failed to create temporary directory");
+    let manager = RepositoryManager::new(None, Some(cache_dir.path().to_path_buf()))
+        .expect("Failed to create RepositoryManager");
+
+    let (result, _local_repo) = services::show_symbol(
+        &manager,
+        ShowSymbolServiceParams {
+            repository_location_str: repo_path.display().to_string(),
+            qualified_name: "models.User.save".to_string(),
+            ref_name: None,
+            include_globs: None,
+            exclude_dirs: None,
+            max_results: None,
+        },
+    )
+    .await
+    .expect("Show symbol failed");
+    assert_eq!(qualified_names(&result), vec!["app.models.User.save"]);
+    assert_eq!(
+        result.definitions[0].symbol.doc.as_deref(),
+        Some("Stores the user")
+    );
+
+    // The decorator is part of the source
+    let response = ShowSymbolResponse::from_result(result);
+    let json = serde_json::to_value(&response).unwrap();
+    let definition = &json["definitions"][0];
+    assert_eq!(definition["kind"], "method");
+    assert_eq!(definition["start_line"], 5);
+    assert_eq!(definition["end_line"], 8);
+    assert_eq!(definition["contents"]["type"], "text");
+    assert_eq!(
+        definition["contents"]["metadata"]["file_path"],
+        "app/models.py"
+    );
+    assert!(
+        definition["contents"]["line_contents"]
+            .as_str()
+            .unwrap()
+            .starts_with("5:    @transaction\n6:    def save(self):")
+    );
+}
@@ -0,0 +1,280 @@
+//! Tests for structural code search with AST patterns and metavariables
+
+mod common;
+
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+use common::TestRepository;
+use gitcodes_mcp::gitcodes::repository_manager::RepositoryManager;
+use gitcodes_mcp::gitcodes::{
+    LocalRepository, SourceLanguage, StructuralSearchOptions, StructuralSearchResult,
+};
+use gitcodes_mcp::services::{self, StructuralSearchServiceParams};
+use gitcodes_mcp::tools::responses::CompactCodeSearchResponse;
+
+const CONFIG_RS: &str = r#"use std::fs::File;
+
+pub fn open_config() -> File {
+    File::open("config.toml").unwrap()
+}
+
+pub fn open_both(dir: &str) -> (File, File) {
+    let first = File::open(format!("{}/a", dir)).unwrap();
+    // File::open("commented").unwrap()
+    let second = File::open(
+        "b", // the second file
+    )
+    .unwrap();
+    (first, second)
+}
+
+pub fn fallible() -> std::io::Result<File> {
+    File::open("c")
+}
+
+pub fn compare(a: u32, b: u32) -> bool {
+    a == a && a == b
+}
+
+pub fn log() {
+    println!("{} {}", 1, 2);
+    tracing::info!("done");
+    debug(1);
+    debug(1, 2, 3);
+}
+
+fn debug(_: u32) {}
+"#;
+
+const APP_PY: &str = r#"import requests
+
+
+def fetch(url):
+    return requests.get(url, timeout=5)
+
+
+def fetch_all(urls):
+    return [requests.get(u) for u in urls]
+"#;
+
+const CLIENT_TS: &str = r#"export async function load(id: string) {
+  const response = await fetch(`/api/${id}`);
+  return response.json();
+}
+"#;
+
+const VIEW_TSX: &str = r#"export function View() {
+  fetch("/api/view");
+  return <div />;
+}
+"#;
+
+/// Helper function to create a committed repository with Rust, Python, TypeScript and
+/// TSX files
+fn create_test_repository() -> (PathBuf, tempfile::TempDir) {
+    let repo = TestRepository::new();
+
+    let files = [
+        ("src/config.rs", CONFIG_RS),
+        ("app.py", APP_PY),
+        ("web/client.ts", CLIENT_TS),
+        ("web/view.tsx", VIEW_TSX),
+    ];
+    repo.write_all(&files).commit("Initial commit");
+
+    repo.into_parts()
+}
+
+fn pattern(pattern: &str, language: SourceLanguage) -> StructuralSearchOptions {
+    StructuralSearchOptions {
+        pattern: pattern.to_string(),
+        language,
+        ref_name: None,
+        include_globs: None,
+        exclude_globs: None,
+        skip: None,
+        take: None,
+        max_lines_per_match: None,
+    }
+}
+
+/// The matches as `file:line:column-line:column`
+fn locations(result: &StructuralSearchResult) -> Vec<String> {
+    result
+        .matches
+        .iter()
+        .map(|m| {
+            format!(
+                "{}:{}:{}-{}:{}",
+                m.file_path, m.start_line, m.start_column, m.end_line, m.end_column
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_pattern_matches_syntax_not_text() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let repo = LocalRepository::new(repo_path);
+
+    // Formatting and comments don't matter, commented-out code isn't code
+    let result = repo
+        .structural_search(pattern("File::open($PATH).unwrap()", SourceLanguage::Rust))
+        .await
+        .expect("Structural search failed");
+    assert_eq!(
+        locations(&result),
+        vec![
+            "src/config.rs:4:5-4:39",
+            "src/config.rs:8:17-8:58",
+            "src/config.rs:10:18-13:14",
+        ]
+    );
+    assert_eq!(result.total_matches, 3);
+    assert_eq!(result.matches[0].metavariables["PATH"], "\"config.toml\"");
+    assert_eq!(
+        result.matches[1].metavariables["PATH"],
+        "format!(\"{}/a\", dir)"
+    );
+    assert_eq!(
+        result.matches[2].lines,
+        vec![
+            "    let second = File::open(",
+            "        \"b\", // the second file",
+            "    )",
+            "    .unwrap();",
+        ]
+    );
+
+    // Only the lines up to the limit are returned
+    let result = repo
+        .structural_search(StructuralSearchOptions {
+            max_lines_per_match: Some(2),
+            skip: Some(2),
+            ..pattern("File::open($PATH).unwrap()", SourceLanguage::Rust)
+        })
+        .await
+        .expect("Structural search failed");
+    assert_eq!(result.matches.len(), 1);
+    assert_eq!(result.matches[0].lines.len(), 2);
+    assert_eq!(result.total_matches, 3);
+
+    // Other languages aren't searched with a Rust pattern
+    let result = repo
+        .structural_search(pattern("fetch($URL)", SourceLanguage::Rust))
+        .await
+        .expect("Structural search failed");
+    assert!(result.matches.is_empty());
+}
+
+#[tokio::test]
+async fn test_metavariables() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let repo = LocalRepository::new(repo_path);
+
+    // A repeated metavariable must match the same code each time
+    let result = repo
+        .structural_search(pattern("$X == $X", SourceLanguage::Rust))
+        .await
+        .expect("Structural search failed");
+    assert_eq!(locations(&result), vec!["src/config.rs:22:5-22:11"]);
+    assert_eq!(result.matches[0].metavariables["X"], "a");
+
+    // `$$$` matches any number of arguments
+    let result = repo
+        .structural_search(pattern("debug($$$ARGS)", SourceLanguage::Rust))
+        .await
+        .expect("Structural search failed");
+    let args: Vec<&str> = result
+        .matches
+        .iter()
+        .map(|m| m.metavariables["ARGS"].as_str())
+        .collect();
+    assert_eq!(args, vec!["1", "1, 2, 3"]);
+
+    // ... also after other arguments, where it may match nothing
+    let result = repo
+        .structural_search(pattern("debug($FIRST, $$$REST)", SourceLanguage::Rust))
+        .await
+        .expect("Structural search failed");
+    assert_eq!(result.total_matches, 2);
+    assert_eq!(result.matches[0].metavariables["REST"], "");
+    assert_eq!(result.matches[1].metavariables["REST"], "2, 3");
+
+    // `$_` matches without being recorded
+    let result = repo
+        .structural_search(pattern("requests.get($_)", SourceLanguage::Python))
+        .await
+        .expect("Structural search failed");
+    assert_eq!(locations(&result), vec!["app.py:9:13-9:28"]);
+    assert!(result.matches[0].metavariables.is_empty());
+
+    // Keyword arguments are part of the shape
+    let result = repo
+        .structural_search(pattern(
+            "requests.get($URL, timeout=$T)",
+            SourceLanguage::Python,
+        ))
+        .await
+        .expect("Structural search failed");
+    assert_eq!(locations(&result), vec!["app.py:5:12-5:40"]);
+    assert_eq!(result.matches[0].metavariables["T"], "5");
+}
+
+#[tokio::test]
+async fn test_structural_search_through_service() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let cache_dir = tempdir().expect("Failed to create temporary directory");
+    let manager = RepositoryManager::new(None, Some(cache_dir.path().to_path_buf()))
+        .expect("Failed to create RepositoryManager");
+
+    let params = |pattern: &str| StructuralSearchServiceParams {
+        repository_location_str: repo_path.display().to_string(),
+        pattern: pattern.to_string(),
+        language: SourceLanguage::TypeScript,
+        ref_name: Some("main".to_string()),
+        include_globs: None,
+        exclude_dirs: None,
+        skip: None,
+        take: None,
+    };
+
+    // TypeScript patterns also search TSX files
+    let (result, _local_repo) = services::structural_search(&manager, params("fetch($URL)"))
+        .await
+        .expect("Structural search failed");
+    assert_eq!(
+        locations(&result),
+        vec!["web/client.ts:2:26-2:45", "web/view.tsx:2:3-2:21"]
+    );
+
+    // The compact response groups the lines by file and keeps the node ranges
+    let compact = CompactCodeSearchResponse::from_structural_result(result);
+    assert_eq!(compact.total_match_line_number, 2);
+    assert_eq!(compact.matches.len(), 2);
+    assert_eq!(
+        compact.matches[0].lines,
+        "2:  const response = await fetch(`/api/${id}`);"
+    );
+    let json = serde_json::to_value(&compact).unwrap();
+    assert_eq!(json["matches"][1]["ranges"][0]["start_column"], 3);
+    assert_eq!(
+        json["matches"][1]["ranges"][0]["metavariables"]["URL"],
+        "\"/api/view\""
+    );
+
+    let err = services::structural_search(&manager, params("fetch(("))
+        .await
+        .unwrap_err();
+    assert!(err.contains("not valid"), "unexpected error: {}", err);
+
+    let err = services::structural_search(&manager, params("$X"))
+        .await
+        .unwrap_err();
+    assert!(
+        err.contains("more than a metavariable"),
+        "unexpected error: {}",
+        err
+    );
+}
@@ -0,0 +1,288 @@
+//! Tests for the syntax-aware symbol definition search
+
+mod common;
+
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+use common::TestRepository;
+use gitcodes_mcp::gitcodes::repository_manager::RepositoryManager;
+use gitcodes_mcp::gitcodes::{
+    LocalRepository, SourceLanguage, SymbolKind, SymbolSearchOptions, SymbolSearchResult,
+};
+use gitcodes_mcp::services::{self, FindSymbolServiceParams};
+
+const LIB_RS: &str = r#"/// A mutual exclusion primitive
+pub struct Mutex<T> {
+    inner: T,
+}
+
+impl<T> Mutex<T> {
+    pub fn lock(
+        &self,
+    ) -> Guard<'_, T> {
+        // Calling lock() here is a usage, not a definition
+        self.lock()
+    }
+}
+
+pub trait Lock {
+    fn lock(&self);
+}
+
+pub mod sync {
+    pub enum State {
+        Locked,
+    }
+}
+
+fn main() {
+    fn helper() {}
+    let mutex = Mutex::new(0);
+    mutex.lock();
+}
+"#;
+
+const MUTEX_GO: &str = r#"package sync
+
+type Mutex struct {
+	state int32
+}
+
+type Locker interface {
+	Lock()
+}
+
+func (m *Mutex) Lock() {
+	m.state = 1
+}
+"#;
+
+const MUTEX_PY: &str = r#"class Mutex:
+    @property
+    def locked(self):
+        return False
+
+    def lock(self):
+        pass
+
+
+def lock(mutex):
+    mutex.lock()
+"#;
+
+const MUTEX_TS: &str = r#"export class Mutex {
+  lock(): void {}
+}
+
+export interface Locker {
+  lock(): void;
+}
+
+export const lock = (mutex: Mutex) => mutex.lock();
+"#;
+
+/// Helper function to create a committed repository with a small mutex implementation
+/// in Rust, Go, Python and TypeScript, plus a README that mentions `lock`
+fn create_test_repository() -> (PathBuf, tempfile::TempDir) {
+    let repo = TestRepository::new();
+
+    let files = [
+        ("src/lib.rs", LIB_RS),
+        ("mutex.go", MUTEX_GO),
+        ("mutex.py", MUTEX_PY),
+        ("mutex.ts", MUTEX_TS),
+        ("README.md", "fn lock() is documented here\n"),
+    ];
+    repo.write_all(&files).commit("Initial commit");
+
+    repo.into_parts()
+}
+
+/// Returns the symbols of the result as `path:start_line kind container`
+fn result_symbols(result: &SymbolSearchResult) -> Vec<String> {
+    result
+        .symbols
+        .iter()
+        .map(|symbol| {
+            format!(
+                "{}:{} {:?} {}",
+                symbol.file_path,
+                symbol.start_line,
+                symbol.kind,
+                symbol.container.as_deref().unwrap_or("-")
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_find_definitions_across_languages() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let repo = LocalRepository::new(repo_path);
+
+    let options = SymbolSearchOptions {
+        name: "lock".to_string(),
+        ..Default::default()
+    };
+    let result = repo
+        .find_symbols(options.clone())
+        .await
+        .expect("Symbol search failed");
+
+    // Calls, comments and the README aren't definitions; names match case-insensitively
+    assert_eq!(
+        result_symbols(&result),
+        vec![
+            "mutex.go:8 Method Locker",
+            "mutex.go:11 Method Mutex",
+            "mutex.py:6 Method Mutex",
+            "mutex.py:10 Function -",
+            "mutex.ts:2 Method Mutex",
+            "mutex.ts:6 Method Locker",
+            "mutex.ts:9 Function -",
+            "src/lib.rs:7 Method Mutex",
+            "src/lib.rs:15 Trait -",
+            "src/lib.rs:16 Method Lock",
+        ]
+    );
+    assert_eq!(result.total_symbols, 10);
+    assert_eq!(result.scanned_files, 4);
+
+    // Signatures stop at the body and collapse whitespace
+    let rust_lock = &result.symbols[7];
+    assert_eq!(rust_lock.language, SourceLanguage::Rust);
+    assert_eq!(rust_lock.signature, "pub fn lock( &self, ) -> Guard<'_, T>");
+    assert_eq!((rust_lock.start_line, rust_lock.end_line), (7, 12));
+    assert_eq!(result.symbols[1].signature, "func (m *Mutex) Lock()");
+    assert_eq!(result.symbols[2].signature, "def lock(self)");
+
+    // The same definitions from the object database
+    let at_ref = repo
+        .find_symbols(SymbolSearchOptions {
+            ref_name: Some("main".to_string()),
+            ..options.clone()
+        })
+        .await
+        .expect("Symbol search at ref failed");
+    assert_eq!(result_symbols(&at_ref), result_symbols(&result));
+
+    // Case-sensitive matching and a kind filter
+    let result = repo
+        .find_symbols(SymbolSearchOptions {
+            case_sensitive: true,
+            kinds: Some(vec![SymbolKind::Function]),
+            ..options
+        })
+        .await
+        .expect("Symbol search failed");
+    assert_eq!(
+        result_symbols(&result),
+        vec!["mutex.py:10 Function -", "mutex.ts:9 Function -"]
+    );
+}
+
+#[tokio::test]
+async fn test_find_types_with_regex_and_pagination() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let repo = LocalRepository::new(repo_path);
+
+    let options = SymbolSearchOptions {
+        name: "^(Mutex|State|Lock|sync|helper)$".to_string(),
+        regex: true,
+        case_sensitive: true,
+        include_globs: Some(vec!["**/*.rs".to_string()]),
+        ..Default::default()
+    };
+    let result = repo
+        .find_symbols(options.clone())
+        .await
+        .expect("Symbol search failed");
+    // Items nested in a module carry it as container; functions inside bodies are skipped
+    assert_eq!(
+        result_symbols(&result),
+        vec![
+            "src/lib.rs:2 Struct -",
+            "src/lib.rs:15 Trait -",
+            "src/lib.rs:19 Module -",
+            "src/lib.rs:20 Enum sync",
+        ]
+    );
+    assert_eq!(result.symbols[0].signature, "pub struct Mutex<T>");
+    assert_eq!(result.scanned_files, 1);
+
+    let result = repo
+        .find_symbols(SymbolSearchOptions {
+            skip: Some(1),
+            take: Some(2),
+            ..options
+        })
+        .await
+        .expect("Symbol search failed");
+    assert_eq!(
+        result_symbols(&result),
+        vec!["src/lib.rs:15 Trait -", "src/lib.rs:19 Module -"]
+    );
+    assert_eq!(result.total_symbols, 4);
+}
+
+#[tokio::test]
+async fn test_find_symbol_errors_and_service() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let repo = LocalRepository::new(repo_path.clone());
+
+    let err = repo
+        .find_symbols(SymbolSearchOptions::default())
+        .await
+        .unwrap_err();
+    assert!(
+        err.contains("must not be empty"),
+        "unexpected error: {}",
+        err
+    );
+
+    let err = repo
+        .find_symbols(SymbolSearchOptions {
+            name: "(".to_string(),
+            regex: true,
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert!(
+        err.contains("Invalid symbol name regex"),
+        "unexpected error: {}",
+        err
+    );
+
+    // Through the service
+    let cache_dir = tempdir().expect("Failed to create temporary directory");
+    let manager = RepositoryManager::new(None, Some(cache_dir.path().to_path_buf()))
+        .expect("Failed to create RepositoryManager");
+    let (result, _local_repo) = services::find_symbol(
+        &manager,
+        FindSymbolServiceParams {
+            repository_location_str: repo_path.display().to_string(),
+            name: "Mutex".to_string(),
+            regex: None,
+            case_sensitive: None,
+            kinds: Some(vec![SymbolKind::Struct, SymbolKind::Class]),
+            ref_name: None,
+            include_globs: None,
+            exclude_dirs: Some(vec!["src".to_string()]),
+            skip: None,
+            take: None,
+        },
+    )
+    .await
+    .expect("Symbol search failed");
+    assert_eq!(
+        result_symbols(&result),
+        vec![
+            "mutex.go:3 Struct -",
+            "mutex.py:1 Class -",
+            "mutex.ts:1 Class -",
+        ]
+    );
+    assert_eq!(result.name, "Mutex");
+}
@@ -0,0 +1,345 @@
+//! Tests for the trigram index that narrows repeated code searches, and the pruning of cached indexes
+
+mod common;
+
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+use common::{TestRepository, git};
+use gitcodes_mcp::gitcodes::local_repository::{
+    CodeSearchParams, RankedSearchOptions, ReferenceSearchOptions,
+};
+use gitcodes_mcp::gitcodes::repository_manager::RepositoryManager;
+use gitcodes_mcp::gitcodes::{CodeSearchResult, LocalRepository};
+use gitcodes_mcp::services;
+
+/// Helper function to create a committed repository with a few source files
+fn create_test_repository() -> (PathBuf, tempfile::TempDir) {
+    let repo = TestRepository::new();
+
+    let files = [
+        (
+            "src/config.rs",
+            "pub fn parse_config(path: &str) -> Config {\n    Config::load(path)\n}\n",
+        ),
+        (
+            "src/args.rs",
+            "pub fn parse_args() -> Vec<String> {\n    std::env::args().collect()\n}\n",
+        ),
+        (
+            "src/main.rs",
+            "fn main() {\n    let args = parse_args();\n    println!(\"{:?}\", args);\n}\n",
+        ),
+        (
+            "docs/README.md",
+            "# Parsing\n\nPARSE_CONFIG reads the file.\n",
+        ),
+        ("data.bin", "binary\0parse_config\0"),
+    ];
+    repo.write_all(&files).commit("Initial commit");
+
+    repo.into_parts()
+}
+
+fn search_params(
+    repo_path: &Path,
+    pattern: &str,
+    ref_name: Option<&str>,
+    use_index: bool,
+) -> CodeSearchParams {
+    CodeSearchParams {
+        ref_name: ref_name.map(str::to_string),
+        case_sensitive: true,
+        file_extensions: None,
+        include_globs: None,
+        exclude_dirs: None,
+        use_index,
+        before_context: None,
+        after_context: None,
+        skip: None,
+        take: None,
+        match_content_omit_num: None,
+        ..common::code_search_params(repo_path, pattern)
+    }
+}
+
+/// The matches as `file:line`, sorted
+fn matched_lines(result: &CodeSearchResult) -> Vec<String> {
+    let mut lines: Vec<String> = result
+        .matches
+        .iter()
+        .map(|line| format!("{}:{}", line.file_path.display(), line.line_number))
+        .collect();
+    lines.sort();
+    lines
+}
+
+/// The cached trigram index files of a repository
+fn index_files(repo_path: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(repo_path.join(".git/gitcodes")) else {
+        return Vec::new();
+    };
+    let mut files: Vec<String> = entries
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| name.starts_with("trigrams-"))
+        .collect();
+    files.sort();
+    files
+}
+
+#[tokio::test]
+async fn test_indexed_search_returns_same_results() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path.clone());
+
+    let patterns = [
+        (r"fn\s+parse_(config|args)", true),
+        ("parse_config", false),
+        ("args", true),
+        (r"\w+", true),
+        ("Config::load", true),
+        ("no such text", true),
+    ];
+    // Context lines, pagination and omitted content must come out the same as well
+    let variants = [
+        (None, None, None),
+        (Some(1), Some(1), None),
+        (None, Some(1), Some(8)),
+    ];
+    for ref_name in [None, Some("main")] {
+        for (pattern, case_sensitive) in patterns {
+            for (context, skip, match_content_omit_num) in variants {
+                let search = |use_index| {
+                    let mut params = search_params(&repo_path, pattern, ref_name, use_index);
+                    params.case_sensitive = case_sensitive;
+                    params.before_context = context;
+                    params.after_context = context;
+                    params.skip = skip;
+                    params.take = skip.map(|_| 2);
+                    params.match_content_omit_num = match_content_omit_num;
+                    local_repo.search_code(params)
+                };
+                let unindexed = search(false).await.expect("Search failed");
+                let indexed = search(true).await.expect("Indexed search failed");
+                assert_eq!(
+                    serde_json::to_value(&indexed).unwrap(),
+                    serde_json::to_value(&unindexed).unwrap(),
+                    "pattern {:?} at {:?} with context {:?}, skip {:?}",
+                    pattern,
+                    ref_name,
+                    context,
+                    skip
+                );
+            }
+        }
+    }
+
+    // The case-insensitive search also finds the upper case spelling
+    let mut params = search_params(&repo_path, "parse_config", None, true);
+    params.case_sensitive = false;
+    let result = local_repo.search_code(params).await.expect("Search failed");
+    assert_eq!(
+        matched_lines(&result),
+        vec!["docs/README.md:3", "src/config.rs:1"]
+    );
+}
+
+#[tokio::test]
+async fn test_index_is_cached_and_covers_uncommitted_changes() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path.clone());
+    assert!(index_files(&repo_path).is_empty());
+
+    let result = local_repo
+        .search_code(search_params(&repo_path, "parse_args", None, true))
+        .await
+        .expect("Search failed");
+    assert_eq!(
+        matched_lines(&result),
+        vec!["src/args.rs:1", "src/main.rs:2"]
+    );
+    let tree_id = git(&repo_path, &["rev-parse", "HEAD^{tree}"]);
+    assert_eq!(
+        index_files(&repo_path),
+        vec![format!("trigrams-v1-{}.json", tree_id)]
+    );
+
+    // Modified and untracked files are searched even though the index predates them
+    std::fs::write(
+        repo_path.join("src/config.rs"),
+        "pub fn load() {\n    parse_args();\n}\n",
+    )
+    .unwrap();
+    std::fs::write(repo_path.join("new.rs"), "// parse_args later\n").unwrap();
+    let result = local_repo
+        .search_code(search_params(&repo_path, "parse_args", None, true))
+        .await
+        .expect("Search failed");
+    assert_eq!(
+        matched_lines(&result),
+        vec![
+            "new.rs:1",
+            "src/args.rs:1",
+            "src/config.rs:2",
+            "src/main.rs:2"
+        ]
+    );
+
+    // Searching a ref with the same tree reuses the index
+    git(&repo_path, &["branch", "feature"]);
+    let result = local_repo
+        .search_code(search_params(
+            &repo_path,
+            "parse_args",
+            Some("feature"),
+            true,
+        ))
+        .await
+        .expect("Search failed");
+    assert_eq!(
+        matched_lines(&result),
+        vec!["src/args.rs:1", "src/main.rs:2"]
+    );
+    assert_eq!(index_files(&repo_path).len(), 1);
+}
+
+#[tokio::test]
+async fn test_stale_indexes_are_pruned() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let cache_dir = tempdir().expect("Failed to create temporary directory");
+    let manager = RepositoryManager::new(None, Some(cache_dir.path().to_path_buf()))
+        .expect("Failed to create RepositoryManager");
+
+    let grep = |pattern: &str| services::GrepParams {
+        repository_location_str: repo_path.display().to_string(),
+        pattern: pattern.to_string(),
+        ref_name: Some("main".to_string()),
+        case_sensitive: true,
+        file_extensions: None,
+        include_globs: None,
+        exclude_dirs: None,
+        use_index: true,
+        before_context: None,
+        after_context: None,
+        skip: None,
+        take: None,
+        match_content_omit_num: None,
+        ..Default::default()
+    };
+
+    let (result, _local_repo) = services::perform_grep_in_repository(&manager, grep("Config"))
+        .await
+        .expect("Grep failed");
+    assert_eq!(
+        matched_lines(&result),
+        vec!["src/config.rs:1", "src/config.rs:2"]
+    );
+    let old_index = index_files(&repo_path);
+    assert_eq!(old_index.len(), 1);
+
+    // A new commit gets its own index, the old one stays while a ref points to its tree
+    std::fs::write(repo_path.join("src/config.rs"), "pub struct Config;\n").unwrap();
+    git(&repo_path, &["commit", "-am", "Replace config"]);
+    let (result, _local_repo) = services::perform_grep_in_repository(&manager, grep("Config"))
+        .await
+        .expect("Grep failed");
+    assert_eq!(matched_lines(&result), vec!["src/config.rs:1"]);
+    assert_eq!(index_files(&repo_path).len(), 2);
+
+    let local_repo = LocalRepository::new(repo_path.clone());
+    git(&repo_path, &["tag", "old", "HEAD~1"]);
+    assert_eq!(local_repo.prune_indexes(), Ok(0));
+
+    // The identifier and chunk indexes of the old commit go along with its trigram index
+    let old_commit = git(&repo_path, &["rev-parse", "old"]);
+    local_repo
+        .find_references(ReferenceSearchOptions {
+            name: "parse_config".to_string(),
+            ref_name: Some("old".to_string()),
+            ..Default::default()
+        })
+        .await
+        .expect("Reference search failed");
+    local_repo
+        .search_code_ranked(RankedSearchOptions {
+            query: "parse config".to_string(),
+            ref_name: Some("old".to_string()),
+            ..Default::default()
+        })
+        .await
+        .expect("Ranked search failed");
+    let cached_files = || {
+        std::fs::read_dir(repo_path.join(".git/gitcodes"))
+            .unwrap()
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        cached_files()
+            .iter()
+            .filter(|name| name.contains(&old_commit))
+            .count(),
+        2
+    );
+
+    git(&repo_path, &["tag", "-d", "old"]);
+    assert_eq!(local_repo.prune_indexes(), Ok(3));
+    let remaining = index_files(&repo_path);
+    assert_eq!(remaining.len(), 1);
+    assert!(!remaining.contains(&old_index[0]));
+    assert!(!cached_files().iter().any(|name| name.contains(&old_commit)));
+}
+
+#[tokio::test]
+async fn test_indexed_search_is_not_slower_than_unindexed() {
+    // Enough files that searching all of them dominates reading the index and the status
+    let repo = TestRepository::new();
+    let filler = "let value = compute(input, other_input);\n".repeat(200);
+    for i in 0..400 {
+        repo.write(&format!("src/module_{}.rs", i), &filler);
+    }
+    repo.write(
+        "src/needle.rs",
+        "pub const NEEDLE: &str = \"needle_value\";\n",
+    )
+    .commit("Initial commit");
+    // Files written in the same second as the index are re-read by every status, as git
+    // can't tell from their timestamps whether they changed; a later refresh settles that
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    repo.git(&["update-index", "--refresh"]);
+    let (repo_path, _temp_dir) = repo.into_parts();
+    let local_repo = LocalRepository::new(repo_path.clone());
+
+    for ref_name in [None, Some("main")] {
+        // The fastest of a few runs, so that a hiccup of the machine doesn't count
+        let fastest = |use_index| {
+            let local_repo = &local_repo;
+            let repo_path = &repo_path;
+            async move {
+                let mut fastest = std::time::Duration::MAX;
+                for _ in 0..3 {
+                    let params = search_params(repo_path, "needle_value", ref_name, use_index);
+                    let started = std::time::Instant::now();
+                    let result = local_repo.search_code(params).await.expect("Search failed");
+                    fastest = fastest.min(started.elapsed());
+                    assert_eq!(matched_lines(&result), vec!["src/needle.rs:1"]);
+                }
+                fastest
+            }
+        };
+
+        // Builds and caches the index before timing
+        fastest(true).await;
+        let unindexed = fastest(false).await;
+        let indexed = fastest(true).await;
+        assert!(
+            indexed <= unindexed,
+            "indexed search took {:?}, unindexed {:?} at {:?}",
+            indexed,
+            unindexed,
+            ref_name
+        );
+    }
+}
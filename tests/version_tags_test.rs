@@ -0,0 +1,270 @@
+//! Tests for semantic version aware tag ordering and the `latest` ref aliases
+
+mod common;
+
+use std::path::PathBuf;
+
+use common::{TestRepository, git};
+use gitcodes_mcp::gitcodes::LocalRepository;
+use gitcodes_mcp::gitcodes::ViewFileParams;
+use gitcodes_mcp::gitcodes::repository_manager::RepositoryManager;
+use gitcodes_mcp::gitcodes::repository_manager::providers::ReferenceInfo;
+use gitcodes_mcp::gitcodes::repository_manager::version_tags::{
+    LatestAlias, parse_tag_version, retain_stable_tags, sort_tags_by_version,
+};
+use lumin::view::FileContents;
+
+fn tag(name: &str) -> ReferenceInfo {
+    ReferenceInfo::new(
+        name.to_string(),
+        format!("refs/tags/{}", name),
+        "0000000000000000000000000000000000000000".to_string(),
+    )
+}
+
+fn names(tags: &[ReferenceInfo]) -> Vec<&str> {
+    tags.iter().map(|tag| tag.name.as_str()).collect()
+}
+
+/// Helper function to create a repository tagged `v1.9.0`, `v1.10.0`, `v2.0.0-rc.1` and
+/// `nightly`, where `VERSION` contains the version of each tagged commit
+fn create_test_repository() -> (PathBuf, tempfile::TempDir) {
+    let repo = TestRepository::new();
+
+    for (version, tag_name) in [
+        ("1.9.0", "v1.9.0"),
+        ("1.10.0", "v1.10.0"),
+        ("2.0.0-rc.1", "v2.0.0-rc.1"),
+    ] {
+        repo.write("VERSION", &format!("{}\n", version))
+            .commit(&format!("Release {}", version))
+            .git(&["tag", "-a", tag_name, "-m", tag_name]);
+    }
+    repo.tag("nightly");
+
+    repo.into_parts()
+}
+
+async fn version_file_at(local_repo: &LocalRepository, ref_name: &str) -> String {
+    let params = ViewFileParams {
+        file_path: PathBuf::from("VERSION"),
+        max_size: None,
+        line_from: None,
+        line_to: None,
+    };
+    let contents = local_repo
+        .view_file_contents_at_ref(ref_name, params)
+        .await
+        .unwrap_or_else(|e| panic!("Failed to read VERSION at {}: {}", ref_name, e));
+    match contents {
+        FileContents::Text { content, .. } => content.line_contents[0].line.clone(),
+        other => panic!("Expected text contents, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_tag_version_prefixes() {
+    let plain = parse_tag_version("1.2.3").unwrap();
+    assert_eq!(plain.prefix, "");
+    assert_eq!(plain.version.to_string(), "1.2.3");
+
+    let v_prefixed = parse_tag_version("v1.2.3").unwrap();
+    assert_eq!(v_prefixed.prefix, "v");
+    assert!(!v_prefixed.is_prerelease());
+
+    let crate_prefixed = parse_tag_version("gix-diff-v0.52.1").unwrap();
+    assert_eq!(crate_prefixed.prefix, "gix-diff-v");
+    assert_eq!(crate_prefixed.version.to_string(), "0.52.1");
+
+    let npm_style = parse_tag_version("@scope/pkg@2.0.0-beta.3").unwrap();
+    assert_eq!(npm_style.prefix, "@scope/pkg@");
+    assert!(npm_style.is_prerelease());
+
+    let short = parse_tag_version("release-4.1").unwrap();
+    assert_eq!(short.prefix, "release-");
+    assert_eq!(short.version.to_string(), "4.1.0");
+
+    assert!(parse_tag_version("nightly").is_none());
+    assert!(parse_tag_version("build-5").is_none());
+    assert!(parse_tag_version("v1").is_none());
+}
+
+#[test]
+fn test_sort_tags_by_version() {
+    let mut tags = vec![
+        tag("v1.9.0"),
+        tag("nightly"),
+        tag("v1.10.0"),
+        tag("v2.0.0-rc.1"),
+        tag("v2.0.0"),
+        tag("archive"),
+        tag("v0.1"),
+        tag("v2.0.0-alpha"),
+    ];
+    sort_tags_by_version(&mut tags);
+
+    assert_eq!(
+        names(&tags),
+        vec![
+            "v2.0.0",
+            "v2.0.0-rc.1",
+            "v2.0.0-alpha",
+            "v1.10.0",
+            "v1.9.0",
+            "v0.1",
+            "archive",
+            "nightly"
+        ]
+    );
+
+    retain_stable_tags(&mut tags);
+    assert_eq!(
+        names(&tags),
+        vec!["v2.0.0", "v1.10.0", "v1.9.0", "v0.1", "archive", "nightly"]
+    );
+}
+
+#[test]
+fn test_latest_alias_selection() {
+    assert_eq!(
+        LatestAlias::from_ref_name("latest"),
+        Some(LatestAlias::Latest)
+    );
+    assert_eq!(
+        LatestAlias::from_ref_name("Latest-Stable"),
+        Some(LatestAlias::LatestStable)
+    );
+    assert_eq!(LatestAlias::from_ref_name("main"), None);
+
+    let tags = ["v1.9.0", "v1.10.0", "v2.0.0-rc.1", "nightly"];
+    assert_eq!(LatestAlias::Latest.select(tags), Some("v2.0.0-rc.1"));
+    assert_eq!(LatestAlias::LatestStable.select(tags), Some("v1.10.0"));
+    assert_eq!(
+        LatestAlias::LatestStable.select(["nightly", "v3.0.0-beta"]),
+        None
+    );
+}
+
+#[test]
+fn test_latest_alias_stays_on_one_release_line() {
+    // The repository's own releases win over the tags of other packages
+    let tags = ["1.2.0", "v1.3.0", "tool-v4.0.0", "plugin@9.0.0"];
+    assert_eq!(LatestAlias::Latest.select(tags), Some("v1.3.0"));
+
+    // Without them, the prefix most tags carry is followed
+    let tags = [
+        "core-v0.3.0",
+        "core-v0.2.0",
+        "macros-v1.0.0",
+        "core-v0.4.0-rc.1",
+    ];
+    assert_eq!(LatestAlias::Latest.select(tags), Some("core-v0.4.0-rc.1"));
+    assert_eq!(LatestAlias::LatestStable.select(tags), Some("core-v0.3.0"));
+    assert_eq!(
+        LatestAlias::LatestStable.select(["a-v2.0.0-rc.1", "b-v1.0.0"]),
+        None
+    );
+}
+
+#[tokio::test]
+async fn test_list_repository_refs_sorts_tags() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let manager = RepositoryManager::new(None, None).expect("Failed to create repository manager");
+
+    let (refs, _local_repo) = manager
+        .list_repository_refs(&repo_path.to_string_lossy())
+        .await
+        .expect("Failed to list refs");
+
+    assert_eq!(
+        names(&refs.tags),
+        vec!["v2.0.0-rc.1", "v1.10.0", "v1.9.0", "nightly"]
+    );
+}
+
+#[tokio::test]
+async fn test_latest_alias_resolves_locally() {
+    let (repo_path, _temp_dir) = create_test_repository();
+    let local_repo = LocalRepository::new(repo_path.clone());
+
+    assert_eq!(version_file_at(&local_repo, "latest").await, "2.0.0-rc.1");
+    assert_eq!(
+        version_file_at(&local_repo, "latest-stable").await,
+        "1.10.0"
+    );
+
+    // A real ref with the alias name takes precedence
+    git(&repo_path, &["branch", "latest", "v1.9.0"]);
+    assert_eq!(version_file_at(&local_repo, "latest").await, "1.9.0");
+}
+
+#[tokio::test]
+async fn test_latest_alias_sees_tags_missing_from_shallow_clone() {
+    let (upstream_path, _upstream_dir) = create_test_repository();
+    let clone_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let clone_path = clone_dir.path().join("clone");
+    let upstream_url = format!("file://{}", upstream_path.display());
+    git(
+        clone_dir.path(),
+        &[
+            "clone",
+            "--quiet",
+            "--depth",
+            "1",
+            "--no-tags",
+            "--branch",
+            "v1.9.0",
+            &upstream_url,
+            clone_path.to_str().unwrap(),
+        ],
+    );
+
+    // A release published after the clone
+    common::write_file(&upstream_path, "VERSION", "2.0.0\n");
+    git(
+        &upstream_path,
+        &["commit", "--quiet", "-am", "Release 2.0.0"],
+    );
+    git(&upstream_path, &["tag", "v2.0.0"]);
+
+    let local_repo = LocalRepository::new(clone_path.clone());
+    local_repo
+        .ensure_ref_available("latest-stable")
+        .await
+        .expect("Failed to fetch the latest stable tag");
+    assert_eq!(version_file_at(&local_repo, "latest-stable").await, "2.0.0");
+    assert_eq!(version_file_at(&local_repo, "latest").await, "2.0.0");
+    assert!(clone_path.join(".git").join("shallow").exists());
+}
+
+#[tokio::test]
+async fn test_latest_alias_prefers_a_remote_branch_named_latest() {
+    let (upstream_path, _upstream_dir) = create_test_repository();
+    let clone_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+    let clone_path = clone_dir.path().join("clone");
+    let upstream_url = format!("file://{}", upstream_path.display());
+    git(
+        clone_dir.path(),
+        &[
+            "clone",
+            "--quiet",
+            "--depth",
+            "1",
+            "--no-tags",
+            "--branch",
+            "v1.9.0",
+            &upstream_url,
+            clone_path.to_str().unwrap(),
+        ],
+    );
+
+    // Published after the clone, so only the remote knows it is a real branch
+    git(&upstream_path, &["branch", "latest", "v1.9.0"]);
+
+    let local_repo = LocalRepository::new(clone_path);
+    local_repo
+        .ensure_ref_available("latest")
+        .await
+        .expect("Failed to fetch the latest branch");
+    assert_eq!(version_file_at(&local_repo, "latest").await, "1.9.0");
+}